@@ -41,6 +41,7 @@ use mz_controller::clusters::{
     ReplicaConfig, ReplicaId, ReplicaLocation, ReplicaLogging, UnmanagedReplicaLocation,
 };
 use mz_expr::{MirScalarExpr, OptimizedMirRelationExpr};
+use mz_orchestrator::Toleration;
 use mz_ore::cast::CastFrom;
 use mz_ore::collections::CollectionExt;
 use mz_ore::metrics::MetricsRegistry;
@@ -4608,6 +4609,9 @@ impl Catalog {
                 size,
                 availability_zone,
                 az_user_specified,
+                preemptible,
+                node_selector,
+                tolerations,
             } => {
                 let cluster_replica_sizes = &self.state.cluster_replica_sizes;
 
@@ -4645,6 +4649,9 @@ impl Catalog {
                     availability_zone,
                     size,
                     az_user_specified,
+                    preemptible,
+                    node_selector,
+                    tolerations,
                 })
             }
         };
@@ -6708,6 +6715,7 @@ impl Catalog {
         ComputeParameters {
             max_result_size: Some(config.max_result_size()),
             dataflow_max_inflight_bytes: Some(config.dataflow_max_inflight_bytes()),
+            max_window_partition_size: Some(config.max_window_partition_size()),
             persist: self.persist_config(),
         }
     }
@@ -7009,6 +7017,15 @@ pub enum SerializedReplicaLocation {
         /// `true` if the AZ was specified by the user and must be respected;
         /// `false` if it was picked arbitrarily by Materialize.
         az_user_specified: bool,
+        /// Whether the replica may be scheduled on preemptible capacity.
+        #[serde(default)]
+        preemptible: bool,
+        /// Labels that the node running the replica must carry.
+        #[serde(default)]
+        node_selector: BTreeMap<String, String>,
+        /// Taints of the nodes that the replica tolerates.
+        #[serde(default)]
+        tolerations: Vec<Toleration>,
     },
 }
 
@@ -7033,10 +7050,16 @@ impl From<ReplicaLocation> for SerializedReplicaLocation {
                 size,
                 availability_zone,
                 az_user_specified,
+                preemptible,
+                node_selector,
+                tolerations,
             }) => SerializedReplicaLocation::Managed {
                 size,
                 availability_zone,
                 az_user_specified,
+                preemptible,
+                node_selector,
+                tolerations,
             },
         }
     }
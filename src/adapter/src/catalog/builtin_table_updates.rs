@@ -214,6 +214,9 @@ impl CatalogState {
                 availability_zone,
                 az_user_specified: _,
                 allocation: _,
+                preemptible: _,
+                node_selector: _,
+                tolerations: _,
             }) => (Some(&**size), Some(availability_zone.as_str())),
             ReplicaLocation::Unmanaged(_) => (None, None),
         };
@@ -853,6 +853,9 @@ fn default_cluster_replica_config(bootstrap_args: &BootstrapArgs) -> SerializedR
             size: bootstrap_args.default_cluster_replica_size.clone(),
             availability_zone: bootstrap_args.default_availability_zone.clone(),
             az_user_specified: false,
+            preemptible: false,
+            node_selector: BTreeMap::new(),
+            tolerations: vec![],
         },
         logging: default_logging_config(),
         idle_arrangement_merge_effort: None,
@@ -865,6 +868,9 @@ fn builtin_cluster_replica_config(bootstrap_args: &BootstrapArgs) -> SerializedR
             size: bootstrap_args.builtin_cluster_replica_size.clone(),
             availability_zone: bootstrap_args.default_availability_zone.clone(),
             az_user_specified: false,
+            preemptible: false,
+            node_selector: BTreeMap::new(),
+            tolerations: vec![],
         },
         logging: default_logging_config(),
         idle_arrangement_merge_effort: None,
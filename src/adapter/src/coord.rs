@@ -125,6 +125,7 @@ use crate::coord::appends::{Deferred, PendingWriteTxn};
 use crate::coord::id_bundle::CollectionIdBundle;
 use crate::coord::peek::PendingPeek;
 use crate::coord::read_policy::ReadCapability;
+use crate::coord::result_cache::{QueryResultCache, QueryResultCacheKey};
 use crate::coord::timeline::{TimelineContext, TimelineState, WriteTimestamp};
 use crate::coord::timestamp_selection::TimestampContext;
 use crate::error::AdapterError;
@@ -147,6 +148,7 @@ mod indexes;
 mod introspection;
 mod message_handler;
 mod read_policy;
+mod result_cache;
 mod sequencer;
 mod sql;
 
@@ -205,6 +207,26 @@ pub enum Message<T = mz_repr::Timestamp> {
         transient_revision: u64,
         real_time_recency_ts: Timestamp,
     },
+    /// Continues a peek that calls `mz_shard_metadata` once the metadata of the
+    /// shards it names has been fetched, keyed by the arguments of the calls.
+    ShardMetadataReady {
+        conn_id: ConnectionId,
+        transient_revision: u64,
+        metadata: Result<BTreeMap<String, MirRelationExpr>, AdapterError>,
+    },
+    /// Reports the outcome of re-provisioning a preempted replica.
+    ReplicaReprovisioned {
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+        result: Result<(), anyhow::Error>,
+    },
+    /// Caches the result of a query once it has been computed.
+    QueryResultReady {
+        key: QueryResultCacheKey,
+        rows: Vec<Row>,
+    },
+    /// Evicts the expired results from the query result cache.
+    QueryResultCacheEvict,
 }
 
 #[derive(Derivative)]
@@ -513,9 +535,18 @@ pub struct Coordinator {
     /// A map from client connection ids to a pending real time recency timestamps.
     pending_real_time_recency_timestamp: BTreeMap<ConnectionId, RealTimeRecencyContext>,
 
+    /// A map from client connection ids to the peek that waits for the
+    /// metadata of the shards it inspects with `mz_shard_metadata`.
+    pending_shard_metadata:
+        BTreeMap<ConnectionId, (ClientTransmitter<ExecuteResponse>, Session, PeekStageFinish)>,
+
     /// A map from active subscribes to the subscribe description.
     active_subscribes: BTreeMap<GlobalId, ActiveSubscribe>,
 
+    /// Results of queries at explicit `AS OF` timestamps, served to identical
+    /// queries within the `query_result_cache_ttl`.
+    query_result_cache: QueryResultCache,
+
     /// Serializes accesses to write critical sections.
     write_lock: Arc<tokio::sync::Mutex<()>>,
     /// Holds plans deferred due to write lock.
@@ -1471,7 +1502,9 @@ pub async fn serve(
                 pending_peeks: BTreeMap::new(),
                 client_pending_peeks: BTreeMap::new(),
                 pending_real_time_recency_timestamp: BTreeMap::new(),
+                pending_shard_metadata: BTreeMap::new(),
                 active_subscribes: BTreeMap::new(),
+                query_result_cache: QueryResultCache::default(),
                 write_lock: Arc::new(tokio::sync::Mutex::new(())),
                 write_lock_wait_group: VecDeque::new(),
                 pending_writes: Vec::new(),
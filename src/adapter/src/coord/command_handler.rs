@@ -601,6 +601,11 @@ impl Coordinator {
                 tx.send(Ok(ExecuteResponse::Canceled), session);
             }
 
+            // Cancel peeks waiting on shard metadata. There is at most one per session.
+            if let Some((tx, session, _)) = self.pending_shard_metadata.remove(&conn_id) {
+                tx.send(Ok(ExecuteResponse::Canceled), session);
+            }
+
             // Inform the target session (if it asks) about the cancellation.
             let _ = conn_meta.cancel_tx.send(Canceled::Canceled);
 
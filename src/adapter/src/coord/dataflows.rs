@@ -31,7 +31,7 @@ use mz_compute_client::types::sinks::{
 use mz_expr::visit::Visit;
 use mz_expr::{
     CollectionPlan, Id, MapFilterProject, MirRelationExpr, MirScalarExpr, OptimizedMirRelationExpr,
-    UnmaterializableFunc, RECURSION_LIMIT,
+    TableFunc, UnmaterializableFunc, RECURSION_LIMIT,
 };
 use mz_ore::cast::ReinterpretCast;
 use mz_ore::stack::{maybe_grow, CheckedRecursion, RecursionGuard, RecursionLimitError};
@@ -610,11 +610,28 @@ impl<'a, T> CheckedRecursion for DataflowBuilder<'a, T> {
 /// Prepares a relation expression for dataflow execution by preparing all
 /// contained scalar expressions (see `prep_scalar_expr`) in the specified
 /// style.
+///
+/// Calls to `mz_shard_metadata` cannot be evaluated by a dataflow. Peeks
+/// resolve them before getting here, so any that remain are rejected.
 pub fn prep_relation_expr(
     catalog: &CatalogState,
     expr: &mut OptimizedMirRelationExpr,
     style: ExprPrepStyle,
 ) -> Result<(), AdapterError> {
+    let mut calls_shard_metadata = false;
+    expr.0.visit_pre(|e| {
+        if let MirRelationExpr::FlatMap {
+            func: TableFunc::ShardMetadata { .. },
+            ..
+        } = e
+        {
+            calls_shard_metadata = true;
+        }
+    });
+    if calls_shard_metadata {
+        coord_bail!("mz_shard_metadata can only be called directly in a SELECT");
+    }
+
     match style {
         ExprPrepStyle::Index => {
             expr.0.try_visit_mut_post(&mut |e| {
@@ -17,16 +17,21 @@ use anyhow::anyhow;
 use chrono::DurationRound;
 use mz_persist_client::usage::ShardsUsage;
 use rand::{rngs, Rng, SeedableRng};
-use tracing::{event, warn, Level};
+use tracing::{event, info, warn, Level};
 
-use mz_controller::clusters::ClusterEvent;
+use mz_controller::clusters::{
+    ClusterEvent, ClusterId, ClusterStatus, DrainOutcome, ReplicaId, ReplicaLocation,
+};
 use mz_controller::ControllerResponse;
+use mz_expr::MirRelationExpr;
 use mz_ore::now::EpochMillis;
 use mz_ore::task;
 use mz_sql::ast::Statement;
+use mz_sql::names::ObjectId;
 use mz_sql::plan::{CreateSourcePlans, Plan};
 use mz_storage_client::controller::CollectionMetadata;
 
+use crate::catalog::SYSTEM_CONN_ID;
 use crate::client::ConnectionId;
 use crate::command::{Command, ExecuteResponse};
 use crate::coord::appends::Deferred;
@@ -38,6 +43,7 @@ use crate::coord::{
 use crate::util::ResultExt;
 use crate::{catalog, AdapterError, AdapterNotice};
 
+use super::peek::inline_shard_metadata;
 use super::{PeekStage, PeekStageFinish};
 
 impl Coordinator {
@@ -99,6 +105,27 @@ impl Coordinator {
                 )
                 .await;
             }
+            Message::ShardMetadataReady {
+                conn_id,
+                transient_revision,
+                metadata,
+            } => {
+                self.message_shard_metadata_ready(conn_id, transient_revision, metadata)
+                    .await;
+            }
+            Message::ReplicaReprovisioned {
+                cluster_id,
+                replica_id,
+                result,
+            } => {
+                self.message_replica_reprovisioned(cluster_id, replica_id, result);
+            }
+            Message::QueryResultReady { key, rows } => {
+                self.message_query_result_ready(key, rows);
+            }
+            Message::QueryResultCacheEvict => {
+                self.message_query_result_cache_evict();
+            }
         }
     }
 
@@ -364,6 +391,40 @@ impl Coordinator {
 
                 self.buffer_builtin_table_updates(builtin_updates);
             }
+            ControllerResponse::DrainFinished(outcome) => {
+                self.message_drain_finished(outcome).await
+            }
+        }
+    }
+
+    /// Drops the replica, or the cluster, of a finished drain from the
+    /// catalog, which in turn drops it from the controller.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn message_drain_finished(&mut self, outcome: DrainOutcome) {
+        let DrainOutcome {
+            cluster_id,
+            replica_id,
+            timed_out: _,
+        } = outcome;
+        // The drained object may have been dropped while it was draining.
+        let Some(cluster) = self.catalog().try_get_cluster(cluster_id) else {
+            return;
+        };
+        let object_id = match replica_id {
+            Some(replica_id) if cluster.replicas_by_id.contains_key(&replica_id) => {
+                ObjectId::ClusterReplica((cluster_id, replica_id))
+            }
+            Some(_) => return,
+            None => ObjectId::Cluster(cluster_id),
+        };
+        let ops = self
+            .catalog()
+            .object_dependents(&vec![object_id], SYSTEM_CONN_ID)
+            .into_iter()
+            .map(catalog::Op::DropObject)
+            .collect();
+        if let Err(e) = self.catalog_transact(None, ops).await {
+            warn!("failed to drop drained cluster {cluster_id} (replica {replica_id:?}): {e}");
         }
     }
 
@@ -554,6 +615,43 @@ impl Coordinator {
         // here.
     }
 
+    /// Re-provisions the specified replica off the coordinator's main loop,
+    /// as ensuring its service waits on the orchestrator. The outcome is
+    /// reported with a [`Message::ReplicaReprovisioned`].
+    fn reprovision_replica(&self, cluster_id: ClusterId, replica_id: ReplicaId) {
+        let reprovision = match self.controller.reprovision_replica(cluster_id, replica_id) {
+            Ok(reprovision) => reprovision,
+            Err(e) => {
+                self.message_replica_reprovisioned(cluster_id, replica_id, Err(e));
+                return;
+            }
+        };
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        task::spawn(|| "reprovision_replica", async move {
+            let result = reprovision.await;
+            // It is not an error for the result to be ready after `internal_cmd_rx` has been dropped.
+            let result = internal_cmd_tx.send(Message::ReplicaReprovisioned {
+                cluster_id,
+                replica_id,
+                result,
+            });
+            if let Err(e) = result {
+                warn!("internal_cmd_rx dropped before we could send: {:?}", e);
+            }
+        });
+    }
+
+    fn message_replica_reprovisioned(
+        &self,
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+        result: Result<(), anyhow::Error>,
+    ) {
+        if let Err(e) = result {
+            warn!("failed to re-provision preemptible replica {cluster_id}.{replica_id}: {e:#}");
+        }
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     async fn message_cluster_event(&mut self, event: ClusterEvent) {
         event!(Level::TRACE, event = format!("{:?}", event));
@@ -567,9 +665,32 @@ impl Coordinator {
             return;
         };
 
-        if event.status != replica.process_status[&event.process_id].status {
+        let old_process_status = replica.process_status[&event.process_id].status;
+        if event.status != old_process_status {
             let old_status = replica.status();
 
+            // Preemptible replicas are expected to go offline whenever their
+            // capacity is reclaimed. This is not an error: we re-provision the
+            // replica so that it is rescheduled, e.g. onto capacity that is
+            // still available. Processes that were OOM killed are not
+            // preempted, and are left to the orchestrator as usual.
+            let preempted = match &replica.config.location {
+                ReplicaLocation::Managed(location) => {
+                    location.preemptible
+                        && old_process_status == ClusterStatus::Ready
+                        && event.status == ClusterStatus::NotReady(None)
+                }
+                ReplicaLocation::Unmanaged(_) => false,
+            };
+            if preempted {
+                info!(
+                    "preemptible replica {}.{} process {} went offline, \
+                     presumably reclaimed; re-provisioning it",
+                    event.cluster_id, event.replica_id, event.process_id
+                );
+                self.reprovision_replica(event.cluster_id, event.replica_id);
+            }
+
             self.catalog_transact(
                 None,
                 vec![catalog::Op::UpdateClusterReplicaStatus {
@@ -729,4 +850,31 @@ impl Coordinator {
             }
         }
     }
+
+    #[tracing::instrument(level = "debug", skip(self, metadata))]
+    async fn message_shard_metadata_ready(
+        &mut self,
+        conn_id: ConnectionId,
+        transient_revision: u64,
+        metadata: Result<BTreeMap<String, MirRelationExpr>, AdapterError>,
+    ) {
+        let Some((tx, session, mut stage)) = self.pending_shard_metadata.remove(&conn_id) else {
+            // Query was cancelled while waiting.
+            return;
+        };
+
+        // Re-validate that the catalog hasn't changed.
+        if transient_revision != self.catalog().transient_revision() {
+            return tx.send(Err(AdapterError::Unstructured(anyhow!("Catalog contents have changed mid-query due to concurrent DDL, please re-try query"))), session);
+        }
+
+        match metadata {
+            Ok(metadata) => {
+                inline_shard_metadata(&mut stage.source, &metadata);
+                self.sequence_peek_stage(tx, session, PeekStage::Finish(stage))
+                    .await;
+            }
+            Err(e) => tx.send(Err(e), session),
+        }
+    }
 }
@@ -16,9 +16,10 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::num::NonZeroUsize;
 
+use anyhow::anyhow;
 use futures::TryFutureExt;
 use serde::{Deserialize, Serialize};
-use timely::progress::Timestamp;
+use timely::progress::{Antichain, Timestamp};
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
@@ -28,14 +29,17 @@ use mz_compute_client::types::dataflows::DataflowDescription;
 use mz_controller::clusters::ClusterId;
 use mz_expr::{
     EvalError, Id, MirRelationExpr, MirScalarExpr, OptimizedMirRelationExpr, RowSetFinishing,
+    TableFunc,
 };
 use mz_ore::cast::CastFrom;
 use mz_ore::str::StrExt;
 use mz_ore::str::{separated, Indent};
 use mz_ore::tracing::OpenTelemetryContext;
+use mz_persist_client::usage::StorageUsageClient;
+use mz_persist_client::ShardId;
 use mz_repr::explain::text::{fmt_text_constant_rows, DisplayText};
 use mz_repr::explain::{CompactScalarSeq, ExprHumanizer, Indices};
-use mz_repr::{Diff, GlobalId, RelationType, Row};
+use mz_repr::{Datum, Diff, GlobalId, RelationType, Row};
 
 use crate::client::ConnectionId;
 use crate::coord::timestamp_selection::TimestampContext;
@@ -558,6 +562,56 @@ impl crate::coord::Coordinator {
     }
 }
 
+/// Fetches the metadata of the `shards` that calls to `mz_shard_metadata`
+/// inspect, as the constant relations that replace the calls.
+pub(crate) async fn fetch_shard_metadata(
+    storage_usage_client: &StorageUsageClient,
+    shards: BTreeMap<String, ShardId>,
+) -> Result<BTreeMap<String, MirRelationExpr>, AdapterError> {
+    let mut metadata = BTreeMap::new();
+    for (id, shard_id) in shards {
+        let Some(shard) = storage_usage_client.shard_metadata(shard_id).await else {
+            return Err(anyhow!("shard {} does not exist", shard_id).into());
+        };
+        let frontier = |frontier: &Antichain<u64>| match frontier.as_option() {
+            Some(t) => Datum::MzTimestamp(mz_repr::Timestamp::from(*t)),
+            None => Datum::Null,
+        };
+        let row = Row::pack_slice(&[
+            frontier(&shard.since),
+            frontier(&shard.upper),
+            Datum::UInt64(u64::cast_from(shard.batch_count)),
+            Datum::UInt64(u64::cast_from(shard.encoded_size_bytes)),
+        ]);
+        let typ = TableFunc::ShardMetadata { id: id.clone() }.output_type();
+        let constant = MirRelationExpr::Constant {
+            rows: Ok(vec![(row, 1)]),
+            typ,
+        };
+        metadata.insert(id, constant);
+    }
+    Ok(metadata)
+}
+
+/// Replaces each call to `mz_shard_metadata` in `source` with the fetched
+/// `metadata` of the shard it inspects.
+pub(crate) fn inline_shard_metadata(
+    source: &mut MirRelationExpr,
+    metadata: &BTreeMap<String, MirRelationExpr>,
+) {
+    source.visit_pre_mut(|e| {
+        if let MirRelationExpr::FlatMap {
+            input,
+            func: TableFunc::ShardMetadata { id },
+            ..
+        } = e
+        {
+            let metadata = metadata[id].clone();
+            *e = input.take_dangerous().product(metadata);
+        }
+    });
+}
+
 fn consolidate_constant_updates(rows: Vec<(Row, Diff)>) -> Vec<(Row, Diff)> {
     // The consolidate API requires timestamps for all rows, so we assigned every row the
     // same timestamp. The actual value of that timestamp doesn't matter.
@@ -0,0 +1,448 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A cache for the results of queries at explicit `AS OF` timestamps.
+//!
+//! The result of a query that reads at a fixed timestamp and does not call any
+//! unmaterializable functions is fully determined by the query and that
+//! timestamp, so it can be served from a cache rather than recomputed. This
+//! helps dashboards that repeatedly issue the same query at the same timestamp.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use futures::FutureExt;
+use tracing::warn;
+
+use mz_compute_client::controller::ReplicaId;
+use mz_controller::clusters::ClusterId;
+use mz_expr::{MirRelationExpr, RowSetFinishing};
+use mz_ore::task;
+use mz_repr::{Row, Timestamp};
+use mz_sql::plan::QueryWhen;
+
+use crate::command::ExecuteResponse;
+use crate::coord::peek::PeekResponseUnary;
+use crate::coord::{Coordinator, Message};
+use crate::session::{Session, TransactionStatus};
+
+/// Identifies the result of a query.
+///
+/// The query is identified by its plan, rather than by its SQL text, as the
+/// plan captures the bound parameters as well as the objects that the names in
+/// the SQL text resolved to. The cluster and target replica are included
+/// because reads of introspection sources depend on them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct QueryResultCacheKey {
+    pub source: MirRelationExpr,
+    pub finishing: RowSetFinishing,
+    pub timestamp: Timestamp,
+    pub cluster_id: ClusterId,
+    pub target_replica: Option<ReplicaId>,
+}
+
+impl QueryResultCacheKey {
+    /// Reports whether the result of `source` is determined by the query and
+    /// the timestamp it reads at.
+    ///
+    /// Unmaterializable functions, like `now()` or `current_user()`, depend on
+    /// the session that issues the query, so queries calling them cannot be
+    /// cached.
+    pub fn is_cacheable(source: &MirRelationExpr) -> bool {
+        let mut cacheable = true;
+        source.visit_scalars(&mut |s| cacheable &= !s.contains_unmaterializable());
+        cacheable
+    }
+}
+
+/// A cache of query results.
+///
+/// Results are evicted once their time to live has passed, and the least
+/// recently used results are evicted to keep the total size of the cached
+/// results within a byte budget.
+///
+/// The `now` passed to the methods of the cache must not decrease between
+/// calls.
+#[derive(Debug, Default)]
+pub struct QueryResultCache {
+    /// The cached results.
+    entries: BTreeMap<QueryResultCacheKey, QueryResultCacheEntry>,
+    /// The keys of the cached results, by when they were inserted.
+    by_insertion: BTreeMap<u64, QueryResultCacheKey>,
+    /// The keys of the cached results, by when they were last used.
+    by_use: BTreeMap<u64, QueryResultCacheKey>,
+    /// The total size of the cached results, in bytes.
+    bytes: usize,
+    /// The logical time of the next insertion or use of a result.
+    next_tick: u64,
+}
+
+/// A result in the [`QueryResultCache`].
+#[derive(Debug)]
+struct QueryResultCacheEntry {
+    rows: Vec<Row>,
+    /// The size of `rows`, in bytes.
+    bytes: usize,
+    /// The time the result was inserted.
+    inserted_at: Instant,
+    /// The logical time the result was inserted.
+    inserted: u64,
+    /// The logical time the result was last used.
+    last_used: u64,
+}
+
+impl QueryResultCache {
+    /// Returns the cached result for `key`, if it was inserted less than `ttl`
+    /// before `now`.
+    pub fn get(
+        &mut self,
+        key: &QueryResultCacheKey,
+        now: Instant,
+        ttl: Duration,
+    ) -> Option<Vec<Row>> {
+        self.evict_expired(now, ttl);
+        let tick = self.tick();
+        let entry = self.entries.get_mut(key)?;
+        let key = self
+            .by_use
+            .remove(&entry.last_used)
+            .expect("cached result has a use");
+        self.by_use.insert(tick, key);
+        entry.last_used = tick;
+        Some(entry.rows.clone())
+    }
+
+    /// Caches `rows` as the result for `key`, inserted at `now`.
+    ///
+    /// The least recently used results are evicted as necessary to keep the
+    /// total size of the cached results within `max_bytes`. Results larger
+    /// than `max_bytes` are not cached.
+    pub fn insert(
+        &mut self,
+        key: QueryResultCacheKey,
+        rows: Vec<Row>,
+        now: Instant,
+        ttl: Duration,
+        max_bytes: usize,
+    ) {
+        self.evict_expired(now, ttl);
+        self.remove(&key);
+        let bytes = rows.iter().map(|row| row.byte_len()).sum();
+        if bytes > max_bytes {
+            return;
+        }
+        self.evict_least_recently_used(max_bytes - bytes);
+
+        let tick = self.tick();
+        self.by_insertion.insert(tick, key.clone());
+        self.by_use.insert(tick, key.clone());
+        self.bytes += bytes;
+        self.entries.insert(
+            key,
+            QueryResultCacheEntry {
+                rows,
+                bytes,
+                inserted_at: now,
+                inserted: tick,
+                last_used: tick,
+            },
+        );
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.by_insertion.clear();
+        self.by_use.clear();
+        self.bytes = 0;
+    }
+
+    /// Removes the results that were inserted `ttl` or more before `now`.
+    pub fn evict_expired(&mut self, now: Instant, ttl: Duration) {
+        // Results are inserted in the order of their insertion times, so the
+        // expired results are the oldest ones.
+        while let Some((_, key)) = self.by_insertion.first_key_value() {
+            let inserted_at = self.entries[key].inserted_at;
+            if now.saturating_duration_since(inserted_at) < ttl {
+                break;
+            }
+            let key = key.clone();
+            self.remove(&key);
+        }
+    }
+
+    /// Removes the least recently used results until the total size of the
+    /// cached results is at most `max_bytes`.
+    fn evict_least_recently_used(&mut self, max_bytes: usize) {
+        while self.bytes > max_bytes {
+            let (_, key) = self
+                .by_use
+                .first_key_value()
+                .expect("cache with a positive size has results");
+            let key = key.clone();
+            self.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, key: &QueryResultCacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.by_insertion.remove(&entry.inserted);
+            self.by_use.remove(&entry.last_used);
+            self.bytes -= entry.bytes;
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        let tick = self.next_tick;
+        self.next_tick += 1;
+        tick
+    }
+}
+
+impl Coordinator {
+    /// Returns the key under which to cache the result of a peek, or `None` if
+    /// its result must not be cached.
+    ///
+    /// Results are only cached if the query result cache is enabled, if the
+    /// peek reads at an explicit `AS OF` timestamp outside of an explicit
+    /// transaction, and if its result does not depend on the session.
+    pub(crate) fn query_result_cache_key(
+        &self,
+        session: &Session,
+        source: &MirRelationExpr,
+        when: &QueryWhen,
+        finishing: &RowSetFinishing,
+        cluster_id: ClusterId,
+        target_replica: Option<ReplicaId>,
+    ) -> Option<QueryResultCacheKey> {
+        if self
+            .catalog()
+            .system_config()
+            .query_result_cache_ttl()
+            .is_zero()
+        {
+            return None;
+        }
+        let QueryWhen::AtTimestamp(timestamp) = when else {
+            return None;
+        };
+        // Explicit transactions need to track the timestamps of their reads.
+        if matches!(session.transaction(), TransactionStatus::InTransaction(_)) {
+            return None;
+        }
+        if !QueryResultCacheKey::is_cacheable(source) {
+            return None;
+        }
+        // Errors evaluating the timestamp are reported when the peek executes.
+        let timestamp = self.evaluate_when(timestamp.clone(), session).ok()?;
+        Some(QueryResultCacheKey {
+            source: source.clone(),
+            finishing: finishing.clone(),
+            timestamp,
+            cluster_id,
+            target_replica,
+        })
+    }
+
+    /// Returns the cached result for `key`, if there is one.
+    pub(crate) fn cached_query_result(&mut self, key: &QueryResultCacheKey) -> Option<Vec<Row>> {
+        let ttl = self.catalog().system_config().query_result_cache_ttl();
+        let rows = self.query_result_cache.get(key, Instant::now(), ttl)?;
+        self.metrics.query_result_cache_hits.inc();
+        Some(rows)
+    }
+
+    /// Arranges for the rows sent by `resp` to be cached under `key`, once
+    /// they are available.
+    pub(crate) fn cache_query_result(
+        &self,
+        key: QueryResultCacheKey,
+        resp: ExecuteResponse,
+    ) -> ExecuteResponse {
+        match resp {
+            ExecuteResponse::SendingRows { future, span } => {
+                let internal_cmd_tx = self.internal_cmd_tx.clone();
+                let future = future.inspect(move |resp| {
+                    if let PeekResponseUnary::Rows(rows) = resp {
+                        let rows = rows.clone();
+                        // It is not an error for these results to be ready after
+                        // `internal_cmd_rx` has been dropped.
+                        let result = internal_cmd_tx.send(Message::QueryResultReady { key, rows });
+                        if let Err(e) = result {
+                            warn!("internal_cmd_rx dropped before we could send: {:?}", e);
+                        }
+                    }
+                });
+                ExecuteResponse::SendingRows {
+                    future: Box::pin(future),
+                    span,
+                }
+            }
+            resp => resp,
+        }
+    }
+
+    pub(crate) fn message_query_result_ready(&mut self, key: QueryResultCacheKey, rows: Vec<Row>) {
+        let system_config = self.catalog().system_config();
+        let ttl = system_config.query_result_cache_ttl();
+        let max_bytes = system_config.query_result_cache_max_bytes();
+        if ttl.is_zero() {
+            // The cache was disabled while the query was executing.
+            self.query_result_cache.clear();
+            return;
+        }
+        self.query_result_cache
+            .insert(key, rows, Instant::now(), ttl, max_bytes);
+
+        // Evict the result once it expires, rather than only when the cache is
+        // next used, so that the memory of expired results is released.
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        task::spawn(|| "query_result_cache_evict", async move {
+            tokio::time::sleep(ttl).await;
+            // It is not an error for this message to be ready after
+            // `internal_cmd_rx` has been dropped.
+            if let Err(e) = internal_cmd_tx.send(Message::QueryResultCacheEvict) {
+                warn!("internal_cmd_rx dropped before we could send: {:?}", e);
+            }
+        });
+    }
+
+    pub(crate) fn message_query_result_cache_evict(&mut self) {
+        let ttl = self.catalog().system_config().query_result_cache_ttl();
+        if ttl.is_zero() {
+            self.query_result_cache.clear();
+        } else {
+            self.query_result_cache.evict_expired(Instant::now(), ttl);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_expr::{MirScalarExpr, UnmaterializableFunc};
+    use mz_repr::{Datum, RelationType, ScalarType};
+
+    use super::*;
+
+    fn key(source: MirRelationExpr, timestamp: u64) -> QueryResultCacheKey {
+        QueryResultCacheKey {
+            source,
+            finishing: RowSetFinishing {
+                order_by: vec![],
+                limit: None,
+                offset: 0,
+                project: vec![0],
+            },
+            timestamp: timestamp.into(),
+            cluster_id: ClusterId::User(1),
+            target_replica: None,
+        }
+    }
+
+    #[test]
+    fn query_result_cache_ttl() {
+        let source = MirRelationExpr::constant(
+            vec![vec![Datum::Int64(1)]],
+            RelationType::new(vec![ScalarType::Int64.nullable(false)]),
+        );
+        let rows = vec![Row::pack_slice(&[Datum::Int64(1)])];
+        let ttl = Duration::from_secs(10);
+        let start = Instant::now();
+
+        let mut cache = QueryResultCache::default();
+        cache.insert(key(source.clone(), 5), rows.clone(), start, ttl, usize::MAX);
+        assert_eq!(cache.get(&key(source.clone(), 5), start, ttl), Some(rows));
+        // Results are cached per timestamp.
+        assert_eq!(cache.get(&key(source.clone(), 6), start, ttl), None);
+
+        // Results are evicted once their time to live has passed.
+        let later = start + ttl;
+        assert_eq!(cache.get(&key(source, 5), later, ttl), None);
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn query_result_cache_evict_expired() {
+        let source = MirRelationExpr::constant(
+            vec![vec![Datum::Int64(1)]],
+            RelationType::new(vec![ScalarType::Int64.nullable(false)]),
+        );
+        let rows = vec![Row::pack_slice(&[Datum::Int64(1)])];
+        let ttl = Duration::from_secs(10);
+        let start = Instant::now();
+
+        let mut cache = QueryResultCache::default();
+        cache.insert(key(source.clone(), 1), rows.clone(), start, ttl, usize::MAX);
+        let later = start + Duration::from_secs(5);
+        cache.insert(key(source.clone(), 2), rows.clone(), later, ttl, usize::MAX);
+
+        // Expired results are evicted without being looked up, and their
+        // size is no longer accounted for.
+        cache.evict_expired(start + ttl, ttl);
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache.entries.contains_key(&key(source.clone(), 2)));
+        assert_eq!(cache.bytes, rows[0].byte_len());
+
+        cache.evict_expired(later + ttl, ttl);
+        assert!(cache.entries.is_empty());
+        assert!(cache.by_insertion.is_empty());
+        assert!(cache.by_use.is_empty());
+        assert_eq!(cache.bytes, 0);
+    }
+
+    #[test]
+    fn query_result_cache_max_bytes() {
+        let source = MirRelationExpr::constant(
+            vec![vec![Datum::Int64(1)]],
+            RelationType::new(vec![ScalarType::Int64.nullable(false)]),
+        );
+        let rows = vec![Row::pack_slice(&[Datum::Int64(1)])];
+        let bytes = rows[0].byte_len();
+        let ttl = Duration::from_secs(10);
+        let now = Instant::now();
+
+        // The cache has room for two results.
+        let max_bytes = 2 * bytes;
+        let mut cache = QueryResultCache::default();
+        cache.insert(key(source.clone(), 1), rows.clone(), now, ttl, max_bytes);
+        cache.insert(key(source.clone(), 2), rows.clone(), now, ttl, max_bytes);
+        // Using the first result makes the second one the least recently used.
+        assert!(cache.get(&key(source.clone(), 1), now, ttl).is_some());
+        cache.insert(key(source.clone(), 3), rows.clone(), now, ttl, max_bytes);
+        assert_eq!(cache.get(&key(source.clone(), 2), now, ttl), None);
+        assert!(cache.get(&key(source.clone(), 1), now, ttl).is_some());
+        assert!(cache.get(&key(source.clone(), 3), now, ttl).is_some());
+        assert_eq!(cache.bytes, max_bytes);
+
+        // Results that exceed the budget on their own are not cached, and do
+        // not evict other results.
+        let large = vec![rows[0].clone(); 3];
+        cache.insert(key(source.clone(), 4), large, now, ttl, max_bytes);
+        assert_eq!(cache.get(&key(source.clone(), 4), now, ttl), None);
+        assert_eq!(cache.entries.len(), 2);
+
+        // Lowering the budget evicts results on the next insertion.
+        cache.insert(key(source.clone(), 5), rows, now, ttl, bytes);
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache.get(&key(source, 5), now, ttl).is_some());
+        assert_eq!(cache.bytes, bytes);
+    }
+
+    #[test]
+    fn query_result_cache_cacheable() {
+        let typ = RelationType::new(vec![ScalarType::Int64.nullable(false)]);
+        let source = MirRelationExpr::constant(vec![vec![Datum::Int64(1)]], typ);
+        assert!(QueryResultCacheKey::is_cacheable(&source));
+
+        let source = source.map(vec![MirScalarExpr::CallUnmaterializable(
+            UnmaterializableFunc::CurrentUser,
+        )]);
+        assert!(!QueryResultCacheKey::is_cacheable(&source));
+    }
+}
@@ -17,7 +17,6 @@ use anyhow::anyhow;
 use futures::future::BoxFuture;
 use maplit::btreeset;
 use mz_transform::Optimizer;
-use rand::seq::SliceRandom;
 use timely::progress::{Antichain, Timestamp as TimelyTimestamp};
 use tokio::sync::{mpsc, oneshot, OwnedMutexGuard};
 use tracing::{event, warn, Level};
@@ -33,12 +32,15 @@ use mz_controller::clusters::{
     DEFAULT_REPLICA_LOGGING_INTERVAL_MICROS,
 };
 use mz_expr::{
-    permutation_for_arrangement, CollectionPlan, MirRelationExpr, MirScalarExpr,
-    OptimizedMirRelationExpr, RowSetFinishing,
+    permutation_for_arrangement, CollectionPlan, JoinInputMapper, MirRelationExpr, MirScalarExpr,
+    OptimizedMirRelationExpr, RowSetFinishing, TableFunc,
 };
+use mz_ore::cast::CastFrom;
 use mz_ore::collections::CollectionExt;
 use mz_ore::result::ResultExt as OreResultExt;
+use mz_ore::str::StrExt;
 use mz_ore::task;
+use mz_persist_client::ShardId;
 use mz_repr::adt::mz_acl_item::{AclMode, MzAclItem};
 use mz_repr::explain::{ExplainFormat, Explainee};
 use mz_repr::role_id::RoleId;
@@ -82,7 +84,7 @@ use crate::command::{ExecuteResponse, Response};
 use crate::coord::appends::{Deferred, DeferredPlan, PendingWriteTxn};
 use crate::coord::dataflows::{prep_relation_expr, prep_scalar_expr, ExprPrepStyle};
 use crate::coord::id_bundle::CollectionIdBundle;
-use crate::coord::peek::{FastPathPlan, PlannedPeek};
+use crate::coord::peek::{fetch_shard_metadata, FastPathPlan, PlannedPeek};
 use crate::coord::read_policy::SINCE_GRANULARITY;
 use crate::coord::timeline::TimelineContext;
 use crate::coord::timestamp_selection::{TimestampContext, TimestampSource};
@@ -445,30 +447,6 @@ impl Coordinator {
             .map(|_| ExecuteResponse::CreatedRole)
     }
 
-    // Utility function used by both `sequence_create_cluster` and
-    // `sequence_create_cluster_replica`. Chooses the availability zone for a
-    // replica arbitrarily based on some state (currently: the number of
-    // replicas of the given cluster per AZ).
-    //
-    // I put this in the `Coordinator`'s impl block in case we ever want to
-    // change the logic and make it depend on some other state, but for now it's
-    // a pure function of the `n_replicas_per_az` state.
-    fn choose_az<'a>(n_replicas_per_az: &'a BTreeMap<String, usize>) -> String {
-        let min = *n_replicas_per_az
-            .values()
-            .min()
-            .expect("Must have at least one availability zone");
-        let argmins = n_replicas_per_az
-            .iter()
-            .filter_map(|(k, v)| (*v == min).then_some(k))
-            .collect::<Vec<_>>();
-        let arbitrary_argmin = argmins
-            .choose(&mut rand::thread_rng())
-            .expect("Must have at least one value corresponding to `min`");
-
-        (*arbitrary_argmin).clone()
-    }
-
     #[tracing::instrument(level = "debug", skip(self))]
     pub(super) async fn sequence_create_cluster(
         &mut self,
@@ -491,30 +469,29 @@ impl Coordinator {
             owner_id: *session.role_id(),
         }];
 
-        let azs = self.catalog().state().availability_zones();
-        let mut n_replicas_per_az = azs
-            .iter()
-            .map(|s| (s.clone(), 0))
-            .collect::<BTreeMap<_, _>>();
+        // The zones of the cluster's replicas, which the controller does not
+        // know about until the cluster is created.
+        let azs = self.catalog().state().availability_zones().to_vec();
+        let mut replica_azs = vec![];
         for (_name, r) in replicas.iter() {
             if let mz_sql::plan::ReplicaConfig::Managed {
                 availability_zone: Some(az),
                 ..
             } = r
             {
-                let ct: &mut usize = n_replicas_per_az.get_mut(az).ok_or_else(|| {
-                    AdapterError::InvalidClusterReplicaAz {
+                if !azs.contains(az) {
+                    return Err(AdapterError::InvalidClusterReplicaAz {
                         az: az.to_string(),
-                        expected: azs.to_vec(),
-                    }
-                })?;
-                *ct += 1
+                        expected: azs,
+                    });
+                }
+                replica_azs.push(az.clone());
             }
         }
 
         for (replica_name, replica_config) in replicas {
-            // If the AZ was not specified, choose one, round-robin, from the ones with
-            // the lowest number of configured replicas for this cluster.
+            // If the AZ was not specified, choose the one with the fewest
+            // replicas of this cluster, as when creating a single replica.
             let (compute, location) = match replica_config {
                 mz_sql::plan::ReplicaConfig::Unmanaged {
                     storagectl_addrs,
@@ -536,20 +513,25 @@ impl Coordinator {
                 mz_sql::plan::ReplicaConfig::Managed {
                     size,
                     availability_zone,
+                    preemptible,
                     compute,
                 } => {
                     let (availability_zone, user_specified) =
                         availability_zone.map(|az| (az, true)).unwrap_or_else(|| {
-                            let az = Self::choose_az(&n_replicas_per_az);
-                            *n_replicas_per_az
-                                .get_mut(&az)
-                                .expect("availability zone does not exist") += 1;
+                            let az = self
+                                .controller
+                                .choose_replica_az(id, &azs, &replica_azs)
+                                .expect("Must have at least one availability zone");
+                            replica_azs.push(az.clone());
                             (az, false)
                         });
                     let location = SerializedReplicaLocation::Managed {
                         size: size.clone(),
                         availability_zone,
                         az_user_specified: user_specified,
+                        preemptible,
+                        node_selector: BTreeMap::new(),
+                        tolerations: vec![],
                     };
                     (compute, location)
                 }
@@ -660,6 +642,7 @@ impl Coordinator {
             mz_sql::plan::ReplicaConfig::Managed {
                 size,
                 availability_zone,
+                preemptible,
                 compute,
             } => {
                 let (availability_zone, user_specified) = match availability_zone {
@@ -678,18 +661,11 @@ impl Coordinator {
                         // if none was specified. If there is a tie for "least popular", pick the first one.
                         // That is globally unbiased (for Materialize, not necessarily for this customer)
                         // because we shuffle the AZs on boot in `crate::serve`.
-                        let cluster = self.catalog().get_cluster(cluster_id);
                         let azs = self.catalog().state().availability_zones();
-                        let mut n_replicas_per_az = azs
-                            .iter()
-                            .map(|s| (s.clone(), 0))
-                            .collect::<BTreeMap<_, _>>();
-                        for r in cluster.replicas_by_id.values() {
-                            if let Some(az) = r.config.location.availability_zone() {
-                                *n_replicas_per_az.get_mut(az).expect("unknown AZ") += 1;
-                            }
-                        }
-                        let az = Self::choose_az(&n_replicas_per_az);
+                        let az = self
+                            .controller
+                            .choose_replica_az(cluster_id, azs, &[])
+                            .expect("Must have at least one availability zone");
                         (az, false)
                     }
                 };
@@ -697,6 +673,9 @@ impl Coordinator {
                     size,
                     availability_zone,
                     az_user_specified: user_specified,
+                    preemptible,
+                    node_selector: BTreeMap::new(),
+                    tolerations: vec![],
                 };
                 (compute, location)
             }
@@ -1861,6 +1840,15 @@ impl Coordinator {
                     }
                 }
                 PeekStage::Finish(stage) => {
+                    let shards = return_if_err!(
+                        self.shard_metadata_ids(&session, &stage.source),
+                        tx,
+                        session
+                    );
+                    if !shards.is_empty() {
+                        self.peek_stage_fetch_shard_metadata(tx, session, stage, shards);
+                        return;
+                    }
                     let res = self.peek_stage_finish(&mut session, stage).await;
                     tx.send(res, session);
                     return;
@@ -1881,6 +1869,15 @@ impl Coordinator {
             copy_to,
         } = plan;
 
+        let max_result_columns =
+            usize::cast_from(self.catalog().system_config().max_result_columns());
+        if finishing.project.len() > max_result_columns {
+            return Err(AdapterError::ResultColumns {
+                limit: max_result_columns,
+                requested: finishing.project.len(),
+            });
+        }
+
         // Two transient allocations. We could reclaim these if we don't use them, potentially.
         // TODO: reclaim transient identifiers in fast path cases.
         let view_id = self.allocate_transient_id()?;
@@ -2050,6 +2047,35 @@ impl Coordinator {
             real_time_recency_ts,
         }: PeekStageFinish,
     ) -> Result<ExecuteResponse, AdapterError> {
+        let cache_key = self.query_result_cache_key(
+            session,
+            &source,
+            &when,
+            &finishing,
+            cluster_id,
+            target_replica,
+        );
+        if let Some((key, rows)) = cache_key
+            .as_ref()
+            .and_then(|key| Some((key, self.cached_query_result(key)?)))
+        {
+            // Report the timestamp the cached result was read at, as the peek
+            // that computed it would have.
+            if session.vars().emit_timestamp_notice() {
+                session.add_notice(AdapterNotice::QueryTimestamp {
+                    timestamp: key.timestamp,
+                });
+            }
+            let resp = send_immediate_rows(rows);
+            return match copy_to {
+                None => Ok(resp),
+                Some(format) => Ok(ExecuteResponse::CopyTo {
+                    format,
+                    resp: Box::new(resp),
+                }),
+            };
+        }
+
         let mut peek_plan = self.plan_peek(
             source,
             session,
@@ -2085,9 +2111,12 @@ impl Coordinator {
         let timestamp = peek_plan.timestamp_context.timestamp().cloned();
 
         // Implement the peek, and capture the response.
-        let resp = self
+        let mut resp = self
             .implement_peek_plan(peek_plan, finishing, cluster_id, target_replica)
             .await?;
+        if let Some(key) = cache_key {
+            resp = self.cache_query_result(key, resp);
+        }
 
         if session.vars().emit_timestamp_notice() {
             if let Some(timestamp) = timestamp {
@@ -2104,6 +2133,85 @@ impl Coordinator {
         }
     }
 
+    /// Resolves the shards that the calls to `mz_shard_metadata` in `source`
+    /// inspect, keyed by the argument of the call. A call naming a storage
+    /// collection inspects the collection's data shard.
+    ///
+    /// Compute replicas have no access to the environment's persist state, so
+    /// the function can only be evaluated here, and only for superusers.
+    fn shard_metadata_ids(
+        &self,
+        session: &Session,
+        source: &MirRelationExpr,
+    ) -> Result<BTreeMap<String, ShardId>, AdapterError> {
+        let mut ids = BTreeSet::new();
+        source.visit_pre(|e| {
+            if let MirRelationExpr::FlatMap {
+                func: TableFunc::ShardMetadata { id },
+                ..
+            } = e
+            {
+                ids.insert(id.clone());
+            }
+        });
+        if ids.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        if !session.is_superuser() {
+            return Err(AdapterError::Unauthorized(
+                rbac::UnauthorizedError::Superuser {
+                    action: "inspect a shard".into(),
+                },
+            ));
+        }
+
+        let mut shards = BTreeMap::new();
+        for id in ids {
+            let shard_id = match (id.parse::<ShardId>(), id.parse::<GlobalId>()) {
+                (Ok(shard_id), _) => shard_id,
+                (Err(_), Ok(collection_id)) => self
+                    .controller
+                    .shard_id_for(collection_id)
+                    .ok_or_else(|| anyhow!("unknown storage collection {}", id.quoted()))?,
+                (Err(e), Err(_)) => {
+                    return Err(anyhow!("invalid shard id {}: {}", id.quoted(), e).into())
+                }
+            };
+            shards.insert(id, shard_id);
+        }
+        Ok(shards)
+    }
+
+    /// Fetches the metadata of the `shards` that the peek inspects from
+    /// persist, off the coordinator's main loop. The peek continues with a
+    /// [`Message::ShardMetadataReady`] once the metadata has been fetched.
+    fn peek_stage_fetch_shard_metadata(
+        &mut self,
+        tx: ClientTransmitter<ExecuteResponse>,
+        session: Session,
+        stage: PeekStageFinish,
+        shards: BTreeMap<String, ShardId>,
+    ) {
+        let transient_revision = self.catalog().transient_revision();
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        let storage_usage_client = self.storage_usage_client.clone();
+        let conn_id = session.conn_id();
+        self.pending_shard_metadata
+            .insert(conn_id, (tx, session, stage));
+        task::spawn(|| "peek_shard_metadata", async move {
+            let metadata = fetch_shard_metadata(&storage_usage_client, shards).await;
+            // It is not an error for these results to be ready after `internal_cmd_rx` has been dropped.
+            let result = internal_cmd_tx.send(Message::ShardMetadataReady {
+                conn_id,
+                transient_revision,
+                metadata,
+            });
+            if let Err(e) = result {
+                warn!("internal_cmd_rx dropped before we could send: {:?}", e);
+            }
+        });
+    }
+
     fn plan_peek(
         &self,
         source: MirRelationExpr,
@@ -2256,6 +2364,22 @@ impl Coordinator {
         // Optimize the dataflow across views, and any other ways that appeal.
         mz_transform::optimize_dataflow(&mut dataflow, &builder.index_oracle())?;
 
+        // Reject queries whose optimized plan contains more cross joins than
+        // allowed before we spend any resources executing them.
+        let max_query_cross_joins =
+            usize::cast_from(self.catalog().system_config().max_query_cross_joins());
+        let cross_joins: usize = dataflow
+            .objects_to_build
+            .iter()
+            .map(|BuildDesc { plan, .. }| count_cross_joins(plan))
+            .sum();
+        if cross_joins > max_query_cross_joins {
+            return Err(AdapterError::QueryCrossJoins {
+                limit: max_query_cross_joins,
+                requested: cross_joins,
+            });
+        }
+
         // At this point, `dataflow_plan` contains our best optimized dataflow.
         // We will check the plan to see if there is a fast path to escape full dataflow construction.
         let peek_plan = self.create_peek_plan(
@@ -3953,16 +4077,17 @@ impl Coordinator {
     ) -> Result<(), AdapterError> {
         let availability_zone = {
             let azs = self.catalog().state().availability_zones();
-            let n_replicas_per_az = azs
-                .iter()
-                .map(|az| (az.clone(), 0))
-                .collect::<BTreeMap<_, _>>();
-            Self::choose_az(&n_replicas_per_az)
+            self.controller
+                .choose_replica_az(cluster_id, azs, &[])
+                .expect("Must have at least one availability zone")
         };
         let location = SerializedReplicaLocation::Managed {
             size: size.to_string(),
             availability_zone,
             az_user_specified: false,
+            preemptible: false,
+            node_selector: BTreeMap::new(),
+            tolerations: vec![],
         };
         let location = self.catalog().concretize_replica_location(
             location,
@@ -4141,6 +4266,42 @@ where
     Ok(())
 }
 
+/// Counts the cross joins in `expr`. Each join contributes one less than the
+/// number of groups of its inputs that are not related by any equivalence.
+fn count_cross_joins(expr: &MirRelationExpr) -> usize {
+    let mut count = 0;
+    expr.visit_pre(|expr| {
+        if let MirRelationExpr::Join {
+            inputs,
+            equivalences,
+            ..
+        } = expr
+        {
+            let input_mapper = JoinInputMapper::new(inputs);
+            // Assign each input to a group, merging the groups of all inputs
+            // referenced by the same equivalence class.
+            let mut groups: Vec<usize> = (0..inputs.len()).collect();
+            for class in equivalences {
+                let merged: BTreeSet<_> = class
+                    .iter()
+                    .flat_map(|expr| input_mapper.lookup_inputs(expr))
+                    .map(|input| groups[input])
+                    .collect();
+                if let Some(target) = merged.first().copied() {
+                    for group in groups.iter_mut() {
+                        if merged.contains(group) {
+                            *group = target;
+                        }
+                    }
+                }
+            }
+            let distinct_groups: BTreeSet<_> = groups.into_iter().collect();
+            count += distinct_groups.len().saturating_sub(1);
+        }
+    });
+    count
+}
+
 /// Return a [`SourceSinkClusterConfig`] based on the possibly altered
 /// parameters.
 fn alter_storage_cluster_config(size: AlterOptionParameter) -> Option<SourceSinkClusterConfig> {
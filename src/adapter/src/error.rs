@@ -111,6 +111,11 @@ pub enum AdapterError {
     PreparedStatementExists(String),
     /// Wrapper around parsing error
     ParseError(mz_sql_parser::parser::ParserError),
+    /// The plan of a query contains more cross joins than allowed.
+    QueryCrossJoins {
+        limit: usize,
+        requested: usize,
+    },
     /// The transaction is in read-only mode.
     ReadOnlyTransaction,
     /// The transaction in in read-only mode and a read already occurred.
@@ -131,6 +136,11 @@ pub enum AdapterError {
         limit: String,
         current: String,
     },
+    /// Result of a query has more columns than allowed.
+    ResultColumns {
+        limit: usize,
+        requested: usize,
+    },
     /// Result size of a query is too large.
     ResultSize(String),
     /// The specified feature is not permitted in safe mode.
@@ -320,6 +330,16 @@ impl AdapterError {
             AdapterError::ResourceExhaustion { resource_type, .. } => Some(format!(
                 "Drop an existing {resource_type} or contact sales to request a limit increase."
             )),
+            AdapterError::QueryCrossJoins { .. } => Some(
+                "Add join conditions relating the joined relations, or ask an \
+                 administrator to raise the max_query_cross_joins system parameter."
+                    .into(),
+            ),
+            AdapterError::ResultColumns { .. } => Some(
+                "Select fewer columns, or ask an administrator to raise the \
+                 max_result_columns system parameter."
+                    .into(),
+            ),
             AdapterError::StatementTimeout => Some(
                 "Consider increasing the maximum allowed statement duration for this session by \
                  setting the statement_timeout session variable. For example, `SET \
@@ -411,6 +431,10 @@ impl fmt::Display for AdapterError {
             AdapterError::PreparedStatementExists(name) => {
                 write!(f, "prepared statement {} already exists", name.quoted())
             }
+            AdapterError::QueryCrossJoins { limit, requested } => write!(
+                f,
+                "query exceeds max cross join count of {limit} (requested: {requested})"
+            ),
             AdapterError::ReadOnlyTransaction => f.write_str("transaction in read-only mode"),
             AdapterError::ReadWriteUnavailable => {
                 f.write_str("transaction read-write mode must be set before any query")
@@ -444,6 +468,10 @@ impl fmt::Display for AdapterError {
                     "creating {resource_type} would violate {limit_name} limit (desired: {desired}, limit: {limit}, current: {current})"
                 )
             }
+            AdapterError::ResultColumns { limit, requested } => write!(
+                f,
+                "result exceeds max column count of {limit} (requested: {requested})"
+            ),
             AdapterError::ResultSize(e) => write!(f, "{e}"),
             AdapterError::SafeModeViolation(feature) => {
                 write!(f, "cannot create {} in safe mode", feature)
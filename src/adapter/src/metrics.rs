@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use prometheus::{HistogramVec, IntCounterVec, IntGaugeVec};
+use prometheus::{HistogramVec, IntCounter, IntCounterVec, IntGaugeVec};
 
 use mz_ore::metric;
 use mz_ore::metrics::MetricsRegistry;
@@ -24,6 +24,7 @@ pub struct Metrics {
     pub determine_timestamp: IntCounterVec,
     pub commands: IntCounterVec,
     pub storage_usage_collection_time_seconds: HistogramVec,
+    pub query_result_cache_hits: IntCounter,
 }
 
 impl Metrics {
@@ -63,7 +64,11 @@ impl Metrics {
                 name: "mz_storage_usage_collection_time_seconds",
                 help: "The number of seconds the coord spends collecting usage metrics from storage.",
                 buckets: histogram_seconds_buckets(0.000_128, 8.0)
-            ))
+            )),
+            query_result_cache_hits: registry.register(metric!(
+                name: "mz_query_result_cache_hits",
+                help: "The number of queries whose results were served from the query result cache.",
+            )),
         }
     }
 }
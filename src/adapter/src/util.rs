@@ -352,7 +352,8 @@ impl ShouldHalt for PeekError {
             PeekError::SinceViolation(_) => true,
             PeekError::InstanceMissing(_)
             | PeekError::CollectionMissing(_)
-            | PeekError::ReplicaMissing(_) => false,
+            | PeekError::ReplicaMissing(_)
+            | PeekError::ReplicaDraining(_) => false,
         }
     }
 }
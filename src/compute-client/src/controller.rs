@@ -31,7 +31,7 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
-use std::num::NonZeroI64;
+use std::num::{NonZeroI64, NonZeroUsize};
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
@@ -48,6 +48,7 @@ use mz_cluster_client::client::ClusterReplicaLocation;
 use mz_expr::RowSetFinishing;
 use mz_orchestrator::ServiceProcessMetrics;
 use mz_ore::metrics::MetricsRegistry;
+use mz_ore::retry::Retry;
 use mz_ore::tracing::OpenTelemetryContext;
 use mz_repr::{GlobalId, Row};
 use mz_storage_client::controller::{ReadPolicy, StorageController};
@@ -63,7 +64,7 @@ use crate::types::dataflows::DataflowDescription;
 use self::error::{
     CollectionLookupError, CollectionMissing, CollectionUpdateError, DataflowCreationError,
     InstanceExists, InstanceMissing, PeekError, ReplicaCreationError, ReplicaDropError,
-    SubscribeTargetError,
+    ReplicaLoggingError, SubscribeTargetError,
 };
 use self::instance::{ActiveInstance, Instance};
 use self::replica::ReplicaConfig;
@@ -121,6 +122,67 @@ pub struct ComputeReplicaConfig {
     pub idle_arrangement_merge_effort: Option<u32>,
 }
 
+impl ComputeReplicaConfig {
+    /// Translate into the [`ReplicaConfig`] used to spawn a replica at the given location.
+    fn to_replica_config(
+        &self,
+        location: ClusterReplicaLocation,
+        reconnect: ReplicaReconnectConfig,
+    ) -> ReplicaConfig {
+        let idle_arrangement_merge_effort = self
+            .idle_arrangement_merge_effort
+            .unwrap_or(DEFAULT_IDLE_ARRANGEMENT_MERGE_EFFORT);
+
+        ReplicaConfig {
+            location,
+            logging: self.logging.to_logging_config(),
+            idle_arrangement_merge_effort,
+            reconnect,
+        }
+    }
+}
+
+/// Configures how the controller (re)connects to compute replicas.
+///
+/// Connection attempts back off exponentially, starting at `initial_backoff`
+/// and never waiting longer than `clamp_backoff` between attempts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplicaReconnectConfig {
+    /// The time to wait after the first failed connection attempt.
+    pub initial_backoff: Duration,
+    /// The maximum time to wait between connection attempts.
+    pub clamp_backoff: Duration,
+    /// The maximum number of connection attempts before the replica task
+    /// gives up, or `None` to retry forever.
+    ///
+    /// A replica task that gives up is rehydrated by the controller like any
+    /// other failed replica.
+    pub max_attempts: Option<NonZeroUsize>,
+}
+
+impl Default for ReplicaReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(125),
+            clamp_backoff: Duration::from_secs(1),
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReplicaReconnectConfig {
+    /// Returns the [`Retry`] policy described by this configuration.
+    fn retry(&self) -> Retry {
+        let retry = Retry::default()
+            .initial_backoff(self.initial_backoff)
+            .clamp_backoff(self.clamp_backoff);
+        match self.max_attempts {
+            Some(max_attempts) => retry.max_tries(max_attempts.get()),
+            None => retry,
+        }
+    }
+}
+
 /// The default logging interval for [`ComputeReplicaLogging`], in number
 /// of microseconds.
 pub const DEFAULT_COMPUTE_REPLICA_LOGGING_INTERVAL_MICROS: u32 = 1_000_000;
@@ -141,6 +203,24 @@ impl ComputeReplicaLogging {
     pub fn enabled(&self) -> bool {
         self.interval.is_some()
     }
+
+    /// Translate into the [`LoggingConfig`] sent to replicas.
+    ///
+    /// The returned config has empty `index_logs`; those are filled in by the
+    /// instance when the config is installed on a replica.
+    fn to_logging_config(&self) -> LoggingConfig {
+        let (enable_logging, interval) = match self.interval {
+            Some(interval) => (true, interval),
+            None => (false, Duration::from_secs(1)),
+        };
+
+        LoggingConfig {
+            interval,
+            enable_logging,
+            log_logging: self.log_logging,
+            index_logs: Default::default(),
+        }
+    }
 }
 
 /// A controller for the compute layer.
@@ -164,6 +244,8 @@ pub struct ComputeController<T> {
     stats_update_pending: bool,
     /// The compute controller metrics
     metrics: ComputeControllerMetrics,
+    /// How replica clients connect to their replicas.
+    reconnect_config: ReplicaReconnectConfig,
 }
 
 impl<T> ComputeController<T> {
@@ -172,6 +254,7 @@ impl<T> ComputeController<T> {
         build_info: &'static BuildInfo,
         envd_epoch: NonZeroI64,
         metrics_registry: MetricsRegistry,
+        reconnect_config: ReplicaReconnectConfig,
     ) -> Self {
         let mut stats_update_ticker = tokio::time::interval(Duration::from_secs(1));
         stats_update_ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
@@ -188,6 +271,7 @@ impl<T> ComputeController<T> {
             stats_update_ticker,
             stats_update_pending: false,
             metrics: ComputeControllerMetrics::new(metrics_registry),
+            reconnect_config,
         }
     }
 
@@ -195,6 +279,16 @@ impl<T> ComputeController<T> {
         self.instances.contains_key(&id)
     }
 
+    /// Return the number of commands in the indicated instance's command history, or `None` if
+    /// the instance does not exist.
+    ///
+    /// This reflects the amount of replay work a newly added replica would incur.
+    pub fn command_history_len(&self, id: ComputeInstanceId) -> Option<usize> {
+        self.instances
+            .get(&id)
+            .map(|instance| instance.command_history_len())
+    }
+
     /// Return a reference to the indicated compute instance.
     fn instance(&self, id: ComputeInstanceId) -> Result<&Instance<T>, InstanceMissing> {
         self.instances.get(&id).ok_or(InstanceMissing(id))
@@ -372,6 +466,18 @@ where
             .set_subscribe_target_replica(subscribe_id, target_replica)?;
         Ok(())
     }
+
+    /// Ask the identified replica to drain, in preparation of dropping it.
+    ///
+    /// See [`ComputeInstanceRef::replica_is_drained`] for when the drain completes.
+    pub fn drain_replica(
+        &mut self,
+        instance_id: ComputeInstanceId,
+        replica_id: ReplicaId,
+    ) -> Result<(), ReplicaDropError> {
+        self.instance_mut(instance_id)?.drain_replica(replica_id)?;
+        Ok(())
+    }
 }
 
 /// A wrapper around a [`ComputeController`] with a live connection to a storage controller.
@@ -419,31 +525,57 @@ where
         location: ClusterReplicaLocation,
         config: ComputeReplicaConfig,
     ) -> Result<(), ReplicaCreationError> {
-        let (enable_logging, interval) = match config.logging.interval {
-            Some(interval) => (true, interval),
-            None => (false, Duration::from_secs(1)),
-        };
-
-        let idle_arrangement_merge_effort = config
-            .idle_arrangement_merge_effort
-            .unwrap_or(DEFAULT_IDLE_ARRANGEMENT_MERGE_EFFORT);
-
-        let replica_config = ReplicaConfig {
-            location,
-            logging: LoggingConfig {
-                interval,
-                enable_logging,
-                log_logging: config.logging.log_logging,
-                index_logs: Default::default(),
-            },
-            idle_arrangement_merge_effort,
-        };
-
+        let reconnect = self.compute.reconnect_config.clone();
+        let replica_config = config.to_replica_config(location, reconnect);
         self.instance(instance_id)?
             .add_replica(replica_id, replica_config)?;
         Ok(())
     }
 
+    /// Updates the logging configuration of a replica of an instance.
+    ///
+    /// Replicas only accept a logging configuration when they are first
+    /// initialized, so a replica whose configuration changes is rehydrated.
+    /// This replaces the replica's existing logging dataflows with ones
+    /// installed according to the new configuration. A replica whose
+    /// configuration is unchanged is left untouched.
+    ///
+    /// The log indexes of an instance are fixed when it is created, so
+    /// logging cannot be enabled on replicas of an instance that was created
+    /// without log indexes.
+    pub fn update_replica_logging(
+        &mut self,
+        instance_id: ComputeInstanceId,
+        replica_id: ReplicaId,
+        logging: ComputeReplicaLogging,
+    ) -> Result<(), ReplicaLoggingError> {
+        use instance::ReplicaLoggingError as Error;
+        match self
+            .instance(instance_id)?
+            .update_replica_logging(replica_id, logging.to_logging_config())
+        {
+            Ok(()) => Ok(()),
+            Err(Error::ReplicaMissing(id)) => Err(ReplicaLoggingError::ReplicaMissing(id)),
+            Err(Error::LogSourcesMissing) => {
+                Err(ReplicaLoggingError::LogSourcesMissing(instance_id))
+            }
+        }
+    }
+
+    /// Rebuilds the command history of an instance and forces all of its replicas to rehydrate.
+    ///
+    /// This is a recovery tool for situations in which the recorded command history may have
+    /// become inconsistent with the state held by the replicas. The history is rebuilt from the
+    /// controller's current collection frontiers and outstanding peeks, and every replica is
+    /// restarted to replay it.
+    pub fn reset_instance_command_history(
+        &mut self,
+        instance_id: ComputeInstanceId,
+    ) -> Result<(), InstanceMissing> {
+        self.instance(instance_id)?.reset_command_history();
+        Ok(())
+    }
+
     /// Removes a replica from an instance, including its service in the orchestrator.
     pub fn drop_replica(
         &mut self,
@@ -635,6 +767,25 @@ impl<T> ComputeInstanceRef<'_, T> {
     pub fn collection(&self, id: GlobalId) -> Result<&CollectionState<T>, CollectionMissing> {
         self.instance.collection(id)
     }
+
+    /// Return the IDs of the replicas of this compute instance.
+    pub fn replica_ids(&self) -> impl Iterator<Item = ReplicaId> + '_ {
+        self.instance.replica_ids()
+    }
+
+    /// Return the number of peeks that some replica of this compute instance has yet to
+    /// respond to.
+    pub fn peeks_in_flight(&self) -> usize {
+        self.instance.peeks_in_flight()
+    }
+
+    /// Return whether the identified replica of this compute instance has finished draining.
+    ///
+    /// Replicas that have not been asked to drain through [`ComputeController::drain_replica`]
+    /// are never drained.
+    pub fn replica_is_drained(&self, id: ReplicaId) -> bool {
+        self.instance.replica_is_drained(id)
+    }
 }
 
 /// State maintained about individual compute collections.
@@ -713,3 +864,45 @@ impl<T: Timestamp> CollectionState<T> {
         self.write_frontier.borrow()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replica_reconnect_config() {
+        let reconnect = ReplicaReconnectConfig {
+            initial_backoff: Duration::from_millis(1),
+            clamp_backoff: Duration::from_millis(3),
+            max_attempts: NonZeroUsize::new(4),
+        };
+
+        let mut backoffs = Vec::new();
+        let result: Result<(), ()> = reconnect.retry().retry(|state| {
+            backoffs.push(state.next_backoff);
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(
+            backoffs,
+            [
+                Some(Duration::from_millis(1)),
+                Some(Duration::from_millis(2)),
+                Some(Duration::from_millis(3)),
+                None,
+            ]
+        );
+
+        let config = ComputeReplicaConfig {
+            logging: Default::default(),
+            idle_arrangement_merge_effort: None,
+        };
+        let location = ClusterReplicaLocation {
+            ctl_addrs: vec![],
+            dataflow_addrs: vec![],
+            workers: 1,
+        };
+        let replica_config = config.to_replica_config(location, reconnect.clone());
+        assert_eq!(replica_config.reconnect, reconnect);
+    }
+}
@@ -110,6 +110,23 @@ impl From<instance::ReplicaMissing> for ReplicaDropError {
     }
 }
 
+/// Errors arising during updates of a replica's logging configuration.
+#[derive(Error, Debug)]
+pub enum ReplicaLoggingError {
+    #[error("instance does not exist: {0}")]
+    InstanceMissing(ComputeInstanceId),
+    #[error("replica does not exist: {0}")]
+    ReplicaMissing(ReplicaId),
+    #[error("instance {0} maintains no log indexes, so logging cannot be enabled")]
+    LogSourcesMissing(ComputeInstanceId),
+}
+
+impl From<InstanceMissing> for ReplicaLoggingError {
+    fn from(error: InstanceMissing) -> Self {
+        Self::InstanceMissing(error.0)
+    }
+}
+
 /// Errors arising during dataflow creation.
 #[derive(Error, Debug)]
 pub enum DataflowCreationError {
@@ -149,6 +166,8 @@ pub enum PeekError {
     CollectionMissing(GlobalId),
     #[error("replica does not exist: {0}")]
     ReplicaMissing(ReplicaId),
+    #[error("replica is draining: {0}")]
+    ReplicaDraining(ReplicaId),
     #[error("peek timestamp is not beyond the since of collection: {0}")]
     SinceViolation(GlobalId),
 }
@@ -165,6 +184,7 @@ impl From<instance::PeekError> for PeekError {
         match error {
             CollectionMissing(id) => Self::CollectionMissing(id),
             ReplicaMissing(id) => Self::ReplicaMissing(id),
+            ReplicaDraining(id) => Self::ReplicaDraining(id),
             SinceViolation(id) => Self::CollectionMissing(id),
         }
     }
@@ -27,7 +27,7 @@ use mz_ore::tracing::OpenTelemetryContext;
 use mz_repr::{GlobalId, Row};
 use mz_storage_client::controller::{ReadPolicy, StorageController};
 
-use crate::logging::LogVariant;
+use crate::logging::{LogVariant, LoggingConfig};
 use crate::metrics::InstanceMetrics;
 use crate::protocol::command::{ComputeCommand, ComputeParameters, Peek};
 use crate::protocol::history::ComputeCommandHistory;
@@ -49,6 +49,14 @@ pub(super) struct ReplicaExists(pub ReplicaId);
 #[error("replica does not exist: {0}")]
 pub(super) struct ReplicaMissing(pub ReplicaId);
 
+#[derive(Error, Debug)]
+pub(super) enum ReplicaLoggingError {
+    #[error("replica does not exist: {0}")]
+    ReplicaMissing(ReplicaId),
+    #[error("instance maintains no log indexes")]
+    LogSourcesMissing,
+}
+
 #[derive(Error, Debug)]
 pub(super) enum DataflowCreationError {
     #[error("collection does not exist: {0}")]
@@ -71,6 +79,8 @@ pub(super) enum PeekError {
     CollectionMissing(GlobalId),
     #[error("replica does not exist: {0}")]
     ReplicaMissing(ReplicaId),
+    #[error("replica is draining: {0}")]
+    ReplicaDraining(ReplicaId),
     #[error("peek timestamp is not beyond the since of collection: {0}")]
     SinceViolation(GlobalId),
 }
@@ -144,6 +154,13 @@ pub(super) struct Instance<T> {
     history: ComputeCommandHistory<T>,
     /// IDs of replicas that have failed and require rehydration.
     failed_replicas: BTreeSet<ReplicaId>,
+    /// IDs of replicas that have been sent a `DrainReplica` command.
+    ///
+    /// Entries are removed when the replica is removed.
+    draining_replicas: BTreeSet<ReplicaId>,
+    /// IDs of draining replicas that have acknowledged their drain with a `ReplicaDrained`
+    /// response.
+    drained_replicas: BTreeSet<ReplicaId>,
     /// Ready compute controller responses to be delivered.
     pub ready_responses: VecDeque<ComputeControllerResponse<T>>,
     /// A number that increases with each restart of `environmentd`.
@@ -191,6 +208,13 @@ impl<T> Instance<T> {
         || !self.ready_responses.is_empty()
     }
 
+    /// Returns the number of commands in the command history.
+    ///
+    /// This is the number of commands that are replayed to a newly added replica.
+    pub fn command_history_len(&self) -> usize {
+        self.history.len()
+    }
+
     /// Returns whether the identified replica exists.
     pub fn replica_exists(&self, id: ReplicaId) -> bool {
         self.replicas.contains_key(&id)
@@ -201,6 +225,78 @@ impl<T> Instance<T> {
         self.replicas.keys().copied()
     }
 
+    /// Returns the number of peeks that some replica has yet to respond to.
+    pub fn peeks_in_flight(&self) -> usize {
+        self.peeks.len()
+    }
+
+    /// Returns whether the identified replica has finished draining.
+    ///
+    /// A replica is drained once it was sent a `DrainReplica` command and either acknowledged it
+    /// with a `ReplicaDrained` response, or no peek awaits its response anymore. Subscribes
+    /// targeting the replica do not prevent it from being drained, as they only finish when
+    /// they are dropped. Removing the replica terminates them with an error.
+    ///
+    /// Replicas that have not been asked to drain are never drained.
+    pub fn replica_is_drained(&self, id: ReplicaId) -> bool {
+        if self.drained_replicas.contains(&id) {
+            return true;
+        }
+        self.draining_replicas.contains(&id) && self.peeks_awaiting(id).next().is_none()
+    }
+
+    /// Returns the ids of the replicas whose responses new peeks await.
+    ///
+    /// Draining replicas are excluded, so that new peeks do not prolong their drain.
+    fn peek_replicas(&self) -> BTreeSet<ReplicaId> {
+        self.replica_ids()
+            .filter(|id| !self.draining_replicas.contains(id))
+            .collect()
+    }
+
+    /// Records that the identified replica has acknowledged its drain.
+    fn handle_replica_drained(&mut self, replica_id: ReplicaId) {
+        if self.draining_replicas.contains(&replica_id) {
+            self.drained_replicas.insert(replica_id);
+        } else {
+            tracing::warn!("unexpected ReplicaDrained response from replica {replica_id}");
+        }
+    }
+
+    /// Return the UUIDs of peeks whose response is awaited from the specified replica.
+    fn peeks_awaiting(&self, replica_id: ReplicaId) -> impl Iterator<Item = Uuid> + '_ {
+        self.peeks.iter().filter_map(move |(uuid, peek)| {
+            let awaiting = peek.awaits_response_from(replica_id);
+            awaiting.then_some(*uuid)
+        })
+    }
+
+    /// Sets the logging configuration of the identified replica.
+    ///
+    /// Enabling logging is rejected if this instance maintains no log indexes, as those are fixed
+    /// when the instance is created. Returns whether the replica's configuration changed, in which
+    /// case the replica must be rehydrated to install it.
+    fn set_replica_logging(
+        &mut self,
+        id: ReplicaId,
+        mut logging: LoggingConfig,
+    ) -> Result<bool, ReplicaLoggingError> {
+        if logging.enable_logging && self.log_sources.is_empty() {
+            return Err(ReplicaLoggingError::LogSourcesMissing);
+        }
+        logging.index_logs = self.log_sources.clone();
+
+        let replica = self
+            .replicas
+            .get_mut(&id)
+            .ok_or(ReplicaLoggingError::ReplicaMissing(id))?;
+        if replica.config.logging == logging {
+            return Ok(false);
+        }
+        replica.config.logging = logging;
+        Ok(true)
+    }
+
     /// Return the IDs of in-progress subscribes targeting the specified replica.
     fn subscribes_targeting(&self, replica_id: ReplicaId) -> impl Iterator<Item = GlobalId> + '_ {
         self.subscribes.iter().filter_map(move |(id, subscribe)| {
@@ -239,6 +335,8 @@ where
             subscribes: Default::default(),
             history: Default::default(),
             failed_replicas: Default::default(),
+            draining_replicas: Default::default(),
+            drained_replicas: Default::default(),
             ready_responses: Default::default(),
             envd_epoch,
             replica_epochs: Default::default(),
@@ -361,6 +459,22 @@ where
         subscribe.target_replica = Some(target_replica);
         Ok(())
     }
+
+    /// Ask the identified replica to drain, in preparation of dropping it.
+    ///
+    /// The `DrainReplica` command is sent only to this replica and is not recorded in the command
+    /// history. New peeks do not await the replica's response, and peeks that target it are
+    /// rejected. Use [`Instance::replica_is_drained`] to learn when the drain has completed.
+    pub fn drain_replica(&mut self, id: ReplicaId) -> Result<(), ReplicaMissing> {
+        let replica = self.replicas.get_mut(&id).ok_or(ReplicaMissing(id))?;
+        // If sending the command fails, the replica requires rehydration, which requests the
+        // drain again.
+        if replica.send(ComputeCommand::DrainReplica).is_err() {
+            self.failed_replicas.insert(id);
+        }
+        self.draining_replicas.insert(id);
+        Ok(())
+    }
 }
 
 /// A wrapper around [`Instance`] with a live storage controller.
@@ -442,6 +556,8 @@ where
             .ok_or(ReplicaMissing(id))?;
 
         self.compute.failed_replicas.remove(&id);
+        self.compute.draining_replicas.remove(&id);
+        self.compute.drained_replicas.remove(&id);
 
         // Remove frontier tracking for this replica.
         self.remove_write_frontiers(id);
@@ -483,6 +599,7 @@ where
     /// Panics if the specified replica does not exist.
     fn rehydrate_replica(&mut self, id: ReplicaId) {
         let config = self.compute.replicas[&id].config.clone();
+        let draining = self.compute.draining_replicas.contains(&id);
         self.remove_replica(id).expect("replica must exist");
         let result = self.add_replica(id, config);
 
@@ -490,6 +607,28 @@ where
             Ok(()) => (),
             Err(ReplicaExists(_)) => unreachable!("replica was removed"),
         }
+
+        // The rehydrated replica has not seen the drain request yet.
+        if draining {
+            self.compute.drain_replica(id).expect("replica was added");
+        }
+    }
+
+    /// Update the logging configuration of the identified replica.
+    ///
+    /// Replicas only accept a logging configuration when they are initialized, so a replica
+    /// whose configuration changes is rehydrated. This replaces its existing logging dataflows
+    /// with ones installed according to the new configuration.
+    pub fn update_replica_logging(
+        &mut self,
+        id: ReplicaId,
+        logging: LoggingConfig,
+    ) -> Result<(), ReplicaLoggingError> {
+        if self.compute.set_replica_logging(id, logging)? {
+            self.rehydrate_replica(id);
+            self.compute.failed_replicas.remove(&id);
+        }
+        Ok(())
     }
 
     /// Rehydrate any failed replicas of this instance.
@@ -501,6 +640,29 @@ where
         }
     }
 
+    /// Rebuild the command history from the current collection state and rehydrate all
+    /// replicas from it.
+    ///
+    /// See [`ComputeCommandHistory::rebuild`] for the commands retained in the rebuilt history.
+    /// Every replica, failed or not, is subsequently restarted and replays the rebuilt history.
+    pub fn reset_command_history(&mut self) {
+        let read_frontiers = self
+            .compute
+            .collections
+            .iter()
+            .map(|(id, collection)| (*id, collection.read_frontier().to_owned()))
+            .collect();
+        self.compute
+            .history
+            .rebuild(&read_frontiers, &self.compute.peeks);
+
+        let replica_ids: Vec<_> = self.compute.replica_ids().collect();
+        for replica_id in replica_ids {
+            self.rehydrate_replica(replica_id);
+            self.compute.failed_replicas.remove(&replica_id);
+        }
+    }
+
     /// Create the described dataflows and initializes state for their output.
     pub fn create_dataflows(
         &mut self,
@@ -708,6 +870,9 @@ where
             if !self.compute.replica_exists(target) {
                 return Err(PeekError::ReplicaMissing(target));
             }
+            if self.compute.draining_replicas.contains(&target) {
+                return Err(PeekError::ReplicaDraining(target));
+            }
         }
 
         // Install a compaction hold on `id` at `timestamp`.
@@ -715,7 +880,7 @@ where
         updates.insert(id, ChangeBatch::new_from(timestamp.clone(), 1));
         self.update_read_capabilities(&mut updates);
 
-        let unfinished = self.compute.replica_ids().collect();
+        let unfinished = self.compute.peek_replicas();
         let otel_ctx = OpenTelemetryContext::obtain();
         self.compute.peeks.insert(
             uuid,
@@ -1052,6 +1217,10 @@ where
             ComputeResponse::SubscribeResponse(id, response) => {
                 self.handle_subscribe_response(id, response, replica_id)
             }
+            ComputeResponse::ReplicaDrained => {
+                self.compute.handle_replica_drained(replica_id);
+                None
+            }
         }
     }
 
@@ -1262,6 +1431,16 @@ impl<T> PendingPeek<T> {
         // case, we wait for new replicas to be added to eventually serve the peek.
         self.otel_ctx.is_none() && self.unfinished.is_empty()
     }
+
+    /// Return whether the response of the specified replica is still needed to serve this peek.
+    ///
+    /// This is the case if no response has been passed on yet, the replica has not responded,
+    /// and the peek does not target a different replica.
+    fn awaits_response_from(&self, replica_id: ReplicaId) -> bool {
+        let served = self.otel_ctx.is_none();
+        let targets_other = self.target_replica.map_or(false, |id| id != replica_id);
+        !served && !targets_other && self.unfinished.contains(&replica_id)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1282,3 +1461,143 @@ impl<T: Timestamp> ActiveSubscribe<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use mz_build_info::DUMMY_BUILD_INFO;
+    use mz_cluster_client::client::ClusterReplicaLocation;
+    use mz_ore::metrics::MetricsRegistry;
+    use mz_storage_client::types::instances::StorageInstanceId;
+
+    use crate::logging::TimelyLog;
+    use crate::metrics::ComputeControllerMetrics;
+
+    use super::*;
+
+    fn instance_with_replicas(
+        log_sources: BTreeMap<LogVariant, GlobalId>,
+        replica_ids: &[ReplicaId],
+    ) -> Instance<mz_repr::Timestamp> {
+        let metrics = ComputeControllerMetrics::new(MetricsRegistry::new())
+            .for_instance(StorageInstanceId::User(1));
+        let envd_epoch = NonZeroI64::new(1).unwrap();
+        let mut instance = Instance::new(&DUMMY_BUILD_INFO, log_sources, envd_epoch, metrics);
+
+        for &id in replica_ids {
+            let config = ReplicaConfig {
+                location: ClusterReplicaLocation {
+                    ctl_addrs: vec![],
+                    dataflow_addrs: vec![],
+                    workers: 1,
+                },
+                logging: Default::default(),
+                idle_arrangement_merge_effort: 1,
+                reconnect: Default::default(),
+            };
+            let replica = Replica::spawn(
+                id,
+                &DUMMY_BUILD_INFO,
+                config,
+                ClusterStartupEpoch::new(envd_epoch, 1),
+                instance.metrics.for_replica(id),
+            );
+            instance.replicas.insert(id, replica);
+        }
+        instance
+    }
+
+    fn pending_peek(target_replica: Option<ReplicaId>) -> PendingPeek<mz_repr::Timestamp> {
+        PendingPeek {
+            target: GlobalId::User(1),
+            time: mz_repr::Timestamp::minimum(),
+            unfinished: BTreeSet::from([1, 2]),
+            target_replica,
+            otel_ctx: Some(OpenTelemetryContext::empty()),
+        }
+    }
+
+    fn enabled_logging() -> LoggingConfig {
+        LoggingConfig {
+            enable_logging: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn toggle_replica_logging() {
+        let log_sources =
+            BTreeMap::from([(LogVariant::Timely(TimelyLog::Operates), GlobalId::System(1))]);
+        let mut instance = instance_with_replicas(log_sources.clone(), &[1]);
+
+        // Enabling logging changes the replica's configuration, which then
+        // maintains the instance's log indexes.
+        assert!(instance.set_replica_logging(1, enabled_logging()).unwrap());
+        let logging = &instance.replicas[&1].config.logging;
+        assert!(logging.enable_logging);
+        assert_eq!(logging.index_logs, log_sources);
+
+        // Setting the same configuration again requires no rehydration.
+        assert!(!instance.set_replica_logging(1, enabled_logging()).unwrap());
+
+        // Logging can be disabled again.
+        assert!(instance.set_replica_logging(1, Default::default()).unwrap());
+        assert!(!instance.replicas[&1].config.logging.enable_logging);
+
+        assert!(matches!(
+            instance.set_replica_logging(2, enabled_logging()),
+            Err(ReplicaLoggingError::ReplicaMissing(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn enable_logging_without_log_sources() {
+        let mut instance = instance_with_replicas(BTreeMap::new(), &[1]);
+        assert!(matches!(
+            instance.set_replica_logging(1, enabled_logging()),
+            Err(ReplicaLoggingError::LogSourcesMissing)
+        ));
+        assert!(!instance.replicas[&1].config.logging.enable_logging);
+    }
+
+    #[tokio::test]
+    async fn drain_busy_replica() {
+        let mut instance = instance_with_replicas(BTreeMap::new(), &[1, 2]);
+        let peek_id = Uuid::new_v4();
+        instance.peeks.insert(peek_id, pending_peek(None));
+        let mut subscribe = ActiveSubscribe::new();
+        subscribe.target_replica = Some(1);
+        instance.subscribes.insert(GlobalId::User(2), subscribe);
+
+        // Replicas that were not asked to drain are never drained.
+        assert!(!instance.replica_is_drained(1));
+
+        // The replica is busy while the peek awaits its response.
+        instance.drain_replica(1).unwrap();
+        assert!(!instance.replica_is_drained(1));
+        assert!(!instance.replica_is_drained(2));
+
+        // New peeks do not await the draining replica.
+        assert_eq!(instance.peek_replicas(), BTreeSet::from([2]));
+
+        // Once the sibling has served the peek, the replica is drained, even though a subscribe
+        // still targets it.
+        instance.peeks.get_mut(&peek_id).unwrap().otel_ctx = None;
+        assert!(instance.replica_is_drained(1));
+
+        // Peeks targeting the sibling do not await the draining replica, but peeks targeting the
+        // draining replica do.
+        instance.peeks.insert(Uuid::new_v4(), pending_peek(Some(2)));
+        assert!(instance.replica_is_drained(1));
+        instance.peeks.insert(Uuid::new_v4(), pending_peek(Some(1)));
+        assert!(!instance.replica_is_drained(1));
+
+        // Unexpected acknowledgements are ignored, but the draining replica's acknowledgement
+        // completes its drain.
+        instance.handle_replica_drained(2);
+        assert!(!instance.replica_is_drained(2));
+        instance.handle_replica_drained(1);
+        assert!(instance.replica_is_drained(1));
+
+        assert!(matches!(instance.drain_replica(3), Err(ReplicaMissing(3))));
+    }
+}
@@ -9,8 +9,6 @@
 
 //! A client for replicas of a compute instance.
 
-use std::time::Duration;
-
 use anyhow::bail;
 use differential_dataflow::lattice::Lattice;
 use timely::progress::Timestamp;
@@ -20,7 +18,6 @@ use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
 use mz_build_info::BuildInfo;
 use mz_cluster_client::client::{ClusterReplicaLocation, ClusterStartupEpoch, TimelyConfig};
-use mz_ore::retry::Retry;
 use mz_ore::task::{AbortOnDropHandle, JoinHandleExt};
 use mz_service::client::GenericClient;
 
@@ -30,7 +27,7 @@ use crate::protocol::command::ComputeCommand;
 use crate::protocol::response::ComputeResponse;
 use crate::service::{ComputeClient, ComputeGrpcClient};
 
-use super::ReplicaId;
+use super::{ReplicaId, ReplicaReconnectConfig};
 
 /// Replica-specific configuration.
 #[derive(Clone, Debug)]
@@ -38,6 +35,7 @@ pub(super) struct ReplicaConfig {
     pub location: ClusterReplicaLocation,
     pub logging: LoggingConfig,
     pub idle_arrangement_merge_effort: u32,
+    pub reconnect: ReplicaReconnectConfig,
 }
 
 /// State for a single replica.
@@ -172,6 +170,7 @@ where
             response_tx,
             build_info,
             addrs,
+            &config.reconnect,
             cmd_spec,
             metrics,
         )
@@ -186,8 +185,9 @@ where
 
 /// Connects to the replica and runs the message loop.
 ///
-/// The initial replica connection is retried forever (with backoff). Once connected, the task
-/// returns (with an `Err`) if it encounters an error condition (e.g. the replica disconnects).
+/// The initial replica connection is retried according to the given [`ReplicaReconnectConfig`].
+/// If all attempts fail, or if the task encounters an error condition once connected (e.g. the
+/// replica disconnects), the task returns an `Err`.
 ///
 /// If no error condition is encountered, the task runs until the controller disconnects from the
 /// command channel, or the task is dropped.
@@ -197,6 +197,7 @@ async fn run_message_loop<T>(
     response_tx: UnboundedSender<ComputeResponse<T>>,
     build_info: &BuildInfo,
     addrs: Vec<String>,
+    reconnect: &ReplicaReconnectConfig,
     cmd_spec: CommandSpecialization,
     metrics: ReplicaMetrics,
 ) -> Result<(), anyhow::Error>
@@ -204,8 +205,8 @@ where
     T: Timestamp + Lattice,
     ComputeGrpcClient: ComputeClient<T>,
 {
-    let mut client = Retry::default()
-        .clamp_backoff(Duration::from_secs(1))
+    let mut client = reconnect
+        .retry()
         .retry_async(|state| {
             let dests = addrs
                 .clone()
@@ -218,24 +219,24 @@ where
                 match ComputeGrpcClient::connect_partitioned(dests, version).await {
                     Ok(client) => Ok(client),
                     Err(e) => {
-                        if state.i >= mz_service::retry::INFO_MIN_RETRIES {
-                            tracing::info!(
-                                "error connecting to replica {replica_id}, retrying in {:?}: {e}",
-                                state.next_backoff.unwrap()
-                            );
-                        } else {
-                            tracing::debug!(
-                                "error connecting to replica {replica_id}, retrying in {:?}: {e}",
-                                state.next_backoff.unwrap()
-                            );
+                        // The error of the final attempt is reported when the task fails.
+                        if let Some(backoff) = state.next_backoff {
+                            if state.i >= mz_service::retry::INFO_MIN_RETRIES {
+                                tracing::info!(
+                                    "error connecting to replica {replica_id}, retrying in {backoff:?}: {e}",
+                                );
+                            } else {
+                                tracing::debug!(
+                                    "error connecting to replica {replica_id}, retrying in {backoff:?}: {e}",
+                                );
+                            }
                         }
                         Err(e)
                     }
                 }
             }
         })
-        .await
-        .expect("retry retries forever");
+        .await?;
 
     loop {
         select! {
@@ -895,8 +895,11 @@ fn reduction_type(func: &AggregateFunc) -> ReductionType {
         | AggregateFunc::SumFloat64
         | AggregateFunc::SumNumeric
         | AggregateFunc::Count
+        | AggregateFunc::CountNumeric
         | AggregateFunc::Any
         | AggregateFunc::All
+        | AggregateFunc::BoolAnd
+        | AggregateFunc::BoolOr
         | AggregateFunc::Dummy => ReductionType::Accumulable,
         AggregateFunc::MaxNumeric
         | AggregateFunc::MaxInt16
@@ -913,6 +916,11 @@ fn reduction_type(func: &AggregateFunc) -> ReductionType {
         | AggregateFunc::MaxDate
         | AggregateFunc::MaxTimestamp
         | AggregateFunc::MaxTimestampTz
+        | AggregateFunc::MaxInterval
+        | AggregateFunc::MaxTime
+        | AggregateFunc::MaxBytes
+        | AggregateFunc::MaxUuid
+        | AggregateFunc::MaxArray
         | AggregateFunc::MinNumeric
         | AggregateFunc::MinInt16
         | AggregateFunc::MinInt32
@@ -927,17 +935,52 @@ fn reduction_type(func: &AggregateFunc) -> ReductionType {
         | AggregateFunc::MinString
         | AggregateFunc::MinDate
         | AggregateFunc::MinTimestamp
-        | AggregateFunc::MinTimestampTz => ReductionType::Hierarchical,
+        | AggregateFunc::MinTimestampTz
+        | AggregateFunc::MinInterval
+        | AggregateFunc::MinTime
+        | AggregateFunc::MinBytes
+        | AggregateFunc::MinUuid
+        | AggregateFunc::MinArray => ReductionType::Hierarchical,
         AggregateFunc::JsonbAgg { .. }
         | AggregateFunc::JsonbObjectAgg { .. }
         | AggregateFunc::ArrayConcat { .. }
         | AggregateFunc::ListConcat { .. }
+        | AggregateFunc::ArrayConcatDistinct { .. }
+        | AggregateFunc::ListConcatDistinct { .. }
         | AggregateFunc::StringAgg { .. }
         | AggregateFunc::RowNumber { .. }
+        | AggregateFunc::Rank { .. }
         | AggregateFunc::DenseRank { .. }
+        | AggregateFunc::PercentRank { .. }
+        | AggregateFunc::CumeDist { .. }
         | AggregateFunc::LagLead { .. }
         | AggregateFunc::FirstValue { .. }
-        | AggregateFunc::LastValue { .. } => ReductionType::Basic,
+        | AggregateFunc::CountDistinct
+        | AggregateFunc::SumInterval
+        | AggregateFunc::ProductFloat32
+        | AggregateFunc::ProductFloat64
+        | AggregateFunc::ProductNumeric
+        | AggregateFunc::NthValue { .. }
+        | AggregateFunc::Ntile { .. }
+        | AggregateFunc::LastValue { .. }
+        | AggregateFunc::AvgInt16
+        | AggregateFunc::AvgInt32
+        | AggregateFunc::AvgInt64
+        | AggregateFunc::AvgFloat32
+        | AggregateFunc::AvgFloat64
+        | AggregateFunc::AvgNumeric
+        | AggregateFunc::AvgInterval
+        | AggregateFunc::StddevPop
+        | AggregateFunc::StddevSamp
+        | AggregateFunc::VarPop
+        | AggregateFunc::VarSamp
+        | AggregateFunc::Corr
+        | AggregateFunc::CovarPop
+        | AggregateFunc::CovarSamp
+        | AggregateFunc::RegrCount
+        | AggregateFunc::RegrSlope
+        | AggregateFunc::RegrIntercept
+        | AggregateFunc::RegrR2 => ReductionType::Basic,
     }
 }
 
@@ -11,7 +11,7 @@
 
 use std::collections::BTreeSet;
 
-use proptest::prelude::{any, Arbitrary};
+use proptest::prelude::{any, Arbitrary, Just};
 use proptest::strategy::{BoxedStrategy, Strategy, Union};
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
@@ -227,6 +227,20 @@ pub enum ComputeCommand<T = mz_repr::Timestamp> {
         /// commands.
         uuids: BTreeSet<Uuid>,
     },
+
+    /// `DrainReplica` informs the replica that it is about to be dropped, and asks it to report
+    /// when it has finished the work that dropping it would abort.
+    ///
+    /// Unlike all other commands, `DrainReplica` is sent only to the replica that is to be
+    /// dropped, rather than to all replicas of the compute instance, and it is not replayed to
+    /// replicas that reconnect.
+    ///
+    /// After receiving a `DrainReplica` command, the replica must send a [`ReplicaDrained`]
+    /// response once it has responded to all `Peek` commands it has received. The replica continues
+    /// to process commands as usual, and it does not stop its subscribes, which cannot be drained.
+    ///
+    /// [`ReplicaDrained`]: super::response::ComputeResponse::ReplicaDrained
+    DrainReplica,
 }
 
 impl RustType<ProtoComputeCommand> for ComputeCommand<mz_repr::Timestamp> {
@@ -258,6 +272,7 @@ impl RustType<ProtoComputeCommand> for ComputeCommand<mz_repr::Timestamp> {
                 ComputeCommand::CancelPeeks { uuids } => CancelPeeks(ProtoCancelPeeks {
                     uuids: uuids.into_proto(),
                 }),
+                ComputeCommand::DrainReplica => DrainReplica(()),
             }),
         }
     }
@@ -289,6 +304,7 @@ impl RustType<ProtoComputeCommand> for ComputeCommand<mz_repr::Timestamp> {
             Some(CancelPeeks(ProtoCancelPeeks { uuids })) => Ok(ComputeCommand::CancelPeeks {
                 uuids: uuids.into_rust()?,
             }),
+            Some(DrainReplica(())) => Ok(ComputeCommand::DrainReplica),
             None => Err(TryFromProtoError::missing_field(
                 "ProtoComputeCommand::kind",
             )),
@@ -342,6 +358,7 @@ impl Arbitrary for ComputeCommand<mz_repr::Timestamp> {
                         uuids: BTreeSet::from_iter(uuids.into_iter()),
                     })
                     .boxed(),
+                Just(ComputeCommand::DrainReplica).boxed(),
             ])
     }
 }
@@ -365,6 +382,14 @@ pub struct ComputeParameters {
     pub max_result_size: Option<u32>,
     /// The maximum number of in-flight bytes emitted by persist_sources feeding dataflows.
     pub dataflow_max_inflight_bytes: Option<usize>,
+    /// The maximum number of rows in a partition of a window function.
+    ///
+    /// Window functions over larger partitions produce a
+    /// [`EvalError::WindowPartitionTooLarge`] error instead. The limit applies
+    /// to dataflows rendered after it was set.
+    ///
+    /// [`EvalError::WindowPartitionTooLarge`]: mz_expr::EvalError::WindowPartitionTooLarge
+    pub max_window_partition_size: Option<usize>,
     /// Persist client configuration.
     pub persist: PersistParameters,
 }
@@ -375,6 +400,7 @@ impl ComputeParameters {
         let ComputeParameters {
             max_result_size,
             dataflow_max_inflight_bytes,
+            max_window_partition_size,
             persist,
         } = other;
 
@@ -384,12 +410,17 @@ impl ComputeParameters {
         if dataflow_max_inflight_bytes.is_some() {
             self.dataflow_max_inflight_bytes = dataflow_max_inflight_bytes;
         }
+        if max_window_partition_size.is_some() {
+            self.max_window_partition_size = max_window_partition_size;
+        }
         self.persist.update(persist);
     }
 
     /// Return whether all parameters are unset.
     pub fn all_unset(&self) -> bool {
-        self.max_result_size.is_none() && self.persist.all_unset()
+        self.max_result_size.is_none()
+            && self.max_window_partition_size.is_none()
+            && self.persist.all_unset()
     }
 }
 
@@ -398,6 +429,7 @@ impl RustType<ProtoComputeParameters> for ComputeParameters {
         ProtoComputeParameters {
             max_result_size: self.max_result_size.into_proto(),
             dataflow_max_inflight_bytes: self.dataflow_max_inflight_bytes.into_proto(),
+            max_window_partition_size: self.max_window_partition_size.into_proto(),
             persist: Some(self.persist.into_proto()),
         }
     }
@@ -406,6 +438,7 @@ impl RustType<ProtoComputeParameters> for ComputeParameters {
         Ok(Self {
             max_result_size: proto.max_result_size.into_rust()?,
             dataflow_max_inflight_bytes: proto.dataflow_max_inflight_bytes.into_rust()?,
+            max_window_partition_size: proto.max_window_partition_size.into_rust()?,
             persist: proto
                 .persist
                 .into_rust_if_some("ProtoComputeParameters::persist")?,
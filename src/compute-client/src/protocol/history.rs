@@ -11,6 +11,7 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 
+use mz_repr::GlobalId;
 use timely::progress::Antichain;
 
 use super::command::{ComputeCommand, ComputeParameters, Peek};
@@ -112,6 +113,10 @@ impl<T: timely::progress::Timestamp> ComputeCommandHistory<T> {
                 ComputeCommand::CancelPeeks { uuids } => {
                     live_cancels.extend(uuids);
                 }
+                ComputeCommand::DrainReplica => {
+                    // Drain requests concern only the connection they were sent on, and are not
+                    // replayed.
+                }
             }
         }
 
@@ -203,6 +208,67 @@ impl<T: timely::progress::Timestamp> ComputeCommandHistory<T> {
         self.reduced_count = command_count;
     }
 
+    /// Rebuilds the history from the controller's current view of its collections and peeks,
+    /// discarding whatever compaction state the recorded commands imply.
+    ///
+    /// The rebuilt history consists of:
+    ///
+    ///  * the `CreateTimely`, `CreateInstance`, and `InitializationComplete` commands, and the
+    ///    unified configuration, as they appear in the reduced history,
+    ///  * every dataflow that exports at least one collection present in `read_frontiers`, with
+    ///    its `as_of` advanced to the read frontiers of its exports, held back by live peeks,
+    ///  * the peeks present in `peeks`, and no peek cancellations,
+    ///  * compaction to the respective read frontier, for every exported collection whose read
+    ///    frontier differs from its dataflow's new `as_of`,
+    ///  * compaction to the respective read frontier, for every collection in `read_frontiers`
+    ///    that is not exported by a dataflow and has not been dropped. These are the log
+    ///    collections, which replicas create on their own and which are thus unknown to the
+    ///    history, but whose compaction must still be replayed.
+    ///
+    /// Dataflows whose exports are all absent from `read_frontiers` are dropped.
+    pub fn rebuild<V>(
+        &mut self,
+        read_frontiers: &BTreeMap<GlobalId, Antichain<T>>,
+        peeks: &BTreeMap<uuid::Uuid, V>,
+    ) {
+        self.commands.retain(|command| match command {
+            ComputeCommand::AllowCompaction(_) | ComputeCommand::CancelPeeks { .. } => false,
+            ComputeCommand::Peek(peek) => peeks.contains_key(&peek.uuid),
+            _ => true,
+        });
+
+        let export_ids: BTreeSet<_> = self
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                ComputeCommand::CreateDataflows(dataflows) => Some(dataflows),
+                _ => None,
+            })
+            .flatten()
+            .flat_map(|dataflow| dataflow.export_ids())
+            .collect();
+        // Replicas panic when asked to drop a collection they do not know, so collections without
+        // a dataflow are only allowed to compact as long as they have not been dropped.
+        let other_frontiers = read_frontiers
+            .iter()
+            .filter(|(id, frontier)| !export_ids.contains(id) && !frontier.is_empty())
+            .map(|(id, frontier)| (*id, frontier.clone()));
+        let frontiers: Vec<_> = export_ids
+            .iter()
+            .map(|id| {
+                let frontier = read_frontiers.get(id).cloned().unwrap_or_default();
+                (*id, frontier)
+            })
+            .chain(other_frontiers)
+            .collect();
+        if !frontiers.is_empty() {
+            self.commands
+                .push(ComputeCommand::AllowCompaction(frontiers));
+        }
+
+        self.reduce();
+    }
+
     /// Retain only those peeks present in `peeks` and discard the rest.
     pub fn retain_peeks<V>(&mut self, peeks: &BTreeMap<uuid::Uuid, V>) {
         for command in self.commands.iter_mut() {
@@ -232,3 +298,176 @@ impl<T> Default for ComputeCommandHistory<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use mz_repr::{GlobalId, RelationType, Timestamp};
+    use timely::progress::{Antichain, Timestamp as _};
+
+    use crate::logging::LoggingConfig;
+    use crate::protocol::command::{ComputeCommand, ComputeParameters};
+    use crate::types::dataflows::{DataflowDescription, IndexDesc};
+
+    use super::ComputeCommandHistory;
+
+    fn update_configuration(max_result_size: u32) -> ComputeCommand {
+        ComputeCommand::UpdateConfiguration(ComputeParameters {
+            max_result_size: Some(max_result_size),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn len_tracks_pushes_and_reduction() {
+        let peeks = BTreeMap::<uuid::Uuid, ()>::new();
+        let mut history = ComputeCommandHistory::default();
+        assert_eq!(history.len(), 0);
+
+        history.push(
+            ComputeCommand::CreateInstance(LoggingConfig::default()),
+            &peeks,
+        );
+        history.push(update_configuration(1), &peeks);
+        history.reduce();
+        assert_eq!(history.len(), 2);
+
+        // Pushing commands increases the length until the next reduction.
+        history.push(update_configuration(2), &peeks);
+        assert_eq!(history.len(), 3);
+        history.push(update_configuration(3), &peeks);
+        assert_eq!(history.len(), 4);
+
+        // Reduction unifies the configuration updates.
+        history.reduce();
+        assert_eq!(history.len(), 2);
+    }
+
+    fn index_dataflow(id: GlobalId, as_of: u64) -> DataflowDescription<crate::plan::Plan> {
+        let desc = IndexDesc {
+            on_id: GlobalId::User(0),
+            key: Vec::new(),
+        };
+        DataflowDescription {
+            source_imports: Default::default(),
+            index_imports: Default::default(),
+            objects_to_build: Vec::new(),
+            index_exports: [(id, (desc, RelationType::empty()))].into(),
+            sink_exports: Default::default(),
+            as_of: Some(Antichain::from_elem(as_of.into())),
+            until: Antichain::new(),
+            debug_name: id.to_string(),
+        }
+    }
+
+    /// Models the collections a replica maintains, and the frontiers they are compacted to, after
+    /// it has processed `commands`. The replica maintains the log collections `log_ids` on its
+    /// own.
+    fn hydrate<'a>(
+        commands: impl IntoIterator<Item = &'a ComputeCommand>,
+        log_ids: &[GlobalId],
+    ) -> BTreeMap<GlobalId, Antichain<Timestamp>> {
+        let mut collections = BTreeMap::new();
+        for command in commands {
+            match command {
+                ComputeCommand::CreateInstance(_) => {
+                    for id in log_ids {
+                        collections.insert(*id, Antichain::from_elem(Timestamp::minimum()));
+                    }
+                }
+                ComputeCommand::CreateDataflows(dataflows) => {
+                    for dataflow in dataflows {
+                        for id in dataflow.export_ids() {
+                            collections.insert(id, dataflow.as_of.clone().unwrap());
+                        }
+                    }
+                }
+                ComputeCommand::AllowCompaction(frontiers) => {
+                    for (id, frontier) in frontiers {
+                        if frontier.is_empty() {
+                            // Like a replica, refuse to drop unknown collections.
+                            collections.remove(id).expect("dropped unknown collection");
+                        } else if let Some(since) = collections.get_mut(id) {
+                            *since = frontier.clone();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        collections
+    }
+
+    #[test]
+    fn rebuild_from_collection_state() {
+        let peeks = BTreeMap::<uuid::Uuid, ()>::new();
+        let mut history = ComputeCommandHistory::default();
+        history.push(
+            ComputeCommand::CreateInstance(LoggingConfig::default()),
+            &peeks,
+        );
+        history.push(
+            ComputeCommand::CreateDataflows(vec![
+                index_dataflow(GlobalId::User(1), 1),
+                index_dataflow(GlobalId::User(2), 1),
+            ]),
+            &peeks,
+        );
+        // A compaction command that is inconsistent with the controller's collection state.
+        history.push(
+            ComputeCommand::AllowCompaction(vec![(
+                GlobalId::User(1),
+                Antichain::from_elem(10.into()),
+            )]),
+            &peeks,
+        );
+        history.push(
+            ComputeCommand::CancelPeeks {
+                uuids: [uuid::Uuid::nil()].into(),
+            },
+            &peeks,
+        );
+        history.push(
+            ComputeCommand::AllowCompaction(vec![(
+                GlobalId::System(1),
+                Antichain::from_elem(3.into()),
+            )]),
+            &peeks,
+        );
+        history.push(ComputeCommand::InitializationComplete, &peeks);
+
+        // `User(1)` is readable from 5, and `User(2)` has been dropped. The log collection
+        // `System(1)` is readable from 7, and `User(3)` has been dropped earlier.
+        let read_frontiers = [
+            (GlobalId::User(1), Antichain::from_elem(5.into())),
+            (GlobalId::System(1), Antichain::from_elem(7.into())),
+            (GlobalId::User(3), Antichain::new()),
+        ]
+        .into();
+        history.rebuild(&read_frontiers, &peeks);
+
+        let rebuilt: Vec<_> = history.iter().cloned().collect();
+        assert_eq!(
+            rebuilt,
+            vec![
+                ComputeCommand::CreateInstance(LoggingConfig::default()),
+                ComputeCommand::CreateDataflows(vec![index_dataflow(GlobalId::User(1), 5)]),
+                ComputeCommand::AllowCompaction(vec![(
+                    GlobalId::System(1),
+                    Antichain::from_elem(7.into())
+                )]),
+                ComputeCommand::InitializationComplete,
+            ]
+        );
+        assert_eq!(history.dataflow_count(), 1);
+
+        // A freshly added replica that is hydrated by replaying the rebuilt history maintains
+        // exactly the collections that have not been dropped, at their read frontiers.
+        let expected: BTreeMap<_, _> = read_frontiers
+            .into_iter()
+            .filter(|(_, frontier)| !frontier.is_empty())
+            .collect();
+        assert_eq!(hydrate(history.iter(), &[GlobalId::System(1)]), expected);
+    }
+}
@@ -133,6 +133,17 @@ pub enum ComputeResponse<T = mz_repr::Timestamp> {
     /// [`AllowCompaction` command]: super::command::ComputeCommand::AllowCompaction
     /// [#16247]: https://github.com/MaterializeInc/materialize/issues/16247
     SubscribeResponse(GlobalId, SubscribeResponse<T>),
+
+    /// `ReplicaDrained` acknowledges a previous [`DrainReplica` command]. It reports that the
+    /// replica has responded to all [`Peek` command]s it received before the acknowledged
+    /// [`DrainReplica` command].
+    ///
+    /// The replica must send exactly one `ReplicaDrained` response for every [`DrainReplica`
+    /// command] it received. It must not send `ReplicaDrained` responses otherwise.
+    ///
+    /// [`DrainReplica` command]: super::command::ComputeCommand::DrainReplica
+    /// [`Peek` command]: super::command::ComputeCommand::Peek
+    ReplicaDrained,
 }
 
 impl RustType<ProtoComputeResponse> for ComputeResponse<mz_repr::Timestamp> {
@@ -155,6 +166,7 @@ impl RustType<ProtoComputeResponse> for ComputeResponse<mz_repr::Timestamp> {
                         resp: Some(resp.into_proto()),
                     })
                 }
+                ComputeResponse::ReplicaDrained => ReplicaDrained(()),
             }),
         }
     }
@@ -176,6 +188,7 @@ impl RustType<ProtoComputeResponse> for ComputeResponse<mz_repr::Timestamp> {
                 resp.resp
                     .into_rust_if_some("ProtoSubscribeResponseKind::resp")?,
             )),
+            Some(ReplicaDrained(())) => Ok(ComputeResponse::ReplicaDrained),
             None => Err(TryFromProtoError::missing_field(
                 "ProtoComputeResponse::kind",
             )),
@@ -200,6 +213,7 @@ impl Arbitrary for ComputeResponse<mz_repr::Timestamp> {
             (any::<GlobalId>(), any::<SubscribeResponse>())
                 .prop_map(|(id, resp)| ComputeResponse::SubscribeResponse(id, resp))
                 .boxed(),
+            Just(ComputeResponse::ReplicaDrained).boxed(),
         ])
     }
 }
@@ -13,7 +13,7 @@
 
 //! Compute layer client and server.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::iter;
 
 use async_trait::async_trait;
@@ -157,6 +157,10 @@ pub struct PartitionedComputeState<T> {
     /// the tracking state maintained for it and b) we won't re-initialize tracking for a subscribe
     /// we have already dropped.
     pending_subscribes: BTreeMap<GlobalId, PendingSubscribe<T>>,
+    /// The partitions that have acknowledged the outstanding `DrainReplica` command.
+    ///
+    /// A `ReplicaDrained` response is emitted once all partitions have acknowledged the drain.
+    drained_parts: BTreeSet<usize>,
 }
 
 impl<T> Partitionable<ComputeCommand<T>, ComputeResponse<T>>
@@ -172,6 +176,7 @@ where
             uppers: BTreeMap::new(),
             peek_responses: BTreeMap::new(),
             pending_subscribes: BTreeMap::new(),
+            drained_parts: BTreeSet::new(),
         }
     }
 }
@@ -186,10 +191,12 @@ where
             uppers,
             peek_responses,
             pending_subscribes,
+            drained_parts,
         } = self;
         uppers.clear();
         peek_responses.clear();
         pending_subscribes.clear();
+        drained_parts.clear();
     }
 
     /// Observes commands that move past, and prepares state for responses.
@@ -411,6 +418,15 @@ where
 
                 emit_response
             }
+            ComputeResponse::ReplicaDrained => {
+                self.drained_parts.insert(shard_id);
+                if self.drained_parts.len() == self.parts {
+                    self.drained_parts.clear();
+                    Some(Ok(ComputeResponse::ReplicaDrained))
+                } else {
+                    None
+                }
+            }
         }
     }
 }
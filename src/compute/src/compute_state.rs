@@ -67,6 +67,10 @@ pub struct ComputeState {
     pub flow_control_probes: BTreeMap<GlobalId, Vec<probe::Handle<Timestamp>>>,
     /// Peek commands that are awaiting fulfillment.
     pub pending_peeks: BTreeMap<Uuid, PendingPeek>,
+    /// Whether a `DrainReplica` command awaits a `ReplicaDrained` response.
+    ///
+    /// The response is sent once `pending_peeks` is empty.
+    pub drain_requested: bool,
     /// Tracks the frontier information that has been sent over `response_tx`.
     pub reported_frontiers: BTreeMap<GlobalId, ReportedFrontier>,
     /// Collections that were recently dropped and whose removal needs to be reported.
@@ -82,6 +86,8 @@ pub struct ComputeState {
     pub max_result_size: u32,
     /// Maximum number of in-flight bytes emitted by persist_sources feeding dataflows.
     pub dataflow_max_inflight_bytes: usize,
+    /// Maximum number of rows in a partition of a window function.
+    pub max_window_partition_size: usize,
     /// Metrics for this replica.
     pub metrics: ComputeMetrics,
 }
@@ -143,6 +149,7 @@ impl<'a, A: Allocate> ActiveComputeState<'a, A> {
                 self.handle_peek(peek)
             }
             CancelPeeks { uuids } => self.handle_cancel_peeks(uuids),
+            DrainReplica => self.compute_state.drain_requested = true,
         }
     }
 
@@ -156,6 +163,7 @@ impl<'a, A: Allocate> ActiveComputeState<'a, A> {
         let ComputeParameters {
             max_result_size,
             dataflow_max_inflight_bytes,
+            max_window_partition_size,
             persist,
         } = params;
 
@@ -165,6 +173,9 @@ impl<'a, A: Allocate> ActiveComputeState<'a, A> {
         if let Some(v) = dataflow_max_inflight_bytes {
             self.compute_state.dataflow_max_inflight_bytes = v;
         }
+        if let Some(v) = max_window_partition_size {
+            self.compute_state.max_window_partition_size = v;
+        }
 
         persist.apply(self.compute_state.persist_clients.cfg())
     }
@@ -470,6 +481,11 @@ impl<'a, A: Allocate> ActiveComputeState<'a, A> {
                 self.compute_state.pending_peeks.insert(uuid, peek);
             }
         }
+
+        if self.compute_state.drain_requested && self.compute_state.pending_peeks.is_empty() {
+            self.compute_state.drain_requested = false;
+            self.send_compute_response(ComputeResponse::ReplicaDrained);
+        }
     }
 
     /// Sends a response for this peek's resolution to the coordinator.
@@ -96,6 +96,8 @@ where
     pub bindings: BTreeMap<Id, CollectionBundle<S, V, T>>,
     /// An optional token that operators can probe to know whether the dataflow is shutting down.
     pub shutdown_token: Option<Weak<()>>,
+    /// The maximum number of rows in a partition of a window function.
+    pub max_window_partition_size: usize,
 }
 
 impl<S: Scope, V: Data + columnation::Columnation> Context<S, V>
@@ -106,6 +108,7 @@ where
     pub fn for_dataflow_in<Plan>(
         dataflow: &DataflowDescription<Plan, CollectionMetadata>,
         scope: S,
+        max_window_partition_size: usize,
     ) -> Self {
         use mz_ore::collections::CollectionExt as IteratorExt;
         let dataflow_id = scope.addr().into_first();
@@ -122,6 +125,7 @@ where
             until: dataflow.until.clone(),
             bindings: BTreeMap::new(),
             shutdown_token: None,
+            max_window_partition_size,
         }
     }
 }
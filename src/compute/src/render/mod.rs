@@ -272,8 +272,11 @@ pub fn build_compute_dataflow<A: Allocate>(
             scope
                 .clone()
                 .iterative::<PointStamp<usize>, _, _>(|region| {
-                    let mut context =
-                        crate::render::context::Context::for_dataflow_in(&dataflow, region.clone());
+                    let mut context = crate::render::context::Context::for_dataflow_in(
+                        &dataflow,
+                        region.clone(),
+                        compute_state.max_window_partition_size,
+                    );
 
                     for (id, (oks, errs)) in imported_sources.into_iter() {
                         let bundle = crate::render::CollectionBundle::from_collections(
@@ -332,8 +335,11 @@ pub fn build_compute_dataflow<A: Allocate>(
                 });
         } else {
             scope.clone().region_named(&build_name, |region| {
-                let mut context =
-                    crate::render::context::Context::for_dataflow_in(&dataflow, region.clone());
+                let mut context = crate::render::context::Context::for_dataflow_in(
+                    &dataflow,
+                    region.clone(),
+                    compute_state.max_window_partition_size,
+                );
 
                 for (id, (oks, errs)) in imported_sources.into_iter() {
                     let bundle = crate::render::CollectionBundle::from_collections(
@@ -545,9 +545,10 @@ where
         for (index, aggr) in aggrs {
             let (result, errs) =
                 self.build_basic_aggregate(input.clone(), index, &aggr, err_output.is_none());
-            if errs.is_some() {
-                err_output = errs
-            }
+            err_output = match (err_output, errs) {
+                (Some(err_output), Some(errs)) => Some(err_output.concat(&errs)),
+                (err_output, errs) => err_output.or(errs),
+            };
             to_collect
                 .push(result.as_collection(move |key, val| (key.clone(), (index, val.clone()))));
         }
@@ -623,6 +624,10 @@ where
 
         let arranged =
             partial.arrange_named::<RowSpine<_, Row, _, _>>("Arranged ReduceInaccumulable");
+        let max_partition_size = self.max_window_partition_size;
+        let is_window_func = func.is_window_func();
+        let is_fallible = func.is_fallible();
+        let eval_func = func.clone();
         let oks = arranged.reduce_abelian::<_, RowSpine<_, _, _, _>>("ReduceInaccumulable", {
             let mut row_buf = Row::default();
             move |_key, source, target| {
@@ -635,11 +640,57 @@ where
                     let count = usize::try_from(*w).unwrap_or(0);
                     std::iter::repeat(v.iter().next().unwrap()).take(count)
                 });
-                row_buf.packer().push(func.eval(iter, &RowArena::new()));
-                target.push((row_buf.clone(), 1));
+                let temp_storage = RowArena::new();
+                // Window functions over partitions that are too large and aggregates that fail to
+                // evaluate produce no output here, but an error in the checks below.
+                if let Ok(datum) =
+                    func.eval_with_max_partition_size(iter, &temp_storage, max_partition_size)
+                {
+                    row_buf.packer().push(datum);
+                    target.push((row_buf.clone(), 1));
+                }
             }
         });
 
+        // Report the partitions of window functions that exceed the maximum size. This is a
+        // separate reduction, so that it is only rendered when a maximum size is set.
+        let partition_errs = (is_window_func && max_partition_size < usize::MAX).then(|| {
+            arranged
+                .reduce_abelian::<_, ErrValSpine<_, _, _>>(
+                    "ReduceInaccumulable Partition Size Check",
+                    move |_key, source, target| {
+                        let size: Diff = source.iter().map(|(_, w)| *w).sum();
+                        if usize::try_from(size).map_or(false, |size| size > max_partition_size) {
+                            let err = EvalError::WindowPartitionTooLarge {
+                                max_size: max_partition_size,
+                            };
+                            target.push((err.into(), 1));
+                        }
+                    },
+                )
+                .as_collection(|_, v| v.clone())
+        });
+
+        // Report the groups on which a fallible aggregate, like the sum of intervals, fails to
+        // evaluate. This is a separate reduction, so that it is only rendered for such aggregates.
+        let eval_errs = is_fallible.then(|| {
+            arranged
+                .reduce_abelian::<_, ErrValSpine<_, _, _>>(
+                    "ReduceInaccumulable Eval Check",
+                    move |_key, source, target| {
+                        let iter = source.iter().flat_map(|(v, w)| {
+                            let count = usize::try_from(*w).unwrap_or(0);
+                            std::iter::repeat(v.iter().next().unwrap()).take(count)
+                        });
+                        let temp_storage = RowArena::new();
+                        if let Err(err) = eval_func.try_eval(iter, &temp_storage) {
+                            target.push((err.into(), 1));
+                        }
+                    },
+                )
+                .as_collection(|_, v| v.clone())
+        });
+
         // Note that we would prefer to use `mz_timely_util::reduce::ReduceExt::reduce_pair` here, but
         // we then wouldn't be able to do this error check conditionally.  See its documentation for the
         // rationale around using a second reduction here.
@@ -664,10 +715,14 @@ where
                     }
                 },
             );
-            (oks, Some(errs.as_collection(|_, v| v.clone())))
-        } else {
-            (oks, err_output)
+            err_output = Some(errs.as_collection(|_, v| v.clone()));
         }
+
+        let err_output = [err_output, partition_errs, eval_errs]
+            .into_iter()
+            .flatten()
+            .reduce(|errs, more_errs| errs.concat(&more_errs));
+        (oks, err_output)
     }
 
     fn build_reduce_inaccumulable_distinct<S, R>(
@@ -1030,7 +1085,10 @@ where
             full_aggrs
                 .iter()
                 .map(|f| match f.func {
-                    AggregateFunc::Any | AggregateFunc::All => Accum::Bool {
+                    AggregateFunc::Any
+                    | AggregateFunc::All
+                    | AggregateFunc::BoolAnd
+                    | AggregateFunc::BoolOr => Accum::Bool {
                         trues: 0,
                         falses: 0,
                     },
@@ -1064,7 +1122,15 @@ where
                     accum: 0, // unused for AggregateFunc::Count
                     non_nulls: if datum.is_null() { 0 } else { 1 },
                 },
-                AggregateFunc::Any | AggregateFunc::All => match datum {
+                // Counted in the wider `accum`, so that the count does not overflow.
+                AggregateFunc::CountNumeric => Accum::SimpleNumber {
+                    accum: if datum.is_null() { 0 } else { 1 },
+                    non_nulls: if datum.is_null() { 0 } else { 1 },
+                },
+                AggregateFunc::Any
+                | AggregateFunc::All
+                | AggregateFunc::BoolAnd
+                | AggregateFunc::BoolOr => match datum {
                     Datum::True => Accum::Bool {
                         trues: 1,
                         falses: 0,
@@ -1281,6 +1347,7 @@ where
                             let value = if total > 0
                                 && accum.is_zero()
                                 && aggr.func != AggregateFunc::Count
+                                && aggr.func != AggregateFunc::CountNumeric
                             {
                                 Datum::Null
                             } else {
@@ -1289,7 +1356,16 @@ where
                                         AggregateFunc::Count,
                                         Accum::SimpleNumber { non_nulls, .. },
                                     ) => Datum::Int64(*non_nulls),
-                                    (AggregateFunc::All, Accum::Bool { falses, trues }) => {
+                                    (
+                                        AggregateFunc::CountNumeric,
+                                        Accum::SimpleNumber { accum, .. },
+                                    ) => Datum::from(
+                                        Numeric::try_from(*accum).expect("i128 fits in numeric"),
+                                    ),
+                                    (
+                                        AggregateFunc::All | AggregateFunc::BoolAnd,
+                                        Accum::Bool { falses, trues },
+                                    ) => {
                                         // If any false, else if all true, else must be no false and some nulls.
                                         if *falses > 0 {
                                             Datum::False
@@ -1299,7 +1375,10 @@ where
                                             Datum::Null
                                         }
                                     }
-                                    (AggregateFunc::Any, Accum::Bool { falses, trues }) => {
+                                    (
+                                        AggregateFunc::Any | AggregateFunc::BoolOr,
+                                        Accum::Bool { falses, trues },
+                                    ) => {
                                         // If any true, else if all false, else must be no true and some nulls.
                                         if *trues > 0 {
                                             Datum::True
@@ -1774,6 +1853,8 @@ pub mod monoids {
     // will not have such elements in this case (they would correspond to positive and
     // negative infinity, which we do not represent).
 
+    use std::cmp::Ordering;
+
     use differential_dataflow::difference::{Multiply, Semigroup};
     use serde::{Deserialize, Serialize};
 
@@ -1812,7 +1893,7 @@ pub mod monoids {
                         match (lhs_val, rhs_val) {
                             (_, Datum::Null) => false,
                             (Datum::Null, _) => true,
-                            (lhs, rhs) => rhs < lhs,
+                            (lhs, rhs) => cmp_datums(rhs, lhs) == Ordering::Less,
                         }
                     };
                     if swap {
@@ -1827,7 +1908,7 @@ pub mod monoids {
                         match (lhs_val, rhs_val) {
                             (_, Datum::Null) => false,
                             (Datum::Null, _) => true,
-                            (lhs, rhs) => rhs > lhs,
+                            (lhs, rhs) => cmp_datums(rhs, lhs) == Ordering::Greater,
                         }
                     };
                     if swap {
@@ -1847,6 +1928,15 @@ pub mod monoids {
         }
     }
 
+    /// Compares the datums of a min or max reduction like the corresponding
+    /// aggregate function does, which for intervals differs from their `Ord`.
+    fn cmp_datums(lhs: Datum, rhs: Datum) -> Ordering {
+        match (lhs, rhs) {
+            (Datum::Interval(lhs), Datum::Interval(rhs)) => lhs.cmp_by_length(&rhs),
+            (lhs, rhs) => lhs.cmp(&rhs),
+        }
+    }
+
     /// Get the correct monoid implementation for a given aggregation function. Note that
     /// all hierarchical aggregation functions need to supply a monoid implementation.
     pub fn get_monoid(row: Row, func: &AggregateFunc) -> Option<ReductionMonoid> {
@@ -1865,7 +1955,12 @@ pub mod monoids {
             | AggregateFunc::MaxString
             | AggregateFunc::MaxDate
             | AggregateFunc::MaxTimestamp
-            | AggregateFunc::MaxTimestampTz => Some(ReductionMonoid::Max(row)),
+            | AggregateFunc::MaxTimestampTz
+            | AggregateFunc::MaxInterval
+            | AggregateFunc::MaxTime
+            | AggregateFunc::MaxBytes
+            | AggregateFunc::MaxUuid
+            | AggregateFunc::MaxArray => Some(ReductionMonoid::Max(row)),
             AggregateFunc::MinNumeric
             | AggregateFunc::MinInt16
             | AggregateFunc::MinInt32
@@ -1880,7 +1975,12 @@ pub mod monoids {
             | AggregateFunc::MinString
             | AggregateFunc::MinDate
             | AggregateFunc::MinTimestamp
-            | AggregateFunc::MinTimestampTz => Some(ReductionMonoid::Min(row)),
+            | AggregateFunc::MinTimestampTz
+            | AggregateFunc::MinInterval
+            | AggregateFunc::MinTime
+            | AggregateFunc::MinBytes
+            | AggregateFunc::MinUuid
+            | AggregateFunc::MinArray => Some(ReductionMonoid::Min(row)),
             AggregateFunc::SumInt16
             | AggregateFunc::SumInt32
             | AggregateFunc::SumInt64
@@ -1890,20 +1990,53 @@ pub mod monoids {
             | AggregateFunc::SumFloat32
             | AggregateFunc::SumFloat64
             | AggregateFunc::SumNumeric
+            | AggregateFunc::SumInterval
+            | AggregateFunc::ProductFloat32
+            | AggregateFunc::ProductFloat64
+            | AggregateFunc::ProductNumeric
             | AggregateFunc::Count
+            | AggregateFunc::CountNumeric
+            | AggregateFunc::CountDistinct
             | AggregateFunc::Any
             | AggregateFunc::All
+            | AggregateFunc::BoolAnd
+            | AggregateFunc::BoolOr
             | AggregateFunc::Dummy
             | AggregateFunc::JsonbAgg { .. }
             | AggregateFunc::JsonbObjectAgg { .. }
             | AggregateFunc::ArrayConcat { .. }
             | AggregateFunc::ListConcat { .. }
+            | AggregateFunc::ArrayConcatDistinct { .. }
+            | AggregateFunc::ListConcatDistinct { .. }
             | AggregateFunc::StringAgg { .. }
             | AggregateFunc::RowNumber { .. }
+            | AggregateFunc::Rank { .. }
             | AggregateFunc::DenseRank { .. }
+            | AggregateFunc::PercentRank { .. }
+            | AggregateFunc::CumeDist { .. }
             | AggregateFunc::LagLead { .. }
             | AggregateFunc::FirstValue { .. }
-            | AggregateFunc::LastValue { .. } => None,
+            | AggregateFunc::NthValue { .. }
+            | AggregateFunc::Ntile { .. }
+            | AggregateFunc::LastValue { .. }
+            | AggregateFunc::AvgInt16
+            | AggregateFunc::AvgInt32
+            | AggregateFunc::AvgInt64
+            | AggregateFunc::AvgFloat32
+            | AggregateFunc::AvgFloat64
+            | AggregateFunc::AvgNumeric
+            | AggregateFunc::AvgInterval
+            | AggregateFunc::StddevPop
+            | AggregateFunc::StddevSamp
+            | AggregateFunc::VarPop
+            | AggregateFunc::VarSamp
+            | AggregateFunc::Corr
+            | AggregateFunc::CovarPop
+            | AggregateFunc::CovarSamp
+            | AggregateFunc::RegrCount
+            | AggregateFunc::RegrSlope
+            | AggregateFunc::RegrIntercept
+            | AggregateFunc::RegrR2 => None,
         }
     }
 }
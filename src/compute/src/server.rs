@@ -399,6 +399,7 @@ impl<'w, A: Allocate> Worker<'w, A> {
                     sink_write_frontiers: BTreeMap::new(),
                     flow_control_probes: BTreeMap::new(),
                     pending_peeks: BTreeMap::new(),
+                    drain_requested: false,
                     reported_frontiers: BTreeMap::new(),
                     dropped_collections: Vec::new(),
                     compute_logger: None,
@@ -406,6 +407,7 @@ impl<'w, A: Allocate> Worker<'w, A> {
                     command_history: ComputeCommandHistory::default(),
                     max_result_size: u32::MAX,
                     dataflow_max_inflight_bytes: usize::MAX,
+                    max_window_partition_size: usize::MAX,
                     metrics: self.compute_metrics.clone(),
                 });
             }
@@ -633,6 +635,8 @@ impl<'w, A: Allocate> Worker<'w, A> {
                     logger.log(ComputeEvent::Peek(peek.as_log_event(), false));
                 }
             }
+            // A drain requested by the previous controller connection is obsolete.
+            compute_state.drain_requested = false;
             // We compact away removed frontiers, and so only need to reset ids we continue to use.
             // We must remember, though, to compensate what already was sent to logging sources.
             for (&id, reported_frontier) in compute_state.reported_frontiers.iter_mut() {
@@ -10,10 +10,12 @@
 //! Cluster management.
 
 use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail, Context};
 use chrono::{DateTime, Utc};
 use differential_dataflow::lattice::Lattice;
 use futures::stream::{BoxStream, StreamExt, TryStreamExt};
@@ -25,20 +27,21 @@ use tracing::{error, warn};
 
 use mz_cluster_client::client::ClusterReplicaLocation;
 use mz_compute_client::controller::{
-    ComputeInstanceId, ComputeReplicaConfig, ComputeReplicaLogging,
+    ComputeInstanceId, ComputeInstanceRef, ComputeReplicaConfig, ComputeReplicaLogging,
 };
 use mz_compute_client::logging::LogVariant;
 use mz_compute_client::service::{ComputeClient, ComputeGrpcClient};
 use mz_orchestrator::{
-    CpuLimit, LabelSelectionLogic, LabelSelector, MemoryLimit, Service, ServiceConfig,
-    ServiceEvent, ServicePort,
+    CpuLimit, LabelSelectionLogic, LabelSelector, MemoryLimit, NamespacedOrchestrator, Service,
+    ServiceConfig, ServiceEvent, ServicePort, ServiceProcessMetrics, Toleration,
 };
 use mz_ore::halt;
+use mz_ore::retry::Retry;
 use mz_ore::task::{AbortOnDropHandle, JoinHandleExt};
 use mz_repr::adt::numeric::Numeric;
 use mz_repr::GlobalId;
 
-use crate::Controller;
+use crate::{Controller, ControllerResponse};
 
 pub use mz_compute_client::controller::DEFAULT_COMPUTE_REPLICA_LOGGING_INTERVAL_MICROS as DEFAULT_REPLICA_LOGGING_INTERVAL_MICROS;
 
@@ -119,6 +122,7 @@ impl ReplicaLocation {
 
 /// The "role" of a cluster, which is currently used to determine the
 /// severity of alerts for problems with its replicas.
+#[derive(Clone, Copy, Debug)]
 pub enum ClusterRole {
     /// The existence and proper functioning of the cluster's replicas is
     /// business-critical for Materialize.
@@ -163,6 +167,87 @@ pub struct ManagedReplicaLocation {
     /// `true` if the AZ was specified by the user and must be respected;
     /// `false` if it was picked arbitrarily by Materialize.
     pub az_user_specified: bool,
+    /// Whether the replica may be scheduled on preemptible capacity. When
+    /// that capacity is reclaimed, the replica goes offline until the
+    /// orchestrator reschedules it.
+    #[serde(default)]
+    pub preemptible: bool,
+    /// Labels that the orchestrator node running the replica must carry, to
+    /// pin the replica to a specialized pool of nodes. Empty by default.
+    #[serde(default)]
+    pub node_selector: BTreeMap<String, String>,
+    /// Taints of the orchestrator nodes that the replica tolerates.
+    #[serde(default)]
+    pub tolerations: Vec<Toleration>,
+}
+
+/// The port hints for the services of managed replicas.
+///
+/// The orchestrator may assign different ports, so the ports are referenced by
+/// name rather than by number.
+#[derive(Clone, Debug)]
+pub struct ReplicaServicePorts {
+    /// The port hint for the storage controller endpoint.
+    pub storagectl: u16,
+    /// The port hint for the storage (Timely) endpoint.
+    pub storage: u16,
+    /// The port hint for the compute controller endpoint.
+    pub computectl: u16,
+    /// The port hint for the compute (Timely) endpoint.
+    pub compute: u16,
+    /// The port hint for the internal HTTP endpoint.
+    pub internal_http: u16,
+}
+
+impl Default for ReplicaServicePorts {
+    fn default() -> Self {
+        // To simplify the changes to tests, the storage port is chosen to be
+        // _after_ the compute ones.
+        // TODO(petrosagg): fix the numerical ordering here
+        Self {
+            storagectl: 2100,
+            storage: 2103,
+            computectl: 2101,
+            compute: 2102,
+            internal_http: 6878,
+        }
+    }
+}
+
+/// Configures how the controller retries creating or updating the services of
+/// managed replicas, which fails on transient orchestrator errors.
+///
+/// Attempts back off exponentially, starting at `initial_backoff` and never
+/// waiting longer than `clamp_backoff` between attempts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplicaServiceRetryConfig {
+    /// The time to wait after the first failed attempt.
+    pub initial_backoff: Duration,
+    /// The maximum time to wait between attempts.
+    pub clamp_backoff: Duration,
+    /// The maximum number of attempts before the error of the last one is
+    /// returned.
+    pub max_attempts: NonZeroUsize,
+}
+
+impl Default for ReplicaServiceRetryConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(125),
+            clamp_backoff: Duration::from_secs(2),
+            max_attempts: NonZeroUsize::new(5).expect("not zero"),
+        }
+    }
+}
+
+impl ReplicaServiceRetryConfig {
+    /// Returns the [`Retry`] policy described by this configuration.
+    fn retry(&self) -> Retry {
+        Retry::default()
+            .initial_backoff(self.initial_backoff)
+            .clamp_backoff(self.clamp_backoff)
+            .max_tries(self.max_attempts.get())
+    }
 }
 
 /// Configures logging for a cluster replica.
@@ -171,6 +256,92 @@ pub type ReplicaLogging = ComputeReplicaLogging;
 /// Identifier of a process within a replica.
 pub type ProcessId = u64;
 
+/// The state that the controller keeps about a replica.
+#[derive(Clone, Debug)]
+pub(crate) struct ReplicaState {
+    role: ClusterRole,
+    /// The configuration of the replica, as last provisioned.
+    config: ReplicaConfig,
+}
+
+/// The outcome of a drain issued through [`Controller::drain_instance`] or
+/// [`Controller::drain_replica`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DrainOutcome {
+    /// The drained cluster.
+    pub cluster_id: ClusterId,
+    /// The drained replica, or `None` if the whole cluster was drained.
+    pub replica_id: Option<ReplicaId>,
+    /// Whether the drain timed out, in which case the drained replicas still
+    /// have work in flight.
+    pub timed_out: bool,
+}
+
+/// A drain that the controller has issued but not yet finished.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PendingDrain {
+    /// The cluster being drained.
+    cluster_id: ClusterId,
+    /// The replica being drained, or `None` if the whole cluster is drained.
+    replica_id: Option<ReplicaId>,
+    /// The time at which the drain is reported as finished regardless.
+    deadline: tokio::time::Instant,
+}
+
+/// The drains that the controller has issued but not yet finished.
+#[derive(Debug, Default)]
+pub(crate) struct PendingDrains(Vec<PendingDrain>);
+
+impl PendingDrains {
+    /// Issues a drain, unless the same drain is already pending.
+    fn insert(&mut self, drain: PendingDrain) -> Result<(), anyhow::Error> {
+        let PendingDrain {
+            cluster_id,
+            replica_id,
+            ..
+        } = drain;
+        let pending = self
+            .0
+            .iter()
+            .any(|d| d.cluster_id == cluster_id && d.replica_id == replica_id);
+        if pending {
+            match replica_id {
+                Some(replica_id) => bail!("replica {cluster_id}.{replica_id} is already draining"),
+                None => bail!("cluster {cluster_id} is already draining"),
+            }
+        }
+        self.0.push(drain);
+        Ok(())
+    }
+
+    /// Returns the earliest deadline of the pending drains.
+    pub(crate) fn next_deadline(&self) -> Option<tokio::time::Instant> {
+        self.0.iter().map(|d| d.deadline).min()
+    }
+
+    /// Removes and returns a drain that is finished, along with whether it
+    /// timed out.
+    ///
+    /// A drain is finished once `drained` holds for it, or once its deadline
+    /// is not after `now`.
+    fn take_finished(
+        &mut self,
+        now: tokio::time::Instant,
+        drained: impl Fn(&PendingDrain) -> bool,
+    ) -> Option<(PendingDrain, bool)> {
+        let (index, timed_out) = self.0.iter().enumerate().find_map(|(i, d)| {
+            if drained(d) {
+                Some((i, false))
+            } else if d.deadline <= now {
+                Some((i, true))
+            } else {
+                None
+            }
+        })?;
+        Some((self.0.remove(index), timed_out))
+    }
+}
+
 /// An event describing a change in status of a cluster replica process.
 #[derive(Debug, Clone, Serialize)]
 pub struct ClusterEvent {
@@ -198,6 +369,79 @@ where
     ) -> Result<(), anyhow::Error> {
         self.storage.create_instance(id);
         self.compute.create_instance(id, config.arranged_logs)?;
+        self.replicas.entry(id).or_default();
+        Ok(())
+    }
+
+    /// Creates a cluster with the specified identifier and configuration,
+    /// together with its initial replicas.
+    ///
+    /// The operation is all-or-nothing. The arguments are validated before
+    /// anything is changed: an error is returned if the cluster exists
+    /// already, or if a replica identifier is specified more than once. Then
+    /// the services of the managed replicas are provisioned with the service
+    /// orchestrator, one after another. If provisioning a service fails, the
+    /// services provisioned so far are dropped again, as described in
+    /// [`provision_replicas`], and the error is returned without the cluster
+    /// having been created. Only once all services exist are the cluster and
+    /// its replicas created in the storage and compute controllers, which
+    /// cannot fail at that point.
+    pub async fn create_cluster_with_replicas(
+        &mut self,
+        id: ClusterId,
+        config: ClusterConfig,
+        replicas: Vec<(ReplicaId, ClusterRole, ReplicaConfig)>,
+    ) -> Result<(), anyhow::Error> {
+        if self.replicas.contains_key(&id) {
+            bail!("cluster {id} exists already");
+        }
+        let mut replica_ids = BTreeSet::new();
+        for (replica_id, _, _) in &replicas {
+            if !replica_ids.insert(*replica_id) {
+                bail!("replica {id}.{replica_id} specified more than once");
+            }
+        }
+
+        let managed = replicas
+            .iter()
+            .filter_map(|(replica_id, role, config)| match &config.location {
+                ReplicaLocation::Managed(m) => Some((*replica_id, (*role, m.clone()))),
+                ReplicaLocation::Unmanaged(_) => None,
+            })
+            .collect();
+        let requested_at = Instant::now();
+        let mut services: BTreeMap<_, _> =
+            provision_replicas(&*self.orchestrator, id, managed, |replica_id, (role, m)| {
+                self.provision_replica(id, replica_id, role, m)
+            })
+            .await?
+            .into_iter()
+            .collect();
+
+        self.create_cluster(id, config)
+            .expect("cluster does not exist");
+        for (replica_id, role, config) in replicas {
+            let (storage_location, compute_location) = match &config.location {
+                ReplicaLocation::Unmanaged(location) => unmanaged_replica_locations(location),
+                ReplicaLocation::Managed(m) => {
+                    let (service, metrics_task) =
+                        services.remove(&replica_id).expect("service provisioned");
+                    self.metrics_tasks.insert(replica_id, metrics_task);
+                    self.provisioning
+                        .provisioning(replica_id, m.size.clone(), requested_at);
+                    service_replica_locations(&*service, m.allocation.workers)
+                }
+            };
+            self.storage.connect_replica(id, storage_location);
+            self.active_compute()
+                .add_replica_to_instance(id, replica_id, compute_location, config.compute.clone())
+                .expect("replica identifiers are unique");
+            self.replicas
+                .entry(id)
+                .or_default()
+                .insert(replica_id, ReplicaState { role, config });
+        }
+
         Ok(())
     }
 
@@ -207,10 +451,97 @@ where
     ///
     /// Panics if the cluster still has replicas.
     pub fn drop_cluster(&mut self, id: ClusterId) {
+        self.replicas.remove(&id);
         self.storage.drop_instance(id);
         self.compute.drop_instance(id);
     }
 
+    /// Drains all replicas of the specified cluster, in preparation of
+    /// dropping the cluster.
+    ///
+    /// Each replica is drained as with [`Controller::drain_replica`]. The
+    /// controller reports the end of the drain with a
+    /// [`ControllerResponse::DrainFinished`] once all replicas are drained, or
+    /// after `drain_timeout` if they are not by then.
+    ///
+    /// The controller does not drop the cluster itself. The caller is
+    /// responsible for dropping it from the catalog, and then with
+    /// [`Controller::drop_cluster`], once the drain finishes.
+    pub fn drain_instance(
+        &mut self,
+        id: ClusterId,
+        drain_timeout: Duration,
+    ) -> Result<(), anyhow::Error> {
+        let Ok(instance) = self.compute.instance_ref(id) else {
+            bail!("cluster {id} does not exist");
+        };
+        let replica_ids: Vec<_> = instance.replica_ids().collect();
+        for replica_id in replica_ids {
+            self.compute.drain_replica(id, replica_id)?;
+        }
+        self.drains.insert(PendingDrain {
+            cluster_id: id,
+            replica_id: None,
+            deadline: tokio::time::Instant::now() + drain_timeout,
+        })
+    }
+
+    /// Removes a pending drain that is finished, along with whether it timed
+    /// out.
+    ///
+    /// Drains of clusters or replicas that no longer exist are finished, so
+    /// that their outcome reports the error.
+    pub(crate) fn take_finished_drain(&mut self) -> Option<(PendingDrain, bool)> {
+        let compute = &self.compute;
+        self.drains
+            .take_finished(tokio::time::Instant::now(), |drain| {
+                let Ok(instance) = compute.instance_ref(drain.cluster_id) else {
+                    return true;
+                };
+                match drain.replica_id {
+                    Some(replica_id) => {
+                        !instance.replica_ids().any(|id| id == replica_id)
+                            || instance.replica_is_drained(replica_id)
+                    }
+                    None => instance
+                        .replica_ids()
+                        .all(|id| instance.replica_is_drained(id)),
+                }
+            })
+    }
+
+    /// Reports a finished drain.
+    ///
+    /// The drained replicas, or the drained cluster, are not dropped here.
+    /// Their owner drops them once it learns about the finished drain.
+    pub(crate) fn finish_drain(
+        &mut self,
+        drain: PendingDrain,
+        timed_out: bool,
+    ) -> ControllerResponse<T> {
+        let PendingDrain {
+            cluster_id,
+            replica_id,
+            ..
+        } = drain;
+        if timed_out {
+            match replica_id {
+                Some(replica_id) => warn!(
+                    "timed out draining replica {cluster_id}.{replica_id}, \
+                     finishing the drain regardless"
+                ),
+                None => {
+                    warn!("timed out draining cluster {cluster_id}, finishing the drain regardless")
+                }
+            }
+        }
+        ControllerResponse::DrainFinished(DrainOutcome {
+            cluster_id,
+            replica_id,
+            timed_out,
+        })
+    }
+
     /// Creates a replica of the specified cluster with the specified identifier
     /// and configuration.
     ///
@@ -226,6 +557,10 @@ where
         let this = &*self;
         let replicas: Vec<_> = futures::stream::iter(replicas)
             .map(|(cluster_id, replica_id, role, config)| async move {
+                let state = ReplicaState {
+                    role,
+                    config: config.clone(),
+                };
                 match config.location {
                     // This branch doesn't do any async work, so there is a slight performance
                     // opportunity to serially process it, but it makes the code worse to read.
@@ -251,7 +586,7 @@ where
                         Ok::<_, anyhow::Error>((
                             cluster_id,
                             replica_id,
-                            config.compute,
+                            state,
                             storage_location,
                             compute_location,
                             None,
@@ -259,6 +594,8 @@ where
                     }
                     ReplicaLocation::Managed(m) => {
                         let workers = m.allocation.workers;
+                        let size = m.size.clone();
+                        let requested_at = Instant::now();
                         let (service, metrics_task_join_handle) = this
                             .provision_replica(cluster_id, replica_id, role, m)
                             .await?;
@@ -275,10 +612,10 @@ where
                         Ok((
                             cluster_id,
                             replica_id,
-                            config.compute,
+                            state,
                             storage_location,
                             compute_location,
-                            Some(metrics_task_join_handle),
+                            Some((metrics_task_join_handle, size, requested_at)),
                         ))
                     }
                 }
@@ -291,27 +628,189 @@ where
             .try_collect()
             .await?;
 
-        for (
-            cluster_id,
-            replica_id,
-            compute_config,
-            storage_location,
-            compute_location,
-            metrics_task_join_handle,
-        ) in replicas
+        for (cluster_id, replica_id, state, storage_location, compute_location, managed) in replicas
         {
-            if let Some(jh) = metrics_task_join_handle {
+            if let Some((jh, size, requested_at)) = managed {
                 self.metrics_tasks.insert(replica_id, jh);
+                self.provisioning
+                    .provisioning(replica_id, size, requested_at);
             }
             self.storage.connect_replica(cluster_id, storage_location);
             self.active_compute().add_replica_to_instance(
                 cluster_id,
                 replica_id,
                 compute_location,
-                compute_config,
+                state.config.compute.clone(),
+            )?;
+            self.replicas
+                .entry(cluster_id)
+                .or_default()
+                .insert(replica_id, state);
+        }
+
+        Ok(())
+    }
+
+    /// Chooses the availability zone in which to place a new managed replica
+    /// of the specified cluster.
+    ///
+    /// Picks the zone among `azs` that hosts the fewest of the cluster's
+    /// managed replicas. Ties are broken by the order of `azs`, so that the
+    /// choice is deterministic. Returns `None` if `azs` is empty.
+    ///
+    /// `pending_azs` lists the zones of replicas of the cluster that are
+    /// about to be created, but are not yet known to the controller, e.g.
+    /// those created along with the cluster itself. They count towards the
+    /// load of their zones like the cluster's existing replicas.
+    pub fn choose_replica_az(
+        &self,
+        cluster_id: ClusterId,
+        azs: &[String],
+        pending_azs: &[String],
+    ) -> Option<String> {
+        let replica_azs = self
+            .replicas
+            .get(&cluster_id)
+            .into_iter()
+            .flat_map(|replicas| replicas.values())
+            .filter_map(|replica| replica.config.location.availability_zone())
+            .chain(pending_azs.iter().map(String::as_str));
+        least_loaded_az(azs, replica_azs)
+    }
+
+    /// Lists the replicas of the specified cluster, along with the
+    /// configuration they were created with, or most recently resized to.
+    ///
+    /// Returns `None` if the cluster does not exist.
+    pub fn list_replicas(&self, cluster_id: ClusterId) -> Option<Vec<(ReplicaId, ReplicaConfig)>> {
+        let replicas = self.replicas.get(&cluster_id)?;
+        Some(
+            replicas
+                .iter()
+                .map(|(id, replica)| (*id, replica.config.clone()))
+                .collect(),
+        )
+    }
+
+    /// Resizes the specified managed replica of the specified cluster in
+    /// place, rather than by dropping and recreating it.
+    ///
+    /// The replica's service is updated under its existing name with the new
+    /// size and allocation. If only the CPU and memory limits change, the
+    /// orchestrator may apply the update to the running processes, where it
+    /// supports doing so. Changing the number of processes or workers
+    /// restarts the replica's processes with a new dataflow configuration,
+    /// which the controller reconnects to, so the replica rehydrates its
+    /// dataflows as a new replica would.
+    ///
+    /// The controller does not persist the new size. Callers must record it in
+    /// the catalog, or the replica reverts to its old size on restart.
+    ///
+    /// Returns an error if the cluster or the replica does not exist, or if the
+    /// replica is not managed.
+    pub async fn resize_replica(
+        &mut self,
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+        size: String,
+        allocation: ReplicaAllocation,
+    ) -> Result<(), anyhow::Error> {
+        let Some(replica) = self
+            .replicas
+            .get(&cluster_id)
+            .and_then(|replicas| replicas.get(&replica_id))
+        else {
+            bail!("replica {cluster_id}.{replica_id} does not exist");
+        };
+        let mut replica = replica.clone();
+        let role = replica.role;
+        let ReplicaLocation::Managed(location) = &mut replica.config.location else {
+            bail!("replica {cluster_id}.{replica_id} is not managed");
+        };
+        let reconnect = resize_replica_service(location, size, allocation, |location| {
+            self.ensure_replica_service(cluster_id, replica_id, role, location)
+        })
+        .await?;
+        if let Some((storage_location, compute_location)) = reconnect {
+            self.storage.connect_replica(cluster_id, storage_location);
+            let mut compute = self.active_compute();
+            compute.drop_replica(cluster_id, replica_id)?;
+            compute.add_replica_to_instance(
+                cluster_id,
+                replica_id,
+                compute_location,
+                replica.config.compute.clone(),
             )?;
         }
 
+        self.replicas
+            .entry(cluster_id)
+            .or_default()
+            .insert(replica_id, replica);
+        Ok(())
+    }
+
+    /// Re-provisions the service of the specified replica of the specified
+    /// cluster with its recorded configuration.
+    ///
+    /// This is used to bring back preemptible replicas whose capacity was
+    /// reclaimed. Ensuring the service again lets the orchestrator reschedule
+    /// processes that it has not rescheduled by itself. The replica's
+    /// addresses do not change, so the controller's clients reconnect to the
+    /// rescheduled processes.
+    ///
+    /// The returned future performs the re-provisioning. It does not borrow the
+    /// controller, so callers can drive it from a separate task.
+    ///
+    /// Returns an error if the cluster or the replica does not exist, or if the
+    /// replica is not managed.
+    pub fn reprovision_replica(
+        &self,
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+    ) -> Result<impl Future<Output = Result<(), anyhow::Error>> + Send + 'static, anyhow::Error>
+    {
+        let Some(replica) = self
+            .replicas
+            .get(&cluster_id)
+            .and_then(|replicas| replicas.get(&replica_id))
+        else {
+            bail!("replica {cluster_id}.{replica_id} does not exist");
+        };
+        let ReplicaLocation::Managed(location) = &replica.config.location else {
+            bail!("replica {cluster_id}.{replica_id} is not managed");
+        };
+        let (role, location) = (replica.role, location.clone());
+        let ensure = self.ensure_replica_service(cluster_id, replica_id, role, location);
+        Ok(async move {
+            ensure.await?;
+            Ok(())
+        })
+    }
+
+    /// Updates the logging configuration of the specified replica of the
+    /// specified cluster.
+    ///
+    /// The compute half of the replica is rehydrated with the new logging
+    /// configuration, if it changed, and the replica's recorded configuration
+    /// is updated to match.
+    pub fn update_replica_logging(
+        &mut self,
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+        logging: ReplicaLogging,
+    ) -> Result<(), anyhow::Error> {
+        let Some(replica) = self
+            .replicas
+            .get_mut(&cluster_id)
+            .and_then(|replicas| replicas.get_mut(&replica_id))
+        else {
+            bail!("replica {cluster_id}.{replica_id} does not exist");
+        };
+        self.compute
+            .activate(&mut *self.storage)
+            .update_replica_logging(cluster_id, replica_id, logging.clone())?;
+        replica.config.compute.logging = logging;
         Ok(())
     }
 
@@ -327,6 +826,10 @@ where
         // provisioned.
         self.deprovision_replica(cluster_id, replica_id).await?;
         self.metrics_tasks.remove(&replica_id);
+        self.provisioning.forget(replica_id);
+        if let Some(replicas) = self.replicas.get_mut(&cluster_id) {
+            replicas.remove(&replica_id);
+        }
 
         // Storage does not support active-active replication and so does not
         // have an API for dropping replicas.
@@ -334,6 +837,34 @@ where
         Ok(())
     }
 
+    /// Drains the specified replica of the specified cluster, in preparation
+    /// of dropping it.
+    ///
+    /// Draining sends the replica a `DrainReplica` command and waits for it to
+    /// respond to the peeks it has received. New peeks are no longer routed to
+    /// the replica, and peeks that a sibling replica serves in the meantime no
+    /// longer wait for it. Subscribes that target the replica are not waited
+    /// for, as they only finish when they are dropped. The controller reports
+    /// the end of the drain with a [`ControllerResponse::DrainFinished`], or
+    /// after `drain_timeout` if the drain does not complete by then.
+    ///
+    /// The controller does not drop the replica itself. The caller is
+    /// responsible for dropping it from the catalog, and then with
+    /// [`Controller::drop_replica`], once the drain finishes.
+    pub fn drain_replica(
+        &mut self,
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+        drain_timeout: Duration,
+    ) -> Result<(), anyhow::Error> {
+        self.compute.drain_replica(cluster_id, replica_id)?;
+        self.drains.insert(PendingDrain {
+            cluster_id,
+            replica_id: Some(replica_id),
+            deadline: tokio::time::Instant::now() + drain_timeout,
+        })
+    }
+
     /// Remove orphaned replicas.
     pub async fn remove_orphaned_replicas(
         &mut self,
@@ -350,16 +881,7 @@ where
             .collect::<Result<_, _>>()?;
 
         for (cluster_id, replica_id) in actual {
-            if replica_id >= next_replica_id {
-                // Found a replica in kubernetes with a higher replica ID than
-                // what we are aware of. This must have been created by an
-                // environmentd with higher epoch number.
-                halt!(
-                    "found replica id ({}) in orchestrator >= next id ({})",
-                    replica_id,
-                    next_replica_id
-                );
-            }
+            fence_replica_id(replica_id, next_replica_id);
 
             if !desired.contains(&replica_id) {
                 self.deprovision_replica(cluster_id, replica_id).await?;
@@ -369,6 +891,88 @@ where
         Ok(())
     }
 
+    /// Compares the managed replicas known to the controller with the replica
+    /// services that exist in the orchestrator, and repairs what it can.
+    ///
+    /// Unlike [`Controller::remove_orphaned_replicas`], which runs once at
+    /// startup, this method is meant to be called periodically, to detect
+    /// drift introduced later on, e.g., by out-of-band changes to the
+    /// orchestrator.
+    ///
+    /// Orphaned services, which the controller does not know about, are
+    /// deprovisioned. Like [`Controller::remove_orphaned_replicas`], this
+    /// method halts if it finds the service of a replica with an ID at or
+    /// above `next_replica_id`, which the caller reads from the catalog. Such
+    /// a service was created by an `environmentd` with a higher epoch, which
+    /// has taken over the orchestrator. Missing services are only logged, as
+    /// the controller does not retain the configuration required to provision
+    /// them again; callers can recreate the affected replicas based on the
+    /// returned discrepancies.
+    pub async fn reconcile_services(
+        &mut self,
+        next_replica_id: ReplicaId,
+    ) -> Result<ServiceDiscrepancies, anyhow::Error> {
+        let expected: BTreeSet<_> = self.metrics_tasks.keys().copied().collect();
+        reconcile_replica_services(&*self.orchestrator, &expected, next_replica_id).await
+    }
+
+    /// Fetches the most recent log lines of the specified process of a
+    /// managed replica from the service orchestrator.
+    ///
+    /// Returns at most `tail` lines, ordered from oldest to newest. If
+    /// `previous` is true, returns the logs of the previous instance of the
+    /// process instead, which is where the logs of a crashed process end up
+    /// once the orchestrator has restarted it. Returns an error if the replica
+    /// does not exist or is not managed, or if the orchestrator does not
+    /// support fetching logs.
+    pub async fn replica_logs(
+        &self,
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+        process_id: ProcessId,
+        tail: usize,
+        previous: bool,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let Some(replica) = self
+            .replicas
+            .get(&cluster_id)
+            .and_then(|replicas| replicas.get(&replica_id))
+        else {
+            bail!("replica {cluster_id}.{replica_id} does not exist");
+        };
+        if !matches!(replica.config.location, ReplicaLocation::Managed(_)) {
+            bail!("replica {cluster_id}.{replica_id} is not managed by the orchestrator");
+        }
+        fetch_replica_logs(
+            &*self.orchestrator,
+            cluster_id,
+            replica_id,
+            process_id,
+            tail,
+            previous,
+        )
+        .await
+    }
+
+    /// Fetches the current resource usage of the processes of the specified
+    /// replica from the service orchestrator.
+    ///
+    /// Returns `None` if the replica is not managed, as the orchestrator does
+    /// not know about it. Otherwise, returns one entry per process. Metrics
+    /// that the orchestrator could not collect for a process are `None`.
+    pub async fn replica_metrics(
+        &self,
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+    ) -> Result<Option<Vec<ServiceProcessMetrics>>, anyhow::Error> {
+        if !self.metrics_tasks.contains_key(&replica_id) {
+            return Ok(None);
+        }
+        fetch_replica_metrics(&*self.orchestrator, cluster_id, replica_id)
+            .await
+            .map(Some)
+    }
+
     pub fn events_stream(&self) -> BoxStream<'static, ClusterEvent> {
         fn translate_event(event: ServiceEvent) -> Result<ClusterEvent, anyhow::Error> {
             let (cluster_id, replica_id) = parse_replica_service_name(&event.service_id)?;
@@ -407,95 +1011,8 @@ where
         location: ManagedReplicaLocation,
     ) -> Result<(Box<dyn Service>, AbortOnDropHandle<()>), anyhow::Error> {
         let service_name = generate_replica_service_name(cluster_id, replica_id);
-        let role_label = match role {
-            ClusterRole::SystemCritical => "system-critical",
-            ClusterRole::System => "system",
-            ClusterRole::User => "user",
-        };
         let service = self
-            .orchestrator
-            .ensure_service(
-                &service_name,
-                ServiceConfig {
-                    image: self.clusterd_image.clone(),
-                    init_container_image: self.init_container_image.clone(),
-                    args: &|assigned| {
-                        vec![
-                            format!(
-                                "--storage-controller-listen-addr={}",
-                                assigned["storagectl"]
-                            ),
-                            format!(
-                                "--compute-controller-listen-addr={}",
-                                assigned["computectl"]
-                            ),
-                            format!("--internal-http-listen-addr={}", assigned["internal-http"]),
-                            format!("--opentelemetry-resource=cluster_id={}", cluster_id),
-                            format!("--opentelemetry-resource=replica_id={}", replica_id),
-                        ]
-                    },
-                    ports: vec![
-                        ServicePort {
-                            name: "storagectl".into(),
-                            port_hint: 2100,
-                        },
-                        // To simplify the changes to tests, the port
-                        // chosen here is _after_ the compute ones.
-                        // TODO(petrosagg): fix the numerical ordering here
-                        ServicePort {
-                            name: "storage".into(),
-                            port_hint: 2103,
-                        },
-                        ServicePort {
-                            name: "computectl".into(),
-                            port_hint: 2101,
-                        },
-                        ServicePort {
-                            name: "compute".into(),
-                            port_hint: 2102,
-                        },
-                        ServicePort {
-                            name: "internal-http".into(),
-                            port_hint: 6878,
-                        },
-                    ],
-                    cpu_limit: location.allocation.cpu_limit,
-                    memory_limit: location.allocation.memory_limit,
-                    scale: location.allocation.scale,
-                    labels: BTreeMap::from([
-                        ("replica-id".into(), replica_id.to_string()),
-                        ("cluster-id".into(), cluster_id.to_string()),
-                        ("type".into(), "cluster".into()),
-                        ("replica-role".into(), role_label.into()),
-                        ("scale".into(), location.allocation.scale.to_string()),
-                        ("workers".into(), location.allocation.workers.to_string()),
-                        ("size".into(), location.size.to_string()),
-                    ]),
-                    availability_zone: Some(location.availability_zone),
-                    // This constrains the orchestrator (for those orchestrators that support
-                    // anti-affinity, today just k8s) to never schedule pods for different replicas
-                    // of the same cluster on the same node. Pods from the _same_ replica are fine;
-                    // pods from different clusters are also fine.
-                    //
-                    // The point is that if pods of two replicas are on the same node, that node
-                    // going down would kill both replicas, and so the replication factor of the
-                    // cluster in question is illusory.
-                    anti_affinity: Some(vec![
-                        LabelSelector {
-                            label_name: "cluster-id".to_string(),
-                            logic: LabelSelectionLogic::Eq {
-                                value: cluster_id.to_string(),
-                            },
-                        },
-                        LabelSelector {
-                            label_name: "replica-id".into(),
-                            logic: LabelSelectionLogic::NotEq {
-                                value: replica_id.to_string(),
-                            },
-                        },
-                    ]),
-                },
-            )
+            .ensure_replica_service(cluster_id, replica_id, role, location)
             .await?;
 
         let metrics_task = mz_ore::task::spawn(|| format!("replica-metrics-{replica_id}"), {
@@ -530,6 +1047,47 @@ where
         Ok((service, metrics_task.abort_on_drop()))
     }
 
+    /// Creates or updates the service of a replica with the service
+    /// orchestrator.
+    ///
+    /// Failed attempts are retried as configured by the controller's
+    /// [`ReplicaServiceRetryConfig`]. Retrying is safe, as ensuring a service
+    /// creates or updates the service of the given name.
+    ///
+    /// The returned future does not borrow the controller.
+    fn ensure_replica_service(
+        &self,
+        cluster_id: ClusterId,
+        replica_id: ReplicaId,
+        role: ClusterRole,
+        location: ManagedReplicaLocation,
+    ) -> impl Future<Output = Result<Box<dyn Service>, anyhow::Error>> + Send + 'static {
+        let retry = self.replica_service_retry.retry();
+        let clusterd_image = self.clusterd_image.clone();
+        let init_container_image = self.init_container_image.clone();
+        let ports = self.replica_service_ports.clone();
+        let orchestrator = Arc::clone(&self.orchestrator);
+        async move {
+            let args = |assigned: &BTreeMap<String, String>| {
+                replica_service_args(cluster_id, replica_id, assigned)
+            };
+            let config = || {
+                replica_service_config(
+                    clusterd_image.clone(),
+                    init_container_image.clone(),
+                    &ports,
+                    cluster_id,
+                    replica_id,
+                    role,
+                    location.clone(),
+                    &args,
+                )
+            };
+            ensure_replica_service_with_retry(&*orchestrator, retry, cluster_id, replica_id, config)
+                .await
+        }
+    }
+
     /// Deprovisions a replica with the service orchestrator.
     async fn deprovision_replica(
         &mut self,
@@ -541,11 +1099,400 @@ where
     }
 }
 
+/// Ensures the service of a managed replica with `orchestrator`, retrying
+/// failed attempts according to `retry`.
+///
+/// Each attempt ensures the service with a fresh configuration from `config`.
+/// Returns the error of the final attempt if all attempts fail.
+async fn ensure_replica_service_with_retry<'a, F>(
+    orchestrator: &dyn NamespacedOrchestrator,
+    retry: Retry,
+    cluster_id: ClusterId,
+    replica_id: ReplicaId,
+    config: F,
+) -> Result<Box<dyn Service>, anyhow::Error>
+where
+    F: Fn() -> ServiceConfig<'a>,
+{
+    let service_name = generate_replica_service_name(cluster_id, replica_id);
+    retry
+        .retry_async(|state| {
+            let config = config();
+            let service_name = &service_name;
+            async move {
+                let result = orchestrator.ensure_service(service_name, config).await;
+                if let Err(e) = &result {
+                    // The error of the final attempt is returned to the caller.
+                    if let Some(backoff) = state.next_backoff {
+                        warn!(
+                            "failed to ensure service for replica \
+                             {cluster_id}.{replica_id}, retrying in {backoff:?}: {e:#}"
+                        );
+                    }
+                }
+                result
+            }
+        })
+        .await
+}
+
+/// Halts if `replica_id` is not below `next_replica_id`.
+///
+/// A replica service in the orchestrator with a higher replica ID than the
+/// controller is aware of must have been created by an `environmentd` with a
+/// higher epoch number, which fences out this one.
+fn fence_replica_id(replica_id: ReplicaId, next_replica_id: ReplicaId) {
+    if replica_id >= next_replica_id {
+        halt!(
+            "found replica id ({}) in orchestrator >= next id ({})",
+            replica_id,
+            next_replica_id
+        );
+    }
+}
+
+/// Compares the `expected` managed replicas with the replica services in
+/// `orchestrator`, and deprovisions the orphaned services.
+///
+/// Halts if any replica service has an ID at or above `next_replica_id`,
+/// before deprovisioning anything.
+async fn reconcile_replica_services(
+    orchestrator: &dyn NamespacedOrchestrator,
+    expected: &BTreeSet<ReplicaId>,
+    next_replica_id: ReplicaId,
+) -> Result<ServiceDiscrepancies, anyhow::Error> {
+    let services = orchestrator.list_services().await?;
+    let discrepancies = ServiceDiscrepancies::new(expected, &services)?;
+    for (_, replica_id) in &discrepancies.orphaned {
+        fence_replica_id(*replica_id, next_replica_id);
+    }
+
+    for replica_id in &discrepancies.missing {
+        warn!("service for replica {replica_id} is missing from the orchestrator");
+    }
+    for (cluster_id, replica_id) in &discrepancies.orphaned {
+        warn!("deprovisioning orphaned service for replica {cluster_id}.{replica_id}");
+        let service_name = generate_replica_service_name(*cluster_id, *replica_id);
+        orchestrator.drop_service(&service_name).await?;
+    }
+
+    Ok(discrepancies)
+}
+
+/// Discrepancies between the managed replicas known to the controller and the
+/// replica services that exist in the orchestrator.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ServiceDiscrepancies {
+    /// Managed replicas whose service does not exist in the orchestrator.
+    pub missing: BTreeSet<ReplicaId>,
+    /// Replica services in the orchestrator that belong to no known replica.
+    pub orphaned: BTreeSet<(ClusterId, ReplicaId)>,
+}
+
+impl ServiceDiscrepancies {
+    /// Determines the discrepancies between the `expected` replicas and the
+    /// names of the `services` that exist in the orchestrator.
+    fn new(expected: &BTreeSet<ReplicaId>, services: &[String]) -> Result<Self, anyhow::Error> {
+        let actual: BTreeMap<_, _> = services
+            .iter()
+            .map(|s| parse_replica_service_name(s).map(|(cluster_id, id)| (id, cluster_id)))
+            .collect::<Result<_, _>>()?;
+
+        let missing = expected
+            .iter()
+            .filter(|id| !actual.contains_key(id))
+            .copied()
+            .collect();
+        let orphaned = actual
+            .into_iter()
+            .filter(|(id, _)| !expected.contains(id))
+            .map(|(id, cluster_id)| (cluster_id, id))
+            .collect();
+
+        Ok(Self { missing, orphaned })
+    }
+
+    /// Reports whether the controller and the orchestrator agree.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.orphaned.is_empty()
+    }
+}
+
+/// Generates the command-line arguments of the processes of a replica, given the
+/// listen addresses assigned to their named ports.
+fn replica_service_args(
+    cluster_id: ClusterId,
+    replica_id: ReplicaId,
+    assigned: &BTreeMap<String, String>,
+) -> Vec<String> {
+    vec![
+        format!(
+            "--storage-controller-listen-addr={}",
+            assigned["storagectl"]
+        ),
+        format!(
+            "--compute-controller-listen-addr={}",
+            assigned["computectl"]
+        ),
+        format!("--internal-http-listen-addr={}", assigned["internal-http"]),
+        format!("--opentelemetry-resource=cluster_id={}", cluster_id),
+        format!("--opentelemetry-resource=replica_id={}", replica_id),
+    ]
+}
+
+/// Describes the orchestrator service that runs a managed replica.
+fn replica_service_config<'a>(
+    image: String,
+    init_container_image: Option<String>,
+    ports: &ReplicaServicePorts,
+    cluster_id: ClusterId,
+    replica_id: ReplicaId,
+    role: ClusterRole,
+    location: ManagedReplicaLocation,
+    args: &'a (dyn Fn(&BTreeMap<String, String>) -> Vec<String> + Send + Sync),
+) -> ServiceConfig<'a> {
+    let role_label = match role {
+        ClusterRole::SystemCritical => "system-critical",
+        ClusterRole::System => "system",
+        ClusterRole::User => "user",
+    };
+    ServiceConfig {
+        image,
+        init_container_image,
+        args,
+        ports: vec![
+            ServicePort {
+                name: "storagectl".into(),
+                port_hint: ports.storagectl,
+            },
+            ServicePort {
+                name: "storage".into(),
+                port_hint: ports.storage,
+            },
+            ServicePort {
+                name: "computectl".into(),
+                port_hint: ports.computectl,
+            },
+            ServicePort {
+                name: "compute".into(),
+                port_hint: ports.compute,
+            },
+            ServicePort {
+                name: "internal-http".into(),
+                port_hint: ports.internal_http,
+            },
+        ],
+        cpu_limit: location.allocation.cpu_limit,
+        memory_limit: location.allocation.memory_limit,
+        scale: location.allocation.scale,
+        labels: BTreeMap::from([
+            ("replica-id".into(), replica_id.to_string()),
+            ("cluster-id".into(), cluster_id.to_string()),
+            ("type".into(), "cluster".into()),
+            ("replica-role".into(), role_label.into()),
+            ("scale".into(), location.allocation.scale.to_string()),
+            ("workers".into(), location.allocation.workers.to_string()),
+            ("size".into(), location.size.to_string()),
+        ]),
+        availability_zone: Some(location.availability_zone),
+        preemptible: location.preemptible,
+        node_selector: location.node_selector,
+        tolerations: location.tolerations,
+        // This constrains the orchestrator (for those orchestrators that support
+        // anti-affinity, today just k8s) to never schedule pods for different replicas
+        // of the same cluster on the same node. Pods from the _same_ replica are fine;
+        // pods from different clusters are also fine.
+        //
+        // The point is that if pods of two replicas are on the same node, that node
+        // going down would kill both replicas, and so the replication factor of the
+        // cluster in question is illusory.
+        anti_affinity: Some(vec![
+            LabelSelector {
+                label_name: "cluster-id".to_string(),
+                logic: LabelSelectionLogic::Eq {
+                    value: cluster_id.to_string(),
+                },
+            },
+            LabelSelector {
+                label_name: "replica-id".into(),
+                logic: LabelSelectionLogic::NotEq {
+                    value: replica_id.to_string(),
+                },
+            },
+        ]),
+    }
+}
+
+/// Returns the availability zone among `azs` that hosts the fewest of
+/// `replica_azs`, preferring zones that come earlier in `azs` on ties.
+fn least_loaded_az<'a>(
+    azs: &[String],
+    replica_azs: impl Iterator<Item = &'a str>,
+) -> Option<String> {
+    let mut counts: BTreeMap<&str, usize> = azs.iter().map(|az| (az.as_str(), 0)).collect();
+    for az in replica_azs {
+        // Replicas in zones that are no longer configured do not count.
+        if let Some(count) = counts.get_mut(az) {
+            *count += 1;
+        }
+    }
+    // `min_by_key` returns the first of several minimal elements.
+    azs.iter().min_by_key(|az| counts[az.as_str()]).cloned()
+}
+
+/// Provisions the services of the specified managed replicas of a cluster
+/// one after another, using `provision`.
+///
+/// If provisioning the service of a replica fails, the services of the
+/// replicas provisioned before it are dropped again, in the order they were
+/// provisioned, and the error is returned. The remaining replicas are not
+/// provisioned. Failures to drop a service are logged rather than returned, so
+/// that the caller sees the error that caused the rollback. Services left
+/// behind that way are not known to the controller and are deprovisioned as
+/// orphans by [`Controller::reconcile_services`].
+async fn provision_replicas<R, S, F, Fut>(
+    orchestrator: &dyn NamespacedOrchestrator,
+    cluster_id: ClusterId,
+    replicas: Vec<(ReplicaId, R)>,
+    mut provision: F,
+) -> Result<Vec<(ReplicaId, S)>, anyhow::Error>
+where
+    F: FnMut(ReplicaId, R) -> Fut,
+    Fut: Future<Output = Result<S, anyhow::Error>>,
+{
+    let mut provisioned = Vec::new();
+    for (replica_id, replica) in replicas {
+        match provision(replica_id, replica).await {
+            Ok(service) => provisioned.push((replica_id, service)),
+            Err(e) => {
+                for (replica_id, _) in provisioned {
+                    let service_name = generate_replica_service_name(cluster_id, replica_id);
+                    if let Err(e) = orchestrator.drop_service(&service_name).await {
+                        warn!(
+                            "failed to drop service for replica {cluster_id}.{replica_id} \
+                             while rolling back: {e:#}"
+                        );
+                    }
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok(provisioned)
+}
+
+/// Returns the storage and compute locations of an unmanaged replica.
+fn unmanaged_replica_locations(
+    location: &UnmanagedReplicaLocation,
+) -> (ClusterReplicaLocation, ClusterReplicaLocation) {
+    let storage_location = ClusterReplicaLocation {
+        ctl_addrs: location.storagectl_addrs.clone(),
+        dataflow_addrs: location.storage_addrs.clone(),
+        // Storage and compute on the same replica have linked sizes.
+        workers: location.workers,
+    };
+    let compute_location = ClusterReplicaLocation {
+        ctl_addrs: location.computectl_addrs.clone(),
+        dataflow_addrs: location.compute_addrs.clone(),
+        workers: location.workers,
+    };
+    (storage_location, compute_location)
+}
+
+/// Updates the location of a managed replica to the specified size and
+/// allocation.
+///
+/// Returns whether the replica's processes must be restarted, which is the
+/// case if the number of processes or workers changes.
+fn resize_replica_location(
+    location: &mut ManagedReplicaLocation,
+    size: String,
+    allocation: ReplicaAllocation,
+) -> bool {
+    let restart = location.allocation.scale != allocation.scale
+        || location.allocation.workers != allocation.workers;
+    location.size = size;
+    location.allocation = allocation;
+    restart
+}
+
+/// Resizes the service of a managed replica, using `ensure` to ensure the
+/// service with the updated location.
+///
+/// Returns the storage and compute locations that the controller must
+/// reconnect to if the replica's processes restart, or `None` if the running
+/// processes are kept.
+async fn resize_replica_service<F, Fut>(
+    location: &mut ManagedReplicaLocation,
+    size: String,
+    allocation: ReplicaAllocation,
+    ensure: F,
+) -> Result<Option<(ClusterReplicaLocation, ClusterReplicaLocation)>, anyhow::Error>
+where
+    F: FnOnce(ManagedReplicaLocation) -> Fut,
+    Fut: Future<Output = Result<Box<dyn Service>, anyhow::Error>>,
+{
+    let restart = resize_replica_location(location, size, allocation);
+    let service = ensure(location.clone()).await?;
+    let workers = location.allocation.workers;
+    Ok(restart.then(|| service_replica_locations(&*service, workers)))
+}
+
+/// Returns the storage and compute locations of a managed replica, as served
+/// by its service.
+fn service_replica_locations(
+    service: &dyn Service,
+    workers: usize,
+) -> (ClusterReplicaLocation, ClusterReplicaLocation) {
+    let storage_location = ClusterReplicaLocation {
+        ctl_addrs: service.addresses("storagectl"),
+        dataflow_addrs: service.addresses("storage"),
+        workers,
+    };
+    let compute_location = ClusterReplicaLocation {
+        ctl_addrs: service.addresses("computectl"),
+        dataflow_addrs: service.addresses("compute"),
+        workers,
+    };
+    (storage_location, compute_location)
+}
+
 /// Deterministically generates replica names based on inputs.
 fn generate_replica_service_name(cluster_id: ClusterId, replica_id: ReplicaId) -> String {
     format!("{cluster_id}-replica-{replica_id}")
 }
 
+/// Fetches the resource usage of the processes of a replica from `orchestrator`.
+async fn fetch_replica_metrics(
+    orchestrator: &dyn NamespacedOrchestrator,
+    cluster_id: ClusterId,
+    replica_id: ReplicaId,
+) -> Result<Vec<ServiceProcessMetrics>, anyhow::Error> {
+    let service_name = generate_replica_service_name(cluster_id, replica_id);
+    orchestrator
+        .fetch_service_metrics(&service_name)
+        .await
+        .with_context(|| format!("fetching metrics for replica {cluster_id}.{replica_id}"))
+}
+
+/// Fetches the most recent log lines of a replica process from `orchestrator`.
+async fn fetch_replica_logs(
+    orchestrator: &dyn NamespacedOrchestrator,
+    cluster_id: ClusterId,
+    replica_id: ReplicaId,
+    process_id: ProcessId,
+    tail: usize,
+    previous: bool,
+) -> Result<Vec<String>, anyhow::Error> {
+    let service_name = generate_replica_service_name(cluster_id, replica_id);
+    orchestrator
+        .fetch_service_logs(&service_name, process_id, tail, previous)
+        .await
+        .with_context(|| {
+            format!("fetching logs for process {process_id} of replica {cluster_id}.{replica_id}")
+        })
+}
+
 /// Parses a name generated by `generate_replica_service_name`, to extract the
 /// replica's cluster ID and replica ID.
 fn parse_replica_service_name(
@@ -562,3 +1509,642 @@ fn parse_replica_service_name(
     let replica_id = caps.get(2).unwrap().as_str().parse().unwrap();
     Ok((cluster_id, replica_id))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    #[test]
+    fn service_discrepancies() {
+        let cluster_id = "u1".parse().unwrap();
+        let services: Vec<_> = [1, 2, 4]
+            .into_iter()
+            .map(|id| generate_replica_service_name(cluster_id, id))
+            .collect();
+
+        let expected = BTreeSet::from([1, 2, 4]);
+        let discrepancies = ServiceDiscrepancies::new(&expected, &services).unwrap();
+        assert!(discrepancies.is_empty());
+
+        // Replica 3's service was deleted out of band, and replica 4's service
+        // was left behind after the replica was dropped.
+        let expected = BTreeSet::from([1, 2, 3]);
+        let discrepancies = ServiceDiscrepancies::new(&expected, &services).unwrap();
+        assert_eq!(
+            discrepancies,
+            ServiceDiscrepancies {
+                missing: BTreeSet::from([3]),
+                orphaned: BTreeSet::from([(cluster_id, 4)]),
+            }
+        );
+
+        let services = vec!["not-a-replica".to_string()];
+        assert!(ServiceDiscrepancies::new(&expected, &services).is_err());
+    }
+
+    #[tokio::test]
+    async fn reconcile_replica_services_deprovisions_orphans() {
+        let cluster_id = "u1".parse().unwrap();
+        let orchestrator = MockOrchestrator {
+            services: Mutex::new(
+                [1, 2, 4]
+                    .into_iter()
+                    .map(|id| generate_replica_service_name(cluster_id, id))
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+
+        // Replica 4's service was left behind after the replica was dropped,
+        // and is deprovisioned. Replica 3's service is only reported.
+        let expected = BTreeSet::from([1, 2, 3]);
+        let discrepancies = reconcile_replica_services(&orchestrator, &expected, 5)
+            .await
+            .unwrap();
+        assert_eq!(
+            discrepancies,
+            ServiceDiscrepancies {
+                missing: BTreeSet::from([3]),
+                orphaned: BTreeSet::from([(cluster_id, 4)]),
+            }
+        );
+        assert_eq!(
+            *orchestrator.services.lock().unwrap(),
+            BTreeSet::from(["u1-replica-1".to_string(), "u1-replica-2".to_string()])
+        );
+
+        // Once the orphan is gone, there is nothing left to reconcile.
+        let expected = BTreeSet::from([1, 2]);
+        let discrepancies = reconcile_replica_services(&orchestrator, &expected, 5)
+            .await
+            .unwrap();
+        assert!(discrepancies.is_empty());
+    }
+
+    #[test]
+    fn least_loaded_az() {
+        let azs = vec!["az2".to_string(), "az1".to_string(), "az3".to_string()];
+
+        // Ties are broken by the order of the configured zones.
+        assert_eq!(
+            super::least_loaded_az(&azs, [].into_iter()),
+            Some("az2".into())
+        );
+        assert_eq!(
+            super::least_loaded_az(&azs, ["az2"].into_iter()),
+            Some("az1".into())
+        );
+        assert_eq!(
+            super::least_loaded_az(&azs, ["az2", "az1", "az1"].into_iter()),
+            Some("az3".into())
+        );
+        // Zones that are not configured are ignored.
+        assert_eq!(
+            super::least_loaded_az(&azs, ["az4", "az2", "az1", "az3", "az4"].into_iter()),
+            Some("az2".into())
+        );
+        assert_eq!(super::least_loaded_az(&[], ["az1"].into_iter()), None);
+    }
+
+    #[test]
+    fn drain_two_replica_cluster() {
+        let cluster_id = ClusterId::User(1);
+        let now = tokio::time::Instant::now();
+        let drain = |replica_id, timeout| PendingDrain {
+            cluster_id,
+            replica_id,
+            deadline: now + Duration::from_secs(timeout),
+        };
+
+        // Replica 1 is drained on its own, replica 2 along with the cluster.
+        let mut drains = PendingDrains::default();
+        drains.insert(drain(Some(1), 10)).unwrap();
+        drains.insert(drain(None, 20)).unwrap();
+        assert!(drains.insert(drain(Some(1), 30)).is_err());
+        assert_eq!(drains.next_deadline(), Some(now + Duration::from_secs(10)));
+
+        // Nothing finishes while the replicas are busy and no deadline has
+        // passed.
+        assert_eq!(drains.take_finished(now, |_| false), None);
+
+        // Replica 1 finishes its drain before its deadline.
+        assert_eq!(
+            drains.take_finished(now, |d| d.replica_id == Some(1)),
+            Some((drain(Some(1), 10), false)),
+        );
+        assert_eq!(drains.next_deadline(), Some(now + Duration::from_secs(20)));
+
+        // The cluster drain times out.
+        let later = now + Duration::from_secs(20);
+        assert_eq!(
+            drains.take_finished(later, |_| false),
+            Some((drain(None, 20), true)),
+        );
+        assert_eq!(drains.next_deadline(), None);
+        assert_eq!(drains.take_finished(later, |_| true), None);
+    }
+
+    /// An orchestrator that serves canned metrics and log lines for a single
+    /// service. `previous_logs` holds the log lines of the previous instance of
+    /// each process.
+    ///
+    /// The orchestrator also keeps track of the services that are ensured and
+    /// dropped, which it lists, of the scale each service was last ensured
+    /// with, and of the number of attempts to ensure each service. Ensuring the
+    /// service named `failing_service` fails, or, if `failing_attempts` is set,
+    /// only the first that many attempts to ensure it fail.
+    #[derive(Debug, Default)]
+    struct MockOrchestrator {
+        service_id: String,
+        metrics: Vec<ServiceProcessMetrics>,
+        logs: Vec<Vec<String>>,
+        previous_logs: Vec<Vec<String>>,
+        failing_service: Option<String>,
+        failing_attempts: Option<usize>,
+        services: Mutex<BTreeSet<String>>,
+        scales: Mutex<BTreeMap<String, u16>>,
+        attempts: Mutex<BTreeMap<String, usize>>,
+    }
+
+    #[derive(Debug)]
+    struct MockService;
+
+    impl Service for MockService {
+        fn addresses(&self, _port: &str) -> Vec<String> {
+            vec![]
+        }
+    }
+
+    #[async_trait]
+    impl NamespacedOrchestrator for MockOrchestrator {
+        async fn ensure_service(
+            &self,
+            id: &str,
+            config: ServiceConfig<'_>,
+        ) -> Result<Box<dyn Service>, anyhow::Error> {
+            let attempt = {
+                let mut attempts = self.attempts.lock().expect("lock poisoned");
+                let attempt = attempts.entry(id.into()).or_default();
+                *attempt += 1;
+                *attempt
+            };
+            if self.failing_service.as_deref() == Some(id)
+                && self.failing_attempts.map_or(true, |n| attempt <= n)
+            {
+                bail!("failed to ensure service {id}");
+            }
+            self.services
+                .lock()
+                .expect("lock poisoned")
+                .insert(id.into());
+            self.scales
+                .lock()
+                .expect("lock poisoned")
+                .insert(id.into(), config.scale);
+            Ok(Box::new(MockService))
+        }
+
+        async fn drop_service(&self, id: &str) -> Result<(), anyhow::Error> {
+            self.services.lock().expect("lock poisoned").remove(id);
+            Ok(())
+        }
+
+        async fn list_services(&self) -> Result<Vec<String>, anyhow::Error> {
+            let services = self.services.lock().expect("lock poisoned");
+            Ok(services.iter().cloned().collect())
+        }
+
+        fn watch_services(&self) -> BoxStream<'static, Result<ServiceEvent, anyhow::Error>> {
+            futures::stream::empty().boxed()
+        }
+
+        async fn fetch_service_metrics(
+            &self,
+            id: &str,
+        ) -> Result<Vec<ServiceProcessMetrics>, anyhow::Error> {
+            if id != self.service_id {
+                bail!("unknown service {id}");
+            }
+            Ok(self.metrics.clone())
+        }
+
+        async fn fetch_service_logs(
+            &self,
+            id: &str,
+            process_id: u64,
+            tail: usize,
+            previous: bool,
+        ) -> Result<Vec<String>, anyhow::Error> {
+            if id != self.service_id {
+                bail!("unknown service {id}");
+            }
+            let logs = if previous {
+                &self.previous_logs
+            } else {
+                &self.logs
+            };
+            let Some(lines) = usize::try_from(process_id)
+                .ok()
+                .and_then(|i| logs.get(i))
+            else {
+                bail!("unknown process {process_id}");
+            };
+            Ok(lines[lines.len().saturating_sub(tail)..].to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn replica_logs() {
+        let cluster_id = "u1".parse().unwrap();
+        let orchestrator = MockOrchestrator {
+            service_id: generate_replica_service_name(cluster_id, 3),
+            metrics: vec![],
+            logs: vec![
+                vec!["starting".into(), "listening".into(), "panicked".into()],
+                vec!["starting".into()],
+            ],
+            previous_logs: vec![vec!["starting".into(), "out of memory".into()]],
+            ..Default::default()
+        };
+
+        let logs = fetch_replica_logs(&orchestrator, cluster_id, 3, 0, 2, false)
+            .await
+            .unwrap();
+        assert_eq!(logs, ["listening", "panicked"]);
+        let logs = fetch_replica_logs(&orchestrator, cluster_id, 3, 1, 10, false)
+            .await
+            .unwrap();
+        assert_eq!(logs, ["starting"]);
+        let logs = fetch_replica_logs(&orchestrator, cluster_id, 3, 0, 0, false)
+            .await
+            .unwrap();
+        assert!(logs.is_empty());
+
+        // The logs of a crashed process are those of its previous instance.
+        let logs = fetch_replica_logs(&orchestrator, cluster_id, 3, 0, 1, true)
+            .await
+            .unwrap();
+        assert_eq!(logs, ["out of memory"]);
+        assert!(fetch_replica_logs(&orchestrator, cluster_id, 3, 1, 10, true)
+            .await
+            .is_err());
+
+        let err = fetch_replica_logs(&orchestrator, cluster_id, 3, 2, 10, false)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "fetching logs for process 2 of replica u1.3: unknown process 2"
+        );
+        assert!(fetch_replica_logs(&orchestrator, cluster_id, 4, 0, 10, false)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn replica_metrics() {
+        let cluster_id = "u1".parse().unwrap();
+        let metrics = vec![
+            ServiceProcessMetrics {
+                cpu_nano_cores: Some(250_000_000),
+                memory_bytes: Some(1 << 30),
+            },
+            // The orchestrator could not collect metrics for this process.
+            ServiceProcessMetrics::default(),
+        ];
+        let orchestrator = MockOrchestrator {
+            service_id: generate_replica_service_name(cluster_id, 3),
+            metrics: metrics.clone(),
+            ..Default::default()
+        };
+
+        let fetched = fetch_replica_metrics(&orchestrator, cluster_id, 3)
+            .await
+            .unwrap();
+        assert_eq!(fetched, metrics);
+
+        let err = fetch_replica_metrics(&orchestrator, cluster_id, 4)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "fetching metrics for replica u1.4: unknown service u1-replica-4"
+        );
+    }
+
+    fn managed_location() -> ManagedReplicaLocation {
+        ManagedReplicaLocation {
+            allocation: ReplicaAllocation {
+                memory_limit: None,
+                cpu_limit: None,
+                scale: 2,
+                workers: 4,
+                credits_per_hour: Numeric::from(1),
+            },
+            size: "2-4".into(),
+            availability_zone: "az1".into(),
+            az_user_specified: false,
+            preemptible: false,
+            node_selector: BTreeMap::new(),
+            tolerations: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn provision_replicas_rolls_back() {
+        async fn ensure(
+            orchestrator: &MockOrchestrator,
+            cluster_id: ClusterId,
+            replica_id: ReplicaId,
+        ) -> Result<Box<dyn Service>, anyhow::Error> {
+            let args = |assigned: &BTreeMap<String, String>| {
+                replica_service_args(cluster_id, replica_id, assigned)
+            };
+            let config = replica_service_config(
+                "clusterd".into(),
+                None,
+                &ReplicaServicePorts::default(),
+                cluster_id,
+                replica_id,
+                ClusterRole::User,
+                managed_location(),
+                &args,
+            );
+            let service_name = generate_replica_service_name(cluster_id, replica_id);
+            orchestrator.ensure_service(&service_name, config).await
+        }
+
+        let cluster_id = "u1".parse().unwrap();
+        let replicas = vec![(1, ()), (2, ()), (3, ())];
+
+        let orchestrator = MockOrchestrator::default();
+        let provisioned =
+            provision_replicas(&orchestrator, cluster_id, replicas.clone(), |id, ()| {
+                ensure(&orchestrator, cluster_id, id)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            provisioned.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(
+            *orchestrator.services.lock().unwrap(),
+            BTreeSet::from([
+                "u1-replica-1".to_string(),
+                "u1-replica-2".to_string(),
+                "u1-replica-3".to_string(),
+            ])
+        );
+
+        // Provisioning the second replica fails. The service of the first
+        // replica is dropped again, and the third replica is not provisioned.
+        let orchestrator = MockOrchestrator {
+            failing_service: Some(generate_replica_service_name(cluster_id, 2)),
+            ..Default::default()
+        };
+        let err = provision_replicas(&orchestrator, cluster_id, replicas, |id, ()| {
+            ensure(&orchestrator, cluster_id, id)
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "failed to ensure service u1-replica-2");
+        assert!(orchestrator.services.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn ensure_replica_service_retries() {
+        let cluster_id = "u1".parse().unwrap();
+        let args =
+            |assigned: &BTreeMap<String, String>| replica_service_args(cluster_id, 1, assigned);
+        let config = || {
+            replica_service_config(
+                "clusterd".into(),
+                None,
+                &ReplicaServicePorts::default(),
+                cluster_id,
+                1,
+                ClusterRole::User,
+                managed_location(),
+                &args,
+            )
+        };
+        let retry = ReplicaServiceRetryConfig {
+            initial_backoff: Duration::from_millis(1),
+            clamp_backoff: Duration::from_millis(1),
+            max_attempts: NonZeroUsize::new(3).unwrap(),
+        };
+
+        // Failed attempts are retried until one succeeds.
+        let orchestrator = MockOrchestrator {
+            failing_service: Some("u1-replica-1".into()),
+            failing_attempts: Some(2),
+            ..Default::default()
+        };
+        ensure_replica_service_with_retry(&orchestrator, retry.retry(), cluster_id, 1, config)
+            .await
+            .unwrap();
+        assert_eq!(orchestrator.attempts.lock().unwrap()["u1-replica-1"], 3);
+        assert!(orchestrator
+            .services
+            .lock()
+            .unwrap()
+            .contains("u1-replica-1"));
+
+        // After `max_attempts` failed attempts, the error of the last one is
+        // returned.
+        let orchestrator = MockOrchestrator {
+            failing_service: Some("u1-replica-1".into()),
+            ..Default::default()
+        };
+        let err =
+            ensure_replica_service_with_retry(&orchestrator, retry.retry(), cluster_id, 1, config)
+                .await
+                .unwrap_err();
+        assert_eq!(err.to_string(), "failed to ensure service u1-replica-1");
+        assert_eq!(orchestrator.attempts.lock().unwrap()["u1-replica-1"], 3);
+        assert!(orchestrator.services.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn resize_replica_in_place() {
+        async fn ensure(
+            orchestrator: &MockOrchestrator,
+            location: ManagedReplicaLocation,
+        ) -> Result<Box<dyn Service>, anyhow::Error> {
+            let cluster_id = "u1".parse().unwrap();
+            let args =
+                |assigned: &BTreeMap<String, String>| replica_service_args(cluster_id, 1, assigned);
+            let config = replica_service_config(
+                "clusterd".into(),
+                None,
+                &ReplicaServicePorts::default(),
+                cluster_id,
+                1,
+                ClusterRole::User,
+                location,
+                &args,
+            );
+            orchestrator.ensure_service("u1-replica-1", config).await
+        }
+
+        let orchestrator = MockOrchestrator::default();
+        let mut location = managed_location();
+        ensure(&orchestrator, location.clone()).await.unwrap();
+
+        // Changing only the limits keeps the running processes, so there is
+        // nothing to reconnect to.
+        let allocation = ReplicaAllocation {
+            cpu_limit: Some(CpuLimit::from_millicpus(4000)),
+            ..location.allocation.clone()
+        };
+        let reconnect =
+            resize_replica_service(&mut location, "2-4-large".into(), allocation, |l| {
+                ensure(&orchestrator, l)
+            })
+            .await
+            .unwrap();
+        assert!(reconnect.is_none());
+        assert_eq!(location.size, "2-4-large");
+
+        // Changing the number of workers restarts the processes, and the
+        // controller reconnects to them with the new number of workers.
+        let allocation = ReplicaAllocation {
+            scale: 4,
+            workers: 8,
+            ..location.allocation.clone()
+        };
+        let (storage_location, compute_location) =
+            resize_replica_service(&mut location, "4-8".into(), allocation, |l| {
+                ensure(&orchestrator, l)
+            })
+            .await
+            .unwrap()
+            .expect("restarted replica must be reconnected");
+        assert_eq!(storage_location.workers, 8);
+        assert_eq!(compute_location.workers, 8);
+
+        // The service is updated under its existing name.
+        assert_eq!(
+            *orchestrator.scales.lock().unwrap(),
+            BTreeMap::from([("u1-replica-1".to_string(), 4)])
+        );
+
+        // If the service cannot be updated, the resize fails.
+        let orchestrator = MockOrchestrator {
+            failing_service: Some("u1-replica-1".into()),
+            ..Default::default()
+        };
+        let allocation = location.allocation.clone();
+        let err = resize_replica_service(&mut location, "4-8".into(), allocation, |l| {
+            ensure(&orchestrator, l)
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "failed to ensure service u1-replica-1");
+    }
+
+    #[test]
+    fn replica_service_config_preemptible() {
+        let cluster_id = "u1".parse().unwrap();
+        let args =
+            |assigned: &BTreeMap<String, String>| replica_service_args(cluster_id, 1, assigned);
+
+        for preemptible in [false, true] {
+            let config = replica_service_config(
+                "clusterd".into(),
+                None,
+                &ReplicaServicePorts::default(),
+                cluster_id,
+                1,
+                ClusterRole::User,
+                ManagedReplicaLocation {
+                    preemptible,
+                    ..managed_location()
+                },
+                &args,
+            );
+            assert_eq!(config.preemptible, preemptible);
+            assert_eq!(config.scale, 2);
+            assert_eq!(config.availability_zone.as_deref(), Some("az1"));
+        }
+    }
+
+    #[test]
+    fn replica_service_config_node_selector() {
+        let cluster_id = "u1".parse().unwrap();
+        let args =
+            |assigned: &BTreeMap<String, String>| replica_service_args(cluster_id, 1, assigned);
+        let config = |location| {
+            replica_service_config(
+                "clusterd".into(),
+                None,
+                &ReplicaServicePorts::default(),
+                cluster_id,
+                1,
+                ClusterRole::User,
+                location,
+                &args,
+            )
+        };
+
+        // By default, the replica may be scheduled on any node.
+        let default = config(managed_location());
+        assert!(default.node_selector.is_empty());
+        assert!(default.tolerations.is_empty());
+
+        let node_selector = BTreeMap::from([("node-pool".into(), "high-memory".into())]);
+        let tolerations = vec![Toleration {
+            key: "high-memory".into(),
+            value: None,
+            effect: Some("NoSchedule".into()),
+        }];
+        let pinned = config(ManagedReplicaLocation {
+            node_selector: node_selector.clone(),
+            tolerations: tolerations.clone(),
+            ..managed_location()
+        });
+        assert_eq!(pinned.node_selector, node_selector);
+        assert_eq!(pinned.tolerations, tolerations);
+    }
+
+    #[test]
+    fn replica_service_config_ports() {
+        let cluster_id = "u1".parse().unwrap();
+        let args =
+            |assigned: &BTreeMap<String, String>| replica_service_args(cluster_id, 1, assigned);
+        let ports = ReplicaServicePorts {
+            internal_http: 7000,
+            ..Default::default()
+        };
+        let config = replica_service_config(
+            "clusterd".into(),
+            None,
+            &ports,
+            cluster_id,
+            1,
+            ClusterRole::User,
+            managed_location(),
+            &args,
+        );
+        let hints: BTreeMap<_, _> = config
+            .ports
+            .iter()
+            .map(|port| (port.name.as_str(), port.port_hint))
+            .collect();
+        assert_eq!(
+            hints,
+            BTreeMap::from([
+                ("storagectl", 2100),
+                ("storage", 2103),
+                ("computectl", 2101),
+                ("compute", 2102),
+                ("internal-http", 7000),
+            ])
+        );
+    }
+}
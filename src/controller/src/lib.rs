@@ -94,7 +94,7 @@ use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use differential_dataflow::lattice::Lattice;
-use futures::future::BoxFuture;
+use futures::future::{self, BoxFuture};
 use futures::stream::{Peekable, StreamExt};
 use serde::{Deserialize, Serialize};
 use timely::order::TotalOrder;
@@ -106,6 +106,7 @@ use uuid::Uuid;
 use mz_build_info::BuildInfo;
 use mz_compute_client::controller::{
     ActiveComputeController, ComputeController, ComputeControllerResponse, ReplicaId,
+    ReplicaReconnectConfig,
 };
 use mz_compute_client::protocol::response::{PeekResponse, SubscribeResponse};
 use mz_compute_client::service::{ComputeClient, ComputeGrpcClient};
@@ -115,7 +116,7 @@ use mz_ore::now::{EpochMillis, NowFn};
 use mz_ore::task::AbortOnDropHandle;
 use mz_ore::tracing::OpenTelemetryContext;
 use mz_persist_client::cache::PersistClientCache;
-use mz_persist_client::PersistLocation;
+use mz_persist_client::{PersistLocation, ShardId};
 use mz_persist_types::Codec64;
 use mz_proto::RustType;
 use mz_repr::{GlobalId, TimestampManipulation};
@@ -123,9 +124,16 @@ use mz_stash::StashFactory;
 use mz_storage_client::client::{
     ProtoStorageCommand, ProtoStorageResponse, StorageCommand, StorageResponse,
 };
-use mz_storage_client::controller::StorageController;
+use mz_storage_client::controller::{RehydrationConfig, StorageController};
+
+use crate::clusters::{
+    ClusterId, DrainOutcome, PendingDrain, PendingDrains, ReplicaServicePorts,
+    ReplicaServiceRetryConfig, ReplicaState,
+};
+use crate::metrics::{ControllerMetrics, ProvisioningTracker};
 
 pub mod clusters;
+pub mod metrics;
 
 /// Configures a controller.
 #[derive(Debug, Clone)]
@@ -152,6 +160,15 @@ pub struct ControllerConfig {
     pub postgres_factory: StashFactory,
     /// The metrics registry.
     pub metrics_registry: MetricsRegistry,
+    /// How compute replica clients connect to their replicas.
+    pub compute_reconnect: ReplicaReconnectConfig,
+    /// How storage instance clients reconnect to their replicas.
+    pub storage_rehydration: RehydrationConfig,
+    /// The port hints for the services of managed replicas.
+    pub replica_service_ports: ReplicaServicePorts,
+    /// How the controller retries creating or updating the services of
+    /// managed replicas.
+    pub replica_service_retry: ReplicaServiceRetryConfig,
 }
 
 /// Responses that [`Controller`] can produce.
@@ -172,6 +189,9 @@ pub enum ControllerResponse<T = mz_repr::Timestamp> {
     ComputeReplicaMetrics(ReplicaId, Vec<ServiceProcessMetrics>),
     /// Notification that the write frontiers of the replicas have changed.
     ComputeReplicaWriteFrontiers(BTreeMap<ReplicaId, Vec<(GlobalId, T)>>),
+    /// Notification that a drain has finished and the drained replicas have
+    /// been dropped.
+    DrainFinished(DrainOutcome),
 }
 
 impl<T> From<ComputeControllerResponse<T>> for ControllerResponse<T> {
@@ -209,6 +229,16 @@ enum Readiness {
     Compute,
     /// The metrics channel is ready.
     Metrics,
+    /// A pending drain has finished, and whether it timed out.
+    Drain(PendingDrain, bool),
+}
+
+/// Sleeps until `deadline`, or forever if there is none.
+async fn sleep_until_deadline(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => future::pending().await,
+    }
 }
 
 /// A client that maintains soft state and validates commands, in addition to forwarding them.
@@ -219,6 +249,10 @@ pub struct Controller<T = mz_repr::Timestamp> {
     clusterd_image: String,
     /// The init container image to use for clusterd.
     init_container_image: Option<String>,
+    /// The port hints for the services of managed replicas.
+    replica_service_ports: ReplicaServicePorts,
+    /// How to retry creating or updating the services of managed replicas.
+    replica_service_retry: ReplicaServiceRetryConfig,
     /// The cluster orchestrator.
     orchestrator: Arc<dyn NamespacedOrchestrator>,
     /// Tracks the readiness of the underlying controllers.
@@ -229,12 +263,25 @@ pub struct Controller<T = mz_repr::Timestamp> {
     metrics_tx: UnboundedSender<(ReplicaId, Vec<ServiceProcessMetrics>)>,
     /// Receiver for the channel over which replica metrics are sent.
     metrics_rx: Peekable<UnboundedReceiverStream<(ReplicaId, Vec<ServiceProcessMetrics>)>>,
+    /// Tracks the provisioning latency of managed replicas.
+    provisioning: ProvisioningTracker,
+    /// The replicas of each cluster, along with the configuration they were
+    /// created with.
+    replicas: BTreeMap<ClusterId, BTreeMap<ReplicaId, ReplicaState>>,
+    /// The drains that have been issued but not yet finished.
+    drains: PendingDrains,
 }
 
 impl<T> Controller<T> {
     pub fn active_compute(&mut self) -> ActiveComputeController<T> {
         self.compute.activate(&mut *self.storage)
     }
+
+    /// Returns the persist shard holding the contents of the storage collection `id`, or `None`
+    /// if the storage controller does not know the collection.
+    pub fn shard_id_for(&self, id: GlobalId) -> Option<ShardId> {
+        self.storage.shard_id_for(id)
+    }
 }
 
 impl<T> Controller<T>
@@ -262,9 +309,23 @@ where
     /// This method is cancellation safe.
     pub async fn ready(&mut self) {
         if let Readiness::NotReady = self.readiness {
+            // Drains finish once the work they wait for is done, which happens
+            // while processing the underlying controllers, or at their
+            // deadline.
+            if let Some((drain, timed_out)) = self.take_finished_drain() {
+                self.readiness = Readiness::Drain(drain, timed_out);
+                return;
+            }
+            let drain_deadline = self.drains.next_deadline();
+
             // The underlying `ready` methods are cancellation safe, so it is
             // safe to construct this `select!`.
             tokio::select! {
+                () = sleep_until_deadline(drain_deadline) => {
+                    if let Some((drain, timed_out)) = self.take_finished_drain() {
+                        self.readiness = Readiness::Drain(drain, timed_out);
+                    }
+                }
                 () = self.storage.ready() => {
                     self.readiness = Readiness::Storage;
                 }
@@ -294,6 +355,10 @@ where
             }
             Readiness::Compute => {
                 let response = self.active_compute().process();
+                if let Some(ComputeControllerResponse::ReplicaHeartbeat(replica_id, _)) = &response
+                {
+                    self.provisioning.heartbeat(*replica_id);
+                }
                 Ok(response.map(Into::into))
             }
             Readiness::Metrics => Ok(self
@@ -301,6 +366,7 @@ where
                 .next()
                 .await
                 .map(|(id, metrics)| ControllerResponse::ComputeReplicaMetrics(id, metrics))),
+            Readiness::Drain(drain, timed_out) => Ok(Some(self.finish_drain(drain, timed_out))),
         }
     }
 
@@ -345,6 +411,7 @@ where
             &config.postgres_factory,
             envd_epoch,
             config.metrics_registry.clone(),
+            config.storage_rehydration,
         )
         .await;
 
@@ -352,19 +419,26 @@ where
             config.build_info,
             envd_epoch,
             config.metrics_registry.clone(),
+            config.compute_reconnect,
         );
         let (metrics_tx, metrics_rx) = mpsc::unbounded_channel();
+        let metrics = ControllerMetrics::new(&config.metrics_registry);
 
         Self {
             storage: Box::new(storage_controller),
             compute: compute_controller,
             clusterd_image: config.clusterd_image,
             init_container_image: config.init_container_image,
+            replica_service_ports: config.replica_service_ports,
+            replica_service_retry: config.replica_service_retry,
             orchestrator: config.orchestrator.namespace("cluster"),
             readiness: Readiness::NotReady,
             metrics_tasks: BTreeMap::new(),
             metrics_tx,
             metrics_rx: UnboundedReceiverStream::new(metrics_rx).peekable(),
+            provisioning: ProvisioningTracker::new(metrics),
+            replicas: BTreeMap::new(),
+            drains: PendingDrains::default(),
         }
     }
 }
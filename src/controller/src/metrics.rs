@@ -0,0 +1,116 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Metrics for the controller.
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use prometheus::HistogramVec;
+
+use mz_compute_client::controller::ReplicaId;
+use mz_ore::metric;
+use mz_ore::metrics::MetricsRegistry;
+use mz_ore::stats::histogram_seconds_buckets;
+
+/// Controller metrics.
+#[derive(Debug, Clone)]
+pub struct ControllerMetrics {
+    replica_provisioning_latency_seconds: HistogramVec,
+}
+
+impl ControllerMetrics {
+    pub fn new(metrics_registry: &MetricsRegistry) -> Self {
+        ControllerMetrics {
+            replica_provisioning_latency_seconds: metrics_registry.register(metric!(
+                name: "mz_replica_provisioning_latency_seconds",
+                help: "The time from requesting a replica's service from the orchestrator until the first heartbeat from the replica.",
+                var_labels: ["size"],
+                buckets: histogram_seconds_buckets(0.128, 32.0)
+            )),
+        }
+    }
+}
+
+/// Tracks managed replicas whose service has been requested from the
+/// orchestrator, but which have not yet sent their first heartbeat.
+#[derive(Debug)]
+pub struct ProvisioningTracker {
+    metrics: ControllerMetrics,
+    /// The size of each pending replica and the time its service was
+    /// requested.
+    pending: BTreeMap<ReplicaId, (String, Instant)>,
+}
+
+impl ProvisioningTracker {
+    pub fn new(metrics: ControllerMetrics) -> Self {
+        ProvisioningTracker {
+            metrics,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Notes that the service for the specified replica of the given size was
+    /// requested at `requested_at`.
+    pub fn provisioning(&mut self, replica_id: ReplicaId, size: String, requested_at: Instant) {
+        self.pending.insert(replica_id, (size, requested_at));
+    }
+
+    /// Notes a heartbeat from the specified replica, recording its
+    /// provisioning latency if this is its first heartbeat.
+    pub fn heartbeat(&mut self, replica_id: ReplicaId) {
+        if let Some((size, requested_at)) = self.pending.remove(&replica_id) {
+            self.metrics
+                .replica_provisioning_latency_seconds
+                .with_label_values(&[&size])
+                .observe(requested_at.elapsed().as_secs_f64());
+        }
+    }
+
+    /// Stops tracking the specified replica, e.g. because it was dropped
+    /// before it sent a heartbeat.
+    pub fn forget(&mut self, replica_id: ReplicaId) {
+        self.pending.remove(&replica_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn provisioning_latency() {
+        let registry = MetricsRegistry::new();
+        let metrics = ControllerMetrics::new(&registry);
+        let histogram = metrics
+            .replica_provisioning_latency_seconds
+            .with_label_values(&["small"]);
+        let mut tracker = ProvisioningTracker::new(metrics.clone());
+
+        let requested_at = Instant::now() - Duration::from_secs(2);
+        tracker.provisioning(1, "small".into(), requested_at);
+        tracker.provisioning(2, "small".into(), requested_at);
+        assert_eq!(histogram.get_sample_count(), 0);
+
+        // Only the first heartbeat of a replica records its latency.
+        tracker.heartbeat(1);
+        assert_eq!(histogram.get_sample_count(), 1);
+        assert!(histogram.get_sample_sum() >= 2.0);
+        tracker.heartbeat(1);
+        assert_eq!(histogram.get_sample_count(), 1);
+
+        // Neither do forgotten replicas, nor unmanaged ones.
+        tracker.forget(2);
+        tracker.heartbeat(2);
+        tracker.heartbeat(3);
+        assert_eq!(histogram.get_sample_count(), 1);
+    }
+}
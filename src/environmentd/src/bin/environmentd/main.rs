@@ -726,6 +726,10 @@ fn run(mut args: Args) -> Result<(), anyhow::Error> {
         now: SYSTEM_TIME.clone(),
         postgres_factory: StashFactory::new(&metrics_registry),
         metrics_registry: metrics_registry.clone(),
+        compute_reconnect: Default::default(),
+        storage_rehydration: Default::default(),
+        replica_service_ports: Default::default(),
+        replica_service_retry: Default::default(),
     };
 
     let cluster_replica_sizes: ClusterReplicaSizeMap = match args.cluster_replica_sizes {
@@ -55,7 +55,7 @@ use mz_sql::session::user::{ExternalUserMetadata, User, HTTP_DEFAULT_USER, SYSTE
 use crate::server::{ConnectionHandler, Server};
 use crate::BUILD_INFO;
 
-pub use sql::{SqlResponse, WebSocketAuth, WebSocketResponse};
+pub use sql::{SqlResponse, WebSocketAuth, WebSocketResponse, WS_SUBPROTOCOLS};
 
 mod catalog;
 mod memory;
@@ -8,62 +8,160 @@
 // by the Apache License, Version 2.0.
 
 use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt;
 use std::time::Duration;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use axum::body::StreamBody;
 use axum::extract::ws::{CloseFrame, Message, WebSocket};
 use axum::extract::{State, WebSocketUpgrade};
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use futures::Future;
-use http::StatusCode;
+use http::{header, HeaderMap, StatusCode};
 use itertools::izip;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 use tokio::time;
+use tokio_stream::wrappers::ReceiverStream;
 use tracing::warn;
 use tungstenite::protocol::frame::coding::CloseCode;
 
 use mz_adapter::session::{EndTransactionAction, RowBatchStream, TransactionStatus};
 use mz_adapter::{
-    AdapterNotice, ExecuteResponse, ExecuteResponseKind, PeekResponseUnary, SessionClient,
+    AdapterError, AdapterNotice, ExecuteResponse, ExecuteResponseKind, PeekResponseUnary,
+    SessionClient,
 };
 use mz_interchange::encode::TypedDatum;
 use mz_interchange::json::ToJson;
 use mz_ore::result::ResultExt;
 use mz_pgwire::Severity;
-use mz_repr::{Datum, RelationDesc, RowArena};
+use mz_repr::{Datum, RelationDesc, Row, RowArena};
 use mz_sql::ast::display::AstDisplay;
 use mz_sql::ast::{Raw, Statement, StatementKind};
 use mz_sql::plan::Plan;
+use tokio_postgres::error::SqlState;
 
 use crate::http::{AuthedClient, MAX_REQUEST_SIZE};
 
 use super::{init_ws, WsState};
 
+/// The media type of responses that stream results as newline-delimited JSON.
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// The number of NDJSON chunks buffered for a client that is slow to read
+/// them.
+const NDJSON_BUFFER_SIZE: usize = 16;
+
+/// The number of rows of a query's result that are encoded as JSON, and sent to
+/// streaming clients, at a time.
+const ROWS_PER_BATCH: usize = 1024;
+
 pub async fn handle_sql(
     mut client: AuthedClient,
+    headers: HeaderMap,
     Json(request): Json<SqlRequest>,
-) -> impl IntoResponse {
+) -> Response {
+    if accepts_ndjson(&headers) {
+        return handle_sql_ndjson(client, request);
+    }
+
     let mut res = SqlResponse {
         results: Vec::new(),
     };
     match execute_request(&mut client, request, &mut res).await {
-        Ok(()) => Ok(Json(res)),
-        Err(e) => Err((StatusCode::BAD_REQUEST, e.to_string())),
+        Ok(()) => Json(res).into_response(),
+        Err(e) => {
+            let error = ErrorResponse::from(e);
+            (StatusCode::BAD_REQUEST, Json(error)).into_response()
+        }
     }
 }
 
+/// Reports whether the client asked for results to be streamed as
+/// newline-delimited JSON.
+fn accepts_ndjson(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_range| media_range.split(';').next())
+        .any(|media_type| media_type.trim().eq_ignore_ascii_case(NDJSON_CONTENT_TYPE))
+}
+
+/// Executes `request`, streaming its results to the client as they are
+/// produced.
+///
+/// The response body uses chunked transfer encoding and contains one
+/// [`WebSocketResponse`] per line. An error that fails the request as a whole
+/// is reported as a final line holding the [`ErrorResponse`] that non-streaming
+/// clients receive, since the response status has been sent by then.
+fn handle_sql_ndjson(mut client: AuthedClient, request: SqlRequest) -> Response {
+    let (tx, rx) = mpsc::channel(NDJSON_BUFFER_SIZE);
+    mz_ore::task::spawn(|| "http_sql_ndjson", async move {
+        let mut sender = NdjsonSender { tx };
+        if let Err(e) = execute_request(&mut client, request, &mut sender).await {
+            // If the client went away, there is nobody left to tell.
+            let _ = sender.send_line(&ErrorResponse::from(e)).await;
+        }
+    });
+    (
+        [(header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)],
+        StreamBody::new(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+/// The response to a [`SqlRequest`] that failed as a whole, e.g. because its
+/// query could not be parsed.
 #[derive(Serialize)]
 struct ErrorResponse {
-    error: String,
+    error: SqlError,
 }
 
+impl From<anyhow::Error> for ErrorResponse {
+    fn from(e: anyhow::Error) -> ErrorResponse {
+        let error = e
+            .downcast::<SqlError>()
+            .unwrap_or_else(|e| SqlError::new(SqlState::INTERNAL_ERROR, e.to_string()));
+        ErrorResponse { error }
+    }
+}
+
+/// The WebSocket subprotocols supported by [`handle_sql_ws`], one for each
+/// version of its message format.
+///
+/// Clients that don't request a subprotocol are served the first version.
+pub const WS_SUBPROTOCOLS: &[&str] = &["mz-sql-v1"];
+
 pub async fn handle_sql_ws(
     State(state): State<WsState>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
-) -> impl IntoResponse {
-    ws.max_message_size(MAX_REQUEST_SIZE)
+) -> Response {
+    // If the client requests any subprotocols, at least one of them must be
+    // supported. Otherwise we refuse the handshake rather than speaking a
+    // message format the client doesn't expect.
+    let requested: Vec<_> = headers
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|protocol| protocol.trim())
+        .collect();
+    if !requested.is_empty() && !requested.iter().any(|p| WS_SUBPROTOCOLS.contains(p)) {
+        let msg = format!(
+            "unsupported WebSocket subprotocol; supported subprotocols: {}",
+            WS_SUBPROTOCOLS.join(", ")
+        );
+        return (StatusCode::BAD_REQUEST, msg).into_response();
+    }
+
+    ws.protocols(WS_SUBPROTOCOLS.iter().copied())
+        .max_message_size(MAX_REQUEST_SIZE)
         .on_upgrade(|ws| async move { run_ws(&state, ws).await })
 }
 
@@ -204,14 +302,9 @@ async fn forward_notices(
     ws: &mut WebSocket,
     notices: impl IntoIterator<Item = AdapterNotice>,
 ) -> Result<(), anyhow::Error> {
-    let ws_notices = notices.into_iter().map(|notice| {
-        WebSocketResponse::Notice(Notice {
-            message: notice.to_string(),
-            severity: Severity::for_adapter_notice(&notice)
-                .as_str()
-                .to_lowercase(),
-        })
-    });
+    let ws_notices = notices
+        .into_iter()
+        .map(|notice| WebSocketResponse::Notice(Notice::from(notice)));
 
     for notice in ws_notices {
         send_ws_response(ws, notice).await?;
@@ -256,6 +349,15 @@ pub struct SqlResponse {
 
 enum StatementResult {
     SqlResult(SqlResult),
+    /// The rows returned by a query, which senders encode as JSON only once
+    /// they are ready to send them.
+    Rows {
+        tag: String,
+        col_names: Vec<String>,
+        desc: RelationDesc,
+        rows: Vec<Row>,
+        notices: Vec<Notice>,
+    },
     Subscribe {
         desc: RelationDesc,
         tag: String,
@@ -293,31 +395,17 @@ pub enum SqlResult {
     },
     /// The query returned an error.
     Err {
-        /// The error message.
-        error: String,
+        /// The error.
+        error: SqlError,
         // Any notices generated during execution of the query.
         notices: Vec<Notice>,
     },
 }
 
 impl SqlResult {
-    fn rows(
-        client: &mut SessionClient,
-        tag: String,
-        rows: Vec<Vec<serde_json::Value>>,
-        col_names: Vec<String>,
-    ) -> SqlResult {
-        SqlResult::Rows {
-            tag,
-            rows,
-            col_names,
-            notices: make_notices(client),
-        }
-    }
-
-    fn err(client: &mut SessionClient, msg: impl std::fmt::Display) -> SqlResult {
+    fn err(client: &mut SessionClient, error: impl Into<SqlError>) -> SqlResult {
         SqlResult::Err {
-            error: msg.to_string(),
+            error: error.into(),
             notices: make_notices(client),
         }
     }
@@ -330,6 +418,54 @@ impl SqlResult {
     }
 }
 
+/// An error from a [`SqlRequest`], with the fields that pgwire clients receive
+/// for the error.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SqlError {
+    message: String,
+    /// The SQLSTATE code of the error.
+    code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+    /// The 1-based character position in the query that the error refers to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<usize>,
+}
+
+impl SqlError {
+    fn new(code: SqlState, message: impl Into<String>) -> SqlError {
+        mz_pgwire::ErrorResponse::error(code, message).into()
+    }
+}
+
+impl fmt::Display for SqlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SqlError {}
+
+impl From<mz_pgwire::ErrorResponse> for SqlError {
+    fn from(response: mz_pgwire::ErrorResponse) -> SqlError {
+        SqlError {
+            message: response.message,
+            code: response.code.code().to_string(),
+            detail: response.detail,
+            hint: response.hint,
+            position: response.position,
+        }
+    }
+}
+
+impl From<AdapterError> for SqlError {
+    fn from(e: AdapterError) -> SqlError {
+        mz_pgwire::ErrorResponse::from_adapter_error(Severity::Error, e).into()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum WebSocketResponse {
@@ -345,6 +481,8 @@ pub enum WebSocketResponse {
 pub struct Notice {
     message: String,
     severity: String,
+    /// The SQLSTATE code that pgwire clients receive for the notice.
+    code: String,
 }
 
 impl Notice {
@@ -353,9 +491,79 @@ impl Notice {
     }
 }
 
+impl From<AdapterNotice> for Notice {
+    fn from(notice: AdapterNotice) -> Notice {
+        let response = mz_pgwire::ErrorResponse::from_adapter_notice(notice);
+        Notice {
+            message: response.message,
+            severity: response.severity.as_str().to_lowercase(),
+            code: response.code.code().to_string(),
+        }
+    }
+}
+
+/// Encodes `rows` as JSON, with one value per column of `desc`, in batches of
+/// at most [`ROWS_PER_BATCH`] rows.
+///
+/// Each row is dropped once it has been encoded, so a caller that sends each
+/// batch before asking for the next never holds more than one batch of encoded
+/// rows.
+fn encode_row_batches(
+    desc: &RelationDesc,
+    rows: Vec<Row>,
+) -> impl Iterator<Item = Vec<Vec<serde_json::Value>>> + '_ {
+    let types = &desc.typ().column_types;
+    let mut datum_vec = mz_repr::DatumVec::new();
+    let mut rows = rows.into_iter();
+    std::iter::from_fn(move || {
+        let batch: Vec<_> = rows
+            .by_ref()
+            .take(ROWS_PER_BATCH)
+            .map(|row| {
+                let datums = datum_vec.borrow_with(&row);
+                datums
+                    .iter()
+                    .zip(types)
+                    .map(|(d, typ)| TypedDatum::new(*d, typ).json())
+                    .collect()
+            })
+            .collect();
+        (!batch.is_empty()).then_some(batch)
+    })
+}
+
+/// Translates a [`SqlResult`] into the messages that streaming clients receive
+/// for it. The returned flag reports whether the result is an error.
+fn sql_result_messages(res: SqlResult) -> (bool, Vec<WebSocketResponse>) {
+    match res {
+        SqlResult::Rows {
+            tag,
+            rows,
+            col_names,
+            notices,
+        } => {
+            let mut msgs = vec![WebSocketResponse::Rows(col_names)];
+            msgs.extend(rows.into_iter().map(WebSocketResponse::Row));
+            msgs.push(WebSocketResponse::CommandComplete(tag));
+            msgs.extend(notices.into_iter().map(WebSocketResponse::Notice));
+            (false, msgs)
+        }
+        SqlResult::Ok { ok, notices } => {
+            let mut msgs = vec![WebSocketResponse::CommandComplete(ok)];
+            msgs.extend(notices.into_iter().map(WebSocketResponse::Notice));
+            (false, msgs)
+        }
+        SqlResult::Err { error, notices } => {
+            let mut msgs = vec![WebSocketResponse::Error(error.message)];
+            msgs.extend(notices.into_iter().map(WebSocketResponse::Notice));
+            (true, msgs)
+        }
+    }
+}
+
 /// Trait describing how to transmit a response to a client. HTTP clients
-/// accumulate into a Vec and send all at once. WebSocket clients send each
-/// message as they occur.
+/// accumulate into a Vec and send all at once, unless they asked for NDJSON.
+/// NDJSON and WebSocket clients send each message as they occur.
 #[async_trait]
 trait ResultSender {
     /// Adds a result to the client. Returns Err if sending to the client
@@ -386,9 +594,27 @@ impl ResultSender for SqlResponse {
                     Ok(())
                 }
             }
+            StatementResult::Rows {
+                tag,
+                col_names,
+                desc,
+                rows,
+                notices,
+            } => {
+                self.results.push(SqlResult::Rows {
+                    tag,
+                    rows: encode_row_batches(&desc, rows).flatten().collect(),
+                    col_names,
+                    notices,
+                });
+                Ok(())
+            }
             StatementResult::Subscribe { .. } => {
                 self.results.push(SqlResult::Err {
-                    error: "SUBSCRIBE only supported over websocket".into(),
+                    error: SqlError::new(
+                        SqlState::FEATURE_NOT_SUPPORTED,
+                        "SUBSCRIBE only supported over websocket",
+                    ),
                     notices: Vec::new(),
                 });
                 Err(())
@@ -417,28 +643,24 @@ impl ResultSender for WebSocket {
         }
 
         let (is_err, msgs) = match res {
-            StatementResult::SqlResult(SqlResult::Rows {
+            StatementResult::SqlResult(res) => sql_result_messages(res),
+            StatementResult::Rows {
                 tag,
-                rows,
                 col_names,
+                desc,
+                rows,
                 notices,
-            }) => {
-                let mut msgs = vec![WebSocketResponse::Rows(col_names)];
-                msgs.extend(rows.into_iter().map(WebSocketResponse::Row));
-                msgs.push(WebSocketResponse::CommandComplete(tag));
-                msgs.extend(notices.into_iter().map(WebSocketResponse::Notice));
-                (false, msgs)
-            }
-            StatementResult::SqlResult(SqlResult::Ok { ok, notices }) => {
-                let mut msgs = vec![WebSocketResponse::CommandComplete(ok)];
+            } => {
+                send(self, WebSocketResponse::Rows(col_names)).await?;
+                for batch in encode_row_batches(&desc, rows) {
+                    for row in batch {
+                        send(self, WebSocketResponse::Row(row)).await?;
+                    }
+                }
+                let mut msgs = vec![WebSocketResponse::CommandComplete(tag)];
                 msgs.extend(notices.into_iter().map(WebSocketResponse::Notice));
                 (false, msgs)
             }
-            StatementResult::SqlResult(SqlResult::Err { error, notices }) => {
-                let mut msgs = vec![WebSocketResponse::Error(error)];
-                msgs.extend(notices.into_iter().map(WebSocketResponse::Notice));
-                (true, msgs)
-            }
             StatementResult::Subscribe { desc, tag, mut rx } => {
                 send(
                     self,
@@ -516,6 +738,85 @@ impl ResultSender for WebSocket {
     }
 }
 
+/// Streams results to an HTTP client as newline-delimited JSON.
+struct NdjsonSender {
+    /// The chunks of the response body, each holding one or more lines.
+    tx: mpsc::Sender<Result<String, Infallible>>,
+}
+
+impl NdjsonSender {
+    async fn send_chunk(&mut self, chunk: String) -> Result<(), anyhow::Error> {
+        self.tx
+            .send(Ok(chunk))
+            .await
+            .map_err(|_| anyhow!("client disconnected"))
+    }
+
+    async fn send_line(&mut self, msg: &impl Serialize) -> Result<(), anyhow::Error> {
+        let mut line = serde_json::to_string(msg).expect("must serialize");
+        line.push('\n');
+        self.send_chunk(line).await
+    }
+
+    async fn send(&mut self, msg: WebSocketResponse) -> Result<(), anyhow::Error> {
+        self.send_line(&msg).await
+    }
+}
+
+#[async_trait]
+impl ResultSender for NdjsonSender {
+    async fn add_result(&mut self, res: StatementResult) -> Result<Result<(), ()>, anyhow::Error> {
+        let (is_err, msgs) = match res {
+            StatementResult::SqlResult(res) => sql_result_messages(res),
+            StatementResult::Rows {
+                tag,
+                col_names,
+                desc,
+                rows,
+                notices,
+            } => {
+                self.send(WebSocketResponse::Rows(col_names)).await?;
+                // Send each batch of rows as a single chunk, so that its lines
+                // are written to the client before the next batch is encoded.
+                for batch in encode_row_batches(&desc, rows) {
+                    let mut chunk = String::new();
+                    for row in batch {
+                        let msg = WebSocketResponse::Row(row);
+                        chunk.push_str(&serde_json::to_string(&msg).expect("must serialize"));
+                        chunk.push('\n');
+                    }
+                    self.send_chunk(chunk).await?;
+                }
+                let mut msgs = vec![WebSocketResponse::CommandComplete(tag)];
+                msgs.extend(notices.into_iter().map(WebSocketResponse::Notice));
+                (false, msgs)
+            }
+            StatementResult::Subscribe { .. } => {
+                let msg = "SUBSCRIBE only supported over websocket";
+                (true, vec![WebSocketResponse::Error(msg.into())])
+            }
+        };
+        for msg in msgs {
+            self.send(msg).await?;
+        }
+        Ok(if is_err { Err(()) } else { Ok(()) })
+    }
+
+    async fn await_rows<F, R>(&mut self, f: F) -> Result<R, anyhow::Error>
+    where
+        F: Future<Output = R> + Send,
+    {
+        tokio::select! {
+            () = self.tx.closed() => Err(anyhow!("client disconnected")),
+            data = f => Ok(data),
+        }
+    }
+
+    fn allow_subscribe(&self) -> bool {
+        false
+    }
+}
+
 /// Returns Ok(Err) if any statement error'd during execution.
 async fn execute_stmt_group<S: ResultSender>(
     client: &mut SessionClient,
@@ -532,7 +833,10 @@ async fn execute_stmt_group<S: ResultSender>(
         if is_aborted_txn && !is_txn_exit_stmt(&stmt) {
             let _ = sender.add_result(SqlResult::err(
                 client,
-                "current transaction is aborted, commands ignored until end of transaction block",
+                SqlError::new(
+                    SqlState::IN_FAILED_SQL_TRANSACTION,
+                    "current transaction is aborted, commands ignored until end of transaction block",
+                ),
             ).into()).await?;
             return Ok(Err(()));
         }
@@ -557,7 +861,7 @@ async fn execute_stmt_group<S: ResultSender>(
                 TransactionStatus::Started(_) | TransactionStatus::InTransactionImplicit(_) => {
                     if let Err(err) = client.end_transaction(EndTransactionAction::Rollback).await {
                         let _ = sender
-                            .add_result(SqlResult::err(client, err.to_string()).into())
+                            .add_result(SqlResult::err(client, err).into())
                             .await?;
                     }
                 }
@@ -613,15 +917,26 @@ async fn execute_request<S: ResultSender>(
                 _ => false,
             }
         }) {
-            anyhow::bail!("unsupported via this API: {}", stmt.to_ast_string());
+            return Err(SqlError::new(
+                SqlState::FEATURE_NOT_SUPPORTED,
+                format!("unsupported via this API: {}", stmt.to_ast_string()),
+            )
+            .into());
         }
         Ok(())
     }
 
     fn parse(query: &str) -> Result<Vec<Statement<Raw>>, anyhow::Error> {
         match mz_sql::parse::parse_with_limit(query) {
-            Ok(result) => result.map_err(|e| anyhow!(e)),
-            Err(e) => Err(anyhow!(e)),
+            Ok(result) => result.map_err(|e| {
+                // Convert the 0-based byte position to the 1-based character
+                // position that pgwire reports.
+                let position = query[..e.pos].chars().count() + 1;
+                let error = mz_pgwire::ErrorResponse::error(SqlState::SYNTAX_ERROR, e.message)
+                    .with_position(position);
+                SqlError::from(error).into()
+            }),
+            Err(e) => Err(SqlError::new(SqlState::PROGRAM_LIMIT_EXCEEDED, e).into()),
         }
     }
 
@@ -641,11 +956,15 @@ async fn execute_request<S: ResultSender>(
             for ExtendedRequest { query, params } in queries {
                 let mut stmts = parse(&query)?;
                 if stmts.len() != 1 {
-                    anyhow::bail!(
-                        "each query must contain exactly 1 statement, but \"{}\" contains {}",
-                        query,
-                        stmts.len()
-                    );
+                    return Err(SqlError::new(
+                        SqlState::PROTOCOL_VIOLATION,
+                        format!(
+                            "each query must contain exactly 1 statement, but \"{}\" contains {}",
+                            query,
+                            stmts.len()
+                        ),
+                    )
+                    .into());
                 }
 
                 let stmt = stmts.pop().unwrap();
@@ -703,7 +1022,8 @@ async fn execute_stmt<S: ResultSender>(
             actual = raw_params.len(),
             expected = param_types.len()
         );
-        return Ok(SqlResult::err(client, message).into());
+        let error = SqlError::new(SqlState::PROTOCOL_VIOLATION, message);
+        return Ok(SqlResult::err(client, error).into());
     }
 
     let buf = RowArena::new();
@@ -721,7 +1041,8 @@ async fn execute_stmt<S: ResultSender>(
                     Ok(param) => param.into_datum(&buf, &pg_typ),
                     Err(err) => {
                         let msg = format!("unable to decode parameter: {}", err);
-                        return Ok(SqlResult::err(client, msg).into());
+                        let error = SqlError::new(SqlState::INVALID_PARAMETER_VALUE, msg);
+                        return Ok(SqlResult::err(client, error).into());
                     }
                 }
             }
@@ -750,7 +1071,7 @@ async fn execute_stmt<S: ResultSender>(
         result_formats,
         revision,
     ) {
-        return Ok(SqlResult::err(client, err.to_string()).into());
+        return Ok(SqlResult::err(client, err).into());
     }
 
     let desc = client
@@ -774,7 +1095,7 @@ async fn execute_stmt<S: ResultSender>(
 
     Ok(match res {
         ExecuteResponse::Canceled => {
-            SqlResult::err(client, "statement canceled due to user request").into()
+            SqlResult::err(client, SqlError::new(SqlState::QUERY_CANCELED, "statement canceled due to user request")).into()
         }
         ExecuteResponse::CreatedConnection { .. }
         | ExecuteResponse::CreatedDatabase { .. }
@@ -821,21 +1142,19 @@ async fn execute_stmt<S: ResultSender>(
             let rows = match sender.await_rows(rows).await? {
                 PeekResponseUnary::Rows(rows) => rows,
                 PeekResponseUnary::Error(e) => {
-                    return Ok(SqlResult::err(client, e).into());
+                    return Ok(SqlResult::err(client, SqlError::new(SqlState::INTERNAL_ERROR, e)).into());
                 }
                 PeekResponseUnary::Canceled => {
-                    return Ok(SqlResult::err(client, "statement canceled due to user request").into());
+                    return Ok(SqlResult::err(client, SqlError::new(SqlState::QUERY_CANCELED, "statement canceled due to user request")).into());
                 }
             };
-            let mut sql_rows: Vec<Vec<serde_json::Value>> = vec![];
-            let mut datum_vec = mz_repr::DatumVec::new();
-            for row in rows {
-                let datums = datum_vec.borrow_with(&row);
-                let types = &desc.relation_desc.as_ref().unwrap().typ().column_types;
-                sql_rows.push(datums.iter().enumerate().map(|(i, d)| TypedDatum::new(*d, &types[i]).json()).collect());
+            StatementResult::Rows {
+                tag: format!("SELECT {}", rows.len()),
+                col_names,
+                desc: desc.relation_desc.unwrap(),
+                rows,
+                notices: make_notices(client),
             }
-            let tag = format!("SELECT {}", sql_rows.len());
-            SqlResult::rows(client, tag, sql_rows, col_names).into()
         }
         ExecuteResponse::Subscribing { rx }  => {
             StatementResult::Subscribe { tag:"SUBSCRIBE".into(), desc: desc.relation_desc.unwrap(), rx }
@@ -847,10 +1166,10 @@ async fn execute_stmt<S: ResultSender>(
         | ExecuteResponse::ClosedCursor) => {
             SqlResult::err(
                 client,
-                format!("internal error: encountered prohibited ExecuteResponse {:?}.\n\n
+                SqlError::new(SqlState::INTERNAL_ERROR, format!("internal error: encountered prohibited ExecuteResponse {:?}.\n\n
 This is a bug. Can you please file an issue letting us know?\n
 https://github.com/MaterializeInc/materialize/issues/new?assignees=&labels=C-bug%2CC-triage&template=01-bug.yml",
-            ExecuteResponseKind::from(res))).into()
+            ExecuteResponseKind::from(res)))).into()
         }
     })
 }
@@ -860,12 +1179,7 @@ fn make_notices(client: &mut SessionClient) -> Vec<Notice> {
         .session()
         .drain_notices()
         .into_iter()
-        .map(|notice| Notice {
-            message: notice.to_string(),
-            severity: Severity::for_adapter_notice(&notice)
-                .as_str()
-                .to_lowercase(),
-        })
+        .map(Notice::from)
         .collect()
 }
 
@@ -882,7 +1196,74 @@ fn is_txn_exit_stmt(stmt: &Statement<Raw>) -> bool {
 mod tests {
     use std::collections::BTreeMap;
 
-    use super::WebSocketAuth;
+    use mz_repr::{Datum, RelationDesc, Row, ScalarType};
+    use tokio::sync::mpsc;
+
+    use super::{
+        NdjsonSender, ResultSender, StatementResult, WebSocketAuth, WebSocketResponse,
+        ROWS_PER_BATCH,
+    };
+
+    #[tokio::test]
+    async fn ndjson_streams_rows_in_batches() {
+        let n_rows = 4 * ROWS_PER_BATCH + 1;
+        let desc = RelationDesc::empty().with_column("a", ScalarType::Int64.nullable(false));
+        let rows = (0..n_rows)
+            .map(|i| Row::pack_slice(&[Datum::Int64(i64::try_from(i).unwrap())]))
+            .collect();
+        let result = StatementResult::Rows {
+            tag: format!("SELECT {n_rows}"),
+            col_names: vec!["a".into()],
+            desc,
+            rows,
+            notices: vec![],
+        };
+
+        // With room for a single chunk, the sender cannot get more than one
+        // chunk ahead of the client, so it must still be sending this
+        // statement's rows when the first of them arrive.
+        let (tx, mut rx) = mpsc::channel(1);
+        let handle = tokio::spawn(async move {
+            let mut sender = NdjsonSender { tx };
+            sender.add_result(result).await.unwrap()
+        });
+
+        let parse = |chunk: String| -> Vec<WebSocketResponse> {
+            chunk
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        };
+        let header = parse(rx.recv().await.unwrap().unwrap());
+        assert!(matches!(&header[..], [WebSocketResponse::Rows(cols)] if cols == &["a"]));
+
+        let first_batch = parse(rx.recv().await.unwrap().unwrap());
+        assert_eq!(first_batch.len(), ROWS_PER_BATCH);
+        assert!(!handle.is_finished());
+
+        let mut n_received = first_batch.len();
+        let mut n_chunks = 1;
+        let mut tail = vec![];
+        while let Some(chunk) = rx.recv().await {
+            let msgs = parse(chunk.unwrap());
+            match &msgs[..] {
+                [WebSocketResponse::CommandComplete(_)] => tail.extend(msgs),
+                _ => {
+                    assert!(msgs.len() <= ROWS_PER_BATCH);
+                    assert!(msgs.iter().all(|m| matches!(m, WebSocketResponse::Row(_))));
+                    n_received += msgs.len();
+                    n_chunks += 1;
+                }
+            }
+        }
+        assert_eq!(n_received, n_rows);
+        assert_eq!(n_chunks, 5);
+        assert!(matches!(
+            &tail[..],
+            [WebSocketResponse::CommandComplete(tag)] if tag == &format!("SELECT {n_rows}")
+        ));
+        assert_eq!(handle.await.unwrap(), Ok(()));
+    }
 
     #[test]
     fn smoke_test_websocket_auth_parse() {
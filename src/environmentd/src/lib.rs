@@ -118,7 +118,7 @@ pub mod http;
 mod server;
 mod telemetry;
 
-pub use crate::http::{SqlResponse, WebSocketAuth, WebSocketResponse};
+pub use crate::http::{SqlResponse, WebSocketAuth, WebSocketResponse, WS_SUBPROTOCOLS};
 
 pub const BUILD_INFO: BuildInfo = build_info!();
 
@@ -455,6 +455,26 @@ fn test_copy() {
             .unwrap();
         assert_eq!(buf, "\\N\t2\n\\t\t4\n");
     }
+
+    // Test that text COPY renders NULLs as the `null_display` session variable.
+    {
+        for (null_display, expected) in [
+            ("NULL", "NULL\t2\n\\t\t4\n"),
+            ("", "\t2\n\\t\t4\n"),
+            ("\\N", "\\N\t2\n\\t\t4\n"),
+        ] {
+            client
+                .batch_execute(&format!("SET null_display = '{null_display}'"))
+                .unwrap();
+            let mut buf = String::new();
+            client
+                .copy_out("COPY (VALUES (NULL, 2), (E'\t', 4)) TO STDOUT")
+                .unwrap()
+                .read_to_string(&mut buf)
+                .unwrap();
+            assert_eq!(buf, expected);
+        }
+    }
 }
 
 #[test]
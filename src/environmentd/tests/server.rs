@@ -77,6 +77,7 @@
 
 use std::collections::BTreeMap;
 use std::fmt::Write;
+use std::io::{BufRead, BufReader};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::{iter, thread};
@@ -90,10 +91,11 @@ use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use tokio_postgres::error::SqlState;
 use tracing::info;
+use tungstenite::client::IntoClientRequest;
 use tungstenite::error::ProtocolError;
 use tungstenite::{Error, Message};
 
-use mz_environmentd::WebSocketResponse;
+use mz_environmentd::{WebSocketResponse, WS_SUBPROTOCOLS};
 use mz_ore::cast::CastLossy;
 use mz_ore::now::NowFn;
 use mz_ore::retry::Retry;
@@ -560,6 +562,78 @@ fn test_storage_usage_collection_interval() {
     assert_eq!(after_drop_storage_usage, 0);
 }
 
+#[test]
+fn test_shard_metadata() {
+    let server = util::start_server(util::Config::default()).unwrap();
+    let mut client = server.connect(postgres::NoTls).unwrap();
+    let mut system_client = server
+        .pg_config_internal()
+        .user(&SYSTEM_USER.name)
+        .connect(postgres::NoTls)
+        .unwrap();
+
+    client
+        .batch_execute("CREATE TABLE shard_metadata_test (a int)")
+        .unwrap();
+    client
+        .batch_execute("INSERT INTO shard_metadata_test VALUES (1), (2)")
+        .unwrap();
+    let row = Retry::default()
+        .max_duration(Duration::from_secs(10))
+        .retry(|_| {
+            client.query_one(
+                "SELECT object_id, shard_id
+                 FROM mz_internal.mz_storage_shards s
+                 JOIN mz_objects o ON o.id = s.object_id
+                 WHERE o.name = 'shard_metadata_test'",
+                &[],
+            )
+        })
+        .unwrap();
+    let object_id: String = row.get("object_id");
+    let shard_id: String = row.get("shard_id");
+    let query = |id: &str| {
+        format!(
+            "SELECT since IS NOT NULL AS has_since, upper IS NOT NULL AS has_upper,
+                    batch_count, encoded_size_bytes
+             FROM mz_internal.mz_shard_metadata('{id}')"
+        )
+    };
+
+    // Only superusers may inspect shards.
+    let err = client.query_one(&query(&shard_id), &[]).unwrap_db_error();
+    assert_eq!(err.message(), "permission denied to inspect a shard");
+
+    // A shard can be named directly or by the collection whose data it holds.
+    for id in [&shard_id, &object_id] {
+        let row = system_client.query_one(&query(id), &[]).unwrap();
+        assert!(row.get::<_, bool>("has_since"));
+        assert!(row.get::<_, bool>("has_upper"));
+        assert!(row.get::<_, UInt8>("batch_count").0 > 0);
+        assert!(row.get::<_, UInt8>("encoded_size_bytes").0 > 0);
+    }
+
+    let err = system_client
+        .query_one(&query("u999999"), &[])
+        .unwrap_db_error();
+    assert_eq!(err.message(), "unknown storage collection \"u999999\"");
+
+    let err = system_client
+        .query_one(
+            "SELECT * FROM mz_internal.mz_shard_metadata('s00000000-0000-0000-0000-000000000000')",
+            &[],
+        )
+        .unwrap_db_error();
+    assert_eq!(
+        err.message(),
+        "shard s00000000-0000-0000-0000-000000000000 does not exist"
+    );
+    let err = system_client
+        .query_one("SELECT * FROM mz_internal.mz_shard_metadata('bogus')", &[])
+        .unwrap_db_error();
+    assert!(err.message().starts_with("invalid shard id \"bogus\""));
+}
+
 #[test]
 fn test_storage_usage_updates_between_restarts() {
     let data_dir = tempfile::tempdir().unwrap();
@@ -872,6 +946,62 @@ fn test_max_request_size() {
     }
 }
 
+// Test that the HTTP SQL API streams results as NDJSON when asked to.
+#[test]
+#[cfg_attr(miri, ignore)] // too slow
+fn test_http_sql_ndjson() {
+    let server = util::start_server(util::Config::default()).unwrap();
+    let http_url = Url::parse(&format!(
+        "http://{}/api/sql",
+        server.inner.http_local_addr()
+    ))
+    .unwrap();
+    let post = |query: &str| {
+        let res = Client::new()
+            .post(http_url.clone())
+            .header(http::header::ACCEPT, "application/x-ndjson")
+            .json(&serde_json::json!({ "query": query }))
+            .send()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()[http::header::CONTENT_TYPE],
+            "application/x-ndjson"
+        );
+        BufReader::new(res).lines().map(|line| line.unwrap())
+    };
+
+    // A single result spanning many batches arrives as one row per line.
+    // `ndjson_streams_rows_in_batches` checks that the batches are sent as
+    // they are encoded.
+    let n_rows = 10_000;
+    let mut lines = post(&format!(
+        "SELECT generate_series(1, {n_rows}) AS a ORDER BY a"
+    ))
+    .map(|line| serde_json::from_str::<WebSocketResponse>(&line).unwrap());
+    let msg = lines.next().unwrap();
+    assert!(matches!(msg, WebSocketResponse::Rows(cols) if cols == ["a"]));
+    for i in 1..=n_rows {
+        let msg = lines.next().unwrap();
+        assert!(matches!(msg, WebSocketResponse::Row(row) if row == [i]));
+    }
+    let msg = lines.next().unwrap();
+    assert!(
+        matches!(msg, WebSocketResponse::CommandComplete(tag) if tag == format!("SELECT {n_rows}"))
+    );
+    assert!(lines.next().is_none());
+
+    // A request that fails as a whole ends with the same structured error that
+    // non-streaming clients receive.
+    let lines: Vec<_> = post("SELECT 1; SELEC 2").collect();
+    let error: serde_json::Value = serde_json::from_str(lines.last().unwrap()).unwrap();
+    assert_eq!(error["error"]["code"], "42601");
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Expected a keyword at the beginning of a statement"));
+}
+
 #[test]
 #[cfg_attr(miri, ignore)] // too slow
 fn test_max_statement_batch_size() {
@@ -913,11 +1043,14 @@ fn test_max_statement_batch_size() {
             res.status().is_client_error(),
             "statement should result in an error: {res:?}"
         );
-        let text = res.text().unwrap();
+        let res: serde_json::Value = res.json().unwrap();
+        let error = &res["error"];
+        assert_eq!(error["code"], SqlState::PROGRAM_LIMIT_EXCEEDED.code());
+        let message = error["message"].as_str().unwrap();
         assert!(
-            text.contains("statement batch size cannot exceed"),
+            message.contains("statement batch size cannot exceed"),
             "error should indicate that the statement was too large: {}",
-            text
+            message
         );
     }
 
@@ -1050,6 +1183,45 @@ fn test_ws_notifies_for_bad_options() {
     };
 }
 
+#[test]
+#[cfg_attr(miri, ignore)] // too slow
+fn test_ws_subprotocol() {
+    let server = util::start_server(util::Config::default()).unwrap();
+    let ws_url = Url::parse(&format!(
+        "ws://{}/api/experimental/sql",
+        server.inner.http_local_addr()
+    ))
+    .unwrap();
+    let request_with_protocol = |protocol: &str| {
+        let mut req = ws_url.as_str().into_client_request().unwrap();
+        req.headers_mut()
+            .insert("Sec-WebSocket-Protocol", protocol.parse().unwrap());
+        req
+    };
+
+    // A supported subprotocol is selected by the server.
+    let (mut ws, resp) = tungstenite::connect(request_with_protocol("mz-sql-v1")).unwrap();
+    assert_eq!(
+        resp.headers().get("Sec-WebSocket-Protocol").unwrap(),
+        WS_SUBPROTOCOLS[0]
+    );
+    util::auth_with_ws(&mut ws, BTreeMap::default());
+
+    // So is a supported subprotocol among unsupported ones.
+    let (_ws, resp) = tungstenite::connect(request_with_protocol("mz-sql-v0, mz-sql-v1")).unwrap();
+    assert_eq!(
+        resp.headers().get("Sec-WebSocket-Protocol").unwrap(),
+        "mz-sql-v1"
+    );
+
+    // Only unsupported subprotocols fail the handshake.
+    match tungstenite::connect(request_with_protocol("mz-sql-v0")) {
+        Err(Error::Http(resp)) => assert_eq!(resp.status(), StatusCode::BAD_REQUEST),
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("handshake with an unsupported subprotocol succeeded"),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct HttpResponse<R> {
     results: Vec<R>,
@@ -1214,6 +1214,47 @@ fn test_subscribe_table_rw_timestamps() {
     assert!(first_write_ts <= second_write_ts);
 }
 
+// Tests that a cursor declared over a table pages through the results in
+// batches, and that closing it releases the cursor.
+#[test]
+fn test_cursor_fetch_batches() {
+    let server = util::start_server(util::Config::default()).unwrap();
+    let mut client = server.connect(postgres::NoTls).unwrap();
+    client
+        .batch_execute("CREATE TABLE t (a int); INSERT INTO t SELECT generate_series(1, 10)")
+        .unwrap();
+
+    client
+        .batch_execute("BEGIN; DECLARE c CURSOR FOR SELECT a FROM t ORDER BY a")
+        .unwrap();
+    let mut fetched = Vec::new();
+    loop {
+        let batch: Vec<i32> = client
+            .query("FETCH 4 c", &[])
+            .unwrap()
+            .into_iter()
+            .map(|row| row.get(0))
+            .collect();
+        assert!(batch.len() <= 4, "batch too large: {batch:?}");
+        if batch.is_empty() {
+            break;
+        }
+        fetched.push(batch);
+    }
+    assert_eq!(
+        fetched,
+        vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10]]
+    );
+
+    client.batch_execute("CLOSE c").unwrap();
+    let err = client.query("FETCH 4 c", &[]).unwrap_db_error();
+    assert!(
+        err.message().starts_with("cursor") && err.message().ends_with("does not exist"),
+        "unexpected error: {}",
+        err.message()
+    );
+}
+
 // Tests that temporary views created by one connection cannot be viewed
 // by another connection.
 #[test]
@@ -1397,6 +1438,55 @@ fn test_utilization_hold() {
     }
 }
 
+// Test that a newly created replica reports its utilization, as collected by
+// the controller from the orchestrator.
+#[test]
+fn test_replica_utilization() {
+    let config = util::Config::default();
+    let server = util::start_server(config).unwrap();
+    let mut client = server.connect(postgres::NoTls).unwrap();
+
+    client
+        .batch_execute("CREATE CLUSTER util REPLICAS (r1 (size '1'))")
+        .unwrap();
+
+    // The controller collects metrics periodically, so wait for the first
+    // collection to include the new replica.
+    let rows = Retry::default()
+        .max_duration(Duration::from_secs(120))
+        .retry(|_| {
+            let rows = client
+                .query(
+                    "SELECT u.cpu_percent, u.memory_percent
+                    FROM mz_internal.mz_cluster_replica_utilization AS u
+                    JOIN mz_cluster_replicas AS r ON u.replica_id = r.id
+                    JOIN mz_clusters AS c ON r.cluster_id = c.id
+                    WHERE c.name = 'util' AND r.name = 'r1'",
+                    &[],
+                )
+                .unwrap();
+            if rows.is_empty() {
+                Err("replica utilization not yet reported")
+            } else {
+                Ok(rows)
+            }
+        })
+        .unwrap();
+
+    // The replica has a single process, which might be idle or have unknown
+    // limits, but never uses a negative amount of resources.
+    assert_eq!(rows.len(), 1);
+    for column in ["cpu_percent", "memory_percent"] {
+        let percent: Option<f64> = rows[0].get(column);
+        if let Some(percent) = percent {
+            assert!(
+                percent.is_finite() && percent >= 0.0,
+                "implausible {column}: {percent}"
+            );
+        }
+    }
+}
+
 // Test that a query that causes a compute instance to panic will resolve
 // the panic and allow the compute instance to restart (instead of crash loop
 // forever) when a client is terminated (disconnects from the server) instead
@@ -1888,6 +1978,98 @@ fn test_alter_system_invalid_param() {
         .contains("unrecognized configuration parameter \"invalid_param\""));
 }
 
+#[test]
+fn test_max_result_columns() {
+    let config = util::Config::default();
+    let server = util::start_server(config).unwrap();
+
+    let mut system_client = server
+        .pg_config_internal()
+        .user(&SYSTEM_USER.name)
+        .connect(postgres::NoTls)
+        .unwrap();
+    system_client
+        .batch_execute("ALTER SYSTEM SET max_result_columns TO 3")
+        .unwrap();
+
+    let mut client = server.connect(postgres::NoTls).unwrap();
+    client
+        .batch_execute("CREATE TABLE t (a int, b int)")
+        .unwrap();
+
+    // Queries at or below the limit succeed.
+    client.query("SELECT 1, 2, 3", &[]).unwrap();
+    client.query("SELECT * FROM t", &[]).unwrap();
+
+    // Queries above the limit fail cleanly, without affecting the connection.
+    let err = client.query("SELECT *, * FROM t", &[]).unwrap_db_error();
+    assert_eq!(err.code(), &SqlState::TOO_MANY_COLUMNS);
+    assert_eq!(
+        err.message(),
+        "result exceeds max column count of 3 (requested: 4)"
+    );
+    assert_eq!(
+        client.query_one("SELECT 1", &[]).unwrap().get::<_, i32>(0),
+        1
+    );
+}
+
+#[test]
+fn test_max_query_cross_joins() {
+    let config = util::Config::default();
+    let server = util::start_server(config).unwrap();
+
+    let mut system_client = server
+        .pg_config_internal()
+        .user(&SYSTEM_USER.name)
+        .connect(postgres::NoTls)
+        .unwrap();
+    system_client
+        .batch_execute("ALTER SYSTEM SET max_query_cross_joins TO 1")
+        .unwrap();
+
+    let mut client = server.connect(postgres::NoTls).unwrap();
+    for name in ["t1", "t2", "t3"] {
+        client
+            .batch_execute(&format!(
+                "CREATE TABLE {name} (a int); \
+                 INSERT INTO {name} SELECT generate_series(1, 1000)"
+            ))
+            .unwrap();
+    }
+
+    // Joins whose inputs are related by join conditions are not cross joins.
+    let count: i64 = client
+        .query_one(
+            "SELECT count(*) FROM t1, t2, t3 WHERE t1.a = t2.a AND t2.a = t3.a AND t1.a < 10",
+            &[],
+        )
+        .unwrap()
+        .get(0);
+    assert_eq!(count, 9);
+
+    // Queries at or below the limit succeed.
+    let count: i64 = client
+        .query_one("SELECT count(*) FROM t1, t2 WHERE t1.a < 10", &[])
+        .unwrap()
+        .get(0);
+    assert_eq!(count, 9000);
+
+    // Queries above the limit fail cleanly, without affecting the connection.
+    let err = client
+        .query("SELECT count(*) FROM t1, t2, t3", &[])
+        .unwrap_db_error();
+    assert_eq!(err.code(), &SqlState::PROGRAM_LIMIT_EXCEEDED);
+    assert_eq!(
+        err.message(),
+        "query exceeds max cross join count of 1 (requested: 2)"
+    );
+    assert_eq!(
+        client.query_one("SELECT 1", &[]).unwrap().get::<_, i32>(0),
+        1
+    );
+}
+
 #[test]
 fn test_concurrent_writes() {
     let config = util::Config::default();
@@ -2972,3 +3154,68 @@ fn test_auto_run_on_introspection_per_replica_relations() {
         .unwrap();
     assert_introspection_notice(false);
 }
+
+// Test that the query result cache serves repeated queries at the same
+// timestamp without executing them again.
+#[test]
+fn test_query_result_cache() {
+    let server = util::start_server(util::Config::default()).unwrap();
+    let mut client = server.connect(postgres::NoTls).unwrap();
+    let mut sys_client = server
+        .pg_config_internal()
+        .user(&SYSTEM_USER.name)
+        .connect(postgres::NoTls)
+        .unwrap();
+    sys_client
+        .batch_execute("ALTER SYSTEM SET query_result_cache_ttl = '1h'")
+        .unwrap();
+
+    let cache_hits = || {
+        server
+            .metrics_registry
+            .gather()
+            .into_iter()
+            .find(|m| m.get_name() == "mz_query_result_cache_hits")
+            .map(|m| m.get_metric()[0].get_counter().get_value())
+            .unwrap_or(0.0)
+    };
+
+    client
+        .batch_execute("CREATE TABLE t (a int); INSERT INTO t VALUES (1), (2), (3)")
+        .unwrap();
+    let ts = util::get_explain_timestamp("t", &mut client);
+    let query = format!("SELECT a FROM t ORDER BY a AS OF {ts}");
+
+    let rows: Vec<i32> = client
+        .query(&query, &[])
+        .unwrap()
+        .into_iter()
+        .map(|row| row.get(0))
+        .collect();
+    assert_eq!(rows, vec![1, 2, 3]);
+    assert_eq!(cache_hits(), 0.0);
+
+    // The result is cached once the first query has sent its rows, which
+    // happens asynchronously.
+    Retry::default()
+        .max_duration(Duration::from_secs(10))
+        .retry(|_| {
+            let cached: Vec<i32> = client
+                .query(&query, &[])
+                .unwrap()
+                .into_iter()
+                .map(|row| row.get(0))
+                .collect();
+            assert_eq!(cached, rows);
+            if cache_hits() == 1.0 {
+                Ok(())
+            } else {
+                Err("result not served from the cache")
+            }
+        })
+        .unwrap();
+
+    // Queries that read at the latest timestamp are not cached.
+    client.query("SELECT a FROM t", &[]).unwrap();
+    assert_eq!(cache_hits(), 1.0);
+}
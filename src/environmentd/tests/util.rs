@@ -337,6 +337,10 @@ pub fn start_server(config: Config) -> Result<Server, anyhow::Error> {
             now: SYSTEM_TIME.clone(),
             postgres_factory,
             metrics_registry: metrics_registry.clone(),
+            compute_reconnect: Default::default(),
+            storage_rehydration: Default::default(),
+            replica_service_ports: Default::default(),
+            replica_service_retry: Default::default(),
         },
         secrets_controller,
         cloud_resource_controller: None,
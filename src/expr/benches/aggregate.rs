@@ -0,0 +1,303 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+// BEGIN LINT CONFIG
+// DO NOT EDIT. Automatically generated by bin/gen-lints.
+// Have complaints about the noise? See the note in misc/python/materialize/cli/gen-lints.py first.
+#![allow(clippy::style)]
+#![allow(clippy::complexity)]
+#![allow(clippy::large_enum_variant)]
+#![allow(clippy::mutable_key_type)]
+#![allow(clippy::stable_sort_primitive)]
+#![allow(clippy::map_entry)]
+#![allow(clippy::box_default)]
+#![warn(clippy::bool_comparison)]
+#![warn(clippy::clone_on_ref_ptr)]
+#![warn(clippy::no_effect)]
+#![warn(clippy::unnecessary_unwrap)]
+#![warn(clippy::dbg_macro)]
+#![warn(clippy::todo)]
+#![warn(clippy::wildcard_dependencies)]
+#![warn(clippy::zero_prefixed_literal)]
+#![warn(clippy::borrowed_box)]
+#![warn(clippy::deref_addrof)]
+#![warn(clippy::double_must_use)]
+#![warn(clippy::double_parens)]
+#![warn(clippy::extra_unused_lifetimes)]
+#![warn(clippy::needless_borrow)]
+#![warn(clippy::needless_question_mark)]
+#![warn(clippy::needless_return)]
+#![warn(clippy::redundant_pattern)]
+#![warn(clippy::redundant_slicing)]
+#![warn(clippy::redundant_static_lifetimes)]
+#![warn(clippy::single_component_path_imports)]
+#![warn(clippy::unnecessary_cast)]
+#![warn(clippy::useless_asref)]
+#![warn(clippy::useless_conversion)]
+#![warn(clippy::builtin_type_shadow)]
+#![warn(clippy::duplicate_underscore_argument)]
+#![warn(clippy::double_neg)]
+#![warn(clippy::unnecessary_mut_passed)]
+#![warn(clippy::wildcard_in_or_patterns)]
+#![warn(clippy::crosspointer_transmute)]
+#![warn(clippy::excessive_precision)]
+#![warn(clippy::overflow_check_conditional)]
+#![warn(clippy::as_conversions)]
+#![warn(clippy::match_overlapping_arm)]
+#![warn(clippy::zero_divided_by_zero)]
+#![warn(clippy::must_use_unit)]
+#![warn(clippy::suspicious_assignment_formatting)]
+#![warn(clippy::suspicious_else_formatting)]
+#![warn(clippy::suspicious_unary_op_formatting)]
+#![warn(clippy::mut_mutex_lock)]
+#![warn(clippy::print_literal)]
+#![warn(clippy::same_item_push)]
+#![warn(clippy::useless_format)]
+#![warn(clippy::write_literal)]
+#![warn(clippy::redundant_closure)]
+#![warn(clippy::redundant_closure_call)]
+#![warn(clippy::unnecessary_lazy_evaluations)]
+#![warn(clippy::partialeq_ne_impl)]
+#![warn(clippy::redundant_field_names)]
+#![warn(clippy::transmutes_expressible_as_ptr_casts)]
+#![warn(clippy::unused_async)]
+#![warn(clippy::disallowed_methods)]
+#![warn(clippy::disallowed_macros)]
+#![warn(clippy::disallowed_types)]
+#![warn(clippy::from_over_into)]
+// END LINT CONFIG
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use mz_expr::{AggregateFunc, ColumnOrder, LagLeadType};
+use mz_repr::adt::numeric::Numeric;
+use mz_repr::{ColumnName, ColumnType, Datum, RowArena, ScalarType};
+
+/// The number of input datums to each aggregate.
+const N: i32 = 100_000;
+
+/// Benchmarks the max, min, and sum aggregates over `N` datums produced by
+/// `datum`, every tenth of which is null.
+fn bench_type<'a, F>(c: &mut Criterion, name: &str, funcs: [AggregateFunc; 3], datum: F)
+where
+    F: Fn(i32) -> Datum<'a>,
+{
+    let datums: Vec<_> = (0..N)
+        .map(|i| if i % 10 == 0 { Datum::Null } else { datum(i) })
+        .collect();
+    let arena = RowArena::new();
+    let mut group = c.benchmark_group("aggregate");
+    for func in funcs {
+        group.bench_function(format!("{func}_{name}"), |b| {
+            b.iter(|| func.eval(black_box(datums.iter().copied()), &arena))
+        });
+    }
+}
+
+pub fn bench_int32(c: &mut Criterion) {
+    let funcs = [
+        AggregateFunc::MaxInt32,
+        AggregateFunc::MinInt32,
+        AggregateFunc::SumInt32,
+    ];
+    bench_type(c, "int32", funcs, Datum::Int32);
+}
+
+pub fn bench_int64(c: &mut Criterion) {
+    let funcs = [
+        AggregateFunc::MaxInt64,
+        AggregateFunc::MinInt64,
+        AggregateFunc::SumInt64,
+    ];
+    bench_type(c, "int64", funcs, |i| Datum::Int64(i64::from(i)));
+}
+
+pub fn bench_float64(c: &mut Criterion) {
+    let funcs = [
+        AggregateFunc::MaxFloat64,
+        AggregateFunc::MinFloat64,
+        AggregateFunc::SumFloat64,
+    ];
+    bench_type(c, "float64", funcs, |i| Datum::from(f64::from(i)));
+}
+
+pub fn bench_count(c: &mut Criterion) {
+    let datums: Vec<_> = (0..N)
+        .map(|i| {
+            if i % 10 == 0 {
+                Datum::Null
+            } else {
+                Datum::Int32(i)
+            }
+        })
+        .collect();
+    let arena = RowArena::new();
+    c.bench_function("aggregate/count", |b| {
+        b.iter(|| AggregateFunc::Count.eval(black_box(datums.iter().copied()), &arena))
+    });
+}
+
+pub fn bench_numeric(c: &mut Criterion) {
+    let funcs = [
+        AggregateFunc::MaxNumeric,
+        AggregateFunc::MinNumeric,
+        AggregateFunc::SumNumeric,
+    ];
+    bench_type(c, "numeric", funcs, |i| Datum::from(Numeric::from(i)));
+}
+
+/// Benchmarks the string_agg and jsonb_agg aggregates over a single group of
+/// `N` datums, with and without an ORDER BY. Without an ORDER BY, the datums
+/// are streamed into the result rather than collected and sorted first.
+pub fn bench_agg_order_by(c: &mut Criterion) {
+    let arena = RowArena::new();
+    let record = |datums: &[Datum]| arena.make_datum(|packer| packer.push_list(datums));
+    let strings: Vec<_> = (0..N).map(|i| (i, i.to_string())).collect();
+    let order_by = vec![ColumnOrder {
+        column: 0,
+        desc: true,
+        nulls_last: false,
+    }];
+
+    let mut group = c.benchmark_group("aggregate");
+    for (name, order_by) in [("unordered", vec![]), ("ordered", order_by)] {
+        // Each datum is a record of the aggregated value, followed by the
+        // ORDER BY expressions, if any.
+        let aggregate_record = |value, i| {
+            if order_by.is_empty() {
+                record(&[value])
+            } else {
+                record(&[value, Datum::Int32(i)])
+            }
+        };
+
+        let datums: Vec<_> = strings
+            .iter()
+            .map(|(i, s)| {
+                let value = record(&[Datum::String(s), Datum::String(",")]);
+                aggregate_record(value, *i)
+            })
+            .collect();
+        let func = AggregateFunc::StringAgg {
+            order_by: order_by.clone(),
+        };
+        group.bench_function(format!("string_agg_{name}"), |b| {
+            b.iter(|| {
+                // Evaluate into a fresh arena, so results don't accumulate
+                // across iterations.
+                let temp_storage = RowArena::new();
+                black_box(func.eval(datums.iter().map(|d| *d), &temp_storage));
+            })
+        });
+
+        let datums: Vec<_> = strings
+            .iter()
+            .map(|(i, s)| aggregate_record(Datum::String(s), *i))
+            .collect();
+        let func = AggregateFunc::JsonbAgg {
+            order_by: order_by.clone(),
+        };
+        group.bench_function(format!("jsonb_agg_{name}"), |b| {
+            b.iter(|| {
+                // Evaluate into a fresh arena, so results don't accumulate
+                // across iterations.
+                let temp_storage = RowArena::new();
+                black_box(func.eval(datums.iter().map(|d| *d), &temp_storage));
+            })
+        });
+    }
+}
+
+/// Benchmarks typing a relation with many window functions over a wide input
+/// row, each of which nests the entire row in its output type.
+pub fn bench_window_output_type(c: &mut Criterion) {
+    let record = |fields: Vec<ColumnType>| ScalarType::Record {
+        fields: fields
+            .into_iter()
+            .enumerate()
+            .map(|(i, typ)| (ColumnName::from(format!("c{i}")), typ))
+            .collect(),
+        custom_id: None,
+    };
+    let list = |element_type| ScalarType::List {
+        element_type: Box::new(element_type),
+        custom_id: None,
+    };
+    let original_row = record(
+        (0..100)
+            .map(|_| ScalarType::String.nullable(true))
+            .collect(),
+    );
+    let order_by_type = ScalarType::Int32.nullable(true);
+    // (List<OriginalRow>, OrderByExprs...)
+    let ranking_input = record(vec![
+        list(original_row.clone()).nullable(false),
+        order_by_type.clone(),
+    ])
+    .nullable(false);
+    // ((OriginalRow, (InputValue, Offset, Default)), OrderByExprs...)
+    let lag_args = record(vec![
+        ScalarType::String.nullable(true),
+        ScalarType::Int32.nullable(false),
+        ScalarType::String.nullable(true),
+    ]);
+    let lag_input = record(vec![
+        record(vec![original_row.nullable(false), lag_args.nullable(false)]).nullable(false),
+        order_by_type,
+    ])
+    .nullable(false);
+
+    let order_by = vec![ColumnOrder {
+        column: 0,
+        desc: false,
+        nulls_last: false,
+    }];
+    let funcs: Vec<_> = (0..100)
+        .map(|i| match i % 3 {
+            0 => (
+                AggregateFunc::RowNumber {
+                    order_by: order_by.clone(),
+                },
+                ranking_input.clone(),
+            ),
+            1 => (
+                AggregateFunc::DenseRank {
+                    order_by: order_by.clone(),
+                },
+                ranking_input.clone(),
+            ),
+            _ => (
+                AggregateFunc::LagLead {
+                    order_by: order_by.clone(),
+                    lag_lead: LagLeadType::Lag,
+                    ignore_nulls: false,
+                },
+                lag_input.clone(),
+            ),
+        })
+        .collect();
+    c.bench_function("aggregate/window_output_type", |b| {
+        b.iter(|| {
+            for (func, input_type) in &funcs {
+                black_box(func.output_type(input_type.clone()));
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_int32,
+    bench_int64,
+    bench_float64,
+    bench_numeric,
+    bench_count,
+    bench_agg_order_by,
+    bench_window_output_type
+);
+criterion_main!(benches);
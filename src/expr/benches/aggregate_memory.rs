@@ -0,0 +1,265 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+// BEGIN LINT CONFIG
+// DO NOT EDIT. Automatically generated by bin/gen-lints.
+// Have complaints about the noise? See the note in misc/python/materialize/cli/gen-lints.py first.
+#![allow(clippy::style)]
+#![allow(clippy::complexity)]
+#![allow(clippy::large_enum_variant)]
+#![allow(clippy::mutable_key_type)]
+#![allow(clippy::stable_sort_primitive)]
+#![allow(clippy::map_entry)]
+#![allow(clippy::box_default)]
+#![warn(clippy::bool_comparison)]
+#![warn(clippy::clone_on_ref_ptr)]
+#![warn(clippy::no_effect)]
+#![warn(clippy::unnecessary_unwrap)]
+#![warn(clippy::dbg_macro)]
+#![warn(clippy::todo)]
+#![warn(clippy::wildcard_dependencies)]
+#![warn(clippy::zero_prefixed_literal)]
+#![warn(clippy::borrowed_box)]
+#![warn(clippy::deref_addrof)]
+#![warn(clippy::double_must_use)]
+#![warn(clippy::double_parens)]
+#![warn(clippy::extra_unused_lifetimes)]
+#![warn(clippy::needless_borrow)]
+#![warn(clippy::needless_question_mark)]
+#![warn(clippy::needless_return)]
+#![warn(clippy::redundant_pattern)]
+#![warn(clippy::redundant_slicing)]
+#![warn(clippy::redundant_static_lifetimes)]
+#![warn(clippy::single_component_path_imports)]
+#![warn(clippy::unnecessary_cast)]
+#![warn(clippy::useless_asref)]
+#![warn(clippy::useless_conversion)]
+#![warn(clippy::builtin_type_shadow)]
+#![warn(clippy::duplicate_underscore_argument)]
+#![warn(clippy::double_neg)]
+#![warn(clippy::unnecessary_mut_passed)]
+#![warn(clippy::wildcard_in_or_patterns)]
+#![warn(clippy::crosspointer_transmute)]
+#![warn(clippy::excessive_precision)]
+#![warn(clippy::overflow_check_conditional)]
+#![warn(clippy::as_conversions)]
+#![warn(clippy::match_overlapping_arm)]
+#![warn(clippy::zero_divided_by_zero)]
+#![warn(clippy::must_use_unit)]
+#![warn(clippy::suspicious_assignment_formatting)]
+#![warn(clippy::suspicious_else_formatting)]
+#![warn(clippy::suspicious_unary_op_formatting)]
+#![warn(clippy::mut_mutex_lock)]
+#![warn(clippy::print_literal)]
+#![warn(clippy::same_item_push)]
+#![warn(clippy::useless_format)]
+#![warn(clippy::write_literal)]
+#![warn(clippy::redundant_closure)]
+#![warn(clippy::redundant_closure_call)]
+#![warn(clippy::unnecessary_lazy_evaluations)]
+#![warn(clippy::partialeq_ne_impl)]
+#![warn(clippy::redundant_field_names)]
+#![warn(clippy::transmutes_expressible_as_ptr_casts)]
+#![warn(clippy::unused_async)]
+#![warn(clippy::disallowed_methods)]
+#![warn(clippy::disallowed_macros)]
+#![warn(clippy::disallowed_types)]
+#![warn(clippy::from_over_into)]
+// END LINT CONFIG
+
+//! Benchmarks the peak memory usage, rather than the running time, of
+//! aggregates.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use mz_expr::{AggregateFunc, ColumnOrder};
+use mz_ore::cast::CastLossy;
+use mz_repr::{Datum, RowArena};
+
+/// The number of input datums to each aggregate.
+const N: i32 = 100_000;
+
+/// The number of bytes currently allocated.
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// The largest number of bytes allocated at once since the last reset.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Wraps the system allocator to keep track of [`ALLOCATED`] and [`PEAK`].
+struct CountingAllocator;
+
+impl CountingAllocator {
+    fn grow(by: usize) {
+        let allocated = ALLOCATED.fetch_add(by, Ordering::Relaxed) + by;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    fn shrink(by: usize) {
+        ALLOCATED.fetch_sub(by, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                Self::grow(new_size - layout.size());
+            } else {
+                Self::shrink(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Measures the peak number of bytes allocated on top of those allocated when
+/// the measurement starts.
+struct PeakAllocated;
+
+impl Measurement for PeakAllocated {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> usize {
+        let allocated = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(allocated, Ordering::Relaxed);
+        allocated
+    }
+
+    fn end(&self, start: usize) -> usize {
+        PEAK.load(Ordering::Relaxed).saturating_sub(start)
+    }
+
+    fn add(&self, v1: &usize, v2: &usize) -> usize {
+        v1 + v2
+    }
+
+    fn zero(&self) -> usize {
+        0
+    }
+
+    fn to_f64(&self, value: &usize) -> f64 {
+        f64::cast_lossy(*value)
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &BytesFormatter
+    }
+}
+
+/// Formats byte counts with binary prefixes.
+struct BytesFormatter;
+
+impl ValueFormatter for BytesFormatter {
+    fn scale_values(&self, typical_value: f64, values: &mut [f64]) -> &'static str {
+        let (factor, unit) = if typical_value < 1024.0 {
+            (1.0, "B")
+        } else if typical_value < 1024.0 * 1024.0 {
+            (1024.0, "KiB")
+        } else {
+            (1024.0 * 1024.0, "MiB")
+        };
+        for value in values {
+            *value /= factor;
+        }
+        unit
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        _throughput: &Throughput,
+        _values: &mut [f64],
+    ) -> &'static str {
+        unimplemented!("peak memory benchmarks do not report throughput")
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "B"
+    }
+}
+
+/// Benchmarks the peak memory usage of the row_number and dense_rank window
+/// functions over a single partition of `N` datums, with a run of ten peers for
+/// every ORDER BY value.
+fn bench_ranking(c: &mut Criterion<PeakAllocated>) {
+    let arena = RowArena::new();
+    // Each datum is a record of the list of original rows, followed by the
+    // ORDER BY expression.
+    let datums: Vec<_> = (0..N)
+        .map(|i| {
+            arena.make_datum(|packer| {
+                packer.push_list_with(|packer| {
+                    packer.push_list([Datum::Int32(i)]);
+                    packer.push(Datum::Int32(i / 10));
+                })
+            })
+        })
+        .collect();
+    let order_by = vec![ColumnOrder {
+        column: 0,
+        desc: true,
+        nulls_last: false,
+    }];
+
+    let mut group = c.benchmark_group("aggregate_memory");
+    for func in [
+        AggregateFunc::RowNumber {
+            order_by: order_by.clone(),
+        },
+        AggregateFunc::DenseRank {
+            order_by: order_by.clone(),
+        },
+    ] {
+        group.bench_function(format!("{func}"), |b| {
+            // Criterion reports the value per iteration, so sum the peaks of
+            // the individual evaluations rather than measuring the peak across
+            // all of them.
+            b.iter_custom(|iters| {
+                (0..iters)
+                    .map(|_| {
+                        let start = PeakAllocated.start();
+                        // Evaluate into a fresh arena, so that the peak includes
+                        // the memory holding the result.
+                        let temp_storage = RowArena::new();
+                        black_box(func.eval(datums.iter().copied(), &temp_storage));
+                        drop(temp_storage);
+                        PeakAllocated.end(start)
+                    })
+                    .sum()
+            })
+        });
+    }
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().with_measurement(PeakAllocated);
+    targets = bench_ranking
+);
+criterion_main!(benches);
@@ -93,6 +93,7 @@ pub mod explain;
 pub mod virtual_syntax;
 pub mod visit;
 
+pub use relation::accumulator::Accumulator;
 pub use relation::canonicalize;
 
 pub use id::{Id, LocalId, PartitionId, SourceInstanceId};
@@ -104,7 +105,9 @@ pub use linear::{
     MapFilterProject, ProtoMapFilterProject, ProtoMfpPlan, ProtoSafeMfpPlan,
 };
 pub use relation::func::{AggregateFunc, LagLeadType, TableFunc};
-pub use relation::func::{AnalyzedRegex, CaptureGroupDesc};
+pub use relation::func::{
+    AnalyzedRegex, CaptureGroupDesc, CsvExtractOptions, JsonPath, JsonPathStep,
+};
 pub use relation::join_input_mapper::JoinInputMapper;
 pub use relation::{
     compare_columns, AggregateExpr, CollectionPlan, ColumnOrder, JoinImplementation,
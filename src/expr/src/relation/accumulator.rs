@@ -0,0 +1,511 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Incrementally maintained aggregates.
+//!
+//! An [`Accumulator`] summarizes the datums an aggregate has seen so far, so
+//! that the aggregate can be updated as datums are added and retracted, and
+//! partial aggregates can be combined, without folding over every datum again.
+//! See [`AggregateFunc::accumulator`](crate::AggregateFunc::accumulator) for
+//! the aggregates that support this.
+
+use std::any::Any;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use mz_repr::{Datum, Diff};
+
+use crate::EvalError;
+
+/// The state of an aggregate that can be maintained incrementally.
+///
+/// Accumulating datums with [`Accumulator::add`] and then calling
+/// [`Accumulator::finish`] produces the same datum as evaluating the aggregate
+/// over those datums, each repeated as many times as its accumulated diff.
+/// Accumulation is associative and commutative, so datums may be added and
+/// partial accumulators merged in any order.
+///
+/// Partial accumulators can hold states that no input produces, e.g. an
+/// unsigned sum that has only seen retractions. Finishing such a state returns
+/// an error rather than panicking.
+pub trait Accumulator: fmt::Debug + Send {
+    /// Accumulates `datum` with multiplicity `diff`. A negative `diff` retracts
+    /// the datum.
+    fn add(&mut self, datum: Datum<'_>, diff: Diff);
+
+    /// Accumulates all the datums that `other` has accumulated.
+    ///
+    /// Panics if `other` is not an accumulator of the same aggregate.
+    fn merge(&mut self, other: &dyn Accumulator);
+
+    /// Returns the value of the aggregate over the datums accumulated so far,
+    /// or an error if that value does not fit the aggregate's output type.
+    fn finish(&self) -> Result<Datum<'_>, EvalError>;
+
+    /// Returns the accumulator as [`Any`], so that [`Accumulator::merge`] can
+    /// recover its concrete type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Downcasts `other` to the type of an accumulator it is being merged into.
+fn downcast<T: 'static>(other: &dyn Accumulator) -> &T {
+    other
+        .as_any()
+        .downcast_ref()
+        .expect("merged accumulators of different aggregates")
+}
+
+/// Accumulates the number of non-null datums.
+#[derive(Debug, Default)]
+pub(crate) struct CountAccumulator {
+    count: Diff,
+}
+
+impl Accumulator for CountAccumulator {
+    fn add(&mut self, datum: Datum<'_>, diff: Diff) {
+        if !datum.is_null() {
+            self.count += diff;
+        }
+    }
+
+    fn merge(&mut self, other: &dyn Accumulator) {
+        self.count += downcast::<Self>(other).count;
+    }
+
+    fn finish(&self) -> Result<Datum<'_>, EvalError> {
+        Ok(Datum::Int64(self.count))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Accumulates the sum of integer datums.
+///
+/// The sum is kept in an `i128`, so that retractions are exact even when an
+/// intermediate sum would not fit the aggregate's output type. Only large
+/// multiplicities can overflow the `i128`. Such an overflow is sticky: the sum
+/// is lost, and finishing fails even if later retractions would bring the sum
+/// back into range.
+#[derive(Debug)]
+pub(crate) struct IntegerSumAccumulator {
+    /// The sum, or `None` if it overflowed.
+    sum: Option<i128>,
+    non_nulls: Diff,
+    /// Builds the sum of a non-empty input, or fails if the sum overflowed or
+    /// does not fit the output type.
+    wrap: fn(Option<i128>) -> Result<Datum<'static>, EvalError>,
+}
+
+impl IntegerSumAccumulator {
+    pub(crate) fn new(wrap: fn(Option<i128>) -> Result<Datum<'static>, EvalError>) -> Self {
+        IntegerSumAccumulator {
+            sum: Some(0),
+            non_nulls: 0,
+            wrap,
+        }
+    }
+}
+
+impl Accumulator for IntegerSumAccumulator {
+    fn add(&mut self, datum: Datum<'_>, diff: Diff) {
+        let value = match datum {
+            Datum::Null => return,
+            Datum::Int16(x) => i128::from(x),
+            Datum::Int32(x) => i128::from(x),
+            Datum::Int64(x) => i128::from(x),
+            Datum::UInt16(x) => i128::from(x),
+            Datum::UInt32(x) => i128::from(x),
+            Datum::UInt64(x) => i128::from(x),
+            d => panic!("unexpected datum {d:?} in an integer sum"),
+        };
+        self.sum = self
+            .sum
+            .and_then(|sum| sum.checked_add(value.checked_mul(i128::from(diff))?));
+        self.non_nulls += diff;
+    }
+
+    fn merge(&mut self, other: &dyn Accumulator) {
+        let other = downcast::<Self>(other);
+        self.sum = self
+            .sum
+            .zip(other.sum)
+            .and_then(|(sum, other_sum)| sum.checked_add(other_sum));
+        self.non_nulls += other.non_nulls;
+    }
+
+    fn finish(&self) -> Result<Datum<'_>, EvalError> {
+        if self.non_nulls == 0 {
+            Ok(Datum::Null)
+        } else {
+            (self.wrap)(self.sum)
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Accumulates the disjunction or conjunction of boolean datums.
+///
+/// Counting each truth value, rather than folding them, is what allows a
+/// retracted `true` to turn an `any` back to `false`.
+#[derive(Debug)]
+pub(crate) struct LogicalAccumulator {
+    trues: Diff,
+    falses: Diff,
+    nulls: Diff,
+    /// Whether this is a disjunction, rather than a conjunction.
+    any: bool,
+    /// Whether the aggregate of an empty input is null, rather than the
+    /// identity of the operation.
+    null_if_empty: bool,
+}
+
+impl LogicalAccumulator {
+    pub(crate) fn new(any: bool, null_if_empty: bool) -> Self {
+        LogicalAccumulator {
+            trues: 0,
+            falses: 0,
+            nulls: 0,
+            any,
+            null_if_empty,
+        }
+    }
+}
+
+impl Accumulator for LogicalAccumulator {
+    fn add(&mut self, datum: Datum<'_>, diff: Diff) {
+        match datum {
+            Datum::True => self.trues += diff,
+            Datum::False => self.falses += diff,
+            Datum::Null => self.nulls += diff,
+            d => panic!("unexpected datum {d:?} in a logical aggregate"),
+        }
+    }
+
+    fn merge(&mut self, other: &dyn Accumulator) {
+        let other = downcast::<Self>(other);
+        self.trues += other.trues;
+        self.falses += other.falses;
+        self.nulls += other.nulls;
+    }
+
+    fn finish(&self) -> Result<Datum<'_>, EvalError> {
+        // `true` decides a disjunction, and `false` a conjunction.
+        let deciding = if self.any { self.trues } else { self.falses };
+        let empty = self.trues + self.falses + self.nulls == 0;
+        let result = if deciding > 0 {
+            Datum::from(self.any)
+        } else if self.nulls > 0 || (empty && self.null_if_empty) {
+            Datum::Null
+        } else {
+            Datum::from(!self.any)
+        };
+        Ok(result)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Accumulates the maximum or minimum of fixed-width datums.
+///
+/// Each distinct value is counted, so that retracting the current extremum
+/// reveals the next one.
+#[derive(Debug)]
+pub(crate) struct ExtremumAccumulator {
+    counts: BTreeMap<Datum<'static>, Diff>,
+    /// Whether this is a maximum, rather than a minimum.
+    max: bool,
+}
+
+impl ExtremumAccumulator {
+    pub(crate) fn new(max: bool) -> Self {
+        ExtremumAccumulator {
+            counts: BTreeMap::new(),
+            max,
+        }
+    }
+
+    fn update(&mut self, value: Datum<'static>, diff: Diff) {
+        match self.counts.entry(value) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += diff;
+                if *entry.get() == 0 {
+                    entry.remove();
+                }
+            }
+            Entry::Vacant(entry) => {
+                if diff != 0 {
+                    entry.insert(diff);
+                }
+            }
+        }
+    }
+}
+
+impl Accumulator for ExtremumAccumulator {
+    fn add(&mut self, datum: Datum<'_>, diff: Diff) {
+        if !datum.is_null() {
+            self.update(fixed_width(datum), diff);
+        }
+    }
+
+    fn merge(&mut self, other: &dyn Accumulator) {
+        for (value, diff) in &downcast::<Self>(other).counts {
+            self.update(*value, *diff);
+        }
+    }
+
+    fn finish(&self) -> Result<Datum<'_>, EvalError> {
+        let mut present = self
+            .counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(value, _)| *value);
+        let extremum = if self.max {
+            present.next_back()
+        } else {
+            present.next()
+        };
+        Ok(extremum.unwrap_or(Datum::Null))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Copies a datum that does not borrow its data out of its row.
+fn fixed_width(datum: Datum<'_>) -> Datum<'static> {
+    match datum {
+        Datum::False => Datum::False,
+        Datum::True => Datum::True,
+        Datum::Int16(x) => Datum::Int16(x),
+        Datum::Int32(x) => Datum::Int32(x),
+        Datum::Int64(x) => Datum::Int64(x),
+        Datum::UInt16(x) => Datum::UInt16(x),
+        Datum::UInt32(x) => Datum::UInt32(x),
+        Datum::UInt64(x) => Datum::UInt64(x),
+        Datum::Float32(x) => Datum::Float32(x),
+        Datum::Float64(x) => Datum::Float64(x),
+        Datum::Numeric(x) => Datum::Numeric(x),
+        Datum::Date(x) => Datum::Date(x),
+        Datum::Time(x) => Datum::Time(x),
+        Datum::Timestamp(x) => Datum::Timestamp(x),
+        Datum::TimestampTz(x) => Datum::TimestampTz(x),
+        Datum::Interval(x) => Datum::Interval(x),
+        Datum::Uuid(x) => Datum::Uuid(x),
+        Datum::MzTimestamp(x) => Datum::MzTimestamp(x),
+        d => panic!("unexpected datum {d:?} in an extremum"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_repr::adt::numeric::Numeric;
+    use mz_repr::{Datum, RowArena};
+
+    use crate::{AggregateFunc, EvalError};
+
+    /// Asserts that accumulating `datums` with unit diffs, and also in two
+    /// halves that are merged, finishes with the aggregate's value over them.
+    fn assert_accumulates(func: &AggregateFunc, datums: &[Datum]) {
+        let arena = RowArena::new();
+        let expected = func.eval(datums.iter().copied(), &arena);
+
+        let mut accumulator = func.accumulator().unwrap();
+        for datum in datums {
+            accumulator.add(*datum, 1);
+        }
+        assert_eq!(accumulator.finish(), Ok(expected), "{func} over {datums:?}");
+
+        let (left, right) = datums.split_at(datums.len() / 2);
+        let mut left_accumulator = func.accumulator().unwrap();
+        let mut right_accumulator = func.accumulator().unwrap();
+        for datum in left {
+            left_accumulator.add(*datum, 1);
+        }
+        for datum in right {
+            right_accumulator.add(*datum, 1);
+        }
+        left_accumulator.merge(&*right_accumulator);
+        assert_eq!(
+            left_accumulator.finish(),
+            Ok(expected),
+            "{func} over {datums:?}"
+        );
+    }
+
+    #[test]
+    fn accumulators_agree_with_eval() {
+        let ints = [
+            Datum::Int32(3),
+            Datum::Null,
+            Datum::Int32(-7),
+            Datum::Int32(3),
+        ];
+        let bools = [Datum::True, Datum::Null, Datum::False];
+        let cases: [(AggregateFunc, &[Datum]); 15] = [
+            (AggregateFunc::Count, &ints),
+            (AggregateFunc::SumInt32, &ints),
+            (AggregateFunc::MaxInt32, &ints),
+            (AggregateFunc::MinInt32, &ints),
+            (
+                AggregateFunc::SumInt16,
+                &[Datum::Int16(i16::MAX), Datum::Int16(1)],
+            ),
+            (
+                AggregateFunc::SumInt64,
+                &[Datum::Int64(i64::MAX), Datum::Int64(1)],
+            ),
+            (
+                AggregateFunc::SumUInt32,
+                &[Datum::UInt32(u32::MAX), Datum::UInt32(1)],
+            ),
+            (
+                AggregateFunc::SumUInt64,
+                &[Datum::UInt64(u64::MAX), Datum::UInt64(1)],
+            ),
+            (
+                AggregateFunc::MaxFloat64,
+                &[Datum::from(1.0), Datum::from(f64::NAN)],
+            ),
+            (
+                AggregateFunc::MinFloat64,
+                &[Datum::from(1.0), Datum::from(f64::NAN)],
+            ),
+            (
+                AggregateFunc::MaxNumeric,
+                &[
+                    Datum::from(Numeric::from(2)),
+                    Datum::from(Numeric::from(-2)),
+                ],
+            ),
+            (AggregateFunc::Any, &bools),
+            (AggregateFunc::All, &bools),
+            (AggregateFunc::BoolOr, &bools[1..]),
+            (AggregateFunc::BoolAnd, &bools[..2]),
+        ];
+        for (func, datums) in cases {
+            assert_accumulates(&func, datums);
+            assert_accumulates(&func, &datums[..1]);
+            assert_accumulates(&func, &[]);
+            assert_accumulates(&func, &[Datum::Null]);
+        }
+    }
+
+    #[test]
+    fn accumulators_retract() {
+        let mut max = AggregateFunc::MaxInt32.accumulator().unwrap();
+        max.add(Datum::Int32(1), 1);
+        max.add(Datum::Int32(5), 2);
+        assert_eq!(max.finish(), Ok(Datum::Int32(5)));
+        max.add(Datum::Int32(5), -1);
+        assert_eq!(max.finish(), Ok(Datum::Int32(5)));
+        max.add(Datum::Int32(5), -1);
+        assert_eq!(max.finish(), Ok(Datum::Int32(1)));
+        max.add(Datum::Int32(1), -1);
+        assert_eq!(max.finish(), Ok(Datum::Null));
+
+        let mut sum = AggregateFunc::SumInt32.accumulator().unwrap();
+        sum.add(Datum::Int32(4), 3);
+        sum.add(Datum::Int32(4), -1);
+        assert_eq!(sum.finish(), Ok(Datum::Int64(8)));
+        sum.add(Datum::Int32(4), -2);
+        assert_eq!(sum.finish(), Ok(Datum::Null));
+
+        let mut any = AggregateFunc::Any.accumulator().unwrap();
+        any.add(Datum::False, 1);
+        any.add(Datum::True, 1);
+        assert_eq!(any.finish(), Ok(Datum::True));
+        any.add(Datum::True, -1);
+        assert_eq!(any.finish(), Ok(Datum::False));
+
+        // A partial accumulator may hold retractions of datums added to another.
+        let mut count = AggregateFunc::Count.accumulator().unwrap();
+        let mut retractions = AggregateFunc::Count.accumulator().unwrap();
+        count.add(Datum::Int32(1), 2);
+        retractions.add(Datum::Int32(1), -1);
+        count.merge(&*retractions);
+        assert_eq!(count.finish(), Ok(Datum::Int64(1)));
+    }
+
+    #[test]
+    fn retraction_only_sums_do_not_panic() {
+        // A partial accumulator that has only seen retractions holds a
+        // negative sum, which an unsigned sum cannot finish into.
+        let mut sum = AggregateFunc::SumUInt32.accumulator().unwrap();
+        sum.add(Datum::UInt32(5), -1);
+        assert_eq!(sum.finish(), Err(EvalError::UInt64OutOfRange));
+        let mut additions = AggregateFunc::SumUInt32.accumulator().unwrap();
+        additions.add(Datum::UInt32(5), 2);
+        sum.merge(&*additions);
+        assert_eq!(sum.finish(), Ok(Datum::UInt64(5)));
+
+        // Large multiplicities can take a sum of 32-bit integers out of the
+        // range of its output type.
+        let mut sum = AggregateFunc::SumInt32.accumulator().unwrap();
+        sum.add(Datum::Int32(i32::MIN), i64::MAX);
+        assert_eq!(sum.finish(), Err(EvalError::Int64OutOfRange));
+        sum.add(Datum::Int32(i32::MIN), 1 - i64::MAX);
+        assert_eq!(sum.finish(), Ok(Datum::Int64(i64::from(i32::MIN))));
+
+        // Unsigned sums of either width reject a negative sum.
+        let mut sum = AggregateFunc::SumUInt64.accumulator().unwrap();
+        sum.add(Datum::UInt64(5), -1);
+        assert_eq!(sum.finish(), Err(EvalError::UInt64OutOfRange));
+    }
+
+    #[test]
+    fn overflowing_sums_stay_overflowed() {
+        // Two maximal products approach `i128::MAX`, and a third overflows it.
+        let mut sum = AggregateFunc::SumInt64.accumulator().unwrap();
+        sum.add(Datum::Int64(i64::MAX), i64::MAX);
+        sum.add(Datum::Int64(i64::MAX), i64::MAX);
+        assert_eq!(
+            sum.finish(),
+            Ok(Datum::from(2 * i128::from(i64::MAX) * i128::from(i64::MAX)))
+        );
+        sum.add(Datum::Int64(i64::MAX), i64::MAX);
+        assert_eq!(sum.finish(), Err(EvalError::NumericFieldOverflow));
+
+        // The overflow is sticky, even once the sum would be back in range.
+        sum.add(Datum::Int64(i64::MAX), -i64::MAX);
+        assert_eq!(sum.finish(), Err(EvalError::NumericFieldOverflow));
+
+        // Merging overflows as well.
+        let mut sum = AggregateFunc::SumInt64.accumulator().unwrap();
+        let mut other = AggregateFunc::SumInt64.accumulator().unwrap();
+        sum.add(Datum::Int64(i64::MAX), i64::MAX);
+        sum.add(Datum::Int64(i64::MAX), i64::MAX);
+        other.add(Datum::Int64(i64::MAX), i64::MAX);
+        sum.merge(&*other);
+        assert_eq!(sum.finish(), Err(EvalError::NumericFieldOverflow));
+    }
+
+    #[test]
+    fn order_sensitive_aggregates_have_no_accumulator() {
+        let funcs = [
+            AggregateFunc::StringAgg { order_by: vec![] },
+            AggregateFunc::RowNumber { order_by: vec![] },
+            AggregateFunc::SumFloat64,
+            AggregateFunc::SumNumeric,
+            AggregateFunc::MaxString,
+        ];
+        for func in funcs {
+            assert!(func.accumulator().is_none(), "{func}");
+        }
+    }
+}
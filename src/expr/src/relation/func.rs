@@ -9,13 +9,14 @@
 
 #![allow(missing_docs)]
 
+use std::collections::BTreeSet;
 use std::fmt;
 use std::iter;
 use std::ops::Deref;
 
-use chrono::{DateTime, NaiveDateTime, Utc};
-use dec::OrderedDecimal;
-use itertools::Itertools;
+use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, Utc};
+use dec::{OrderedDecimal, Rounding};
+use itertools::{Either, Itertools};
 use num::{CheckedAdd, Integer, Signed};
 use ordered_float::OrderedFloat;
 use proptest::prelude::{Arbitrary, Just};
@@ -23,25 +24,37 @@ use proptest::strategy::{BoxedStrategy, Strategy, Union};
 use proptest_derive::Arbitrary;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use mz_lowertest::MzReflect;
-use mz_ore::cast::CastFrom;
+use mz_ore::cast::{CastFrom, CastLossy};
+use mz_ore::soft_assert;
 use mz_proto::{IntoRustIfSome, ProtoType, RustType, TryFromProtoError};
 use mz_repr::adt::array::ArrayDimension;
 use mz_repr::adt::date::Date;
+use mz_repr::adt::datetime::Timezone;
 use mz_repr::adt::interval::Interval;
-use mz_repr::adt::numeric::{self, NumericMaxScale};
+use mz_repr::adt::jsonb::JsonbRef;
+use mz_repr::adt::numeric::{self, Numeric, NumericMaxScale};
 use mz_repr::adt::regex::Regex as ReprRegex;
 use mz_repr::adt::timestamp::CheckedTimestamp;
 use mz_repr::adt::timestamp::TimestampLike;
-use mz_repr::{ColumnName, ColumnType, Datum, Diff, RelationType, Row, RowArena, ScalarType};
+use mz_repr::{
+    strconv, ColumnName, ColumnType, Datum, Diff, RelationType, Row, RowArena, RowPacker,
+    ScalarType,
+};
 
+use crate::relation::accumulator::{
+    Accumulator, CountAccumulator, ExtremumAccumulator, IntegerSumAccumulator, LogicalAccumulator,
+};
 use crate::relation::{
     compare_columns, proto_aggregate_func, proto_aggregate_func::ProtoColumnOrders,
     proto_table_func, ColumnOrder, ProtoAggregateFunc, ProtoTableFunc, WindowFrame,
     WindowFrameBound, WindowFrameUnits,
 };
-use crate::scalar::func::{add_timestamp_months, jsonb_stringify};
+use crate::scalar::func::{
+    add_timestamp_months, jsonb_stringify, parse_timezone, timezone_timestamp, timezone_timestamptz,
+};
 use crate::EvalError;
 
 include!(concat!(env!("OUT_DIR"), "/mz_expr.relation.func.rs"));
@@ -55,8 +68,7 @@ where
 {
     let x: Option<OrderedDecimal<numeric::Numeric>> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_numeric())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_numeric()))
         .max();
     x.map(Datum::Numeric).unwrap_or(Datum::Null)
 }
@@ -67,8 +79,7 @@ where
 {
     let x: Option<i16> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_int16())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_int16()))
         .max();
     Datum::from(x)
 }
@@ -79,8 +90,7 @@ where
 {
     let x: Option<i32> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_int32())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_int32()))
         .max();
     Datum::from(x)
 }
@@ -91,8 +101,7 @@ where
 {
     let x: Option<i64> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_int64())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_int64()))
         .max();
     Datum::from(x)
 }
@@ -103,8 +112,7 @@ where
 {
     let x: Option<u16> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_uint16())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_uint16()))
         .max();
     Datum::from(x)
 }
@@ -115,8 +123,7 @@ where
 {
     let x: Option<u32> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_uint32())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_uint32()))
         .max();
     Datum::from(x)
 }
@@ -127,8 +134,7 @@ where
 {
     let x: Option<u64> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_uint64())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_uint64()))
         .max();
     Datum::from(x)
 }
@@ -139,20 +145,21 @@ where
 {
     let x: Option<mz_repr::Timestamp> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_mz_timestamp())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_mz_timestamp()))
         .max();
     Datum::from(x)
 }
 
+// `OrderedFloat` orders NaN above every other value, as PostgreSQL does, so
+// NaN is the maximum of any input containing it, and the minimum only of an
+// input containing nothing else.
 fn max_float32<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
     let x: Option<OrderedFloat<f32>> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_ordered_float32())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_ordered_float32()))
         .max();
     Datum::from(x)
 }
@@ -163,8 +170,7 @@ where
 {
     let x: Option<OrderedFloat<f64>> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_ordered_float64())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_ordered_float64()))
         .max();
     Datum::from(x)
 }
@@ -175,8 +181,7 @@ where
 {
     let x: Option<bool> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_bool())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_bool()))
         .max();
     Datum::from(x)
 }
@@ -201,8 +206,7 @@ where
 {
     let x: Option<Date> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_date())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_date()))
         .max();
     Datum::from(x)
 }
@@ -213,8 +217,7 @@ where
 {
     let x: Option<CheckedTimestamp<NaiveDateTime>> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_timestamp())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_timestamp()))
         .max();
     Datum::from(x)
 }
@@ -224,21 +227,80 @@ where
     I: IntoIterator<Item = Datum<'a>>,
 {
     let x: Option<CheckedTimestamp<DateTime<Utc>>> = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_timestamptz()))
+        .max();
+    Datum::from(x)
+}
+
+fn max_interval<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let x: Option<Interval> = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_interval()))
+        .max_by(Interval::cmp_by_length);
+    Datum::from(x)
+}
+
+fn max_time<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let x: Option<NaiveTime> = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_time()))
+        .max();
+    Datum::from(x)
+}
+
+fn max_bytes<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    match datums
         .into_iter()
         .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_timestamptz())
+        .max_by(|a, b| a.unwrap_bytes().cmp(b.unwrap_bytes()))
+    {
+        Some(datum) => datum,
+        None => Datum::Null,
+    }
+}
+
+fn max_uuid<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let x: Option<Uuid> = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_uuid()))
         .max();
     Datum::from(x)
 }
 
+fn max_array<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    match datums
+        .into_iter()
+        .filter(|d| !d.is_null())
+        .max_by(|a, b| a.unwrap_array().cmp(&b.unwrap_array()))
+    {
+        Some(datum) => datum,
+        None => Datum::Null,
+    }
+}
+
 fn min_numeric<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
     let x: Option<OrderedDecimal<numeric::Numeric>> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_numeric())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_numeric()))
         .min();
     x.map(Datum::Numeric).unwrap_or(Datum::Null)
 }
@@ -249,8 +311,7 @@ where
 {
     let x: Option<i16> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_int16())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_int16()))
         .min();
     Datum::from(x)
 }
@@ -261,8 +322,7 @@ where
 {
     let x: Option<i32> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_int32())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_int32()))
         .min();
     Datum::from(x)
 }
@@ -273,8 +333,7 @@ where
 {
     let x: Option<i64> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_int64())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_int64()))
         .min();
     Datum::from(x)
 }
@@ -285,8 +344,7 @@ where
 {
     let x: Option<u16> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_uint16())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_uint16()))
         .min();
     Datum::from(x)
 }
@@ -297,8 +355,7 @@ where
 {
     let x: Option<u32> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_uint32())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_uint32()))
         .min();
     Datum::from(x)
 }
@@ -309,8 +366,7 @@ where
 {
     let x: Option<u64> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_uint64())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_uint64()))
         .min();
     Datum::from(x)
 }
@@ -321,8 +377,7 @@ where
 {
     let x: Option<mz_repr::Timestamp> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_mz_timestamp())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_mz_timestamp()))
         .min();
     Datum::from(x)
 }
@@ -333,8 +388,7 @@ where
 {
     let x: Option<OrderedFloat<f32>> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_ordered_float32())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_ordered_float32()))
         .min();
     Datum::from(x)
 }
@@ -345,8 +399,7 @@ where
 {
     let x: Option<OrderedFloat<f64>> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_ordered_float64())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_ordered_float64()))
         .min();
     Datum::from(x)
 }
@@ -357,8 +410,7 @@ where
 {
     let x: Option<bool> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_bool())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_bool()))
         .min();
     Datum::from(x)
 }
@@ -383,8 +435,7 @@ where
 {
     let x: Option<Date> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_date())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_date()))
         .min();
     Datum::from(x)
 }
@@ -395,8 +446,7 @@ where
 {
     let x: Option<CheckedTimestamp<NaiveDateTime>> = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_timestamp())
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_timestamp()))
         .min();
     Datum::from(x)
 }
@@ -406,61 +456,208 @@ where
     I: IntoIterator<Item = Datum<'a>>,
 {
     let x: Option<CheckedTimestamp<DateTime<Utc>>> = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_timestamptz()))
+        .min();
+    Datum::from(x)
+}
+
+fn min_interval<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let x: Option<Interval> = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_interval()))
+        .min_by(Interval::cmp_by_length);
+    Datum::from(x)
+}
+
+fn min_time<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let x: Option<NaiveTime> = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_time()))
+        .min();
+    Datum::from(x)
+}
+
+fn min_bytes<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    match datums
         .into_iter()
         .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_timestamptz())
+        .min_by(|a, b| a.unwrap_bytes().cmp(b.unwrap_bytes()))
+    {
+        Some(datum) => datum,
+        None => Datum::Null,
+    }
+}
+
+fn min_uuid<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let x: Option<Uuid> = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_uuid()))
         .min();
     Datum::from(x)
 }
 
+fn min_array<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    match datums
+        .into_iter()
+        .filter(|d| !d.is_null())
+        .min_by(|a, b| a.unwrap_array().cmp(&b.unwrap_array()))
+    {
+        Some(datum) => datum,
+        None => Datum::Null,
+    }
+}
+
 fn sum_int16<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
+    try_sum_int16(datums).expect("overflowing an i64 takes more than 2^48 int16 values")
+}
+
+fn try_sum_int16<'a, I>(datums: I) -> Result<Datum<'a>, EvalError>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let mut datums = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| i64::from(d.unwrap_int16())))
+        .peekable();
     if datums.peek().is_none() {
-        Datum::Null
+        Ok(Datum::Null)
     } else {
-        let x: i64 = datums.map(|d| i64::from(d.unwrap_int16())).sum();
-        Datum::from(x)
+        let x = datums.try_fold(0i64, |sum, x| {
+            sum.checked_add(x).ok_or(EvalError::NumericFieldOverflow)
+        })?;
+        Ok(Datum::from(x))
     }
 }
 
-fn sum_int32<'a, I>(datums: I) -> Datum<'a>
+/// The number of primitives that [`fold_primitive_chunks`] buffers before
+/// folding them.
+const PRIMITIVE_CHUNK_SIZE: usize = 1024;
+
+/// Unwraps the non-null `datums` into a buffer of primitives, and folds each
+/// full buffer, and then the final partial one, into an accumulator with `f`.
+///
+/// Folding over a contiguous slice of primitives, rather than over the datums
+/// themselves, lets the compiler vectorize `f`. Returns `None` if every datum
+/// is null.
+fn fold_primitive_chunks<'a, I, T, A, U, F>(datums: I, unwrap: U, init: A, mut f: F) -> Option<A>
 where
     I: IntoIterator<Item = Datum<'a>>,
+    T: Copy + Default,
+    U: Fn(Datum<'a>) -> T,
+    F: FnMut(A, &[T]) -> A,
 {
-    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
-    if datums.peek().is_none() {
-        Datum::Null
-    } else {
-        let x: i64 = datums.map(|d| i64::from(d.unwrap_int32())).sum();
-        Datum::from(x)
+    let mut chunk = [T::default(); PRIMITIVE_CHUNK_SIZE];
+    let mut len = 0;
+    let mut acc = init;
+    let mut any_non_null = false;
+    for d in datums {
+        if d.is_null() {
+            continue;
+        }
+        chunk[len] = unwrap(d);
+        len += 1;
+        if len == PRIMITIVE_CHUNK_SIZE {
+            acc = f(acc, &chunk);
+            len = 0;
+            any_non_null = true;
+        }
+    }
+    if len > 0 {
+        acc = f(acc, &chunk[..len]);
+        any_non_null = true;
     }
+    any_non_null.then_some(acc)
+}
+
+fn sum_int32<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    try_sum_int32(datums).expect("overflowing an i64 takes more than 2^32 int32 values")
+}
+
+fn try_sum_int32<'a, I>(datums: I) -> Result<Datum<'a>, EvalError>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // A chunk's sum cannot overflow an i64, so only adding it to the running
+    // sum needs to be checked.
+    let sum = fold_primitive_chunks(
+        datums,
+        |d| d.unwrap_int32(),
+        Ok(0i64),
+        |sum, chunk| {
+            let chunk_sum = chunk.iter().map(|x| i64::from(*x)).sum::<i64>();
+            sum?.checked_add(chunk_sum)
+                .ok_or(EvalError::NumericFieldOverflow)
+        },
+    );
+    Ok(sum.transpose()?.map_or(Datum::Null, Datum::from))
 }
 
 fn sum_int64<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
-    if datums.peek().is_none() {
-        Datum::Null
-    } else {
-        let x: i128 = datums.map(|d| i128::from(d.unwrap_int64())).sum();
-        Datum::from(x)
-    }
+    try_sum_int64(datums).expect("overflowing an i128 takes more than 2^64 int64 values")
+}
+
+fn try_sum_int64<'a, I>(datums: I) -> Result<Datum<'a>, EvalError>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let sum = fold_primitive_chunks(
+        datums,
+        |d| d.unwrap_int64(),
+        Ok(0i128),
+        |sum, chunk| add_int64_chunk(sum?, chunk),
+    );
+    Ok(sum.transpose()?.map_or(Datum::Null, Datum::from))
+}
+
+/// Adds the values of `chunk`, which holds at most [`PRIMITIVE_CHUNK_SIZE`]
+/// values, to `sum`.
+fn add_int64_chunk(sum: i128, chunk: &[i64]) -> Result<i128, EvalError> {
+    // Widening every value to an i128 defeats vectorization, so instead sum the
+    // signed upper and the unsigned lower 32 bits of the values separately,
+    // neither of which can overflow an i64 within a chunk.
+    let high: i64 = chunk.iter().map(|x| x >> 32).sum();
+    let low: i64 = chunk.iter().map(|x| x & 0xFFFF_FFFF).sum();
+    sum.checked_add((i128::from(high) << 32) + i128::from(low))
+        .ok_or(EvalError::NumericFieldOverflow)
 }
 
 fn sum_uint16<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
+    let mut datums = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| u64::from(d.unwrap_uint16())))
+        .peekable();
     if datums.peek().is_none() {
         Datum::Null
     } else {
-        let x: u64 = datums.map(|d| u64::from(d.unwrap_uint16())).sum();
+        let x: u64 = datums.sum();
         Datum::from(x)
     }
 }
@@ -469,11 +666,14 @@ fn sum_uint32<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
+    let mut datums = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| u64::from(d.unwrap_uint32())))
+        .peekable();
     if datums.peek().is_none() {
         Datum::Null
     } else {
-        let x: u64 = datums.map(|d| u64::from(d.unwrap_uint32())).sum();
+        let x: u64 = datums.sum();
         Datum::from(x)
     }
 }
@@ -482,11 +682,14 @@ fn sum_uint64<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
+    let mut datums = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| u128::from(d.unwrap_uint64())))
+        .peekable();
     if datums.peek().is_none() {
         Datum::Null
     } else {
-        let x: u128 = datums.map(|d| u128::from(d.unwrap_uint64())).sum();
+        let x: u128 = datums.sum();
         Datum::from(x)
     }
 }
@@ -495,11 +698,14 @@ fn sum_float32<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
+    let mut datums = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_float32()))
+        .peekable();
     if datums.peek().is_none() {
         Datum::Null
     } else {
-        let x: f32 = datums.map(|d| d.unwrap_float32()).sum();
+        let x: f32 = datums.sum();
         Datum::from(x)
     }
 }
@@ -508,562 +714,1045 @@ fn sum_float64<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
+    let mut datums = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_float64()))
+        .peekable();
     if datums.peek().is_none() {
         Datum::Null
     } else {
-        let x: f64 = datums.map(|d| d.unwrap_float64()).sum();
+        let x: f64 = datums.sum();
         Datum::from(x)
     }
 }
 
 fn sum_numeric<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // `AggregateFunc::try_eval` reports the error instead. Here an overflowing
+    // sum evaluates to infinity, and an invalid one to NaN.
+    let (sum, _err) = checked_sum_numeric(datums);
+    sum
+}
+
+/// Sums numeric values, also returning the error described by the decimal
+/// context's status flags, if any.
+fn checked_sum_numeric<'a, I>(datums: I) -> (Datum<'a>, Option<EvalError>)
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
     let datums = datums
         .into_iter()
-        .filter(|d| !d.is_null())
-        .map(|d| d.unwrap_numeric().0)
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_numeric().0))
         .collect::<Vec<_>>();
     if datums.is_empty() {
-        Datum::Null
-    } else {
-        let mut cx = numeric::cx_datum();
-        let sum = cx.sum(datums.iter());
-        Datum::from(sum)
+        return (Datum::Null, None);
     }
+    let mut cx = numeric::cx_datum();
+    let sum = cx.sum(datums.iter());
+    let cx_status = cx.status();
+    let err = if cx_status.overflow() {
+        Some(EvalError::NumericFieldOverflow)
+    } else if cx_status.invalid_operation() {
+        Some(EvalError::InvalidParameterValue(
+            "invalid operation in numeric sum".into(),
+        ))
+    } else {
+        None
+    };
+    (Datum::from(sum), err)
 }
 
-// TODO(benesch): remove potentially dangerous usage of `as`.
-#[allow(clippy::as_conversions)]
-fn count<'a, I>(datums: I) -> Datum<'a>
+fn sum_interval<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    // TODO(jkosh44) This should error when the count can't fit inside of an `i64` instead of returning a negative result.
-    let x: i64 = datums.into_iter().filter(|d| !d.is_null()).count() as i64;
-    Datum::from(x)
+    // `AggregateFunc::try_eval` reports the error instead. Here each field of an
+    // overflowing sum saturates.
+    let (sum, _err) = checked_sum_interval(datums);
+    sum
 }
 
-fn any<'a, I>(datums: I) -> Datum<'a>
+/// Sums interval values field by field, saturating any field that overflows,
+/// and also returns an error if one did.
+fn checked_sum_interval<'a, I>(datums: I) -> (Datum<'a>, Option<EvalError>)
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    datums
+    let mut datums = datums
         .into_iter()
-        .fold(Datum::False, |state, next| match (state, next) {
-            (Datum::True, _) | (_, Datum::True) => Datum::True,
-            (Datum::Null, _) | (_, Datum::Null) => Datum::Null,
-            _ => Datum::False,
-        })
+        .filter(|d| !d.is_null())
+        .map(|d| d.unwrap_interval());
+    let Some(mut sum) = datums.next() else {
+        return (Datum::Null, None);
+    };
+    let mut err = None;
+    for interval in datums {
+        sum = sum.checked_add(&interval).unwrap_or_else(|| {
+            err = Some(EvalError::IntervalOutOfRange);
+            Interval::new(
+                sum.months.saturating_add(interval.months),
+                sum.days.saturating_add(interval.days),
+                sum.micros.saturating_add(interval.micros),
+            )
+        });
+    }
+    (Datum::from(sum), err)
 }
 
-fn all<'a, I>(datums: I) -> Datum<'a>
+fn product_float32<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    datums
+    let mut datums = datums
         .into_iter()
-        .fold(Datum::True, |state, next| match (state, next) {
-            (Datum::False, _) | (_, Datum::False) => Datum::False,
-            (Datum::Null, _) | (_, Datum::Null) => Datum::Null,
-            _ => Datum::True,
-        })
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_float32()))
+        .peekable();
+    if datums.peek().is_none() {
+        Datum::Null
+    } else {
+        let x: f32 = datums.product();
+        Datum::from(x)
+    }
 }
 
-fn string_agg<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+fn product_float64<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    const EMPTY_SEP: &str = "";
-
-    let datums = order_aggregate_datums(datums, order_by);
-    let mut sep_value_pairs = datums.into_iter().filter_map(|d| {
-        if d.is_null() {
-            return None;
-        }
-        let mut value_sep = d.unwrap_list().iter();
-        match (value_sep.next().unwrap(), value_sep.next().unwrap()) {
-            (Datum::Null, _) => None,
-            (Datum::String(val), Datum::Null) => Some((EMPTY_SEP, val)),
-            (Datum::String(val), Datum::String(sep)) => Some((sep, val)),
-            _ => unreachable!(),
-        }
-    });
-
-    let mut s = String::default();
-    match sep_value_pairs.next() {
-        // First value not prefixed by its separator
-        Some((_, value)) => s.push_str(value),
-        // If no non-null values sent, return NULL.
-        None => return Datum::Null,
-    }
-
-    for (sep, value) in sep_value_pairs {
-        s.push_str(sep);
-        s.push_str(value);
+    let mut datums = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_float64()))
+        .peekable();
+    if datums.peek().is_none() {
+        Datum::Null
+    } else {
+        let x: f64 = datums.product();
+        Datum::from(x)
     }
-
-    Datum::String(temp_storage.push_string(s))
 }
 
-fn jsonb_agg<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+fn product_numeric<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let datums = order_aggregate_datums(datums, order_by);
-    temp_storage.make_datum(|packer| {
-        packer.push_list(datums.into_iter().filter(|d| !d.is_null()));
-    })
+    let mut datums = datums
+        .into_iter()
+        .filter_map(|d| (!d.is_null()).then(|| d.unwrap_numeric().0));
+    let Some(mut product) = datums.next() else {
+        return Datum::Null;
+    };
+    let mut cx = numeric::cx_datum();
+    for d in datums {
+        cx.mul(&mut product, &d);
+    }
+    // Like `sum_numeric`, an overflowing product evaluates to infinity. Any
+    // other product is already within the maximum precision, but might be
+    // negative zero.
+    if product.is_finite() {
+        numeric::munge_numeric(&mut product).expect("product within maximum precision");
+    }
+    Datum::from(product)
 }
 
-fn jsonb_object_agg<'a, I>(
-    datums: I,
-    temp_storage: &'a RowArena,
-    order_by: &[ColumnOrder],
-) -> Datum<'a>
+/// Divides `sum` by `count`, rounding half to even.
+fn avg_from_sum<'a>(mut sum: Numeric, count: usize) -> Datum<'a> {
+    let mut cx = numeric::cx_datum();
+    cx.set_rounding(Rounding::HalfEven);
+    cx.div(&mut sum, &Numeric::from(u64::cast_from(count)));
+    numeric::munge_numeric(&mut sum).unwrap();
+    Datum::from(sum)
+}
+
+/// Averages integers, accumulating them in an `i128` so that the only
+/// rounding happens in the final division.
+fn avg_int<'a, I, F>(datums: I, unwrap: F) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
+    F: Fn(Datum<'a>) -> i128,
 {
-    let datums = order_aggregate_datums(datums, order_by);
-    temp_storage.make_datum(|packer| {
-        let mut datums: Vec<_> = datums
-            .into_iter()
-            .filter_map(|d| {
-                if d.is_null() {
-                    return None;
-                }
-                let mut list = d.unwrap_list().iter();
-                let key = list.next().unwrap();
-                let val = list.next().unwrap();
-                if key.is_null() {
-                    // TODO(benesch): this should produce an error, but
-                    // aggregate functions cannot presently produce errors.
-                    None
-                } else {
-                    Some((key.unwrap_str(), val))
-                }
-            })
-            .collect();
-        // datums are ordered by any ORDER BY clause now, and we want to preserve
-        // the last entry for each key, but we also need to present unique and sorted
-        // keys to push_dict. Use sort_by here, which is stable, and so will preserve
-        // the ORDER BY order. Then reverse and dedup to retain the last of each
-        // key. Reverse again so we're back in push_dict order.
-        datums.sort_by_key(|(k, _v)| *k);
-        datums.reverse();
-        datums.dedup_by_key(|(k, _v)| *k);
-        datums.reverse();
-        packer.push_dict(datums);
-    })
+    let (sum, count) = datums
+        .into_iter()
+        .filter(|d| !d.is_null())
+        .fold((0i128, 0usize), |(sum, count), d| {
+            (sum + unwrap(d), count + 1)
+        });
+    if count == 0 {
+        Datum::Null
+    } else {
+        avg_from_sum(Numeric::try_from(sum).unwrap(), count)
+    }
 }
 
-// Assuming datums is a List, sort them by the 2nd through Nth elements
-// corresponding to order_by, then return the 1st element.
-fn order_aggregate_datums<'a, I>(
-    datums: I,
-    order_by: &[ColumnOrder],
-) -> impl Iterator<Item = Datum<'a>>
+fn avg_int16<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    order_aggregate_datums_with_rank(datums, order_by).map(|(expr, _order_row)| expr)
+    avg_int(datums, |d| i128::from(d.unwrap_int16()))
 }
 
-// Assuming datums is a List, sort them by the 2nd through Nth elements
-// corresponding to order_by, then return the 1st element and computed order by expression.
-fn order_aggregate_datums_with_rank<'a, I>(
-    datums: I,
-    order_by: &[ColumnOrder],
-) -> impl Iterator<Item = (Datum<'a>, Row)>
+fn avg_int32<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let mut rows: Vec<(Datum, Row)> = datums
-        .into_iter()
-        .filter_map(|d| {
-            let list = d.unwrap_list();
-            let expr = list.iter().next().unwrap();
-            let order_row = Row::pack(list.iter().skip(1));
-            Some((expr, order_row))
-        })
-        .collect();
+    avg_int(datums, |d| i128::from(d.unwrap_int32()))
+}
 
-    let mut left_datum_vec = mz_repr::DatumVec::new();
-    let mut right_datum_vec = mz_repr::DatumVec::new();
-    let mut sort_by = |left: &(_, Row), right: &(_, Row)| {
-        let left = &left.1;
-        let right = &right.1;
-        let left_datums = left_datum_vec.borrow_with(left);
-        let right_datums = right_datum_vec.borrow_with(right);
-        compare_columns(order_by, &left_datums, &right_datums, || left.cmp(right))
-    };
-    rows.sort_by(&mut sort_by);
-    rows.into_iter()
+fn avg_int64<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    avg_int(datums, |d| i128::from(d.unwrap_int64()))
 }
 
-fn array_concat<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+/// Averages floats, accumulating them in an `f64`.
+fn avg_float<'a, I, F>(datums: I, unwrap: F) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
+    F: Fn(Datum<'a>) -> f64,
 {
-    let datums = order_aggregate_datums(datums, order_by);
-    let datums: Vec<_> = datums
+    let (sum, count) = datums
         .into_iter()
-        .map(|d| d.unwrap_array().elements().iter())
-        .flatten()
-        .collect();
-    let dims = ArrayDimension {
-        lower_bound: 1,
-        length: datums.len(),
-    };
-    temp_storage.make_datum(|packer| {
-        packer.push_array(&[dims], datums).unwrap();
-    })
+        .filter(|d| !d.is_null())
+        .fold((0f64, 0usize), |(sum, count), d| {
+            (sum + unwrap(d), count + 1)
+        });
+    if count == 0 {
+        Datum::Null
+    } else {
+        Datum::from(sum / f64::cast_lossy(count))
+    }
 }
 
-fn list_concat<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+fn avg_float32<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let datums = order_aggregate_datums(datums, order_by);
-    temp_storage.make_datum(|packer| {
-        packer.push_list(datums.into_iter().map(|d| d.unwrap_list().iter()).flatten());
-    })
+    avg_float(datums, |d| f64::from(d.unwrap_float32()))
 }
 
-fn row_number<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+fn avg_float64<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    avg_float(datums, |d| d.unwrap_float64())
+}
+
+fn avg_numeric<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    let datums = order_aggregate_datums(datums, order_by);
     let datums = datums
         .into_iter()
-        .map(|d| d.unwrap_list().iter())
-        .flatten()
-        .zip(1i64..)
-        .map(|(d, i)| {
-            temp_storage.make_datum(|packer| {
-                packer.push_list(vec![Datum::Int64(i), d]);
-            })
-        });
+        .filter(|d| !d.is_null())
+        .map(|d| d.unwrap_numeric().0)
+        .collect::<Vec<_>>();
+    if datums.is_empty() {
+        Datum::Null
+    } else {
+        let mut cx = numeric::cx_datum();
+        let sum = cx.sum(datums.iter());
+        avg_from_sum(sum, datums.len())
+    }
+}
 
-    temp_storage.make_datum(|packer| {
-        packer.push_list(datums);
-    })
+fn avg_interval<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // Like `sum_interval`, an overflowing sum saturates here and is reported
+    // by `AggregateFunc::try_eval` instead.
+    let (avg, _err) = checked_avg_interval(datums);
+    avg
 }
 
-fn dense_rank<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+/// Averages intervals by dividing their field-by-field sum by their count,
+/// and also returns an error if the sum overflowed.
+fn checked_avg_interval<'a, I>(datums: I) -> (Datum<'a>, Option<EvalError>)
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    // Keep the row used for ordering around, as it is used to determine the rank
-    let datums = order_aggregate_datums_with_rank(datums, order_by);
+    let mut count = 0usize;
+    let datums = datums.into_iter().filter(|d| !d.is_null()).inspect(|_| {
+        count += 1;
+    });
+    let (sum, err) = checked_sum_interval(datums);
+    if sum.is_null() {
+        return (Datum::Null, err);
+    }
+    let avg = sum
+        .unwrap_interval()
+        .checked_div(f64::cast_lossy(count))
+        .expect("dividing by a positive count cannot overflow");
+    (Datum::from(avg), err)
+}
 
-    let mut datums = datums
+/// Computes the population (`sample == false`) or sample (`sample == true`)
+/// variance of the non-null numeric values in `datums`.
+///
+/// Returns `None` if there are no non-null values or, for the sample variance,
+/// only a single non-null value.
+fn variance_numeric<'a, I>(datums: I, sample: bool) -> Option<Numeric>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let datums = datums
         .into_iter()
-        .map(|(d0, row)| d0.unwrap_list().iter().map(move |d1| (d1, row.clone())))
-        .flatten();
+        .filter(|d| !d.is_null())
+        .map(|d| d.unwrap_numeric().0)
+        .collect::<Vec<_>>();
+    let n = u64::cast_from(datums.len());
+    if n == 0 || (sample && n == 1) {
+        return None;
+    }
 
-    let datums = datums
-        .next()
-        .map_or(vec![], |(first_datum, first_row)| {
-            // Folding with (last order_by row, last assigned rank, output vec)
-            datums.fold((first_row, 1, vec![(first_datum, 1)]), |mut acc, (next_datum, next_row)| {
-                let (ref mut acc_row, ref mut acc_rank, ref mut output) = acc;
-                // Identity is based on the order_by expression
-                if *acc_row != next_row {
-                    *acc_rank += 1;
-                    *acc_row = next_row;
-                }
+    let mut cx = numeric::cx_datum();
+    let mut mean = cx.sum(datums.iter());
+    cx.div(&mut mean, &Numeric::from(n));
 
-                (*output).push((next_datum, *acc_rank));
-                acc
-            })
-        }.2).into_iter().map(|(d, i)| {
-            temp_storage.make_datum(|packer| {
-                packer.push_list(vec![Datum::Int64(i), d]);
-            })
-        });
+    let mut sum_of_squares = Numeric::zero();
+    for mut d in datums {
+        cx.sub(&mut d, &mean);
+        let delta = d;
+        cx.mul(&mut d, &delta);
+        cx.add(&mut sum_of_squares, &d);
+    }
 
-    temp_storage.make_datum(|packer| {
-        packer.push_list(datums);
-    })
+    let mut variance = sum_of_squares;
+    cx.div(
+        &mut variance,
+        &Numeric::from(if sample { n - 1 } else { n }),
+    );
+    numeric::munge_numeric(&mut variance).unwrap();
+    Some(variance)
 }
 
-// The expected input is in the format of [((OriginalRow, EncodedArgs), OrderByExprs...)]
-fn lag_lead<'a, I>(
-    datums: I,
-    temp_storage: &'a RowArena,
-    order_by: &[ColumnOrder],
-    lag_lead_type: &LagLeadType,
-) -> Datum<'a>
+fn stddev_numeric<'a, I>(datums: I, sample: bool) -> Option<Numeric>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    // Sort the datums according to the ORDER BY expressions and return the (OriginalRow, EncodedArgs) record
-    let datums = order_aggregate_datums(datums, order_by);
+    let mut variance = variance_numeric(datums, sample)?;
+    let mut cx = numeric::cx_datum();
+    cx.sqrt(&mut variance);
+    numeric::munge_numeric(&mut variance).unwrap();
+    Some(variance)
+}
 
-    // Decode the input (OriginalRow, EncodedArgs) into separate datums
-    // EncodedArgs = (InputValue, Offset, DefaultValue) for Lag/Lead
-    let datums = datums
-        .into_iter()
-        .map(|d| {
-            let mut iter = d.unwrap_list().iter();
-            let original_row = iter.next().unwrap();
-            let mut encoded_args = iter.next().unwrap().unwrap_list().iter();
-            let (input_value, offset, default_value) = (
-                encoded_args.next().unwrap(),
-                encoded_args.next().unwrap(),
-                encoded_args.next().unwrap(),
-            );
+fn var_pop_numeric<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    Datum::from(variance_numeric(datums, false))
+}
 
-            (input_value, offset, default_value, original_row)
-        })
-        .collect_vec();
+fn var_samp_numeric<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    Datum::from(variance_numeric(datums, true))
+}
 
-    let mut result: Vec<(Datum, Datum)> = Vec::with_capacity(datums.len());
-    for (idx, (_, offset, default_value, original_row)) in datums.iter().enumerate() {
-        // Null offsets are acceptable, and always return null
-        if offset.is_null() {
-            result.push((*offset, *original_row));
-            continue;
+fn stddev_pop_numeric<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    Datum::from(stddev_numeric(datums, false))
+}
+
+fn stddev_samp_numeric<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    Datum::from(stddev_numeric(datums, true))
+}
+
+/// Centered moments of pairs of float64 values `(y, x)`, from which the
+/// statistical aggregates over two variables are computed.
+///
+/// The moments are accumulated with Welford's method, rather than derived
+/// from raw sums of squares, which cancel catastrophically for values that are
+/// large relative to their spread.
+#[derive(Default)]
+struct RegrAccumulator {
+    count: usize,
+    mean_x: f64,
+    mean_y: f64,
+    /// The sum of squared deviations of `x` from its mean.
+    m2_x: f64,
+    /// The sum of squared deviations of `y` from its mean.
+    m2_y: f64,
+    /// The sum of the products of the deviations of `x` and `y` from their
+    /// means.
+    c_xy: f64,
+    max_abs_x: f64,
+    max_abs_y: f64,
+}
+
+impl RegrAccumulator {
+    /// Accumulates `datums`, each of which is a `Datum::List` of `y` and `x`,
+    /// in a single pass. Pairs in which either value is null are skipped, as
+    /// are null pairs, which is the identity of these aggregates.
+    fn new<'a, I>(datums: I) -> Self
+    where
+        I: IntoIterator<Item = Datum<'a>>,
+    {
+        let mut acc = Self::default();
+        for d in datums {
+            if d.is_null() {
+                continue;
+            }
+            let mut pair = d.unwrap_list().iter();
+            let (y, x) = (pair.next().unwrap(), pair.next().unwrap());
+            if y.is_null() || x.is_null() {
+                continue;
+            }
+            let (y, x) = (y.unwrap_float64(), x.unwrap_float64());
+            acc.count += 1;
+            let n = acc.n();
+            let dx = x - acc.mean_x;
+            let dy = y - acc.mean_y;
+            acc.mean_x += dx / n;
+            acc.mean_y += dy / n;
+            acc.m2_x += dx * (x - acc.mean_x);
+            acc.m2_y += dy * (y - acc.mean_y);
+            acc.c_xy += dx * (y - acc.mean_y);
+            acc.max_abs_x = acc.max_abs_x.max(x.abs());
+            acc.max_abs_y = acc.max_abs_y.max(y.abs());
         }
+        acc
+    }
 
-        let idx = i64::try_from(idx).expect("Array index does not fit in i64");
-        let offset = i64::from(offset.unwrap_int32());
-        // By default, offset is applied backwards (for `lag`): flip the sign if `lead` should run instead
-        let offset = match lag_lead_type {
-            LagLeadType::Lag => offset,
-            LagLeadType::Lead => -offset,
-        };
-        let vec_offset = idx - offset;
+    fn n(&self) -> f64 {
+        f64::cast_lossy(self.count)
+    }
 
-        let lagged_value = match u64::try_from(vec_offset) {
-            Ok(vec_offset) => datums
-                .get(usize::cast_from(vec_offset))
-                .map(|d| d.0)
-                .unwrap_or(*default_value),
-            Err(_) => *default_value,
-        };
+    /// Clamps a sum of squared deviations that rounding errors alone could
+    /// account for to zero, so that a constant variable is recognized as such.
+    fn clamp_m2(&self, m2: f64, max_abs: f64) -> f64 {
+        let tolerance = 4.0 * f64::EPSILON * max_abs;
+        if m2 <= self.n() * tolerance * tolerance {
+            0.0
+        } else {
+            m2
+        }
+    }
 
-        result.push((lagged_value, *original_row));
+    /// The sum of squared deviations of `x` from its mean.
+    fn sxx(&self) -> f64 {
+        self.clamp_m2(self.m2_x, self.max_abs_x)
     }
 
-    let result = result.into_iter().map(|(lag, original_row)| {
-        temp_storage.make_datum(|packer| {
-            packer.push_list(vec![lag, original_row]);
-        })
-    });
+    /// The sum of squared deviations of `y` from its mean.
+    fn syy(&self) -> f64 {
+        self.clamp_m2(self.m2_y, self.max_abs_y)
+    }
 
-    temp_storage.make_datum(|packer| {
-        packer.push_list(result);
-    })
+    /// The sum of the products of the deviations of `x` and `y` from their
+    /// means.
+    fn sxy(&self) -> f64 {
+        self.c_xy
+    }
 }
 
-// The expected input is in the format of [((OriginalRow, InputValue), OrderByExprs...)]
-fn first_value<'a, I>(
-    datums: I,
-    temp_storage: &'a RowArena,
-    order_by: &[ColumnOrder],
-    window_frame: &WindowFrame,
-) -> Datum<'a>
+fn corr<'a, I>(datums: I) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    // Sort the datums according to the ORDER BY expressions and return the (OriginalRow, InputValue) record
-    let datums = order_aggregate_datums(datums, order_by);
-
-    // Decode the input (OriginalRow, InputValue) into separate datums
-    let datums = datums
-        .into_iter()
-        .map(|d| {
-            let mut iter = d.unwrap_list().iter();
-            let original_row = iter.next().unwrap();
-            let input_value = iter.next().unwrap();
+    let acc = RegrAccumulator::new(datums);
+    let (sxx, syy) = (acc.sxx(), acc.syy());
+    // The correlation is undefined if either variable is constant, which
+    // includes the case of fewer than two pairs.
+    if acc.count < 2 || sxx == 0.0 || syy == 0.0 {
+        return Datum::Null;
+    }
+    // Rounding errors must not push the correlation out of its range.
+    Datum::from((acc.sxy() / (sxx * syy).sqrt()).clamp(-1.0, 1.0))
+}
 
-            (input_value, original_row)
-        })
-        .collect_vec();
+fn covar_pop<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let acc = RegrAccumulator::new(datums);
+    if acc.count < 1 {
+        return Datum::Null;
+    }
+    Datum::from(acc.sxy() / acc.n())
+}
 
-    let length = datums.len();
-    let mut result: Vec<(Datum, Datum)> = Vec::with_capacity(length);
-    for (idx, (current_datum, original_row)) in datums.iter().enumerate() {
-        let first_value = match &window_frame.start_bound {
-            // Always return the current value
-            WindowFrameBound::CurrentRow => *current_datum,
-            WindowFrameBound::UnboundedPreceding => {
-                if let WindowFrameBound::OffsetPreceding(end_offset) = &window_frame.end_bound {
-                    let end_offset = usize::cast_from(*end_offset);
+fn covar_samp<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let acc = RegrAccumulator::new(datums);
+    if acc.count < 2 {
+        return Datum::Null;
+    }
+    Datum::from(acc.sxy() / (acc.n() - 1.0))
+}
 
-                    // If the frame ends before the first row, return null
-                    if idx < end_offset {
-                        Datum::Null
-                    } else {
-                        datums[0].0
-                    }
-                } else {
-                    datums[0].0
-                }
-            }
-            WindowFrameBound::OffsetPreceding(offset) => {
-                let start_offset = usize::cast_from(*offset);
-                let start_idx = idx.saturating_sub(start_offset);
-                if let WindowFrameBound::OffsetPreceding(end_offset) = &window_frame.end_bound {
-                    let end_offset = usize::cast_from(*end_offset);
+fn regr_count<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let acc = RegrAccumulator::new(datums);
+    Datum::Int64(i64::try_from(acc.count).expect("regr_count overflow"))
+}
 
-                    // If the frame is empty or ends before the first row, return null
-                    if start_offset < end_offset || idx < end_offset {
-                        Datum::Null
-                    } else {
-                        datums[start_idx].0
-                    }
-                } else {
-                    datums[start_idx].0
-                }
-            }
-            WindowFrameBound::OffsetFollowing(offset) => {
-                let start_offset = usize::cast_from(*offset);
-                let start_idx = idx.saturating_add(start_offset);
-                if let WindowFrameBound::OffsetFollowing(end_offset) = &window_frame.end_bound {
-                    // If the frame is empty or starts after the last row, return null
-                    if offset > end_offset || start_idx >= length {
-                        Datum::Null
-                    } else {
-                        datums[start_idx].0
-                    }
-                } else {
-                    datums.get(start_idx).map(|d| d.0).unwrap_or(Datum::Null)
-                }
-            }
-            // Forbidden during planning
-            WindowFrameBound::UnboundedFollowing => unreachable!(),
-        };
+fn regr_slope<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let acc = RegrAccumulator::new(datums);
+    // The slope is undefined if x is constant, which includes the case of
+    // fewer than two pairs.
+    let sxx = acc.sxx();
+    if sxx == 0.0 {
+        return Datum::Null;
+    }
+    Datum::from(acc.sxy() / sxx)
+}
 
-        result.push((first_value, *original_row));
+fn regr_intercept<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let acc = RegrAccumulator::new(datums);
+    let sxx = acc.sxx();
+    if sxx == 0.0 {
+        return Datum::Null;
     }
+    let slope = acc.sxy() / sxx;
+    Datum::from(acc.mean_y - acc.mean_x * slope)
+}
 
-    let result = result.into_iter().map(|(lag, original_row)| {
-        temp_storage.make_datum(|packer| {
-            packer.push_list(vec![lag, original_row]);
+fn regr_r2<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let acc = RegrAccumulator::new(datums);
+    let (sxx, syy) = (acc.sxx(), acc.syy());
+    if sxx == 0.0 {
+        return Datum::Null;
+    }
+    // A constant y is fit perfectly by a horizontal line.
+    if syy == 0.0 {
+        return Datum::from(1.0f64);
+    }
+    let sxy = acc.sxy();
+    Datum::from(((sxy * sxy) / (sxx * syy)).min(1.0))
+}
+
+fn count<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // TODO(jkosh44) This should error when the count can't fit inside of an `i64`.
+    // Summing the non-null indicators, rather than counting the datums that
+    // pass a filter, keeps the loop free of branches.
+    let x: i64 = datums.into_iter().map(|d| i64::from(!d.is_null())).sum();
+    Datum::from(x)
+}
+
+fn count_numeric<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // A `u64` holds the number of datums any iterator can yield, so unlike
+    // `count` this cannot overflow.
+    let x: u64 = datums.into_iter().map(|d| u64::from(!d.is_null())).sum();
+    Datum::from(Numeric::from(x))
+}
+
+fn count_distinct<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let distinct: BTreeSet<_> = datums.into_iter().filter(|d| !d.is_null()).collect();
+    let x = i64::try_from(distinct.len()).expect("distinct count fits in i64");
+    Datum::from(x)
+}
+
+fn any<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    datums
+        .into_iter()
+        .fold(Datum::False, |state, next| match (state, next) {
+            (Datum::True, _) | (_, Datum::True) => Datum::True,
+            (Datum::Null, _) | (_, Datum::Null) => Datum::Null,
+            _ => Datum::False,
+        })
+}
+
+fn all<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    datums
+        .into_iter()
+        .fold(Datum::True, |state, next| match (state, next) {
+            (Datum::False, _) | (_, Datum::False) => Datum::False,
+            (Datum::Null, _) | (_, Datum::Null) => Datum::Null,
+            _ => Datum::True,
         })
+}
+
+/// Like [`all`], but returns `Datum::Null` for an empty input.
+fn bool_and<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let mut datums = datums.into_iter().peekable();
+    if datums.peek().is_none() {
+        Datum::Null
+    } else {
+        all(datums)
+    }
+}
+
+/// Like [`any`], but returns `Datum::Null` for an empty input.
+fn bool_or<'a, I>(datums: I) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let mut datums = datums.into_iter().peekable();
+    if datums.peek().is_none() {
+        Datum::Null
+    } else {
+        any(datums)
+    }
+}
+
+fn string_agg<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    const EMPTY_SEP: &str = "";
+
+    let datums = order_aggregate_datums(datums, order_by);
+    let mut sep_value_pairs = datums.into_iter().filter_map(|d| {
+        if d.is_null() {
+            return None;
+        }
+        let mut value_sep = d.unwrap_list().iter();
+        match (value_sep.next().unwrap(), value_sep.next().unwrap()) {
+            (Datum::Null, _) => None,
+            (Datum::String(val), Datum::Null) => Some((EMPTY_SEP, val)),
+            (Datum::String(val), Datum::String(sep)) => Some((sep, val)),
+            _ => unreachable!(),
+        }
     });
 
+    let mut s = String::default();
+    match sep_value_pairs.next() {
+        // First value not prefixed by its separator
+        Some((_, value)) => s.push_str(value),
+        // If no non-null values sent, return NULL.
+        None => return Datum::Null,
+    }
+
+    for (sep, value) in sep_value_pairs {
+        s.push_str(sep);
+        s.push_str(value);
+    }
+
+    Datum::String(temp_storage.push_string(s))
+}
+
+fn jsonb_agg<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let datums = order_aggregate_datums(datums, order_by);
     temp_storage.make_datum(|packer| {
-        packer.push_list(result);
+        packer.push_list(datums.into_iter().filter(|d| !d.is_null()));
     })
 }
 
-// The expected input is in the format of [((OriginalRow, InputValue), OrderByExprs...)]
-fn last_value<'a, I>(
+fn jsonb_object_agg<'a, I>(
     datums: I,
     temp_storage: &'a RowArena,
     order_by: &[ColumnOrder],
-    window_frame: &WindowFrame,
+    stringify_values: bool,
 ) -> Datum<'a>
 where
     I: IntoIterator<Item = Datum<'a>>,
 {
-    // Sort the datums according to the ORDER BY expressions and return the ((OriginalRow, InputValue), OrderByRow) record
-    // The OrderByRow is kept around because it is required to compute the peer groups in RANGE mode
-    let datums = order_aggregate_datums_with_rank(datums, order_by);
+    let datums = order_aggregate_datums(datums, order_by);
+    temp_storage.make_datum(|packer| {
+        let mut datums: Vec<_> = datums
+            .into_iter()
+            .filter_map(|d| {
+                if d.is_null() {
+                    return None;
+                }
+                let mut list = d.unwrap_list().iter();
+                let key = list.next().unwrap();
+                let mut val = list.next().unwrap();
+                if stringify_values {
+                    // A stringified JSON null is SQL NULL, which is stored as a
+                    // JSON null again.
+                    val = match jsonb_stringify(val, temp_storage) {
+                        Datum::Null => Datum::JsonNull,
+                        val => val,
+                    };
+                }
+                if key.is_null() {
+                    // TODO(benesch): this should produce an error, but
+                    // aggregate functions cannot presently produce errors.
+                    None
+                } else {
+                    Some((key.unwrap_str(), val))
+                }
+            })
+            .collect();
+        // datums are ordered by any ORDER BY clause now, and we want to preserve
+        // the last entry for each key, but we also need to present unique and sorted
+        // keys to push_dict. Use sort_by here, which is stable, and so will preserve
+        // the ORDER BY order. Then reverse and dedup to retain the last of each
+        // key. Reverse again so we're back in push_dict order.
+        datums.sort_by_key(|(k, _v)| *k);
+        datums.reverse();
+        datums.dedup_by_key(|(k, _v)| *k);
+        datums.reverse();
+        packer.push_dict(datums);
+    })
+}
 
-    // Decode the input (OriginalRow, InputValue) into separate datums, while keeping the OrderByRow
-    let datums = datums
-        .into_iter()
-        .map(|(d, order_by_row)| {
-            let mut iter = d.unwrap_list().iter();
-            let original_row = iter.next().unwrap();
-            let input_value = iter.next().unwrap();
+// Assuming datums is a List, sort them by the 2nd through Nth elements
+// corresponding to order_by, then return the 1st element.
+//
+// Without an ORDER BY, all datums are peers and the stable sort preserves their
+// input order, so they are streamed through instead of being collected first.
+fn order_aggregate_datums<'a, I>(
+    datums: I,
+    order_by: &[ColumnOrder],
+) -> impl Iterator<Item = Datum<'a>>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    if order_by.is_empty() {
+        Either::Left(
+            datums
+                .into_iter()
+                .map(|d| d.unwrap_list().iter().next().unwrap()),
+        )
+    } else {
+        Either::Right(
+            order_aggregate_datums_with_rank(datums, order_by).map(|(expr, _order_row)| expr),
+        )
+    }
+}
 
-            (input_value, original_row, order_by_row)
+// Assuming datums is a List, sort them by the 2nd through Nth elements
+// corresponding to order_by, then return the 1st element and computed order by expression.
+fn order_aggregate_datums_with_rank<'a, I>(
+    datums: I,
+    order_by: &[ColumnOrder],
+) -> impl Iterator<Item = (Datum<'a>, Row)>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let mut rows: Vec<(Datum, Row)> = datums
+        .into_iter()
+        .filter_map(|d| {
+            let list = d.unwrap_list();
+            let expr = list.iter().next().unwrap();
+            let order_row = Row::pack(list.iter().skip(1));
+            Some((expr, order_row))
         })
-        .collect_vec();
+        .collect();
 
-    let length = datums.len();
-    let mut result: Vec<(Datum, Datum)> = Vec::with_capacity(length);
-    for (idx, (current_datum, original_row, order_by_row)) in datums.iter().enumerate() {
-        let last_value = match &window_frame.end_bound {
-            WindowFrameBound::CurrentRow => match &window_frame.units {
-                // Always return the current value when in ROWS mode
-                WindowFrameUnits::Rows => *current_datum,
-                WindowFrameUnits::Range => {
-                    // When in RANGE mode, return the last value of the peer group
-                    // The peer group is the group of rows with the same ORDER BY value
-                    // Note: Range is only supported for the default window frame (RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW),
-                    // which is why it does not appear in the other branches
-                    datums[idx..]
-                        .iter()
-                        .take_while(|(_, _, row)| row == order_by_row)
-                        .last()
-                        .unwrap()
-                        .0
-                }
-                // GROUPS is not supported, and forbidden during planning
-                WindowFrameUnits::Groups => unreachable!(),
-            },
-            WindowFrameBound::UnboundedFollowing => {
-                if let WindowFrameBound::OffsetFollowing(start_offset) = &window_frame.start_bound {
-                    let start_offset = usize::cast_from(*start_offset);
+    let mut left_datum_vec = mz_repr::DatumVec::new();
+    let mut right_datum_vec = mz_repr::DatumVec::new();
+    let mut sort_by = |left: &(_, Row), right: &(_, Row)| {
+        let left = &left.1;
+        let right = &right.1;
+        let left_datums = left_datum_vec.borrow_with(left);
+        let right_datums = right_datum_vec.borrow_with(right);
+        compare_columns(order_by, &left_datums, &right_datums, || left.cmp(right))
+    };
+    rows.sort_by(&mut sort_by);
+    rows.into_iter()
+}
 
-                    // If the frame starts after the last row of the window, return null
-                    if idx + start_offset > length - 1 {
-                        Datum::Null
-                    } else {
-                        datums[length - 1].0
-                    }
-                } else {
-                    datums[length - 1].0
-                }
+/// Retains only the first occurrence of each datum in `datums`.
+fn dedup_datums<'a, I>(datums: I) -> impl Iterator<Item = Datum<'a>>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let mut seen = BTreeSet::new();
+    datums.into_iter().filter(move |d| seen.insert(*d))
+}
+
+fn array_concat<'a, I>(
+    datums: I,
+    temp_storage: &'a RowArena,
+    order_by: &[ColumnOrder],
+    distinct: bool,
+) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let datums = order_aggregate_datums(datums, order_by);
+    let datums = datums
+        .into_iter()
+        .map(|d| d.unwrap_array().elements().iter())
+        .flatten();
+    let datums: Vec<_> = if distinct {
+        dedup_datums(datums).collect()
+    } else {
+        datums.collect()
+    };
+    let dims = ArrayDimension {
+        lower_bound: 1,
+        length: datums.len(),
+    };
+    temp_storage.make_datum(|packer| {
+        packer.push_array(&[dims], datums).unwrap();
+    })
+}
+
+fn list_concat<'a, I>(
+    datums: I,
+    temp_storage: &'a RowArena,
+    order_by: &[ColumnOrder],
+    distinct: bool,
+) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let datums = order_aggregate_datums(datums, order_by);
+    let datums = datums.into_iter().map(|d| d.unwrap_list().iter()).flatten();
+    temp_storage.make_datum(|packer| {
+        if distinct {
+            packer.push_list(dedup_datums(datums));
+        } else {
+            packer.push_list(datums);
+        }
+    })
+}
+
+fn row_number<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let datums = order_aggregate_datums(datums, order_by);
+    let datums = datums
+        .into_iter()
+        .map(|d| d.unwrap_list().iter())
+        .flatten()
+        .zip(1i64..)
+        .map(|(d, i)| (Datum::Int64(i), d));
+
+    pack_ranked_datums(datums, temp_storage)
+}
+
+/// Sorts the datums according to the ORDER BY expressions, and groups them into
+/// runs of peers, i.e. of datums whose ORDER BY rows are equal. Each group
+/// contains the original rows of its peers.
+fn order_aggregate_datums_by_peers<'a, I>(
+    datums: I,
+    order_by: &[ColumnOrder],
+) -> impl Iterator<Item = Vec<Datum<'a>>>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // Keep the row used for ordering around, as it is used to identify peers
+    let mut datums = order_aggregate_datums_with_rank(datums, order_by).peekable();
+    // Only the identity, an empty list of rows, makes for a group without
+    // peers, which must not take up a rank
+    iter::from_fn(move || {
+        let (first_datum, first_row) = datums.next()?;
+        let mut peers = first_datum.unwrap_list().iter().collect_vec();
+        while let Some((next_datum, _)) = datums.next_if(|(_, next_row)| *next_row == first_row) {
+            peers.extend(next_datum.unwrap_list().iter());
+        }
+        Some(peers)
+    })
+    .filter(|peers| !peers.is_empty())
+}
+
+/// Packs the `(value, original_row)` records of a ranking window function.
+///
+/// Each record is packed straight into the output list as it is produced, so
+/// the arena holds the records only once, rather than once as datums of their
+/// own and again in the list.
+fn pack_ranked_datums<'a, I>(datums: I, temp_storage: &'a RowArena) -> Datum<'a>
+where
+    I: IntoIterator<Item = (Datum<'a>, Datum<'a>)>,
+{
+    temp_storage.make_datum(|packer| {
+        packer.push_list_with(|packer| {
+            for (value, original_row) in datums {
+                packer.push_list([value, original_row]);
             }
-            WindowFrameBound::OffsetFollowing(offset) => {
-                let end_offset = usize::cast_from(*offset);
-                let end_idx = idx.saturating_add(end_offset);
-                if let WindowFrameBound::OffsetFollowing(start_offset) = &window_frame.start_bound {
-                    let start_offset = usize::cast_from(*start_offset);
-                    let start_idx = idx.saturating_add(start_offset);
+        });
+    })
+}
 
-                    // If the frame is empty or starts after the last row of the window, return null
-                    if end_offset < start_offset || start_idx >= length {
-                        Datum::Null
-                    } else {
-                        // Return the last valid element in the window
-                        datums
-                            .get(end_idx)
-                            .map(|d| d.0)
-                            .unwrap_or(datums[length - 1].0)
-                    }
-                } else {
-                    datums
-                        .get(end_idx)
-                        .map(|d| d.0)
-                        .unwrap_or(datums[length - 1].0)
-                }
+fn rank<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // A rank skips past the size of the previous group of peers
+    let mut position = 1;
+    let datums = order_aggregate_datums_by_peers(datums, order_by).flat_map(|peers| {
+        let rank = position;
+        position += i64::try_from(peers.len()).expect("partition size fits in i64");
+        peers.into_iter().map(move |d| (Datum::Int64(rank), d))
+    });
+
+    pack_ranked_datums(datums, temp_storage)
+}
+
+fn dense_rank<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let datums = order_aggregate_datums_by_peers(datums, order_by)
+        .zip(1..)
+        .flat_map(|(peers, rank)| peers.into_iter().map(move |d| (Datum::Int64(rank), d)));
+
+    pack_ranked_datums(datums, temp_storage)
+}
+
+fn percent_rank<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // The number of rows is needed up front, so collect the groups of peers
+    let groups = order_aggregate_datums_by_peers(datums, order_by).collect_vec();
+    let rows: usize = groups.iter().map(|peers| peers.len()).sum();
+
+    // The relative rank is (rank - 1) / (rows - 1), or 0 for a single row
+    let mut preceding = 0;
+    let datums = groups.into_iter().flat_map(|peers| {
+        let percent_rank = if rows > 1 {
+            f64::cast_lossy(preceding) / f64::cast_lossy(rows - 1)
+        } else {
+            0.0
+        };
+        preceding += peers.len();
+        peers
+            .into_iter()
+            .map(move |d| (Datum::from(percent_rank), d))
+    });
+
+    pack_ranked_datums(datums, temp_storage)
+}
+
+fn cume_dist<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // The number of rows is needed up front, so collect the groups of peers
+    let groups = order_aggregate_datums_by_peers(datums, order_by).collect_vec();
+    let rows: usize = groups.iter().map(|peers| peers.len()).sum();
+
+    // The cumulative distribution counts the rows that precede or are peers of
+    // the current row
+    let mut preceding_or_peer = 0;
+    let datums = groups.into_iter().flat_map(|peers| {
+        preceding_or_peer += peers.len();
+        let cume_dist = f64::cast_lossy(preceding_or_peer) / f64::cast_lossy(rows);
+        peers.into_iter().map(move |d| (Datum::from(cume_dist), d))
+    });
+
+    pack_ranked_datums(datums, temp_storage)
+}
+
+// The expected input is in the format of [((OriginalRow, EncodedArgs), OrderByExprs...)]
+//
+// The offset may be an int32 or an int64. The planner rejects offsets of other
+// types, so any other offset is a bug, and is treated like a null offset. As in
+// PostgreSQL, a negative offset is not an error, but looks in the opposite
+// direction, i.e. `lag(x, -n)` is equivalent to `lead(x, n)`.
+//
+// With `ignore_nulls`, the offset counts only the rows whose input value is not
+// null, and the default value is produced when it runs past the partition.
+fn lag_lead<'a, I>(
+    datums: I,
+    temp_storage: &'a RowArena,
+    order_by: &[ColumnOrder],
+    lag_lead_type: &LagLeadType,
+    ignore_nulls: bool,
+) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // Sort the datums according to the ORDER BY expressions and return the (OriginalRow, EncodedArgs) record
+    let datums = order_aggregate_datums(datums, order_by);
+
+    // Decode the input (OriginalRow, EncodedArgs) into separate datums
+    // EncodedArgs = (InputValue, Offset, DefaultValue) for Lag/Lead
+    // The identity, an empty record, is skipped
+    let datums = datums
+        .into_iter()
+        .filter_map(|d| {
+            let mut iter = d.unwrap_list().iter();
+            let original_row = iter.next()?;
+            let mut encoded_args = iter.next().unwrap().unwrap_list().iter();
+            let (input_value, offset, default_value) = (
+                encoded_args.next().unwrap(),
+                encoded_args.next().unwrap(),
+                encoded_args.next().unwrap(),
+            );
+
+            Some((input_value, offset, default_value, original_row))
+        })
+        .collect_vec();
+
+    let non_nulls = if ignore_nulls {
+        datums.iter().positions(|d| !d.0.is_null()).collect_vec()
+    } else {
+        vec![]
+    };
+
+    let mut result: Vec<(Datum, Datum)> = Vec::with_capacity(datums.len());
+    for (idx, (_, offset, default_value, original_row)) in datums.iter().enumerate() {
+        soft_assert!(
+            matches!(offset, Datum::Null | Datum::Int32(_) | Datum::Int64(_)),
+            "lag/lead offset must be an integer, got {offset:?}"
+        );
+        let offset = match offset {
+            Datum::Int32(offset) => i64::from(*offset),
+            Datum::Int64(offset) => *offset,
+            // Null offsets are acceptable, and always return null
+            _ => {
+                result.push((Datum::Null, *original_row));
+                continue;
             }
-            WindowFrameBound::OffsetPreceding(offset) => {
-                let end_offset = usize::cast_from(*offset);
-                let end_idx = idx.saturating_sub(end_offset);
-                if idx < end_offset {
-                    // If the frame ends before the first row, return null
-                    Datum::Null
-                } else if let WindowFrameBound::OffsetPreceding(start_offset) =
-                    &window_frame.start_bound
-                {
-                    // If the frame is empty, return null
-                    if offset > start_offset {
-                        Datum::Null
-                    } else {
-                        datums[end_idx].0
-                    }
-                } else {
-                    datums[end_idx].0
-                }
+        };
+        // By default, offset is applied backwards (for `lag`): flip the sign if `lead` should run instead
+        let offset = match lag_lead_type {
+            LagLeadType::Lag => Some(offset),
+            LagLeadType::Lead => offset.checked_neg(),
+        };
+        // Offsets too large to address any row produce the default value.
+        let vec_offset = offset.and_then(|offset| {
+            if ignore_nulls {
+                lag_index_ignoring_nulls(&non_nulls, idx, offset)
+            } else {
+                let idx = i64::try_from(idx).expect("Array index does not fit in i64");
+                idx.checked_sub(offset)
+                    .and_then(|vec_offset| u64::try_from(vec_offset).ok())
+                    .map(usize::cast_from)
             }
-            // Forbidden during planning
-            WindowFrameBound::UnboundedPreceding => unreachable!(),
+        });
+
+        let lagged_value = match vec_offset {
+            Some(vec_offset) => datums
+                .get(vec_offset)
+                .map(|d| d.0)
+                .unwrap_or(*default_value),
+            None => *default_value,
         };
 
-        result.push((last_value, *original_row));
+        result.push((lagged_value, *original_row));
     }
 
     let result = result.into_iter().map(|(lag, original_row)| {
@@ -1077,491 +1766,1394 @@ where
     })
 }
 
-/// Identify whether the given aggregate function is Lag or Lead, since they share
-/// implementations.
-#[derive(
-    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
-)]
-pub enum LagLeadType {
-    Lag,
-    Lead,
+/// Returns the index of the row whose value `lag` reads at `offset` from the
+/// row at `idx`, counting only the rows at the sorted indexes in `non_nulls`.
+///
+/// A negative offset looks forward instead, like `lead`. A zero offset reads
+/// the row at `idx` itself, even if its value is null.
+fn lag_index_ignoring_nulls(non_nulls: &[usize], idx: usize, offset: i64) -> Option<usize> {
+    let steps = usize::try_from(offset.unsigned_abs()).ok()?;
+    if offset == 0 {
+        Some(idx)
+    } else if offset > 0 {
+        // The rows at `non_nulls[..preceding]` come before the current row
+        let preceding = non_nulls.partition_point(|i| *i < idx);
+        preceding.checked_sub(steps).map(|i| non_nulls[i])
+    } else {
+        // The rows at `non_nulls[following..]` come after the current row
+        let following = non_nulls.partition_point(|i| *i <= idx);
+        non_nulls.get(following.checked_add(steps - 1)?).copied()
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect)]
-pub enum AggregateFunc {
-    MaxNumeric,
-    MaxInt16,
-    MaxInt32,
-    MaxInt64,
-    MaxUInt16,
-    MaxUInt32,
-    MaxUInt64,
-    MaxMzTimestamp,
-    MaxFloat32,
-    MaxFloat64,
-    MaxBool,
-    MaxString,
-    MaxDate,
-    MaxTimestamp,
-    MaxTimestampTz,
-    MinNumeric,
-    MinInt16,
-    MinInt32,
-    MinInt64,
-    MinUInt16,
-    MinUInt32,
-    MinUInt64,
-    MinMzTimestamp,
-    MinFloat32,
-    MinFloat64,
-    MinBool,
-    MinString,
-    MinDate,
-    MinTimestamp,
-    MinTimestampTz,
-    SumInt16,
-    SumInt32,
-    SumInt64,
-    SumUInt16,
-    SumUInt32,
-    SumUInt64,
-    SumFloat32,
-    SumFloat64,
-    SumNumeric,
-    Count,
-    Any,
-    All,
-    /// Accumulates `Datum::List`s whose first element is a JSON-typed `Datum`s
-    /// into a JSON list. The other elements are columns used by `order_by`.
-    ///
-    /// WARNING: Unlike the `jsonb_agg` function that is exposed by the SQL
-    /// layer, this function filters out `Datum::Null`, for consistency with
-    /// the other aggregate functions.
-    JsonbAgg {
-        order_by: Vec<ColumnOrder>,
-    },
-    /// Zips `Datum::List`s whose first element is a JSON-typed `Datum`s into a
-    /// JSON map. The other elements are columns used by `order_by`.
-    ///
-    /// WARNING: Unlike the `jsonb_object_agg` function that is exposed by the SQL
-    /// layer, this function filters out `Datum::Null`, for consistency with
-    /// the other aggregate functions.
-    JsonbObjectAgg {
-        order_by: Vec<ColumnOrder>,
-    },
-    /// Accumulates `Datum::Array`s of `ScalarType::Record` whose first element is a `Datum::Array`
-    /// into a single `Datum::Array` (the remaining fields are used by `order_by`).
-    ArrayConcat {
-        order_by: Vec<ColumnOrder>,
-    },
-    /// Accumulates `Datum::List`s of `ScalarType::Record` whose first field is a `Datum::List`
-    /// into a single `Datum::List` (the remaining fields are used by `order_by`).
-    ListConcat {
-        order_by: Vec<ColumnOrder>,
-    },
-    StringAgg {
-        order_by: Vec<ColumnOrder>,
-    },
-    RowNumber {
-        order_by: Vec<ColumnOrder>,
-    },
-    DenseRank {
-        order_by: Vec<ColumnOrder>,
-    },
-    LagLead {
-        order_by: Vec<ColumnOrder>,
-        lag_lead: LagLeadType,
-    },
-    FirstValue {
-        order_by: Vec<ColumnOrder>,
-        window_frame: WindowFrame,
-    },
-    LastValue {
-        order_by: Vec<ColumnOrder>,
-        window_frame: WindowFrame,
-    },
-    /// Accumulates any number of `Datum::Dummy`s into `Datum::Dummy`.
-    ///
-    /// Useful for removing an expensive aggregation while maintaining the shape
-    /// of a reduce operator.
-    Dummy,
-}
-
-/// An explicit [`Arbitrary`] implementation needed here because of a known
-/// `proptest` issue.
-///
-/// Revert to the derive-macro impementation once the issue[^1] is fixed.
-///
-/// [^1]: <https://github.com/AltSysrq/proptest/issues/152>
-impl Arbitrary for AggregateFunc {
-    type Parameters = ();
+// The expected input is in the format of [((OriginalRow, InputValue), OrderByExprs...)]
+fn first_value<'a, I>(
+    datums: I,
+    temp_storage: &'a RowArena,
+    order_by: &[ColumnOrder],
+    window_frame: &WindowFrame,
+) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // Sort the datums according to the ORDER BY expressions and return the (OriginalRow, InputValue) record
+    let datums = order_aggregate_datums(datums, order_by);
 
-    type Strategy = Union<BoxedStrategy<Self>>;
+    // Decode the input (OriginalRow, InputValue) into separate datums
+    // The identity, an empty record, is skipped
+    let datums = datums
+        .into_iter()
+        .filter_map(|d| {
+            let mut iter = d.unwrap_list().iter();
+            let original_row = iter.next()?;
+            let input_value = iter.next().unwrap();
 
-    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
-        use proptest::collection::vec;
-        use proptest::prelude::any as proptest_any;
-        Union::new(vec![
-            Just(AggregateFunc::MaxNumeric).boxed(),
-            Just(AggregateFunc::MaxInt16).boxed(),
-            Just(AggregateFunc::MaxInt32).boxed(),
-            Just(AggregateFunc::MaxInt64).boxed(),
-            Just(AggregateFunc::MaxUInt16).boxed(),
-            Just(AggregateFunc::MaxUInt32).boxed(),
-            Just(AggregateFunc::MaxUInt64).boxed(),
-            Just(AggregateFunc::MaxMzTimestamp).boxed(),
-            Just(AggregateFunc::MaxFloat32).boxed(),
-            Just(AggregateFunc::MaxFloat64).boxed(),
-            Just(AggregateFunc::MaxBool).boxed(),
-            Just(AggregateFunc::MaxString).boxed(),
-            Just(AggregateFunc::MaxTimestamp).boxed(),
-            Just(AggregateFunc::MaxDate).boxed(),
-            Just(AggregateFunc::MaxTimestampTz).boxed(),
-            Just(AggregateFunc::MinNumeric).boxed(),
-            Just(AggregateFunc::MinInt16).boxed(),
-            Just(AggregateFunc::MinInt32).boxed(),
-            Just(AggregateFunc::MinInt64).boxed(),
-            Just(AggregateFunc::MinUInt16).boxed(),
-            Just(AggregateFunc::MinUInt32).boxed(),
-            Just(AggregateFunc::MinUInt64).boxed(),
-            Just(AggregateFunc::MinMzTimestamp).boxed(),
-            Just(AggregateFunc::MinFloat32).boxed(),
-            Just(AggregateFunc::MinFloat64).boxed(),
-            Just(AggregateFunc::MinBool).boxed(),
-            Just(AggregateFunc::MinString).boxed(),
-            Just(AggregateFunc::MinDate).boxed(),
-            Just(AggregateFunc::MinTimestamp).boxed(),
-            Just(AggregateFunc::MinTimestampTz).boxed(),
-            Just(AggregateFunc::SumInt16).boxed(),
-            Just(AggregateFunc::SumInt32).boxed(),
-            Just(AggregateFunc::SumInt64).boxed(),
-            Just(AggregateFunc::SumUInt16).boxed(),
-            Just(AggregateFunc::SumUInt32).boxed(),
-            Just(AggregateFunc::SumUInt64).boxed(),
-            Just(AggregateFunc::SumFloat32).boxed(),
-            Just(AggregateFunc::SumFloat64).boxed(),
-            Just(AggregateFunc::SumNumeric).boxed(),
-            Just(AggregateFunc::Count).boxed(),
-            Just(AggregateFunc::Any).boxed(),
-            Just(AggregateFunc::All).boxed(),
-            vec(proptest_any::<ColumnOrder>(), 1..4)
-                .prop_map(|order_by| AggregateFunc::JsonbAgg { order_by })
-                .boxed(),
-            vec(proptest_any::<ColumnOrder>(), 1..4)
-                .prop_map(|order_by| AggregateFunc::JsonbObjectAgg { order_by })
-                .boxed(),
-            vec(proptest_any::<ColumnOrder>(), 1..4)
-                .prop_map(|order_by| AggregateFunc::ArrayConcat { order_by })
-                .boxed(),
-            vec(proptest_any::<ColumnOrder>(), 1..4)
-                .prop_map(|order_by| AggregateFunc::ListConcat { order_by })
-                .boxed(),
-            vec(proptest_any::<ColumnOrder>(), 1..4)
-                .prop_map(|order_by| AggregateFunc::StringAgg { order_by })
-                .boxed(),
-            vec(proptest_any::<ColumnOrder>(), 1..4)
-                .prop_map(|order_by| AggregateFunc::RowNumber { order_by })
-                .boxed(),
-            vec(proptest_any::<ColumnOrder>(), 1..4)
-                .prop_map(|order_by| AggregateFunc::DenseRank { order_by })
-                .boxed(),
-            (
-                vec(proptest_any::<ColumnOrder>(), 1..4),
-                proptest_any::<LagLeadType>(),
-            )
-                .prop_map(|(order_by, lag_lead)| AggregateFunc::LagLead { order_by, lag_lead })
-                .boxed(),
-            (
-                vec(proptest_any::<ColumnOrder>(), 1..4),
-                proptest_any::<WindowFrame>(),
-            )
-                .prop_map(|(order_by, window_frame)| AggregateFunc::FirstValue {
-                    order_by,
-                    window_frame,
-                })
-                .boxed(),
-            (
-                vec(proptest_any::<ColumnOrder>(), 1..4),
-                proptest_any::<WindowFrame>(),
-            )
-                .prop_map(|(order_by, window_frame)| AggregateFunc::LastValue {
-                    order_by,
-                    window_frame,
-                })
-                .boxed(),
-            Just(AggregateFunc::Dummy).boxed(),
-        ])
-    }
-}
+            Some((input_value, original_row))
+        })
+        .collect_vec();
 
-impl RustType<ProtoColumnOrders> for Vec<ColumnOrder> {
-    fn into_proto(&self) -> ProtoColumnOrders {
-        ProtoColumnOrders {
-            orders: self.into_proto(),
-        }
-    }
+    let length = datums.len();
+    let mut result: Vec<(Datum, Datum)> = Vec::with_capacity(length);
+    for (idx, (current_datum, original_row)) in datums.iter().enumerate() {
+        let first_value = match &window_frame.start_bound {
+            // Always return the current value
+            WindowFrameBound::CurrentRow => *current_datum,
+            WindowFrameBound::UnboundedPreceding => {
+                if let WindowFrameBound::OffsetPreceding(end_offset) = &window_frame.end_bound {
+                    let end_offset = usize::cast_from(*end_offset);
 
-    fn from_proto(proto: ProtoColumnOrders) -> Result<Self, TryFromProtoError> {
-        proto.orders.into_rust()
-    }
-}
+                    // If the frame ends before the first row, return null
+                    if idx < end_offset {
+                        Datum::Null
+                    } else {
+                        datums[0].0
+                    }
+                } else {
+                    datums[0].0
+                }
+            }
+            WindowFrameBound::OffsetPreceding(offset) => {
+                let start_offset = usize::cast_from(*offset);
+                let start_idx = idx.saturating_sub(start_offset);
+                if let WindowFrameBound::OffsetPreceding(end_offset) = &window_frame.end_bound {
+                    let end_offset = usize::cast_from(*end_offset);
 
-impl RustType<ProtoAggregateFunc> for AggregateFunc {
-    fn into_proto(&self) -> ProtoAggregateFunc {
-        use proto_aggregate_func::Kind;
-        ProtoAggregateFunc {
-            kind: Some(match self {
-                AggregateFunc::MaxNumeric => Kind::MaxNumeric(()),
-                AggregateFunc::MaxInt16 => Kind::MaxInt16(()),
-                AggregateFunc::MaxInt32 => Kind::MaxInt32(()),
-                AggregateFunc::MaxInt64 => Kind::MaxInt64(()),
-                AggregateFunc::MaxUInt16 => Kind::MaxUint16(()),
-                AggregateFunc::MaxUInt32 => Kind::MaxUint32(()),
-                AggregateFunc::MaxUInt64 => Kind::MaxUint64(()),
-                AggregateFunc::MaxMzTimestamp => Kind::MaxMzTimestamp(()),
-                AggregateFunc::MaxFloat32 => Kind::MaxFloat32(()),
-                AggregateFunc::MaxFloat64 => Kind::MaxFloat64(()),
-                AggregateFunc::MaxBool => Kind::MaxBool(()),
-                AggregateFunc::MaxString => Kind::MaxString(()),
-                AggregateFunc::MaxDate => Kind::MaxDate(()),
-                AggregateFunc::MaxTimestamp => Kind::MaxTimestamp(()),
-                AggregateFunc::MaxTimestampTz => Kind::MaxTimestampTz(()),
-                AggregateFunc::MinNumeric => Kind::MinNumeric(()),
-                AggregateFunc::MinInt16 => Kind::MinInt16(()),
-                AggregateFunc::MinInt32 => Kind::MinInt32(()),
-                AggregateFunc::MinInt64 => Kind::MinInt64(()),
-                AggregateFunc::MinUInt16 => Kind::MinUint16(()),
-                AggregateFunc::MinUInt32 => Kind::MinUint32(()),
-                AggregateFunc::MinUInt64 => Kind::MinUint64(()),
-                AggregateFunc::MinMzTimestamp => Kind::MinMzTimestamp(()),
-                AggregateFunc::MinFloat32 => Kind::MinFloat32(()),
-                AggregateFunc::MinFloat64 => Kind::MinFloat64(()),
-                AggregateFunc::MinBool => Kind::MinBool(()),
-                AggregateFunc::MinString => Kind::MinString(()),
-                AggregateFunc::MinDate => Kind::MinDate(()),
-                AggregateFunc::MinTimestamp => Kind::MinTimestamp(()),
-                AggregateFunc::MinTimestampTz => Kind::MinTimestampTz(()),
-                AggregateFunc::SumInt16 => Kind::SumInt16(()),
-                AggregateFunc::SumInt32 => Kind::SumInt32(()),
-                AggregateFunc::SumInt64 => Kind::SumInt64(()),
-                AggregateFunc::SumUInt16 => Kind::SumUint16(()),
-                AggregateFunc::SumUInt32 => Kind::SumUint32(()),
-                AggregateFunc::SumUInt64 => Kind::SumUint64(()),
-                AggregateFunc::SumFloat32 => Kind::SumFloat32(()),
-                AggregateFunc::SumFloat64 => Kind::SumFloat64(()),
-                AggregateFunc::SumNumeric => Kind::SumNumeric(()),
-                AggregateFunc::Count => Kind::Count(()),
-                AggregateFunc::Any => Kind::Any(()),
-                AggregateFunc::All => Kind::All(()),
-                AggregateFunc::JsonbAgg { order_by } => Kind::JsonbAgg(order_by.into_proto()),
-                AggregateFunc::JsonbObjectAgg { order_by } => {
-                    Kind::JsonbObjectAgg(order_by.into_proto())
+                    // If the frame is empty or ends before the first row, return null
+                    if start_offset < end_offset || idx < end_offset {
+                        Datum::Null
+                    } else {
+                        datums[start_idx].0
+                    }
+                } else {
+                    datums[start_idx].0
                 }
-                AggregateFunc::ArrayConcat { order_by } => Kind::ArrayConcat(order_by.into_proto()),
-                AggregateFunc::ListConcat { order_by } => Kind::ListConcat(order_by.into_proto()),
-                AggregateFunc::StringAgg { order_by } => Kind::StringAgg(order_by.into_proto()),
-                AggregateFunc::RowNumber { order_by } => Kind::RowNumber(order_by.into_proto()),
-                AggregateFunc::DenseRank { order_by } => Kind::DenseRank(order_by.into_proto()),
-                AggregateFunc::LagLead { order_by, lag_lead } => {
-                    Kind::LagLead(proto_aggregate_func::ProtoLagLead {
-                        order_by: Some(order_by.into_proto()),
-                        lag_lead: Some(match lag_lead {
-                            LagLeadType::Lag => {
-                                proto_aggregate_func::proto_lag_lead::LagLead::Lag(())
-                            }
-                            LagLeadType::Lead => {
-                                proto_aggregate_func::proto_lag_lead::LagLead::Lead(())
-                            }
-                        }),
-                    })
+            }
+            WindowFrameBound::OffsetFollowing(offset) => {
+                let start_offset = usize::cast_from(*offset);
+                let start_idx = idx.saturating_add(start_offset);
+                if let WindowFrameBound::OffsetFollowing(end_offset) = &window_frame.end_bound {
+                    // If the frame is empty or starts after the last row, return null
+                    if offset > end_offset || start_idx >= length {
+                        Datum::Null
+                    } else {
+                        datums[start_idx].0
+                    }
+                } else {
+                    datums.get(start_idx).map(|d| d.0).unwrap_or(Datum::Null)
                 }
-                AggregateFunc::FirstValue {
-                    order_by,
-                    window_frame,
-                } => Kind::FirstValue(proto_aggregate_func::ProtoWindowFrame {
-                    order_by: Some(order_by.into_proto()),
-                    window_frame: Some(window_frame.into_proto()),
-                }),
-                AggregateFunc::LastValue {
-                    order_by,
-                    window_frame,
-                } => Kind::LastValue(proto_aggregate_func::ProtoWindowFrame {
-                    order_by: Some(order_by.into_proto()),
-                    window_frame: Some(window_frame.into_proto()),
-                }),
-                AggregateFunc::Dummy => Kind::Dummy(()),
-            }),
-        }
+            }
+            // Forbidden during planning
+            WindowFrameBound::UnboundedFollowing => unreachable!(),
+        };
+
+        result.push((first_value, *original_row));
     }
 
-    fn from_proto(proto: ProtoAggregateFunc) -> Result<Self, TryFromProtoError> {
-        use proto_aggregate_func::Kind;
-        let kind = proto
-            .kind
-            .ok_or_else(|| TryFromProtoError::missing_field("ProtoAggregateFunc::kind"))?;
-        Ok(match kind {
-            Kind::MaxNumeric(()) => AggregateFunc::MaxNumeric,
-            Kind::MaxInt16(()) => AggregateFunc::MaxInt16,
-            Kind::MaxInt32(()) => AggregateFunc::MaxInt32,
-            Kind::MaxInt64(()) => AggregateFunc::MaxInt64,
-            Kind::MaxUint16(()) => AggregateFunc::MaxUInt16,
-            Kind::MaxUint32(()) => AggregateFunc::MaxUInt32,
-            Kind::MaxUint64(()) => AggregateFunc::MaxUInt64,
-            Kind::MaxMzTimestamp(()) => AggregateFunc::MaxMzTimestamp,
-            Kind::MaxFloat32(()) => AggregateFunc::MaxFloat32,
-            Kind::MaxFloat64(()) => AggregateFunc::MaxFloat64,
-            Kind::MaxBool(()) => AggregateFunc::MaxBool,
-            Kind::MaxString(()) => AggregateFunc::MaxString,
-            Kind::MaxDate(()) => AggregateFunc::MaxDate,
-            Kind::MaxTimestamp(()) => AggregateFunc::MaxTimestamp,
-            Kind::MaxTimestampTz(()) => AggregateFunc::MaxTimestampTz,
-            Kind::MinNumeric(()) => AggregateFunc::MinNumeric,
-            Kind::MinInt16(()) => AggregateFunc::MinInt16,
-            Kind::MinInt32(()) => AggregateFunc::MinInt32,
-            Kind::MinInt64(()) => AggregateFunc::MinInt64,
-            Kind::MinUint16(()) => AggregateFunc::MinUInt16,
-            Kind::MinUint32(()) => AggregateFunc::MinUInt32,
-            Kind::MinUint64(()) => AggregateFunc::MinUInt64,
-            Kind::MinMzTimestamp(()) => AggregateFunc::MinMzTimestamp,
-            Kind::MinFloat32(()) => AggregateFunc::MinFloat32,
-            Kind::MinFloat64(()) => AggregateFunc::MinFloat64,
-            Kind::MinBool(()) => AggregateFunc::MinBool,
-            Kind::MinString(()) => AggregateFunc::MinString,
-            Kind::MinDate(()) => AggregateFunc::MinDate,
-            Kind::MinTimestamp(()) => AggregateFunc::MinTimestamp,
-            Kind::MinTimestampTz(()) => AggregateFunc::MinTimestampTz,
-            Kind::SumInt16(()) => AggregateFunc::SumInt16,
-            Kind::SumInt32(()) => AggregateFunc::SumInt32,
-            Kind::SumInt64(()) => AggregateFunc::SumInt64,
-            Kind::SumUint16(()) => AggregateFunc::SumUInt16,
-            Kind::SumUint32(()) => AggregateFunc::SumUInt32,
-            Kind::SumUint64(()) => AggregateFunc::SumUInt64,
-            Kind::SumFloat32(()) => AggregateFunc::SumFloat32,
-            Kind::SumFloat64(()) => AggregateFunc::SumFloat64,
-            Kind::SumNumeric(()) => AggregateFunc::SumNumeric,
-            Kind::Count(()) => AggregateFunc::Count,
-            Kind::Any(()) => AggregateFunc::Any,
-            Kind::All(()) => AggregateFunc::All,
-            Kind::JsonbAgg(order_by) => AggregateFunc::JsonbAgg {
-                order_by: order_by.into_rust()?,
-            },
-            Kind::JsonbObjectAgg(order_by) => AggregateFunc::JsonbObjectAgg {
-                order_by: order_by.into_rust()?,
-            },
-            Kind::ArrayConcat(order_by) => AggregateFunc::ArrayConcat {
-                order_by: order_by.into_rust()?,
-            },
-            Kind::ListConcat(order_by) => AggregateFunc::ListConcat {
-                order_by: order_by.into_rust()?,
-            },
-            Kind::StringAgg(order_by) => AggregateFunc::StringAgg {
-                order_by: order_by.into_rust()?,
-            },
-            Kind::RowNumber(order_by) => AggregateFunc::RowNumber {
-                order_by: order_by.into_rust()?,
-            },
-            Kind::DenseRank(order_by) => AggregateFunc::DenseRank {
-                order_by: order_by.into_rust()?,
-            },
-            Kind::LagLead(pll) => AggregateFunc::LagLead {
-                order_by: pll.order_by.into_rust_if_some("ProtoLagLead::order_by")?,
-                lag_lead: match pll.lag_lead {
-                    Some(proto_aggregate_func::proto_lag_lead::LagLead::Lag(())) => {
-                        LagLeadType::Lag
-                    }
-                    Some(proto_aggregate_func::proto_lag_lead::LagLead::Lead(())) => {
-                        LagLeadType::Lead
-                    }
-                    None => {
-                        return Err(TryFromProtoError::MissingField(
-                            "ProtoLagLead::lag_lead".into(),
-                        ))
-                    }
-                },
-            },
-            Kind::FirstValue(pfv) => AggregateFunc::FirstValue {
-                order_by: pfv
-                    .order_by
-                    .into_rust_if_some("ProtoWindowFrame::order_by")?,
-                window_frame: pfv
-                    .window_frame
-                    .into_rust_if_some("ProtoWindowFrame::window_frame")?,
-            },
-            Kind::LastValue(pfv) => AggregateFunc::LastValue {
-                order_by: pfv
-                    .order_by
-                    .into_rust_if_some("ProtoWindowFrame::order_by")?,
-                window_frame: pfv
-                    .window_frame
-                    .into_rust_if_some("ProtoWindowFrame::window_frame")?,
-            },
-            Kind::Dummy(()) => AggregateFunc::Dummy,
+    let result = result.into_iter().map(|(lag, original_row)| {
+        temp_storage.make_datum(|packer| {
+            packer.push_list(vec![lag, original_row]);
         })
-    }
+    });
+
+    temp_storage.make_datum(|packer| {
+        packer.push_list(result);
+    })
 }
 
-impl AggregateFunc {
-    pub fn eval<'a, I>(&self, datums: I, temp_storage: &'a RowArena) -> Datum<'a>
-    where
-        I: IntoIterator<Item = Datum<'a>>,
-    {
-        match self {
-            AggregateFunc::MaxNumeric => max_numeric(datums),
-            AggregateFunc::MaxInt16 => max_int16(datums),
-            AggregateFunc::MaxInt32 => max_int32(datums),
-            AggregateFunc::MaxInt64 => max_int64(datums),
-            AggregateFunc::MaxUInt16 => max_uint16(datums),
-            AggregateFunc::MaxUInt32 => max_uint32(datums),
-            AggregateFunc::MaxUInt64 => max_uint64(datums),
-            AggregateFunc::MaxMzTimestamp => max_mz_timestamp(datums),
-            AggregateFunc::MaxFloat32 => max_float32(datums),
-            AggregateFunc::MaxFloat64 => max_float64(datums),
-            AggregateFunc::MaxBool => max_bool(datums),
-            AggregateFunc::MaxString => max_string(datums),
-            AggregateFunc::MaxDate => max_date(datums),
-            AggregateFunc::MaxTimestamp => max_timestamp(datums),
-            AggregateFunc::MaxTimestampTz => max_timestamptz(datums),
-            AggregateFunc::MinNumeric => min_numeric(datums),
-            AggregateFunc::MinInt16 => min_int16(datums),
-            AggregateFunc::MinInt32 => min_int32(datums),
-            AggregateFunc::MinInt64 => min_int64(datums),
-            AggregateFunc::MinUInt16 => min_uint16(datums),
-            AggregateFunc::MinUInt32 => min_uint32(datums),
-            AggregateFunc::MinUInt64 => min_uint64(datums),
-            AggregateFunc::MinMzTimestamp => min_mz_timestamp(datums),
-            AggregateFunc::MinFloat32 => min_float32(datums),
-            AggregateFunc::MinFloat64 => min_float64(datums),
-            AggregateFunc::MinBool => min_bool(datums),
-            AggregateFunc::MinString => min_string(datums),
-            AggregateFunc::MinDate => min_date(datums),
-            AggregateFunc::MinTimestamp => min_timestamp(datums),
-            AggregateFunc::MinTimestampTz => min_timestamptz(datums),
-            AggregateFunc::SumInt16 => sum_int16(datums),
-            AggregateFunc::SumInt32 => sum_int32(datums),
-            AggregateFunc::SumInt64 => sum_int64(datums),
-            AggregateFunc::SumUInt16 => sum_uint16(datums),
-            AggregateFunc::SumUInt32 => sum_uint32(datums),
-            AggregateFunc::SumUInt64 => sum_uint64(datums),
-            AggregateFunc::SumFloat32 => sum_float32(datums),
-            AggregateFunc::SumFloat64 => sum_float64(datums),
-            AggregateFunc::SumNumeric => sum_numeric(datums),
-            AggregateFunc::Count => count(datums),
-            AggregateFunc::Any => any(datums),
-            AggregateFunc::All => all(datums),
-            AggregateFunc::JsonbAgg { order_by } => jsonb_agg(datums, temp_storage, order_by),
-            AggregateFunc::JsonbObjectAgg { order_by } => {
-                jsonb_object_agg(datums, temp_storage, order_by)
-            }
-            AggregateFunc::ArrayConcat { order_by } => array_concat(datums, temp_storage, order_by),
-            AggregateFunc::ListConcat { order_by } => list_concat(datums, temp_storage, order_by),
-            AggregateFunc::StringAgg { order_by } => string_agg(datums, temp_storage, order_by),
-            AggregateFunc::RowNumber { order_by } => row_number(datums, temp_storage, order_by),
-            AggregateFunc::DenseRank { order_by } => dense_rank(datums, temp_storage, order_by),
-            AggregateFunc::LagLead {
-                order_by,
-                lag_lead: lag_lead_type,
-            } => lag_lead(datums, temp_storage, order_by, lag_lead_type),
-            AggregateFunc::FirstValue {
+// The expected input is in the format of [((OriginalRow, InputValue), OrderByExprs...)]
+fn last_value<'a, I>(
+    datums: I,
+    temp_storage: &'a RowArena,
+    order_by: &[ColumnOrder],
+    window_frame: &WindowFrame,
+) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // Sort the datums according to the ORDER BY expressions and return the ((OriginalRow, InputValue), OrderByRow) record
+    // The OrderByRow is kept around because it is required to compute the peer groups in RANGE mode
+    let datums = order_aggregate_datums_with_rank(datums, order_by);
+
+    // Decode the input (OriginalRow, InputValue) into separate datums, while keeping the OrderByRow
+    // The identity, an empty record, is skipped
+    let datums = datums
+        .into_iter()
+        .filter_map(|(d, order_by_row)| {
+            let mut iter = d.unwrap_list().iter();
+            let original_row = iter.next()?;
+            let input_value = iter.next().unwrap();
+
+            Some((input_value, original_row, order_by_row))
+        })
+        .collect_vec();
+
+    let length = datums.len();
+    let mut result: Vec<(Datum, Datum)> = Vec::with_capacity(length);
+    for (idx, (current_datum, original_row, order_by_row)) in datums.iter().enumerate() {
+        let last_value = match &window_frame.end_bound {
+            WindowFrameBound::CurrentRow => match &window_frame.units {
+                // Always return the current value when in ROWS mode
+                WindowFrameUnits::Rows => *current_datum,
+                WindowFrameUnits::Range => {
+                    // When in RANGE mode, return the last value of the peer group
+                    // The peer group is the group of rows with the same ORDER BY value
+                    // Note: Range is only supported for the default window frame (RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW),
+                    // which is why it does not appear in the other branches
+                    datums[idx..]
+                        .iter()
+                        .take_while(|(_, _, row)| row == order_by_row)
+                        .last()
+                        .unwrap()
+                        .0
+                }
+                // GROUPS is not supported, and forbidden during planning
+                WindowFrameUnits::Groups => unreachable!(),
+            },
+            WindowFrameBound::UnboundedFollowing => {
+                if let WindowFrameBound::OffsetFollowing(start_offset) = &window_frame.start_bound {
+                    let start_offset = usize::cast_from(*start_offset);
+
+                    // If the frame starts after the last row of the window, return null
+                    if idx + start_offset > length - 1 {
+                        Datum::Null
+                    } else {
+                        datums[length - 1].0
+                    }
+                } else {
+                    datums[length - 1].0
+                }
+            }
+            WindowFrameBound::OffsetFollowing(offset) => {
+                let end_offset = usize::cast_from(*offset);
+                let end_idx = idx.saturating_add(end_offset);
+                if let WindowFrameBound::OffsetFollowing(start_offset) = &window_frame.start_bound {
+                    let start_offset = usize::cast_from(*start_offset);
+                    let start_idx = idx.saturating_add(start_offset);
+
+                    // If the frame is empty or starts after the last row of the window, return null
+                    if end_offset < start_offset || start_idx >= length {
+                        Datum::Null
+                    } else {
+                        // Return the last valid element in the window
+                        datums
+                            .get(end_idx)
+                            .map(|d| d.0)
+                            .unwrap_or(datums[length - 1].0)
+                    }
+                } else {
+                    datums
+                        .get(end_idx)
+                        .map(|d| d.0)
+                        .unwrap_or(datums[length - 1].0)
+                }
+            }
+            WindowFrameBound::OffsetPreceding(offset) => {
+                let end_offset = usize::cast_from(*offset);
+                let end_idx = idx.saturating_sub(end_offset);
+                if idx < end_offset {
+                    // If the frame ends before the first row, return null
+                    Datum::Null
+                } else if let WindowFrameBound::OffsetPreceding(start_offset) =
+                    &window_frame.start_bound
+                {
+                    // If the frame is empty, return null
+                    if offset > start_offset {
+                        Datum::Null
+                    } else {
+                        datums[end_idx].0
+                    }
+                } else {
+                    datums[end_idx].0
+                }
+            }
+            // Forbidden during planning
+            WindowFrameBound::UnboundedPreceding => unreachable!(),
+        };
+
+        result.push((last_value, *original_row));
+    }
+
+    let result = result.into_iter().map(|(lag, original_row)| {
+        temp_storage.make_datum(|packer| {
+            packer.push_list(vec![lag, original_row]);
+        })
+    });
+
+    temp_storage.make_datum(|packer| {
+        packer.push_list(result);
+    })
+}
+
+// The expected input is in the format of [((OriginalRow, (InputValue, N)), OrderByExprs...)]
+fn nth_value<'a, I>(
+    datums: I,
+    temp_storage: &'a RowArena,
+    order_by: &[ColumnOrder],
+    window_frame: &WindowFrame,
+) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // Sort the datums according to the ORDER BY expressions and return the ((OriginalRow, EncodedArgs), OrderByRow) record
+    // The OrderByRow is kept around because it is required to compute the peer groups in RANGE mode
+    let datums = order_aggregate_datums_with_rank(datums, order_by);
+
+    // Decode the input (OriginalRow, EncodedArgs) into separate datums, while keeping the OrderByRow
+    // EncodedArgs = (InputValue, N) for NthValue
+    // The identity, an empty record, is skipped
+    let datums = datums
+        .into_iter()
+        .filter_map(|(d, order_by_row)| {
+            let mut iter = d.unwrap_list().iter();
+            let original_row = iter.next()?;
+            let mut encoded_args = iter.next().unwrap().unwrap_list().iter();
+            let (input_value, n) = (encoded_args.next().unwrap(), encoded_args.next().unwrap());
+
+            Some((input_value, n, original_row, order_by_row))
+        })
+        .collect_vec();
+
+    let length = datums.len();
+    let mut result: Vec<(Datum, Datum)> = Vec::with_capacity(length);
+    for (idx, (_, n, original_row, order_by_row)) in datums.iter().enumerate() {
+        // The number of peers of the current row before and after it, which
+        // bound the frame at the current row in RANGE mode
+        let peers_before = datums[..idx]
+            .iter()
+            .rev()
+            .take_while(|(_, _, _, row)| row == order_by_row)
+            .count();
+        let peers_after = datums[idx + 1..]
+            .iter()
+            .take_while(|(_, _, _, row)| row == order_by_row)
+            .count();
+
+        // The first row of the frame, if the frame does not start after the last row
+        let frame_start = match &window_frame.start_bound {
+            WindowFrameBound::UnboundedPreceding => Some(0),
+            WindowFrameBound::OffsetPreceding(offset) => {
+                Some(idx.saturating_sub(usize::cast_from(*offset)))
+            }
+            WindowFrameBound::CurrentRow => match &window_frame.units {
+                WindowFrameUnits::Rows => Some(idx),
+                WindowFrameUnits::Range => Some(idx - peers_before),
+                // GROUPS is not supported, and forbidden during planning
+                WindowFrameUnits::Groups => unreachable!(),
+            },
+            WindowFrameBound::OffsetFollowing(offset) => idx.checked_add(usize::cast_from(*offset)),
+            // Forbidden during planning
+            WindowFrameBound::UnboundedFollowing => unreachable!(),
+        };
+        // The last row of the frame, if the frame does not end before the first row
+        let frame_end = match &window_frame.end_bound {
+            WindowFrameBound::UnboundedFollowing => Some(length - 1),
+            WindowFrameBound::OffsetFollowing(offset) => Some(
+                idx.saturating_add(usize::cast_from(*offset))
+                    .min(length - 1),
+            ),
+            WindowFrameBound::CurrentRow => match &window_frame.units {
+                WindowFrameUnits::Rows => Some(idx),
+                WindowFrameUnits::Range => Some(idx + peers_after),
+                // GROUPS is not supported, and forbidden during planning
+                WindowFrameUnits::Groups => unreachable!(),
+            },
+            WindowFrameBound::OffsetPreceding(offset) => idx.checked_sub(usize::cast_from(*offset)),
+            // Forbidden during planning
+            WindowFrameBound::UnboundedPreceding => unreachable!(),
+        };
+
+        // `n` is 1-based, so a null, non-positive, or too large `n` addresses no row
+        let nth_value = match (n, frame_start, frame_end) {
+            (Datum::Null, _, _) | (_, None, _) | (_, _, None) => Datum::Null,
+            (n, Some(frame_start), Some(frame_end)) => usize::try_from(n.unwrap_int32())
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|offset| frame_start.checked_add(offset))
+                .filter(|nth_idx| *nth_idx <= frame_end)
+                .map(|nth_idx| datums[nth_idx].0)
+                .unwrap_or(Datum::Null),
+        };
+
+        result.push((nth_value, *original_row));
+    }
+
+    let result = result.into_iter().map(|(nth_value, original_row)| {
+        temp_storage.make_datum(|packer| {
+            packer.push_list(vec![nth_value, original_row]);
+        })
+    });
+
+    temp_storage.make_datum(|packer| {
+        packer.push_list(result);
+    })
+}
+
+// The expected input is in the format of [((OriginalRow, N), OrderByExprs...)]
+fn ntile<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    // Sort the datums according to the ORDER BY expressions and return the (OriginalRow, N) record
+    let datums = order_aggregate_datums(datums, order_by);
+
+    // Decode the input (OriginalRow, N) into separate datums
+    // The identity, an empty record, is skipped
+    let datums = datums
+        .into_iter()
+        .filter_map(|d| {
+            let mut iter = d.unwrap_list().iter();
+            let original_row = iter.next()?;
+            let n = iter.next().unwrap();
+
+            Some((n, original_row))
+        })
+        .collect_vec();
+
+    let length = datums.len();
+    let result = datums.iter().enumerate().map(|(idx, (n, original_row))| {
+        let bucket = match n {
+            Datum::Null => Datum::Null,
+            n => match usize::try_from(n.unwrap_int32()) {
+                Ok(n) if n > 0 => {
+                    // The first `length % n` buckets hold one row more than the rest
+                    let small = length / n;
+                    let large_rows = (length % n) * (small + 1);
+                    let bucket = if idx < large_rows {
+                        idx / (small + 1)
+                    } else {
+                        // `small` is non-zero here, as otherwise every row is in a large bucket
+                        length % n + (idx - large_rows) / small
+                    };
+                    Datum::Int64(i64::try_from(bucket + 1).expect("bucket fits in i64"))
+                }
+                _ => Datum::Null,
+            },
+        };
+
+        temp_storage.make_datum(|packer| {
+            packer.push_list(vec![bucket, *original_row]);
+        })
+    });
+
+    temp_storage.make_datum(|packer| {
+        packer.push_list(result);
+    })
+}
+
+/// The output type of a window function: a list of `(value, original row)`
+/// records, one per row of the partition.
+fn window_output_type(
+    column_name: &str,
+    value_type: ColumnType,
+    original_row_type: ScalarType,
+) -> ScalarType {
+    ScalarType::List {
+        element_type: Box::new(ScalarType::Record {
+            fields: vec![
+                (ColumnName::from(column_name), value_type),
+                (
+                    ColumnName::from("?record?"),
+                    original_row_type.nullable(false),
+                ),
+            ],
+            custom_id: None,
+        }),
+        custom_id: None,
+    }
+}
+
+/// Moves the field types out of a record type.
+fn record_field_types(typ: ScalarType) -> Vec<ScalarType> {
+    match typ {
+        ScalarType::Record { fields, .. } => {
+            fields.into_iter().map(|(_, typ)| typ.scalar_type).collect()
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// The input type for the ranking window functions is
+/// `(List<OriginalRow>, OrderByExprs...)`; returns `OriginalRow`.
+fn scalar_window_original_row_type(input_type: ScalarType) -> ScalarType {
+    match record_field_types(input_type).swap_remove(0) {
+        ScalarType::List { element_type, .. } => *element_type,
+        _ => unreachable!(),
+    }
+}
+
+/// The input type for the value window functions is
+/// `((OriginalRow, EncodedArgs), OrderByExprs...)`; returns `OriginalRow` and
+/// `EncodedArgs`.
+fn value_window_input_types(input_type: ScalarType) -> (ScalarType, ScalarType) {
+    let mut fields = record_field_types(record_field_types(input_type).swap_remove(0));
+    let args_type = fields.swap_remove(1);
+    let original_row_type = fields.swap_remove(0);
+    (original_row_type, args_type)
+}
+
+/// Identify whether the given aggregate function is Lag or Lead, since they share
+/// implementations.
+#[derive(
+    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
+)]
+pub enum LagLeadType {
+    Lag,
+    Lead,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect)]
+pub enum AggregateFunc {
+    MaxNumeric,
+    MaxInt16,
+    MaxInt32,
+    MaxInt64,
+    MaxUInt16,
+    MaxUInt32,
+    MaxUInt64,
+    MaxMzTimestamp,
+    MaxFloat32,
+    MaxFloat64,
+    MaxBool,
+    MaxString,
+    MaxDate,
+    MaxTimestamp,
+    MaxTimestampTz,
+    MaxInterval,
+    MaxTime,
+    MaxBytes,
+    MaxUuid,
+    MaxArray,
+    MinNumeric,
+    MinInt16,
+    MinInt32,
+    MinInt64,
+    MinUInt16,
+    MinUInt32,
+    MinUInt64,
+    MinMzTimestamp,
+    MinFloat32,
+    MinFloat64,
+    MinBool,
+    MinString,
+    MinDate,
+    MinTimestamp,
+    MinTimestampTz,
+    MinInterval,
+    MinTime,
+    MinBytes,
+    MinUuid,
+    MinArray,
+    SumInt16,
+    SumInt32,
+    SumInt64,
+    SumUInt16,
+    SumUInt32,
+    SumUInt64,
+    SumFloat32,
+    SumFloat64,
+    SumNumeric,
+    SumInterval,
+    ProductFloat32,
+    ProductFloat64,
+    ProductNumeric,
+    AvgInt16,
+    AvgInt32,
+    AvgInt64,
+    AvgFloat32,
+    AvgFloat64,
+    AvgNumeric,
+    AvgInterval,
+    StddevPop,
+    StddevSamp,
+    VarPop,
+    VarSamp,
+    /// Accumulates `Datum::List`s of two float64 values `(y, x)` into their
+    /// correlation coefficient. Pairs in which either value is null are
+    /// skipped.
+    Corr,
+    /// Like `Corr`, but computes the population covariance.
+    CovarPop,
+    /// Like `Corr`, but computes the sample covariance.
+    CovarSamp,
+    /// Like `Corr`, but counts the pairs in which neither value is null.
+    RegrCount,
+    /// Like `Corr`, but computes the slope of the least-squares line.
+    RegrSlope,
+    /// Like `Corr`, but computes the y-intercept of the least-squares line.
+    RegrIntercept,
+    /// Like `Corr`, but computes the coefficient of determination.
+    RegrR2,
+    Count,
+    /// Like `Count`, but produces a numeric value that cannot overflow.
+    CountNumeric,
+    /// Counts the distinct non-null values.
+    CountDistinct,
+    Any,
+    All,
+    /// Like `All`, but returns `Datum::Null` rather than `Datum::True` when
+    /// applied to an empty input.
+    BoolAnd,
+    /// Like `Any`, but returns `Datum::Null` rather than `Datum::False` when
+    /// applied to an empty input.
+    BoolOr,
+    /// Accumulates `Datum::List`s whose first element is a JSON-typed `Datum`s
+    /// into a JSON list. The other elements are columns used by `order_by`.
+    ///
+    /// WARNING: Unlike the `jsonb_agg` function that is exposed by the SQL
+    /// layer, this function filters out `Datum::Null`, for consistency with
+    /// the other aggregate functions.
+    JsonbAgg {
+        order_by: Vec<ColumnOrder>,
+    },
+    /// Zips `Datum::List`s whose first element is a JSON-typed `Datum`s into a
+    /// JSON map. The other elements are columns used by `order_by`.
+    ///
+    /// WARNING: Unlike the `jsonb_object_agg` function that is exposed by the SQL
+    /// layer, this function filters out `Datum::Null`, for consistency with
+    /// the other aggregate functions.
+    JsonbObjectAgg {
+        order_by: Vec<ColumnOrder>,
+        /// Whether to replace each value with its text representation, as the
+        /// `_text` variants of the JSON functions do.
+        stringify_values: bool,
+    },
+    /// Accumulates `Datum::Array`s of `ScalarType::Record` whose first element is a `Datum::Array`
+    /// into a single `Datum::Array` (the remaining fields are used by `order_by`).
+    ArrayConcat {
+        order_by: Vec<ColumnOrder>,
+    },
+    /// Accumulates `Datum::List`s of `ScalarType::Record` whose first field is a `Datum::List`
+    /// into a single `Datum::List` (the remaining fields are used by `order_by`).
+    ListConcat {
+        order_by: Vec<ColumnOrder>,
+    },
+    /// Like `ArrayConcat`, but keeps only the first occurrence of each element
+    /// after ordering. Null elements are deduplicated like any other element.
+    ArrayConcatDistinct {
+        order_by: Vec<ColumnOrder>,
+    },
+    /// Like `ListConcat`, but keeps only the first occurrence of each element
+    /// after ordering. Null elements are deduplicated like any other element.
+    ListConcatDistinct {
+        order_by: Vec<ColumnOrder>,
+    },
+    StringAgg {
+        order_by: Vec<ColumnOrder>,
+    },
+    RowNumber {
+        order_by: Vec<ColumnOrder>,
+    },
+    Rank {
+        order_by: Vec<ColumnOrder>,
+    },
+    DenseRank {
+        order_by: Vec<ColumnOrder>,
+    },
+    /// The relative rank of each row, `(rank - 1) / (rows - 1)`, or 0 for a
+    /// single row.
+    PercentRank {
+        order_by: Vec<ColumnOrder>,
+    },
+    /// The fraction of the rows that precede or are peers of each row.
+    CumeDist {
+        order_by: Vec<ColumnOrder>,
+    },
+    LagLead {
+        order_by: Vec<ColumnOrder>,
+        lag_lead: LagLeadType,
+        /// Whether the offset skips over null input values, as with `IGNORE
+        /// NULLS`.
+        ignore_nulls: bool,
+    },
+    FirstValue {
+        order_by: Vec<ColumnOrder>,
+        window_frame: WindowFrame,
+    },
+    LastValue {
+        order_by: Vec<ColumnOrder>,
+        window_frame: WindowFrame,
+    },
+    /// Returns the value at the 1-based position `n` of the window frame of
+    /// each row. The value is null if `n` is null or beyond the end of the
+    /// frame. PostgreSQL rejects an `n` that is not positive, but as
+    /// aggregates cannot error, such an `n` also produces null.
+    NthValue {
+        order_by: Vec<ColumnOrder>,
+        window_frame: WindowFrame,
+    },
+    /// Divides the ordered partition into `n` buckets that are as equal in
+    /// size as possible, and returns the 1-based bucket of each row. The
+    /// earlier buckets receive one more row if the partition does not divide
+    /// evenly. The bucket is null if `n` is null or not positive, as
+    /// aggregates cannot error.
+    Ntile {
+        order_by: Vec<ColumnOrder>,
+    },
+    /// Accumulates any number of `Datum::Dummy`s into `Datum::Dummy`.
+    ///
+    /// Useful for removing an expensive aggregation while maintaining the shape
+    /// of a reduce operator.
+    Dummy,
+}
+
+/// An explicit [`Arbitrary`] implementation needed here because of a known
+/// `proptest` issue.
+///
+/// Revert to the derive-macro impementation once the issue[^1] is fixed.
+///
+/// [^1]: <https://github.com/AltSysrq/proptest/issues/152>
+impl Arbitrary for AggregateFunc {
+    type Parameters = ();
+
+    type Strategy = Union<BoxedStrategy<Self>>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::collection::vec;
+        use proptest::prelude::any as proptest_any;
+        Union::new(vec![
+            Just(AggregateFunc::MaxNumeric).boxed(),
+            Just(AggregateFunc::MaxInt16).boxed(),
+            Just(AggregateFunc::MaxInt32).boxed(),
+            Just(AggregateFunc::MaxInt64).boxed(),
+            Just(AggregateFunc::MaxUInt16).boxed(),
+            Just(AggregateFunc::MaxUInt32).boxed(),
+            Just(AggregateFunc::MaxUInt64).boxed(),
+            Just(AggregateFunc::MaxMzTimestamp).boxed(),
+            Just(AggregateFunc::MaxFloat32).boxed(),
+            Just(AggregateFunc::MaxFloat64).boxed(),
+            Just(AggregateFunc::MaxBool).boxed(),
+            Just(AggregateFunc::MaxString).boxed(),
+            Just(AggregateFunc::MaxTimestamp).boxed(),
+            Just(AggregateFunc::MaxDate).boxed(),
+            Just(AggregateFunc::MaxTimestampTz).boxed(),
+            Just(AggregateFunc::MaxInterval).boxed(),
+            Just(AggregateFunc::MaxTime).boxed(),
+            Just(AggregateFunc::MaxBytes).boxed(),
+            Just(AggregateFunc::MaxUuid).boxed(),
+            Just(AggregateFunc::MaxArray).boxed(),
+            Just(AggregateFunc::MinNumeric).boxed(),
+            Just(AggregateFunc::MinInt16).boxed(),
+            Just(AggregateFunc::MinInt32).boxed(),
+            Just(AggregateFunc::MinInt64).boxed(),
+            Just(AggregateFunc::MinUInt16).boxed(),
+            Just(AggregateFunc::MinUInt32).boxed(),
+            Just(AggregateFunc::MinUInt64).boxed(),
+            Just(AggregateFunc::MinMzTimestamp).boxed(),
+            Just(AggregateFunc::MinFloat32).boxed(),
+            Just(AggregateFunc::MinFloat64).boxed(),
+            Just(AggregateFunc::MinBool).boxed(),
+            Just(AggregateFunc::MinString).boxed(),
+            Just(AggregateFunc::MinDate).boxed(),
+            Just(AggregateFunc::MinTimestamp).boxed(),
+            Just(AggregateFunc::MinTimestampTz).boxed(),
+            Just(AggregateFunc::MinInterval).boxed(),
+            Just(AggregateFunc::MinTime).boxed(),
+            Just(AggregateFunc::MinBytes).boxed(),
+            Just(AggregateFunc::MinUuid).boxed(),
+            Just(AggregateFunc::MinArray).boxed(),
+            Just(AggregateFunc::SumInt16).boxed(),
+            Just(AggregateFunc::SumInt32).boxed(),
+            Just(AggregateFunc::SumInt64).boxed(),
+            Just(AggregateFunc::SumUInt16).boxed(),
+            Just(AggregateFunc::SumUInt32).boxed(),
+            Just(AggregateFunc::SumUInt64).boxed(),
+            Just(AggregateFunc::SumFloat32).boxed(),
+            Just(AggregateFunc::SumFloat64).boxed(),
+            Just(AggregateFunc::SumNumeric).boxed(),
+            Just(AggregateFunc::SumInterval).boxed(),
+            Just(AggregateFunc::ProductFloat32).boxed(),
+            Just(AggregateFunc::ProductFloat64).boxed(),
+            Just(AggregateFunc::ProductNumeric).boxed(),
+            Just(AggregateFunc::AvgInt16).boxed(),
+            Just(AggregateFunc::AvgInt32).boxed(),
+            Just(AggregateFunc::AvgInt64).boxed(),
+            Just(AggregateFunc::AvgFloat32).boxed(),
+            Just(AggregateFunc::AvgFloat64).boxed(),
+            Just(AggregateFunc::AvgNumeric).boxed(),
+            Just(AggregateFunc::AvgInterval).boxed(),
+            Just(AggregateFunc::StddevPop).boxed(),
+            Just(AggregateFunc::StddevSamp).boxed(),
+            Just(AggregateFunc::VarPop).boxed(),
+            Just(AggregateFunc::VarSamp).boxed(),
+            Just(AggregateFunc::Corr).boxed(),
+            Just(AggregateFunc::CovarPop).boxed(),
+            Just(AggregateFunc::CovarSamp).boxed(),
+            Just(AggregateFunc::RegrCount).boxed(),
+            Just(AggregateFunc::RegrSlope).boxed(),
+            Just(AggregateFunc::RegrIntercept).boxed(),
+            Just(AggregateFunc::RegrR2).boxed(),
+            Just(AggregateFunc::Count).boxed(),
+            Just(AggregateFunc::CountNumeric).boxed(),
+            Just(AggregateFunc::CountDistinct).boxed(),
+            Just(AggregateFunc::Any).boxed(),
+            Just(AggregateFunc::All).boxed(),
+            Just(AggregateFunc::BoolAnd).boxed(),
+            Just(AggregateFunc::BoolOr).boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::JsonbAgg { order_by })
+                .boxed(),
+            (
+                vec(proptest_any::<ColumnOrder>(), 1..4),
+                proptest_any::<bool>(),
+            )
+                .prop_map(
+                    |(order_by, stringify_values)| AggregateFunc::JsonbObjectAgg {
+                        order_by,
+                        stringify_values,
+                    },
+                )
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::ArrayConcat { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::ListConcat { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::ArrayConcatDistinct { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::ListConcatDistinct { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::StringAgg { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::RowNumber { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::Rank { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::DenseRank { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::PercentRank { order_by })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::CumeDist { order_by })
+                .boxed(),
+            (
+                vec(proptest_any::<ColumnOrder>(), 1..4),
+                proptest_any::<LagLeadType>(),
+                proptest_any::<bool>(),
+            )
+                .prop_map(
+                    |(order_by, lag_lead, ignore_nulls)| AggregateFunc::LagLead {
+                        order_by,
+                        lag_lead,
+                        ignore_nulls,
+                    },
+                )
+                .boxed(),
+            (
+                vec(proptest_any::<ColumnOrder>(), 1..4),
+                proptest_any::<WindowFrame>(),
+            )
+                .prop_map(|(order_by, window_frame)| AggregateFunc::FirstValue {
+                    order_by,
+                    window_frame,
+                })
+                .boxed(),
+            (
+                vec(proptest_any::<ColumnOrder>(), 1..4),
+                proptest_any::<WindowFrame>(),
+            )
+                .prop_map(|(order_by, window_frame)| AggregateFunc::LastValue {
+                    order_by,
+                    window_frame,
+                })
+                .boxed(),
+            (
+                vec(proptest_any::<ColumnOrder>(), 1..4),
+                proptest_any::<WindowFrame>(),
+            )
+                .prop_map(|(order_by, window_frame)| AggregateFunc::NthValue {
+                    order_by,
+                    window_frame,
+                })
+                .boxed(),
+            vec(proptest_any::<ColumnOrder>(), 1..4)
+                .prop_map(|order_by| AggregateFunc::Ntile { order_by })
+                .boxed(),
+            Just(AggregateFunc::Dummy).boxed(),
+        ])
+    }
+}
+
+impl RustType<ProtoColumnOrders> for Vec<ColumnOrder> {
+    fn into_proto(&self) -> ProtoColumnOrders {
+        ProtoColumnOrders {
+            orders: self.into_proto(),
+        }
+    }
+
+    fn from_proto(proto: ProtoColumnOrders) -> Result<Self, TryFromProtoError> {
+        proto.orders.into_rust()
+    }
+}
+
+impl RustType<ProtoAggregateFunc> for AggregateFunc {
+    fn into_proto(&self) -> ProtoAggregateFunc {
+        use proto_aggregate_func::Kind;
+        ProtoAggregateFunc {
+            kind: Some(match self {
+                AggregateFunc::MaxNumeric => Kind::MaxNumeric(()),
+                AggregateFunc::MaxInt16 => Kind::MaxInt16(()),
+                AggregateFunc::MaxInt32 => Kind::MaxInt32(()),
+                AggregateFunc::MaxInt64 => Kind::MaxInt64(()),
+                AggregateFunc::MaxUInt16 => Kind::MaxUint16(()),
+                AggregateFunc::MaxUInt32 => Kind::MaxUint32(()),
+                AggregateFunc::MaxUInt64 => Kind::MaxUint64(()),
+                AggregateFunc::MaxMzTimestamp => Kind::MaxMzTimestamp(()),
+                AggregateFunc::MaxFloat32 => Kind::MaxFloat32(()),
+                AggregateFunc::MaxFloat64 => Kind::MaxFloat64(()),
+                AggregateFunc::MaxBool => Kind::MaxBool(()),
+                AggregateFunc::MaxString => Kind::MaxString(()),
+                AggregateFunc::MaxDate => Kind::MaxDate(()),
+                AggregateFunc::MaxTimestamp => Kind::MaxTimestamp(()),
+                AggregateFunc::MaxTimestampTz => Kind::MaxTimestampTz(()),
+                AggregateFunc::MaxInterval => Kind::MaxInterval(()),
+                AggregateFunc::MaxTime => Kind::MaxTime(()),
+                AggregateFunc::MaxBytes => Kind::MaxBytes(()),
+                AggregateFunc::MaxUuid => Kind::MaxUuid(()),
+                AggregateFunc::MaxArray => Kind::MaxArray(()),
+                AggregateFunc::MinNumeric => Kind::MinNumeric(()),
+                AggregateFunc::MinInt16 => Kind::MinInt16(()),
+                AggregateFunc::MinInt32 => Kind::MinInt32(()),
+                AggregateFunc::MinInt64 => Kind::MinInt64(()),
+                AggregateFunc::MinUInt16 => Kind::MinUint16(()),
+                AggregateFunc::MinUInt32 => Kind::MinUint32(()),
+                AggregateFunc::MinUInt64 => Kind::MinUint64(()),
+                AggregateFunc::MinMzTimestamp => Kind::MinMzTimestamp(()),
+                AggregateFunc::MinFloat32 => Kind::MinFloat32(()),
+                AggregateFunc::MinFloat64 => Kind::MinFloat64(()),
+                AggregateFunc::MinBool => Kind::MinBool(()),
+                AggregateFunc::MinString => Kind::MinString(()),
+                AggregateFunc::MinDate => Kind::MinDate(()),
+                AggregateFunc::MinTimestamp => Kind::MinTimestamp(()),
+                AggregateFunc::MinTimestampTz => Kind::MinTimestampTz(()),
+                AggregateFunc::MinInterval => Kind::MinInterval(()),
+                AggregateFunc::MinTime => Kind::MinTime(()),
+                AggregateFunc::MinBytes => Kind::MinBytes(()),
+                AggregateFunc::MinUuid => Kind::MinUuid(()),
+                AggregateFunc::MinArray => Kind::MinArray(()),
+                AggregateFunc::SumInt16 => Kind::SumInt16(()),
+                AggregateFunc::SumInt32 => Kind::SumInt32(()),
+                AggregateFunc::SumInt64 => Kind::SumInt64(()),
+                AggregateFunc::SumUInt16 => Kind::SumUint16(()),
+                AggregateFunc::SumUInt32 => Kind::SumUint32(()),
+                AggregateFunc::SumUInt64 => Kind::SumUint64(()),
+                AggregateFunc::SumFloat32 => Kind::SumFloat32(()),
+                AggregateFunc::SumFloat64 => Kind::SumFloat64(()),
+                AggregateFunc::SumNumeric => Kind::SumNumeric(()),
+                AggregateFunc::SumInterval => Kind::SumInterval(()),
+                AggregateFunc::ProductFloat32 => Kind::ProductFloat32(()),
+                AggregateFunc::ProductFloat64 => Kind::ProductFloat64(()),
+                AggregateFunc::ProductNumeric => Kind::ProductNumeric(()),
+                AggregateFunc::AvgInt16 => Kind::AvgInt16(()),
+                AggregateFunc::AvgInt32 => Kind::AvgInt32(()),
+                AggregateFunc::AvgInt64 => Kind::AvgInt64(()),
+                AggregateFunc::AvgFloat32 => Kind::AvgFloat32(()),
+                AggregateFunc::AvgFloat64 => Kind::AvgFloat64(()),
+                AggregateFunc::AvgNumeric => Kind::AvgNumeric(()),
+                AggregateFunc::AvgInterval => Kind::AvgInterval(()),
+                AggregateFunc::StddevPop => Kind::StddevPop(()),
+                AggregateFunc::StddevSamp => Kind::StddevSamp(()),
+                AggregateFunc::VarPop => Kind::VarPop(()),
+                AggregateFunc::VarSamp => Kind::VarSamp(()),
+                AggregateFunc::Corr => Kind::Corr(()),
+                AggregateFunc::CovarPop => Kind::CovarPop(()),
+                AggregateFunc::CovarSamp => Kind::CovarSamp(()),
+                AggregateFunc::RegrCount => Kind::RegrCount(()),
+                AggregateFunc::RegrSlope => Kind::RegrSlope(()),
+                AggregateFunc::RegrIntercept => Kind::RegrIntercept(()),
+                AggregateFunc::RegrR2 => Kind::RegrR2(()),
+                AggregateFunc::Count => Kind::Count(()),
+                AggregateFunc::CountNumeric => Kind::CountNumeric(()),
+                AggregateFunc::CountDistinct => Kind::CountDistinct(()),
+                AggregateFunc::Any => Kind::Any(()),
+                AggregateFunc::All => Kind::All(()),
+                AggregateFunc::BoolAnd => Kind::BoolAnd(()),
+                AggregateFunc::BoolOr => Kind::BoolOr(()),
+                AggregateFunc::JsonbAgg { order_by } => Kind::JsonbAgg(order_by.into_proto()),
+                AggregateFunc::JsonbObjectAgg {
+                    order_by,
+                    stringify_values,
+                } => Kind::JsonbObjectAgg(proto_aggregate_func::ProtoJsonbObjectAgg {
+                    order_by: Some(order_by.into_proto()),
+                    stringify_values: *stringify_values,
+                }),
+                AggregateFunc::ArrayConcat { order_by } => Kind::ArrayConcat(order_by.into_proto()),
+                AggregateFunc::ListConcat { order_by } => Kind::ListConcat(order_by.into_proto()),
+                AggregateFunc::ArrayConcatDistinct { order_by } => {
+                    Kind::ArrayConcatDistinct(order_by.into_proto())
+                }
+                AggregateFunc::ListConcatDistinct { order_by } => {
+                    Kind::ListConcatDistinct(order_by.into_proto())
+                }
+                AggregateFunc::StringAgg { order_by } => Kind::StringAgg(order_by.into_proto()),
+                AggregateFunc::RowNumber { order_by } => Kind::RowNumber(order_by.into_proto()),
+                AggregateFunc::Rank { order_by } => Kind::Rank(order_by.into_proto()),
+                AggregateFunc::DenseRank { order_by } => Kind::DenseRank(order_by.into_proto()),
+                AggregateFunc::PercentRank { order_by } => Kind::PercentRank(order_by.into_proto()),
+                AggregateFunc::CumeDist { order_by } => Kind::CumeDist(order_by.into_proto()),
+                AggregateFunc::LagLead {
+                    order_by,
+                    lag_lead,
+                    ignore_nulls,
+                } => Kind::LagLead(proto_aggregate_func::ProtoLagLead {
+                    order_by: Some(order_by.into_proto()),
+                    lag_lead: Some(match lag_lead {
+                        LagLeadType::Lag => proto_aggregate_func::proto_lag_lead::LagLead::Lag(()),
+                        LagLeadType::Lead => {
+                            proto_aggregate_func::proto_lag_lead::LagLead::Lead(())
+                        }
+                    }),
+                    ignore_nulls: *ignore_nulls,
+                }),
+                AggregateFunc::FirstValue {
+                    order_by,
+                    window_frame,
+                } => Kind::FirstValue(proto_aggregate_func::ProtoWindowFrame {
+                    order_by: Some(order_by.into_proto()),
+                    window_frame: Some(window_frame.into_proto()),
+                }),
+                AggregateFunc::LastValue {
+                    order_by,
+                    window_frame,
+                } => Kind::LastValue(proto_aggregate_func::ProtoWindowFrame {
+                    order_by: Some(order_by.into_proto()),
+                    window_frame: Some(window_frame.into_proto()),
+                }),
+                AggregateFunc::NthValue {
+                    order_by,
+                    window_frame,
+                } => Kind::NthValue(proto_aggregate_func::ProtoWindowFrame {
+                    order_by: Some(order_by.into_proto()),
+                    window_frame: Some(window_frame.into_proto()),
+                }),
+                AggregateFunc::Ntile { order_by } => Kind::Ntile(order_by.into_proto()),
+                AggregateFunc::Dummy => Kind::Dummy(()),
+            }),
+        }
+    }
+
+    fn from_proto(proto: ProtoAggregateFunc) -> Result<Self, TryFromProtoError> {
+        use proto_aggregate_func::Kind;
+        let kind = proto
+            .kind
+            .ok_or_else(|| TryFromProtoError::missing_field("ProtoAggregateFunc::kind"))?;
+        Ok(match kind {
+            Kind::MaxNumeric(()) => AggregateFunc::MaxNumeric,
+            Kind::MaxInt16(()) => AggregateFunc::MaxInt16,
+            Kind::MaxInt32(()) => AggregateFunc::MaxInt32,
+            Kind::MaxInt64(()) => AggregateFunc::MaxInt64,
+            Kind::MaxUint16(()) => AggregateFunc::MaxUInt16,
+            Kind::MaxUint32(()) => AggregateFunc::MaxUInt32,
+            Kind::MaxUint64(()) => AggregateFunc::MaxUInt64,
+            Kind::MaxMzTimestamp(()) => AggregateFunc::MaxMzTimestamp,
+            Kind::MaxFloat32(()) => AggregateFunc::MaxFloat32,
+            Kind::MaxFloat64(()) => AggregateFunc::MaxFloat64,
+            Kind::MaxBool(()) => AggregateFunc::MaxBool,
+            Kind::MaxString(()) => AggregateFunc::MaxString,
+            Kind::MaxDate(()) => AggregateFunc::MaxDate,
+            Kind::MaxTimestamp(()) => AggregateFunc::MaxTimestamp,
+            Kind::MaxTimestampTz(()) => AggregateFunc::MaxTimestampTz,
+            Kind::MaxInterval(()) => AggregateFunc::MaxInterval,
+            Kind::MaxTime(()) => AggregateFunc::MaxTime,
+            Kind::MaxBytes(()) => AggregateFunc::MaxBytes,
+            Kind::MaxUuid(()) => AggregateFunc::MaxUuid,
+            Kind::MaxArray(()) => AggregateFunc::MaxArray,
+            Kind::MinNumeric(()) => AggregateFunc::MinNumeric,
+            Kind::MinInt16(()) => AggregateFunc::MinInt16,
+            Kind::MinInt32(()) => AggregateFunc::MinInt32,
+            Kind::MinInt64(()) => AggregateFunc::MinInt64,
+            Kind::MinUint16(()) => AggregateFunc::MinUInt16,
+            Kind::MinUint32(()) => AggregateFunc::MinUInt32,
+            Kind::MinUint64(()) => AggregateFunc::MinUInt64,
+            Kind::MinMzTimestamp(()) => AggregateFunc::MinMzTimestamp,
+            Kind::MinFloat32(()) => AggregateFunc::MinFloat32,
+            Kind::MinFloat64(()) => AggregateFunc::MinFloat64,
+            Kind::MinBool(()) => AggregateFunc::MinBool,
+            Kind::MinString(()) => AggregateFunc::MinString,
+            Kind::MinDate(()) => AggregateFunc::MinDate,
+            Kind::MinTimestamp(()) => AggregateFunc::MinTimestamp,
+            Kind::MinTimestampTz(()) => AggregateFunc::MinTimestampTz,
+            Kind::MinInterval(()) => AggregateFunc::MinInterval,
+            Kind::MinTime(()) => AggregateFunc::MinTime,
+            Kind::MinBytes(()) => AggregateFunc::MinBytes,
+            Kind::MinUuid(()) => AggregateFunc::MinUuid,
+            Kind::MinArray(()) => AggregateFunc::MinArray,
+            Kind::SumInt16(()) => AggregateFunc::SumInt16,
+            Kind::SumInt32(()) => AggregateFunc::SumInt32,
+            Kind::SumInt64(()) => AggregateFunc::SumInt64,
+            Kind::SumUint16(()) => AggregateFunc::SumUInt16,
+            Kind::SumUint32(()) => AggregateFunc::SumUInt32,
+            Kind::SumUint64(()) => AggregateFunc::SumUInt64,
+            Kind::SumFloat32(()) => AggregateFunc::SumFloat32,
+            Kind::SumFloat64(()) => AggregateFunc::SumFloat64,
+            Kind::SumNumeric(()) => AggregateFunc::SumNumeric,
+            Kind::SumInterval(()) => AggregateFunc::SumInterval,
+            Kind::ProductFloat32(()) => AggregateFunc::ProductFloat32,
+            Kind::ProductFloat64(()) => AggregateFunc::ProductFloat64,
+            Kind::ProductNumeric(()) => AggregateFunc::ProductNumeric,
+            Kind::AvgInt16(()) => AggregateFunc::AvgInt16,
+            Kind::AvgInt32(()) => AggregateFunc::AvgInt32,
+            Kind::AvgInt64(()) => AggregateFunc::AvgInt64,
+            Kind::AvgFloat32(()) => AggregateFunc::AvgFloat32,
+            Kind::AvgFloat64(()) => AggregateFunc::AvgFloat64,
+            Kind::AvgNumeric(()) => AggregateFunc::AvgNumeric,
+            Kind::AvgInterval(()) => AggregateFunc::AvgInterval,
+            Kind::StddevPop(()) => AggregateFunc::StddevPop,
+            Kind::StddevSamp(()) => AggregateFunc::StddevSamp,
+            Kind::VarPop(()) => AggregateFunc::VarPop,
+            Kind::VarSamp(()) => AggregateFunc::VarSamp,
+            Kind::Corr(()) => AggregateFunc::Corr,
+            Kind::CovarPop(()) => AggregateFunc::CovarPop,
+            Kind::CovarSamp(()) => AggregateFunc::CovarSamp,
+            Kind::RegrCount(()) => AggregateFunc::RegrCount,
+            Kind::RegrSlope(()) => AggregateFunc::RegrSlope,
+            Kind::RegrIntercept(()) => AggregateFunc::RegrIntercept,
+            Kind::RegrR2(()) => AggregateFunc::RegrR2,
+            Kind::Count(()) => AggregateFunc::Count,
+            Kind::CountNumeric(()) => AggregateFunc::CountNumeric,
+            Kind::CountDistinct(()) => AggregateFunc::CountDistinct,
+            Kind::Any(()) => AggregateFunc::Any,
+            Kind::All(()) => AggregateFunc::All,
+            Kind::BoolAnd(()) => AggregateFunc::BoolAnd,
+            Kind::BoolOr(()) => AggregateFunc::BoolOr,
+            Kind::JsonbAgg(order_by) => AggregateFunc::JsonbAgg {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::JsonbObjectAgg(agg) => AggregateFunc::JsonbObjectAgg {
+                order_by: agg
+                    .order_by
+                    .into_rust_if_some("ProtoJsonbObjectAgg::order_by")?,
+                stringify_values: agg.stringify_values,
+            },
+            Kind::ArrayConcat(order_by) => AggregateFunc::ArrayConcat {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::ListConcat(order_by) => AggregateFunc::ListConcat {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::ArrayConcatDistinct(order_by) => AggregateFunc::ArrayConcatDistinct {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::ListConcatDistinct(order_by) => AggregateFunc::ListConcatDistinct {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::StringAgg(order_by) => AggregateFunc::StringAgg {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::RowNumber(order_by) => AggregateFunc::RowNumber {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::Rank(order_by) => AggregateFunc::Rank {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::DenseRank(order_by) => AggregateFunc::DenseRank {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::PercentRank(order_by) => AggregateFunc::PercentRank {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::CumeDist(order_by) => AggregateFunc::CumeDist {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::LagLead(pll) => AggregateFunc::LagLead {
+                order_by: pll.order_by.into_rust_if_some("ProtoLagLead::order_by")?,
+                lag_lead: match pll.lag_lead {
+                    Some(proto_aggregate_func::proto_lag_lead::LagLead::Lag(())) => {
+                        LagLeadType::Lag
+                    }
+                    Some(proto_aggregate_func::proto_lag_lead::LagLead::Lead(())) => {
+                        LagLeadType::Lead
+                    }
+                    None => {
+                        return Err(TryFromProtoError::MissingField(
+                            "ProtoLagLead::lag_lead".into(),
+                        ))
+                    }
+                },
+                ignore_nulls: pll.ignore_nulls,
+            },
+            Kind::FirstValue(pfv) => AggregateFunc::FirstValue {
+                order_by: pfv
+                    .order_by
+                    .into_rust_if_some("ProtoWindowFrame::order_by")?,
+                window_frame: pfv
+                    .window_frame
+                    .into_rust_if_some("ProtoWindowFrame::window_frame")?,
+            },
+            Kind::LastValue(pfv) => AggregateFunc::LastValue {
+                order_by: pfv
+                    .order_by
+                    .into_rust_if_some("ProtoWindowFrame::order_by")?,
+                window_frame: pfv
+                    .window_frame
+                    .into_rust_if_some("ProtoWindowFrame::window_frame")?,
+            },
+            Kind::NthValue(pnv) => AggregateFunc::NthValue {
+                order_by: pnv
+                    .order_by
+                    .into_rust_if_some("ProtoWindowFrame::order_by")?,
+                window_frame: pnv
+                    .window_frame
+                    .into_rust_if_some("ProtoWindowFrame::window_frame")?,
+            },
+            Kind::Ntile(order_by) => AggregateFunc::Ntile {
+                order_by: order_by.into_rust()?,
+            },
+            Kind::Dummy(()) => AggregateFunc::Dummy,
+        })
+    }
+}
+
+impl AggregateFunc {
+    pub fn eval<'a, I>(&self, datums: I, temp_storage: &'a RowArena) -> Datum<'a>
+    where
+        I: IntoIterator<Item = Datum<'a>>,
+    {
+        self.eval_with_filter(datums.into_iter().map(|d| (d, true)), temp_storage)
+    }
+
+    /// Like [`AggregateFunc::eval`], but reports overflow in the sum aggregates
+    /// as an error rather than returning a wrong result.
+    pub fn try_eval<'a, I>(
+        &self,
+        datums: I,
+        temp_storage: &'a RowArena,
+    ) -> Result<Datum<'a>, EvalError>
+    where
+        I: IntoIterator<Item = Datum<'a>>,
+    {
+        match self {
+            AggregateFunc::SumInt16 => try_sum_int16(datums),
+            AggregateFunc::SumInt32 => try_sum_int32(datums),
+            AggregateFunc::SumInt64 => try_sum_int64(datums),
+            AggregateFunc::SumNumeric => match checked_sum_numeric(datums) {
+                (_, Some(err)) => Err(err),
+                (sum, None) => Ok(sum),
+            },
+            AggregateFunc::SumInterval => match checked_sum_interval(datums) {
+                (_, Some(err)) => Err(err),
+                (sum, None) => Ok(sum),
+            },
+            AggregateFunc::AvgInterval => match checked_avg_interval(datums) {
+                (_, Some(err)) => Err(err),
+                (avg, None) => Ok(avg),
+            },
+            _ => Ok(self.eval(datums, temp_storage)),
+        }
+    }
+
+    /// Like [`AggregateFunc::try_eval`], but also reports an error rather than
+    /// evaluating a window function over a partition of more than
+    /// `max_partition_size` rows.
+    ///
+    /// Window functions materialize and sort their entire partition, so the
+    /// limit bounds the memory that a single partition can take up. Other
+    /// aggregates are evaluated as usual.
+    pub fn eval_with_max_partition_size<'a, I>(
+        &self,
+        datums: I,
+        temp_storage: &'a RowArena,
+        max_partition_size: usize,
+    ) -> Result<Datum<'a>, EvalError>
+    where
+        I: IntoIterator<Item = Datum<'a>>,
+    {
+        if !self.is_window_func() {
+            return self.try_eval(datums, temp_storage);
+        }
+        let mut partition = Vec::new();
+        for datum in datums {
+            if partition.len() == max_partition_size {
+                return Err(EvalError::WindowPartitionTooLarge {
+                    max_size: max_partition_size,
+                });
+            }
+            partition.push(datum);
+        }
+        self.try_eval(partition, temp_storage)
+    }
+
+    /// Reports whether [`AggregateFunc::try_eval`] can return an error for
+    /// this function.
+    pub fn is_fallible(&self) -> bool {
+        matches!(
+            self,
+            AggregateFunc::SumInt16
+                | AggregateFunc::SumInt32
+                | AggregateFunc::SumInt64
+                | AggregateFunc::SumNumeric
+                | AggregateFunc::SumInterval
+                | AggregateFunc::AvgInterval
+        )
+    }
+
+    /// Reports whether this function is a window function, which is evaluated
+    /// over the whole partition of each row.
+    pub fn is_window_func(&self) -> bool {
+        matches!(
+            self,
+            AggregateFunc::RowNumber { .. }
+                | AggregateFunc::Rank { .. }
+                | AggregateFunc::DenseRank { .. }
+                | AggregateFunc::PercentRank { .. }
+                | AggregateFunc::CumeDist { .. }
+                | AggregateFunc::LagLead { .. }
+                | AggregateFunc::FirstValue { .. }
+                | AggregateFunc::LastValue { .. }
+                | AggregateFunc::NthValue { .. }
+                | AggregateFunc::Ntile { .. }
+        )
+    }
+
+    /// Like [`AggregateFunc::eval`], but each datum is paired with a flag
+    /// indicating whether its row passes the aggregate's `FILTER` clause.
+    ///
+    /// Datums whose rows do not pass the filter are dropped before any other
+    /// processing, including the handling of nulls. For example, `Count` over
+    /// only filtered out datums is zero, and still ignores nulls among the
+    /// datums that pass.
+    pub fn eval_with_filter<'a, I>(&self, datums: I, temp_storage: &'a RowArena) -> Datum<'a>
+    where
+        I: IntoIterator<Item = (Datum<'a>, bool)>,
+    {
+        let datums = datums
+            .into_iter()
+            .filter_map(|(d, passes)| passes.then_some(d));
+        match self {
+            AggregateFunc::MaxNumeric => max_numeric(datums),
+            AggregateFunc::MaxInt16 => max_int16(datums),
+            AggregateFunc::MaxInt32 => max_int32(datums),
+            AggregateFunc::MaxInt64 => max_int64(datums),
+            AggregateFunc::MaxUInt16 => max_uint16(datums),
+            AggregateFunc::MaxUInt32 => max_uint32(datums),
+            AggregateFunc::MaxUInt64 => max_uint64(datums),
+            AggregateFunc::MaxMzTimestamp => max_mz_timestamp(datums),
+            AggregateFunc::MaxFloat32 => max_float32(datums),
+            AggregateFunc::MaxFloat64 => max_float64(datums),
+            AggregateFunc::MaxBool => max_bool(datums),
+            AggregateFunc::MaxString => max_string(datums),
+            AggregateFunc::MaxDate => max_date(datums),
+            AggregateFunc::MaxTimestamp => max_timestamp(datums),
+            AggregateFunc::MaxTimestampTz => max_timestamptz(datums),
+            AggregateFunc::MaxInterval => max_interval(datums),
+            AggregateFunc::MaxTime => max_time(datums),
+            AggregateFunc::MaxBytes => max_bytes(datums),
+            AggregateFunc::MaxUuid => max_uuid(datums),
+            AggregateFunc::MaxArray => max_array(datums),
+            AggregateFunc::MinNumeric => min_numeric(datums),
+            AggregateFunc::MinInt16 => min_int16(datums),
+            AggregateFunc::MinInt32 => min_int32(datums),
+            AggregateFunc::MinInt64 => min_int64(datums),
+            AggregateFunc::MinUInt16 => min_uint16(datums),
+            AggregateFunc::MinUInt32 => min_uint32(datums),
+            AggregateFunc::MinUInt64 => min_uint64(datums),
+            AggregateFunc::MinMzTimestamp => min_mz_timestamp(datums),
+            AggregateFunc::MinFloat32 => min_float32(datums),
+            AggregateFunc::MinFloat64 => min_float64(datums),
+            AggregateFunc::MinBool => min_bool(datums),
+            AggregateFunc::MinString => min_string(datums),
+            AggregateFunc::MinDate => min_date(datums),
+            AggregateFunc::MinTimestamp => min_timestamp(datums),
+            AggregateFunc::MinTimestampTz => min_timestamptz(datums),
+            AggregateFunc::MinInterval => min_interval(datums),
+            AggregateFunc::MinTime => min_time(datums),
+            AggregateFunc::MinBytes => min_bytes(datums),
+            AggregateFunc::MinUuid => min_uuid(datums),
+            AggregateFunc::MinArray => min_array(datums),
+            AggregateFunc::SumInt16 => sum_int16(datums),
+            AggregateFunc::SumInt32 => sum_int32(datums),
+            AggregateFunc::SumInt64 => sum_int64(datums),
+            AggregateFunc::SumUInt16 => sum_uint16(datums),
+            AggregateFunc::SumUInt32 => sum_uint32(datums),
+            AggregateFunc::SumUInt64 => sum_uint64(datums),
+            AggregateFunc::SumFloat32 => sum_float32(datums),
+            AggregateFunc::SumFloat64 => sum_float64(datums),
+            AggregateFunc::SumNumeric => sum_numeric(datums),
+            AggregateFunc::SumInterval => sum_interval(datums),
+            AggregateFunc::ProductFloat32 => product_float32(datums),
+            AggregateFunc::ProductFloat64 => product_float64(datums),
+            AggregateFunc::ProductNumeric => product_numeric(datums),
+            AggregateFunc::AvgInt16 => avg_int16(datums),
+            AggregateFunc::AvgInt32 => avg_int32(datums),
+            AggregateFunc::AvgInt64 => avg_int64(datums),
+            AggregateFunc::AvgFloat32 => avg_float32(datums),
+            AggregateFunc::AvgFloat64 => avg_float64(datums),
+            AggregateFunc::AvgNumeric => avg_numeric(datums),
+            AggregateFunc::AvgInterval => avg_interval(datums),
+            AggregateFunc::StddevPop => stddev_pop_numeric(datums),
+            AggregateFunc::StddevSamp => stddev_samp_numeric(datums),
+            AggregateFunc::VarPop => var_pop_numeric(datums),
+            AggregateFunc::VarSamp => var_samp_numeric(datums),
+            AggregateFunc::Corr => corr(datums),
+            AggregateFunc::CovarPop => covar_pop(datums),
+            AggregateFunc::CovarSamp => covar_samp(datums),
+            AggregateFunc::RegrCount => regr_count(datums),
+            AggregateFunc::RegrSlope => regr_slope(datums),
+            AggregateFunc::RegrIntercept => regr_intercept(datums),
+            AggregateFunc::RegrR2 => regr_r2(datums),
+            AggregateFunc::Count => count(datums),
+            AggregateFunc::CountNumeric => count_numeric(datums),
+            AggregateFunc::CountDistinct => count_distinct(datums),
+            AggregateFunc::Any => any(datums),
+            AggregateFunc::All => all(datums),
+            AggregateFunc::BoolAnd => bool_and(datums),
+            AggregateFunc::BoolOr => bool_or(datums),
+            AggregateFunc::JsonbAgg { order_by } => jsonb_agg(datums, temp_storage, order_by),
+            AggregateFunc::JsonbObjectAgg {
+                order_by,
+                stringify_values,
+            } => jsonb_object_agg(datums, temp_storage, order_by, *stringify_values),
+            AggregateFunc::ArrayConcat { order_by } => {
+                array_concat(datums, temp_storage, order_by, false)
+            }
+            AggregateFunc::ListConcat { order_by } => {
+                list_concat(datums, temp_storage, order_by, false)
+            }
+            AggregateFunc::ArrayConcatDistinct { order_by } => {
+                array_concat(datums, temp_storage, order_by, true)
+            }
+            AggregateFunc::ListConcatDistinct { order_by } => {
+                list_concat(datums, temp_storage, order_by, true)
+            }
+            AggregateFunc::StringAgg { order_by } => string_agg(datums, temp_storage, order_by),
+            AggregateFunc::RowNumber { order_by } => row_number(datums, temp_storage, order_by),
+            AggregateFunc::Rank { order_by } => rank(datums, temp_storage, order_by),
+            AggregateFunc::DenseRank { order_by } => dense_rank(datums, temp_storage, order_by),
+            AggregateFunc::PercentRank { order_by } => percent_rank(datums, temp_storage, order_by),
+            AggregateFunc::CumeDist { order_by } => cume_dist(datums, temp_storage, order_by),
+            AggregateFunc::LagLead {
+                order_by,
+                lag_lead: lag_lead_type,
+                ignore_nulls,
+            } => lag_lead(datums, temp_storage, order_by, lag_lead_type, *ignore_nulls),
+            AggregateFunc::FirstValue {
                 order_by,
                 window_frame,
             } => first_value(datums, temp_storage, order_by, window_frame),
@@ -1569,994 +3161,6002 @@ impl AggregateFunc {
                 order_by,
                 window_frame,
             } => last_value(datums, temp_storage, order_by, window_frame),
+            AggregateFunc::NthValue {
+                order_by,
+                window_frame,
+            } => nth_value(datums, temp_storage, order_by, window_frame),
+            AggregateFunc::Ntile { order_by } => ntile(datums, temp_storage, order_by),
+            AggregateFunc::Dummy => Datum::Dummy,
+        }
+    }
+
+    /// Returns the output of the aggregation function when applied on an empty
+    /// input relation.
+    pub fn default(&self) -> Datum<'static> {
+        match self {
+            AggregateFunc::Count | AggregateFunc::CountDistinct | AggregateFunc::RegrCount => {
+                Datum::Int64(0)
+            }
+            AggregateFunc::CountNumeric => Datum::from(Numeric::from(0)),
+            AggregateFunc::Any => Datum::False,
+            AggregateFunc::All => Datum::True,
+            AggregateFunc::BoolAnd | AggregateFunc::BoolOr => Datum::Null,
+            AggregateFunc::Dummy => Datum::Dummy,
+            _ => Datum::Null,
+        }
+    }
+
+    /// Returns an [`Accumulator`] that maintains this aggregate incrementally
+    /// as datums are added and retracted, or `None` if the aggregate cannot be
+    /// maintained exactly that way.
+    ///
+    /// Order-sensitive aggregates have no accumulator, as their partial results
+    /// cannot be merged. Neither do float and numeric sums, whose rounding would
+    /// make retractions inexact, nor the extrema of types whose datums borrow
+    /// their data.
+    pub fn accumulator(&self) -> Option<Box<dyn Accumulator>> {
+        let accumulator: Box<dyn Accumulator> = match self {
+            AggregateFunc::Count => Box::new(CountAccumulator::default()),
+            // Retractions and large multiplicities can take the sum out of the
+            // range of the output type, even if no input does.
+            AggregateFunc::SumInt16 | AggregateFunc::SumInt32 => {
+                Box::new(IntegerSumAccumulator::new(|sum| {
+                    let sum = sum
+                        .and_then(|sum| i64::try_from(sum).ok())
+                        .ok_or(EvalError::Int64OutOfRange)?;
+                    Ok(Datum::Int64(sum))
+                }))
+            }
+            AggregateFunc::SumUInt16 | AggregateFunc::SumUInt32 => {
+                Box::new(IntegerSumAccumulator::new(|sum| {
+                    let sum = sum
+                        .and_then(|sum| u64::try_from(sum).ok())
+                        .ok_or(EvalError::UInt64OutOfRange)?;
+                    Ok(Datum::UInt64(sum))
+                }))
+            }
+            AggregateFunc::SumInt64 => Box::new(IntegerSumAccumulator::new(|sum| {
+                let sum = sum.ok_or(EvalError::NumericFieldOverflow)?;
+                Ok(Datum::from(sum))
+            })),
+            // Like the narrower unsigned sums, reject a negative sum, which
+            // only retractions produce.
+            AggregateFunc::SumUInt64 => Box::new(IntegerSumAccumulator::new(|sum| {
+                let sum = sum.ok_or(EvalError::NumericFieldOverflow)?;
+                if sum < 0 {
+                    return Err(EvalError::UInt64OutOfRange);
+                }
+                Ok(Datum::from(sum))
+            })),
+            AggregateFunc::Any => Box::new(LogicalAccumulator::new(true, false)),
+            AggregateFunc::All => Box::new(LogicalAccumulator::new(false, false)),
+            AggregateFunc::BoolOr => Box::new(LogicalAccumulator::new(true, true)),
+            AggregateFunc::BoolAnd => Box::new(LogicalAccumulator::new(false, true)),
+            AggregateFunc::MaxNumeric
+            | AggregateFunc::MaxInt16
+            | AggregateFunc::MaxInt32
+            | AggregateFunc::MaxInt64
+            | AggregateFunc::MaxUInt16
+            | AggregateFunc::MaxUInt32
+            | AggregateFunc::MaxUInt64
+            | AggregateFunc::MaxMzTimestamp
+            | AggregateFunc::MaxFloat32
+            | AggregateFunc::MaxFloat64
+            | AggregateFunc::MaxBool
+            | AggregateFunc::MaxDate
+            | AggregateFunc::MaxTimestamp
+            | AggregateFunc::MaxTimestampTz
+            | AggregateFunc::MaxInterval
+            | AggregateFunc::MaxTime
+            | AggregateFunc::MaxUuid => Box::new(ExtremumAccumulator::new(true)),
+            AggregateFunc::MinNumeric
+            | AggregateFunc::MinInt16
+            | AggregateFunc::MinInt32
+            | AggregateFunc::MinInt64
+            | AggregateFunc::MinUInt16
+            | AggregateFunc::MinUInt32
+            | AggregateFunc::MinUInt64
+            | AggregateFunc::MinMzTimestamp
+            | AggregateFunc::MinFloat32
+            | AggregateFunc::MinFloat64
+            | AggregateFunc::MinBool
+            | AggregateFunc::MinDate
+            | AggregateFunc::MinTimestamp
+            | AggregateFunc::MinTimestampTz
+            | AggregateFunc::MinInterval
+            | AggregateFunc::MinTime
+            | AggregateFunc::MinUuid => Box::new(ExtremumAccumulator::new(false)),
+            _ => return None,
+        };
+        Some(accumulator)
+    }
+
+    /// Returns whether the aggregate can be maintained under retractions by
+    /// inverting its accumulation, i.e. whether it is a function of sums and
+    /// counts of its inputs, from which an input with a negative diff can be
+    /// subtracted.
+    ///
+    /// [`AggregateFunc::eval`] assumes every datum has a diff of one, so over a
+    /// collection with retractions it is only correct for these aggregates if
+    /// the accumulation is inverted first. Other aggregates must be reduced
+    /// again from the remaining inputs, or, as [`AggregateFunc::accumulator`]
+    /// does for extrema, keep enough state to replace a retracted input.
+    pub fn supports_retractions(&self) -> bool {
+        match self {
+            AggregateFunc::SumInt16
+            | AggregateFunc::SumInt32
+            | AggregateFunc::SumInt64
+            | AggregateFunc::SumUInt16
+            | AggregateFunc::SumUInt32
+            | AggregateFunc::SumUInt64
+            | AggregateFunc::SumFloat32
+            | AggregateFunc::SumFloat64
+            | AggregateFunc::SumNumeric
+            | AggregateFunc::SumInterval
+            | AggregateFunc::AvgInt16
+            | AggregateFunc::AvgInt32
+            | AggregateFunc::AvgInt64
+            | AggregateFunc::AvgFloat32
+            | AggregateFunc::AvgFloat64
+            | AggregateFunc::AvgNumeric
+            | AggregateFunc::AvgInterval
+            | AggregateFunc::StddevPop
+            | AggregateFunc::StddevSamp
+            | AggregateFunc::VarPop
+            | AggregateFunc::VarSamp
+            | AggregateFunc::Corr
+            | AggregateFunc::CovarPop
+            | AggregateFunc::CovarSamp
+            | AggregateFunc::RegrCount
+            | AggregateFunc::RegrSlope
+            | AggregateFunc::RegrIntercept
+            | AggregateFunc::RegrR2
+            | AggregateFunc::Count
+            | AggregateFunc::CountNumeric
+            // The logical aggregates count their true, false, and null inputs.
+            | AggregateFunc::Any
+            | AggregateFunc::All
+            | AggregateFunc::BoolAnd
+            | AggregateFunc::BoolOr => true,
+            AggregateFunc::MaxNumeric
+            | AggregateFunc::MaxInt16
+            | AggregateFunc::MaxInt32
+            | AggregateFunc::MaxInt64
+            | AggregateFunc::MaxUInt16
+            | AggregateFunc::MaxUInt32
+            | AggregateFunc::MaxUInt64
+            | AggregateFunc::MaxMzTimestamp
+            | AggregateFunc::MaxFloat32
+            | AggregateFunc::MaxFloat64
+            | AggregateFunc::MaxBool
+            | AggregateFunc::MaxString
+            | AggregateFunc::MaxDate
+            | AggregateFunc::MaxTimestamp
+            | AggregateFunc::MaxTimestampTz
+            | AggregateFunc::MaxInterval
+            | AggregateFunc::MaxTime
+            | AggregateFunc::MaxBytes
+            | AggregateFunc::MaxUuid
+            | AggregateFunc::MaxArray
+            | AggregateFunc::MinNumeric
+            | AggregateFunc::MinInt16
+            | AggregateFunc::MinInt32
+            | AggregateFunc::MinInt64
+            | AggregateFunc::MinUInt16
+            | AggregateFunc::MinUInt32
+            | AggregateFunc::MinUInt64
+            | AggregateFunc::MinMzTimestamp
+            | AggregateFunc::MinFloat32
+            | AggregateFunc::MinFloat64
+            | AggregateFunc::MinBool
+            | AggregateFunc::MinString
+            | AggregateFunc::MinDate
+            | AggregateFunc::MinTimestamp
+            | AggregateFunc::MinTimestampTz
+            | AggregateFunc::MinInterval
+            | AggregateFunc::MinTime
+            | AggregateFunc::MinBytes
+            | AggregateFunc::MinUuid
+            | AggregateFunc::MinArray
+            // A product cannot be divided by a retracted zero.
+            | AggregateFunc::ProductFloat32
+            | AggregateFunc::ProductFloat64
+            | AggregateFunc::ProductNumeric
+            | AggregateFunc::CountDistinct
+            | AggregateFunc::JsonbAgg { .. }
+            | AggregateFunc::JsonbObjectAgg { .. }
+            | AggregateFunc::ArrayConcat { .. }
+            | AggregateFunc::ListConcat { .. }
+            | AggregateFunc::ArrayConcatDistinct { .. }
+            | AggregateFunc::ListConcatDistinct { .. }
+            | AggregateFunc::StringAgg { .. }
+            | AggregateFunc::RowNumber { .. }
+            | AggregateFunc::Rank { .. }
+            | AggregateFunc::DenseRank { .. }
+            | AggregateFunc::PercentRank { .. }
+            | AggregateFunc::CumeDist { .. }
+            | AggregateFunc::LagLead { .. }
+            | AggregateFunc::FirstValue { .. }
+            | AggregateFunc::LastValue { .. }
+            | AggregateFunc::NthValue { .. }
+            | AggregateFunc::Ntile { .. }
+            | AggregateFunc::Dummy => false,
+        }
+    }
+
+    /// Returns a datum whose inclusion in the aggregation will not change its
+    /// result.
+    ///
+    /// `BoolAnd` and `BoolOr` do not ignore nulls, and have no such datum for
+    /// an empty input. Their identity is that of `All` and `Any`, which leaves
+    /// the result over any other input unchanged.
+    pub fn identity_datum(&self) -> Datum<'static> {
+        match self {
+            AggregateFunc::Any | AggregateFunc::BoolOr => Datum::False,
+            AggregateFunc::All | AggregateFunc::BoolAnd => Datum::True,
             AggregateFunc::Dummy => Datum::Dummy,
+            AggregateFunc::ProductFloat32 => Datum::from(1f32),
+            AggregateFunc::ProductFloat64 => Datum::from(1f64),
+            AggregateFunc::ProductNumeric => Datum::from(Numeric::from(1)),
+            AggregateFunc::ArrayConcat { .. } => Datum::empty_array(),
+            AggregateFunc::ListConcat { .. } => Datum::empty_list(),
+            AggregateFunc::ArrayConcatDistinct { .. } => Datum::empty_array(),
+            AggregateFunc::ListConcatDistinct { .. } => Datum::empty_list(),
+            AggregateFunc::RowNumber { .. } => Datum::empty_list(),
+            AggregateFunc::Rank { .. } => Datum::empty_list(),
+            AggregateFunc::DenseRank { .. } => Datum::empty_list(),
+            AggregateFunc::PercentRank { .. } => Datum::empty_list(),
+            AggregateFunc::CumeDist { .. } => Datum::empty_list(),
+            AggregateFunc::LagLead { .. } => Datum::empty_list(),
+            AggregateFunc::FirstValue { .. } => Datum::empty_list(),
+            AggregateFunc::LastValue { .. } => Datum::empty_list(),
+            AggregateFunc::NthValue { .. } => Datum::empty_list(),
+            AggregateFunc::Ntile { .. } => Datum::empty_list(),
+            _ => Datum::Null,
+        }
+    }
+
+    /// The output column type for the result of an aggregation.
+    ///
+    /// The output column type also contains nullability information, which
+    /// is (without further information) true for aggregations that are not
+    /// counts.
+    pub fn output_type(&self, input_type: ColumnType) -> ColumnType {
+        // Count never produces null, and other aggregations only produce
+        // null in the presence of null inputs. Note that count must not
+        // inherit the nullability of its input: `count(expr)` skips nulls and
+        // `count(*)` is planned as `count(true)`, and both produce `0` rather
+        // than null on empty input.
+        let nullable = match self {
+            AggregateFunc::Count
+            | AggregateFunc::CountNumeric
+            | AggregateFunc::CountDistinct
+            | AggregateFunc::RegrCount => false,
+            // Use the nullability of the underlying column being aggregated, not the Records wrapping it
+            AggregateFunc::StringAgg { .. } => match &input_type.scalar_type {
+                // The outer Record wraps the input in the first position, and any ORDER BY expressions afterwards
+                ScalarType::Record { fields, .. } => match &fields[0].1.scalar_type {
+                    // The inner Record is a (value, separator) tuple
+                    ScalarType::Record { fields, .. } => fields[0].1.nullable,
+                    _ => unreachable!(),
+                },
+                _ => unreachable!(),
+            },
+            // The sample variance of a single value is undefined.
+            AggregateFunc::StddevSamp | AggregateFunc::VarSamp => true,
+            // The input pairs are non-null lists even if their values are null.
+            AggregateFunc::Corr
+            | AggregateFunc::CovarPop
+            | AggregateFunc::CovarSamp
+            | AggregateFunc::RegrSlope
+            | AggregateFunc::RegrIntercept
+            | AggregateFunc::RegrR2 => true,
+            _ => input_type.nullable,
+        };
+        let scalar_type = match self {
+            AggregateFunc::Count | AggregateFunc::CountDistinct | AggregateFunc::RegrCount => {
+                ScalarType::Int64
+            }
+            AggregateFunc::CountNumeric => ScalarType::Numeric { max_scale: None },
+            AggregateFunc::Any => ScalarType::Bool,
+            AggregateFunc::All => ScalarType::Bool,
+            AggregateFunc::BoolAnd => ScalarType::Bool,
+            AggregateFunc::BoolOr => ScalarType::Bool,
+            AggregateFunc::JsonbAgg { .. } => ScalarType::Jsonb,
+            AggregateFunc::JsonbObjectAgg { .. } => ScalarType::Jsonb,
+            AggregateFunc::SumInt16 => ScalarType::Int64,
+            AggregateFunc::SumInt32 => ScalarType::Int64,
+            AggregateFunc::SumInt64 => ScalarType::Numeric {
+                max_scale: Some(NumericMaxScale::ZERO),
+            },
+            AggregateFunc::SumUInt16 => ScalarType::UInt64,
+            AggregateFunc::SumUInt32 => ScalarType::UInt64,
+            AggregateFunc::SumUInt64 => ScalarType::Numeric {
+                max_scale: Some(NumericMaxScale::ZERO),
+            },
+            AggregateFunc::AvgInt16 | AggregateFunc::AvgInt32 | AggregateFunc::AvgInt64 => {
+                ScalarType::Numeric { max_scale: None }
+            }
+            AggregateFunc::AvgFloat32 | AggregateFunc::AvgFloat64 => ScalarType::Float64,
+            AggregateFunc::AvgNumeric => ScalarType::Numeric { max_scale: None },
+            AggregateFunc::AvgInterval => ScalarType::Interval,
+            AggregateFunc::StddevPop
+            | AggregateFunc::StddevSamp
+            | AggregateFunc::VarPop
+            | AggregateFunc::VarSamp => ScalarType::Numeric { max_scale: None },
+            AggregateFunc::Corr
+            | AggregateFunc::CovarPop
+            | AggregateFunc::CovarSamp
+            | AggregateFunc::RegrSlope
+            | AggregateFunc::RegrIntercept
+            | AggregateFunc::RegrR2 => ScalarType::Float64,
+            AggregateFunc::ArrayConcat { .. }
+            | AggregateFunc::ListConcat { .. }
+            | AggregateFunc::ArrayConcatDistinct { .. }
+            | AggregateFunc::ListConcatDistinct { .. } => {
+                match input_type.scalar_type {
+                    // The input is wrapped in a Record if there's an ORDER BY, so extract it out.
+                    // Array and list element types carry no nullability of their own, as any
+                    // element may be null, so cloning the input type loses nothing.
+                    ScalarType::Record { ref fields, .. } => fields[0].1.scalar_type.clone(),
+                    _ => unreachable!(),
+                }
+            }
+            AggregateFunc::StringAgg { .. } => ScalarType::String,
+            AggregateFunc::RowNumber { .. } => window_output_type(
+                "?row_number?",
+                ScalarType::Int64.nullable(false),
+                scalar_window_original_row_type(input_type.scalar_type),
+            ),
+            AggregateFunc::Rank { .. } => window_output_type(
+                "?rank?",
+                ScalarType::Int64.nullable(false),
+                scalar_window_original_row_type(input_type.scalar_type),
+            ),
+            AggregateFunc::DenseRank { .. } => window_output_type(
+                "?dense_rank?",
+                ScalarType::Int64.nullable(false),
+                scalar_window_original_row_type(input_type.scalar_type),
+            ),
+            AggregateFunc::PercentRank { .. } => window_output_type(
+                "?percent_rank?",
+                ScalarType::Float64.nullable(false),
+                scalar_window_original_row_type(input_type.scalar_type),
+            ),
+            AggregateFunc::CumeDist { .. } => window_output_type(
+                "?cume_dist?",
+                ScalarType::Float64.nullable(false),
+                scalar_window_original_row_type(input_type.scalar_type),
+            ),
+            AggregateFunc::LagLead { lag_lead, .. } => {
+                // The encoded args for LagLead are (InputValue, Offset, Default)
+                let (original_row_type, args_type) =
+                    value_window_input_types(input_type.scalar_type);
+                let value_type = record_field_types(args_type).swap_remove(0);
+                let column_name = match lag_lead {
+                    LagLeadType::Lag => "?lag?",
+                    LagLeadType::Lead => "?lead?",
+                };
+                window_output_type(column_name, value_type.nullable(true), original_row_type)
+            }
+            AggregateFunc::FirstValue { .. } => {
+                let (original_row_type, value_type) =
+                    value_window_input_types(input_type.scalar_type);
+                window_output_type(
+                    "?first_value?",
+                    value_type.nullable(true),
+                    original_row_type,
+                )
+            }
+            AggregateFunc::LastValue { .. } => {
+                let (original_row_type, value_type) =
+                    value_window_input_types(input_type.scalar_type);
+                window_output_type("?last_value?", value_type.nullable(true), original_row_type)
+            }
+            AggregateFunc::NthValue { .. } => {
+                // The encoded args for NthValue are (InputValue, N)
+                let (original_row_type, args_type) =
+                    value_window_input_types(input_type.scalar_type);
+                let value_type = record_field_types(args_type).swap_remove(0);
+                window_output_type("?nth_value?", value_type.nullable(true), original_row_type)
+            }
+            AggregateFunc::Ntile { .. } => {
+                let (original_row_type, _) = value_window_input_types(input_type.scalar_type);
+                // A null or non-positive `n` assigns no bucket
+                window_output_type(
+                    "?ntile?",
+                    ScalarType::Int64.nullable(true),
+                    original_row_type,
+                )
+            }
+            // Note AggregateFunc::MaxString, MinString rely on returning input
+            // type as output type to support the proper return type for
+            // character input. Likewise, MaxArray and MinArray preserve the
+            // element type of the input array.
+            _ => input_type.scalar_type,
+        };
+        scalar_type.nullable(nullable)
+    }
+
+    /// Returns true if the non-null constraint on the aggregation can be
+    /// converted into a non-null constraint on its parameter expression, ie.
+    /// whether the result of the aggregation is null if all the input values
+    /// are null.
+    pub fn propagates_nonnull_constraint(&self) -> bool {
+        match self {
+            AggregateFunc::MaxNumeric
+            | AggregateFunc::MaxInt16
+            | AggregateFunc::MaxInt32
+            | AggregateFunc::MaxInt64
+            | AggregateFunc::MaxUInt16
+            | AggregateFunc::MaxUInt32
+            | AggregateFunc::MaxUInt64
+            | AggregateFunc::MaxMzTimestamp
+            | AggregateFunc::MaxFloat32
+            | AggregateFunc::MaxFloat64
+            | AggregateFunc::MaxBool
+            | AggregateFunc::MaxString
+            | AggregateFunc::MaxDate
+            | AggregateFunc::MaxTimestamp
+            | AggregateFunc::MaxTimestampTz
+            | AggregateFunc::MaxInterval
+            | AggregateFunc::MaxTime
+            | AggregateFunc::MaxBytes
+            | AggregateFunc::MaxUuid
+            | AggregateFunc::MaxArray
+            | AggregateFunc::MinNumeric
+            | AggregateFunc::MinInt16
+            | AggregateFunc::MinInt32
+            | AggregateFunc::MinInt64
+            | AggregateFunc::MinUInt16
+            | AggregateFunc::MinUInt32
+            | AggregateFunc::MinUInt64
+            | AggregateFunc::MinMzTimestamp
+            | AggregateFunc::MinFloat32
+            | AggregateFunc::MinFloat64
+            | AggregateFunc::MinBool
+            | AggregateFunc::MinString
+            | AggregateFunc::MinDate
+            | AggregateFunc::MinTimestamp
+            | AggregateFunc::MinTimestampTz
+            | AggregateFunc::MinInterval
+            | AggregateFunc::MinTime
+            | AggregateFunc::MinBytes
+            | AggregateFunc::MinUuid
+            | AggregateFunc::MinArray
+            | AggregateFunc::SumInt16
+            | AggregateFunc::SumInt32
+            | AggregateFunc::SumInt64
+            | AggregateFunc::SumUInt16
+            | AggregateFunc::SumUInt32
+            | AggregateFunc::SumUInt64
+            | AggregateFunc::SumFloat32
+            | AggregateFunc::SumFloat64
+            | AggregateFunc::SumNumeric
+            | AggregateFunc::SumInterval
+            | AggregateFunc::ProductFloat32
+            | AggregateFunc::ProductFloat64
+            | AggregateFunc::ProductNumeric
+            | AggregateFunc::AvgInt16
+            | AggregateFunc::AvgInt32
+            | AggregateFunc::AvgInt64
+            | AggregateFunc::AvgFloat32
+            | AggregateFunc::AvgFloat64
+            | AggregateFunc::AvgNumeric
+            | AggregateFunc::AvgInterval
+            | AggregateFunc::StddevPop
+            | AggregateFunc::StddevSamp
+            | AggregateFunc::VarPop
+            | AggregateFunc::VarSamp
+            | AggregateFunc::Corr
+            | AggregateFunc::CovarPop
+            | AggregateFunc::CovarSamp
+            | AggregateFunc::RegrSlope
+            | AggregateFunc::RegrIntercept
+            | AggregateFunc::RegrR2
+            | AggregateFunc::BoolAnd
+            | AggregateFunc::BoolOr
+            | AggregateFunc::StringAgg { .. } => true,
+            // Count is never null
+            AggregateFunc::Count
+            | AggregateFunc::CountNumeric
+            | AggregateFunc::CountDistinct
+            | AggregateFunc::RegrCount => false,
+            _ => false,
+        }
+    }
+}
+
+fn jsonb_each<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+    stringify: bool,
+) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    // First produce a map, so that a common iterator can be returned.
+    let map = match a {
+        Datum::Map(dict) => dict,
+        _ => mz_repr::DatumMap::empty(),
+    };
+
+    // Maps are packed with their keys in ascending order, so the entries are
+    // emitted sorted by key without needing to sort them here.
+    map.iter().map(move |(k, mut v)| {
+        if stringify {
+            v = jsonb_stringify(v, temp_storage);
+        }
+        (Row::pack_slice(&[Datum::String(k), v]), 1)
+    })
+}
+
+fn jsonb_object_keys<'a>(a: Datum<'a>) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    let map = match a {
+        Datum::Map(dict) => dict,
+        _ => mz_repr::DatumMap::empty(),
+    };
+
+    map.iter()
+        .map(move |(k, _)| (Row::pack_slice(&[Datum::String(k)]), 1))
+}
+
+/// Yields each value selected by `path` from the jsonb value `a`.
+///
+/// Like PostgreSQL, the path is evaluated in lax mode. A member access on an
+/// array applies to each of the array's elements, unwrapping one level of
+/// array, and a subscript of a value that is not an array treats the value as
+/// a single-element array. Steps that still do not apply to a value, like a
+/// member access on a number or an out-of-bounds subscript, select nothing
+/// rather than erroring.
+fn jsonb_path_query<'a>(a: Datum<'a>, path: &JsonPath) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    fn member<'a>(value: Datum<'a>, key: &str) -> Option<Datum<'a>> {
+        match value {
+            Datum::Map(map) => map.iter().find(|(k, _)| *k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    let mut values = vec![a];
+    for step in &path.0 {
+        let mut selected = Vec::new();
+        for value in values {
+            match (step, value) {
+                (JsonPathStep::Member(key), Datum::List(list)) => {
+                    selected.extend(list.iter().filter_map(|elem| member(elem, key)))
+                }
+                (JsonPathStep::Member(key), value) => selected.extend(member(value, key)),
+                (JsonPathStep::Index(i), Datum::List(list)) => selected.extend(list.iter().nth(*i)),
+                (JsonPathStep::Wildcard, Datum::List(list)) => selected.extend(list.iter()),
+                (JsonPathStep::Index(0) | JsonPathStep::Wildcard, value) => selected.push(value),
+                _ => (),
+            }
+        }
+        values = selected;
+    }
+    values
+        .into_iter()
+        .map(|value| (Row::pack_slice(&[value]), 1))
+}
+
+/// Expands a jsonb array of objects into one row per element, pulling out the
+/// value of each of `columns` by key and converting it to the column's type.
+/// Values that are missing, JSON null, or not convertible to the column's type
+/// become `Datum::Null`, as do all the values of an element that is not an
+/// object. A value that is not an array yields no rows.
+fn jsonb_to_recordset<'a>(
+    a: Datum<'a>,
+    columns: &'a [(ColumnName, ScalarType)],
+) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    let list = match a {
+        Datum::List(list) => list,
+        _ => mz_repr::DatumList::empty(),
+    };
+    list.iter().map(move |element| {
+        let mut row = Row::default();
+        let mut packer = row.packer();
+        for (name, typ) in columns {
+            let value = match element {
+                Datum::Map(map) => map
+                    .iter()
+                    .find(|(k, _)| *k == name.as_str())
+                    .map(|(_, v)| v),
+                _ => None,
+            };
+            match value {
+                None | Some(Datum::JsonNull) => packer.push(Datum::Null),
+                Some(value) => jsonb_value_to_scalar(value, typ, &mut packer),
+            }
+        }
+        (row, 1)
+    })
+}
+
+/// Reports whether [`jsonb_value_to_scalar`] can convert jsonb values to `typ`.
+fn jsonb_to_scalar_supported(typ: &ScalarType) -> bool {
+    match typ {
+        ScalarType::Bool
+        | ScalarType::Int16
+        | ScalarType::Int32
+        | ScalarType::Int64
+        | ScalarType::UInt16
+        | ScalarType::UInt32
+        | ScalarType::UInt64
+        | ScalarType::Float32
+        | ScalarType::Float64
+        | ScalarType::Numeric { .. }
+        | ScalarType::Date
+        | ScalarType::Time
+        | ScalarType::Timestamp
+        | ScalarType::TimestampTz
+        | ScalarType::Interval
+        | ScalarType::Bytes
+        | ScalarType::String
+        | ScalarType::Char { .. }
+        | ScalarType::VarChar { .. }
+        | ScalarType::Jsonb
+        | ScalarType::Uuid
+        | ScalarType::Oid
+        | ScalarType::MzTimestamp => true,
+        ScalarType::PgLegacyChar
+        | ScalarType::Array(_)
+        | ScalarType::List { .. }
+        | ScalarType::Record { .. }
+        | ScalarType::Map { .. }
+        | ScalarType::RegProc
+        | ScalarType::RegType
+        | ScalarType::RegClass
+        | ScalarType::Int2Vector
+        | ScalarType::Range { .. }
+        | ScalarType::MzAclItem => false,
+    }
+}
+
+/// Pushes the jsonb `value` converted to `typ`, or `Datum::Null` if it cannot be
+/// converted. Like PostgreSQL, the conversion parses the text of a string value,
+/// or the JSON text of any other value, as the target type.
+///
+/// `typ` must be supported, as reported by [`jsonb_to_scalar_supported`].
+fn jsonb_value_to_scalar(value: Datum, typ: &ScalarType, packer: &mut RowPacker) {
+    if let ScalarType::Jsonb = typ {
+        packer.push(value);
+        return;
+    }
+    let text = match value {
+        Datum::String(s) => s.to_owned(),
+        _ => JsonbRef::from_datum(value).to_string(),
+    };
+    let (formatted, bytes);
+    let datum = match typ {
+        ScalarType::Bool => strconv::parse_bool(&text).ok().map(Datum::from),
+        ScalarType::Int16 => strconv::parse_int16(&text).ok().map(Datum::from),
+        ScalarType::Int32 => strconv::parse_int32(&text).ok().map(Datum::from),
+        ScalarType::Int64 => strconv::parse_int64(&text).ok().map(Datum::from),
+        ScalarType::UInt16 => strconv::parse_uint16(&text).ok().map(Datum::from),
+        ScalarType::UInt32 => strconv::parse_uint32(&text).ok().map(Datum::from),
+        ScalarType::UInt64 => strconv::parse_uint64(&text).ok().map(Datum::from),
+        ScalarType::Float32 => strconv::parse_float32(&text).ok().map(Datum::from),
+        ScalarType::Float64 => strconv::parse_float64(&text).ok().map(Datum::from),
+        ScalarType::Numeric { max_scale } => {
+            strconv::parse_numeric(&text).ok().and_then(|mut n| {
+                if let Some(scale) = max_scale {
+                    numeric::rescale(&mut n.0, scale.into_u8()).ok()?;
+                }
+                Some(Datum::Numeric(n))
+            })
+        }
+        ScalarType::Date => strconv::parse_date(&text).ok().map(Datum::Date),
+        ScalarType::Time => strconv::parse_time(&text).ok().map(Datum::Time),
+        ScalarType::Timestamp => strconv::parse_timestamp(&text).ok().map(Datum::Timestamp),
+        ScalarType::TimestampTz => strconv::parse_timestamptz(&text)
+            .ok()
+            .map(Datum::TimestampTz),
+        ScalarType::Interval => strconv::parse_interval(&text).ok().map(Datum::Interval),
+        ScalarType::Bytes => match strconv::parse_bytes(&text) {
+            Ok(b) => {
+                bytes = b;
+                Some(Datum::Bytes(&bytes))
+            }
+            Err(_) => None,
+        },
+        ScalarType::Uuid => strconv::parse_uuid(&text).ok().map(Datum::Uuid),
+        ScalarType::String => Some(Datum::String(&text)),
+        ScalarType::Char { length } => {
+            match mz_repr::adt::char::format_str_trim(&text, *length, true) {
+                Ok(s) => {
+                    formatted = s;
+                    Some(Datum::String(&formatted))
+                }
+                Err(_) => None,
+            }
+        }
+        ScalarType::VarChar { max_length } => {
+            match mz_repr::adt::varchar::format_str(&text, *max_length, true) {
+                Ok(s) => {
+                    formatted = s;
+                    Some(Datum::String(&formatted))
+                }
+                Err(_) => None,
+            }
+        }
+        ScalarType::Oid => strconv::parse_oid(&text).ok().map(Datum::UInt32),
+        ScalarType::MzTimestamp => strconv::parse_mz_timestamp(&text)
+            .ok()
+            .map(Datum::MzTimestamp),
+        // Rejected when the table function is constructed.
+        _ => None,
+    };
+    packer.push(datum.unwrap_or(Datum::Null));
+}
+
+fn jsonb_array_elements<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+    stringify: bool,
+) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    let list = match a {
+        Datum::List(list) => list,
+        _ => mz_repr::DatumList::empty(),
+    };
+    list.iter().map(move |mut e| {
+        if stringify {
+            e = jsonb_stringify(e, temp_storage);
+        }
+        (Row::pack_slice(&[e]), 1)
+    })
+}
+
+fn regexp_extract(a: Datum, r: &AnalyzedRegex) -> Option<(Row, Diff)> {
+    let r = r.inner();
+    let a = a.unwrap_str();
+    let captures = r.captures(a)?;
+    let datums = captures
+        .iter()
+        .skip(1)
+        .map(|m| Datum::from(m.map(|m| m.as_str())));
+    Some((Row::pack(datums), 1))
+}
+
+/// Emits the capture groups of the matches of `r` against `a`, one row per
+/// match. Unless `global` is set, only the first match is emitted.
+///
+/// Like PostgreSQL, an empty match does not stall the search: the next search
+/// resumes one character past it, so patterns like `x*` terminate.
+fn regexp_matches<'a>(
+    a: Datum<'a>,
+    r: &'a AnalyzedRegex,
+    global: bool,
+) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    let limit = if global { usize::MAX } else { 1 };
+    r.inner()
+        .captures_iter(a.unwrap_str())
+        .take(limit)
+        .map(|captures| {
+            let datums = captures
+                .iter()
+                .skip(1)
+                .map(|m| Datum::from(m.map(|m| m.as_str())));
+            (Row::pack(datums), 1)
+        })
+}
+
+/// Splits `a` into one row per substring separated by matches of `r`.
+///
+/// Like PostgreSQL, leading and trailing matches produce empty fields, and an
+/// empty input produces a single empty field. Zero-length matches at the start
+/// or end of the input, or directly after the previous match, are ignored, so
+/// that e.g. an empty pattern splits the input into its characters.
+fn regexp_split_to_table<'a>(
+    a: Datum<'a>,
+    r: &'a AnalyzedRegex,
+) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    let text = a.unwrap_str();
+    let mut fields = vec![];
+    let mut field_start = 0;
+    for m in r.inner().find_iter(text) {
+        if m.start() < text.len() && m.end() > field_start {
+            fields.push(&text[field_start..m.start()]);
+            field_start = m.end();
+        }
+    }
+    fields.push(&text[field_start..]);
+    fields
+        .into_iter()
+        .map(|field| (Row::pack_slice(&[Datum::String(field)]), 1))
+}
+
+/// Splits `input` into one row per field separated by `delimiter`, replacing
+/// fields equal to `null_string` with NULL.
+///
+/// This matches PostgreSQL: a NULL delimiter splits the input into individual
+/// characters, an empty delimiter yields the whole input as a single field, and
+/// a NULL or empty input yields no rows at all.
+fn string_to_table<'a>(
+    input: Datum<'a>,
+    delimiter: Datum<'a>,
+    null_string: Datum<'a>,
+) -> Box<dyn Iterator<Item = (Row, Diff)> + 'a> {
+    let input = match input {
+        Datum::Null | Datum::String("") => return Box::new(iter::empty()),
+        input => input.unwrap_str(),
+    };
+    let null_string = match null_string {
+        Datum::Null => None,
+        null_string => Some(null_string.unwrap_str()),
+    };
+    let fields: Box<dyn Iterator<Item = &'a str> + 'a> = match delimiter {
+        Datum::Null => Box::new(
+            input
+                .char_indices()
+                .map(move |(i, c)| &input[i..i + c.len_utf8()]),
+        ),
+        Datum::String("") => Box::new(iter::once(input)),
+        delimiter => Box::new(input.split(delimiter.unwrap_str())),
+    };
+    Box::new(fields.map(move |field| {
+        let datum = if Some(field) == null_string {
+            Datum::Null
+        } else {
+            Datum::String(field)
+        };
+        (Row::pack_slice(&[datum]), 1)
+    }))
+}
+
+fn generate_series<N>(
+    start: N,
+    stop: N,
+    step: N,
+) -> Result<impl Iterator<Item = (Row, Diff)>, EvalError>
+where
+    N: Integer + Signed + CheckedAdd + Clone,
+    Datum<'static>: From<N>,
+{
+    if step == N::zero() {
+        return Err(EvalError::InvalidParameterValue(
+            "step size cannot equal zero".to_owned(),
+        ));
+    }
+    Ok(num::range_step_inclusive(start, stop, step)
+        .map(move |i| (Row::pack_slice(&[Datum::from(i)]), 1)))
+}
+
+/// Like [`generate_series`], but for numeric values, which are added using
+/// the numeric datum context. The series ends early if a step overflows, or
+/// if it no longer changes the value because the step is below the value's
+/// precision.
+fn generate_series_numeric(
+    start: Numeric,
+    stop: Numeric,
+    step: Numeric,
+) -> Result<impl Iterator<Item = (Row, Diff)>, EvalError> {
+    if step.is_zero() {
+        return Err(EvalError::InvalidParameterValue(
+            "step size cannot equal zero".to_owned(),
+        ));
+    }
+    for (name, value) in [
+        ("start value", start),
+        ("stop value", stop),
+        ("step size", step),
+    ] {
+        if value.is_nan() {
+            return Err(EvalError::InvalidParameterValue(format!(
+                "{name} cannot be NaN"
+            )));
+        }
+    }
+
+    let mut cx = numeric::cx_datum();
+    let mut second = start;
+    cx.add(&mut second, &step);
+    if !cx.status().overflow() && second == start {
+        return Err(EvalError::InvalidParameterValue(
+            "step size is below the precision of the start value".to_owned(),
+        ));
+    }
+
+    let rev = step.is_negative();
+    let mut cx = numeric::cx_datum();
+    let mut next = Some(start);
+    Ok(iter::from_fn(move || {
+        let value = next.take()?;
+        if (rev && value < stop) || (!rev && value > stop) {
+            return None;
+        }
+        let mut following = value;
+        cx.add(&mut following, &step);
+        if !cx.status().overflow() && following != value {
+            next = Some(following);
+        }
+        Some((Row::pack_slice(&[Datum::from(value)]), 1))
+    }))
+}
+
+/// Like
+/// [`num::range_step_inclusive`](https://github.com/rust-num/num-iter/blob/ddb14c1e796d401014c6c7a727de61d8109ad986/src/lib.rs#L279),
+/// but for our timestamp types using [`Interval`] for `step`.xwxw
+#[derive(Clone)]
+pub struct TimestampRangeStepInclusive<T> {
+    state: CheckedTimestamp<T>,
+    stop: CheckedTimestamp<T>,
+    step: Interval,
+    rev: bool,
+    done: bool,
+}
+
+impl<T: TimestampLike> Iterator for TimestampRangeStepInclusive<T> {
+    type Item = CheckedTimestamp<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<CheckedTimestamp<T>> {
+        if !self.done
+            && ((self.rev && self.state >= self.stop) || (!self.rev && self.state <= self.stop))
+        {
+            let result = self.state.clone();
+            match add_timestamp_months(self.state.deref(), self.step.months) {
+                Ok(state) => match state.checked_add_signed(self.step.duration_as_chrono()) {
+                    Some(v) => match CheckedTimestamp::from_timestamplike(v) {
+                        Ok(v) => self.state = v,
+                        Err(_) => self.done = true,
+                    },
+                    None => self.done = true,
+                },
+                Err(..) => {
+                    self.done = true;
+                }
+            }
+
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+fn generate_series_ts<T: TimestampLike>(
+    start: CheckedTimestamp<T>,
+    stop: CheckedTimestamp<T>,
+    step: Interval,
+    conv: fn(CheckedTimestamp<T>) -> Datum<'static>,
+) -> Result<impl Iterator<Item = (Row, Diff)>, EvalError> {
+    let normalized_step = step.as_microseconds();
+    if normalized_step == 0 {
+        return Err(EvalError::InvalidParameterValue(
+            "step size cannot equal zero".to_owned(),
+        ));
+    }
+    let rev = normalized_step < 0;
+
+    let trsi = TimestampRangeStepInclusive {
+        state: start,
+        stop,
+        step,
+        rev,
+        done: false,
+    };
+
+    Ok(trsi.map(move |i| (Row::pack_slice(&[conv(i)]), 1)))
+}
+
+/// Like [`generate_series_ts`], but for `timestamptz` values whose step is
+/// applied in the time zone `tz`, as PostgreSQL does.
+///
+/// The month and day components of `step` are added to the local time in `tz`,
+/// so that, e.g., a step of `1 day` across a daylight saving time transition
+/// keeps the local time of day stable. The time component of `step` is added
+/// to the resulting instant directly.
+fn generate_series_ts_tz(
+    start: CheckedTimestamp<DateTime<Utc>>,
+    stop: CheckedTimestamp<DateTime<Utc>>,
+    step: Interval,
+    tz: Timezone,
+) -> Result<impl Iterator<Item = (Row, Diff)>, EvalError> {
+    let normalized_step = step.as_microseconds();
+    if normalized_step == 0 {
+        return Err(EvalError::InvalidParameterValue(
+            "step size cannot equal zero".to_owned(),
+        ));
+    }
+    let rev = normalized_step < 0;
+
+    let add_step = move |ts: &CheckedTimestamp<DateTime<Utc>>| {
+        let mut ts = *ts.deref();
+        // Only round trip through local time if necessary, as local times
+        // are ambiguous when clocks are turned back.
+        if step.months != 0 || step.days != 0 {
+            let local = timezone_timestamptz(tz, ts);
+            let local = add_timestamp_months(&local, step.months).ok()?;
+            let local = local.checked_add_signed(Duration::days(step.days.into()))?;
+            ts = *timezone_timestamp(tz, local).ok()?;
+        }
+        let ts = ts.checked_add_signed(Duration::microseconds(step.micros))?;
+        CheckedTimestamp::from_timestamplike(ts).ok()
+    };
+
+    Ok(iter::successors(Some(start), add_step)
+        .take_while(move |ts| if rev { *ts >= stop } else { *ts <= stop })
+        .map(|ts| (Row::pack_slice(&[Datum::from(ts)]), 1)))
+}
+
+/// Yields the subscripts of dimension `dim` of the array `a`, from its lower
+/// bound up to its upper bound, or down from its upper bound if `reverse` is
+/// set. A `dim` that the array does not have, as for any dimension of an empty
+/// array, yields no subscripts.
+fn generate_subscripts_array(
+    a: Datum,
+    dim: i32,
+    reverse: bool,
+) -> Result<Box<dyn Iterator<Item = (Row, Diff)>>, EvalError> {
+    if dim <= 0 {
+        return Ok(Box::new(iter::empty()));
+    }
+
+    match a.unwrap_array().dims().into_iter().nth(
+        (dim - 1)
+            .try_into()
+            .map_err(|_| EvalError::Int32OutOfRange)?,
+    ) {
+        Some(requested_dim) => {
+            let lower: i32 = requested_dim
+                .lower_bound
+                .try_into()
+                .map_err(|_| EvalError::Int32OutOfRange)?;
+            let length: i32 = requested_dim
+                .length
+                .try_into()
+                .map_err(|_| EvalError::Int32OutOfRange)?;
+            let upper = lower
+                .checked_add(length - 1)
+                .ok_or(EvalError::Int32OutOfRange)?;
+            if reverse {
+                Ok(Box::new(generate_series::<i32>(upper, lower, -1)?))
+            } else {
+                Ok(Box::new(generate_series::<i32>(lower, upper, 1)?))
+            }
+        }
+        None => Ok(Box::new(iter::empty())),
+    }
+}
+
+// The planner only unnests arrays and lists, so a datum of another type
+// indicates a planner bug, which is reported as an error rather than a panic.
+fn unnest_array<'a>(
+    a: Datum<'a>,
+    ordinality: bool,
+) -> Result<impl Iterator<Item = (Row, Diff)> + 'a, EvalError> {
+    match a {
+        Datum::Array(array) => Ok(pack_unnested(array.elements().iter(), ordinality)),
+        d => Err(EvalError::Internal(format!(
+            "unnest_array called on non-array datum: {:?}",
+            d
+        ))),
+    }
+}
+
+/// Zips the elements of the arrays in `datums` into rows, padding the rows past
+/// the end of the shorter arrays with `Datum::Null`. A null array is treated
+/// as empty.
+fn unnest_arrays<'a>(
+    datums: &'a [Datum<'a>],
+) -> Result<impl Iterator<Item = (Row, Diff)> + 'a, EvalError> {
+    let arrays = datums
+        .iter()
+        .map(|d| match d {
+            Datum::Null => Ok(Vec::new()),
+            Datum::Array(array) => Ok(array.elements().iter().collect()),
+            d => Err(EvalError::Internal(format!(
+                "unnest_arrays called on non-array datum: {:?}",
+                d
+            ))),
+        })
+        .collect::<Result<Vec<Vec<_>>, _>>()?;
+    let len = arrays.iter().map(Vec::len).max().unwrap_or(0);
+    Ok((0..len).map(move |i| {
+        let row = Row::pack(
+            arrays
+                .iter()
+                .map(|elements| elements.get(i).copied().unwrap_or(Datum::Null)),
+        );
+        (row, 1)
+    }))
+}
+
+/// Emits one row per combination of the elements of the arrays in `datums`,
+/// with one column per array.
+///
+/// Rows are emitted in lexicographic order of the element positions, i.e., the
+/// elements of the last array vary fastest, as in nested loops over the arrays
+/// from first to last. Returns an error rather than emitting more than
+/// `max_rows` rows.
+fn cross_product<'a>(
+    datums: &'a [Datum<'a>],
+    max_rows: u64,
+) -> Result<impl Iterator<Item = (Row, Diff)> + 'a, EvalError> {
+    let arrays = datums
+        .iter()
+        .map(|d| match d {
+            Datum::Array(array) => Ok(array.elements().iter().collect()),
+            d => Err(EvalError::Internal(format!(
+                "cross_product called on non-array datum: {:?}",
+                d
+            ))),
+        })
+        .collect::<Result<Vec<Vec<_>>, _>>()?;
+    let n_rows = arrays
+        .iter()
+        .try_fold(1u64, |n, elements| {
+            n.checked_mul(u64::cast_from(elements.len()))
+        })
+        .filter(|n| *n <= max_rows)
+        .ok_or_else(|| {
+            EvalError::InvalidParameterValue(format!(
+                "cross product exceeds the maximum of {} rows",
+                max_rows
+            ))
+        })?;
+    Ok((0..n_rows).map(move |i| {
+        let mut positions = Vec::with_capacity(arrays.len());
+        let mut rest = i;
+        for elements in arrays.iter().rev() {
+            let len = u64::cast_from(elements.len());
+            positions.push(usize::cast_from(rest % len));
+            rest /= len;
+        }
+        let row = Row::pack(
+            arrays
+                .iter()
+                .zip(positions.into_iter().rev())
+                .map(|(elements, position)| elements[position]),
+        );
+        (row, 1)
+    }))
+}
+
+fn unnest_list<'a>(
+    a: Datum<'a>,
+    ordinality: bool,
+) -> Result<impl Iterator<Item = (Row, Diff)> + 'a, EvalError> {
+    match a {
+        Datum::List(list) => Ok(pack_unnested(list.iter(), ordinality)),
+        d => Err(EvalError::Internal(format!(
+            "unnest_list called on non-list datum: {:?}",
+            d
+        ))),
+    }
+}
+
+/// Packs each of `elements` into its own row. If `ordinality` is set, each row
+/// also contains the 1-based position of its element, counting null elements.
+fn pack_unnested<'a, I>(elements: I, ordinality: bool) -> impl Iterator<Item = (Row, Diff)> + 'a
+where
+    I: Iterator<Item = Datum<'a>> + 'a,
+{
+    elements.zip(1..).map(move |(e, i)| {
+        let row = if ordinality {
+            Row::pack_slice(&[e, Datum::Int64(i)])
+        } else {
+            Row::pack_slice(&[e])
+        };
+        (row, 1)
+    })
+}
+
+impl fmt::Display for AggregateFunc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AggregateFunc::MaxNumeric => f.write_str("max"),
+            AggregateFunc::MaxInt16 => f.write_str("max"),
+            AggregateFunc::MaxInt32 => f.write_str("max"),
+            AggregateFunc::MaxInt64 => f.write_str("max"),
+            AggregateFunc::MaxUInt16 => f.write_str("max"),
+            AggregateFunc::MaxUInt32 => f.write_str("max"),
+            AggregateFunc::MaxUInt64 => f.write_str("max"),
+            AggregateFunc::MaxMzTimestamp => f.write_str("max"),
+            AggregateFunc::MaxFloat32 => f.write_str("max"),
+            AggregateFunc::MaxFloat64 => f.write_str("max"),
+            AggregateFunc::MaxBool => f.write_str("max"),
+            AggregateFunc::MaxString => f.write_str("max"),
+            AggregateFunc::MaxDate => f.write_str("max"),
+            AggregateFunc::MaxTimestamp => f.write_str("max"),
+            AggregateFunc::MaxTimestampTz => f.write_str("max"),
+            AggregateFunc::MaxInterval => f.write_str("max"),
+            AggregateFunc::MaxTime => f.write_str("max"),
+            AggregateFunc::MaxBytes => f.write_str("max"),
+            AggregateFunc::MaxUuid => f.write_str("max"),
+            AggregateFunc::MaxArray => f.write_str("max"),
+            AggregateFunc::MinNumeric => f.write_str("min"),
+            AggregateFunc::MinInt16 => f.write_str("min"),
+            AggregateFunc::MinInt32 => f.write_str("min"),
+            AggregateFunc::MinInt64 => f.write_str("min"),
+            AggregateFunc::MinUInt16 => f.write_str("min"),
+            AggregateFunc::MinUInt32 => f.write_str("min"),
+            AggregateFunc::MinUInt64 => f.write_str("min"),
+            AggregateFunc::MinMzTimestamp => f.write_str("min"),
+            AggregateFunc::MinFloat32 => f.write_str("min"),
+            AggregateFunc::MinFloat64 => f.write_str("min"),
+            AggregateFunc::MinBool => f.write_str("min"),
+            AggregateFunc::MinString => f.write_str("min"),
+            AggregateFunc::MinDate => f.write_str("min"),
+            AggregateFunc::MinTimestamp => f.write_str("min"),
+            AggregateFunc::MinTimestampTz => f.write_str("min"),
+            AggregateFunc::MinInterval => f.write_str("min"),
+            AggregateFunc::MinTime => f.write_str("min"),
+            AggregateFunc::MinBytes => f.write_str("min"),
+            AggregateFunc::MinUuid => f.write_str("min"),
+            AggregateFunc::MinArray => f.write_str("min"),
+            AggregateFunc::SumInt16 => f.write_str("sum"),
+            AggregateFunc::SumInt32 => f.write_str("sum"),
+            AggregateFunc::SumInt64 => f.write_str("sum"),
+            AggregateFunc::SumUInt16 => f.write_str("sum"),
+            AggregateFunc::SumUInt32 => f.write_str("sum"),
+            AggregateFunc::SumUInt64 => f.write_str("sum"),
+            AggregateFunc::SumFloat32 => f.write_str("sum"),
+            AggregateFunc::SumFloat64 => f.write_str("sum"),
+            AggregateFunc::SumNumeric => f.write_str("sum"),
+            AggregateFunc::SumInterval => f.write_str("sum"),
+            AggregateFunc::ProductFloat32 => f.write_str("product"),
+            AggregateFunc::ProductFloat64 => f.write_str("product"),
+            AggregateFunc::ProductNumeric => f.write_str("product"),
+            AggregateFunc::AvgInt16 => f.write_str("avg"),
+            AggregateFunc::AvgInt32 => f.write_str("avg"),
+            AggregateFunc::AvgInt64 => f.write_str("avg"),
+            AggregateFunc::AvgFloat32 => f.write_str("avg"),
+            AggregateFunc::AvgFloat64 => f.write_str("avg"),
+            AggregateFunc::AvgNumeric => f.write_str("avg"),
+            AggregateFunc::AvgInterval => f.write_str("avg"),
+            AggregateFunc::StddevPop => f.write_str("stddev_pop"),
+            AggregateFunc::StddevSamp => f.write_str("stddev_samp"),
+            AggregateFunc::VarPop => f.write_str("var_pop"),
+            AggregateFunc::VarSamp => f.write_str("var_samp"),
+            AggregateFunc::Corr => f.write_str("corr"),
+            AggregateFunc::CovarPop => f.write_str("covar_pop"),
+            AggregateFunc::CovarSamp => f.write_str("covar_samp"),
+            AggregateFunc::RegrCount => f.write_str("regr_count"),
+            AggregateFunc::RegrSlope => f.write_str("regr_slope"),
+            AggregateFunc::RegrIntercept => f.write_str("regr_intercept"),
+            AggregateFunc::RegrR2 => f.write_str("regr_r2"),
+            AggregateFunc::Count => f.write_str("count"),
+            AggregateFunc::CountNumeric => f.write_str("count"),
+            AggregateFunc::CountDistinct => f.write_str("count"),
+            AggregateFunc::Any => f.write_str("any"),
+            AggregateFunc::All => f.write_str("all"),
+            AggregateFunc::BoolAnd => f.write_str("bool_and"),
+            AggregateFunc::BoolOr => f.write_str("bool_or"),
+            AggregateFunc::JsonbAgg { .. } => f.write_str("jsonb_agg"),
+            AggregateFunc::JsonbObjectAgg {
+                stringify_values: false,
+                ..
+            } => f.write_str("jsonb_object_agg"),
+            AggregateFunc::JsonbObjectAgg {
+                stringify_values: true,
+                ..
+            } => f.write_str("jsonb_object_agg_text"),
+            AggregateFunc::ArrayConcat { .. } => f.write_str("array_agg"),
+            AggregateFunc::ListConcat { .. } => f.write_str("list_agg"),
+            AggregateFunc::ArrayConcatDistinct { .. } => f.write_str("array_agg_distinct"),
+            AggregateFunc::ListConcatDistinct { .. } => f.write_str("list_agg_distinct"),
+            AggregateFunc::StringAgg { .. } => f.write_str("string_agg"),
+            AggregateFunc::RowNumber { .. } => f.write_str("row_number"),
+            AggregateFunc::Rank { .. } => f.write_str("rank"),
+            AggregateFunc::DenseRank { .. } => f.write_str("dense_rank"),
+            AggregateFunc::PercentRank { .. } => f.write_str("percent_rank"),
+            AggregateFunc::CumeDist { .. } => f.write_str("cume_dist"),
+            AggregateFunc::LagLead {
+                lag_lead: LagLeadType::Lag,
+                ..
+            } => f.write_str("lag"),
+            AggregateFunc::LagLead {
+                lag_lead: LagLeadType::Lead,
+                ..
+            } => f.write_str("lead"),
+            AggregateFunc::FirstValue { .. } => f.write_str("first_value"),
+            AggregateFunc::LastValue { .. } => f.write_str("last_value"),
+            AggregateFunc::NthValue { .. } => f.write_str("nth_value"),
+            AggregateFunc::Ntile { .. } => f.write_str("ntile"),
+            AggregateFunc::Dummy => f.write_str("dummy"),
+        }
+    }
+}
+
+#[derive(
+    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
+)]
+pub struct CaptureGroupDesc {
+    pub index: u32,
+    pub name: Option<String>,
+    pub nullable: bool,
+}
+
+impl RustType<ProtoCaptureGroupDesc> for CaptureGroupDesc {
+    fn into_proto(&self) -> ProtoCaptureGroupDesc {
+        ProtoCaptureGroupDesc {
+            index: self.index,
+            name: self.name.clone(),
+            nullable: self.nullable,
+        }
+    }
+
+    fn from_proto(proto: ProtoCaptureGroupDesc) -> Result<Self, TryFromProtoError> {
+        Ok(Self {
+            index: proto.index,
+            name: proto.name,
+            nullable: proto.nullable,
+        })
+    }
+}
+
+#[derive(
+    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
+)]
+pub struct AnalyzedRegex(
+    #[proptest(strategy = "mz_repr::adt::regex::any_regex()")] ReprRegex,
+    Vec<CaptureGroupDesc>,
+);
+
+impl RustType<ProtoAnalyzedRegex> for AnalyzedRegex {
+    fn into_proto(&self) -> ProtoAnalyzedRegex {
+        ProtoAnalyzedRegex {
+            regex: Some(self.0.into_proto()),
+            groups: self.1.into_proto(),
+        }
+    }
+
+    fn from_proto(proto: ProtoAnalyzedRegex) -> Result<Self, TryFromProtoError> {
+        Ok(AnalyzedRegex(
+            proto.regex.into_rust_if_some("ProtoAnalyzedRegex::regex")?,
+            proto.groups.into_rust()?,
+        ))
+    }
+}
+
+impl AnalyzedRegex {
+    pub fn new(s: &str) -> Result<Self, regex::Error> {
+        let r = regex::Regex::new(s)?;
+        // TODO(benesch): remove potentially dangerous usage of `as`.
+        #[allow(clippy::as_conversions)]
+        let descs: Vec<_> = r
+            .capture_names()
+            .enumerate()
+            // The first capture is the entire matched string.
+            // This will often not be useful, so skip it.
+            // If people want it they can just surround their
+            // entire regex in an explicit capture group.
+            .skip(1)
+            .map(|(i, name)| CaptureGroupDesc {
+                index: i as u32,
+                name: name.map(String::from),
+                // TODO -- we can do better.
+                // https://github.com/MaterializeInc/materialize/issues/1685
+                nullable: true,
+            })
+            .collect();
+        Ok(Self(ReprRegex(r), descs))
+    }
+    pub fn capture_groups_len(&self) -> usize {
+        self.1.len()
+    }
+    pub fn capture_groups_iter(&self) -> impl Iterator<Item = &CaptureGroupDesc> {
+        self.1.iter()
+    }
+    pub fn inner(&self) -> &Regex {
+        &(self.0).0
+    }
+}
+
+/// A step of a [`JsonPath`].
+#[derive(
+    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
+)]
+pub enum JsonPathStep {
+    /// Selects the value of the named member of an object.
+    Member(String),
+    /// Selects the element at the given 0-based position of an array.
+    Index(usize),
+    /// Selects every element of an array.
+    Wildcard,
+}
+
+impl RustType<ProtoJsonPathStep> for JsonPathStep {
+    fn into_proto(&self) -> ProtoJsonPathStep {
+        use proto_json_path_step::Kind;
+        let kind = match self {
+            JsonPathStep::Member(key) => Kind::Member(key.clone()),
+            JsonPathStep::Index(i) => Kind::Index(i.into_proto()),
+            JsonPathStep::Wildcard => Kind::Wildcard(()),
+        };
+        ProtoJsonPathStep { kind: Some(kind) }
+    }
+
+    fn from_proto(proto: ProtoJsonPathStep) -> Result<Self, TryFromProtoError> {
+        use proto_json_path_step::Kind;
+        let kind = proto
+            .kind
+            .ok_or_else(|| TryFromProtoError::missing_field("ProtoJsonPathStep::kind"))?;
+        Ok(match kind {
+            Kind::Member(key) => JsonPathStep::Member(key),
+            Kind::Index(i) => JsonPathStep::Index(i.into_rust()?),
+            Kind::Wildcard(()) => JsonPathStep::Wildcard,
+        })
+    }
+}
+
+/// A JSON path expression, parsed from its textual form by [`JsonPath::parse`].
+///
+/// Only a subset of the SQL/JSON path language is supported: a path starts at
+/// the root, `$`, followed by any number of member accesses (`.key` or
+/// `."key"`), array subscripts (`[0]`), and array wildcards (`[*]`). Quoted
+/// member names may contain the escapes `\"`, `\\`, `\b`, `\f`, `\n`, `\r`
+/// and `\t`. Paths are always evaluated in lax mode, which may be spelled out
+/// with a leading `lax`; strict mode is not supported.
+#[derive(
+    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
+)]
+pub struct JsonPath(Vec<JsonPathStep>);
+
+impl RustType<ProtoJsonPath> for JsonPath {
+    fn into_proto(&self) -> ProtoJsonPath {
+        ProtoJsonPath {
+            steps: self.0.into_proto(),
+        }
+    }
+
+    fn from_proto(proto: ProtoJsonPath) -> Result<Self, TryFromProtoError> {
+        Ok(JsonPath(proto.steps.into_rust()?))
+    }
+}
+
+impl JsonPath {
+    pub fn parse(s: &str) -> Result<Self, EvalError> {
+        let err = |msg: &str| {
+            EvalError::InvalidParameterValue(format!("invalid JSON path \"{}\": {}", s, msg))
+        };
+        let mut rest = s.trim();
+        if let Some(path) = rest.strip_prefix("lax") {
+            if path.starts_with(char::is_whitespace) {
+                rest = path.trim_start();
+            }
+        } else if let Some(path) = rest.strip_prefix("strict") {
+            if path.starts_with(char::is_whitespace) {
+                return Err(err("strict mode is not supported"));
+            }
+        }
+        let mut rest = rest
+            .strip_prefix('$')
+            .ok_or_else(|| err("path must start with $"))?;
+        let mut steps = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(member) = rest.strip_prefix('.') {
+                let member = member.trim_start();
+                let (key, remainder) = if let Some(quoted) = member.strip_prefix('"') {
+                    let mut key = String::new();
+                    let mut chars = quoted.char_indices();
+                    let end = loop {
+                        match chars.next() {
+                            Some((i, '"')) => break i,
+                            Some((_, '\\')) => key.push(match chars.next() {
+                                Some((_, c @ ('"' | '\\'))) => c,
+                                Some((_, 'b')) => '\x08',
+                                Some((_, 'f')) => '\x0c',
+                                Some((_, 'n')) => '\n',
+                                Some((_, 'r')) => '\r',
+                                Some((_, 't')) => '\t',
+                                _ => return Err(err("invalid escape in quoted member name")),
+                            }),
+                            Some((_, c)) => key.push(c),
+                            None => return Err(err("unterminated quoted member name")),
+                        }
+                    };
+                    (key, &quoted[end + 1..])
+                } else {
+                    let end = member
+                        .find(|c: char| !c.is_alphanumeric() && c != '_')
+                        .unwrap_or(member.len());
+                    if end == 0 {
+                        return Err(err("expected member name after ."));
+                    }
+                    let (key, remainder) = member.split_at(end);
+                    (key.to_owned(), remainder)
+                };
+                steps.push(JsonPathStep::Member(key));
+                rest = remainder;
+            } else if let Some(subscript) = rest.strip_prefix('[') {
+                let end = subscript
+                    .find(']')
+                    .ok_or_else(|| err("unterminated array subscript"))?;
+                let step = match subscript[..end].trim() {
+                    "*" => JsonPathStep::Wildcard,
+                    i => JsonPathStep::Index(
+                        i.parse()
+                            .map_err(|_| err("array subscript must be * or an integer"))?,
+                    ),
+                };
+                steps.push(step);
+                rest = &subscript[end + 1..];
+            } else if rest.is_empty() {
+                return Ok(JsonPath(steps));
+            } else {
+                return Err(err("expected . or [ after path step"));
+            }
+        }
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("$")?;
+        for step in &self.0 {
+            match step {
+                JsonPathStep::Member(key)
+                    if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') =>
+                {
+                    write!(f, ".{}", key)?
+                }
+                JsonPathStep::Member(key) => {
+                    f.write_str(".\"")?;
+                    for c in key.chars() {
+                        match c {
+                            '"' => f.write_str("\\\"")?,
+                            '\\' => f.write_str("\\\\")?,
+                            '\x08' => f.write_str("\\b")?,
+                            '\x0c' => f.write_str("\\f")?,
+                            '\n' => f.write_str("\\n")?,
+                            '\r' => f.write_str("\\r")?,
+                            '\t' => f.write_str("\\t")?,
+                            c => write!(f, "{}", c)?,
+                        }
+                    }
+                    f.write_str("\"")?
+                }
+                JsonPathStep::Index(i) => write!(f, "[{}]", i)?,
+                JsonPathStep::Wildcard => f.write_str("[*]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How [`csv_extract`] splits its input into fields.
+#[derive(
+    Arbitrary,
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    Hash,
+    MzReflect,
+)]
+pub struct CsvExtractOptions {
+    /// The byte that separates fields.
+    pub delimiter: u8,
+    /// The byte that surrounds fields that contain delimiters, quotes or
+    /// newlines.
+    pub quote: u8,
+    /// Whether quotes are interpreted at all. If not, `quote` is treated like
+    /// any other byte.
+    pub quoting: bool,
+}
+
+impl Default for CsvExtractOptions {
+    fn default() -> Self {
+        CsvExtractOptions {
+            delimiter: b',',
+            quote: b'"',
+            quoting: true,
+        }
+    }
+}
+
+impl RustType<ProtoCsvExtractOptions> for CsvExtractOptions {
+    fn into_proto(&self) -> ProtoCsvExtractOptions {
+        ProtoCsvExtractOptions {
+            delimiter: self.delimiter.into_proto(),
+            quote: self.quote.into_proto(),
+            quoting: self.quoting,
+        }
+    }
+
+    fn from_proto(proto: ProtoCsvExtractOptions) -> Result<Self, TryFromProtoError> {
+        Ok(CsvExtractOptions {
+            delimiter: proto.delimiter.into_rust()?,
+            quote: proto.quote.into_rust()?,
+            quoting: proto.quoting,
+        })
+    }
+}
+
+impl fmt::Display for CsvExtractOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "delimiter={:?}", char::from(self.delimiter))?;
+        if self.quoting {
+            write!(f, ", quote={:?}", char::from(self.quote))
+        } else {
+            f.write_str(", quote=none")
+        }
+    }
+}
+
+pub fn csv_extract(
+    a: Datum,
+    n_cols: usize,
+    options: CsvExtractOptions,
+) -> impl Iterator<Item = (Row, Diff)> + '_ {
+    let bytes = a.unwrap_str().as_bytes();
+    let mut row = Row::default();
+    let csv_reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .quoting(options.quoting)
+        .from_reader(bytes);
+    csv_reader.into_records().filter_map(move |res| match res {
+        Ok(sr) if sr.len() == n_cols => {
+            row.packer().extend(sr.iter().map(Datum::String));
+            Some((row.clone(), 1))
+        }
+        _ => None,
+    })
+}
+
+/// Splits each line of `a` into fields of `widths` characters.
+///
+/// Widths are measured in characters rather than bytes, so multibyte
+/// characters are never split. Lines that are shorter than the sum of
+/// `widths` are dropped, mirroring how [`csv_extract`] drops records with
+/// the wrong number of columns; any characters past the sum of `widths` are
+/// ignored. Fields are emitted verbatim, including any padding.
+pub fn fixed_width_extract<'a>(
+    a: Datum<'a>,
+    widths: &'a [usize],
+) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    let mut row = Row::default();
+    let mut fields = Vec::with_capacity(widths.len());
+    a.unwrap_str().lines().filter_map(move |line| {
+        fields.clear();
+        let mut rest = line;
+        for width in widths {
+            // The byte offset at which the `width`-th character ends.
+            let end = rest
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(iter::once(rest.len()))
+                .nth(*width)?;
+            let (field, tail) = rest.split_at(end);
+            fields.push(field);
+            rest = tail;
+        }
+        row.packer()
+            .extend(fields.iter().copied().map(Datum::String));
+        Some((row.clone(), 1))
+    })
+}
+
+/// Emits an empty row with multiplicity `a`.
+///
+/// Negative counts are deliberately passed through as negative diffs: this
+/// is how tests inject retractions and exercise the handling of negative
+/// multiplicities. The SQL function exposing this, `repeat_row`, is only
+/// available in unsafe mode.
+pub fn repeat(a: Datum) -> Option<(Row, Diff)> {
+    let n = a.unwrap_int64();
+    if n != 0 {
+        Some((Row::default(), n))
+    } else {
+        None
+    }
+}
+
+fn wrap<'a>(datums: &'a [Datum<'a>], width: usize) -> impl Iterator<Item = (Row, Diff)> + 'a {
+    datums.chunks(width).map(|chunk| (Row::pack(chunk), 1))
+}
+
+#[derive(
+    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
+)]
+pub enum TableFunc {
+    JsonbEach {
+        stringify: bool,
+    },
+    JsonbObjectKeys,
+    JsonbArrayElements {
+        stringify: bool,
+    },
+    RegexpExtract(AnalyzedRegex),
+    /// Emits one row of capture groups per match of `regex`. Only the first
+    /// match is emitted unless `global` is set. See [`regexp_matches`] for
+    /// details.
+    RegexpMatches {
+        regex: AnalyzedRegex,
+        global: bool,
+    },
+    RegexpSplitToTable(AnalyzedRegex),
+    /// Emits each value selected by the path from its jsonb argument. See
+    /// [`jsonb_path_query`] for details.
+    JsonbPathQuery(JsonPath),
+    /// Expands a jsonb array of objects into rows with the given columns. See
+    /// [`jsonb_to_recordset`] for details. Construct it with
+    /// [`TableFunc::jsonb_to_recordset`], which rejects unsupported column types.
+    JsonbToRecordset {
+        columns: Vec<(ColumnName, ScalarType)>,
+    },
+    /// Splits its first argument on the delimiter given as its second
+    /// argument, optionally replacing fields equal to its third argument with
+    /// NULL. See [`string_to_table`] for details.
+    StringToTable,
+    /// Parses its argument as CSV, yielding the records that have exactly
+    /// `n_cols` fields.
+    CsvExtract {
+        n_cols: usize,
+        options: CsvExtractOptions,
+    },
+    /// Splits each line of the input into fields of the given widths, in
+    /// characters. See [`fixed_width_extract`] for details.
+    FixedWidthExtract {
+        widths: Vec<usize>,
+    },
+    GenerateSeriesInt32,
+    GenerateSeriesInt64,
+    GenerateSeriesNumeric,
+    GenerateSeriesTimestamp,
+    GenerateSeriesTimestampTz,
+    /// Like `GenerateSeriesTimestampTz`, but takes a fourth argument naming
+    /// the time zone in which the month and day components of the step are
+    /// applied. See [`generate_series_ts_tz`] for details.
+    GenerateSeriesTimestampTzInTimezone,
+    /// Emits an empty row with the given, possibly negative, multiplicity.
+    /// See [`repeat`] for details.
+    Repeat,
+    UnnestArray {
+        el_typ: ScalarType,
+        /// Whether to follow each element with its 1-based position, as in
+        /// `unnest(...) WITH ORDINALITY`.
+        ordinality: bool,
+    },
+    UnnestList {
+        el_typ: ScalarType,
+        /// Whether to follow each element with its 1-based position, as in
+        /// `unnest(...) WITH ORDINALITY`.
+        ordinality: bool,
+    },
+    /// Zips its array arguments, whose element types are `el_typs`, into one
+    /// column each, as in `unnest(a, b, ...)`. See [`unnest_arrays`] for
+    /// details.
+    UnnestArrays {
+        el_typs: Vec<ScalarType>,
+    },
+    /// Emits one row per combination of the elements of its array arguments,
+    /// whose element types are `el_typs`, with one column per array. Errors
+    /// rather than emitting more than `max_rows` rows. See [`cross_product`]
+    /// for the order of the rows.
+    CrossProduct {
+        el_typs: Vec<ScalarType>,
+        max_rows: u64,
+    },
+    /// Given `n` input expressions, wraps them into `n / width` rows, each of
+    /// `width` columns.
+    ///
+    /// This function is not intended to be called directly by end users, but
+    /// is useful in the planning of e.g. VALUES clauses.
+    Wrap {
+        types: Vec<ColumnType>,
+        width: usize,
+    },
+    /// Yields the subscripts of an array dimension. An optional third
+    /// argument reverses their order, as in PostgreSQL.
+    GenerateSubscriptsArray,
+    /// Emits the since and upper frontiers, batch count, and encoded size of
+    /// a persist shard, identified either by its own ID or by the ID of the
+    /// storage collection whose data it holds. Reading the shard's state
+    /// requires the persist client held by the coordinator, which replaces
+    /// calls with their results before the query reaches a dataflow; see
+    /// `Coordinator::resolve_shard_metadata`.
+    ShardMetadata {
+        id: String,
+    },
+}
+
+impl RustType<ProtoTableFunc> for TableFunc {
+    fn into_proto(&self) -> ProtoTableFunc {
+        use proto_table_func::Kind;
+        use proto_table_func::{
+            ProtoCrossProduct, ProtoCsvExtract, ProtoFixedWidthExtract, ProtoJsonbToRecordset,
+            ProtoJsonbToRecordsetColumn, ProtoRegexpMatches, ProtoUnnest, ProtoUnnestArrays,
+            ProtoWrap,
+        };
+
+        ProtoTableFunc {
+            kind: Some(match self {
+                TableFunc::JsonbEach { stringify } => Kind::JsonbEach(*stringify),
+                TableFunc::JsonbObjectKeys => Kind::JsonbObjectKeys(()),
+                TableFunc::JsonbArrayElements { stringify } => Kind::JsonbArrayElements(*stringify),
+                TableFunc::RegexpExtract(x) => Kind::RegexpExtract(x.into_proto()),
+                TableFunc::RegexpMatches { regex, global } => {
+                    Kind::RegexpMatches(ProtoRegexpMatches {
+                        regex: Some(regex.into_proto()),
+                        global: *global,
+                    })
+                }
+                TableFunc::RegexpSplitToTable(x) => Kind::RegexpSplitToTable(x.into_proto()),
+                TableFunc::JsonbPathQuery(path) => Kind::JsonbPathQuery(path.into_proto()),
+                TableFunc::JsonbToRecordset { columns } => {
+                    Kind::JsonbToRecordset(ProtoJsonbToRecordset {
+                        columns: columns
+                            .iter()
+                            .map(|(name, typ)| ProtoJsonbToRecordsetColumn {
+                                name: Some(name.into_proto()),
+                                typ: Some(typ.into_proto()),
+                            })
+                            .collect(),
+                    })
+                }
+                TableFunc::StringToTable => Kind::StringToTable(()),
+                TableFunc::CsvExtract { n_cols, options } => Kind::CsvExtract(ProtoCsvExtract {
+                    n_cols: n_cols.into_proto(),
+                    options: Some(options.into_proto()),
+                }),
+                TableFunc::FixedWidthExtract { widths } => {
+                    Kind::FixedWidthExtract(ProtoFixedWidthExtract {
+                        widths: widths.into_proto(),
+                    })
+                }
+                TableFunc::GenerateSeriesInt32 => Kind::GenerateSeriesInt32(()),
+                TableFunc::GenerateSeriesInt64 => Kind::GenerateSeriesInt64(()),
+                TableFunc::GenerateSeriesNumeric => Kind::GenerateSeriesNumeric(()),
+                TableFunc::GenerateSeriesTimestamp => Kind::GenerateSeriesTimestamp(()),
+                TableFunc::GenerateSeriesTimestampTz => Kind::GenerateSeriesTimestampTz(()),
+                TableFunc::GenerateSeriesTimestampTzInTimezone => {
+                    Kind::GenerateSeriesTimestampTzInTimezone(())
+                }
+                TableFunc::Repeat => Kind::Repeat(()),
+                TableFunc::UnnestArray { el_typ, ordinality } => Kind::UnnestArray(ProtoUnnest {
+                    el_typ: Some(el_typ.into_proto()),
+                    ordinality: *ordinality,
+                }),
+                TableFunc::UnnestList { el_typ, ordinality } => Kind::UnnestList(ProtoUnnest {
+                    el_typ: Some(el_typ.into_proto()),
+                    ordinality: *ordinality,
+                }),
+                TableFunc::UnnestArrays { el_typs } => Kind::UnnestArrays(ProtoUnnestArrays {
+                    el_typs: el_typs.into_proto(),
+                }),
+                TableFunc::CrossProduct { el_typs, max_rows } => {
+                    Kind::CrossProduct(ProtoCrossProduct {
+                        el_typs: el_typs.into_proto(),
+                        max_rows: *max_rows,
+                    })
+                }
+                TableFunc::Wrap { types, width } => Kind::Wrap(ProtoWrap {
+                    types: types.into_proto(),
+                    width: width.into_proto(),
+                }),
+                TableFunc::GenerateSubscriptsArray => Kind::GenerateSubscriptsArray(()),
+                TableFunc::ShardMetadata { id } => Kind::ShardMetadata(id.clone()),
+            }),
+        }
+    }
+
+    fn from_proto(proto: ProtoTableFunc) -> Result<Self, TryFromProtoError> {
+        use proto_table_func::Kind;
+
+        let kind = proto
+            .kind
+            .ok_or_else(|| TryFromProtoError::missing_field("ProtoTableFunc::Kind"))?;
+
+        Ok(match kind {
+            Kind::JsonbEach(stringify) => TableFunc::JsonbEach { stringify },
+            Kind::JsonbObjectKeys(()) => TableFunc::JsonbObjectKeys,
+            Kind::JsonbArrayElements(stringify) => TableFunc::JsonbArrayElements { stringify },
+            Kind::RegexpExtract(x) => TableFunc::RegexpExtract(x.into_rust()?),
+            Kind::RegexpMatches(x) => TableFunc::RegexpMatches {
+                regex: x.regex.into_rust_if_some("ProtoRegexpMatches::regex")?,
+                global: x.global,
+            },
+            Kind::RegexpSplitToTable(x) => TableFunc::RegexpSplitToTable(x.into_rust()?),
+            Kind::JsonbPathQuery(path) => TableFunc::JsonbPathQuery(path.into_rust()?),
+            Kind::JsonbToRecordset(x) => TableFunc::JsonbToRecordset {
+                columns: x
+                    .columns
+                    .into_iter()
+                    .map(|column| {
+                        Ok((
+                            column
+                                .name
+                                .into_rust_if_some("ProtoJsonbToRecordsetColumn::name")?,
+                            column
+                                .typ
+                                .into_rust_if_some("ProtoJsonbToRecordsetColumn::typ")?,
+                        ))
+                    })
+                    .collect::<Result<_, TryFromProtoError>>()?,
+            },
+            Kind::StringToTable(()) => TableFunc::StringToTable,
+            Kind::CsvExtract(x) => TableFunc::CsvExtract {
+                n_cols: x.n_cols.into_rust()?,
+                options: x.options.into_rust_if_some("ProtoCsvExtract::options")?,
+            },
+            Kind::FixedWidthExtract(x) => TableFunc::FixedWidthExtract {
+                widths: x.widths.into_rust()?,
+            },
+            Kind::GenerateSeriesInt32(()) => TableFunc::GenerateSeriesInt32,
+            Kind::GenerateSeriesInt64(()) => TableFunc::GenerateSeriesInt64,
+            Kind::GenerateSeriesNumeric(()) => TableFunc::GenerateSeriesNumeric,
+            Kind::GenerateSeriesTimestamp(()) => TableFunc::GenerateSeriesTimestamp,
+            Kind::GenerateSeriesTimestampTz(()) => TableFunc::GenerateSeriesTimestampTz,
+            Kind::GenerateSeriesTimestampTzInTimezone(()) => {
+                TableFunc::GenerateSeriesTimestampTzInTimezone
+            }
+            Kind::Repeat(()) => TableFunc::Repeat,
+            Kind::UnnestArray(x) => TableFunc::UnnestArray {
+                el_typ: x.el_typ.into_rust_if_some("ProtoUnnest::el_typ")?,
+                ordinality: x.ordinality,
+            },
+            Kind::UnnestList(x) => TableFunc::UnnestList {
+                el_typ: x.el_typ.into_rust_if_some("ProtoUnnest::el_typ")?,
+                ordinality: x.ordinality,
+            },
+            Kind::UnnestArrays(x) => TableFunc::UnnestArrays {
+                el_typs: x.el_typs.into_rust()?,
+            },
+            Kind::CrossProduct(x) => TableFunc::CrossProduct {
+                el_typs: x.el_typs.into_rust()?,
+                max_rows: x.max_rows,
+            },
+            Kind::Wrap(x) => TableFunc::Wrap {
+                width: x.width.into_rust()?,
+                types: x.types.into_rust()?,
+            },
+            Kind::GenerateSubscriptsArray(()) => TableFunc::GenerateSubscriptsArray,
+            Kind::ShardMetadata(id) => TableFunc::ShardMetadata { id },
+        })
+    }
+}
+
+impl TableFunc {
+    /// Constructs a [`TableFunc::JsonbToRecordset`] with the given columns,
+    /// rejecting column types that jsonb values cannot be converted to.
+    pub fn jsonb_to_recordset(
+        columns: Vec<(ColumnName, ScalarType)>,
+    ) -> Result<TableFunc, EvalError> {
+        if let Some((name, typ)) = columns
+            .iter()
+            .find(|(_, typ)| !jsonb_to_scalar_supported(typ))
+        {
+            return Err(EvalError::Unsupported {
+                feature: format!(
+                    "jsonb_to_recordset column {} of type {:?}",
+                    name.as_str(),
+                    typ
+                ),
+                issue_no: None,
+            });
+        }
+        Ok(TableFunc::JsonbToRecordset { columns })
+    }
+
+    pub fn eval<'a>(
+        &'a self,
+        datums: &'a [Datum<'a>],
+        temp_storage: &'a RowArena,
+    ) -> Result<Box<dyn Iterator<Item = (Row, Diff)> + 'a>, EvalError> {
+        if self.empty_on_null_input() && datums.iter().any(|d| d.is_null()) {
+            return Ok(Box::new(vec![].into_iter()));
+        }
+        match self {
+            TableFunc::JsonbEach { stringify } => {
+                Ok(Box::new(jsonb_each(datums[0], temp_storage, *stringify)))
+            }
+            TableFunc::JsonbObjectKeys => Ok(Box::new(jsonb_object_keys(datums[0]))),
+            TableFunc::JsonbPathQuery(path) => Ok(Box::new(jsonb_path_query(datums[0], path))),
+            TableFunc::JsonbToRecordset { columns } => {
+                Ok(Box::new(jsonb_to_recordset(datums[0], columns)))
+            }
+            TableFunc::JsonbArrayElements { stringify } => Ok(Box::new(jsonb_array_elements(
+                datums[0],
+                temp_storage,
+                *stringify,
+            ))),
+            TableFunc::RegexpExtract(a) => Ok(Box::new(regexp_extract(datums[0], a).into_iter())),
+            TableFunc::RegexpMatches { regex, global } => {
+                Ok(Box::new(regexp_matches(datums[0], regex, *global)))
+            }
+            TableFunc::RegexpSplitToTable(a) => Ok(Box::new(regexp_split_to_table(datums[0], a))),
+            TableFunc::StringToTable => {
+                let null_string = datums.get(2).copied().unwrap_or(Datum::Null);
+                Ok(string_to_table(datums[0], datums[1], null_string))
+            }
+            TableFunc::CsvExtract { n_cols, options } => {
+                Ok(Box::new(csv_extract(datums[0], *n_cols, *options)))
+            }
+            TableFunc::FixedWidthExtract { widths } => {
+                Ok(Box::new(fixed_width_extract(datums[0], widths)))
+            }
+            TableFunc::GenerateSeriesInt32 => {
+                let res = generate_series(
+                    datums[0].unwrap_int32(),
+                    datums[1].unwrap_int32(),
+                    datums.get(2).map_or(1, Datum::unwrap_int32),
+                )?;
+                Ok(Box::new(res))
+            }
+            TableFunc::GenerateSeriesInt64 => {
+                let res = generate_series(
+                    datums[0].unwrap_int64(),
+                    datums[1].unwrap_int64(),
+                    datums.get(2).map_or(1, Datum::unwrap_int64),
+                )?;
+                Ok(Box::new(res))
+            }
+            TableFunc::GenerateSeriesNumeric => {
+                let res = generate_series_numeric(
+                    datums[0].unwrap_numeric().0,
+                    datums[1].unwrap_numeric().0,
+                    datums[2].unwrap_numeric().0,
+                )?;
+                Ok(Box::new(res))
+            }
+            TableFunc::GenerateSeriesTimestamp => {
+                fn pass_through<'a>(d: CheckedTimestamp<NaiveDateTime>) -> Datum<'a> {
+                    Datum::from(d)
+                }
+                let res = generate_series_ts(
+                    datums[0].unwrap_timestamp(),
+                    datums[1].unwrap_timestamp(),
+                    datums[2].unwrap_interval(),
+                    pass_through,
+                )?;
+                Ok(Box::new(res))
+            }
+            TableFunc::GenerateSeriesTimestampTz => {
+                fn gen_ts_tz<'a>(d: CheckedTimestamp<DateTime<Utc>>) -> Datum<'a> {
+                    Datum::from(d)
+                }
+                let res = generate_series_ts(
+                    datums[0].unwrap_timestamptz(),
+                    datums[1].unwrap_timestamptz(),
+                    datums[2].unwrap_interval(),
+                    gen_ts_tz,
+                )?;
+                Ok(Box::new(res))
+            }
+            TableFunc::GenerateSeriesTimestampTzInTimezone => {
+                let tz = parse_timezone(datums[3].unwrap_str())?;
+                let res = generate_series_ts_tz(
+                    datums[0].unwrap_timestamptz(),
+                    datums[1].unwrap_timestamptz(),
+                    datums[2].unwrap_interval(),
+                    tz,
+                )?;
+                Ok(Box::new(res))
+            }
+            TableFunc::GenerateSubscriptsArray => generate_subscripts_array(
+                datums[0],
+                datums[1].unwrap_int32(),
+                datums.get(2).map_or(false, |reverse| reverse.unwrap_bool()),
+            ),
+            TableFunc::Repeat => Ok(Box::new(repeat(datums[0]).into_iter())),
+            TableFunc::UnnestArray { ordinality, .. } => {
+                Ok(Box::new(unnest_array(datums[0], *ordinality)?))
+            }
+            TableFunc::UnnestList { ordinality, .. } => {
+                Ok(Box::new(unnest_list(datums[0], *ordinality)?))
+            }
+            TableFunc::UnnestArrays { .. } => Ok(Box::new(unnest_arrays(datums)?)),
+            TableFunc::CrossProduct { max_rows, .. } => {
+                Ok(Box::new(cross_product(datums, *max_rows)?))
+            }
+            TableFunc::Wrap { width, .. } => Ok(Box::new(wrap(datums, *width))),
+            TableFunc::ShardMetadata { .. } => Err(EvalError::Internal(
+                "mz_shard_metadata must be evaluated by the coordinator".into(),
+            )),
+        }
+    }
+
+    pub fn output_type(&self) -> RelationType {
+        let (column_types, keys) = match self {
+            TableFunc::JsonbEach { stringify: true } => {
+                let column_types = vec![
+                    ScalarType::String.nullable(false),
+                    ScalarType::String.nullable(true),
+                ];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::JsonbEach { stringify: false } => {
+                let column_types = vec![
+                    ScalarType::String.nullable(false),
+                    ScalarType::Jsonb.nullable(false),
+                ];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::JsonbObjectKeys => {
+                let column_types = vec![ScalarType::String.nullable(false)];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::JsonbPathQuery(_) => {
+                let column_types = vec![ScalarType::Jsonb.nullable(true)];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::JsonbToRecordset { columns } => {
+                let column_types = columns
+                    .iter()
+                    .map(|(_, typ)| typ.clone().nullable(true))
+                    .collect();
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::JsonbArrayElements { stringify: true } => {
+                let column_types = vec![ScalarType::String.nullable(true)];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::JsonbArrayElements { stringify: false } => {
+                let column_types = vec![ScalarType::Jsonb.nullable(false)];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::RegexpExtract(a) | TableFunc::RegexpMatches { regex: a, .. } => {
+                let column_types = a
+                    .capture_groups_iter()
+                    .map(|cg| ScalarType::String.nullable(cg.nullable))
+                    .collect();
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::RegexpSplitToTable(_) => {
+                let column_types = vec![ScalarType::String.nullable(false)];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::StringToTable => {
+                let column_types = vec![ScalarType::String.nullable(true)];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::CsvExtract { n_cols, .. } => {
+                let column_types = iter::repeat(ScalarType::String.nullable(false))
+                    .take(*n_cols)
+                    .collect();
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::FixedWidthExtract { widths } => {
+                let column_types = iter::repeat(ScalarType::String.nullable(false))
+                    .take(widths.len())
+                    .collect();
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::GenerateSeriesInt32 => {
+                let column_types = vec![ScalarType::Int32.nullable(false)];
+                let keys = vec![vec![0]];
+                (column_types, keys)
+            }
+            TableFunc::GenerateSeriesInt64 => {
+                let column_types = vec![ScalarType::Int64.nullable(false)];
+                let keys = vec![vec![0]];
+                (column_types, keys)
+            }
+            TableFunc::GenerateSeriesNumeric => {
+                let column_types = vec![ScalarType::Numeric { max_scale: None }.nullable(false)];
+                let keys = vec![vec![0]];
+                (column_types, keys)
+            }
+            TableFunc::GenerateSeriesTimestamp => {
+                let column_types = vec![ScalarType::Timestamp.nullable(false)];
+                let keys = vec![vec![0]];
+                (column_types, keys)
+            }
+            TableFunc::GenerateSeriesTimestampTz
+            | TableFunc::GenerateSeriesTimestampTzInTimezone => {
+                let column_types = vec![ScalarType::TimestampTz.nullable(false)];
+                let keys = vec![vec![0]];
+                (column_types, keys)
+            }
+            TableFunc::GenerateSubscriptsArray => {
+                let column_types = vec![ScalarType::Int32.nullable(false)];
+                let keys = vec![vec![0]];
+                (column_types, keys)
+            }
+            TableFunc::Repeat => {
+                let column_types = vec![];
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::UnnestArray { el_typ, ordinality }
+            | TableFunc::UnnestList { el_typ, ordinality } => {
+                let mut column_types = vec![el_typ.clone().nullable(true)];
+                if *ordinality {
+                    column_types.push(ScalarType::Int64.nullable(false));
+                }
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::UnnestArrays { el_typs } | TableFunc::CrossProduct { el_typs, .. } => {
+                let column_types = el_typs
+                    .iter()
+                    .map(|el_typ| el_typ.clone().nullable(true))
+                    .collect();
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::Wrap { types, .. } => {
+                let column_types = types.clone();
+                let keys = vec![];
+                (column_types, keys)
+            }
+            TableFunc::ShardMetadata { .. } => {
+                let column_types = vec![
+                    ScalarType::MzTimestamp.nullable(true),
+                    ScalarType::MzTimestamp.nullable(true),
+                    ScalarType::UInt64.nullable(false),
+                    ScalarType::UInt64.nullable(false),
+                ];
+                let keys = vec![];
+                (column_types, keys)
+            }
+        };
+
+        if !keys.is_empty() {
+            RelationType::new(column_types).with_keys(keys)
+        } else {
+            RelationType::new(column_types)
         }
     }
 
-    /// Returns the output of the aggregation function when applied on an empty
-    /// input relation.
-    pub fn default(&self) -> Datum<'static> {
+    pub fn output_arity(&self) -> usize {
         match self {
-            AggregateFunc::Count => Datum::Int64(0),
-            AggregateFunc::Any => Datum::False,
-            AggregateFunc::All => Datum::True,
-            AggregateFunc::Dummy => Datum::Dummy,
-            _ => Datum::Null,
+            TableFunc::JsonbEach { .. } => 2,
+            TableFunc::JsonbObjectKeys => 1,
+            TableFunc::JsonbArrayElements { .. } => 1,
+            TableFunc::RegexpExtract(a) => a.capture_groups_len(),
+            TableFunc::RegexpMatches { regex, .. } => regex.capture_groups_len(),
+            TableFunc::RegexpSplitToTable(_) => 1,
+            TableFunc::JsonbPathQuery(_) => 1,
+            TableFunc::JsonbToRecordset { columns } => columns.len(),
+            TableFunc::StringToTable => 1,
+            TableFunc::CsvExtract { n_cols, .. } => *n_cols,
+            TableFunc::FixedWidthExtract { widths } => widths.len(),
+            TableFunc::GenerateSeriesInt32 => 1,
+            TableFunc::GenerateSeriesInt64 => 1,
+            TableFunc::GenerateSeriesNumeric => 1,
+            TableFunc::GenerateSeriesTimestamp => 1,
+            TableFunc::GenerateSeriesTimestampTz => 1,
+            TableFunc::GenerateSeriesTimestampTzInTimezone => 1,
+            TableFunc::GenerateSubscriptsArray => 1,
+            TableFunc::Repeat => 0,
+            TableFunc::UnnestArray { ordinality, .. } => 1 + usize::from(*ordinality),
+            TableFunc::UnnestList { ordinality, .. } => 1 + usize::from(*ordinality),
+            TableFunc::UnnestArrays { el_typs } => el_typs.len(),
+            TableFunc::CrossProduct { el_typs, .. } => el_typs.len(),
+            TableFunc::Wrap { width, .. } => *width,
+            TableFunc::ShardMetadata { .. } => 4,
         }
     }
 
-    /// Returns a datum whose inclusion in the aggregation will not change its
-    /// result.
-    pub fn identity_datum(&self) -> Datum<'static> {
+    pub fn empty_on_null_input(&self) -> bool {
         match self {
-            AggregateFunc::Any => Datum::False,
-            AggregateFunc::All => Datum::True,
-            AggregateFunc::Dummy => Datum::Dummy,
-            AggregateFunc::ArrayConcat { .. } => Datum::empty_array(),
-            AggregateFunc::ListConcat { .. } => Datum::empty_list(),
-            AggregateFunc::RowNumber { .. } => Datum::empty_list(),
-            AggregateFunc::DenseRank { .. } => Datum::empty_list(),
-            AggregateFunc::LagLead { .. } => Datum::empty_list(),
-            AggregateFunc::FirstValue { .. } => Datum::empty_list(),
-            AggregateFunc::LastValue { .. } => Datum::empty_list(),
-            _ => Datum::Null,
+            TableFunc::JsonbEach { .. }
+            | TableFunc::JsonbObjectKeys
+            | TableFunc::JsonbArrayElements { .. }
+            | TableFunc::JsonbPathQuery(_)
+            | TableFunc::JsonbToRecordset { .. }
+            | TableFunc::GenerateSeriesInt32
+            | TableFunc::GenerateSeriesInt64
+            | TableFunc::GenerateSeriesNumeric
+            | TableFunc::GenerateSeriesTimestamp
+            | TableFunc::GenerateSeriesTimestampTz
+            | TableFunc::GenerateSeriesTimestampTzInTimezone
+            | TableFunc::GenerateSubscriptsArray
+            | TableFunc::RegexpExtract(_)
+            | TableFunc::RegexpMatches { .. }
+            | TableFunc::RegexpSplitToTable(_)
+            | TableFunc::CsvExtract { .. }
+            | TableFunc::FixedWidthExtract { .. }
+            | TableFunc::Repeat
+            | TableFunc::UnnestArray { .. }
+            | TableFunc::UnnestList { .. }
+            | TableFunc::CrossProduct { .. }
+            | TableFunc::ShardMetadata { .. } => true,
+            // A NULL delimiter is meaningful, and a NULL array only ends its
+            // own column, so NULL inputs must reach `eval`.
+            TableFunc::StringToTable | TableFunc::UnnestArrays { .. } | TableFunc::Wrap { .. } => {
+                false
+            }
         }
     }
 
-    /// The output column type for the result of an aggregation.
-    ///
-    /// The output column type also contains nullability information, which
-    /// is (without further information) true for aggregations that are not
-    /// counts.
-    pub fn output_type(&self, input_type: ColumnType) -> ColumnType {
-        let scalar_type = match self {
-            AggregateFunc::Count => ScalarType::Int64,
-            AggregateFunc::Any => ScalarType::Bool,
-            AggregateFunc::All => ScalarType::Bool,
-            AggregateFunc::JsonbAgg { .. } => ScalarType::Jsonb,
-            AggregateFunc::JsonbObjectAgg { .. } => ScalarType::Jsonb,
-            AggregateFunc::SumInt16 => ScalarType::Int64,
-            AggregateFunc::SumInt32 => ScalarType::Int64,
-            AggregateFunc::SumInt64 => ScalarType::Numeric {
-                max_scale: Some(NumericMaxScale::ZERO),
-            },
-            AggregateFunc::SumUInt16 => ScalarType::UInt64,
-            AggregateFunc::SumUInt32 => ScalarType::UInt64,
-            AggregateFunc::SumUInt64 => ScalarType::Numeric {
-                max_scale: Some(NumericMaxScale::ZERO),
-            },
-            AggregateFunc::ArrayConcat { .. } | AggregateFunc::ListConcat { .. } => {
-                match input_type.scalar_type {
-                    // The input is wrapped in a Record if there's an ORDER BY, so extract it out.
-                    ScalarType::Record { ref fields, .. } => fields[0].1.scalar_type.clone(),
-                    _ => unreachable!(),
-                }
-            }
-            AggregateFunc::StringAgg { .. } => ScalarType::String,
-            AggregateFunc::RowNumber { .. } => match input_type.scalar_type {
-                ScalarType::Record { ref fields, .. } => ScalarType::List {
-                    element_type: Box::new(ScalarType::Record {
-                        fields: vec![
-                            (
-                                ColumnName::from("?row_number?"),
-                                ScalarType::Int64.nullable(false),
-                            ),
-                            (ColumnName::from("?record?"), {
-                                let inner = match &fields[0].1.scalar_type {
-                                    ScalarType::List { element_type, .. } => element_type.clone(),
-                                    _ => unreachable!(),
-                                };
-                                inner.nullable(false)
-                            }),
-                        ],
-                        custom_id: None,
-                    }),
-                    custom_id: None,
-                },
-                _ => unreachable!(),
-            },
-            AggregateFunc::DenseRank { .. } => match input_type.scalar_type {
-                ScalarType::Record { ref fields, .. } => ScalarType::List {
-                    element_type: Box::new(ScalarType::Record {
-                        fields: vec![
-                            (
-                                ColumnName::from("?dense_rank?"),
-                                ScalarType::Int64.nullable(false),
-                            ),
-                            (ColumnName::from("?record?"), {
-                                let inner = match &fields[0].1.scalar_type {
-                                    ScalarType::List { element_type, .. } => element_type.clone(),
-                                    _ => unreachable!(),
-                                };
-                                inner.nullable(false)
-                            }),
-                        ],
-                        custom_id: None,
-                    }),
-                    custom_id: None,
-                },
-                _ => unreachable!(),
-            },
-            AggregateFunc::LagLead { lag_lead, .. } => {
-                // The input type for Lag is a ((OriginalRow, EncodedArgs), OrderByExprs...)
-                let fields = input_type.scalar_type.unwrap_record_element_type();
-                let original_row_type = fields[0].unwrap_record_element_type()[0]
-                    .clone()
-                    .nullable(false);
-                let value_type = fields[0].unwrap_record_element_type()[1]
-                    .unwrap_record_element_type()[0]
-                    .clone()
-                    .nullable(true);
-                let column_name = match lag_lead {
-                    LagLeadType::Lag => "?lag?",
-                    LagLeadType::Lead => "?lead?",
-                };
+    /// True iff the table function preserves the append-only property of its input.
+    pub fn preserves_monotonicity(&self) -> bool {
+        // Most variants preserve monotonicity, but all variants are enumerated to
+        // ensure that added variants at least check that this is the case.
+        match self {
+            TableFunc::JsonbEach { .. } => true,
+            TableFunc::JsonbObjectKeys => true,
+            TableFunc::JsonbPathQuery(_) => true,
+            TableFunc::JsonbToRecordset { .. } => true,
+            TableFunc::JsonbArrayElements { .. } => true,
+            TableFunc::RegexpExtract(_) => true,
+            TableFunc::RegexpMatches { .. } => true,
+            TableFunc::RegexpSplitToTable(_) => true,
+            TableFunc::StringToTable => true,
+            TableFunc::CsvExtract { .. } => true,
+            TableFunc::FixedWidthExtract { .. } => true,
+            TableFunc::GenerateSeriesInt32 => true,
+            TableFunc::GenerateSeriesInt64 => true,
+            TableFunc::GenerateSeriesNumeric => true,
+            TableFunc::GenerateSeriesTimestamp => true,
+            TableFunc::GenerateSeriesTimestampTz => true,
+            TableFunc::GenerateSeriesTimestampTzInTimezone => true,
+            TableFunc::GenerateSubscriptsArray => true,
+            TableFunc::Repeat => false,
+            TableFunc::UnnestArray { .. } => true,
+            TableFunc::UnnestList { .. } => true,
+            TableFunc::UnnestArrays { .. } => true,
+            TableFunc::CrossProduct { .. } => true,
+            TableFunc::Wrap { .. } => true,
+            TableFunc::ShardMetadata { .. } => true,
+        }
+    }
+}
 
-                ScalarType::List {
-                    element_type: Box::new(ScalarType::Record {
-                        fields: vec![
-                            (ColumnName::from(column_name), value_type),
-                            (ColumnName::from("?record?"), original_row_type),
-                        ],
-                        custom_id: None,
-                    }),
-                    custom_id: None,
+impl fmt::Display for TableFunc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableFunc::JsonbEach { stringify: false } => f.write_str("jsonb_each"),
+            TableFunc::JsonbEach { stringify: true } => f.write_str("jsonb_each_text"),
+            TableFunc::JsonbObjectKeys => f.write_str("jsonb_object_keys"),
+            TableFunc::JsonbPathQuery(path) => {
+                write!(f, "jsonb_path_query(_, {:?})", path.to_string())
+            }
+            TableFunc::JsonbToRecordset { .. } => f.write_str("jsonb_to_recordset"),
+            TableFunc::JsonbArrayElements { stringify: false } => {
+                f.write_str("jsonb_array_elements")
+            }
+            TableFunc::JsonbArrayElements { stringify: true } => {
+                f.write_str("jsonb_array_elements_text")
+            }
+            TableFunc::RegexpExtract(a) => write!(f, "regexp_extract({:?}, _)", a.0),
+            TableFunc::RegexpMatches { regex, global } => {
+                write!(f, "regexp_matches({:?}, _, global={})", regex.0, global)
+            }
+            TableFunc::RegexpSplitToTable(a) => write!(f, "regexp_split_to_table(_, {:?})", a.0),
+            TableFunc::StringToTable => f.write_str("string_to_table"),
+            TableFunc::CsvExtract { n_cols, options } => {
+                if *options == CsvExtractOptions::default() {
+                    write!(f, "csv_extract({}, _)", n_cols)
+                } else {
+                    write!(f, "csv_extract({}, _, {})", n_cols, options)
                 }
             }
-            AggregateFunc::FirstValue { .. } => {
-                // The input type for FirstValue is ((OriginalRow, EncodedArgs), OrderByExprs...)
-                let fields = input_type.scalar_type.unwrap_record_element_type();
-                let original_row_type = fields[0].unwrap_record_element_type()[0]
-                    .clone()
-                    .nullable(false);
-                let value_type = fields[0].unwrap_record_element_type()[1]
-                    .clone()
-                    .nullable(true);
-
-                ScalarType::List {
-                    element_type: Box::new(ScalarType::Record {
-                        fields: vec![
-                            (ColumnName::from("?first_value?"), value_type),
-                            (ColumnName::from("?record?"), original_row_type),
-                        ],
-                        custom_id: None,
-                    }),
-                    custom_id: None,
+            TableFunc::FixedWidthExtract { widths } => {
+                write!(f, "fixed_width_extract({:?}, _)", widths)
+            }
+            TableFunc::GenerateSeriesInt32 => f.write_str("generate_series"),
+            TableFunc::GenerateSeriesInt64 => f.write_str("generate_series"),
+            TableFunc::GenerateSeriesNumeric => f.write_str("generate_series"),
+            TableFunc::GenerateSeriesTimestamp => f.write_str("generate_series"),
+            TableFunc::GenerateSeriesTimestampTz => f.write_str("generate_series"),
+            TableFunc::GenerateSeriesTimestampTzInTimezone => f.write_str("generate_series"),
+            TableFunc::GenerateSubscriptsArray => f.write_str("generate_subscripts"),
+            TableFunc::Repeat => f.write_str("repeat_row"),
+            TableFunc::UnnestArray { ordinality, .. } => {
+                f.write_str("unnest_array")?;
+                if *ordinality {
+                    f.write_str(" with ordinality")?;
                 }
+                Ok(())
             }
-            AggregateFunc::LastValue { .. } => {
-                // The input type for LastValue is ((OriginalRow, EncodedArgs), OrderByExprs...)
-                let fields = input_type.scalar_type.unwrap_record_element_type();
-                let original_row_type = fields[0].unwrap_record_element_type()[0]
-                    .clone()
-                    .nullable(false);
-                let value_type = fields[0].unwrap_record_element_type()[1]
-                    .clone()
-                    .nullable(true);
-
-                ScalarType::List {
-                    element_type: Box::new(ScalarType::Record {
-                        fields: vec![
-                            (ColumnName::from("?last_value?"), value_type),
-                            (ColumnName::from("?record?"), original_row_type),
-                        ],
-                        custom_id: None,
-                    }),
-                    custom_id: None,
+            TableFunc::UnnestList { ordinality, .. } => {
+                f.write_str("unnest_list")?;
+                if *ordinality {
+                    f.write_str(" with ordinality")?;
                 }
+                Ok(())
             }
-            // Note AggregateFunc::MaxString, MinString rely on returning input
-            // type as output type to support the proper return type for
-            // character input.
-            _ => input_type.scalar_type.clone(),
-        };
-        // Count never produces null, and other aggregations only produce
-        // null in the presence of null inputs.
-        let nullable = match self {
-            AggregateFunc::Count => false,
-            // Use the nullability of the underlying column being aggregated, not the Records wrapping it
-            AggregateFunc::StringAgg { .. } => match input_type.scalar_type {
-                // The outer Record wraps the input in the first position, and any ORDER BY expressions afterwards
-                ScalarType::Record { fields, .. } => match &fields[0].1.scalar_type {
-                    // The inner Record is a (value, separator) tuple
-                    ScalarType::Record { fields, .. } => fields[0].1.nullable,
-                    _ => unreachable!(),
-                },
-                _ => unreachable!(),
-            },
-            _ => input_type.nullable,
+            TableFunc::UnnestArrays { .. } => f.write_str("unnest_arrays"),
+            TableFunc::CrossProduct { .. } => f.write_str("cross_product"),
+            TableFunc::Wrap { width, .. } => write!(f, "wrap{}", width),
+            TableFunc::ShardMetadata { id } => write!(f, "mz_shard_metadata({:?})", id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+    use std::str::FromStr;
+
+    use chrono::{DateTime, NaiveTime, Utc};
+    use itertools::Itertools;
+    use mz_ore::cast::CastLossy;
+    use mz_proto::protobuf_roundtrip;
+    use mz_repr::adt::array::ArrayDimension;
+    use mz_repr::adt::char::CharLength;
+    use mz_repr::adt::date::Date;
+    use mz_repr::adt::interval::Interval;
+    use mz_repr::adt::jsonb::{Jsonb, JsonbRef};
+    use mz_repr::adt::numeric::{self, Numeric, NumericMaxScale};
+    use mz_repr::adt::timestamp::CheckedTimestamp;
+    use mz_repr::adt::varchar::VarCharMaxLength;
+    use mz_repr::{ColumnName, Datum, Row, RowArena, ScalarType};
+    use proptest::prelude::*;
+    use uuid::Uuid;
+
+    use super::{
+        add_int64_chunk, checked_sum_numeric, csv_extract, fixed_width_extract,
+        generate_series_ts_tz, order_aggregate_datums, order_aggregate_datums_with_rank,
+        parse_timezone, AggregateFunc, AnalyzedRegex, CsvExtractOptions, JsonPath, LagLeadType,
+        ProtoAggregateFunc, ProtoTableFunc, TableFunc, PRIMITIVE_CHUNK_SIZE,
+    };
+    use crate::{ColumnOrder, EvalError, WindowFrame, WindowFrameBound, WindowFrameUnits};
+
+    proptest! {
+       #[test]
+        fn aggregate_func_protobuf_roundtrip(expect in any::<AggregateFunc>() ) {
+            let actual = protobuf_roundtrip::<_, ProtoAggregateFunc>(&expect);
+            assert!(actual.is_ok());
+            assert_eq!(actual.unwrap(), expect);
+        }
+    }
+
+    proptest! {
+       #[test]
+        fn table_func_protobuf_roundtrip(expect in any::<TableFunc>() ) {
+            let actual = protobuf_roundtrip::<_, ProtoTableFunc>(&expect);
+            assert!(actual.is_ok());
+            assert_eq!(actual.unwrap(), expect);
+        }
+    }
+
+    fn int32_array<'a>(arena: &'a RowArena, elems: &[i32]) -> Datum<'a> {
+        let dims = ArrayDimension {
+            lower_bound: 1,
+            length: elems.len(),
         };
-        scalar_type.nullable(nullable)
+        arena.make_datum(|packer| {
+            packer
+                .push_array(&[dims], elems.iter().map(|e| Datum::Int32(*e)))
+                .unwrap();
+        })
     }
 
-    /// Returns true if the non-null constraint on the aggregation can be
-    /// converted into a non-null constraint on its parameter expression, ie.
-    /// whether the result of the aggregation is null if all the input values
-    /// are null.
-    pub fn propagates_nonnull_constraint(&self) -> bool {
-        match self {
-            AggregateFunc::MaxNumeric
-            | AggregateFunc::MaxInt16
-            | AggregateFunc::MaxInt32
-            | AggregateFunc::MaxInt64
-            | AggregateFunc::MaxUInt16
-            | AggregateFunc::MaxUInt32
-            | AggregateFunc::MaxUInt64
-            | AggregateFunc::MaxMzTimestamp
-            | AggregateFunc::MaxFloat32
-            | AggregateFunc::MaxFloat64
-            | AggregateFunc::MaxBool
-            | AggregateFunc::MaxString
-            | AggregateFunc::MaxDate
-            | AggregateFunc::MaxTimestamp
-            | AggregateFunc::MaxTimestampTz
-            | AggregateFunc::MinNumeric
-            | AggregateFunc::MinInt16
-            | AggregateFunc::MinInt32
-            | AggregateFunc::MinInt64
-            | AggregateFunc::MinUInt16
-            | AggregateFunc::MinUInt32
-            | AggregateFunc::MinUInt64
-            | AggregateFunc::MinMzTimestamp
-            | AggregateFunc::MinFloat32
-            | AggregateFunc::MinFloat64
-            | AggregateFunc::MinBool
-            | AggregateFunc::MinString
-            | AggregateFunc::MinDate
-            | AggregateFunc::MinTimestamp
-            | AggregateFunc::MinTimestampTz
-            | AggregateFunc::SumInt16
-            | AggregateFunc::SumInt32
-            | AggregateFunc::SumInt64
-            | AggregateFunc::SumUInt16
-            | AggregateFunc::SumUInt32
-            | AggregateFunc::SumUInt64
-            | AggregateFunc::SumFloat32
-            | AggregateFunc::SumFloat64
-            | AggregateFunc::SumNumeric
-            | AggregateFunc::StringAgg { .. } => true,
-            // Count is never null
-            AggregateFunc::Count => false,
-            _ => false,
+    #[test]
+    fn array_list_concat_keep_null_elements() {
+        let arena = RowArena::new();
+        let elements = [Datum::Int32(1), Datum::Null];
+        let array = arena.make_datum(|packer| {
+            let dims = ArrayDimension {
+                lower_bound: 1,
+                length: elements.len(),
+            };
+            packer.push_array(&[dims], elements).unwrap()
+        });
+        let list = arena.make_datum(|packer| packer.push_list(elements));
+        // Each input is a record of the value and its (here empty) ORDER BY keys.
+        let record = |value: Datum| arena.make_datum(|packer| packer.push_list([value]));
+        let input_type = |value_type: ScalarType| {
+            ScalarType::Record {
+                fields: vec![(ColumnName::from("?column?"), value_type.nullable(false))],
+                custom_id: None,
+            }
+            .nullable(false)
+        };
+        let int32_list = ScalarType::List {
+            element_type: Box::new(ScalarType::Int32),
+            custom_id: None,
+        };
+
+        for (func, value, value_type) in [
+            (
+                AggregateFunc::ArrayConcat { order_by: vec![] },
+                array,
+                ScalarType::Array(Box::new(ScalarType::Int32)),
+            ),
+            (
+                AggregateFunc::ArrayConcatDistinct { order_by: vec![] },
+                array,
+                ScalarType::Array(Box::new(ScalarType::Int32)),
+            ),
+            (
+                AggregateFunc::ListConcat { order_by: vec![] },
+                list,
+                int32_list.clone(),
+            ),
+            (
+                AggregateFunc::ListConcatDistinct { order_by: vec![] },
+                list,
+                int32_list.clone(),
+            ),
+        ] {
+            let output_type = func.output_type(input_type(value_type.clone()));
+            assert_eq!(output_type, value_type.nullable(false), "{func}");
+
+            let result = func.eval([record(value), record(value)], &arena);
+            let result_elements: Vec<_> = match result {
+                Datum::Array(array) => array.elements().iter().collect(),
+                Datum::List(list) => list.iter().collect(),
+                d => panic!("{func} produced {d:?}"),
+            };
+            assert!(result_elements.contains(&Datum::Null), "{func}");
+            assert!(result.is_instance_of(&output_type), "{func}");
         }
     }
-}
 
-fn jsonb_each<'a>(
-    a: Datum<'a>,
-    temp_storage: &'a RowArena,
-    stringify: bool,
-) -> impl Iterator<Item = (Row, Diff)> + 'a {
-    // First produce a map, so that a common iterator can be returned.
-    let map = match a {
-        Datum::Map(dict) => dict,
-        _ => mz_repr::DatumMap::empty(),
-    };
+    #[test]
+    fn max_min_array() {
+        let arena = RowArena::new();
+        let datums = vec![
+            int32_array(&arena, &[1, 2]),
+            Datum::Null,
+            int32_array(&arena, &[1, 3]),
+            int32_array(&arena, &[2]),
+        ];
+        assert_eq!(
+            AggregateFunc::MaxArray.eval(datums.clone(), &arena),
+            int32_array(&arena, &[2])
+        );
+        assert_eq!(
+            AggregateFunc::MinArray.eval(datums, &arena),
+            int32_array(&arena, &[1, 2])
+        );
+        assert_eq!(
+            AggregateFunc::MaxArray.eval(vec![Datum::Null], &arena),
+            Datum::Null
+        );
+    }
+
+    #[test]
+    fn csv_extract_options() {
+        let extract = |input: &str, n_cols: usize, options: CsvExtractOptions| {
+            csv_extract(Datum::String(input), n_cols, options)
+                .map(|(row, diff)| {
+                    assert_eq!(diff, 1);
+                    row.iter().map(|d| d.unwrap_str().to_owned()).join("|")
+                })
+                .collect::<Vec<_>>()
+        };
+        let default = CsvExtractOptions::default();
+        let tab = CsvExtractOptions {
+            delimiter: b'\t',
+            ..default
+        };
+
+        assert_eq!(
+            extract("a,\"b,c\"\nd,\"e \"\"f\"\"\"\ng,h,i", 2, default),
+            ["a|b,c", "d|e \"f\""]
+        );
+        assert_eq!(
+            extract("a\tb,c\nd\t\"e\tf\"\ng", 2, tab),
+            ["a|b,c", "d|e\tf"]
+        );
+        // Rows whose field count does not match are dropped, as with commas.
+        assert!(extract("a,b\tc", 2, tab).is_empty());
+        assert_eq!(
+            extract(
+                "a|'b|c'",
+                2,
+                CsvExtractOptions {
+                    delimiter: b'|',
+                    quote: b'\'',
+                    quoting: true,
+                }
+            ),
+            ["a|b|c"]
+        );
+        assert_eq!(
+            extract(
+                "\"a,\"b",
+                2,
+                CsvExtractOptions {
+                    quoting: false,
+                    ..default
+                }
+            ),
+            ["\"a|\"b"]
+        );
+
+        assert_eq!(
+            TableFunc::CsvExtract {
+                n_cols: 2,
+                options: default,
+            }
+            .to_string(),
+            "csv_extract(2, _)"
+        );
+        assert_eq!(
+            TableFunc::CsvExtract {
+                n_cols: 2,
+                options: tab,
+            }
+            .to_string(),
+            "csv_extract(2, _, delimiter='\\t', quote='\"')"
+        );
+    }
+
+    #[test]
+    fn fixed_width_extract_record() {
+        let widths = vec![5, 3, 4];
+        let input = "Alice 30 NYC \nBob\nZoë  7  Köln extra";
+        let rows: Vec<_> = fixed_width_extract(Datum::String(input), &widths)
+            .map(|(row, diff)| {
+                assert_eq!(diff, 1);
+                row.iter()
+                    .map(|d| d.unwrap_str().to_owned())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Alice".to_owned(), " 30".to_owned(), " NYC".to_owned()],
+                vec!["Zoë  ".to_owned(), "7  ".to_owned(), "Köln".to_owned()],
+            ]
+        );
+    }
 
-    map.iter().map(move |(k, mut v)| {
-        if stringify {
-            v = jsonb_stringify(v, temp_storage);
+    #[test]
+    fn count_output_type_is_not_nullable() {
+        for nullable in [false, true] {
+            let input_type = ScalarType::String.nullable(nullable);
+            assert_eq!(
+                AggregateFunc::Count.output_type(input_type),
+                ScalarType::Int64.nullable(false)
+            );
         }
-        (Row::pack_slice(&[Datum::String(k), v]), 1)
-    })
-}
-
-fn jsonb_object_keys<'a>(a: Datum<'a>) -> impl Iterator<Item = (Row, Diff)> + 'a {
-    let map = match a {
-        Datum::Map(dict) => dict,
-        _ => mz_repr::DatumMap::empty(),
-    };
+    }
 
-    map.iter()
-        .map(move |(k, _)| (Row::pack_slice(&[Datum::String(k)]), 1))
-}
+    #[test]
+    fn variance_aggregates() {
+        fn numeric(n: i32) -> Datum<'static> {
+            Datum::from(Numeric::from(n))
+        }
+        fn numerics(ns: &[i32]) -> Vec<Datum<'static>> {
+            ns.iter().copied().map(numeric).collect()
+        }
 
-fn jsonb_array_elements<'a>(
-    a: Datum<'a>,
-    temp_storage: &'a RowArena,
-    stringify: bool,
-) -> impl Iterator<Item = (Row, Diff)> + 'a {
-    let list = match a {
-        Datum::List(list) => list,
-        _ => mz_repr::DatumList::empty(),
-    };
-    list.iter().map(move |mut e| {
-        if stringify {
-            e = jsonb_stringify(e, temp_storage);
+        let arena = RowArena::new();
+
+        let mut datums = numerics(&[2, 4, 4, 4, 5, 5, 7, 9]);
+        datums.push(Datum::Null);
+        assert_eq!(
+            AggregateFunc::VarPop.eval(datums.clone(), &arena),
+            numeric(4)
+        );
+        assert_eq!(
+            AggregateFunc::StddevPop.eval(datums.clone(), &arena),
+            numeric(2)
+        );
+        let mut expected = Numeric::from(32);
+        mz_repr::adt::numeric::cx_datum().div(&mut expected, &Numeric::from(7));
+        assert_eq!(
+            AggregateFunc::VarSamp.eval(datums, &arena),
+            Datum::from(expected)
+        );
+
+        // The population variance of a single row is zero, but the sample
+        // variance is undefined.
+        let datums = numerics(&[42]);
+        assert_eq!(
+            AggregateFunc::VarPop.eval(datums.clone(), &arena),
+            numeric(0)
+        );
+        assert_eq!(
+            AggregateFunc::StddevPop.eval(datums.clone(), &arena),
+            numeric(0)
+        );
+        assert_eq!(
+            AggregateFunc::VarSamp.eval(datums.clone(), &arena),
+            Datum::Null
+        );
+        assert_eq!(AggregateFunc::StddevSamp.eval(datums, &arena), Datum::Null);
+
+        // All-null and empty inputs produce null.
+        for func in [
+            AggregateFunc::StddevPop,
+            AggregateFunc::StddevSamp,
+            AggregateFunc::VarPop,
+            AggregateFunc::VarSamp,
+        ] {
+            assert_eq!(
+                func.eval(vec![Datum::Null, Datum::Null], &arena),
+                Datum::Null
+            );
+            assert_eq!(func.eval(vec![], &arena), Datum::Null);
+            assert_eq!(func.default(), Datum::Null);
         }
-        (Row::pack_slice(&[e]), 1)
-    })
-}
+    }
 
-fn regexp_extract(a: Datum, r: &AnalyzedRegex) -> Option<(Row, Diff)> {
-    let r = r.inner();
-    let a = a.unwrap_str();
-    let captures = r.captures(a)?;
-    let datums = captures
-        .iter()
-        .skip(1)
-        .map(|m| Datum::from(m.map(|m| m.as_str())));
-    Some((Row::pack(datums), 1))
-}
+    #[test]
+    fn avg_aggregates() {
+        let arena = RowArena::new();
+        let one_and_a_half = {
+            let mut n = Numeric::from(3);
+            mz_repr::adt::numeric::cx_datum().div(&mut n, &Numeric::from(2));
+            Datum::from(n)
+        };
 
-fn generate_series<N>(
-    start: N,
-    stop: N,
-    step: N,
-) -> Result<impl Iterator<Item = (Row, Diff)>, EvalError>
-where
-    N: Integer + Signed + CheckedAdd + Clone,
-    Datum<'static>: From<N>,
-{
-    if step == N::zero() {
-        return Err(EvalError::InvalidParameterValue(
-            "step size cannot equal zero".to_owned(),
-        ));
+        let datums = vec![Datum::Int32(1), Datum::Null, Datum::Int32(2)];
+        assert_eq!(AggregateFunc::AvgInt32.eval(datums, &arena), one_and_a_half);
+        let datums = vec![Datum::Int64(i64::MAX), Datum::Int64(i64::MAX)];
+        assert_eq!(
+            AggregateFunc::AvgInt64.eval(datums, &arena),
+            Datum::from(Numeric::from(i64::MAX))
+        );
+        let datums = vec![Datum::from(1f32), Datum::from(2f32)];
+        assert_eq!(
+            AggregateFunc::AvgFloat32.eval(datums, &arena),
+            Datum::from(1.5f64)
+        );
+        let datums = vec![Datum::from(Numeric::from(1)), Datum::from(Numeric::from(2))];
+        assert_eq!(
+            AggregateFunc::AvgNumeric.eval(datums, &arena),
+            one_and_a_half
+        );
+        let datums = vec![
+            Datum::from(Interval::new(1, 0, 0)),
+            Datum::Null,
+            Datum::from(Interval::new(0, 1, 0)),
+        ];
+        assert_eq!(
+            AggregateFunc::AvgInterval.eval(datums, &arena),
+            Datum::from(Interval::new(0, 15, 43_200_000_000))
+        );
+
+        for (func, input_type, output_type) in [
+            (
+                AggregateFunc::AvgInt16,
+                ScalarType::Int16,
+                ScalarType::Numeric { max_scale: None },
+            ),
+            (
+                AggregateFunc::AvgInt32,
+                ScalarType::Int32,
+                ScalarType::Numeric { max_scale: None },
+            ),
+            (
+                AggregateFunc::AvgInt64,
+                ScalarType::Int64,
+                ScalarType::Numeric { max_scale: None },
+            ),
+            (
+                AggregateFunc::AvgFloat32,
+                ScalarType::Float32,
+                ScalarType::Float64,
+            ),
+            (
+                AggregateFunc::AvgFloat64,
+                ScalarType::Float64,
+                ScalarType::Float64,
+            ),
+            (
+                AggregateFunc::AvgNumeric,
+                ScalarType::Numeric { max_scale: None },
+                ScalarType::Numeric { max_scale: None },
+            ),
+            (
+                AggregateFunc::AvgInterval,
+                ScalarType::Interval,
+                ScalarType::Interval,
+            ),
+        ] {
+            assert_eq!(
+                func.output_type(input_type.nullable(false)),
+                output_type.nullable(false)
+            );
+            assert_eq!(func.eval(vec![Datum::Null], &arena), Datum::Null);
+            assert_eq!(func.eval(vec![], &arena), Datum::Null);
+        }
     }
-    Ok(num::range_step_inclusive(start, stop, step)
-        .map(move |i| (Row::pack_slice(&[Datum::from(i)]), 1)))
-}
-
-/// Like
-/// [`num::range_step_inclusive`](https://github.com/rust-num/num-iter/blob/ddb14c1e796d401014c6c7a727de61d8109ad986/src/lib.rs#L279),
-/// but for our timestamp types using [`Interval`] for `step`.xwxw
-#[derive(Clone)]
-pub struct TimestampRangeStepInclusive<T> {
-    state: CheckedTimestamp<T>,
-    stop: CheckedTimestamp<T>,
-    step: Interval,
-    rev: bool,
-    done: bool,
-}
 
-impl<T: TimestampLike> Iterator for TimestampRangeStepInclusive<T> {
-    type Item = CheckedTimestamp<T>;
+    #[test]
+    fn sum_accumulation_widths() {
+        let arena = RowArena::new();
 
-    #[inline]
-    fn next(&mut self) -> Option<CheckedTimestamp<T>> {
-        if !self.done
-            && ((self.rev && self.state >= self.stop) || (!self.rev && self.state <= self.stop))
-        {
-            let result = self.state.clone();
-            match add_timestamp_months(self.state.deref(), self.step.months) {
-                Ok(state) => match state.checked_add_signed(self.step.duration_as_chrono()) {
-                    Some(v) => match CheckedTimestamp::from_timestamplike(v) {
-                        Ok(v) => self.state = v,
-                        Err(_) => self.done = true,
-                    },
-                    None => self.done = true,
-                },
-                Err(..) => {
-                    self.done = true;
-                }
+        // `SumInt64` accumulates into an `i128` and reports a numeric, so
+        // summing values at the extremes of `i64` does not overflow.
+        assert_eq!(
+            AggregateFunc::SumInt64.output_type(ScalarType::Int64.nullable(false)),
+            ScalarType::Numeric {
+                max_scale: Some(NumericMaxScale::ZERO)
             }
+            .nullable(false)
+        );
+        let n = 1000;
+        let datums = vec![Datum::Int64(i64::MAX); n];
+        assert_eq!(
+            AggregateFunc::SumInt64.eval(datums, &arena),
+            Datum::from(i128::from(i64::MAX) * 1000)
+        );
+        let datums = vec![Datum::Int64(i64::MIN); n];
+        assert_eq!(
+            AggregateFunc::SumInt64.eval(datums, &arena),
+            Datum::from(i128::from(i64::MIN) * 1000)
+        );
+
+        // Reaching the limits of the `i128` accumulator takes 2^64 inputs, so
+        // start from a running sum close to them instead. Sums up to the limits
+        // are exact, and are represented exactly by the numeric result.
+        let chunk = vec![i64::MAX; PRIMITIVE_CHUNK_SIZE];
+        let chunk_sum: i128 = chunk.iter().copied().map(i128::from).sum();
+        assert_eq!(
+            add_int64_chunk(i128::MAX - chunk_sum, &chunk),
+            Ok(i128::MAX)
+        );
+        assert_eq!(
+            add_int64_chunk(i128::MAX - chunk_sum + 1, &chunk),
+            Err(EvalError::NumericFieldOverflow)
+        );
+        let chunk = vec![i64::MIN; PRIMITIVE_CHUNK_SIZE];
+        let chunk_sum: i128 = chunk.iter().copied().map(i128::from).sum();
+        assert_eq!(
+            add_int64_chunk(i128::MIN - chunk_sum, &chunk),
+            Ok(i128::MIN)
+        );
+        assert_eq!(
+            add_int64_chunk(i128::MIN - chunk_sum - 1, &chunk),
+            Err(EvalError::NumericFieldOverflow)
+        );
+        assert_eq!(
+            add_int64_chunk(i128::MAX - 1, &[i64::MIN, i64::MAX, 2]),
+            Ok(i128::MAX)
+        );
+        for sum in [i128::MAX, i128::MIN] {
+            assert_eq!(
+                Datum::from(sum).unwrap_numeric().0.to_string(),
+                sum.to_string()
+            );
+        }
 
-            Some(result)
-        } else {
-            None
+        // `SumInt32` accumulates into an `i64`, which can only overflow after
+        // more than 2^32 maximal inputs.
+        assert_eq!(
+            AggregateFunc::SumInt32.output_type(ScalarType::Int32.nullable(false)),
+            ScalarType::Int64.nullable(false)
+        );
+        let datums = vec![Datum::Int32(i32::MAX); n];
+        assert_eq!(
+            AggregateFunc::SumInt32.eval(datums, &arena),
+            Datum::Int64(i64::from(i32::MAX) * 1000)
+        );
+        let datums = vec![Datum::Int32(i32::MIN); n];
+        assert_eq!(
+            AggregateFunc::SumInt32.eval(datums, &arena),
+            Datum::Int64(i64::from(i32::MIN) * 1000)
+        );
+    }
+
+    #[test]
+    fn max_min_sum_skip_nulls() {
+        let arena = RowArena::new();
+        let numeric = |n: i32| Datum::from(Numeric::from(n));
+        // For each numeric type, the aggregates to test and their expected
+        // results over 3, NULL, 1, 2.
+        let cases: [(fn(i16) -> Datum<'static>, [AggregateFunc; 3], [Datum; 3]); 9] = [
+            (
+                Datum::Int16,
+                [
+                    AggregateFunc::MaxInt16,
+                    AggregateFunc::MinInt16,
+                    AggregateFunc::SumInt16,
+                ],
+                [Datum::Int16(3), Datum::Int16(1), Datum::Int64(6)],
+            ),
+            (
+                |n| Datum::Int32(i32::from(n)),
+                [
+                    AggregateFunc::MaxInt32,
+                    AggregateFunc::MinInt32,
+                    AggregateFunc::SumInt32,
+                ],
+                [Datum::Int32(3), Datum::Int32(1), Datum::Int64(6)],
+            ),
+            (
+                |n| Datum::Int64(i64::from(n)),
+                [
+                    AggregateFunc::MaxInt64,
+                    AggregateFunc::MinInt64,
+                    AggregateFunc::SumInt64,
+                ],
+                [Datum::Int64(3), Datum::Int64(1), numeric(6)],
+            ),
+            (
+                |n| Datum::UInt16(u16::try_from(n).unwrap()),
+                [
+                    AggregateFunc::MaxUInt16,
+                    AggregateFunc::MinUInt16,
+                    AggregateFunc::SumUInt16,
+                ],
+                [Datum::UInt16(3), Datum::UInt16(1), Datum::UInt64(6)],
+            ),
+            (
+                |n| Datum::UInt32(u32::try_from(n).unwrap()),
+                [
+                    AggregateFunc::MaxUInt32,
+                    AggregateFunc::MinUInt32,
+                    AggregateFunc::SumUInt32,
+                ],
+                [Datum::UInt32(3), Datum::UInt32(1), Datum::UInt64(6)],
+            ),
+            (
+                |n| Datum::UInt64(u64::try_from(n).unwrap()),
+                [
+                    AggregateFunc::MaxUInt64,
+                    AggregateFunc::MinUInt64,
+                    AggregateFunc::SumUInt64,
+                ],
+                [Datum::UInt64(3), Datum::UInt64(1), numeric(6)],
+            ),
+            (
+                |n| Datum::from(f32::from(n)),
+                [
+                    AggregateFunc::MaxFloat32,
+                    AggregateFunc::MinFloat32,
+                    AggregateFunc::SumFloat32,
+                ],
+                [Datum::from(3f32), Datum::from(1f32), Datum::from(6f32)],
+            ),
+            (
+                |n| Datum::from(f64::from(n)),
+                [
+                    AggregateFunc::MaxFloat64,
+                    AggregateFunc::MinFloat64,
+                    AggregateFunc::SumFloat64,
+                ],
+                [Datum::from(3f64), Datum::from(1f64), Datum::from(6f64)],
+            ),
+            (
+                |n| Datum::from(Numeric::from(i32::from(n))),
+                [
+                    AggregateFunc::MaxNumeric,
+                    AggregateFunc::MinNumeric,
+                    AggregateFunc::SumNumeric,
+                ],
+                [numeric(3), numeric(1), numeric(6)],
+            ),
+        ];
+        for (datum, funcs, expected) in cases {
+            let datums = vec![datum(3), Datum::Null, datum(1), datum(2)];
+            for (func, expected) in funcs.into_iter().zip(expected) {
+                assert_eq!(func.eval(datums.clone(), &arena), expected, "{func}");
+                // Inputs that are empty or entirely null produce null.
+                assert_eq!(func.eval(vec![], &arena), Datum::Null, "{func}");
+                assert_eq!(
+                    func.eval(vec![Datum::Null; 2], &arena),
+                    Datum::Null,
+                    "{func}"
+                );
+            }
         }
     }
-}
 
-fn generate_series_ts<T: TimestampLike>(
-    start: CheckedTimestamp<T>,
-    stop: CheckedTimestamp<T>,
-    step: Interval,
-    conv: fn(CheckedTimestamp<T>) -> Datum<'static>,
-) -> Result<impl Iterator<Item = (Row, Diff)>, EvalError> {
-    let normalized_step = step.as_microseconds();
-    if normalized_step == 0 {
-        return Err(EvalError::InvalidParameterValue(
-            "step size cannot equal zero".to_owned(),
-        ));
+    #[test]
+    fn bool_and_bool_or() {
+        let arena = RowArena::new();
+
+        for (datums, bool_and, bool_or) in [
+            (vec![Datum::True, Datum::True], Datum::True, Datum::True),
+            (vec![Datum::True, Datum::False], Datum::False, Datum::True),
+            (vec![Datum::True, Datum::Null], Datum::Null, Datum::True),
+            (vec![Datum::False, Datum::Null], Datum::False, Datum::Null),
+            (vec![Datum::Null], Datum::Null, Datum::Null),
+            (vec![], Datum::Null, Datum::Null),
+        ] {
+            assert_eq!(
+                AggregateFunc::BoolAnd.eval(datums.clone(), &arena),
+                bool_and
+            );
+            assert_eq!(AggregateFunc::BoolOr.eval(datums, &arena), bool_or);
+        }
+
+        // Unlike `All` and `Any`, the empty-input result is null.
+        assert_eq!(AggregateFunc::All.default(), Datum::True);
+        assert_eq!(AggregateFunc::BoolAnd.default(), Datum::Null);
+        assert_eq!(AggregateFunc::Any.default(), Datum::False);
+        assert_eq!(AggregateFunc::BoolOr.default(), Datum::Null);
+
+        assert_eq!(AggregateFunc::BoolAnd.to_string(), "bool_and");
+        assert_eq!(AggregateFunc::BoolOr.to_string(), "bool_or");
     }
-    let rev = normalized_step < 0;
 
-    let trsi = TimestampRangeStepInclusive {
-        state: start,
-        stop,
-        step,
-        rev,
-        done: false,
-    };
+    #[test]
+    fn generate_series_timestamptz_in_timezone() {
+        fn ts(s: &str) -> CheckedTimestamp<DateTime<Utc>> {
+            let ts = DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+            CheckedTimestamp::from_timestamplike(ts).unwrap()
+        }
+        fn series(
+            start: &str,
+            stop: &str,
+            step: Interval,
+            tz: &str,
+        ) -> Vec<CheckedTimestamp<DateTime<Utc>>> {
+            let tz = parse_timezone(tz).unwrap();
+            generate_series_ts_tz(ts(start), ts(stop), step, tz)
+                .unwrap()
+                .map(|(row, _)| row.unpack_first().unwrap_timestamptz())
+                .collect()
+        }
+        fn timestamps(ss: &[&str]) -> Vec<CheckedTimestamp<DateTime<Utc>>> {
+            ss.iter().map(|s| ts(s)).collect()
+        }
 
-    Ok(trsi.map(move |i| (Row::pack_slice(&[conv(i)]), 1)))
-}
+        // Daylight saving time starts in New York on 2023-03-12, so a day
+        // step keeps the local time of day at noon, rather than the UTC time.
+        let one_day = Interval::new(0, 1, 0);
+        assert_eq!(
+            series(
+                "2023-03-11T17:00:00Z",
+                "2023-03-13T16:00:00Z",
+                one_day,
+                "America/New_York"
+            ),
+            timestamps(&[
+                "2023-03-11T17:00:00Z",
+                "2023-03-12T16:00:00Z",
+                "2023-03-13T16:00:00Z",
+            ])
+        );
+        assert_eq!(
+            series(
+                "2023-03-11T17:00:00Z",
+                "2023-03-13T16:00:00Z",
+                one_day,
+                "UTC"
+            ),
+            timestamps(&["2023-03-11T17:00:00Z", "2023-03-12T17:00:00Z"])
+        );
+
+        let one_month = Interval::new(1, 0, 0);
+        assert_eq!(
+            series(
+                "2023-02-15T17:00:00Z",
+                "2023-04-15T16:00:00Z",
+                one_month,
+                "America/New_York"
+            ),
+            timestamps(&[
+                "2023-02-15T17:00:00Z",
+                "2023-03-15T16:00:00Z",
+                "2023-04-15T16:00:00Z",
+            ])
+        );
+
+        // Steps without month or day components are applied in absolute time,
+        // including across the ambiguous hour when clocks are turned back.
+        let half_hour = Interval::new(0, 0, 30 * 60 * 1_000_000);
+        assert_eq!(
+            series(
+                "2023-11-05T05:00:00Z",
+                "2023-11-05T06:30:00Z",
+                half_hour,
+                "America/New_York"
+            ),
+            timestamps(&[
+                "2023-11-05T05:00:00Z",
+                "2023-11-05T05:30:00Z",
+                "2023-11-05T06:00:00Z",
+                "2023-11-05T06:30:00Z",
+            ])
+        );
+    }
 
-fn generate_subscripts_array(
-    a: Datum,
-    dim: i32,
-) -> Result<Box<dyn Iterator<Item = (Row, Diff)>>, EvalError> {
-    if dim <= 0 {
-        return Ok(Box::new(iter::empty()));
+    #[test]
+    fn eval_with_filter() {
+        let arena = RowArena::new();
+
+        // `count(*) FILTER (WHERE false)`
+        let datums = vec![(Datum::True, false), (Datum::True, false)];
+        assert_eq!(
+            AggregateFunc::Count.eval_with_filter(datums, &arena),
+            Datum::Int64(0)
+        );
+
+        // `count(x) FILTER (...)` ignores nulls among the passing rows.
+        let datums = vec![
+            (Datum::Int32(1), true),
+            (Datum::Null, true),
+            (Datum::Int32(2), false),
+            (Datum::Int32(3), true),
+        ];
+        assert_eq!(
+            AggregateFunc::Count.eval_with_filter(datums.clone(), &arena),
+            Datum::Int64(2)
+        );
+        assert_eq!(
+            AggregateFunc::MaxInt32.eval_with_filter(datums.clone(), &arena),
+            Datum::Int32(3)
+        );
+        assert_eq!(
+            AggregateFunc::SumInt32.eval_with_filter(datums, &arena),
+            Datum::Int64(4)
+        );
+
+        // Aggregates over no passing rows behave as over no rows at all.
+        let datums = vec![(Datum::Int32(1), false)];
+        assert_eq!(
+            AggregateFunc::MaxInt32.eval_with_filter(datums, &arena),
+            Datum::Null
+        );
     }
 
-    match a.unwrap_array().dims().into_iter().nth(
-        (dim - 1)
-            .try_into()
-            .map_err(|_| EvalError::Int32OutOfRange)?,
-    ) {
-        Some(requested_dim) => Ok(Box::new(generate_series::<i32>(
-            requested_dim
-                .lower_bound
-                .try_into()
-                .map_err(|_| EvalError::Int32OutOfRange)?,
-            requested_dim
-                .length
-                .try_into()
-                .map_err(|_| EvalError::Int32OutOfRange)?,
-            1,
-        )?)),
-        None => Ok(Box::new(iter::empty())),
+    #[test]
+    fn concat_distinct() {
+        let arena = RowArena::new();
+        // The inputs to the concatenation aggregates are records whose first
+        // field is the array or list to concatenate.
+        let record = |d: Datum| arena.make_datum(|packer| packer.push_list([d]));
+        let list = |ds: &[Datum]| arena.make_datum(|packer| packer.push_list(ds));
+
+        let datums = vec![
+            record(int32_array(&arena, &[1, 2])),
+            record(int32_array(&arena, &[2, 3])),
+        ];
+        let func = AggregateFunc::ArrayConcat { order_by: vec![] };
+        assert_eq!(
+            func.eval(datums.clone(), &arena),
+            int32_array(&arena, &[1, 2, 2, 3])
+        );
+        let func = AggregateFunc::ArrayConcatDistinct { order_by: vec![] };
+        assert_eq!(func.eval(datums, &arena), int32_array(&arena, &[1, 2, 3]));
+        assert_eq!(func.identity_datum(), Datum::empty_array());
+
+        // Nulls are deduplicated to a single null.
+        let datums = vec![
+            record(list(&[Datum::Null, Datum::Int32(1)])),
+            record(list(&[Datum::Int32(1), Datum::Null])),
+        ];
+        let func = AggregateFunc::ListConcatDistinct { order_by: vec![] };
+        assert_eq!(
+            func.eval(datums, &arena),
+            list(&[Datum::Null, Datum::Int32(1)])
+        );
+        assert_eq!(func.identity_datum(), Datum::empty_list());
     }
-}
 
-fn unnest_array<'a>(a: Datum<'a>) -> impl Iterator<Item = (Row, Diff)> + 'a {
-    a.unwrap_array()
-        .elements()
-        .iter()
-        .map(move |e| (Row::pack_slice(&[e]), 1))
-}
+    #[test]
+    fn covariance_aggregates() {
+        let arena = RowArena::new();
+        let pair = |y: Option<f64>, x: Option<f64>| {
+            arena.make_datum(|packer| packer.push_list([Datum::from(y), Datum::from(x)]))
+        };
+        let eval = |func: AggregateFunc, pairs: &[(Option<f64>, Option<f64>)]| {
+            func.eval(pairs.iter().map(|(y, x)| pair(*y, *x)), &arena)
+        };
 
-fn unnest_list<'a>(a: Datum<'a>) -> impl Iterator<Item = (Row, Diff)> + 'a {
-    a.unwrap_list()
-        .iter()
-        .map(move |e| (Row::pack_slice(&[e]), 1))
-}
+        // y = 2x, with a pair containing a null that must be skipped.
+        let pairs = [
+            (Some(2.0), Some(1.0)),
+            (Some(4.0), Some(2.0)),
+            (None, Some(100.0)),
+            (Some(6.0), Some(3.0)),
+        ];
+        assert_eq!(eval(AggregateFunc::Corr, &pairs), Datum::from(1.0f64));
+        assert_eq!(
+            eval(AggregateFunc::CovarPop, &pairs),
+            Datum::from(4.0f64 / 3.0)
+        );
+        assert_eq!(eval(AggregateFunc::CovarSamp, &pairs), Datum::from(2.0f64));
+
+        // A single pair has a population covariance but no sample covariance
+        // or correlation.
+        let pairs = [(Some(1.0), Some(1.0)), (Some(5.0), None)];
+        assert_eq!(eval(AggregateFunc::Corr, &pairs), Datum::Null);
+        assert_eq!(eval(AggregateFunc::CovarPop, &pairs), Datum::from(0.0f64));
+        assert_eq!(eval(AggregateFunc::CovarSamp, &pairs), Datum::Null);
+
+        // The correlation with a constant variable is undefined.
+        let pairs = [(Some(1.0), Some(1.0)), (Some(2.0), Some(1.0))];
+        assert_eq!(eval(AggregateFunc::Corr, &pairs), Datum::Null);
+        assert_eq!(eval(AggregateFunc::CovarSamp, &pairs), Datum::from(0.0f64));
+
+        // Near-constant variables do not push the correlation out of its
+        // range.
+        for base in [1e6, 1e9, 1e12] {
+            for sign in [1.0, -1.0] {
+                let pairs: Vec<_> = (0..5)
+                    .map(|i| {
+                        let x = base + f64::from(i) * 1e-6;
+                        (Some(sign * x + 0.1), Some(x))
+                    })
+                    .collect();
+                let corr = eval(AggregateFunc::Corr, &pairs);
+                if !corr.is_null() {
+                    let corr = corr.unwrap_float64();
+                    assert!((-1.0..=1.0).contains(&corr), "corr {corr} for base {base}");
+                }
+            }
+        }
 
-impl fmt::Display for AggregateFunc {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            AggregateFunc::MaxNumeric => f.write_str("max"),
-            AggregateFunc::MaxInt16 => f.write_str("max"),
-            AggregateFunc::MaxInt32 => f.write_str("max"),
-            AggregateFunc::MaxInt64 => f.write_str("max"),
-            AggregateFunc::MaxUInt16 => f.write_str("max"),
-            AggregateFunc::MaxUInt32 => f.write_str("max"),
-            AggregateFunc::MaxUInt64 => f.write_str("max"),
-            AggregateFunc::MaxMzTimestamp => f.write_str("max"),
-            AggregateFunc::MaxFloat32 => f.write_str("max"),
-            AggregateFunc::MaxFloat64 => f.write_str("max"),
-            AggregateFunc::MaxBool => f.write_str("max"),
-            AggregateFunc::MaxString => f.write_str("max"),
-            AggregateFunc::MaxDate => f.write_str("max"),
-            AggregateFunc::MaxTimestamp => f.write_str("max"),
-            AggregateFunc::MaxTimestampTz => f.write_str("max"),
-            AggregateFunc::MinNumeric => f.write_str("min"),
-            AggregateFunc::MinInt16 => f.write_str("min"),
-            AggregateFunc::MinInt32 => f.write_str("min"),
-            AggregateFunc::MinInt64 => f.write_str("min"),
-            AggregateFunc::MinUInt16 => f.write_str("min"),
-            AggregateFunc::MinUInt32 => f.write_str("min"),
-            AggregateFunc::MinUInt64 => f.write_str("min"),
-            AggregateFunc::MinMzTimestamp => f.write_str("min"),
-            AggregateFunc::MinFloat32 => f.write_str("min"),
-            AggregateFunc::MinFloat64 => f.write_str("min"),
-            AggregateFunc::MinBool => f.write_str("min"),
-            AggregateFunc::MinString => f.write_str("min"),
-            AggregateFunc::MinDate => f.write_str("min"),
-            AggregateFunc::MinTimestamp => f.write_str("min"),
-            AggregateFunc::MinTimestampTz => f.write_str("min"),
-            AggregateFunc::SumInt16 => f.write_str("sum"),
-            AggregateFunc::SumInt32 => f.write_str("sum"),
-            AggregateFunc::SumInt64 => f.write_str("sum"),
-            AggregateFunc::SumUInt16 => f.write_str("sum"),
-            AggregateFunc::SumUInt32 => f.write_str("sum"),
-            AggregateFunc::SumUInt64 => f.write_str("sum"),
-            AggregateFunc::SumFloat32 => f.write_str("sum"),
-            AggregateFunc::SumFloat64 => f.write_str("sum"),
-            AggregateFunc::SumNumeric => f.write_str("sum"),
-            AggregateFunc::Count => f.write_str("count"),
-            AggregateFunc::Any => f.write_str("any"),
-            AggregateFunc::All => f.write_str("all"),
-            AggregateFunc::JsonbAgg { .. } => f.write_str("jsonb_agg"),
-            AggregateFunc::JsonbObjectAgg { .. } => f.write_str("jsonb_object_agg"),
-            AggregateFunc::ArrayConcat { .. } => f.write_str("array_agg"),
-            AggregateFunc::ListConcat { .. } => f.write_str("list_agg"),
-            AggregateFunc::StringAgg { .. } => f.write_str("string_agg"),
-            AggregateFunc::RowNumber { .. } => f.write_str("row_number"),
-            AggregateFunc::DenseRank { .. } => f.write_str("dense_rank"),
-            AggregateFunc::LagLead {
-                lag_lead: LagLeadType::Lag,
-                ..
-            } => f.write_str("lag"),
-            AggregateFunc::LagLead {
-                lag_lead: LagLeadType::Lead,
-                ..
-            } => f.write_str("lead"),
-            AggregateFunc::FirstValue { .. } => f.write_str("first_value"),
-            AggregateFunc::LastValue { .. } => f.write_str("last_value"),
-            AggregateFunc::Dummy => f.write_str("dummy"),
+        assert_eq!(eval(AggregateFunc::CovarPop, &[]), Datum::Null);
+        let input_type = ScalarType::List {
+            element_type: Box::new(ScalarType::Float64),
+            custom_id: None,
+        }
+        .nullable(false);
+        for func in [
+            AggregateFunc::Corr,
+            AggregateFunc::CovarPop,
+            AggregateFunc::CovarSamp,
+        ] {
+            assert_eq!(
+                func.output_type(input_type.clone()),
+                ScalarType::Float64.nullable(true)
+            );
         }
     }
-}
 
-#[derive(
-    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
-)]
-pub struct CaptureGroupDesc {
-    pub index: u32,
-    pub name: Option<String>,
-    pub nullable: bool,
-}
+    #[test]
+    fn regression_aggregates() {
+        let arena = RowArena::new();
+        let pair = |y: Option<f64>, x: Option<f64>| {
+            arena.make_datum(|packer| packer.push_list([Datum::from(y), Datum::from(x)]))
+        };
+        let eval = |func: AggregateFunc, pairs: &[(Option<f64>, Option<f64>)]| {
+            func.eval(pairs.iter().map(|(y, x)| pair(*y, *x)), &arena)
+        };
 
-impl RustType<ProtoCaptureGroupDesc> for CaptureGroupDesc {
-    fn into_proto(&self) -> ProtoCaptureGroupDesc {
-        ProtoCaptureGroupDesc {
-            index: self.index,
-            name: self.name.clone(),
-            nullable: self.nullable,
+        // y = 2x + 1, with pairs containing nulls that must be skipped.
+        let pairs = [
+            (Some(3.0), Some(1.0)),
+            (Some(5.0), Some(2.0)),
+            (None, Some(100.0)),
+            (Some(7.0), Some(3.0)),
+            (Some(100.0), None),
+        ];
+        assert_eq!(eval(AggregateFunc::RegrCount, &pairs), Datum::Int64(3));
+        assert_eq!(eval(AggregateFunc::RegrSlope, &pairs), Datum::from(2.0f64));
+        assert_eq!(
+            eval(AggregateFunc::RegrIntercept, &pairs),
+            Datum::from(1.0f64)
+        );
+        assert_eq!(eval(AggregateFunc::RegrR2, &pairs), Datum::from(1.0f64));
+
+        // A constant x has no least-squares line.
+        let pairs = [(Some(1.0), Some(1.0)), (Some(2.0), Some(1.0))];
+        assert_eq!(eval(AggregateFunc::RegrCount, &pairs), Datum::Int64(2));
+        assert_eq!(eval(AggregateFunc::RegrSlope, &pairs), Datum::Null);
+        assert_eq!(eval(AggregateFunc::RegrIntercept, &pairs), Datum::Null);
+        assert_eq!(eval(AggregateFunc::RegrR2, &pairs), Datum::Null);
+
+        // A constant x that is not exactly representable is still constant.
+        let pairs: Vec<_> = (1..=7).map(|y| (Some(f64::from(y)), Some(0.1))).collect();
+        assert_eq!(eval(AggregateFunc::RegrSlope, &pairs), Datum::Null);
+        assert_eq!(eval(AggregateFunc::RegrIntercept, &pairs), Datum::Null);
+        assert_eq!(eval(AggregateFunc::RegrR2, &pairs), Datum::Null);
+
+        // A constant y is fit perfectly by a horizontal line.
+        let pairs = [(Some(4.0), Some(1.0)), (Some(4.0), Some(2.0))];
+        assert_eq!(eval(AggregateFunc::RegrSlope, &pairs), Datum::from(0.0f64));
+        assert_eq!(
+            eval(AggregateFunc::RegrIntercept, &pairs),
+            Datum::from(4.0f64)
+        );
+        assert_eq!(eval(AggregateFunc::RegrR2, &pairs), Datum::from(1.0f64));
+
+        // The count is zero, rather than null, on empty input.
+        assert_eq!(eval(AggregateFunc::RegrCount, &[]), Datum::Int64(0));
+        assert_eq!(AggregateFunc::RegrCount.default(), Datum::Int64(0));
+        assert_eq!(eval(AggregateFunc::RegrSlope, &[]), Datum::Null);
+
+        let input_type = ScalarType::List {
+            element_type: Box::new(ScalarType::Float64),
+            custom_id: None,
         }
+        .nullable(false);
+        assert_eq!(
+            AggregateFunc::RegrCount.output_type(input_type.clone()),
+            ScalarType::Int64.nullable(false)
+        );
+        assert_eq!(
+            AggregateFunc::RegrSlope.output_type(input_type),
+            ScalarType::Float64.nullable(true)
+        );
     }
 
-    fn from_proto(proto: ProtoCaptureGroupDesc) -> Result<Self, TryFromProtoError> {
-        Ok(Self {
-            index: proto.index,
-            name: proto.name,
-            nullable: proto.nullable,
-        })
+    #[test]
+    fn supports_retractions() {
+        for func in [
+            AggregateFunc::SumInt32,
+            AggregateFunc::SumFloat64,
+            AggregateFunc::AvgNumeric,
+            AggregateFunc::Count,
+        ] {
+            assert!(func.supports_retractions(), "{func}");
+        }
+        for func in [
+            AggregateFunc::MaxInt32,
+            AggregateFunc::MinString,
+            AggregateFunc::ProductFloat64,
+            AggregateFunc::CountDistinct,
+            AggregateFunc::StringAgg { order_by: vec![] },
+            AggregateFunc::RowNumber { order_by: vec![] },
+        ] {
+            assert!(!func.supports_retractions(), "{func}");
+        }
+
+        // Retracting an input inverts its accumulation.
+        let arena = RowArena::new();
+        for (func, datum) in [
+            (AggregateFunc::SumInt32, Datum::Int32(7)),
+            (AggregateFunc::Count, Datum::Int32(7)),
+            (AggregateFunc::Any, Datum::True),
+            (AggregateFunc::BoolAnd, Datum::False),
+        ] {
+            assert!(func.supports_retractions(), "{func}");
+            let mut accumulator = func.accumulator().unwrap();
+            accumulator.add(datum, 1);
+            accumulator.add(datum, -1);
+            assert_eq!(accumulator.finish(), Ok(func.eval([], &arena)), "{func}");
+        }
     }
-}
 
-#[derive(
-    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
-)]
-pub struct AnalyzedRegex(
-    #[proptest(strategy = "mz_repr::adt::regex::any_regex()")] ReprRegex,
-    Vec<CaptureGroupDesc>,
-);
+    #[test]
+    fn window_func_max_partition_size() {
+        let arena = RowArena::new();
+        // Each input is ([OriginalRow], OrderByExprs...), without any ORDER BY
+        // expressions.
+        let partition = |size: i32| {
+            (0..size)
+                .map(|row| {
+                    arena.make_datum(|packer| {
+                        packer.push_list_with(|packer| packer.push_list([Datum::Int32(row)]))
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
 
-impl RustType<ProtoAnalyzedRegex> for AnalyzedRegex {
-    fn into_proto(&self) -> ProtoAnalyzedRegex {
-        ProtoAnalyzedRegex {
-            regex: Some(self.0.into_proto()),
-            groups: self.1.into_proto(),
+        let func = AggregateFunc::RowNumber { order_by: vec![] };
+        assert!(func.is_window_func());
+        assert_eq!(
+            func.eval_with_max_partition_size(partition(3), &arena, 3),
+            Ok(func.eval(partition(3), &arena))
+        );
+        assert_eq!(
+            func.eval_with_max_partition_size(partition(4), &arena, 3),
+            Err(EvalError::WindowPartitionTooLarge { max_size: 3 })
+        );
+
+        // Other aggregates are not limited.
+        let func = AggregateFunc::SumInt32;
+        assert!(!func.is_window_func());
+        assert_eq!(
+            func.eval_with_max_partition_size([Datum::Int32(1); 4], &arena, 3),
+            Ok(Datum::Int64(4))
+        );
+    }
+
+    #[test]
+    fn float_max_min_nan() {
+        let arena = RowArena::new();
+        let cases: [(AggregateFunc, AggregateFunc, fn(f64) -> Datum<'static>); 2] = [
+            (AggregateFunc::MaxFloat32, AggregateFunc::MinFloat32, |f| {
+                Datum::from(f32::cast_lossy(f))
+            }),
+            (
+                AggregateFunc::MaxFloat64,
+                AggregateFunc::MinFloat64,
+                Datum::from,
+            ),
+        ];
+        for (max, min, datum) in cases {
+            let values = [datum(1.0), datum(f64::NAN), Datum::Null];
+            assert_eq!(max.eval(values, &arena), datum(f64::NAN));
+            assert_eq!(min.eval(values, &arena), datum(1.0));
+
+            let values = [datum(f64::NAN), datum(-f64::INFINITY)];
+            assert_eq!(max.eval(values, &arena), datum(f64::NAN));
+            assert_eq!(min.eval(values, &arena), datum(-f64::INFINITY));
+
+            let values = [datum(f64::NAN), datum(f64::NAN)];
+            assert_eq!(max.eval(values, &arena), datum(f64::NAN));
+            assert_eq!(min.eval(values, &arena), datum(f64::NAN));
         }
     }
 
-    fn from_proto(proto: ProtoAnalyzedRegex) -> Result<Self, TryFromProtoError> {
-        Ok(AnalyzedRegex(
-            proto.regex.into_rust_if_some("ProtoAnalyzedRegex::regex")?,
-            proto.groups.into_rust()?,
-        ))
+    #[test]
+    fn first_value_last_value_full_partition() {
+        let arena = RowArena::new();
+        // Each input is ((OriginalRow, InputValue), OrderByExpr), and each
+        // output is (Value, OriginalRow).
+        let input = |row: i32, value: Datum, order: i32| {
+            arena.make_datum(|packer| {
+                packer.push_list_with(|packer| {
+                    packer.push_list([Datum::Int32(row), value]);
+                    packer.push(Datum::Int32(order));
+                })
+            })
+        };
+        let output = |values: &[(Datum, i32)]| {
+            let records = values.iter().map(|(value, row)| {
+                arena.make_datum(|packer| packer.push_list([*value, Datum::Int32(*row)]))
+            });
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+
+        // The inputs are deliberately not in ORDER BY order, and the first
+        // value in that order is null.
+        let datums = vec![
+            input(1, Datum::Int32(10), 2),
+            input(2, Datum::Int32(30), 3),
+            input(3, Datum::Null, 1),
+        ];
+        let order_by = vec![ColumnOrder {
+            column: 0,
+            desc: false,
+            nulls_last: false,
+        }];
+        let window_frame = WindowFrame {
+            units: WindowFrameUnits::Rows,
+            start_bound: WindowFrameBound::UnboundedPreceding,
+            end_bound: WindowFrameBound::UnboundedFollowing,
+        };
+
+        let func = AggregateFunc::FirstValue {
+            order_by: order_by.clone(),
+            window_frame: window_frame.clone(),
+        };
+        assert_eq!(
+            func.eval(datums.clone(), &arena),
+            output(&[(Datum::Null, 3), (Datum::Null, 1), (Datum::Null, 2)])
+        );
+
+        let func = AggregateFunc::LastValue {
+            order_by,
+            window_frame,
+        };
+        assert_eq!(
+            func.eval(datums, &arena),
+            output(&[
+                (Datum::Int32(30), 3),
+                (Datum::Int32(30), 1),
+                (Datum::Int32(30), 2)
+            ])
+        );
     }
-}
 
-impl AnalyzedRegex {
-    pub fn new(s: &str) -> Result<Self, regex::Error> {
-        let r = regex::Regex::new(s)?;
-        // TODO(benesch): remove potentially dangerous usage of `as`.
-        #[allow(clippy::as_conversions)]
-        let descs: Vec<_> = r
-            .capture_names()
-            .enumerate()
-            // The first capture is the entire matched string.
-            // This will often not be useful, so skip it.
-            // If people want it they can just surround their
-            // entire regex in an explicit capture group.
-            .skip(1)
-            .map(|(i, name)| CaptureGroupDesc {
-                index: i as u32,
-                name: name.map(String::from),
-                // TODO -- we can do better.
-                // https://github.com/MaterializeInc/materialize/issues/1685
-                nullable: true,
+    #[test]
+    fn first_value_last_value_frames() {
+        let arena = RowArena::new();
+        // Each input is ((OriginalRow, InputValue), OrderByExpr), and each
+        // output is (Value, OriginalRow).
+        let datums = (1..=3)
+            .map(|row| {
+                arena.make_datum(|packer| {
+                    packer.push_list_with(|packer| {
+                        packer.push_list([Datum::Int32(row), Datum::Int32(row * 10)]);
+                        packer.push(Datum::Int32(row));
+                    })
+                })
             })
-            .collect();
-        Ok(Self(ReprRegex(r), descs))
+            .collect_vec();
+        let output = |values: &[i32]| {
+            let records = values.iter().zip(1..).map(|(value, row)| {
+                arena.make_datum(|packer| {
+                    packer.push_list([Datum::Int32(*value), Datum::Int32(row)])
+                })
+            });
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+        let order_by = vec![ColumnOrder {
+            column: 0,
+            desc: false,
+            nulls_last: false,
+        }];
+        let frame = |start_bound, end_bound| WindowFrame {
+            units: WindowFrameUnits::Rows,
+            start_bound,
+            end_bound,
+        };
+        let full_partition = frame(
+            WindowFrameBound::UnboundedPreceding,
+            WindowFrameBound::UnboundedFollowing,
+        );
+        let up_to_current = frame(
+            WindowFrameBound::UnboundedPreceding,
+            WindowFrameBound::CurrentRow,
+        );
+        let from_current = frame(
+            WindowFrameBound::CurrentRow,
+            WindowFrameBound::UnboundedFollowing,
+        );
+        let last_value = |window_frame| {
+            AggregateFunc::LastValue {
+                order_by: order_by.clone(),
+                window_frame,
+            }
+            .eval(datums.clone(), &arena)
+        };
+        let first_value = |window_frame| {
+            AggregateFunc::FirstValue {
+                order_by: order_by.clone(),
+                window_frame,
+            }
+            .eval(datums.clone(), &arena)
+        };
+
+        // A frame ending at the current row hides the rows that follow it.
+        assert_eq!(last_value(full_partition.clone()), output(&[30, 30, 30]));
+        assert_eq!(last_value(up_to_current.clone()), output(&[10, 20, 30]));
+        assert_eq!(last_value(from_current.clone()), output(&[30, 30, 30]));
+
+        // A frame starting at the current row hides the rows that precede it.
+        assert_eq!(first_value(full_partition), output(&[10, 10, 10]));
+        assert_eq!(first_value(up_to_current), output(&[10, 10, 10]));
+        assert_eq!(first_value(from_current), output(&[10, 20, 30]));
     }
-    pub fn capture_groups_len(&self) -> usize {
-        self.1.len()
+
+    #[test]
+    fn nth_value_frames() {
+        let arena = RowArena::new();
+        // Each input is ((OriginalRow, (InputValue, N)), OrderByExpr), and
+        // each output is (Value, OriginalRow). Rows 2 and 3 are peers.
+        let datums = |n: i32| {
+            [(1, 1), (2, 2), (3, 2), (4, 3)]
+                .into_iter()
+                .map(|(row, order)| {
+                    arena.make_datum(|packer| {
+                        packer.push_list_with(|packer| {
+                            packer.push_list_with(|packer| {
+                                packer.push(Datum::Int32(row));
+                                packer.push_list([Datum::Int32(row * 10), Datum::Int32(n)]);
+                            });
+                            packer.push(Datum::Int32(order));
+                        })
+                    })
+                })
+                .collect_vec()
+        };
+        let output = |values: &[Option<i32>]| {
+            let records = values.iter().zip(1..).map(|(value, row)| {
+                let value = value.map_or(Datum::Null, Datum::Int32);
+                arena.make_datum(|packer| packer.push_list([value, Datum::Int32(row)]))
+            });
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+        let nth_value = |units, start_bound, end_bound, n| {
+            AggregateFunc::NthValue {
+                order_by: vec![ColumnOrder {
+                    column: 0,
+                    desc: false,
+                    nulls_last: false,
+                }],
+                window_frame: WindowFrame {
+                    units,
+                    start_bound,
+                    end_bound,
+                },
+            }
+            .eval(datums(n), &arena)
+        };
+        // The default frame, RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW
+        let default_frame = |n| {
+            nth_value(
+                WindowFrameUnits::Range,
+                WindowFrameBound::UnboundedPreceding,
+                WindowFrameBound::CurrentRow,
+                n,
+            )
+        };
+
+        // The frame of the first row only contains that row, so there is no
+        // second row yet, as in PostgreSQL.
+        assert_eq!(
+            default_frame(2),
+            output(&[None, Some(20), Some(20), Some(20)])
+        );
+        // In RANGE mode, the frame extends to the last peer of the current
+        // row, while in ROWS mode it ends at the current row.
+        assert_eq!(
+            default_frame(3),
+            output(&[None, Some(30), Some(30), Some(30)])
+        );
+        assert_eq!(
+            nth_value(
+                WindowFrameUnits::Rows,
+                WindowFrameBound::UnboundedPreceding,
+                WindowFrameBound::CurrentRow,
+                3
+            ),
+            output(&[None, None, Some(30), Some(30)])
+        );
+        // A frame starting at the current row counts from it.
+        assert_eq!(
+            nth_value(
+                WindowFrameUnits::Rows,
+                WindowFrameBound::CurrentRow,
+                WindowFrameBound::UnboundedFollowing,
+                2
+            ),
+            output(&[Some(20), Some(30), Some(40), None])
+        );
+        // A frame that ends before the first row is empty.
+        assert_eq!(
+            nth_value(
+                WindowFrameUnits::Rows,
+                WindowFrameBound::UnboundedPreceding,
+                WindowFrameBound::OffsetPreceding(1),
+                1
+            ),
+            output(&[None, Some(10), Some(10), Some(10)])
+        );
     }
-    pub fn capture_groups_iter(&self) -> impl Iterator<Item = &CaptureGroupDesc> {
-        self.1.iter()
+
+    #[test]
+    fn lag_lead_offsets() {
+        let arena = RowArena::new();
+        // Each input is ((OriginalRow, (InputValue, Offset, Default)),
+        // OrderByExpr), and each output is (Value, OriginalRow).
+        let input = |row: i32, offset: Datum| {
+            arena.make_datum(|packer| {
+                packer.push_list_with(|packer| {
+                    packer.push_list_with(|packer| {
+                        packer.push(Datum::Int32(row));
+                        packer.push_list([Datum::Int32(row * 10), offset, Datum::Int32(-1)]);
+                    });
+                    packer.push(Datum::Int32(row));
+                })
+            })
+        };
+        let output = |values: &[i32]| {
+            let records = values.iter().zip(1..).map(|(value, row)| {
+                arena.make_datum(|packer| {
+                    packer.push_list([Datum::Int32(*value), Datum::Int32(row)])
+                })
+            });
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+        let order_by = vec![ColumnOrder {
+            column: 0,
+            desc: false,
+            nulls_last: false,
+        }];
+        let lag = AggregateFunc::LagLead {
+            order_by: order_by.clone(),
+            lag_lead: LagLeadType::Lag,
+            ignore_nulls: false,
+        };
+        let lead = AggregateFunc::LagLead {
+            order_by,
+            lag_lead: LagLeadType::Lead,
+            ignore_nulls: false,
+        };
+        let eval = |func: &AggregateFunc, offset: Datum| {
+            func.eval((1..=3).map(|row| input(row, offset)), &arena)
+        };
+
+        // Bigint offsets behave like integer offsets.
+        assert_eq!(eval(&lag, Datum::Int32(1)), output(&[-1, 10, 20]));
+        assert_eq!(eval(&lag, Datum::Int64(1)), output(&[-1, 10, 20]));
+        assert_eq!(eval(&lead, Datum::Int64(2)), output(&[30, -1, -1]));
+
+        // Negative offsets look in the opposite direction.
+        assert_eq!(eval(&lag, Datum::Int64(-1)), output(&[20, 30, -1]));
+        assert_eq!(eval(&lead, Datum::Int32(-1)), output(&[-1, 10, 20]));
+
+        // Offsets beyond the partition produce the default, even at the
+        // extremes of the offset type.
+        assert_eq!(eval(&lag, Datum::Int64(i64::MIN)), output(&[-1, -1, -1]));
+        assert_eq!(eval(&lead, Datum::Int64(i64::MIN)), output(&[-1, -1, -1]));
     }
-    pub fn inner(&self) -> &Regex {
-        &(self.0).0
+
+    #[test]
+    fn lag_lead_ignore_nulls() {
+        let arena = RowArena::new();
+        // Rows 2, 3 and 5 have null input values.
+        let values = [Some(1), None, None, Some(4), None, Some(6)];
+        let input = |row: i32, offset: i64| {
+            let value = match values[usize::try_from(row - 1).unwrap()] {
+                Some(value) => Datum::Int32(value),
+                None => Datum::Null,
+            };
+            arena.make_datum(|packer| {
+                packer.push_list_with(|packer| {
+                    packer.push_list_with(|packer| {
+                        packer.push(Datum::Int32(row));
+                        packer.push_list([value, Datum::Int64(offset), Datum::Int32(-1)]);
+                    });
+                    packer.push(Datum::Int32(row));
+                })
+            })
+        };
+        let output = |values: &[Option<i32>]| {
+            let records = values.iter().zip(1..).map(|(value, row)| {
+                let value = match value {
+                    Some(value) => Datum::Int32(*value),
+                    None => Datum::Null,
+                };
+                arena.make_datum(|packer| packer.push_list([value, Datum::Int32(row)]))
+            });
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+        let func = |lag_lead, ignore_nulls| AggregateFunc::LagLead {
+            order_by: vec![ColumnOrder {
+                column: 0,
+                desc: false,
+                nulls_last: false,
+            }],
+            lag_lead,
+            ignore_nulls,
+        };
+        let lag = func(LagLeadType::Lag, true);
+        let lead = func(LagLeadType::Lead, true);
+        let eval = |func: &AggregateFunc, offset: i64| {
+            func.eval((1..=6).map(|row| input(row, offset)), &arena)
+        };
+        let (d, n) = (Some(-1), None);
+
+        assert_eq!(
+            eval(&lag, 1),
+            output(&[d, Some(1), Some(1), Some(1), Some(4), Some(4)])
+        );
+        assert_eq!(eval(&lag, 2), output(&[d, d, d, d, Some(1), Some(1)]));
+        assert_eq!(
+            eval(&lead, 1),
+            output(&[Some(4), Some(4), Some(4), Some(6), Some(6), d])
+        );
+        assert_eq!(eval(&lead, 1), eval(&lag, -1));
+        assert_eq!(eval(&lead, 3), output(&[d, d, d, d, d, d]));
+        assert_eq!(eval(&lag, i64::MIN), output(&[d, d, d, d, d, d]));
+
+        // A zero offset reads the current row, null or not.
+        assert_eq!(eval(&lag, 0), output(&values));
+
+        // Without IGNORE NULLS, null input values are returned as they are.
+        assert_eq!(
+            eval(&func(LagLeadType::Lag, false), 1),
+            output(&[d, Some(1), n, n, Some(4), n])
+        );
     }
-}
 
-pub fn csv_extract(a: Datum, n_cols: usize) -> impl Iterator<Item = (Row, Diff)> + '_ {
-    let bytes = a.unwrap_str().as_bytes();
-    let mut row = Row::default();
-    let csv_reader = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .from_reader(bytes);
-    csv_reader.into_records().filter_map(move |res| match res {
-        Ok(sr) if sr.len() == n_cols => {
-            row.packer().extend(sr.iter().map(Datum::String));
-            Some((row.clone(), 1))
+    #[test]
+    fn lag_lead_output_type() {
+        let record = |fields: Vec<ScalarType>| ScalarType::Record {
+            fields: fields
+                .into_iter()
+                .map(|typ| (ColumnName::from("?column?"), typ.nullable(false)))
+                .collect(),
+            custom_id: None,
+        };
+        // ((OriginalRow, (InputValue, Offset, Default)), OrderByExpr)
+        let input_type = record(vec![
+            record(vec![
+                record(vec![ScalarType::String]),
+                record(vec![
+                    ScalarType::Int64,
+                    ScalarType::Int32,
+                    ScalarType::Int64,
+                ]),
+            ]),
+            ScalarType::String,
+        ])
+        .nullable(false);
+
+        // `lead` shares the output type of `lag`, apart from the column name.
+        let output_type = |lag_lead| {
+            AggregateFunc::LagLead {
+                order_by: vec![],
+                lag_lead,
+                ignore_nulls: false,
+            }
+            .output_type(input_type.clone())
+        };
+        let expected = |name| {
+            ScalarType::List {
+                element_type: Box::new(ScalarType::Record {
+                    fields: vec![
+                        (ColumnName::from(name), ScalarType::Int64.nullable(true)),
+                        (
+                            ColumnName::from("?record?"),
+                            record(vec![ScalarType::String]).nullable(false),
+                        ),
+                    ],
+                    custom_id: None,
+                }),
+                custom_id: None,
+            }
+            .nullable(false)
+        };
+        assert_eq!(output_type(LagLeadType::Lag), expected("?lag?"));
+        assert_eq!(output_type(LagLeadType::Lead), expected("?lead?"));
+    }
+
+    #[test]
+    fn nth_value() {
+        let arena = RowArena::new();
+        // Each input is ((OriginalRow, (InputValue, N)), OrderByExpr), and
+        // each output is (Value, OriginalRow).
+        let input = |row: i32, value: i32, n: Datum, order: i32| {
+            arena.make_datum(|packer| {
+                packer.push_list_with(|packer| {
+                    packer.push_list_with(|packer| {
+                        packer.push(Datum::Int32(row));
+                        packer.push_list([Datum::Int32(value), n]);
+                    });
+                    packer.push(Datum::Int32(order));
+                })
+            })
+        };
+        let output = |values: &[(Datum, i32)]| {
+            let records = values.iter().map(|(value, row)| {
+                arena.make_datum(|packer| packer.push_list([*value, Datum::Int32(*row)]))
+            });
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+        // With a frame that spans the whole partition, `n` addresses the same
+        // row for every row.
+        let func = AggregateFunc::NthValue {
+            order_by: vec![ColumnOrder {
+                column: 0,
+                desc: false,
+                nulls_last: false,
+            }],
+            window_frame: WindowFrame {
+                units: WindowFrameUnits::Rows,
+                start_bound: WindowFrameBound::UnboundedPreceding,
+                end_bound: WindowFrameBound::UnboundedFollowing,
+            },
+        };
+
+        // The inputs are deliberately not in ORDER BY order.
+        let datums = |n: Datum| vec![input(1, 20, n, 2), input(2, 30, n, 3), input(3, 10, n, 1)];
+        assert_eq!(
+            func.eval(datums(Datum::Int32(2)), &arena),
+            output(&[
+                (Datum::Int32(20), 3),
+                (Datum::Int32(20), 1),
+                (Datum::Int32(20), 2)
+            ]),
+        );
+        // An `n` that addresses no row produces null.
+        for n in [
+            Datum::Int32(4),
+            Datum::Int32(0),
+            Datum::Int32(-1),
+            Datum::Null,
+        ] {
+            assert_eq!(
+                func.eval(datums(n), &arena),
+                output(&[(Datum::Null, 3), (Datum::Null, 1), (Datum::Null, 2)]),
+            );
         }
-        _ => None,
-    })
-}
+    }
 
-pub fn repeat(a: Datum) -> Option<(Row, Diff)> {
-    let n = a.unwrap_int64();
-    if n != 0 {
-        Some((Row::default(), n))
-    } else {
-        None
+    #[test]
+    fn jsonb_each_sorted_by_key() {
+        let arena = RowArena::new();
+        let jsonb = Jsonb::from_str(r#"{"c": 3, "a": 1, "b": {"y": 2, "x": 1}}"#).unwrap();
+        let datums = [jsonb.as_ref().into_datum()];
+
+        for stringify in [false, true] {
+            let func = TableFunc::JsonbEach { stringify };
+            let keys: Vec<_> = func
+                .eval(&datums, &arena)
+                .unwrap()
+                .map(|(row, diff)| {
+                    assert_eq!(diff, 1);
+                    row.unpack_first().unwrap_str().to_owned()
+                })
+                .collect();
+            assert_eq!(keys, ["a", "b", "c"]);
+        }
     }
-}
 
-fn wrap<'a>(datums: &'a [Datum<'a>], width: usize) -> impl Iterator<Item = (Row, Diff)> + 'a {
-    datums.chunks(width).map(|chunk| (Row::pack(chunk), 1))
-}
+    #[test]
+    fn jsonb_text_variants_json_null() {
+        let arena = RowArena::new();
+        let object = Jsonb::from_str(r#"{"a": null, "b": "null"}"#).unwrap();
+        let array = Jsonb::from_str(r#"[null, "null"]"#).unwrap();
+        let values = |func: TableFunc, jsonb: &Jsonb| {
+            let datums = [jsonb.as_ref().into_datum()];
+            let column = func.output_arity() - 1;
+            func.eval(&datums, &arena)
+                .unwrap()
+                .map(|(row, _)| row.iter().nth(column).unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
 
-#[derive(
-    Arbitrary, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash, MzReflect,
-)]
-pub enum TableFunc {
-    JsonbEach {
-        stringify: bool,
-    },
-    JsonbObjectKeys,
-    JsonbArrayElements {
-        stringify: bool,
-    },
-    RegexpExtract(AnalyzedRegex),
-    CsvExtract(usize),
-    GenerateSeriesInt32,
-    GenerateSeriesInt64,
-    GenerateSeriesTimestamp,
-    GenerateSeriesTimestampTz,
-    Repeat,
-    UnnestArray {
-        el_typ: ScalarType,
-    },
-    UnnestList {
-        el_typ: ScalarType,
-    },
-    /// Given `n` input expressions, wraps them into `n / width` rows, each of
-    /// `width` columns.
-    ///
-    /// This function is not intended to be called directly by end users, but
-    /// is useful in the planning of e.g. VALUES clauses.
-    Wrap {
-        types: Vec<ColumnType>,
-        width: usize,
-    },
-    GenerateSubscriptsArray,
-}
+        // A JSON null is a non-null jsonb value, but stringifies to NULL rather
+        // than the string "null".
+        assert_eq!(
+            values(TableFunc::JsonbEach { stringify: false }, &object),
+            ["json_null", r#""null""#]
+        );
+        assert_eq!(
+            values(TableFunc::JsonbEach { stringify: true }, &object),
+            ["null", r#""null""#]
+        );
+        assert_eq!(
+            values(TableFunc::JsonbArrayElements { stringify: false }, &array),
+            ["json_null", r#""null""#]
+        );
+        assert_eq!(
+            values(TableFunc::JsonbArrayElements { stringify: true }, &array),
+            ["null", r#""null""#]
+        );
+
+        for (func, name) in [
+            (TableFunc::JsonbEach { stringify: false }, "jsonb_each"),
+            (TableFunc::JsonbEach { stringify: true }, "jsonb_each_text"),
+            (
+                TableFunc::JsonbArrayElements { stringify: false },
+                "jsonb_array_elements",
+            ),
+            (
+                TableFunc::JsonbArrayElements { stringify: true },
+                "jsonb_array_elements_text",
+            ),
+        ] {
+            let stringify = name.ends_with("_text");
+            let value_type = func.output_type().column_types.last().cloned().unwrap();
+            assert_eq!(value_type.nullable, stringify, "{func}");
+            assert_eq!(func.to_string(), name);
+        }
+    }
+
+    #[test]
+    fn jsonb_path_query() {
+        let arena = RowArena::new();
+        let jsonb = Jsonb::from_str(
+            r#"{
+                "a": {"b": [1, {"c": true}, [2, 3]]},
+                "d e": "f",
+                "g": [{"h": 4}, {"h": 5}, {}],
+                "i": [[{"h": 6}]],
+                "j\"k": 7
+            }"#,
+        )
+        .unwrap();
+        let query = |path: &str| {
+            let path = JsonPath::parse(path).unwrap();
+            TableFunc::JsonbPathQuery(path)
+                .eval(&[jsonb.as_ref().into_datum()], &arena)
+                .unwrap()
+                .map(|(row, diff)| {
+                    assert_eq!(diff, 1);
+                    JsonbRef::from_datum(row.unpack_first()).to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(query("$.a.b[1]"), [r#"{"c":true}"#]);
+        assert_eq!(query("$.a.b[*]"), ["1", r#"{"c":true}"#, "[2,3]"]);
+        assert_eq!(query("$.a.b[2][0]"), ["2"]);
+        assert_eq!(query(r#"$."d e""#), [r#""f""#]);
+        assert_eq!(query("$.g[*].h"), ["4", "5"]);
+        assert_eq!(query(" $ . a . b [ 0 ] "), ["1"]);
+        assert_eq!(query("$").len(), 1);
+        assert_eq!(query(r#"$."j\"k""#), ["7"]);
+        assert_eq!(query("lax $.a.b[0]"), ["1"]);
+        assert!(query("$.a.b[3]").is_empty());
+        assert!(query("$.missing").is_empty());
+
+        // Lax mode unwraps one level of array for member accesses, and wraps
+        // values that are not arrays for subscripts.
+        assert_eq!(query("$.a.b.c"), ["true"]);
+        assert_eq!(query("$.g.h"), ["4", "5"]);
+        assert!(query("$.i.h").is_empty());
+        assert_eq!(query("$.a[0].b[1].c"), ["true"]);
+        assert_eq!(query(r#"$."d e"[*]"#), [r#""f""#]);
+        assert!(query(r#"$."d e"[1]"#).is_empty());
+        assert!(query("$.a.b[0].c").is_empty());
+
+        for path in [
+            "",
+            "a.b",
+            "$.",
+            "$..a",
+            "$.a[",
+            "$.a[-1]",
+            "$.a[x]",
+            "$.\"a",
+            "$a",
+            r#"$."a\x""#,
+            "strict $.a",
+        ] {
+            assert!(JsonPath::parse(path).is_err(), "{path}");
+        }
+
+        for path in [
+            "$",
+            "$.a.b[0][*]",
+            r#"$."d e".f"#,
+            r#"$."j\"k""#,
+            r#"$."\\\n\t""#,
+        ] {
+            assert_eq!(JsonPath::parse(path).unwrap().to_string(), path);
+        }
+    }
+
+    #[test]
+    fn regexp_matches() {
+        let arena = RowArena::new();
+        let matches = |pattern: &str, input: &str, global: bool| {
+            let func = TableFunc::RegexpMatches {
+                regex: AnalyzedRegex::new(pattern).unwrap(),
+                global,
+            };
+            assert_eq!(func.output_arity(), 2);
+            func.eval(&[Datum::String(input)], &arena)
+                .unwrap()
+                .map(|(row, _)| {
+                    row.iter()
+                        .map(|d| (!d.is_null()).then(|| d.unwrap_str().to_owned()))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let s = |s: &str| Some(s.to_owned());
+
+        assert_eq!(
+            matches(r"(\d)(\w)?", "1a 2", true),
+            [vec![s("1"), s("a")], vec![s("2"), None]]
+        );
+        assert_eq!(matches(r"(\d)(\w)?", "1a 2", false), [vec![s("1"), s("a")]]);
+        assert!(matches(r"(\d)(\w)?", "abc", true).is_empty());
+
+        // Zero-width matches advance the search rather than repeating.
+        assert_eq!(
+            matches("(x*)(y?)", "ab", true),
+            [vec![s(""), s("")], vec![s(""), s("")], vec![s(""), s("")]]
+        );
+        assert_eq!(matches("(x*)(y?)", "ab", false), [vec![s(""), s("")]]);
+    }
+
+    #[test]
+    fn regexp_split_to_table() {
+        let arena = RowArena::new();
+        let func = TableFunc::RegexpSplitToTable(AnalyzedRegex::new(r"\s+").unwrap());
+        let split = |input: &str| {
+            func.eval(&[Datum::String(input)], &arena)
+                .unwrap()
+                .map(|(row, _)| row.unpack_first().unwrap_str().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            split("the quick  brown\tfox"),
+            ["the", "quick", "brown", "fox"]
+        );
+        assert_eq!(split(" a b "), ["", "a", "b", ""]);
+        assert_eq!(split("abc"), ["abc"]);
+        assert_eq!(split(""), [""]);
+
+        // Zero-length matches at either end or right after a match are ignored.
+        let split = |input: &str, regex: &str| {
+            TableFunc::RegexpSplitToTable(AnalyzedRegex::new(regex).unwrap())
+                .eval(&[Datum::String(input)], &arena)
+                .unwrap()
+                .map(|(row, _)| row.unpack_first().unwrap_str().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(split("abc", ""), ["a", "b", "c"]);
+        assert_eq!(split("baaac", "a*"), ["b", "c"]);
+        assert_eq!(split("a b", r"\s*"), ["a", "b"]);
+        assert_eq!(split("", ""), [""]);
+    }
+
+    #[test]
+    fn string_to_table() {
+        let arena = RowArena::new();
+        let split = |datums: &[Datum]| {
+            TableFunc::StringToTable
+                .eval(datums, &arena)
+                .unwrap()
+                .map(|(row, _)| {
+                    let datum = row.unpack_first();
+                    (!datum.is_null()).then(|| datum.unwrap_str().to_owned())
+                })
+                .collect::<Vec<_>>()
+        };
+        let s = |s: &str| Some(s.to_owned());
+        let (a, b, comma) = (
+            Datum::String("a,b,,c"),
+            Datum::String("xy"),
+            Datum::String(","),
+        );
+
+        assert_eq!(split(&[a, comma]), [s("a"), s("b"), s(""), s("c")]);
+        assert_eq!(
+            split(&[Datum::String(",a,"), comma]),
+            [s(""), s("a"), s("")]
+        );
+        // A NULL delimiter splits into characters, an empty one not at all.
+        assert_eq!(split(&[Datum::String("xé"), Datum::Null]), [s("x"), s("é")]);
+        assert_eq!(split(&[a, Datum::String("")]), [s("a,b,,c")]);
+        assert_eq!(split(&[b, Datum::String("")]), [s("xy")]);
+        // NULL and empty inputs produce no rows.
+        assert!(split(&[Datum::Null, comma]).is_empty());
+        assert!(split(&[Datum::String(""), comma]).is_empty());
+        assert!(split(&[Datum::String(""), Datum::Null]).is_empty());
+
+        // Fields equal to the null string become NULL.
+        assert_eq!(
+            split(&[a, comma, Datum::String("")]),
+            [s("a"), s("b"), None, s("c")]
+        );
+        assert_eq!(split(&[b, Datum::Null, Datum::String("y")]), [s("x"), None]);
+        assert_eq!(
+            split(&[a, comma, Datum::Null]),
+            [s("a"), s("b"), s(""), s("c")]
+        );
+    }
+
+    #[test]
+    fn unnest_with_ordinality() {
+        let arena = RowArena::new();
+        let elements = [Datum::Null, Datum::Int32(1), Datum::Null, Datum::Int32(2)];
+        let list = arena.make_datum(|packer| packer.push_list(elements));
+        let array = arena.make_datum(|packer| {
+            let dims = ArrayDimension {
+                lower_bound: 1,
+                length: elements.len(),
+            };
+            packer.push_array(&[dims], elements).unwrap()
+        });
+        let expected: Vec<_> = elements.into_iter().zip(1i64..).collect();
+
+        for (func, datum) in [
+            (
+                TableFunc::UnnestArray {
+                    el_typ: ScalarType::Int32,
+                    ordinality: true,
+                },
+                array,
+            ),
+            (
+                TableFunc::UnnestList {
+                    el_typ: ScalarType::Int32,
+                    ordinality: true,
+                },
+                list,
+            ),
+        ] {
+            assert_eq!(func.output_arity(), 2);
+            let typ = func.output_type();
+            assert_eq!(typ.column_types[1], ScalarType::Int64.nullable(false));
+
+            // Every call numbers its elements from 1, null elements included.
+            for _ in 0..2 {
+                let rows: Vec<_> = func
+                    .eval(&[datum], &arena)
+                    .unwrap()
+                    .map(|(row, _)| row)
+                    .collect();
+                let unpacked: Vec<_> = rows
+                    .iter()
+                    .map(|row| {
+                        let mut datums = row.iter();
+                        let element = datums.next().unwrap();
+                        (element, datums.next().unwrap().unwrap_int64())
+                    })
+                    .collect();
+                assert_eq!(unpacked, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn generate_series_default_step() {
+        let arena = RowArena::new();
+        let series = |func: TableFunc, datums: &[Datum]| {
+            func.eval(datums, &arena)
+                .unwrap()
+                .map(|(row, _)| row.unpack_first().to_string())
+                .collect::<Vec<_>>()
+        };
 
-impl RustType<ProtoTableFunc> for TableFunc {
-    fn into_proto(&self) -> ProtoTableFunc {
-        use proto_table_func::Kind;
-        use proto_table_func::ProtoWrap;
+        let (one, three) = (Datum::Int32(1), Datum::Int32(3));
+        assert_eq!(
+            series(TableFunc::GenerateSeriesInt32, &[one, three]),
+            ["1", "2", "3"]
+        );
+        assert!(series(TableFunc::GenerateSeriesInt32, &[three, one]).is_empty());
+
+        let (one, three) = (Datum::Int64(1), Datum::Int64(3));
+        assert_eq!(
+            series(TableFunc::GenerateSeriesInt64, &[one, three]),
+            ["1", "2", "3"]
+        );
+        assert!(series(TableFunc::GenerateSeriesInt64, &[three, one]).is_empty());
+    }
 
-        ProtoTableFunc {
-            kind: Some(match self {
-                TableFunc::JsonbEach { stringify } => Kind::JsonbEach(*stringify),
-                TableFunc::JsonbObjectKeys => Kind::JsonbObjectKeys(()),
-                TableFunc::JsonbArrayElements { stringify } => Kind::JsonbArrayElements(*stringify),
-                TableFunc::RegexpExtract(x) => Kind::RegexpExtract(x.into_proto()),
-                TableFunc::CsvExtract(x) => Kind::CsvExtract(x.into_proto()),
-                TableFunc::GenerateSeriesInt32 => Kind::GenerateSeriesInt32(()),
-                TableFunc::GenerateSeriesInt64 => Kind::GenerateSeriesInt64(()),
-                TableFunc::GenerateSeriesTimestamp => Kind::GenerateSeriesTimestamp(()),
-                TableFunc::GenerateSeriesTimestampTz => Kind::GenerateSeriesTimestampTz(()),
-                TableFunc::Repeat => Kind::Repeat(()),
-                TableFunc::UnnestArray { el_typ } => Kind::UnnestArray(el_typ.into_proto()),
-                TableFunc::UnnestList { el_typ } => Kind::UnnestList(el_typ.into_proto()),
-                TableFunc::Wrap { types, width } => Kind::Wrap(ProtoWrap {
-                    types: types.into_proto(),
-                    width: width.into_proto(),
-                }),
-                TableFunc::GenerateSubscriptsArray => Kind::GenerateSubscriptsArray(()),
-            }),
+    #[test]
+    fn generate_series_numeric() {
+        let arena = RowArena::new();
+        let n = |s: &str| Datum::from(Numeric::from_str(s).unwrap());
+        let series = |start, stop, step| {
+            TableFunc::GenerateSeriesNumeric
+                .eval(&[n(start), n(stop), n(step)], &arena)
+                .map(|rows| {
+                    rows.map(|(row, _)| row.unpack_first().unwrap_numeric().0.to_string())
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        assert_eq!(series("1", "2", "0.5").unwrap(), ["1", "1.5", "2"]);
+        assert_eq!(series("0.1", "0.4", "0.2").unwrap(), ["0.1", "0.3"]);
+        assert_eq!(series("2", "1", "-0.5").unwrap(), ["2", "1.5", "1"]);
+        assert!(series("2", "1", "0.5").unwrap().is_empty());
+        assert!(series("1", "2", "-0.5").unwrap().is_empty());
+        assert_eq!(series("1", "1", "-1").unwrap(), ["1"]);
+
+        // A step that no longer changes the value ends the series, rather
+        // than repeating the value forever.
+        let start = "99999999999999999999999999999999999999";
+        assert_eq!(series(start, "1e39", "0.1").unwrap().len(), 11);
+
+        for (start, stop, step) in [
+            ("1", "2", "0"),
+            ("NaN", "2", "1"),
+            ("1", "2", "NaN"),
+            ("1e30", "2e30", "1e-20"),
+        ] {
+            match series(start, stop, step) {
+                Err(EvalError::InvalidParameterValue(_)) => {}
+                res => panic!("unexpected result for ({start}, {stop}, {step}): {res:?}"),
+            }
         }
     }
 
-    fn from_proto(proto: ProtoTableFunc) -> Result<Self, TryFromProtoError> {
-        use proto_table_func::Kind;
+    #[test]
+    fn repeat_negative_counts() {
+        let arena = RowArena::new();
+        let repeat = |n: i64| {
+            TableFunc::Repeat
+                .eval(&[Datum::Int64(n)], &arena)
+                .unwrap()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(repeat(3), vec![(Row::default(), 3)]);
+        assert_eq!(repeat(0), vec![]);
+        // Negative counts are retractions, not errors.
+        assert_eq!(repeat(-2), vec![(Row::default(), -2)]);
+    }
 
-        let kind = proto
-            .kind
-            .ok_or_else(|| TryFromProtoError::missing_field("ProtoTableFunc::Kind"))?;
+    #[test]
+    fn generate_subscripts_reverse() {
+        let arena = RowArena::new();
+        let array = |dims: &[ArrayDimension], elements: &[i32]| {
+            arena.make_datum(|packer| {
+                packer
+                    .push_array(dims, elements.iter().map(|e| Datum::Int32(*e)))
+                    .unwrap()
+            })
+        };
+        let subscripts = |array: Datum, dim: i32, reverse: Option<bool>| {
+            let mut datums = vec![array, Datum::Int32(dim)];
+            datums.extend(reverse.map(Datum::from));
+            TableFunc::GenerateSubscriptsArray
+                .eval(&datums, &arena)
+                .unwrap()
+                .map(|(row, diff)| {
+                    assert_eq!(diff, 1);
+                    row.unpack_first().unwrap_int32()
+                })
+                .collect::<Vec<_>>()
+        };
 
-        Ok(match kind {
-            Kind::JsonbEach(stringify) => TableFunc::JsonbEach { stringify },
-            Kind::JsonbObjectKeys(()) => TableFunc::JsonbObjectKeys,
-            Kind::JsonbArrayElements(stringify) => TableFunc::JsonbArrayElements { stringify },
-            Kind::RegexpExtract(x) => TableFunc::RegexpExtract(x.into_rust()?),
-            Kind::CsvExtract(x) => TableFunc::CsvExtract(x.into_rust()?),
-            Kind::GenerateSeriesInt32(()) => TableFunc::GenerateSeriesInt32,
-            Kind::GenerateSeriesInt64(()) => TableFunc::GenerateSeriesInt64,
-            Kind::GenerateSeriesTimestamp(()) => TableFunc::GenerateSeriesTimestamp,
-            Kind::GenerateSeriesTimestampTz(()) => TableFunc::GenerateSeriesTimestampTz,
-            Kind::Repeat(()) => TableFunc::Repeat,
-            Kind::UnnestArray(x) => TableFunc::UnnestArray {
-                el_typ: x.into_rust()?,
-            },
-            Kind::UnnestList(x) => TableFunc::UnnestList {
-                el_typ: x.into_rust()?,
-            },
-            Kind::Wrap(x) => TableFunc::Wrap {
-                width: x.width.into_rust()?,
-                types: x.types.into_rust()?,
-            },
-            Kind::GenerateSubscriptsArray(()) => TableFunc::GenerateSubscriptsArray,
-        })
+        let two_by_three = array(
+            &[
+                ArrayDimension {
+                    lower_bound: 1,
+                    length: 2,
+                },
+                ArrayDimension {
+                    lower_bound: 1,
+                    length: 3,
+                },
+            ],
+            &[1, 2, 3, 4, 5, 6],
+        );
+        assert_eq!(subscripts(two_by_three, 1, None), [1, 2]);
+        assert_eq!(subscripts(two_by_three, 1, Some(false)), [1, 2]);
+        assert_eq!(subscripts(two_by_three, 1, Some(true)), [2, 1]);
+        assert_eq!(subscripts(two_by_three, 2, Some(false)), [1, 2, 3]);
+        assert_eq!(subscripts(two_by_three, 2, Some(true)), [3, 2, 1]);
+        for reverse in [false, true] {
+            assert!(subscripts(two_by_three, 3, Some(reverse)).is_empty());
+            assert!(subscripts(two_by_three, 0, Some(reverse)).is_empty());
+        }
+
+        let shifted = array(
+            &[ArrayDimension {
+                lower_bound: 2,
+                length: 3,
+            }],
+            &[1, 2, 3],
+        );
+        assert_eq!(subscripts(shifted, 1, Some(false)), [2, 3, 4]);
+        assert_eq!(subscripts(shifted, 1, Some(true)), [4, 3, 2]);
+
+        let empty = array(&[], &[]);
+        for reverse in [false, true] {
+            assert!(subscripts(empty, 1, Some(reverse)).is_empty());
+        }
     }
-}
 
-impl TableFunc {
-    pub fn eval<'a>(
-        &'a self,
-        datums: &'a [Datum<'a>],
-        temp_storage: &'a RowArena,
-    ) -> Result<Box<dyn Iterator<Item = (Row, Diff)> + 'a>, EvalError> {
-        if self.empty_on_null_input() && datums.iter().any(|d| d.is_null()) {
-            return Ok(Box::new(vec![].into_iter()));
+    #[test]
+    fn jsonb_to_recordset() {
+        let arena = RowArena::new();
+        let func = TableFunc::jsonb_to_recordset(vec![
+            (ColumnName::from("a"), ScalarType::Int32),
+            (ColumnName::from("b"), ScalarType::String),
+            (ColumnName::from("c"), ScalarType::Jsonb),
+        ])
+        .unwrap();
+        assert_eq!(func.output_arity(), 3);
+        assert_eq!(
+            func.output_type().column_types,
+            vec![
+                ScalarType::Int32.nullable(true),
+                ScalarType::String.nullable(true),
+                ScalarType::Jsonb.nullable(true),
+            ]
+        );
+
+        let eval = |json: &str| {
+            let jsonb = Jsonb::from_str(json).unwrap();
+            func.eval(&[jsonb.as_ref().into_datum()], &arena)
+                .unwrap()
+                .map(|(row, diff)| {
+                    assert_eq!(diff, 1);
+                    row.unpack().iter().map(|d| d.to_string()).join(", ")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            eval(r#"[{"a": 1, "b": "x", "c": [1]}, {"a": "2", "b": 3}, {"c": null}, 4]"#),
+            [
+                r#"1, "x", [1]"#,
+                r#"2, "3", null"#,
+                "null, null, null",
+                "null, null, null",
+            ]
+        );
+        // Values that cannot be converted to the column type become NULL.
+        assert_eq!(
+            eval(r#"[{"a": "x", "b": {"d": true}}]"#),
+            [r#"null, "{\"d\":true}", null"#]
+        );
+        // A value that is not an array yields no rows.
+        assert!(eval(r#"{"a": 1}"#).is_empty());
+        assert!(eval("1").is_empty());
+
+        let func = TableFunc::jsonb_to_recordset(vec![
+            (ColumnName::from("t"), ScalarType::Time),
+            (ColumnName::from("u"), ScalarType::UInt64),
+            (ColumnName::from("by"), ScalarType::Bytes),
+            (
+                ColumnName::from("ch"),
+                ScalarType::Char {
+                    length: Some(CharLength::try_from(3).unwrap()),
+                },
+            ),
+            (
+                ColumnName::from("vc"),
+                ScalarType::VarChar {
+                    max_length: Some(VarCharMaxLength::try_from(2).unwrap()),
+                },
+            ),
+            (ColumnName::from("o"), ScalarType::Oid),
+            (ColumnName::from("mz"), ScalarType::MzTimestamp),
+        ])
+        .unwrap();
+        let jsonb = Jsonb::from_str(
+            r#"[
+                {"t": "01:02:03", "u": 18446744073709551615, "by": "\\x0102",
+                 "ch": "ab", "vc": "ab", "o": 23, "mz": "12"},
+                {"u": -1, "ch": "abcd", "vc": "abc", "o": "x"}
+            ]"#,
+        )
+        .unwrap();
+        let rows = func
+            .eval(&[jsonb.as_ref().into_datum()], &arena)
+            .unwrap()
+            .map(|(row, _)| row)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows[0].unpack(),
+            vec![
+                Datum::Time(NaiveTime::from_hms_opt(1, 2, 3).unwrap()),
+                Datum::UInt64(u64::MAX),
+                Datum::Bytes(&[1, 2]),
+                Datum::String("ab "),
+                Datum::String("ab"),
+                Datum::UInt32(23),
+                Datum::MzTimestamp(12.into()),
+            ]
+        );
+        // Out of range values and strings too long for their type become NULL.
+        assert_eq!(rows[1].unpack(), vec![Datum::Null; 7]);
+
+        // Column types that jsonb values cannot be converted to are rejected.
+        for typ in [
+            ScalarType::Array(Box::new(ScalarType::Int32)),
+            ScalarType::Int2Vector,
+            ScalarType::RegClass,
+        ] {
+            assert!(matches!(
+                TableFunc::jsonb_to_recordset(vec![(ColumnName::from("a"), typ)]),
+                Err(EvalError::Unsupported { .. })
+            ));
         }
-        match self {
-            TableFunc::JsonbEach { stringify } => {
-                Ok(Box::new(jsonb_each(datums[0], temp_storage, *stringify)))
-            }
-            TableFunc::JsonbObjectKeys => Ok(Box::new(jsonb_object_keys(datums[0]))),
-            TableFunc::JsonbArrayElements { stringify } => Ok(Box::new(jsonb_array_elements(
-                datums[0],
-                temp_storage,
-                *stringify,
-            ))),
-            TableFunc::RegexpExtract(a) => Ok(Box::new(regexp_extract(datums[0], a).into_iter())),
-            TableFunc::CsvExtract(n_cols) => Ok(Box::new(csv_extract(datums[0], *n_cols))),
-            TableFunc::GenerateSeriesInt32 => {
-                let res = generate_series(
-                    datums[0].unwrap_int32(),
-                    datums[1].unwrap_int32(),
-                    datums[2].unwrap_int32(),
-                )?;
-                Ok(Box::new(res))
-            }
-            TableFunc::GenerateSeriesInt64 => {
-                let res = generate_series(
-                    datums[0].unwrap_int64(),
-                    datums[1].unwrap_int64(),
-                    datums[2].unwrap_int64(),
-                )?;
-                Ok(Box::new(res))
-            }
-            TableFunc::GenerateSeriesTimestamp => {
-                fn pass_through<'a>(d: CheckedTimestamp<NaiveDateTime>) -> Datum<'a> {
-                    Datum::from(d)
-                }
-                let res = generate_series_ts(
-                    datums[0].unwrap_timestamp(),
-                    datums[1].unwrap_timestamp(),
-                    datums[2].unwrap_interval(),
-                    pass_through,
-                )?;
-                Ok(Box::new(res))
-            }
-            TableFunc::GenerateSeriesTimestampTz => {
-                fn gen_ts_tz<'a>(d: CheckedTimestamp<DateTime<Utc>>) -> Datum<'a> {
-                    Datum::from(d)
-                }
-                let res = generate_series_ts(
-                    datums[0].unwrap_timestamptz(),
-                    datums[1].unwrap_timestamptz(),
-                    datums[2].unwrap_interval(),
-                    gen_ts_tz,
-                )?;
-                Ok(Box::new(res))
-            }
-            TableFunc::GenerateSubscriptsArray => {
-                generate_subscripts_array(datums[0], datums[1].unwrap_int32())
+    }
+
+    #[test]
+    fn unnest_arrays() {
+        let arena = RowArena::new();
+        let func = TableFunc::UnnestArrays {
+            el_typs: vec![ScalarType::Int32, ScalarType::Int32, ScalarType::Int32],
+        };
+        assert_eq!(func.output_arity(), 3);
+        assert_eq!(
+            func.output_type().column_types,
+            vec![ScalarType::Int32.nullable(true); 3]
+        );
+
+        let datums = [
+            int32_array(&arena, &[1, 2]),
+            int32_array(&arena, &[3]),
+            Datum::Null,
+        ];
+        let rows: Vec<_> = func
+            .eval(&datums, &arena)
+            .unwrap()
+            .map(|(row, diff)| {
+                assert_eq!(diff, 1);
+                row.unpack().iter().map(|d| d.to_string()).join(", ")
+            })
+            .collect();
+        assert_eq!(rows, ["1, 3, null", "2, null, null"]);
+
+        let datums = [
+            int32_array(&arena, &[]),
+            Datum::Null,
+            int32_array(&arena, &[]),
+        ];
+        assert_eq!(func.eval(&datums, &arena).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn cross_product() {
+        let arena = RowArena::new();
+        let string_array = |elements: &[&str]| {
+            arena.make_datum(|packer| {
+                packer
+                    .push_array(
+                        &[ArrayDimension {
+                            lower_bound: 1,
+                            length: elements.len(),
+                        }],
+                        elements.iter().map(|e| Datum::String(e)),
+                    )
+                    .unwrap()
+            })
+        };
+        let func = |max_rows| TableFunc::CrossProduct {
+            el_typs: vec![ScalarType::Int32, ScalarType::String],
+            max_rows,
+        };
+        assert_eq!(func(4).output_arity(), 2);
+        assert_eq!(
+            func(4).output_type().column_types,
+            vec![
+                ScalarType::Int32.nullable(true),
+                ScalarType::String.nullable(true)
+            ]
+        );
+
+        // The elements of the last array vary fastest.
+        let datums = [int32_array(&arena, &[1, 2]), string_array(&["a", "b"])];
+        let rows: Vec<_> = func(4)
+            .eval(&datums, &arena)
+            .unwrap()
+            .map(|(row, diff)| {
+                assert_eq!(diff, 1);
+                row.unpack().iter().map(|d| d.to_string()).join(", ")
+            })
+            .collect();
+        assert_eq!(rows, ["1, a", "1, b", "2, a", "2, b"]);
+
+        let datums = [int32_array(&arena, &[1, 2]), string_array(&[])];
+        assert_eq!(func(4).eval(&datums, &arena).unwrap().count(), 0);
+
+        let datums = [int32_array(&arena, &[1, 2]), string_array(&["a", "b"])];
+        assert!(matches!(
+            func(3).eval(&datums, &arena),
+            Err(EvalError::InvalidParameterValue(_))
+        ));
+    }
+
+    #[test]
+    fn unnest_wrong_type() {
+        let arena = RowArena::new();
+        let list = arena.make_datum(|packer| packer.push_list([Datum::Int32(1), Datum::Int32(2)]));
+        let unnest_array = TableFunc::UnnestArray {
+            el_typ: ScalarType::Int32,
+            ordinality: false,
+        };
+        let unnest_list = TableFunc::UnnestList {
+            el_typ: ScalarType::Int32,
+            ordinality: false,
+        };
+
+        let elements: Vec<_> = unnest_list
+            .eval(&[list], &arena)
+            .unwrap()
+            .map(|(row, _)| row.unpack_first().unwrap_int32())
+            .collect();
+        assert_eq!(elements, [1, 2]);
+
+        // Datums of the wrong type are errors, not panics.
+        for (func, datum) in [(&unnest_array, list), (&unnest_list, Datum::Int32(1))] {
+            let datums = [datum];
+            match func.eval(&datums, &arena) {
+                Err(EvalError::Internal(msg)) => assert!(msg.contains("non-"), "{msg}"),
+                Err(e) => panic!("unexpected error: {e}"),
+                Ok(_) => panic!("{func} of {datum:?} succeeded"),
             }
-            TableFunc::Repeat => Ok(Box::new(repeat(datums[0]).into_iter())),
-            TableFunc::UnnestArray { .. } => Ok(Box::new(unnest_array(datums[0]))),
-            TableFunc::UnnestList { .. } => Ok(Box::new(unnest_list(datums[0]))),
-            TableFunc::Wrap { width, .. } => Ok(Box::new(wrap(datums, *width))),
         }
     }
 
-    pub fn output_type(&self) -> RelationType {
-        let (column_types, keys) = match self {
-            TableFunc::JsonbEach { stringify: true } => {
-                let column_types = vec![
-                    ScalarType::String.nullable(false),
-                    ScalarType::String.nullable(true),
-                ];
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::JsonbEach { stringify: false } => {
-                let column_types = vec![
-                    ScalarType::String.nullable(false),
-                    ScalarType::Jsonb.nullable(false),
-                ];
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::JsonbObjectKeys => {
-                let column_types = vec![ScalarType::String.nullable(false)];
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::JsonbArrayElements { stringify: true } => {
-                let column_types = vec![ScalarType::String.nullable(true)];
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::JsonbArrayElements { stringify: false } => {
-                let column_types = vec![ScalarType::Jsonb.nullable(false)];
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::RegexpExtract(a) => {
-                let column_types = a
-                    .capture_groups_iter()
-                    .map(|cg| ScalarType::String.nullable(cg.nullable))
-                    .collect();
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::CsvExtract(n_cols) => {
-                let column_types = iter::repeat(ScalarType::String.nullable(false))
-                    .take(*n_cols)
-                    .collect();
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::GenerateSeriesInt32 => {
-                let column_types = vec![ScalarType::Int32.nullable(false)];
-                let keys = vec![vec![0]];
-                (column_types, keys)
-            }
-            TableFunc::GenerateSeriesInt64 => {
-                let column_types = vec![ScalarType::Int64.nullable(false)];
-                let keys = vec![vec![0]];
-                (column_types, keys)
-            }
-            TableFunc::GenerateSeriesTimestamp => {
-                let column_types = vec![ScalarType::Timestamp.nullable(false)];
-                let keys = vec![vec![0]];
-                (column_types, keys)
-            }
-            TableFunc::GenerateSeriesTimestampTz => {
-                let column_types = vec![ScalarType::TimestampTz.nullable(false)];
-                let keys = vec![vec![0]];
-                (column_types, keys)
-            }
-            TableFunc::GenerateSubscriptsArray => {
-                let column_types = vec![ScalarType::Int32.nullable(false)];
-                let keys = vec![vec![0]];
-                (column_types, keys)
-            }
-            TableFunc::Repeat => {
-                let column_types = vec![];
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::UnnestArray { el_typ } => {
-                let column_types = vec![el_typ.clone().nullable(true)];
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::UnnestList { el_typ } => {
-                let column_types = vec![el_typ.clone().nullable(true)];
-                let keys = vec![];
-                (column_types, keys)
-            }
-            TableFunc::Wrap { types, .. } => {
-                let column_types = types.clone();
-                let keys = vec![];
-                (column_types, keys)
-            }
+    #[test]
+    fn ranking_output() {
+        let arena = RowArena::new();
+        let order_by = vec![ColumnOrder {
+            column: 0,
+            desc: false,
+            nulls_last: false,
+        }];
+        // Each input is ([OriginalRow], OrderByExpr), with ties among the
+        // ORDER BY expressions.
+        let datums: Vec<_> = [2, 1, 3, 2, 1]
+            .into_iter()
+            .zip(0..)
+            .map(|(key, row)| {
+                arena.make_datum(|packer| {
+                    packer.push_list_with(|packer| {
+                        packer.push_list([Datum::Int32(row)]);
+                        packer.push(Datum::Int32(key));
+                    })
+                })
+            })
+            .collect();
+        // Packs the expected output the way it was packed before ranks were
+        // streamed into the output list, one (rank, original row) datum at a
+        // time.
+        let expected = |ranked: &[(i64, i32)]| {
+            let records: Vec<_> = ranked
+                .iter()
+                .map(|(rank, row)| {
+                    arena.make_datum(|packer| {
+                        packer.push_list(vec![Datum::Int64(*rank), Datum::Int32(*row)])
+                    })
+                })
+                .collect();
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+
+        let row_number = AggregateFunc::RowNumber {
+            order_by: order_by.clone(),
+        };
+        assert_eq!(
+            row_number.eval(datums.iter().copied(), &arena),
+            expected(&[(1, 1), (2, 4), (3, 0), (4, 3), (5, 2)]),
+        );
+        let dense_rank = AggregateFunc::DenseRank {
+            order_by: order_by.clone(),
+        };
+        assert_eq!(
+            dense_rank.eval(datums.iter().copied(), &arena),
+            expected(&[(1, 1), (1, 4), (2, 0), (2, 3), (3, 2)]),
+        );
+        let rank = AggregateFunc::Rank { order_by };
+        assert_eq!(
+            rank.eval(datums.iter().copied(), &arena),
+            expected(&[(1, 1), (1, 4), (3, 0), (3, 3), (5, 2)]),
+        );
+    }
+
+    #[test]
+    fn ntile() {
+        let arena = RowArena::new();
+        let func = AggregateFunc::Ntile {
+            order_by: vec![ColumnOrder {
+                column: 0,
+                desc: false,
+                nulls_last: false,
+            }],
+        };
+        // Each input is ((OriginalRow, N), OrderByExpr), and each output is
+        // (Bucket, OriginalRow). The inputs are fed in reverse ORDER BY order.
+        let buckets = |n: Datum| {
+            let datums = (0..10).rev().map(|row| {
+                arena.make_datum(|packer| {
+                    packer.push_list_with(|packer| {
+                        packer.push_list([Datum::Int32(row), n]);
+                        packer.push(Datum::Int32(row));
+                    })
+                })
+            });
+            func.eval(datums, &arena)
+                .unwrap_list()
+                .iter()
+                .zip(0..)
+                .map(|(record, row)| {
+                    let mut record = record.unwrap_list().iter();
+                    let bucket = record.next().unwrap();
+                    assert_eq!(record.next().unwrap(), Datum::Int32(row));
+                    bucket
+                })
+                .collect::<Vec<_>>()
         };
 
-        if !keys.is_empty() {
-            RelationType::new(column_types).with_keys(keys)
-        } else {
-            RelationType::new(column_types)
+        // The remainder of the ten rows goes to the first bucket.
+        assert_eq!(
+            buckets(Datum::Int32(3)),
+            [1, 1, 1, 1, 2, 2, 2, 3, 3, 3].map(Datum::Int64),
+        );
+        // More buckets than rows leaves the trailing buckets empty.
+        assert_eq!(
+            buckets(Datum::Int32(12)),
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10].map(Datum::Int64),
+        );
+        for n in [Datum::Int32(0), Datum::Int32(-1), Datum::Null] {
+            assert_eq!(buckets(n), [Datum::Null; 10]);
         }
     }
 
-    pub fn output_arity(&self) -> usize {
-        match self {
-            TableFunc::JsonbEach { .. } => 2,
-            TableFunc::JsonbObjectKeys => 1,
-            TableFunc::JsonbArrayElements { .. } => 1,
-            TableFunc::RegexpExtract(a) => a.capture_groups_len(),
-            TableFunc::CsvExtract(n_cols) => *n_cols,
-            TableFunc::GenerateSeriesInt32 => 1,
-            TableFunc::GenerateSeriesInt64 => 1,
-            TableFunc::GenerateSeriesTimestamp => 1,
-            TableFunc::GenerateSeriesTimestampTz => 1,
-            TableFunc::GenerateSubscriptsArray => 1,
-            TableFunc::Repeat => 0,
-            TableFunc::UnnestArray { .. } => 1,
-            TableFunc::UnnestList { .. } => 1,
-            TableFunc::Wrap { width, .. } => *width,
+    #[test]
+    fn rank_with_gaps() {
+        let arena = RowArena::new();
+        // Each input is ([OriginalRow], OrderByExpr), and each output is
+        // (Rank, OriginalRow).
+        let input = |row: i32, order: i32| {
+            arena.make_datum(|packer| {
+                packer.push_list_with(|packer| {
+                    packer.push_list([Datum::Int32(row)]);
+                    packer.push(Datum::Int32(order));
+                })
+            })
+        };
+        let output = |values: &[(i64, i32)]| {
+            let records = values.iter().map(|(rank, row)| {
+                arena.make_datum(|packer| {
+                    packer.push_list([Datum::Int64(*rank), Datum::Int32(*row)])
+                })
+            });
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+        let order_by = vec![ColumnOrder {
+            column: 0,
+            desc: false,
+            nulls_last: false,
+        }];
+
+        // Three rows tie for the first rank, so the next row is fourth.
+        let datums = vec![
+            input(1, 10),
+            input(2, 10),
+            input(3, 10),
+            input(4, 20),
+            input(5, 20),
+            input(6, 30),
+        ];
+        let func = AggregateFunc::Rank {
+            order_by: order_by.clone(),
+        };
+        assert_eq!(
+            func.eval(datums.clone(), &arena),
+            output(&[(1, 1), (1, 2), (1, 3), (4, 4), (4, 5), (6, 6)]),
+        );
+        let func = AggregateFunc::DenseRank { order_by };
+        assert_eq!(
+            func.eval(datums, &arena),
+            output(&[(1, 1), (1, 2), (1, 3), (2, 4), (2, 5), (3, 6)]),
+        );
+    }
+
+    #[test]
+    fn chunked_sums_match_reference() {
+        // The reference sums every non-null value as an i128.
+        fn reference(values: impl Iterator<Item = i128>) -> Option<i128> {
+            values.fold(None, |sum, x| Some(sum.unwrap_or(0) + x))
+        }
+
+        let arena = RowArena::new();
+        let lens = [
+            0,
+            1,
+            PRIMITIVE_CHUNK_SIZE - 1,
+            PRIMITIVE_CHUNK_SIZE,
+            PRIMITIVE_CHUNK_SIZE + 1,
+            3 * PRIMITIVE_CHUNK_SIZE + 7,
+        ];
+        // Every `null_every`th datum is null, where 1 makes them all null.
+        for (len, null_every) in lens.into_iter().cartesian_product([1, 2, 7, usize::MAX]) {
+            let is_null = |i: usize| i % null_every == null_every - 1;
+
+            // The extremes of each type make any overflow within a chunk
+            // visible.
+            let int32s: Vec<_> = (0..len)
+                .map(|i| match i % 3 {
+                    _ if is_null(i) => None,
+                    0 => Some(i32::MAX),
+                    1 => Some(i32::MIN),
+                    _ => Some(i32::MAX - i32::try_from(i).unwrap()),
+                })
+                .collect();
+            let int64s: Vec<_> = (0..len)
+                .map(|i| match i % 3 {
+                    _ if is_null(i) => None,
+                    0 => Some(i64::MAX),
+                    1 => Some(i64::MIN + 1),
+                    _ => Some(i64::MAX - i64::try_from(i).unwrap()),
+                })
+                .collect();
+
+            let datums: Vec<_> = int32s
+                .iter()
+                .map(|v| v.map_or(Datum::Null, Datum::Int32))
+                .collect();
+            let expected = reference(int32s.iter().flatten().map(|x| i128::from(*x)));
+            assert_eq!(
+                AggregateFunc::SumInt32.eval(datums.iter().copied(), &arena),
+                expected.map_or(Datum::Null, |x| Datum::Int64(i64::try_from(x).unwrap())),
+                "sum of {len} int32s with every {null_every}th null",
+            );
+
+            let datums: Vec<_> = int64s
+                .iter()
+                .map(|v| v.map_or(Datum::Null, Datum::Int64))
+                .collect();
+            let expected = reference(int64s.iter().flatten().map(|x| i128::from(*x)));
+            assert_eq!(
+                AggregateFunc::SumInt64.eval(datums.iter().copied(), &arena),
+                expected.map_or(Datum::Null, Datum::from),
+                "sum of {len} int64s with every {null_every}th null",
+            );
+
+            let non_null = int64s.iter().flatten().count();
+            assert_eq!(
+                AggregateFunc::Count.eval(datums.iter().copied(), &arena),
+                Datum::Int64(i64::try_from(non_null).unwrap()),
+                "count of {len} datums with every {null_every}th null",
+            );
         }
     }
 
-    pub fn empty_on_null_input(&self) -> bool {
-        match self {
-            TableFunc::JsonbEach { .. }
-            | TableFunc::JsonbObjectKeys
-            | TableFunc::JsonbArrayElements { .. }
-            | TableFunc::GenerateSeriesInt32
-            | TableFunc::GenerateSeriesInt64
-            | TableFunc::GenerateSeriesTimestamp
-            | TableFunc::GenerateSeriesTimestampTz
-            | TableFunc::GenerateSubscriptsArray
-            | TableFunc::RegexpExtract(_)
-            | TableFunc::CsvExtract(_)
-            | TableFunc::Repeat
-            | TableFunc::UnnestArray { .. }
-            | TableFunc::UnnestList { .. } => true,
-            TableFunc::Wrap { .. } => false,
+    #[test]
+    fn percent_rank_cume_dist() {
+        let arena = RowArena::new();
+        // Each input is ([OriginalRow], OrderByExpr), and each output is
+        // (Value, OriginalRow).
+        let input = |row: i32, order: i32| {
+            arena.make_datum(|packer| {
+                packer.push_list_with(|packer| {
+                    packer.push_list([Datum::Int32(row)]);
+                    packer.push(Datum::Int32(order));
+                })
+            })
+        };
+        let output = |values: &[(f64, i32)]| {
+            let records = values.iter().map(|(value, row)| {
+                arena.make_datum(|packer| {
+                    packer.push_list([Datum::from(*value), Datum::Int32(*row)])
+                })
+            });
+            arena.make_datum(|packer| packer.push_list(records))
+        };
+        let order_by = vec![ColumnOrder {
+            column: 0,
+            desc: false,
+            nulls_last: false,
+        }];
+        let percent_rank = AggregateFunc::PercentRank {
+            order_by: order_by.clone(),
+        };
+        let cume_dist = AggregateFunc::CumeDist { order_by };
+
+        // A single row has a relative rank of 0 and a cumulative distribution
+        // of 1.
+        let datums = vec![input(1, 10)];
+        assert_eq!(
+            percent_rank.eval(datums.clone(), &arena),
+            output(&[(0.0, 1)])
+        );
+        assert_eq!(cume_dist.eval(datums, &arena), output(&[(1.0, 1)]));
+
+        // Peers share their values.
+        let datums = vec![input(1, 10), input(2, 20), input(3, 20), input(4, 30)];
+        assert_eq!(
+            percent_rank.eval(datums.clone(), &arena),
+            output(&[(0.0, 1), (1.0 / 3.0, 2), (1.0 / 3.0, 3), (1.0, 4)]),
+        );
+        assert_eq!(
+            cume_dist.eval(datums, &arena),
+            output(&[(0.25, 1), (0.75, 2), (0.75, 3), (1.0, 4)]),
+        );
+    }
+
+    #[test]
+    fn jsonb_object_agg_stringify_values() {
+        let arena = RowArena::new();
+        let agg = |stringify_values, datums: &[(&'static str, Datum<'static>)]| {
+            let datums = datums.iter().map(|(key, val)| {
+                arena.make_datum(|packer| packer.push_list([Datum::String(key), *val]))
+            });
+            let func = AggregateFunc::JsonbObjectAgg {
+                order_by: vec![],
+                stringify_values,
+            };
+            JsonbRef::from_datum(func.eval(datums, &arena)).to_string()
+        };
+
+        let one = Datum::from(Numeric::from(1));
+        assert_eq!(agg(true, &[("a", one)]), r#"{"a":"1"}"#);
+        assert_eq!(agg(false, &[("a", one)]), r#"{"a":1}"#);
+
+        let values = [
+            ("b", Datum::String("x")),
+            ("c", Datum::JsonNull),
+            ("d", Datum::True),
+        ];
+        assert_eq!(agg(true, &values), r#"{"b":"x","c":null,"d":"true"}"#);
+        assert_eq!(agg(false, &values), r#"{"b":"x","c":null,"d":true}"#);
+    }
+
+    #[test]
+    fn count_numeric() {
+        let arena = RowArena::new();
+        let datums = vec![
+            Datum::Int32(1),
+            Datum::Null,
+            Datum::Int32(2),
+            Datum::Int32(1),
+            Datum::Null,
+        ];
+        for datums in [datums, vec![Datum::Null], vec![]] {
+            let count = AggregateFunc::Count
+                .eval(datums.clone(), &arena)
+                .unwrap_int64();
+            assert_eq!(
+                AggregateFunc::CountNumeric.eval(datums, &arena),
+                Datum::from(Numeric::from(count))
+            );
         }
+        assert_eq!(
+            AggregateFunc::CountNumeric.default(),
+            Datum::from(Numeric::from(0))
+        );
+        assert_eq!(
+            AggregateFunc::CountNumeric.output_type(ScalarType::Int32.nullable(true)),
+            ScalarType::Numeric { max_scale: None }.nullable(false)
+        );
+        assert_eq!(AggregateFunc::CountNumeric.to_string(), "count");
     }
 
-    /// True iff the table function preserves the append-only property of its input.
-    pub fn preserves_monotonicity(&self) -> bool {
-        // Most variants preserve monotonicity, but all variants are enumerated to
-        // ensure that added variants at least check that this is the case.
-        match self {
-            TableFunc::JsonbEach { .. } => true,
-            TableFunc::JsonbObjectKeys => true,
-            TableFunc::JsonbArrayElements { .. } => true,
-            TableFunc::RegexpExtract(_) => true,
-            TableFunc::CsvExtract(_) => true,
-            TableFunc::GenerateSeriesInt32 => true,
-            TableFunc::GenerateSeriesInt64 => true,
-            TableFunc::GenerateSeriesTimestamp => true,
-            TableFunc::GenerateSeriesTimestampTz => true,
-            TableFunc::GenerateSubscriptsArray => true,
-            TableFunc::Repeat => false,
-            TableFunc::UnnestArray { .. } => true,
-            TableFunc::UnnestList { .. } => true,
-            TableFunc::Wrap { .. } => true,
+    #[test]
+    fn count_distinct() {
+        let arena = RowArena::new();
+        let datums = vec![
+            Datum::Int32(1),
+            Datum::Null,
+            Datum::Int32(2),
+            Datum::Int32(1),
+            Datum::Null,
+            Datum::Int32(2),
+            Datum::Int32(3),
+        ];
+        assert_eq!(
+            AggregateFunc::CountDistinct.eval(datums, &arena),
+            Datum::Int64(3)
+        );
+        assert_eq!(
+            AggregateFunc::CountDistinct.eval(vec![Datum::Null], &arena),
+            Datum::Int64(0)
+        );
+        assert_eq!(AggregateFunc::CountDistinct.default(), Datum::Int64(0));
+        assert_eq!(AggregateFunc::CountDistinct.to_string(), "count");
+    }
+
+    #[test]
+    fn sum_numeric_overflow() {
+        let mut cx = numeric::cx_datum();
+        let max = cx.parse("9E+38").unwrap();
+        let datums = vec![Datum::from(max), Datum::Null, Datum::from(max)];
+        let (sum, err) = checked_sum_numeric(datums);
+        assert_eq!(err, Some(EvalError::NumericFieldOverflow));
+        assert!(sum.unwrap_numeric().0.is_infinite());
+
+        let min = cx.parse("-9E+38").unwrap();
+        let (sum, err) = checked_sum_numeric(vec![Datum::from(max), Datum::from(min)]);
+        assert_eq!(err, None);
+        assert_eq!(sum, Datum::from(Numeric::zero()));
+    }
+
+    #[test]
+    fn try_eval_sum_overflow() {
+        let arena = RowArena::new();
+        let datums = vec![Datum::Int16(1), Datum::Null, Datum::Int16(-3)];
+        assert_eq!(
+            AggregateFunc::SumInt16.try_eval(datums, &arena),
+            Ok(Datum::Int64(-2))
+        );
+        assert_eq!(
+            AggregateFunc::SumInt32.try_eval(vec![Datum::Null], &arena),
+            Ok(Datum::Null)
+        );
+        let datums = (0..3 * PRIMITIVE_CHUNK_SIZE).map(|_| Datum::Int32(i32::MAX));
+        assert_eq!(
+            AggregateFunc::SumInt32.try_eval(datums, &arena),
+            Ok(Datum::Int64(
+                i64::try_from(3 * PRIMITIVE_CHUNK_SIZE).unwrap() * i64::from(i32::MAX)
+            ))
+        );
+        let datums = vec![Datum::Int64(i64::MAX), Datum::Int64(i64::MAX)];
+        assert_eq!(
+            AggregateFunc::SumInt64.try_eval(datums, &arena),
+            Ok(Datum::from(2 * i128::from(i64::MAX)))
+        );
+
+        let mut cx = numeric::cx_datum();
+        let max = Datum::from(cx.parse("9E+38").unwrap());
+        assert_eq!(
+            AggregateFunc::SumNumeric.try_eval(vec![max, max], &arena),
+            Err(EvalError::NumericFieldOverflow)
+        );
+        // Aggregates without a fallible implementation defer to `eval`.
+        assert_eq!(
+            AggregateFunc::Count.try_eval(vec![max, Datum::Null], &arena),
+            Ok(Datum::Int64(1))
+        );
+    }
+
+    #[test]
+    fn min_max_interval() {
+        let arena = RowArena::new();
+        let interval = |months, days, micros| {
+            Datum::Interval(Interval {
+                months,
+                days,
+                micros,
+            })
+        };
+        let datums = vec![
+            interval(0, -2, 0),
+            interval(0, 1, 7_200_000_000),
+            Datum::Null,
+            interval(0, 0, -3_600_000_000),
+            interval(3, -5, 0),
+            interval(-12, 0, 0),
+        ];
+        assert_eq!(
+            AggregateFunc::MaxInterval.eval(datums.clone(), &arena),
+            interval(3, -5, 0)
+        );
+        assert_eq!(
+            AggregateFunc::MinInterval.eval(datums, &arena),
+            interval(-12, 0, 0)
+        );
+        assert_eq!(
+            AggregateFunc::MaxInterval.eval(vec![Datum::Null], &arena),
+            Datum::Null
+        );
+        assert!(AggregateFunc::MinInterval.propagates_nonnull_constraint());
+
+        // Like PostgreSQL, intervals are compared by their length, with a
+        // month of 30 days and a day of 24 hours, rather than field by field.
+        let one_day = interval(0, 1, 0);
+        let twenty_five_hours = interval(0, 0, 90_000_000_000);
+        let datums = vec![one_day, twenty_five_hours];
+        assert_eq!(
+            AggregateFunc::MaxInterval.eval(datums.clone(), &arena),
+            twenty_five_hours
+        );
+        assert_eq!(AggregateFunc::MinInterval.eval(datums, &arena), one_day);
+        let datums = vec![interval(1, 0, 0), interval(0, 31, 0), interval(0, 29, 0)];
+        assert_eq!(
+            AggregateFunc::MaxInterval.eval(datums.clone(), &arena),
+            interval(0, 31, 0)
+        );
+        assert_eq!(
+            AggregateFunc::MinInterval.eval(datums, &arena),
+            interval(0, 29, 0)
+        );
+    }
+
+    #[test]
+    fn window_output_type() {
+        let record = |fields: Vec<(&str, ScalarType, bool)>| ScalarType::Record {
+            fields: fields
+                .into_iter()
+                .map(|(name, typ, nullable)| (ColumnName::from(name), typ.nullable(nullable)))
+                .collect(),
+            custom_id: None,
+        };
+        let list = |element_type| ScalarType::List {
+            element_type: Box::new(element_type),
+            custom_id: None,
+        };
+        let original_row = record(vec![
+            ("a", ScalarType::Int32, true),
+            ("b", ScalarType::String, false),
+        ]);
+        let expected = |name: &str, value_type: ScalarType, nullable: bool| {
+            list(record(vec![
+                (name, value_type, nullable),
+                ("?record?", original_row.clone(), false),
+            ]))
+            .nullable(false)
+        };
+
+        // (List<OriginalRow>, OrderByExprs...)
+        let ranking_input = record(vec![
+            ("?column?", list(original_row.clone()), false),
+            ("?column?", ScalarType::Int32, true),
+        ])
+        .nullable(false);
+        // ((OriginalRow, EncodedArgs), OrderByExprs...)
+        let value_input = |args| {
+            record(vec![
+                (
+                    "?column?",
+                    record(vec![
+                        ("?column?", original_row.clone(), false),
+                        ("?column?", args, false),
+                    ]),
+                    false,
+                ),
+                ("?column?", ScalarType::Int32, true),
+            ])
+            .nullable(false)
+        };
+
+        let order_by = vec![ColumnOrder {
+            column: 0,
+            desc: false,
+            nulls_last: false,
+        }];
+        let window_frame = WindowFrame {
+            units: WindowFrameUnits::Rows,
+            start_bound: WindowFrameBound::UnboundedPreceding,
+            end_bound: WindowFrameBound::CurrentRow,
+        };
+        let lag_args = record(vec![
+            ("?column?", ScalarType::String, false),
+            ("?column?", ScalarType::Int32, false),
+            ("?column?", ScalarType::String, true),
+        ]);
+        let nth_value_args = record(vec![
+            ("?column?", ScalarType::String, false),
+            ("?column?", ScalarType::Int32, false),
+        ]);
+
+        let cases = vec![
+            (
+                AggregateFunc::RowNumber {
+                    order_by: order_by.clone(),
+                },
+                ranking_input.clone(),
+                expected("?row_number?", ScalarType::Int64, false),
+            ),
+            (
+                AggregateFunc::Rank {
+                    order_by: order_by.clone(),
+                },
+                ranking_input.clone(),
+                expected("?rank?", ScalarType::Int64, false),
+            ),
+            (
+                AggregateFunc::DenseRank {
+                    order_by: order_by.clone(),
+                },
+                ranking_input.clone(),
+                expected("?dense_rank?", ScalarType::Int64, false),
+            ),
+            (
+                AggregateFunc::PercentRank {
+                    order_by: order_by.clone(),
+                },
+                ranking_input.clone(),
+                expected("?percent_rank?", ScalarType::Float64, false),
+            ),
+            (
+                AggregateFunc::CumeDist {
+                    order_by: order_by.clone(),
+                },
+                ranking_input,
+                expected("?cume_dist?", ScalarType::Float64, false),
+            ),
+            (
+                AggregateFunc::LagLead {
+                    order_by: order_by.clone(),
+                    lag_lead: LagLeadType::Lag,
+                    ignore_nulls: false,
+                },
+                value_input(lag_args.clone()),
+                expected("?lag?", ScalarType::String, true),
+            ),
+            (
+                AggregateFunc::LagLead {
+                    order_by: order_by.clone(),
+                    lag_lead: LagLeadType::Lead,
+                    ignore_nulls: false,
+                },
+                value_input(lag_args),
+                expected("?lead?", ScalarType::String, true),
+            ),
+            (
+                AggregateFunc::FirstValue {
+                    order_by: order_by.clone(),
+                    window_frame: window_frame.clone(),
+                },
+                value_input(ScalarType::String),
+                expected("?first_value?", ScalarType::String, true),
+            ),
+            (
+                AggregateFunc::LastValue {
+                    order_by: order_by.clone(),
+                    window_frame: window_frame.clone(),
+                },
+                value_input(ScalarType::String),
+                expected("?last_value?", ScalarType::String, true),
+            ),
+            (
+                AggregateFunc::NthValue {
+                    order_by: order_by.clone(),
+                    window_frame,
+                },
+                value_input(nth_value_args),
+                expected("?nth_value?", ScalarType::String, true),
+            ),
+            (
+                AggregateFunc::Ntile { order_by },
+                value_input(ScalarType::Int32),
+                expected("?ntile?", ScalarType::Int64, true),
+            ),
+        ];
+        for (func, input_type, expected) in cases {
+            assert_eq!(func.output_type(input_type), expected, "{func}");
         }
     }
-}
 
-impl fmt::Display for TableFunc {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            TableFunc::JsonbEach { .. } => f.write_str("jsonb_each"),
-            TableFunc::JsonbObjectKeys => f.write_str("jsonb_object_keys"),
-            TableFunc::JsonbArrayElements { .. } => f.write_str("jsonb_array_elements"),
-            TableFunc::RegexpExtract(a) => write!(f, "regexp_extract({:?}, _)", a.0),
-            TableFunc::CsvExtract(n_cols) => write!(f, "csv_extract({}, _)", n_cols),
-            TableFunc::GenerateSeriesInt32 => f.write_str("generate_series"),
-            TableFunc::GenerateSeriesInt64 => f.write_str("generate_series"),
-            TableFunc::GenerateSeriesTimestamp => f.write_str("generate_series"),
-            TableFunc::GenerateSeriesTimestampTz => f.write_str("generate_series"),
-            TableFunc::GenerateSubscriptsArray => f.write_str("generate_subscripts"),
-            TableFunc::Repeat => f.write_str("repeat_row"),
-            TableFunc::UnnestArray { .. } => f.write_str("unnest_array"),
-            TableFunc::UnnestList { .. } => f.write_str("unnest_list"),
-            TableFunc::Wrap { width, .. } => write!(f, "wrap{}", width),
+    #[test]
+    fn min_max_time() {
+        let arena = RowArena::new();
+        let time =
+            |h, m, s, micro| Datum::Time(NaiveTime::from_hms_micro_opt(h, m, s, micro).unwrap());
+        // Times just before and after midnight are at opposite ends of the day.
+        let datums = vec![
+            time(23, 59, 59, 999_999),
+            time(0, 0, 0, 0),
+            Datum::Null,
+            time(12, 0, 0, 0),
+            time(0, 0, 0, 1),
+        ];
+        assert_eq!(
+            AggregateFunc::MaxTime.eval(datums.clone(), &arena),
+            time(23, 59, 59, 999_999)
+        );
+        assert_eq!(
+            AggregateFunc::MinTime.eval(datums, &arena),
+            time(0, 0, 0, 0)
+        );
+        assert_eq!(
+            AggregateFunc::MinTime.eval(vec![Datum::Null], &arena),
+            Datum::Null
+        );
+        assert_eq!(
+            AggregateFunc::MaxTime.output_type(ScalarType::Time.nullable(true)),
+            ScalarType::Time.nullable(true)
+        );
+        assert!(AggregateFunc::MaxTime.propagates_nonnull_constraint());
+    }
+
+    #[test]
+    fn min_max_bytes() {
+        let arena = RowArena::new();
+        // Bytes compare lexicographically, so neither longer nor shorter byte
+        // strings win by length alone.
+        let datums = vec![
+            Datum::Bytes(&[1, 2]),
+            Datum::Bytes(&[1, 2, 0]),
+            Datum::Null,
+            Datum::Bytes(&[2]),
+            Datum::Bytes(&[1, 255, 255]),
+            Datum::Bytes(&[]),
+        ];
+        assert_eq!(
+            AggregateFunc::MaxBytes.eval(datums.clone(), &arena),
+            Datum::Bytes(&[2])
+        );
+        assert_eq!(
+            AggregateFunc::MinBytes.eval(datums, &arena),
+            Datum::Bytes(&[])
+        );
+        // A proper prefix orders before the byte strings it prefixes.
+        let datums = vec![Datum::Bytes(&[1, 2, 0]), Datum::Bytes(&[1, 2])];
+        assert_eq!(
+            AggregateFunc::MinBytes.eval(datums.clone(), &arena),
+            Datum::Bytes(&[1, 2])
+        );
+        assert_eq!(
+            AggregateFunc::MaxBytes.eval(datums, &arena),
+            Datum::Bytes(&[1, 2, 0])
+        );
+        assert_eq!(
+            AggregateFunc::MaxBytes.eval(vec![Datum::Null], &arena),
+            Datum::Null
+        );
+    }
+
+    #[test]
+    fn min_max_uuid() {
+        let arena = RowArena::new();
+        let uuid = |s| Datum::Uuid(Uuid::parse_str(s).unwrap());
+        let datums = vec![
+            uuid("63616665-6630-3064-6465-616462656568"),
+            Datum::Null,
+            uuid("86565626-4616-5646-4603-036656661636"),
+            uuid("00000000-0000-0000-0000-000000000000"),
+        ];
+        assert_eq!(
+            AggregateFunc::MaxUuid.eval(datums.clone(), &arena),
+            uuid("86565626-4616-5646-4603-036656661636")
+        );
+        assert_eq!(
+            AggregateFunc::MinUuid.eval(datums, &arena),
+            uuid("00000000-0000-0000-0000-000000000000")
+        );
+        assert_eq!(
+            AggregateFunc::MinUuid.eval(vec![Datum::Null], &arena),
+            Datum::Null
+        );
+        assert_eq!(
+            AggregateFunc::MaxUuid.output_type(ScalarType::Uuid.nullable(false)),
+            ScalarType::Uuid.nullable(false)
+        );
+    }
+
+    #[test]
+    fn unordered_aggregates_preserve_input_order() {
+        let arena = RowArena::new();
+        // Each datum is a record of the aggregated value, followed by no ORDER
+        // BY expressions.
+        let record = |d: Datum| arena.make_datum(|packer| packer.push_list([d]));
+        let pair = |value, sep| record(arena.make_datum(|packer| packer.push_list([value, sep])));
+
+        let datums = vec![
+            pair(Datum::String("c"), Datum::String(",")),
+            pair(Datum::Null, Datum::String("-")),
+            pair(Datum::String("a"), Datum::String(";")),
+            pair(Datum::String("b"), Datum::Null),
+        ];
+        let streamed: Vec<_> = order_aggregate_datums(datums.clone(), &[]).collect();
+        let sorted: Vec<_> = order_aggregate_datums_with_rank(datums.clone(), &[])
+            .map(|(datum, _)| datum)
+            .collect();
+        assert_eq!(streamed, sorted);
+        assert_eq!(
+            AggregateFunc::StringAgg { order_by: vec![] }.eval(datums, &arena),
+            Datum::String("c;ab")
+        );
+
+        let datums = vec![
+            record(Datum::String("z")),
+            record(Datum::Null),
+            record(Datum::JsonNull),
+            record(Datum::String("a")),
+        ];
+        let streamed: Vec<_> = order_aggregate_datums(datums.clone(), &[]).collect();
+        let sorted: Vec<_> = order_aggregate_datums_with_rank(datums.clone(), &[])
+            .map(|(datum, _)| datum)
+            .collect();
+        assert_eq!(streamed, sorted);
+        assert_eq!(
+            AggregateFunc::JsonbAgg { order_by: vec![] }.eval(datums, &arena),
+            arena.make_datum(|packer| {
+                packer.push_list([Datum::String("z"), Datum::JsonNull, Datum::String("a")])
+            })
+        );
+    }
+
+    #[test]
+    fn sum_interval() {
+        let arena = RowArena::new();
+        let interval = |months, days, micros| {
+            Datum::Interval(Interval {
+                months,
+                days,
+                micros,
+            })
+        };
+        let datums = vec![
+            interval(1, 2, 3_000_000),
+            Datum::Null,
+            interval(-3, 5, 60_000_000),
+            interval(0, 0, -1),
+        ];
+        assert_eq!(
+            AggregateFunc::SumInterval.eval(datums.clone(), &arena),
+            interval(-2, 7, 62_999_999)
+        );
+        assert_eq!(
+            AggregateFunc::SumInterval.try_eval(datums, &arena),
+            Ok(interval(-2, 7, 62_999_999))
+        );
+        assert_eq!(
+            AggregateFunc::SumInterval.eval(vec![Datum::Null], &arena),
+            Datum::Null
+        );
+
+        // Overflowing months and micros saturate, and the fallible path
+        // reports the overflow.
+        let datums = vec![interval(i32::MAX, 1, i64::MIN), interval(1, 1, -1)];
+        assert_eq!(
+            AggregateFunc::SumInterval.eval(datums.clone(), &arena),
+            interval(i32::MAX, 2, i64::MIN)
+        );
+        assert_eq!(
+            AggregateFunc::SumInterval.try_eval(datums.clone(), &arena),
+            Err(EvalError::IntervalOutOfRange)
+        );
+        // Rendering evaluates basic aggregates through this path.
+        assert!(AggregateFunc::SumInterval.is_fallible());
+        assert_eq!(
+            AggregateFunc::SumInterval.eval_with_max_partition_size(datums, &arena, usize::MAX),
+            Err(EvalError::IntervalOutOfRange)
+        );
+        let datums = vec![interval(i32::MAX, 0, 0), interval(i32::MAX, 0, 0)];
+        assert_eq!(
+            AggregateFunc::AvgInterval.eval_with_max_partition_size(datums, &arena, usize::MAX),
+            Err(EvalError::IntervalOutOfRange)
+        );
+    }
+
+    #[test]
+    fn product_aggregates() {
+        let arena = RowArena::new();
+        let datums = vec![Datum::from(1.5f64), Datum::Null, Datum::from(-2.0f64)];
+        assert_eq!(
+            AggregateFunc::ProductFloat64.eval(datums, &arena),
+            Datum::from(-3.0f64)
+        );
+        assert_eq!(
+            AggregateFunc::ProductFloat32.eval(vec![Datum::from(0.5f32)], &arena),
+            Datum::from(0.5f32)
+        );
+        assert_eq!(
+            AggregateFunc::ProductFloat64.eval(vec![Datum::Null], &arena),
+            Datum::Null
+        );
+
+        let mut cx = numeric::cx_datum();
+        let datums = vec![
+            Datum::from(cx.parse("1.05").unwrap()),
+            Datum::Null,
+            Datum::from(cx.parse("1.05").unwrap()),
+            Datum::from(cx.parse("-2").unwrap()),
+        ];
+        assert_eq!(
+            AggregateFunc::ProductNumeric.eval(datums, &arena),
+            Datum::from(cx.parse("-2.205").unwrap())
+        );
+        let datums = vec![
+            Datum::from(cx.parse("-1").unwrap()),
+            Datum::from(Numeric::zero()),
+        ];
+        let product = AggregateFunc::ProductNumeric.eval(datums, &arena);
+        assert!(!product.unwrap_numeric().0.is_negative());
+        let max = cx.parse("9E+38").unwrap();
+        let product =
+            AggregateFunc::ProductNumeric.eval(vec![Datum::from(max), Datum::from(max)], &arena);
+        assert!(product.unwrap_numeric().0.is_infinite());
+        assert_eq!(
+            AggregateFunc::ProductNumeric.eval(Vec::<Datum>::new(), &arena),
+            Datum::Null
+        );
+        assert_eq!(
+            AggregateFunc::ProductNumeric.identity_datum(),
+            Datum::from(Numeric::from(1))
+        );
+    }
+
+    /// Asserts that inserting the identity of `func` at any position among
+    /// `values` leaves the result of the aggregation unchanged.
+    ///
+    /// The values of aggregates that take an ORDER BY are wrapped in records
+    /// whose ordering key is their position, and the identity is ordered
+    /// between its neighbours.
+    fn assert_identity_is_neutral<'a>(
+        arena: &'a RowArena,
+        func: &AggregateFunc,
+        values: &[Datum<'a>],
+    ) {
+        let ordered = matches!(
+            func,
+            AggregateFunc::JsonbAgg { .. }
+                | AggregateFunc::JsonbObjectAgg { .. }
+                | AggregateFunc::ArrayConcat { .. }
+                | AggregateFunc::ListConcat { .. }
+                | AggregateFunc::ArrayConcatDistinct { .. }
+                | AggregateFunc::ListConcatDistinct { .. }
+                | AggregateFunc::StringAgg { .. }
+                | AggregateFunc::RowNumber { .. }
+                | AggregateFunc::Rank { .. }
+                | AggregateFunc::DenseRank { .. }
+                | AggregateFunc::PercentRank { .. }
+                | AggregateFunc::CumeDist { .. }
+                | AggregateFunc::LagLead { .. }
+                | AggregateFunc::FirstValue { .. }
+                | AggregateFunc::LastValue { .. }
+                | AggregateFunc::NthValue { .. }
+                | AggregateFunc::Ntile { .. }
+        );
+        let input = |values: &[(Datum<'a>, i64)]| {
+            values
+                .iter()
+                .map(|(value, key)| {
+                    if ordered {
+                        arena.make_datum(|packer| packer.push_list([*value, Datum::Int64(*key)]))
+                    } else {
+                        *value
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let values = values.iter().copied().zip((0..).step_by(2)).collect_vec();
+        let expected = func.eval(input(&values), arena);
+        for (position, key) in (0..=values.len()).zip((-1..).step_by(2)) {
+            let mut with_identity = values.clone();
+            with_identity.insert(position, (func.identity_datum(), key));
+            assert_eq!(
+                func.eval(input(&with_identity), arena),
+                expected,
+                "identity of {func} at position {position} of {values:?}"
+            );
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{AggregateFunc, ProtoAggregateFunc, ProtoTableFunc, TableFunc};
-    use mz_proto::protobuf_roundtrip;
-    use proptest::prelude::*;
+    /// Builds one instance of each of the listed `AggregateFunc` variants. The
+    /// list must name every variant, or the exhaustive match generated
+    /// alongside it fails to compile.
+    macro_rules! every_aggregate_func {
+        ($($variant:ident $({ $($field:ident: $value:expr),* $(,)? })?),* $(,)?) => {{
+            // Variants may be listed more than once to cover their fields.
+            #[allow(dead_code, unreachable_patterns)]
+            fn is_listed(func: &AggregateFunc) {
+                match func {
+                    $(AggregateFunc::$variant { .. } => (),)*
+                }
+            }
+            vec![$(AggregateFunc::$variant $({ $($field: $value),* })?),*]
+        }};
+    }
 
-    proptest! {
-       #[test]
-        fn aggregate_func_protobuf_roundtrip(expect in any::<AggregateFunc>() ) {
-            let actual = protobuf_roundtrip::<_, ProtoAggregateFunc>(&expect);
-            assert!(actual.is_ok());
-            assert_eq!(actual.unwrap(), expect);
+    /// Calls every method that matches on `AggregateFunc` with every variant.
+    /// Listing each variant is enforced at compile time by
+    /// `every_aggregate_func!`. The calls then check that no method panics for
+    /// any variant, that each variant displays as a non-empty name, and that
+    /// each survives a protobuf roundtrip. A variant that a wildcard arm
+    /// handles incorrectly, without panicking, is not caught here.
+    #[test]
+    fn every_aggregate_func_is_handled() {
+        let window_frame = WindowFrame {
+            units: WindowFrameUnits::Rows,
+            start_bound: WindowFrameBound::UnboundedPreceding,
+            end_bound: WindowFrameBound::UnboundedFollowing,
+        };
+        let funcs = every_aggregate_func![
+            MaxNumeric,
+            MaxInt16,
+            MaxInt32,
+            MaxInt64,
+            MaxUInt16,
+            MaxUInt32,
+            MaxUInt64,
+            MaxMzTimestamp,
+            MaxFloat32,
+            MaxFloat64,
+            MaxBool,
+            MaxString,
+            MaxDate,
+            MaxTimestamp,
+            MaxTimestampTz,
+            MaxInterval,
+            MaxTime,
+            MaxBytes,
+            MaxUuid,
+            MaxArray,
+            MinNumeric,
+            MinInt16,
+            MinInt32,
+            MinInt64,
+            MinUInt16,
+            MinUInt32,
+            MinUInt64,
+            MinMzTimestamp,
+            MinFloat32,
+            MinFloat64,
+            MinBool,
+            MinString,
+            MinDate,
+            MinTimestamp,
+            MinTimestampTz,
+            MinInterval,
+            MinTime,
+            MinBytes,
+            MinUuid,
+            MinArray,
+            SumInt16,
+            SumInt32,
+            SumInt64,
+            SumUInt16,
+            SumUInt32,
+            SumUInt64,
+            SumFloat32,
+            SumFloat64,
+            SumNumeric,
+            SumInterval,
+            ProductFloat32,
+            ProductFloat64,
+            ProductNumeric,
+            AvgInt16,
+            AvgInt32,
+            AvgInt64,
+            AvgFloat32,
+            AvgFloat64,
+            AvgNumeric,
+            AvgInterval,
+            StddevPop,
+            StddevSamp,
+            VarPop,
+            VarSamp,
+            Corr,
+            CovarPop,
+            CovarSamp,
+            RegrCount,
+            RegrSlope,
+            RegrIntercept,
+            RegrR2,
+            Count,
+            CountNumeric,
+            CountDistinct,
+            Any,
+            All,
+            BoolAnd,
+            BoolOr,
+            JsonbAgg { order_by: vec![] },
+            JsonbObjectAgg {
+                order_by: vec![],
+                stringify_values: false,
+            },
+            JsonbObjectAgg {
+                order_by: vec![],
+                stringify_values: true,
+            },
+            ArrayConcat { order_by: vec![] },
+            ListConcat { order_by: vec![] },
+            ArrayConcatDistinct { order_by: vec![] },
+            ListConcatDistinct { order_by: vec![] },
+            StringAgg { order_by: vec![] },
+            RowNumber { order_by: vec![] },
+            Rank { order_by: vec![] },
+            DenseRank { order_by: vec![] },
+            PercentRank { order_by: vec![] },
+            CumeDist { order_by: vec![] },
+            LagLead {
+                order_by: vec![],
+                lag_lead: LagLeadType::Lag,
+                ignore_nulls: false,
+            },
+            LagLead {
+                order_by: vec![],
+                lag_lead: LagLeadType::Lead,
+                ignore_nulls: true,
+            },
+            FirstValue {
+                order_by: vec![],
+                window_frame: window_frame.clone(),
+            },
+            LastValue {
+                order_by: vec![],
+                window_frame: window_frame.clone(),
+            },
+            NthValue {
+                order_by: vec![],
+                window_frame,
+            },
+            Ntile { order_by: vec![] },
+            Dummy,
+        ];
+
+        let record = |types: Vec<ScalarType>| ScalarType::Record {
+            fields: types
+                .into_iter()
+                .map(|typ| (ColumnName::from("?column?"), typ.nullable(true)))
+                .collect(),
+            custom_id: None,
+        };
+        // The smallest input type of the shape that `output_type` expects.
+        let input_type = |func: &AggregateFunc| match func {
+            AggregateFunc::ArrayConcat { .. }
+            | AggregateFunc::ListConcat { .. }
+            | AggregateFunc::ArrayConcatDistinct { .. }
+            | AggregateFunc::ListConcatDistinct { .. }
+            | AggregateFunc::StringAgg { .. } => {
+                record(vec![record(vec![ScalarType::String, ScalarType::String])])
+            }
+            AggregateFunc::RowNumber { .. }
+            | AggregateFunc::Rank { .. }
+            | AggregateFunc::DenseRank { .. }
+            | AggregateFunc::PercentRank { .. }
+            | AggregateFunc::CumeDist { .. } => record(vec![ScalarType::List {
+                element_type: Box::new(record(vec![ScalarType::Int32])),
+                custom_id: None,
+            }]),
+            AggregateFunc::LagLead { .. }
+            | AggregateFunc::FirstValue { .. }
+            | AggregateFunc::LastValue { .. }
+            | AggregateFunc::NthValue { .. }
+            | AggregateFunc::Ntile { .. } => record(vec![record(vec![
+                record(vec![ScalarType::Int32]),
+                record(vec![ScalarType::Int32, ScalarType::Int32]),
+            ])]),
+            _ => ScalarType::Int32,
+        };
+
+        let arena = RowArena::new();
+        for func in &funcs {
+            assert!(!func.to_string().is_empty(), "{func:?} displays as nothing");
+            func.output_type(input_type(func).nullable(true));
+            func.propagates_nonnull_constraint();
+            func.supports_retractions();
+            func.accumulator();
+            func.default();
+            func.identity_datum();
+            func.eval(iter::empty(), &arena);
+            assert_eq!(
+                protobuf_roundtrip::<_, ProtoAggregateFunc>(func).unwrap(),
+                *func
+            );
         }
     }
 
-    proptest! {
-       #[test]
-        fn table_func_protobuf_roundtrip(expect in any::<TableFunc>() ) {
-            let actual = protobuf_roundtrip::<_, ProtoTableFunc>(&expect);
-            assert!(actual.is_ok());
-            assert_eq!(actual.unwrap(), expect);
+    #[test]
+    fn identity_datum_is_neutral() {
+        let arena = RowArena::new();
+        let numeric = |n: i32| Datum::from(Numeric::from(n));
+        let list = |ds: &[Datum]| arena.make_datum(|packer| packer.push_list(ds));
+        let timestamptz = |s: &str| {
+            let ts = DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+            CheckedTimestamp::from_timestamplike(ts).unwrap()
+        };
+        let timestamp = |s: &str| timestamptz(s).naive_utc();
+        let uuid = |s| Datum::Uuid(Uuid::parse_str(s).unwrap());
+        let order_by = vec![ColumnOrder {
+            column: 0,
+            desc: false,
+            nulls_last: false,
+        }];
+        let window_frame = WindowFrame {
+            units: WindowFrameUnits::Rows,
+            start_bound: WindowFrameBound::UnboundedPreceding,
+            end_bound: WindowFrameBound::CurrentRow,
+        };
+
+        // Groups of values of each type, most of which include a null
+        let int16s = || vec![Datum::Int16(3), Datum::Null, Datum::Int16(1)];
+        let ints = || vec![Datum::Int32(3), Datum::Null, Datum::Int32(1)];
+        let int64s = || vec![Datum::Int64(3), Datum::Null, Datum::Int64(1)];
+        let uint16s = || vec![Datum::UInt16(3), Datum::Null, Datum::UInt16(1)];
+        let uint32s = || vec![Datum::UInt32(3), Datum::Null, Datum::UInt32(1)];
+        let uint64s = || vec![Datum::UInt64(3), Datum::Null, Datum::UInt64(1)];
+        let mz_timestamps = || {
+            vec![
+                Datum::MzTimestamp(3u64.into()),
+                Datum::MzTimestamp(1u64.into()),
+            ]
+        };
+        let float32s = || vec![Datum::from(1.5f32), Datum::Null, Datum::from(-2f32)];
+        let floats = || vec![Datum::from(1.5f64), Datum::Null, Datum::from(-2f64)];
+        let numerics = || vec![numeric(2), Datum::Null, numeric(-7), numeric(2)];
+        let bools = || vec![Datum::True, Datum::False, Datum::Null];
+        let strings = || vec![Datum::String("b"), Datum::Null, Datum::String("a")];
+        let dates = || {
+            [3, -1]
+                .map(|days| Datum::Date(Date::from_pg_epoch(days).unwrap()))
+                .to_vec()
+        };
+        let timestamps = || {
+            ["2023-01-01T00:00:00Z", "2022-01-01T00:00:00Z"]
+                .map(|s| Datum::from(CheckedTimestamp::from_timestamplike(timestamp(s)).unwrap()))
+                .to_vec()
+        };
+        let timestamptzs = || {
+            ["2023-01-01T00:00:00Z", "2022-01-01T00:00:00Z"]
+                .map(|s| Datum::from(timestamptz(s)))
+                .to_vec()
+        };
+        let intervals = || {
+            vec![
+                Datum::Interval(Interval::new(1, 0, 0)),
+                Datum::Null,
+                Datum::Interval(Interval::new(0, 31, -1)),
+            ]
+        };
+        let times = || {
+            [(1, 30), (0, 15)]
+                .map(|(h, m)| Datum::Time(NaiveTime::from_hms_opt(h, m, 0).unwrap()))
+                .to_vec()
+        };
+        let bytes = || vec![Datum::Bytes(&[1, 2]), Datum::Null, Datum::Bytes(&[])];
+        let uuids = || {
+            vec![
+                uuid("00000000-0000-0000-0000-000000000002"),
+                uuid("00000000-0000-0000-0000-000000000001"),
+            ]
+        };
+        let arrays = || vec![int32_array(&arena, &[1, 2]), int32_array(&arena, &[1])];
+        let pairs = || {
+            vec![
+                list(&[Datum::from(1f64), Datum::from(2f64)]),
+                list(&[Datum::Null, Datum::from(3f64)]),
+                list(&[Datum::from(4f64), Datum::from(3f64)]),
+                list(&[Datum::from(2f64), Datum::from(7f64)]),
+            ]
+        };
+        // Window functions aggregate the original rows, and any arguments
+        let rows = || (1..=3).map(|row| list(&[Datum::Int32(row)])).collect_vec();
+        let rows_with = |args: [Datum; 3]| {
+            args.into_iter()
+                .zip(1..)
+                .map(|(args, row)| list(&[Datum::Int32(row), args]))
+                .collect_vec()
+        };
+
+        let cases = vec![
+            (AggregateFunc::MaxNumeric, numerics()),
+            (AggregateFunc::MaxInt16, int16s()),
+            (AggregateFunc::MaxInt32, ints()),
+            (AggregateFunc::MaxInt64, int64s()),
+            (AggregateFunc::MaxUInt16, uint16s()),
+            (AggregateFunc::MaxUInt32, uint32s()),
+            (AggregateFunc::MaxUInt64, uint64s()),
+            (AggregateFunc::MaxMzTimestamp, mz_timestamps()),
+            (AggregateFunc::MaxFloat32, float32s()),
+            (AggregateFunc::MaxFloat64, floats()),
+            (AggregateFunc::MaxBool, bools()),
+            (AggregateFunc::MaxString, strings()),
+            (AggregateFunc::MaxDate, dates()),
+            (AggregateFunc::MaxTimestamp, timestamps()),
+            (AggregateFunc::MaxTimestampTz, timestamptzs()),
+            (AggregateFunc::MaxInterval, intervals()),
+            (AggregateFunc::MaxTime, times()),
+            (AggregateFunc::MaxBytes, bytes()),
+            (AggregateFunc::MaxUuid, uuids()),
+            (AggregateFunc::MaxArray, arrays()),
+            (AggregateFunc::MinNumeric, numerics()),
+            (AggregateFunc::MinInt16, int16s()),
+            (AggregateFunc::MinInt32, ints()),
+            (AggregateFunc::MinInt64, int64s()),
+            (AggregateFunc::MinUInt16, uint16s()),
+            (AggregateFunc::MinUInt32, uint32s()),
+            (AggregateFunc::MinUInt64, uint64s()),
+            (AggregateFunc::MinMzTimestamp, mz_timestamps()),
+            (AggregateFunc::MinFloat32, float32s()),
+            (AggregateFunc::MinFloat64, floats()),
+            (AggregateFunc::MinBool, bools()),
+            (AggregateFunc::MinString, strings()),
+            (AggregateFunc::MinDate, dates()),
+            (AggregateFunc::MinTimestamp, timestamps()),
+            (AggregateFunc::MinTimestampTz, timestamptzs()),
+            (AggregateFunc::MinInterval, intervals()),
+            (AggregateFunc::MinTime, times()),
+            (AggregateFunc::MinBytes, bytes()),
+            (AggregateFunc::MinUuid, uuids()),
+            (AggregateFunc::MinArray, arrays()),
+            (AggregateFunc::SumInt16, int16s()),
+            (AggregateFunc::SumInt32, ints()),
+            (AggregateFunc::SumInt64, int64s()),
+            (AggregateFunc::SumUInt16, uint16s()),
+            (AggregateFunc::SumUInt32, uint32s()),
+            (AggregateFunc::SumUInt64, uint64s()),
+            (AggregateFunc::SumFloat32, float32s()),
+            (AggregateFunc::SumFloat64, floats()),
+            (AggregateFunc::SumNumeric, numerics()),
+            (AggregateFunc::SumInterval, intervals()),
+            (AggregateFunc::ProductFloat32, float32s()),
+            (AggregateFunc::ProductFloat64, floats()),
+            (AggregateFunc::ProductNumeric, numerics()),
+            (AggregateFunc::AvgInt16, int16s()),
+            (AggregateFunc::AvgInt32, ints()),
+            (AggregateFunc::AvgInt64, int64s()),
+            (AggregateFunc::AvgFloat32, float32s()),
+            (AggregateFunc::AvgFloat64, floats()),
+            (AggregateFunc::AvgNumeric, numerics()),
+            (AggregateFunc::AvgInterval, intervals()),
+            (AggregateFunc::StddevPop, numerics()),
+            (AggregateFunc::StddevSamp, numerics()),
+            (AggregateFunc::VarPop, numerics()),
+            (AggregateFunc::VarSamp, numerics()),
+            (AggregateFunc::Corr, pairs()),
+            (AggregateFunc::CovarPop, pairs()),
+            (AggregateFunc::CovarSamp, pairs()),
+            (AggregateFunc::RegrCount, pairs()),
+            (AggregateFunc::RegrSlope, pairs()),
+            (AggregateFunc::RegrIntercept, pairs()),
+            (AggregateFunc::RegrR2, pairs()),
+            (AggregateFunc::Count, ints()),
+            (AggregateFunc::CountNumeric, ints()),
+            (AggregateFunc::CountDistinct, ints()),
+            (AggregateFunc::Any, bools()),
+            (AggregateFunc::All, bools()),
+            (AggregateFunc::BoolAnd, vec![Datum::True]),
+            (AggregateFunc::BoolAnd, bools()),
+            (AggregateFunc::BoolOr, vec![Datum::False]),
+            (AggregateFunc::BoolOr, bools()),
+            (
+                AggregateFunc::JsonbAgg {
+                    order_by: order_by.clone(),
+                },
+                vec![Datum::String("a"), Datum::Null, Datum::True],
+            ),
+            (
+                AggregateFunc::JsonbObjectAgg {
+                    order_by: order_by.clone(),
+                    stringify_values: false,
+                },
+                vec![
+                    list(&[Datum::String("a"), Datum::True]),
+                    list(&[Datum::String("b"), Datum::JsonNull]),
+                ],
+            ),
+            (
+                AggregateFunc::JsonbObjectAgg {
+                    order_by: order_by.clone(),
+                    stringify_values: true,
+                },
+                vec![
+                    list(&[Datum::String("a"), Datum::True]),
+                    list(&[Datum::String("b"), Datum::JsonNull]),
+                ],
+            ),
+            (
+                AggregateFunc::ArrayConcat {
+                    order_by: order_by.clone(),
+                },
+                vec![int32_array(&arena, &[1, 2]), int32_array(&arena, &[2])],
+            ),
+            (
+                AggregateFunc::ListConcat {
+                    order_by: order_by.clone(),
+                },
+                vec![
+                    list(&[Datum::Int32(1), Datum::Int32(2)]),
+                    list(&[Datum::Int32(2)]),
+                ],
+            ),
+            (
+                AggregateFunc::ArrayConcatDistinct {
+                    order_by: order_by.clone(),
+                },
+                vec![int32_array(&arena, &[1, 2]), int32_array(&arena, &[2])],
+            ),
+            (
+                AggregateFunc::ListConcatDistinct {
+                    order_by: order_by.clone(),
+                },
+                vec![
+                    list(&[Datum::Int32(1), Datum::Int32(2)]),
+                    list(&[Datum::Int32(2)]),
+                ],
+            ),
+            (
+                AggregateFunc::StringAgg {
+                    order_by: order_by.clone(),
+                },
+                vec![
+                    list(&[Datum::String("a"), Datum::String(",")]),
+                    list(&[Datum::Null, Datum::String(",")]),
+                    list(&[Datum::String("b"), Datum::String(";")]),
+                ],
+            ),
+            (
+                AggregateFunc::RowNumber {
+                    order_by: order_by.clone(),
+                },
+                rows(),
+            ),
+            (
+                AggregateFunc::Rank {
+                    order_by: order_by.clone(),
+                },
+                rows(),
+            ),
+            (
+                AggregateFunc::DenseRank {
+                    order_by: order_by.clone(),
+                },
+                rows(),
+            ),
+            (
+                AggregateFunc::PercentRank {
+                    order_by: order_by.clone(),
+                },
+                rows(),
+            ),
+            (
+                AggregateFunc::CumeDist {
+                    order_by: order_by.clone(),
+                },
+                rows(),
+            ),
+            (
+                AggregateFunc::LagLead {
+                    order_by: order_by.clone(),
+                    lag_lead: LagLeadType::Lag,
+                    ignore_nulls: false,
+                },
+                rows_with(
+                    [10, 20, 30].map(|v| list(&[Datum::Int32(v), Datum::Int32(1), Datum::Null])),
+                ),
+            ),
+            (
+                AggregateFunc::LagLead {
+                    order_by: order_by.clone(),
+                    lag_lead: LagLeadType::Lead,
+                    ignore_nulls: false,
+                },
+                rows_with(
+                    [10, 20, 30].map(|v| list(&[Datum::Int32(v), Datum::Int32(1), Datum::Null])),
+                ),
+            ),
+            (
+                AggregateFunc::FirstValue {
+                    order_by: order_by.clone(),
+                    window_frame: window_frame.clone(),
+                },
+                rows_with([10, 20, 30].map(Datum::Int32)),
+            ),
+            (
+                AggregateFunc::LastValue {
+                    order_by: order_by.clone(),
+                    window_frame: window_frame.clone(),
+                },
+                rows_with([10, 20, 30].map(Datum::Int32)),
+            ),
+            (
+                AggregateFunc::NthValue {
+                    order_by: order_by.clone(),
+                    window_frame,
+                },
+                rows_with([10, 20, 30].map(|v| list(&[Datum::Int32(v), Datum::Int32(2)]))),
+            ),
+            (
+                AggregateFunc::Ntile {
+                    order_by: order_by.clone(),
+                },
+                rows_with([Datum::Int32(2); 3]),
+            ),
+            (AggregateFunc::Dummy, vec![Datum::Dummy]),
+        ];
+        for (func, values) in &cases {
+            assert_identity_is_neutral(&arena, func, values);
+            // `BoolAnd` and `BoolOr` differ from `All` and `Any` only on an
+            // empty input, where their results are not the identity.
+            if !matches!(func, AggregateFunc::BoolAnd | AggregateFunc::BoolOr) {
+                assert_identity_is_neutral(&arena, func, &[]);
+            }
         }
     }
 }
@@ -26,7 +26,7 @@ use mz_ore::id_gen::IdGen;
 use mz_ore::stack::RecursionLimitError;
 use mz_ore::str::Indent;
 use mz_proto::{IntoRustIfSome, ProtoType, RustType, TryFromProtoError};
-use mz_repr::adt::numeric::NumericMaxScale;
+use mz_repr::adt::numeric::{Numeric, NumericMaxScale};
 use mz_repr::explain::text::text_string_at;
 use mz_repr::explain::{DummyHumanizer, ExplainConfig, ExprHumanizer, PlanRenderingContext};
 use mz_repr::{ColumnName, ColumnType, Datum, Diff, GlobalId, RelationType, Row, ScalarType};
@@ -40,6 +40,7 @@ use crate::{
 
 use self::func::{AggregateFunc, LagLeadType, TableFunc};
 
+pub mod accumulator;
 pub mod canonicalize;
 pub mod func;
 pub mod join_input_mapper;
@@ -2153,6 +2154,11 @@ impl AggregateExpr {
             | AggregateFunc::MaxDate
             | AggregateFunc::MaxTimestamp
             | AggregateFunc::MaxTimestampTz
+            | AggregateFunc::MaxInterval
+            | AggregateFunc::MaxTime
+            | AggregateFunc::MaxBytes
+            | AggregateFunc::MaxUuid
+            | AggregateFunc::MaxArray
             | AggregateFunc::MinInt16
             | AggregateFunc::MinInt32
             | AggregateFunc::MinInt64
@@ -2167,19 +2173,29 @@ impl AggregateExpr {
             | AggregateFunc::MinDate
             | AggregateFunc::MinTimestamp
             | AggregateFunc::MinTimestampTz
+            | AggregateFunc::MinInterval
+            | AggregateFunc::MinTime
+            | AggregateFunc::MinBytes
+            | AggregateFunc::MinUuid
+            | AggregateFunc::MinArray
             | AggregateFunc::Any
             | AggregateFunc::All
+            | AggregateFunc::BoolAnd
+            | AggregateFunc::BoolOr
             | AggregateFunc::Dummy => self.expr.is_literal(),
-            AggregateFunc::Count => self.expr.is_literal_null(),
+            AggregateFunc::Count | AggregateFunc::CountDistinct => self.expr.is_literal_null(),
             _ => self.expr.is_literal_err(),
         }
     }
 
     /// Extracts unique input from aggregate type
-    pub fn on_unique(&self, input_type: &[ColumnType]) -> MirScalarExpr {
-        match &self.func {
+    ///
+    /// Returns `None` if the aggregate cannot be computed from a single input
+    /// row with a scalar expression.
+    pub fn on_unique(&self, input_type: &[ColumnType]) -> Option<MirScalarExpr> {
+        let expr = match &self.func {
             // Count is one if non-null, and zero if null.
-            AggregateFunc::Count => self
+            AggregateFunc::Count | AggregateFunc::CountDistinct => self
                 .expr
                 .clone()
                 .call_unary(UnaryFunc::IsNull(crate::func::IsNull))
@@ -2187,6 +2203,20 @@ impl AggregateExpr {
                     MirScalarExpr::literal_ok(Datum::Int64(0), ScalarType::Int64),
                     MirScalarExpr::literal_ok(Datum::Int64(1), ScalarType::Int64),
                 ),
+            AggregateFunc::CountNumeric => self
+                .expr
+                .clone()
+                .call_unary(UnaryFunc::IsNull(crate::func::IsNull))
+                .if_then_else(
+                    MirScalarExpr::literal_ok(
+                        Datum::from(Numeric::from(0)),
+                        ScalarType::Numeric { max_scale: None },
+                    ),
+                    MirScalarExpr::literal_ok(
+                        Datum::from(Numeric::from(1)),
+                        ScalarType::Numeric { max_scale: None },
+                    ),
+                ),
 
             // SumInt16 takes Int16s as input, but outputs Int64s.
             AggregateFunc::SumInt16 => self
@@ -2222,6 +2252,66 @@ impl AggregateExpr {
                 ))
             }
 
+            // The average of a single value is that value, promoted to the
+            // output type.
+            AggregateFunc::AvgInt16 => self.expr.clone().call_unary(UnaryFunc::CastInt16ToNumeric(
+                scalar_func::CastInt16ToNumeric(None),
+            )),
+            AggregateFunc::AvgInt32 => self.expr.clone().call_unary(UnaryFunc::CastInt32ToNumeric(
+                scalar_func::CastInt32ToNumeric(None),
+            )),
+            AggregateFunc::AvgInt64 => self.expr.clone().call_unary(UnaryFunc::CastInt64ToNumeric(
+                scalar_func::CastInt64ToNumeric(None),
+            )),
+            AggregateFunc::AvgFloat32 => {
+                self.expr
+                    .clone()
+                    .call_unary(UnaryFunc::CastFloat32ToFloat64(
+                        scalar_func::CastFloat32ToFloat64,
+                    ))
+            }
+
+            // The population variance of a single value is zero, or null if the
+            // value is null.
+            AggregateFunc::StddevPop | AggregateFunc::VarPop => self
+                .expr
+                .clone()
+                .call_unary(UnaryFunc::IsNull(crate::func::IsNull))
+                .if_then_else(
+                    MirScalarExpr::literal_null(ScalarType::Numeric { max_scale: None }),
+                    MirScalarExpr::literal_ok(
+                        Datum::from(Numeric::zero()),
+                        ScalarType::Numeric { max_scale: None },
+                    ),
+                ),
+
+            // The sample variance of a single value is undefined.
+            AggregateFunc::StddevSamp | AggregateFunc::VarSamp => {
+                MirScalarExpr::literal_null(ScalarType::Numeric { max_scale: None })
+            }
+
+            // The population covariance of a single pair is zero, or null if
+            // either of its values is null.
+            AggregateFunc::CovarPop => self.pair_has_null().if_then_else(
+                MirScalarExpr::literal_null(ScalarType::Float64),
+                MirScalarExpr::literal_ok(Datum::from(0f64), ScalarType::Float64),
+            ),
+
+            // The regression count of a single pair is one if neither of its
+            // values is null, and zero otherwise.
+            AggregateFunc::RegrCount => self.pair_has_null().if_then_else(
+                MirScalarExpr::literal_ok(Datum::Int64(0), ScalarType::Int64),
+                MirScalarExpr::literal_ok(Datum::Int64(1), ScalarType::Int64),
+            ),
+
+            // The correlation, the sample covariance, and the least-squares
+            // line of a single pair are undefined.
+            AggregateFunc::Corr
+            | AggregateFunc::CovarSamp
+            | AggregateFunc::RegrSlope
+            | AggregateFunc::RegrIntercept
+            | AggregateFunc::RegrR2 => MirScalarExpr::literal_null(ScalarType::Float64),
+
             // JsonbAgg takes _anything_ as input, but must output a Jsonb array.
             AggregateFunc::JsonbAgg { .. } => MirScalarExpr::CallVariadic {
                 func: VariadicFunc::JsonbBuildArray,
@@ -2231,6 +2321,13 @@ impl AggregateExpr {
                     .call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(0)))],
             },
 
+            // There is no scalar function that stringifies a jsonb value into
+            // a jsonb string.
+            AggregateFunc::JsonbObjectAgg {
+                stringify_values: true,
+                ..
+            } => return None,
+
             // JsonbAgg takes _anything_ as input, but must output a Jsonb object.
             AggregateFunc::JsonbObjectAgg { .. } => {
                 let record = self
@@ -2302,6 +2399,46 @@ impl AggregateExpr {
                 }
             }
 
+            // Rank takes a list of records and outputs a list containing exactly 1 element
+            AggregateFunc::Rank { .. } => {
+                let list = self
+                    .expr
+                    .clone()
+                    // extract the list within the record
+                    .call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(0)));
+
+                // extract the expression within the list
+                let record = MirScalarExpr::CallVariadic {
+                    func: VariadicFunc::ListIndex,
+                    exprs: vec![
+                        list,
+                        MirScalarExpr::literal_ok(Datum::Int64(1), ScalarType::Int64),
+                    ],
+                };
+
+                MirScalarExpr::CallVariadic {
+                    func: VariadicFunc::ListCreate {
+                        elem_type: self
+                            .typ(input_type)
+                            .scalar_type
+                            .unwrap_list_element_type()
+                            .clone(),
+                    },
+                    exprs: vec![MirScalarExpr::CallVariadic {
+                        func: VariadicFunc::RecordCreate {
+                            field_names: vec![
+                                ColumnName::from("?rank?"),
+                                ColumnName::from("?record?"),
+                            ],
+                        },
+                        exprs: vec![
+                            MirScalarExpr::literal_ok(Datum::Int64(1), ScalarType::Int64),
+                            record,
+                        ],
+                    }],
+                }
+            }
+
             // DenseRank takes a list of records and outputs a list containing exactly 1 element
             AggregateFunc::DenseRank { .. } => {
                 let list = self
@@ -2342,6 +2479,48 @@ impl AggregateExpr {
                 }
             }
 
+            // PercentRank and CumeDist take a list of records and output a list containing exactly 1
+            // element, whose relative rank is 0 and whose cumulative distribution is 1
+            AggregateFunc::PercentRank { .. } | AggregateFunc::CumeDist { .. } => {
+                let (name, value) = match self.func {
+                    AggregateFunc::PercentRank { .. } => ("?percent_rank?", 0.0),
+                    _ => ("?cume_dist?", 1.0),
+                };
+                let list = self
+                    .expr
+                    .clone()
+                    // extract the list within the record
+                    .call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(0)));
+
+                // extract the expression within the list
+                let record = MirScalarExpr::CallVariadic {
+                    func: VariadicFunc::ListIndex,
+                    exprs: vec![
+                        list,
+                        MirScalarExpr::literal_ok(Datum::Int64(1), ScalarType::Int64),
+                    ],
+                };
+
+                MirScalarExpr::CallVariadic {
+                    func: VariadicFunc::ListCreate {
+                        elem_type: self
+                            .typ(input_type)
+                            .scalar_type
+                            .unwrap_list_element_type()
+                            .clone(),
+                    },
+                    exprs: vec![MirScalarExpr::CallVariadic {
+                        func: VariadicFunc::RecordCreate {
+                            field_names: vec![ColumnName::from(name), ColumnName::from("?record?")],
+                        },
+                        exprs: vec![
+                            MirScalarExpr::literal_ok(Datum::from(value), ScalarType::Float64),
+                            record,
+                        ],
+                    }],
+                }
+            }
+
             // The input type for LagLead is a ((OriginalRow, (InputValue, Offset, Default)), OrderByExprs...)
             AggregateFunc::LagLead { lag_lead, .. } => {
                 let tuple = self
@@ -2500,6 +2679,113 @@ impl AggregateExpr {
                 }
             }
 
+            // The input type for NthValue is a ((OriginalRow, (InputValue, N)), OrderByExprs...)
+            AggregateFunc::NthValue { window_frame, .. } => {
+                let tuple = self
+                    .expr
+                    .clone()
+                    .call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(0)));
+
+                // Get the overall return type
+                let return_type = self
+                    .typ(input_type)
+                    .scalar_type
+                    .unwrap_list_element_type()
+                    .clone();
+                let nth_value_return_type = return_type.unwrap_record_element_type()[0].clone();
+
+                // Extract the original row
+                let original_row = tuple
+                    .clone()
+                    .call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(0)));
+
+                // Extract the encoded args
+                let encoded_args =
+                    tuple.call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(1)));
+                let expr = encoded_args
+                    .clone()
+                    .call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(0)));
+                let n = encoded_args.call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(1)));
+
+                // In this case, the window always has only one element, so only an n of 1
+                // addresses a row, if the window frame includes it. Note that a null n also
+                // fails the comparison.
+                let value = if window_frame.includes_current_row() {
+                    n.call_binary(
+                        MirScalarExpr::literal_ok(Datum::Int32(1), ScalarType::Int32),
+                        crate::BinaryFunc::Eq,
+                    )
+                    .if_then_else(expr, MirScalarExpr::literal_null(nth_value_return_type))
+                } else {
+                    MirScalarExpr::literal_null(nth_value_return_type)
+                };
+
+                MirScalarExpr::CallVariadic {
+                    func: VariadicFunc::ListCreate {
+                        elem_type: return_type,
+                    },
+                    exprs: vec![MirScalarExpr::CallVariadic {
+                        func: VariadicFunc::RecordCreate {
+                            field_names: vec![
+                                ColumnName::from("?nth_value?"),
+                                ColumnName::from("?record?"),
+                            ],
+                        },
+                        exprs: vec![value, original_row],
+                    }],
+                }
+            }
+
+            // The input type for Ntile is a ((OriginalRow, N), OrderByExprs...)
+            AggregateFunc::Ntile { .. } => {
+                let tuple = self
+                    .expr
+                    .clone()
+                    .call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(0)));
+
+                // Get the overall return type
+                let return_type = self
+                    .typ(input_type)
+                    .scalar_type
+                    .unwrap_list_element_type()
+                    .clone();
+
+                // Extract the original row
+                let original_row = tuple
+                    .clone()
+                    .call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(0)));
+
+                // Extract the number of buckets
+                let n = tuple.call_unary(UnaryFunc::RecordGet(scalar_func::RecordGet(1)));
+
+                // In this case, the window always has only one element, which is in the first
+                // bucket for any positive n. Note that a null n also fails the comparison.
+                let bucket = n
+                    .call_binary(
+                        MirScalarExpr::literal_ok(Datum::Int32(0), ScalarType::Int32),
+                        crate::BinaryFunc::Gt,
+                    )
+                    .if_then_else(
+                        MirScalarExpr::literal_ok(Datum::Int64(1), ScalarType::Int64),
+                        MirScalarExpr::literal_null(ScalarType::Int64),
+                    );
+
+                MirScalarExpr::CallVariadic {
+                    func: VariadicFunc::ListCreate {
+                        elem_type: return_type,
+                    },
+                    exprs: vec![MirScalarExpr::CallVariadic {
+                        func: VariadicFunc::RecordCreate {
+                            field_names: vec![
+                                ColumnName::from("?ntile?"),
+                                ColumnName::from("?record?"),
+                            ],
+                        },
+                        exprs: vec![bucket, original_row],
+                    }],
+                }
+            }
+
             // All other variants should return the argument to the aggregation.
             AggregateFunc::MaxNumeric
             | AggregateFunc::MaxInt16
@@ -2516,6 +2802,11 @@ impl AggregateExpr {
             | AggregateFunc::MaxDate
             | AggregateFunc::MaxTimestamp
             | AggregateFunc::MaxTimestampTz
+            | AggregateFunc::MaxInterval
+            | AggregateFunc::MaxTime
+            | AggregateFunc::MaxBytes
+            | AggregateFunc::MaxUuid
+            | AggregateFunc::MaxArray
             | AggregateFunc::MinNumeric
             | AggregateFunc::MinInt16
             | AggregateFunc::MinInt32
@@ -2531,13 +2822,49 @@ impl AggregateExpr {
             | AggregateFunc::MinDate
             | AggregateFunc::MinTimestamp
             | AggregateFunc::MinTimestampTz
+            | AggregateFunc::MinInterval
+            | AggregateFunc::MinTime
+            | AggregateFunc::MinBytes
+            | AggregateFunc::MinUuid
+            | AggregateFunc::MinArray
             | AggregateFunc::SumFloat32
             | AggregateFunc::SumFloat64
             | AggregateFunc::SumNumeric
+            | AggregateFunc::SumInterval
+            | AggregateFunc::ProductFloat32
+            | AggregateFunc::ProductFloat64
+            | AggregateFunc::ProductNumeric
+            | AggregateFunc::AvgFloat64
+            | AggregateFunc::AvgNumeric
+            | AggregateFunc::AvgInterval
             | AggregateFunc::Any
             | AggregateFunc::All
+            | AggregateFunc::BoolAnd
+            | AggregateFunc::BoolOr
             | AggregateFunc::Dummy => self.expr.clone(),
-        }
+
+            // The distinct variants would have to deduplicate the elements of
+            // the single input, for which there is no scalar function.
+            AggregateFunc::ArrayConcatDistinct { .. }
+            | AggregateFunc::ListConcatDistinct { .. } => return None,
+        };
+        Some(expr)
+    }
+
+    /// Returns an expression that tests whether either value of the `(y, x)`
+    /// pair consumed by the statistical aggregates over two variables is null.
+    fn pair_has_null(&self) -> MirScalarExpr {
+        let value_is_null = |index| {
+            MirScalarExpr::CallVariadic {
+                func: VariadicFunc::ListIndex,
+                exprs: vec![
+                    self.expr.clone(),
+                    MirScalarExpr::literal_ok(Datum::Int64(index), ScalarType::Int64),
+                ],
+            }
+            .call_unary(UnaryFunc::IsNull(crate::func::IsNull))
+        };
+        value_is_null(1).or(value_is_null(2))
     }
 
     /// Returns whether the expression is COUNT(*) or not.  Note that
@@ -2696,7 +3023,7 @@ impl JoinInputCharacteristics {
 /// keywords), whereas much of the rest of SQL is defined in terms of unordered
 /// multisets. But as it turns out, the same idea can be used to optimize
 /// trivial peeks.
-#[derive(Arbitrary, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Arbitrary, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RowSetFinishing {
     /// Order rows by the given columns.
     pub order_by: Vec<ColumnOrder>,
@@ -2213,6 +2213,9 @@ pub enum EvalError {
     InvalidRange(InvalidRangeError),
     InvalidRoleId(String),
     InvalidPrivileges(String),
+    WindowPartitionTooLarge {
+        max_size: usize,
+    },
 }
 
 impl fmt::Display for EvalError {
@@ -2366,6 +2369,10 @@ impl fmt::Display for EvalError {
             EvalError::InvalidRange(e) => e.fmt(f),
             EvalError::InvalidRoleId(msg) => write!(f, "{msg}"),
             EvalError::InvalidPrivileges(msg) => write!(f, "{msg}"),
+            EvalError::WindowPartitionTooLarge { max_size } => write!(
+                f,
+                "window function partition exceeds the maximum size of {max_size} rows"
+            ),
         }
     }
 }
@@ -2395,6 +2402,10 @@ impl EvalError {
             EvalError::LikeEscapeTooLong => {
                 Some("Escape string must be empty or one character.".into())
             }
+            EvalError::WindowPartitionTooLarge { .. } => Some(
+                "Add columns to the PARTITION BY clause to split the partition into smaller ones."
+                    .into(),
+            ),
             EvalError::MzTimestampOutOfRange => Some(
                 "Integer, numeric, and text casts to mz_timestamp must be in the form of whole \
                 milliseconds since the Unix epoch. Values with fractional parts cannot be \
@@ -2569,6 +2580,9 @@ impl RustType<ProtoEvalError> for EvalError {
             EvalError::InvalidRange(error) => InvalidRange(error.into_proto()),
             EvalError::InvalidRoleId(v) => InvalidRoleId(v.clone()),
             EvalError::InvalidPrivileges(v) => InvalidPrivileges(v.clone()),
+            EvalError::WindowPartitionTooLarge { max_size } => {
+                WindowPartitionTooLarge(max_size.into_proto())
+            }
         };
         ProtoEvalError { kind: Some(kind) }
     }
@@ -2667,6 +2681,9 @@ impl RustType<ProtoEvalError> for EvalError {
                 InvalidRange(e) => Ok(EvalError::InvalidRange(e.into_rust()?)),
                 InvalidRoleId(v) => Ok(EvalError::InvalidRoleId(v)),
                 InvalidPrivileges(v) => Ok(EvalError::InvalidPrivileges(v)),
+                WindowPartitionTooLarge(v) => Ok(EvalError::WindowPartitionTooLarge {
+                    max_size: usize::from_proto(v)?,
+                }),
             },
             None => Err(TryFromProtoError::missing_field("ProtoEvalError::kind")),
         }
@@ -150,6 +150,22 @@ impl Display for Notice {
     }
 }
 
+/// The error of a failed query. Older servers report only the message.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum SqlError {
+    Structured { message: String },
+    Message(String),
+}
+
+impl Display for SqlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SqlError::Structured { message } | SqlError::Message(message) => f.write_str(message),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(untagged, deny_unknown_fields)]
 #[allow(dead_code)]
@@ -160,7 +176,7 @@ enum SqlResult {
         notices: Vec<Notice>,
     },
     Error {
-        error: String,
+        error: SqlError,
         notices: Vec<Notice>,
     },
     Ok {
@@ -86,11 +86,11 @@ use k8s_openapi::api::apps::v1::{StatefulSet, StatefulSetSpec};
 use k8s_openapi::api::core::v1::{
     Affinity, Container, ContainerPort, ContainerState, EnvVar, EnvVarSource, ObjectFieldSelector,
     Pod, PodAffinityTerm, PodAntiAffinity, PodSpec, PodTemplateSpec, ResourceRequirements, Secret,
-    Service as K8sService, ServicePort, ServiceSpec,
+    Service as K8sService, ServicePort, ServiceSpec, Toleration as K8sToleration,
 };
 use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::{LabelSelector, LabelSelectorRequirement};
-use kube::api::{Api, DeleteParams, ListParams, ObjectMeta, Patch, PatchParams};
+use kube::api::{Api, DeleteParams, ListParams, LogParams, ObjectMeta, Patch, PatchParams};
 use kube::client::Client;
 use kube::error::Error;
 use kube::runtime::{watcher, WatchStreamExt};
@@ -106,7 +106,9 @@ use mz_orchestrator::{
     LabelSelectionLogic, NamespacedOrchestrator, NotReadyReason, Orchestrator, Service,
     ServiceConfig, ServiceEvent, ServiceStatus,
 };
-use mz_orchestrator::{LabelSelector as MzLabelSelector, ServiceProcessMetrics};
+use mz_orchestrator::{
+    LabelSelector as MzLabelSelector, ServiceProcessMetrics, Toleration as MzToleration,
+};
 
 pub mod cloud_resource_controller;
 pub mod secrets;
@@ -416,6 +418,79 @@ fn parse_k8s_quantity(s: &str) -> Result<ScaledQuantity, anyhow::Error> {
     })
 }
 
+/// The label of nodes that provide preemptible capacity, like spot instances.
+///
+/// Cloud providers label their spot nodes differently, e.g.
+/// `eks.amazonaws.com/capacityType=SPOT` or `cloud.google.com/gke-spot=true`,
+/// and self-managed node groups carry no such label at all. Like
+/// `materialize.cloud/availability-zone`, this label is therefore applied by the
+/// infrastructure that provisions the node pools, which keeps the orchestrator
+/// independent of the cloud provider.
+const PREEMPTIBLE_NODE_LABEL: &str = "materialize.cloud/preemptible";
+
+/// Converts tolerations into their Kubernetes representation.
+///
+/// Returns `None` rather than an empty list if there are no tolerations, so
+/// that the pod template, and with it the pod template hash, of services
+/// without tolerations stays the same as before tolerations were supported.
+fn k8s_tolerations(tolerations: Vec<MzToleration>) -> Option<Vec<K8sToleration>> {
+    if tolerations.is_empty() {
+        return None;
+    }
+    let tolerations = tolerations
+        .into_iter()
+        .map(|toleration| K8sToleration {
+            operator: Some(
+                if toleration.value.is_some() {
+                    "Equal"
+                } else {
+                    "Exists"
+                }
+                .to_string(),
+            ),
+            key: Some(toleration.key),
+            value: toleration.value,
+            effect: toleration.effect,
+            ..Default::default()
+        })
+        .collect();
+    Some(tolerations)
+}
+
+/// Combines the node selector of a service's pods.
+///
+/// The operator's `service_node_selector` takes precedence over the node
+/// selector of the service, so that a replica cannot move its pods off the
+/// nodes the operator reserved for services. The availability zone and
+/// preemptibility of the service take precedence over both.
+fn service_node_selector(
+    operator_node_selector: &BTreeMap<String, String>,
+    mut node_selector: BTreeMap<String, String>,
+    availability_zone: Option<String>,
+    preemptible: bool,
+) -> BTreeMap<String, String> {
+    node_selector.extend(operator_node_selector.clone());
+    if let Some(availability_zone) = availability_zone {
+        node_selector.insert(
+            "materialize.cloud/availability-zone".to_string(),
+            availability_zone,
+        );
+    }
+    if preemptible {
+        node_selector.insert(PREEMPTIBLE_NODE_LABEL.to_string(), "true".to_string());
+    }
+    node_selector
+}
+
+/// Computes the hash of a pod template that is used to detect pods which do
+/// not match the current template and need to be recreated.
+fn pod_template_hash(pod_template_spec: &PodTemplateSpec) -> String {
+    let pod_template_json = serde_json::to_string(pod_template_spec).unwrap();
+    let mut hasher = Sha256::new();
+    hasher.update(pod_template_json);
+    format!("{:x}", hasher.finalize())
+}
+
 #[async_trait]
 impl NamespacedOrchestrator for NamespacedKubernetesOrchestrator {
     async fn fetch_service_metrics(
@@ -487,6 +562,23 @@ impl NamespacedOrchestrator for NamespacedKubernetesOrchestrator {
         Ok(ret.await)
     }
 
+    async fn fetch_service_logs(
+        &self,
+        id: &str,
+        process_id: u64,
+        tail: usize,
+        previous: bool,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let name = format!("{}-{id}-{process_id}", self.namespace);
+        let params = LogParams {
+            tail_lines: Some(i64::try_from(tail).unwrap_or(i64::MAX)),
+            previous,
+            ..Default::default()
+        };
+        let logs = self.pod_api.logs(&name, &params).await?;
+        Ok(logs.lines().map(String::from).collect())
+    }
+
     async fn ensure_service(
         &self,
         id: &str,
@@ -500,6 +592,9 @@ impl NamespacedOrchestrator for NamespacedKubernetesOrchestrator {
             scale,
             labels: labels_in,
             availability_zone,
+            preemptible,
+            node_selector: node_selector_in,
+            tolerations,
             anti_affinity,
         }: ServiceConfig<'_>,
     ) -> Result<Box<dyn Service>, anyhow::Error> {
@@ -615,18 +710,12 @@ impl NamespacedOrchestrator for NamespacedKubernetesOrchestrator {
             "cluster-autoscaler.kubernetes.io/safe-to-evict".to_owned() => "false".to_string(),
         };
 
-        let mut node_selector: BTreeMap<String, String> = self
-            .config
-            .service_node_selector
-            .clone()
-            .into_iter()
-            .collect();
-        if let Some(availability_zone) = availability_zone {
-            node_selector.insert(
-                "materialize.cloud/availability-zone".to_string(),
-                availability_zone,
-            );
-        }
+        let node_selector = service_node_selector(
+            &self.config.service_node_selector,
+            node_selector_in,
+            availability_zone,
+            preemptible,
+        );
 
         let container_name = image
             .splitn(2, '/')
@@ -710,6 +799,7 @@ impl NamespacedOrchestrator for NamespacedKubernetesOrchestrator {
                     ..Default::default()
                 }],
                 node_selector: Some(node_selector),
+                tolerations: k8s_tolerations(tolerations),
                 scheduler_name: self.config.scheduler_name.clone(),
                 service_account: self.config.service_account.clone(),
                 affinity: Some(Affinity {
@@ -719,10 +809,7 @@ impl NamespacedOrchestrator for NamespacedKubernetesOrchestrator {
                 ..Default::default()
             }),
         };
-        let pod_template_json = serde_json::to_string(&pod_template_spec).unwrap();
-        let mut hasher = Sha256::new();
-        hasher.update(pod_template_json);
-        let pod_template_hash = format!("{:x}", hasher.finalize());
+        let pod_template_hash = pod_template_hash(&pod_template_spec);
         let pod_template_hash_annotation = "environmentd.materialize.cloud/pod-template-hash";
         pod_template_spec
             .metadata
@@ -946,3 +1033,83 @@ impl Service for KubernetesService {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pod_template(tolerations: Option<Vec<K8sToleration>>) -> PodTemplateSpec {
+        PodTemplateSpec {
+            spec: Some(PodSpec {
+                node_selector: Some(BTreeMap::new()),
+                tolerations,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pod_template_hash_without_tolerations() {
+        // Services without tolerations must hash the same as before
+        // tolerations were supported, which never set the field. Otherwise
+        // all existing pods are recreated on upgrade.
+        let baseline = pod_template_hash(&pod_template(None));
+        let tolerations = k8s_tolerations(vec![]);
+        assert_eq!(tolerations, None);
+        assert_eq!(pod_template_hash(&pod_template(tolerations)), baseline);
+
+        let tolerations = k8s_tolerations(vec![MzToleration {
+            key: "materialize.cloud/preemptible".into(),
+            value: None,
+            effect: Some("NoSchedule".into()),
+        }]);
+        assert_ne!(pod_template_hash(&pod_template(tolerations)), baseline);
+    }
+
+    #[test]
+    fn service_node_selector_precedence() {
+        let operator = BTreeMap::from([
+            ("workload".to_string(), "materialize".to_string()),
+            (
+                "materialize.cloud/preemptible".to_string(),
+                "false".to_string(),
+            ),
+        ]);
+        let replica = BTreeMap::from([
+            ("workload".to_string(), "other".to_string()),
+            ("disk".to_string(), "ssd".to_string()),
+        ]);
+
+        // The operator's selector wins a conflicting key, and the replica's
+        // other keys are kept.
+        assert_eq!(
+            service_node_selector(&operator, replica.clone(), None, false),
+            BTreeMap::from([
+                ("disk".to_string(), "ssd".to_string()),
+                (
+                    "materialize.cloud/preemptible".to_string(),
+                    "false".to_string()
+                ),
+                ("workload".to_string(), "materialize".to_string()),
+            ])
+        );
+
+        // The availability zone and preemptibility win over both.
+        assert_eq!(
+            service_node_selector(&operator, replica, Some("az1".into()), true),
+            BTreeMap::from([
+                ("disk".to_string(), "ssd".to_string()),
+                (
+                    "materialize.cloud/availability-zone".to_string(),
+                    "az1".to_string()
+                ),
+                (
+                    "materialize.cloud/preemptible".to_string(),
+                    "true".to_string()
+                ),
+                ("workload".to_string(), "materialize".to_string()),
+            ])
+        );
+    }
+}
@@ -336,6 +336,9 @@ impl NamespacedOrchestrator for NamespacedProcessOrchestrator {
             scale,
             labels,
             availability_zone: _,
+            preemptible: _,
+            node_selector: _,
+            tolerations: _,
             anti_affinity: _,
         }: ServiceConfig<'_>,
     ) -> Result<Box<dyn Service>, anyhow::Error> {
@@ -393,6 +393,18 @@ impl NamespacedOrchestrator for NamespacedTracingOrchestrator {
         self.inner.fetch_service_metrics(id).await
     }
 
+    async fn fetch_service_logs(
+        &self,
+        id: &str,
+        process_id: u64,
+        tail: usize,
+        previous: bool,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        self.inner
+            .fetch_service_logs(id, process_id, tail, previous)
+            .await
+    }
+
     async fn ensure_service(
         &self,
         id: &str,
@@ -139,6 +139,24 @@ pub trait NamespacedOrchestrator: fmt::Debug + Send + Sync {
         &self,
         id: &str,
     ) -> Result<Vec<ServiceProcessMetrics>, anyhow::Error>;
+
+    /// Gets the most recent log lines of a process associated with a service.
+    ///
+    /// Returns at most `tail` lines, ordered from oldest to newest. If
+    /// `previous` is true, returns the logs of the previous instance of the
+    /// process, e.g. the one that crashed before the process was restarted,
+    /// rather than those of the current one. Returns `Err` if the orchestrator
+    /// does not retain the logs of its services.
+    async fn fetch_service_logs(
+        &self,
+        id: &str,
+        process_id: u64,
+        tail: usize,
+        previous: bool,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let _ = (process_id, tail, previous);
+        anyhow::bail!("fetching logs for service {id} is not supported by this orchestrator")
+    }
 }
 
 /// An event describing a status change of an orchestrated service.
@@ -228,6 +246,24 @@ pub struct LabelSelector {
     pub logic: LabelSelectionLogic,
 }
 
+/// Permits a service to be scheduled on machines with a matching taint.
+///
+/// Intended to correspond to Kubernetes tolerations, but without directly
+/// exposing Kubernetes API code to consumers of this module.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Toleration {
+    /// The key of the taint.
+    pub key: String,
+    /// The value of the taint. If absent, taints with the key are tolerated
+    /// regardless of their value.
+    #[serde(default)]
+    pub value: Option<String>,
+    /// The effect of the taint, like `NoSchedule`. If absent, taints with the
+    /// key are tolerated regardless of their effect.
+    #[serde(default)]
+    pub effect: Option<String>,
+}
+
 /// Describes the desired state of a service.
 #[derive(Derivative, Clone)]
 #[derivative(Debug)]
@@ -259,6 +295,22 @@ pub struct ServiceConfig<'a> {
     /// The availability zone the service should be run in. If no availability
     /// zone is specified, the orchestrator is free to choose one.
     pub availability_zone: Option<String>,
+    /// Whether the service may run on preemptible capacity, like spot
+    /// instances, which the infrastructure can reclaim at any time.
+    ///
+    /// Orchestrator backends without a notion of preemptible capacity ignore
+    /// this hint.
+    pub preemptible: bool,
+    /// Labels that a machine must carry for the service to be scheduled on
+    /// it, e.g. to target a pool of high-memory machines.
+    ///
+    /// The orchestrator backend may apply its own node selectors in addition
+    /// to these, which take precedence.
+    pub node_selector: BTreeMap<String, String>,
+    /// Taints of the machines that the service may be scheduled on.
+    ///
+    /// Orchestrator backends without a notion of taints ignore these.
+    pub tolerations: Vec<Toleration>,
     /// A set of label selectors declaring anti-affinity. If _all_ such selectors
     /// match for a given service, this service should not be co-scheduled on
     /// a machine with that service.
@@ -238,6 +238,13 @@ impl CastLossy<u64> for f64 {
     }
 }
 
+impl CastLossy<f64> for f32 {
+    #[allow(clippy::as_conversions)]
+    fn cast_lossy(from: f64) -> Self {
+        from as f32
+    }
+}
+
 #[test]
 fn test_try_cast_from() {
     let f64_i64_cases = vec![
@@ -41,10 +41,12 @@
 //! naming closures like above.
 
 use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use tokio::runtime::{Handle, Runtime};
-use tokio::task::{self, JoinHandle};
+use tokio::task::{self, JoinError, JoinHandle};
 
 /// Wraps a [`JoinHandle`] to abort the underlying task when dropped.
 #[derive(Debug)]
@@ -56,6 +58,16 @@ impl<T> Drop for AbortOnDropHandle<T> {
     }
 }
 
+/// Awaiting an [`AbortOnDropHandle`] waits for the underlying task to finish,
+/// like awaiting the wrapped [`JoinHandle`].
+impl<T> Future for AbortOnDropHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
 /// Extension methods for [`JoinHandle`].
 pub trait JoinHandleExt<T> {
     /// Converts a [`JoinHandle`] into a [`AbortOnDropHandle`].
@@ -16,6 +16,7 @@ use std::time::Instant;
 use futures::stream::{FuturesUnordered, StreamExt};
 use mz_ore::cast::CastFrom;
 use mz_persist::location::Blob;
+use timely::progress::Antichain;
 use tokio::sync::Semaphore;
 use tracing::{error, info};
 
@@ -101,6 +102,19 @@ pub struct ShardsUsage {
     pub unattributable_bytes: u64,
 }
 
+/// A summary of the most recent version of a shard's state.
+#[derive(Clone, Debug)]
+pub struct ShardMetadata {
+    /// The frontier at or beyond which the shard's contents can be read.
+    pub since: Antichain<u64>,
+    /// The frontier before which the shard's contents have been written.
+    pub upper: Antichain<u64>,
+    /// The number of batches in the shard's trace.
+    pub batch_count: usize,
+    /// The total encoded size of the parts in the shard's batches.
+    pub encoded_size_bytes: usize,
+}
+
 #[derive(Clone, Debug, Default)]
 struct BlobUsage {
     by_shard: BTreeMap<ShardId, ShardBlobUsage>,
@@ -159,6 +173,39 @@ impl StorageUsageClient {
             .await
     }
 
+    /// Summarizes the most recent version of the state of a single shard.
+    ///
+    /// Returns `None` if the shard has not been initialized.
+    pub async fn shard_metadata(&self, shard_id: ShardId) -> Option<ShardMetadata> {
+        let live_diffs = self
+            .state_versions
+            .fetch_recent_live_diffs::<u64>(&shard_id)
+            .await;
+        if live_diffs.0.is_empty() {
+            return None;
+        }
+        let state = self
+            .state_versions
+            .fetch_current_state::<u64>(&shard_id, live_diffs.0)
+            .await
+            .check_ts_codec(&shard_id)
+            .expect("ts should be a u64 in all prod shards");
+
+        let trace = &state.collections.trace;
+        let mut batch_count = 0;
+        let mut encoded_size_bytes = 0;
+        trace.map_batches(|b| {
+            batch_count += 1;
+            encoded_size_bytes += b.parts.iter().map(|p| p.encoded_size_bytes).sum::<usize>();
+        });
+        Some(ShardMetadata {
+            since: trace.since().clone(),
+            upper: trace.upper().clone(),
+            batch_count,
+            encoded_size_bytes,
+        })
+    }
+
     /// Computes [ShardUsage] for every shard in an env.
     pub async fn shards_usage(&self) -> ShardsUsage {
         let blob_usage = self.blob_raw_usage(BlobKeyPrefix::All).await;
@@ -1037,4 +1084,33 @@ mod tests {
             3
         );
     }
+
+    #[tokio::test]
+    async fn shard_metadata() {
+        mz_ore::test::init_logging();
+
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let shard_id = ShardId::new();
+        let client = new_test_client().await;
+        let usage = StorageUsageClient::open(client.clone());
+
+        // An uninitialized shard has no metadata.
+        assert!(usage.shard_metadata(shard_id).await.is_none());
+
+        let (mut write, _) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data[..1], 0, 2).await;
+        write.expect_compare_and_append(&data[1..], 2, 3).await;
+
+        let metadata = usage.shard_metadata(shard_id).await.unwrap();
+        assert_eq!(metadata.since, Antichain::from_elem(0));
+        assert_eq!(metadata.upper, Antichain::from_elem(3));
+        assert!(metadata.batch_count > 0);
+        assert!(metadata.encoded_size_bytes > 0);
+    }
 }
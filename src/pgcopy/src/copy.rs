@@ -62,20 +62,22 @@ pub fn encode_copy_row_binary(
     Ok(())
 }
 
+/// Encodes `row` in the text format of `COPY TO`, writing `null` for its NULL
+/// fields.
 pub fn encode_copy_row_text(
     row: Row,
     typ: &RelationType,
+    null: &str,
     out: &mut Vec<u8>,
 ) -> Result<(), io::Error> {
     let delim = b'\t';
-    let null = b"\\N";
     let mut buf = BytesMut::new();
     for (idx, field) in mz_pgrepr::values_from_row(row, typ).into_iter().enumerate() {
         if idx > 0 {
             out.push(delim);
         }
         match field {
-            None => out.extend(null),
+            None => out.extend(null.as_bytes()),
             Some(field) => {
                 buf.clear();
                 field.encode_text(&mut buf);
@@ -289,3 +289,13 @@ pub const FUNC_MZ_ACL_ITEM_GRANTOR_OID: u32 = 16_571;
 pub const FUNC_MZ_ACL_ITEM_GRANTEE_OID: u32 = 16_572;
 pub const FUNC_MZ_ACL_ITEM_PRIVILEGES_OID: u32 = 16_573;
 pub const FUNC_IS_RBAC_ENABLED_OID: u32 = 16_574;
+pub const FUNC_MAX_BYTES_OID: u32 = 16_575;
+pub const FUNC_MIN_BYTES_OID: u32 = 16_576;
+pub const FUNC_MAX_UUID_OID: u32 = 16_577;
+pub const FUNC_MIN_UUID_OID: u32 = 16_578;
+pub const FUNC_PRODUCT_FLOAT32_OID: u32 = 16_579;
+pub const FUNC_PRODUCT_FLOAT64_OID: u32 = 16_580;
+pub const FUNC_PRODUCT_NUMERIC_OID: u32 = 16_581;
+pub const FUNC_CSV_EXTRACT_DELIMITER_OID: u32 = 16_582;
+pub const FUNC_CSV_EXTRACT_DELIMITER_QUOTE_OID: u32 = 16_583;
+pub const FUNC_MZ_SHARD_METADATA_OID: u32 = 16_584;
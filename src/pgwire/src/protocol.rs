@@ -1617,12 +1617,9 @@ where
         row_desc: RelationDesc,
         mut stream: RowBatchStream,
     ) -> Result<State, io::Error> {
-        let (encode_fn, encode_format): (
-            fn(Row, &RelationType, &mut Vec<u8>) -> Result<(), std::io::Error>,
-            mz_pgrepr::Format,
-        ) = match format {
-            CopyFormat::Text => (mz_pgcopy::encode_copy_row_text, mz_pgrepr::Format::Text),
-            CopyFormat::Binary => (mz_pgcopy::encode_copy_row_binary, mz_pgrepr::Format::Binary),
+        let encode_format = match format {
+            CopyFormat::Text => mz_pgrepr::Format::Text,
+            CopyFormat::Binary => mz_pgrepr::Format::Binary,
             _ => {
                 return self
                     .error(ErrorResponse::error(
@@ -1633,6 +1630,17 @@ where
             }
         };
 
+        let null_display = self
+            .adapter_client
+            .session()
+            .vars()
+            .null_display()
+            .to_owned();
+        let encode_fn = |row: Row, typ: &RelationType, out: &mut Vec<u8>| match format {
+            CopyFormat::Text => mz_pgcopy::encode_copy_row_text(row, typ, &null_display, out),
+            _ => mz_pgcopy::encode_copy_row_binary(row, typ, out),
+        };
+
         let typ = row_desc.typ();
         let column_formats = iter::repeat(encode_format)
             .take(typ.column_types.len())
@@ -9,6 +9,7 @@
 
 //! A time interval abstract data type.
 
+use std::cmp::Ordering;
 use std::fmt::{self, Write};
 use std::time::Duration;
 
@@ -376,6 +377,16 @@ impl Interval {
         i128::from(self.micros)
     }
 
+    /// Compares intervals by their length, like PostgreSQL does, i.e., treating
+    /// a month as 30 days and a day as 24 hours. Unlike PostgreSQL, intervals of
+    /// the same length but with different fields, like `'1 day'` and
+    /// `'24 hours'`, are not equal but ordered field by field.
+    pub fn cmp_by_length(&self, other: &Self) -> Ordering {
+        self.as_microseconds()
+            .cmp(&other.as_microseconds())
+            .then_with(|| self.cmp(other))
+    }
+
     /// Converts this `Interval`'s duration into `chrono::Duration`.
     pub fn duration_as_chrono(&self) -> chrono::Duration {
         use chrono::Duration;
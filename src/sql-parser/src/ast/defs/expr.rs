@@ -694,6 +694,10 @@ pub struct WindowSpec<T: AstInfo> {
     pub partition_by: Vec<Expr<T>>,
     pub order_by: Vec<OrderByExpr<T>>,
     pub window_frame: Option<WindowFrame>,
+    // Window functions may specify `IGNORE NULLS` or `RESPECT NULLS` before
+    // the `OVER` clause.
+    pub ignore_nulls: bool,
+    pub respect_nulls: bool,
 }
 
 impl<T: AstInfo> AstDisplay for WindowSpec<T> {
@@ -820,6 +824,12 @@ impl<T: AstInfo> AstDisplay for Function<T> {
             f.write_str(")");
         }
         if let Some(o) = &self.over {
+            if o.ignore_nulls {
+                f.write_str(" IGNORE NULLS");
+            }
+            if o.respect_nulls {
+                f.write_str(" RESPECT NULLS");
+            }
             f.write_str(" OVER (");
             f.write_node(o);
             f.write_str(")");
@@ -1281,6 +1281,8 @@ pub enum ReplicaOptionName {
     IntrospectionDebugging,
     /// The `IDLE ARRANGEMENT MERGE EFFORT [=] <value>` option.
     IdleArrangementMergeEffort,
+    /// The `PREEMPTIBLE [[=] <enabled>]` option.
+    Preemptible,
 }
 
 impl AstDisplay for ReplicaOptionName {
@@ -1298,6 +1300,7 @@ impl AstDisplay for ReplicaOptionName {
             ReplicaOptionName::IdleArrangementMergeEffort => {
                 f.write_str("IDLE ARRANGEMENT MERGE EFFORT")
             }
+            ReplicaOptionName::Preemptible => f.write_str("PREEMPTIBLE"),
         }
     }
 }
@@ -624,7 +624,13 @@ impl<'a> Parser<'a> {
         } else {
             None
         };
-        let over = if self.parse_keyword(OVER) {
+        let ignore_nulls = self.parse_keywords(&[IGNORE, NULLS]);
+        let respect_nulls = self.parse_keywords(&[RESPECT, NULLS]);
+        // An `IGNORE NULLS` or `RESPECT NULLS` must be followed by `OVER`
+        let over = if ignore_nulls || respect_nulls || self.parse_keyword(OVER) {
+            if ignore_nulls || respect_nulls {
+                self.expect_keyword(OVER)?;
+            }
             // TBD: support window names (`OVER mywin`) in place of inline specification
             self.expect_token(&Token::LParen)?;
             let partition_by = if self.parse_keywords(&[PARTITION, BY]) {
@@ -650,6 +656,8 @@ impl<'a> Parser<'a> {
                 partition_by,
                 order_by,
                 window_frame,
+                ignore_nulls,
+                respect_nulls,
             })
         } else {
             None
@@ -3133,6 +3141,7 @@ impl<'a> Parser<'a> {
             COMPUTECTL,
             IDLE,
             INTROSPECTION,
+            PREEMPTIBLE,
             SIZE,
             STORAGE,
             STORAGECTL,
@@ -3159,6 +3168,7 @@ impl<'a> Parser<'a> {
                 INTERVAL => ReplicaOptionName::IntrospectionInterval,
                 _ => unreachable!(),
             },
+            PREEMPTIBLE => ReplicaOptionName::Preemptible,
             SIZE => ReplicaOptionName::Size,
             STORAGE => {
                 self.expect_keyword(ADDRESSES)?;
@@ -13,14 +13,17 @@
 use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::str::FromStr;
 
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 
-use mz_expr::func;
+use mz_expr::{func, CsvExtractOptions};
 use mz_ore::collections::CollectionExt;
+use mz_ore::str::StrExt;
+use mz_persist_client::ShardId;
 use mz_pgrepr::oid;
-use mz_repr::{ColumnName, ColumnType, Datum, RelationType, Row, ScalarBaseType, ScalarType};
+use mz_repr::{ColumnName, Datum, GlobalId, RelationType, ScalarBaseType, ScalarType};
 
 use crate::ast::{SelectStatement, Statement};
 use crate::catalog::{CatalogType, TypeCategory, TypeReference};
@@ -1688,17 +1691,6 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
         "ascii" => Scalar {
             params!(String) => UnaryFunc::Ascii(func::Ascii) => Int32, 1620;
         },
-        "avg" => Scalar {
-            params!(Int64) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Numeric, 2100;
-            params!(Int32) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Numeric, 2101;
-            params!(Int16) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Numeric, 2102;
-            params!(UInt64) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Numeric, oid::FUNC_AVG_UINT64_OID;
-            params!(UInt32) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Numeric, oid::FUNC_AVG_UINT32_OID;
-            params!(UInt16) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Numeric, oid::FUNC_AVG_UINT16_OID;
-            params!(Float32) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Float64, 2104;
-            params!(Float64) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Float64, 2105;
-            params!(Interval) => Operation::nullary(|_ecx| catalog_name_only!("avg")) => Interval, 2106;
-        },
         "bit_length" => Scalar {
             params!(Bytes) => UnaryFunc::BitLengthBytes(func::BitLengthBytes) => Int32, 1810;
             params!(String) => UnaryFunc::BitLengthString(func::BitLengthString) => Int32, 1811;
@@ -2476,6 +2468,25 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
             }) => ArrayAny, 2335;
             params!(ArrayAny) => Operation::unary(|_ecx, _e| bail_unsupported!("array_agg on arrays")) => ArrayAny, 4053;
         },
+        "avg" => Aggregate {
+            params!(Int64) => AggregateFunc::AvgInt64 => Numeric, 2100;
+            params!(Int32) => AggregateFunc::AvgInt32 => Numeric, 2101;
+            params!(Int16) => AggregateFunc::AvgInt16 => Numeric, 2102;
+            // Unsigned integers are averaged as numerics, which is exact.
+            params!(UInt64) => Operation::unary(|_ecx, e| {
+                Ok((e.call_unary(UnaryFunc::CastUint64ToNumeric(func::CastUint64ToNumeric(None))), AggregateFunc::AvgNumeric))
+            }) => Numeric, oid::FUNC_AVG_UINT64_OID;
+            params!(UInt32) => Operation::unary(|_ecx, e| {
+                Ok((e.call_unary(UnaryFunc::CastUint32ToNumeric(func::CastUint32ToNumeric(None))), AggregateFunc::AvgNumeric))
+            }) => Numeric, oid::FUNC_AVG_UINT32_OID;
+            params!(UInt16) => Operation::unary(|_ecx, e| {
+                Ok((e.call_unary(UnaryFunc::CastUint16ToNumeric(func::CastUint16ToNumeric(None))), AggregateFunc::AvgNumeric))
+            }) => Numeric, oid::FUNC_AVG_UINT16_OID;
+            params!(Float32) => AggregateFunc::AvgFloat32 => Float64, 2104;
+            params!(Float64) => AggregateFunc::AvgFloat64 => Float64, 2105;
+            params!(Numeric) => AggregateFunc::AvgNumeric => Numeric, 2103;
+            params!(Interval) => AggregateFunc::AvgInterval => Interval, 2106;
+        },
         "bool_and" => Aggregate {
             params!(Bool) => Operation::nullary(|_ecx| catalog_name_only!("bool_and")) => Bool, 2517;
         },
@@ -2506,7 +2517,12 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
             params!(Date) => AggregateFunc::MaxDate => Date, 2122;
             params!(Timestamp) => AggregateFunc::MaxTimestamp => Timestamp, 2126;
             params!(TimestampTz) => AggregateFunc::MaxTimestampTz => TimestampTz, 2127;
+            params!(Interval) => AggregateFunc::MaxInterval => Interval, 2128;
+            params!(Time) => AggregateFunc::MaxTime => Time, 2123;
+            params!(Bytes) => AggregateFunc::MaxBytes => Bytes, oid::FUNC_MAX_BYTES_OID;
+            params!(Uuid) => AggregateFunc::MaxUuid => Uuid, oid::FUNC_MAX_UUID_OID;
             params!(Numeric) => AggregateFunc::MaxNumeric => Numeric, oid::FUNC_MAX_NUMERIC_OID;
+            params!(ArrayAny) => AggregateFunc::MaxArray => ArrayAny, 2050;
         },
         "min" => Aggregate {
             params!(Bool) => AggregateFunc::MinBool => Bool, oid::FUNC_MIN_BOOL_OID;
@@ -2525,7 +2541,12 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
             params!(Date) => AggregateFunc::MinDate => Date, 2138;
             params!(Timestamp) => AggregateFunc::MinTimestamp => Timestamp, 2142;
             params!(TimestampTz) => AggregateFunc::MinTimestampTz => TimestampTz, 2143;
+            params!(Interval) => AggregateFunc::MinInterval => Interval, 2144;
+            params!(Time) => AggregateFunc::MinTime => Time, 2139;
+            params!(Bytes) => AggregateFunc::MinBytes => Bytes, oid::FUNC_MIN_BYTES_OID;
+            params!(Uuid) => AggregateFunc::MinUuid => Uuid, oid::FUNC_MIN_UUID_OID;
             params!(Numeric) => AggregateFunc::MinNumeric => Numeric, oid::FUNC_MIN_NUMERIC_OID;
+            params!(ArrayAny) => AggregateFunc::MinArray => ArrayAny, 2051;
         },
         "jsonb_agg" => Aggregate {
             params!(Any) => Operation::unary_ordered(|ecx, e, order_by| {
@@ -2592,22 +2613,28 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
             params!(Float32) => AggregateFunc::SumFloat32 => Float32, 2110;
             params!(Float64) => AggregateFunc::SumFloat64 => Float64, 2111;
             params!(Numeric) => AggregateFunc::SumNumeric => Numeric, 2114;
-            params!(Interval) => Operation::unary(|_ecx, _e| {
-                // Explicitly providing this unsupported overload
-                // prevents `sum(NULL)` from choosing the `Float64`
-                // implementation, so that we match PostgreSQL's behavior.
-                // Plus we will one day want to support this overload.
-                bail_unsupported!("sum(interval)");
-            }) => Interval, 2113;
+            params!(Interval) => AggregateFunc::SumInterval => Interval, 2113;
         },
 
         // Scalar window functions.
         "row_number" => ScalarWindow {
             params!() => ScalarWindowFunc::RowNumber => Int64, 3100;
         },
+        "rank" => ScalarWindow {
+            params!() => ScalarWindowFunc::Rank => Int64, 3101;
+        },
         "dense_rank" => ScalarWindow {
             params!() => ScalarWindowFunc::DenseRank => Int64, 3102;
         },
+        "percent_rank" => ScalarWindow {
+            params!() => ScalarWindowFunc::PercentRank => Float64, 3103;
+        },
+        "cume_dist" => ScalarWindow {
+            params!() => ScalarWindowFunc::CumeDist => Float64, 3104;
+        },
+        "ntile" => ValueWindow {
+            params!(Int32) => ValueWindowFunc::Ntile => Int64, 3105;
+        },
         "lag" => ValueWindow {
             // All args are encoded into a single record to be handled later
             params!(Any) => Operation::unary(|ecx, e| {
@@ -2678,6 +2705,18 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
         "last_value" => ValueWindow {
             params!(Any) => ValueWindowFunc::LastValue => Any, 3113;
         },
+        "nth_value" => ValueWindow {
+            // All args are encoded into a single record to be handled later
+            params!(Any, Int32) => Operation::binary(|_ecx, e, n| {
+                let e = HirScalarExpr::CallVariadic {
+                    func: VariadicFunc::RecordCreate {
+                        field_names: vec![ColumnName::from("expr"), ColumnName::from("n")]
+                    },
+                    exprs: vec![e, n],
+                };
+                Ok((e, ValueWindowFunc::NthValue))
+            }) => Any, 3114;
+        },
 
         // Table functions.
         "generate_series" => Table {
@@ -2694,7 +2733,7 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
                 Ok(TableFuncPlan {
                     expr: HirRelationExpr::CallTable {
                         func: TableFunc::GenerateSeriesInt32,
-                        exprs: vec![start, stop, HirScalarExpr::literal(Datum::Int32(1), ScalarType::Int32)],
+                        exprs: vec![start, stop],
                     },
                     column_names: vec!["generate_series".into()],
                 })
@@ -2709,16 +2748,36 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
                 })
             }) => ReturnType::set_of(Int64.into()), 1068;
             params!(Int64, Int64) => Operation::binary(move |_ecx, start, stop| {
-                let row = Row::pack([Datum::Int64(1)]);
-                let column_type = ColumnType { scalar_type: ScalarType::Int64, nullable: false };
                 Ok(TableFuncPlan {
                     expr: HirRelationExpr::CallTable {
                         func: TableFunc::GenerateSeriesInt64,
-                        exprs: vec![start, stop, HirScalarExpr::Literal(row, column_type)],
+                        exprs: vec![start, stop],
                     },
                     column_names: vec!["generate_series".into()],
                 })
             }) => ReturnType::set_of(Int64.into()), 1069;
+            params!(Numeric, Numeric, Numeric) => Operation::variadic(move |_ecx, exprs| {
+                Ok(TableFuncPlan {
+                    expr: HirRelationExpr::CallTable {
+                        func: TableFunc::GenerateSeriesNumeric,
+                        exprs,
+                    },
+                    column_names: vec!["generate_series".into()],
+                })
+            }) => ReturnType::set_of(Numeric.into()), 3259;
+            params!(Numeric, Numeric) => Operation::binary(move |_ecx, start, stop| {
+                let step = HirScalarExpr::literal(
+                    Datum::from(mz_repr::adt::numeric::Numeric::from(1)),
+                    ScalarType::Numeric { max_scale: None },
+                );
+                Ok(TableFuncPlan {
+                    expr: HirRelationExpr::CallTable {
+                        func: TableFunc::GenerateSeriesNumeric,
+                        exprs: vec![start, stop, step],
+                    },
+                    column_names: vec!["generate_series".into()],
+                })
+            }) => ReturnType::set_of(Numeric.into()), 3260;
             params!(Timestamp, Timestamp, Interval) => Operation::variadic(move |_ecx, exprs| {
                 Ok(TableFuncPlan {
                     expr: HirRelationExpr::CallTable {
@@ -2737,6 +2796,15 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
                     column_names: vec!["generate_series".into()],
                 })
             }) => ReturnType::set_of(TimestampTz.into()), 939;
+            params!(TimestampTz, TimestampTz, Interval, String) => Operation::variadic(move |_ecx, exprs| {
+                Ok(TableFuncPlan {
+                    expr: HirRelationExpr::CallTable {
+                        func: TableFunc::GenerateSeriesTimestampTzInTimezone,
+                        exprs,
+                    },
+                    column_names: vec!["generate_series".into()],
+                })
+            }) => ReturnType::set_of(TimestampTz.into()), 6274;
         },
 
         "generate_subscripts" => Table {
@@ -2749,6 +2817,15 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
                     column_names: vec!["generate_subscripts".into()],
                 })
             }) => ReturnType::set_of(Int32.into()), 1192;
+            params!(ArrayAny, Int32, Bool) => Operation::variadic(move |_ecx, exprs| {
+                Ok(TableFuncPlan {
+                    expr: HirRelationExpr::CallTable {
+                        func: TableFunc::GenerateSubscriptsArray,
+                        exprs,
+                    },
+                    column_names: vec!["generate_subscripts".into()],
+                })
+            }) => ReturnType::set_of(Int32.into()), 1191;
         },
 
         "jsonb_array_elements" => Table {
@@ -2806,6 +2883,41 @@ pub static PG_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
                 })
             }) => ReturnType::set_of(String.into()), 3931;
         },
+        "jsonb_path_query" => Table {
+            params!(Jsonb, String) => Operation::binary(move |_ecx, jsonb, path| {
+                let path = match path.into_literal_string() {
+                    None => sql_bail!("jsonb_path_query requires a string literal as its second argument"),
+                    Some(path) => mz_expr::JsonPath::parse(&path).map_err(|e| sql_err!("{}", e))?,
+                };
+                Ok(TableFuncPlan {
+                    expr: HirRelationExpr::CallTable {
+                        func: TableFunc::JsonbPathQuery(path),
+                        exprs: vec![jsonb],
+                    },
+                    column_names: vec!["jsonb_path_query".into()],
+                })
+            }) => ReturnType::set_of(Jsonb.into()), 4006;
+        },
+        "string_to_table" => Table {
+            params!(String, String) => Operation::variadic(move |_ecx, exprs| {
+                Ok(TableFuncPlan {
+                    expr: HirRelationExpr::CallTable {
+                        func: TableFunc::StringToTable,
+                        exprs,
+                    },
+                    column_names: vec!["string_to_table".into()],
+                })
+            }) => ReturnType::set_of(String.into()), 6160;
+            params!(String, String, String) => Operation::variadic(move |_ecx, exprs| {
+                Ok(TableFuncPlan {
+                    expr: HirRelationExpr::CallTable {
+                        func: TableFunc::StringToTable,
+                        exprs,
+                    },
+                    column_names: vec!["string_to_table".into()],
+                })
+            }) => ReturnType::set_of(String.into()), 6161;
+        },
         // Note that these implementations' input to `generate_series` is
         // contrived to match Flink's expected values. There are other,
         // equally valid windows we could generate.
@@ -2871,22 +2983,12 @@ pub static MZ_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
     use ScalarType::*;
     builtins! {
         "csv_extract" => Table {
-            params!(Int64, String) => Operation::binary(move |_ecx, ncols, input| {
-                let ncols = match ncols.into_literal_int64() {
-                    None | Some(i64::MIN..=0) => {
-                        sql_bail!("csv_extract number of columns must be a positive integer literal");
-                    },
-                    Some(ncols) => ncols,
-                };
-                let ncols = usize::try_from(ncols).expect("known to be greater than zero");
-                Ok(TableFuncPlan {
-                    expr: HirRelationExpr::CallTable {
-                        func: TableFunc::CsvExtract(ncols),
-                        exprs: vec![input],
-                    },
-                    column_names: (1..=ncols).map(|i| format!("column{}", i).into()).collect(),
-                })
-            }) => ReturnType::set_of(RecordAny), oid::FUNC_CSV_EXTRACT_OID;
+            params!(Int64, String) => Operation::variadic(csv_extract)
+                => ReturnType::set_of(RecordAny), oid::FUNC_CSV_EXTRACT_OID;
+            params!(Int64, String, String) => Operation::variadic(csv_extract)
+                => ReturnType::set_of(RecordAny), oid::FUNC_CSV_EXTRACT_DELIMITER_OID;
+            params!(Int64, String, String, String) => Operation::variadic(csv_extract)
+                => ReturnType::set_of(RecordAny), oid::FUNC_CSV_EXTRACT_DELIMITER_QUOTE_OID;
         },
         "concat_agg" => Aggregate {
             params!(Any) => Operation::unary(|_ecx, _e| bail_unsupported!("concat_agg")) => String, oid::FUNC_CONCAT_AGG_OID;
@@ -2965,6 +3067,11 @@ pub static MZ_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
         "mz_version_num" => Scalar {
             params!() => UnmaterializableFunc::MzVersionNum => Int32, oid::FUNC_MZ_VERSION_NUM_OID;
         },
+        "product" => Aggregate {
+            params!(Float32) => AggregateFunc::ProductFloat32 => Float32, oid::FUNC_PRODUCT_FLOAT32_OID;
+            params!(Float64) => AggregateFunc::ProductFloat64 => Float64, oid::FUNC_PRODUCT_FLOAT64_OID;
+            params!(Numeric) => AggregateFunc::ProductNumeric => Numeric, oid::FUNC_PRODUCT_NUMERIC_OID;
+        },
         "regexp_extract" => Table {
             params!(String, String) => Operation::binary(move |_ecx, regex, haystack| {
                 let regex = match regex.into_literal_string() {
@@ -3003,7 +3110,10 @@ pub static MZ_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
                 let el_typ = ecx.scalar_type(&e).unwrap_array_element_type().clone();
                 Ok(TableFuncPlan {
                     expr: HirRelationExpr::CallTable {
-                        func: TableFunc::UnnestArray { el_typ },
+                        func: TableFunc::UnnestArray {
+                            el_typ,
+                            ordinality: false,
+                        },
                         exprs: vec![e],
                     },
                     column_names: vec!["unnest".into()],
@@ -3015,7 +3125,10 @@ pub static MZ_CATALOG_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|
                 let el_typ = ecx.scalar_type(&e).unwrap_list_element_type().clone();
                 Ok(TableFuncPlan {
                     expr: HirRelationExpr::CallTable {
-                        func: TableFunc::UnnestList { el_typ },
+                        func: TableFunc::UnnestList {
+                            el_typ,
+                            ordinality: false,
+                        },
                         exprs: vec![e],
                     },
                     column_names: vec!["unnest".into()],
@@ -3095,6 +3208,30 @@ pub static MZ_INTERNAL_BUILTINS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(
         "mz_session_id" => Scalar {
             params!() => UnmaterializableFunc::MzSessionId => Uuid, oid::FUNC_MZ_SESSION_ID_OID;
         },
+        "mz_shard_metadata" => Table {
+            // Accepts either the ID of a shard or the ID of the storage
+            // collection whose data shard to inspect.
+            params!(String) => Operation::unary(move |_ecx, id| {
+                let Some(id) = id.into_literal_string() else {
+                    sql_bail!("mz_shard_metadata id must be a string literal");
+                };
+                if let (Err(e), Err(_)) = (ShardId::from_str(&id), GlobalId::from_str(&id)) {
+                    sql_bail!("invalid shard id {}: {}", id.quoted(), e);
+                }
+                Ok(TableFuncPlan {
+                    expr: HirRelationExpr::CallTable {
+                        func: TableFunc::ShardMetadata { id },
+                        exprs: vec![],
+                    },
+                    column_names: vec![
+                        "since".into(),
+                        "upper".into(),
+                        "batch_count".into(),
+                        "encoded_size_bytes".into(),
+                    ],
+                })
+            }) => ReturnType::set_of(RecordAny), oid::FUNC_MZ_SHARD_METADATA_OID;
+        },
         "mz_sleep" => Scalar {
             params!(Float64) => UnaryFunc::Sleep(func::Sleep) => TimestampTz, oid::FUNC_MZ_SLEEP_OID;
         },
@@ -3128,6 +3265,49 @@ fn array_to_string(
     })
 }
 
+/// Plans `csv_extract(ncols, input [, delimiter [, quote]])`. The delimiter and
+/// quote must be single-byte literals, except that an empty quote disables
+/// quoting.
+fn csv_extract(
+    _ecx: &ExprContext,
+    mut exprs: Vec<HirScalarExpr>,
+) -> Result<TableFuncPlan, PlanError> {
+    let mut options = CsvExtractOptions::default();
+    if exprs.len() > 3 {
+        let quote = exprs.remove(3).into_literal_string();
+        match quote.as_deref().map(str::as_bytes) {
+            Some(&[]) => options.quoting = false,
+            Some(&[byte]) => options.quote = byte,
+            _ => sql_bail!("csv_extract quote must be a single one-byte character literal"),
+        }
+    }
+    if exprs.len() > 2 {
+        let delimiter = exprs.remove(2).into_literal_string();
+        match delimiter.as_deref().map(str::as_bytes) {
+            Some(&[byte]) => options.delimiter = byte,
+            _ => sql_bail!("csv_extract delimiter must be a single one-byte character literal"),
+        }
+    }
+    let input = exprs.remove(1);
+    let ncols = match exprs.remove(0).into_literal_int64() {
+        None | Some(i64::MIN..=0) => {
+            sql_bail!("csv_extract number of columns must be a positive integer literal");
+        }
+        Some(ncols) => ncols,
+    };
+    let ncols = usize::try_from(ncols).expect("known to be greater than zero");
+    Ok(TableFuncPlan {
+        expr: HirRelationExpr::CallTable {
+            func: TableFunc::CsvExtract {
+                n_cols: ncols,
+                options,
+            },
+            exprs: vec![input],
+        },
+        column_names: (1..=ncols).map(|i| format!("column{}", i).into()).collect(),
+    })
+}
+
 /// Correlates an operator with all of its implementations.
 pub static OP_IMPLS: Lazy<BTreeMap<&'static str, Func>> = Lazy::new(|| {
     use BinaryFunc::*;
@@ -434,6 +434,8 @@ pub enum ReplicaConfig {
     Managed {
         size: String,
         availability_zone: Option<String>,
+        /// Whether the replica may be scheduled on preemptible capacity.
+        preemptible: bool,
         compute: ComputeReplicaConfig,
     },
 }
@@ -440,7 +440,10 @@ impl ScalarWindowExpr {
     {
         match self.func {
             ScalarWindowFunc::RowNumber => {}
+            ScalarWindowFunc::Rank => {}
             ScalarWindowFunc::DenseRank => {}
+            ScalarWindowFunc::PercentRank => {}
+            ScalarWindowFunc::CumeDist => {}
         }
         Ok(())
     }
@@ -452,7 +455,10 @@ impl ScalarWindowExpr {
     {
         match self.func {
             ScalarWindowFunc::RowNumber => {}
+            ScalarWindowFunc::Rank => {}
             ScalarWindowFunc::DenseRank => {}
+            ScalarWindowFunc::PercentRank => {}
+            ScalarWindowFunc::CumeDist => {}
         }
         Ok(())
     }
@@ -471,9 +477,18 @@ impl ScalarWindowExpr {
             ScalarWindowFunc::RowNumber => mz_expr::AggregateFunc::RowNumber {
                 order_by: self.order_by,
             },
+            ScalarWindowFunc::Rank => mz_expr::AggregateFunc::Rank {
+                order_by: self.order_by,
+            },
             ScalarWindowFunc::DenseRank => mz_expr::AggregateFunc::DenseRank {
                 order_by: self.order_by,
             },
+            ScalarWindowFunc::PercentRank => mz_expr::AggregateFunc::PercentRank {
+                order_by: self.order_by,
+            },
+            ScalarWindowFunc::CumeDist => mz_expr::AggregateFunc::CumeDist {
+                order_by: self.order_by,
+            },
         }
     }
 }
@@ -482,14 +497,20 @@ impl ScalarWindowExpr {
 /// Scalar Window functions
 pub enum ScalarWindowFunc {
     RowNumber,
+    Rank,
     DenseRank,
+    PercentRank,
+    CumeDist,
 }
 
 impl ScalarWindowFunc {
     pub fn output_type(&self) -> ColumnType {
         match self {
             ScalarWindowFunc::RowNumber => ScalarType::Int64.nullable(false),
+            ScalarWindowFunc::Rank => ScalarType::Int64.nullable(false),
             ScalarWindowFunc::DenseRank => ScalarType::Int64.nullable(false),
+            ScalarWindowFunc::PercentRank => ScalarType::Float64.nullable(false),
+            ScalarWindowFunc::CumeDist => ScalarType::Float64.nullable(false),
         }
     }
 }
@@ -500,6 +521,9 @@ pub struct ValueWindowExpr {
     pub expr: Box<HirScalarExpr>,
     pub order_by: Vec<ColumnOrder>,
     pub window_frame: WindowFrame,
+    /// Whether null input values are skipped, as with `IGNORE NULLS`. Only
+    /// supported by `lag` and `lead`.
+    pub ignore_nulls: bool,
 }
 
 impl ValueWindowExpr {
@@ -534,10 +558,12 @@ impl ValueWindowExpr {
             ValueWindowFunc::Lag => mz_expr::AggregateFunc::LagLead {
                 order_by: self.order_by,
                 lag_lead: mz_expr::LagLeadType::Lag,
+                ignore_nulls: self.ignore_nulls,
             },
             ValueWindowFunc::Lead => mz_expr::AggregateFunc::LagLead {
                 order_by: self.order_by,
                 lag_lead: mz_expr::LagLeadType::Lead,
+                ignore_nulls: self.ignore_nulls,
             },
             ValueWindowFunc::FirstValue => mz_expr::AggregateFunc::FirstValue {
                 order_by: self.order_by,
@@ -547,6 +573,13 @@ impl ValueWindowExpr {
                 order_by: self.order_by,
                 window_frame: self.window_frame,
             },
+            ValueWindowFunc::NthValue => mz_expr::AggregateFunc::NthValue {
+                order_by: self.order_by,
+                window_frame: self.window_frame,
+            },
+            ValueWindowFunc::Ntile => mz_expr::AggregateFunc::Ntile {
+                order_by: self.order_by,
+            },
         }
     }
 }
@@ -590,13 +623,15 @@ pub enum ValueWindowFunc {
     Lead,
     FirstValue,
     LastValue,
+    NthValue,
+    Ntile,
 }
 
 impl ValueWindowFunc {
     pub fn output_type(&self, input_type: ColumnType) -> ColumnType {
         match self {
-            ValueWindowFunc::Lag | ValueWindowFunc::Lead => {
-                // The input is a (value, offset, default) record, so extract the type of the first arg
+            ValueWindowFunc::Lag | ValueWindowFunc::Lead | ValueWindowFunc::NthValue => {
+                // The input is a (value, offset, default) or (value, n) record, so extract the type of the first arg
                 input_type.scalar_type.unwrap_record_element_type()[0]
                     .clone()
                     .nullable(true)
@@ -604,6 +639,8 @@ impl ValueWindowFunc {
             ValueWindowFunc::FirstValue | ValueWindowFunc::LastValue => {
                 input_type.scalar_type.nullable(true)
             }
+            // The bucket is null when the bucket count is null or not positive
+            ValueWindowFunc::Ntile => ScalarType::Int64.nullable(true),
         }
     }
 }
@@ -837,6 +874,11 @@ pub enum AggregateFunc {
     MaxDate,
     MaxTimestamp,
     MaxTimestampTz,
+    MaxInterval,
+    MaxTime,
+    MaxBytes,
+    MaxUuid,
+    MaxArray,
     MinNumeric,
     MinInt16,
     MinInt32,
@@ -852,6 +894,11 @@ pub enum AggregateFunc {
     MinDate,
     MinTimestamp,
     MinTimestampTz,
+    MinInterval,
+    MinTime,
+    MinBytes,
+    MinUuid,
+    MinArray,
     SumInt16,
     SumInt32,
     SumInt64,
@@ -861,6 +908,10 @@ pub enum AggregateFunc {
     SumFloat32,
     SumFloat64,
     SumNumeric,
+    SumInterval,
+    ProductFloat32,
+    ProductFloat64,
+    ProductNumeric,
     Count,
     Any,
     All,
@@ -917,6 +968,11 @@ impl AggregateFunc {
             AggregateFunc::MaxDate => mz_expr::AggregateFunc::MaxDate,
             AggregateFunc::MaxTimestamp => mz_expr::AggregateFunc::MaxTimestamp,
             AggregateFunc::MaxTimestampTz => mz_expr::AggregateFunc::MaxTimestampTz,
+            AggregateFunc::MaxInterval => mz_expr::AggregateFunc::MaxInterval,
+            AggregateFunc::MaxTime => mz_expr::AggregateFunc::MaxTime,
+            AggregateFunc::MaxBytes => mz_expr::AggregateFunc::MaxBytes,
+            AggregateFunc::MaxUuid => mz_expr::AggregateFunc::MaxUuid,
+            AggregateFunc::MaxArray => mz_expr::AggregateFunc::MaxArray,
             AggregateFunc::MinNumeric => mz_expr::AggregateFunc::MinNumeric,
             AggregateFunc::MinInt16 => mz_expr::AggregateFunc::MinInt16,
             AggregateFunc::MinInt32 => mz_expr::AggregateFunc::MinInt32,
@@ -932,6 +988,11 @@ impl AggregateFunc {
             AggregateFunc::MinDate => mz_expr::AggregateFunc::MinDate,
             AggregateFunc::MinTimestamp => mz_expr::AggregateFunc::MinTimestamp,
             AggregateFunc::MinTimestampTz => mz_expr::AggregateFunc::MinTimestampTz,
+            AggregateFunc::MinInterval => mz_expr::AggregateFunc::MinInterval,
+            AggregateFunc::MinTime => mz_expr::AggregateFunc::MinTime,
+            AggregateFunc::MinBytes => mz_expr::AggregateFunc::MinBytes,
+            AggregateFunc::MinUuid => mz_expr::AggregateFunc::MinUuid,
+            AggregateFunc::MinArray => mz_expr::AggregateFunc::MinArray,
             AggregateFunc::SumInt16 => mz_expr::AggregateFunc::SumInt16,
             AggregateFunc::SumInt32 => mz_expr::AggregateFunc::SumInt32,
             AggregateFunc::SumInt64 => mz_expr::AggregateFunc::SumInt64,
@@ -941,13 +1002,18 @@ impl AggregateFunc {
             AggregateFunc::SumFloat32 => mz_expr::AggregateFunc::SumFloat32,
             AggregateFunc::SumFloat64 => mz_expr::AggregateFunc::SumFloat64,
             AggregateFunc::SumNumeric => mz_expr::AggregateFunc::SumNumeric,
+            AggregateFunc::SumInterval => mz_expr::AggregateFunc::SumInterval,
+            AggregateFunc::ProductFloat32 => mz_expr::AggregateFunc::ProductFloat32,
+            AggregateFunc::ProductFloat64 => mz_expr::AggregateFunc::ProductFloat64,
+            AggregateFunc::ProductNumeric => mz_expr::AggregateFunc::ProductNumeric,
             AggregateFunc::Count => mz_expr::AggregateFunc::Count,
             AggregateFunc::Any => mz_expr::AggregateFunc::Any,
             AggregateFunc::All => mz_expr::AggregateFunc::All,
             AggregateFunc::JsonbAgg { order_by } => mz_expr::AggregateFunc::JsonbAgg { order_by },
-            AggregateFunc::JsonbObjectAgg { order_by } => {
-                mz_expr::AggregateFunc::JsonbObjectAgg { order_by }
-            }
+            AggregateFunc::JsonbObjectAgg { order_by } => mz_expr::AggregateFunc::JsonbObjectAgg {
+                order_by,
+                stringify_values: false,
+            },
             AggregateFunc::ArrayConcat { order_by } => {
                 mz_expr::AggregateFunc::ArrayConcat { order_by }
             }
@@ -1074,6 +1074,7 @@ impl HirScalarExpr {
                                                         .scalar_type
                                                         .unwrap_list_element_type()
                                                         .clone(),
+                                                    ordinality: false,
                                                 },
                                                 vec![mz_expr::MirScalarExpr::Column(
                                                     group_key.len(),
@@ -1292,6 +1293,7 @@ impl HirScalarExpr {
                                                         .scalar_type
                                                         .unwrap_list_element_type()
                                                         .clone(),
+                                                    ordinality: false,
                                                 },
                                                 vec![mz_expr::MirScalarExpr::Column(
                                                     group_key.len(),
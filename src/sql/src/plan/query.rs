@@ -73,8 +73,8 @@ use crate::plan::error::PlanError;
 use crate::plan::expr::{
     AbstractColumnType, AbstractExpr, AggregateExpr, AggregateFunc, BinaryFunc,
     CoercibleScalarExpr, ColumnOrder, ColumnRef, Hir, HirRelationExpr, HirScalarExpr, JoinKind,
-    ScalarWindowExpr, ScalarWindowFunc, UnaryFunc, ValueWindowExpr, VariadicFunc, WindowExpr,
-    WindowExprType,
+    ScalarWindowExpr, ScalarWindowFunc, UnaryFunc, ValueWindowExpr, ValueWindowFunc, VariadicFunc,
+    WindowExpr, WindowExprType,
 };
 use crate::plan::plan_utils::{self, JoinSide};
 use crate::plan::scope::{Scope, ScopeItem};
@@ -2607,19 +2607,48 @@ fn plan_table_function_internal(
         item: table_name,
     });
 
-    let (mut expr, mut scope) = match resolve_func(ecx, name, args)? {
-        Func::Table(impls) => {
-            let tf = func::select_impl(
-                ecx,
-                FuncSpec::Func(&resolved_name),
-                impls,
-                scalar_args,
-                vec![],
-            )?;
-            let scope = Scope::from_source(scope_name.clone(), tf.column_names);
-            (tf.expr, scope)
+    let resolved_func = resolve_func(ecx, name, args)?;
+
+    // Like PostgreSQL, `unnest` with several arguments zips its arrays
+    // together rather than resolving to a function overload.
+    let is_multi_arg_unnest =
+        scalar_args.len() > 1 && std::ptr::eq(resolved_func, &func::MZ_CATALOG_BUILTINS["unnest"]);
+
+    let (mut expr, mut scope) = if is_multi_arg_unnest {
+        let mut el_typs = vec![];
+        let mut exprs = vec![];
+        for arg in scalar_args {
+            let expr = arg.type_as_any(ecx)?;
+            match ecx.scalar_type(&expr) {
+                ScalarType::Array(el_typ) => el_typs.push(*el_typ),
+                typ => sql_bail!(
+                    "unnest with multiple arguments requires array arguments, but got {}",
+                    ecx.humanize_scalar_type(&typ)
+                ),
+            }
+            exprs.push(expr);
+        }
+        let column_names = vec![ColumnName::from("unnest"); exprs.len()];
+        let expr = HirRelationExpr::CallTable {
+            func: mz_expr::TableFunc::UnnestArrays { el_typs },
+            exprs,
+        };
+        (expr, Scope::from_source(scope_name.clone(), column_names))
+    } else {
+        match resolved_func {
+            Func::Table(impls) => {
+                let tf = func::select_impl(
+                    ecx,
+                    FuncSpec::Func(&resolved_name),
+                    impls,
+                    scalar_args,
+                    vec![],
+                )?;
+                let scope = Scope::from_source(scope_name.clone(), tf.column_names);
+                (tf.expr, scope)
+            }
+            _ => sql_bail!("{} is not a table function", name),
         }
-        _ => sql_bail!("{} is not a table function", name),
     };
 
     if with_ordinality {
@@ -4362,6 +4391,10 @@ fn plan_function<'a>(
         Func::ScalarWindow(impls) => {
             let (window_spec, _, scalar_args, partition) = validate_window_function_plan(ecx, f)?;
 
+            if window_spec.ignore_nulls {
+                bail_unsupported!(format!("IGNORE NULLS in {}", unresolved_name));
+            }
+
             let func = func::select_impl(
                 ecx,
                 FuncSpec::Func(&unresolved_name),
@@ -4393,6 +4426,12 @@ fn plan_function<'a>(
                 vec![],
             )?;
 
+            if window_spec.ignore_nulls
+                && !matches!(func, ValueWindowFunc::Lag | ValueWindowFunc::Lead)
+            {
+                bail_unsupported!(format!("IGNORE NULLS in {}", unresolved_name));
+            }
+
             let (order_by, col_orders) = plan_function_order_by(ecx, &window_spec.order_by)?;
 
             return Ok(HirScalarExpr::Windowing(WindowExpr {
@@ -4401,6 +4440,7 @@ fn plan_function<'a>(
                     expr: Box::new(expr),
                     order_by: col_orders,
                     window_frame,
+                    ignore_nulls: window_spec.ignore_nulls,
                 }),
                 partition,
                 order_by,
@@ -4672,6 +4712,9 @@ fn validate_window_function_plan<'a>(
         Some(over) => over,
         None => sql_bail!("window function {} requires an OVER clause", name),
     };
+    if window_spec.ignore_nulls && window_spec.respect_nulls {
+        sql_bail!("both IGNORE NULLS and RESPECT NULLS specified");
+    }
     let window_frame = match window_spec.window_frame.as_ref() {
         Some(frame) => plan_window_frame(frame)?,
         None => mz_expr::WindowFrame::default(),
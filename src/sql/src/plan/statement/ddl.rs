@@ -2676,7 +2676,8 @@ generate_extracted_config!(
     (Workers, u16),
     (IntrospectionInterval, OptionalInterval),
     (IntrospectionDebugging, bool, Default(false)),
-    (IdleArrangementMergeEffort, u32)
+    (IdleArrangementMergeEffort, u32),
+    (Preemptible, bool, Default(false))
 );
 
 fn plan_replica_config(
@@ -2694,6 +2695,7 @@ fn plan_replica_config(
         introspection_interval,
         introspection_debugging,
         idle_arrangement_merge_effort,
+        preemptible,
         ..
     }: ReplicaOptionExtracted = options.try_into()?;
 
@@ -2734,6 +2736,7 @@ fn plan_replica_config(
             Ok(ReplicaConfig::Managed {
                 size,
                 availability_zone,
+                preemptible,
                 compute,
             })
         }
@@ -2781,6 +2784,9 @@ fn plan_replica_config(
             if workers == 0 {
                 sql_bail!("WORKERS must be greater than 0");
             }
+            if preemptible {
+                sql_bail!("PREEMPTIBLE cannot be specified for unmanaged replicas");
+            }
 
             Ok(ReplicaConfig::Unmanaged {
                 storagectl_addrs,
@@ -50,13 +50,10 @@ where
 //   * Rewrites the `nullif` function to a `CASE` statement, to reuse the code
 //     for planning equality of datums.
 //
-//   * Rewrites `avg(col)` to `sum(col) / count(col)`, so that we can pretend
-//     the `avg` aggregate function doesn't exist from here on out. This also
-//     has the nice side effect of reusing the division planning logic, which
-//     is not trivial for some types, like decimals.
-//
-//   * Rewrites the suite of standard deviation and variance functions in a
-//     manner similar to `avg`.
+//   * Rewrites the suite of standard deviation and variance functions in
+//     terms of `sum` and `count`. This has the nice side effect of reusing the
+//     division planning logic, which is not trivial for some types, like
+//     decimals.
 //
 // TODO(sploiselle): rewrite these in terms of func::sql_op!
 struct FuncRewriter<'a> {
@@ -126,25 +123,6 @@ impl<'a> FuncRewriter<'a> {
         })
     }
 
-    fn plan_avg(expr: Expr<Aug>, filter: Option<Box<Expr<Aug>>>, distinct: bool) -> Expr<Aug> {
-        let sum = Self::plan_agg(
-            UnresolvedItemName::qualified(&["pg_catalog", "sum"]),
-            expr.clone(),
-            vec![],
-            filter.clone(),
-            distinct,
-        )
-        .call_unary(vec!["mz_internal", "mz_avg_promotion"]);
-        let count = Self::plan_agg(
-            UnresolvedItemName::qualified(&["pg_catalog", "count"]),
-            expr,
-            vec![],
-            filter,
-            distinct,
-        );
-        Self::plan_divide(sum, count)
-    }
-
     fn plan_variance(
         expr: Expr<Aug>,
         filter: Option<Box<Expr<Aug>>>,
@@ -289,7 +267,6 @@ impl<'a> FuncRewriter<'a> {
                 let expr = if args.len() == 1 {
                     let arg = args[0].clone();
                     match name.item.as_str() {
-                        "avg" => Self::plan_avg(arg, filter, distinct),
                         "variance" | "var_samp" => Self::plan_variance(arg, filter, distinct, true),
                         "var_pop" => Self::plan_variance(arg, filter, distinct, false),
                         "stddev" | "stddev_samp" => Self::plan_stddev(arg, filter, distinct, true),
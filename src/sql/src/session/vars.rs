@@ -438,6 +438,27 @@ pub const MAX_RESULT_SIZE: ServerVar<u32> = ServerVar {
     safe: true,
 };
 
+/// The maximum number of columns a single query's result may contain.
+///
+/// Postgres limits target lists to 1664 entries; we use the same default.
+pub const MAX_RESULT_COLUMNS: ServerVar<u32> = ServerVar {
+    name: UncasedStr::new("max_result_columns"),
+    value: &1664,
+    description: "The maximum number of columns in a single query's result (Materialize).",
+    internal: false,
+    safe: true,
+};
+
+/// The maximum number of cross joins, i.e. joins of relations that are not
+/// related by any join condition, in a single query's optimized plan.
+pub const MAX_QUERY_CROSS_JOINS: ServerVar<u32> = ServerVar {
+    name: UncasedStr::new("max_query_cross_joins"),
+    value: &1000,
+    description: "The maximum number of cross joins in a single query's plan (Materialize).",
+    internal: false,
+    safe: true,
+};
+
 /// The logical compaction window for builtin tables and sources that have the
 /// `retained_metrics_relation` flag set.
 ///
@@ -452,6 +473,30 @@ pub const METRICS_RETENTION: ServerVar<Duration> = ServerVar {
     safe: true,
 };
 
+/// How long to cache the results of queries at an explicit `AS OF` timestamp.
+///
+/// A zero duration disables the query result cache.
+pub const QUERY_RESULT_CACHE_TTL: ServerVar<Duration> = ServerVar {
+    name: UncasedStr::new("query_result_cache_ttl"),
+    value: &Duration::ZERO,
+    description: "How long to cache the results of queries at an explicit AS OF timestamp; zero disables the cache (Materialize).",
+    internal: true,
+    safe: true,
+};
+
+/// The maximum total size of the results in the query result cache.
+///
+/// Once the cache is full, the least recently used results are evicted.
+pub const QUERY_RESULT_CACHE_MAX_BYTES: ServerVar<usize> = ServerVar {
+    name: UncasedStr::new("query_result_cache_max_bytes"),
+    // 64 MiB
+    value: &67_108_864,
+    description:
+        "The maximum total size in bytes of the results in the query result cache (Materialize).",
+    internal: true,
+    safe: true,
+};
+
 static DEFAULT_ALLOWED_CLUSTER_REPLICA_SIZES: Lazy<Vec<Ident>> = Lazy::new(Vec::new);
 static ALLOWED_CLUSTER_REPLICA_SIZES: Lazy<ServerVar<Vec<Ident>>> = Lazy::new(|| ServerVar {
     name: UncasedStr::new("allowed_cluster_replica_sizes"),
@@ -594,6 +639,16 @@ const DATAFLOW_MAX_INFLIGHT_BYTES: ServerVar<usize> = ServerVar {
     safe: true,
 };
 
+/// The maximum number of rows in a partition of a window function.
+const MAX_WINDOW_PARTITION_SIZE: ServerVar<usize> = ServerVar {
+    name: UncasedStr::new("max_window_partition_size"),
+    value: &usize::MAX,
+    description: "The maximum number of rows in a partition of a window function, beyond which \
+                  the window function errors rather than evaluating the partition (Materialize).",
+    internal: true,
+    safe: true,
+};
+
 /// Controls [`mz_persist_client::cfg::PersistConfig::sink_minimum_batch_updates`].
 const PERSIST_SINK_MINIMUM_BATCH_UPDATES: ServerVar<usize> = ServerVar {
     name: UncasedStr::new("persist_sink_minimum_batch_updates"),
@@ -692,6 +747,15 @@ static EMIT_TRACE_ID_NOTICE: ServerVar<bool> = ServerVar {
     safe: true,
 };
 
+const NULL_DISPLAY: ServerVar<str> = ServerVar {
+    name: UncasedStr::new("null_display"),
+    value: "\\N",
+    description:
+        "Sets the string that represents NULL in text-format COPY TO output (Materialize).",
+    internal: false,
+    safe: true,
+};
+
 static MOCK_AUDIT_EVENT_TIMESTAMP: ServerVar<Option<mz_repr::Timestamp>> = ServerVar {
     name: UncasedStr::new("mock_audit_event_timestamp"),
     value: &None,
@@ -838,6 +902,7 @@ pub struct SessionVars {
     emit_trace_id_notice: SessionVar<bool>,
     auto_route_introspection_queries: SessionVar<bool>,
     enable_session_rbac_checks: SessionVar<bool>,
+    null_display: SessionVar<str>,
     // Inputs to computed variables.
     build_info: &'static BuildInfo,
     user: User,
@@ -874,6 +939,7 @@ impl SessionVars {
             emit_trace_id_notice: SessionVar::new(&EMIT_TRACE_ID_NOTICE),
             auto_route_introspection_queries: SessionVar::new(&AUTO_ROUTE_INTROSPECTION_QUERIES),
             enable_session_rbac_checks: SessionVar::new(&ENABLE_SESSION_RBAC_CHECKS),
+            null_display: SessionVar::new(&NULL_DISPLAY),
             build_info,
             user,
         }
@@ -910,6 +976,7 @@ impl SessionVars {
             &self.emit_trace_id_notice,
             &self.auto_route_introspection_queries,
             &self.enable_session_rbac_checks,
+            &self.null_display,
             self.build_info,
             &self.user,
         ];
@@ -1005,6 +1072,8 @@ impl SessionVars {
             Ok(&self.user)
         } else if name == ENABLE_SESSION_RBAC_CHECKS.name {
             Ok(&self.enable_session_rbac_checks)
+        } else if name == NULL_DISPLAY.name {
+            Ok(&self.null_display)
         } else {
             Err(VarError::UnknownParameter(name.into()))
         }
@@ -1145,6 +1214,8 @@ impl SessionVars {
             Err(VarError::ReadOnlyParameter(self.user.name()))
         } else if name == ENABLE_SESSION_RBAC_CHECKS.name {
             self.enable_session_rbac_checks.set(input, local)
+        } else if name == NULL_DISPLAY.name {
+            self.null_display.set(input, local)
         } else {
             Err(VarError::UnknownParameter(name.into()))
         }
@@ -1195,6 +1266,8 @@ impl SessionVars {
             self.auto_route_introspection_queries.reset(local);
         } else if name == ENABLE_SESSION_RBAC_CHECKS.name {
             self.enable_session_rbac_checks.reset(local);
+        } else if name == NULL_DISPLAY.name {
+            self.null_display.reset(local);
         } else if name == CLIENT_ENCODING.name
             || name == DATE_STYLE.name
             || name == FAILPOINTS.name
@@ -1243,6 +1316,7 @@ impl SessionVars {
             emit_trace_id_notice,
             auto_route_introspection_queries,
             enable_session_rbac_checks,
+            null_display,
             build_info: _,
             user: _,
         } = self;
@@ -1263,6 +1337,7 @@ impl SessionVars {
         emit_trace_id_notice.end_transaction(action);
         auto_route_introspection_queries.end_transaction(action);
         enable_session_rbac_checks.end_transaction(action);
+        null_display.end_transaction(action);
     }
 
     /// Returns the value of the `application_name` configuration parameter.
@@ -1397,6 +1472,11 @@ impl SessionVars {
         *self.enable_session_rbac_checks.value()
     }
 
+    /// Returns the value of the `null_display` configuration parameter.
+    pub fn null_display(&self) -> &str {
+        self.null_display.value()
+    }
+
     /// Returns the value of `is_superuser` configuration parameter.
     pub fn is_superuser(&self) -> bool {
         self.user.is_superuser()
@@ -1451,12 +1531,15 @@ impl Default for SystemVars {
             .with_var(&MAX_SECRETS)
             .with_var(&MAX_ROLES)
             .with_var(&MAX_RESULT_SIZE)
+            .with_var(&MAX_RESULT_COLUMNS)
+            .with_var(&MAX_QUERY_CROSS_JOINS)
             .with_var(&ALLOWED_CLUSTER_REPLICA_SIZES)
             .with_var(&ENABLE_MULTI_WORKER_STORAGE_PERSIST_SINK)
             .with_var(&PERSIST_BLOB_TARGET_SIZE)
             .with_var(&PERSIST_COMPACTION_MINIMUM_TIMEOUT)
             .with_var(&CRDB_CONNECT_TIMEOUT)
             .with_var(&DATAFLOW_MAX_INFLIGHT_BYTES)
+            .with_var(&MAX_WINDOW_PARTITION_SIZE)
             .with_var(&PERSIST_SINK_MINIMUM_BATCH_UPDATES)
             .with_var(&STORAGE_PERSIST_SINK_MINIMUM_BATCH_UPDATES)
             .with_var(&PERSIST_NEXT_LISTEN_BATCH_RETRYER_INITIAL_BACKOFF)
@@ -1465,6 +1548,8 @@ impl Default for SystemVars {
             .with_var(&PERSIST_STATS_COLLECTION_ENABLED)
             .with_var(&PERSIST_STATS_FILTER_ENABLED)
             .with_var(&METRICS_RETENTION)
+            .with_var(&QUERY_RESULT_CACHE_TTL)
+            .with_var(&QUERY_RESULT_CACHE_MAX_BYTES)
             .with_var(&MOCK_AUDIT_EVENT_TIMESTAMP)
             .with_var(&ENABLE_WITH_MUTUALLY_RECURSIVE)
             .with_var(&ENABLE_MONOTONIC_ONESHOT_SELECTS)
@@ -1681,6 +1766,16 @@ impl SystemVars {
         *self.expect_value(&MAX_RESULT_SIZE)
     }
 
+    /// Returns the value of the `max_result_columns` configuration parameter.
+    pub fn max_result_columns(&self) -> u32 {
+        *self.expect_value(&MAX_RESULT_COLUMNS)
+    }
+
+    /// Returns the value of the `max_query_cross_joins` configuration parameter.
+    pub fn max_query_cross_joins(&self) -> u32 {
+        *self.expect_value(&MAX_QUERY_CROSS_JOINS)
+    }
+
     /// Returns the value of the `allowed_cluster_replica_sizes` configuration parameter.
     pub fn allowed_cluster_replica_sizes(&self) -> Vec<String> {
         self.expect_value(&ALLOWED_CLUSTER_REPLICA_SIZES)
@@ -1754,6 +1849,11 @@ impl SystemVars {
         *self.expect_value(&DATAFLOW_MAX_INFLIGHT_BYTES)
     }
 
+    /// Returns the `max_window_partition_size` configuration parameter.
+    pub fn max_window_partition_size(&self) -> usize {
+        *self.expect_value(&MAX_WINDOW_PARTITION_SIZE)
+    }
+
     /// Returns the `persist_sink_minimum_batch_updates` configuration parameter.
     pub fn persist_sink_minimum_batch_updates(&self) -> usize {
         *self.expect_value(&PERSIST_SINK_MINIMUM_BATCH_UPDATES)
@@ -1779,6 +1879,16 @@ impl SystemVars {
         *self.expect_value(&METRICS_RETENTION)
     }
 
+    /// Returns the `query_result_cache_ttl` configuration parameter.
+    pub fn query_result_cache_ttl(&self) -> Duration {
+        *self.expect_value(&QUERY_RESULT_CACHE_TTL)
+    }
+
+    /// Returns the `query_result_cache_max_bytes` configuration parameter.
+    pub fn query_result_cache_max_bytes(&self) -> usize {
+        *self.expect_value(&QUERY_RESULT_CACHE_MAX_BYTES)
+    }
+
     /// Returns the `mock_audit_event_timestamp` configuration parameter.
     pub fn mock_audit_event_timestamp(&self) -> Option<mz_repr::Timestamp> {
         *self.expect_value(&MOCK_AUDIT_EVENT_TIMESTAMP)
@@ -2781,6 +2891,7 @@ impl From<TransactionIsolationLevel> for IsolationLevel {
 pub fn is_compute_config_var(name: &str) -> bool {
     name == MAX_RESULT_SIZE.name()
         || name == DATAFLOW_MAX_INFLIGHT_BYTES.name()
+        || name == MAX_WINDOW_PARTITION_SIZE.name()
         || is_persist_config_var(name)
 }
 
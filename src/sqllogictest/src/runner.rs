@@ -878,6 +878,10 @@ impl RunnerInner {
                 now: SYSTEM_TIME.clone(),
                 postgres_factory: postgres_factory.clone(),
                 metrics_registry: metrics_registry.clone(),
+                compute_reconnect: Default::default(),
+                storage_rehydration: Default::default(),
+                replica_service_ports: Default::default(),
+                replica_service_retry: Default::default(),
             },
             secrets_controller,
             cloud_resource_controller: None,
@@ -26,6 +26,7 @@ use serde::{Deserialize, Serialize};
 use timely::progress::frontier::{Antichain, MutableAntichain};
 use timely::PartialOrder;
 use tonic::{Request, Status, Streaming};
+use tracing::warn;
 
 use mz_cluster_client::client::{ClusterStartupEpoch, TimelyConfig};
 use mz_ore::cast::CastFrom;
@@ -113,6 +114,9 @@ pub enum StorageCommand<T = mz_repr::Timestamp> {
     /// accumulations must be correct.
     AllowCompaction(Vec<(GlobalId, Antichain<T>)>),
     CreateSinks(Vec<CreateSinkCommand<T>>),
+    /// Asks the storage server(s) to answer with a [`StorageResponse::Pong`]
+    /// carrying the same nonce, to check that they are still responsive.
+    Ping(u64),
 }
 
 /// A command that starts ingesting the given ingestion description
@@ -232,6 +236,7 @@ impl RustType<ProtoStorageCommand> for StorageCommand<mz_repr::Timestamp> {
                 StorageCommand::CreateSinks(sinks) => CreateSinks(ProtoCreateSinks {
                     sinks: sinks.into_proto(),
                 }),
+                StorageCommand::Ping(nonce) => Ping(*nonce),
             }),
         }
     }
@@ -259,6 +264,7 @@ impl RustType<ProtoStorageCommand> for StorageCommand<mz_repr::Timestamp> {
             Some(CreateSinks(ProtoCreateSinks { sinks })) => {
                 Ok(StorageCommand::CreateSinks(sinks.into_rust()?))
             }
+            Some(Ping(nonce)) => Ok(StorageCommand::Ping(nonce)),
             None => Err(TryFromProtoError::missing_field(
                 "ProtoStorageCommand::kind",
             )),
@@ -370,6 +376,8 @@ pub enum StorageResponse<T = mz_repr::Timestamp> {
 
     /// A list of statistics updates, currently only for sources.
     StatisticsUpdates(Vec<SourceStatisticsUpdate>, Vec<SinkStatisticsUpdate>),
+    /// The answer to a [`StorageCommand::Ping`] with the same nonce.
+    Pong(u64),
 }
 
 impl RustType<ProtoStorageResponse> for StorageResponse<mz_repr::Timestamp> {
@@ -411,6 +419,7 @@ impl RustType<ProtoStorageResponse> for StorageResponse<mz_repr::Timestamp> {
                             .collect(),
                     })
                 }
+                StorageResponse::Pong(nonce) => Pong(*nonce),
             }),
         }
     }
@@ -459,6 +468,7 @@ impl RustType<ProtoStorageResponse> for StorageResponse<mz_repr::Timestamp> {
                     })
                     .collect::<Result<Vec<_>, TryFromProtoError>>()?,
             )),
+            Some(Pong(nonce)) => Ok(StorageResponse::Pong(nonce)),
             None => Err(TryFromProtoError::missing_field(
                 "ProtoStorageResponse::kind",
             )),
@@ -472,12 +482,12 @@ impl Arbitrary for StorageResponse<mz_repr::Timestamp> {
 
     fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
         // TODO(guswynn): test `SourceStatisticsUpdates`
-        Union::new(vec![proptest::collection::vec(
-            (any::<GlobalId>(), any_antichain()),
-            1..4,
-        )
-        .prop_map(StorageResponse::FrontierUppers)
-        .boxed()])
+        Union::new(vec![
+            proptest::collection::vec((any::<GlobalId>(), any_antichain()), 1..4)
+                .prop_map(StorageResponse::FrontierUppers)
+                .boxed(),
+            any::<u64>().prop_map(StorageResponse::Pong).boxed(),
+        ])
     }
 }
 
@@ -492,6 +502,8 @@ pub struct PartitionedStorageState<T> {
     /// Upper frontiers for sources and sinks, both unioned across all partitions and from each
     /// individual partition.
     uppers: BTreeMap<GlobalId, (MutableAntichain<T>, Vec<Option<Antichain<T>>>)>,
+    /// For each outstanding ping, the number of partitions that have answered it.
+    pongs: BTreeMap<u64, usize>,
 }
 
 impl<T> Partitionable<StorageCommand<T>, StorageResponse<T>>
@@ -505,6 +517,7 @@ where
         PartitionedStorageState {
             parts,
             uppers: BTreeMap::new(),
+            pongs: BTreeMap::new(),
         }
     }
 }
@@ -553,6 +566,9 @@ where
                     assert!(previous.is_none(), "Protocol error: starting frontier tracking for already present identifier {:?} due to command {:?}", export.id, command);
                 }
             }
+            StorageCommand::Ping(nonce) => {
+                self.pongs.insert(*nonce, 0);
+            }
             StorageCommand::InitializationComplete
             | StorageCommand::UpdateConfiguration(_)
             | StorageCommand::AllowCompaction(_) => {
@@ -658,6 +674,20 @@ where
                     sink_stats,
                 )))
             }
+            // Present as a single pong once all partitions have answered the ping.
+            StorageResponse::Pong(nonce) => {
+                let Some(answered) = self.pongs.get_mut(&nonce) else {
+                    warn!("dropping pong for absent ping {nonce} from shard {shard_id}");
+                    return None;
+                };
+                *answered += 1;
+                if *answered == self.parts {
+                    self.pongs.remove(&nonce);
+                    Some(Ok(StorageResponse::Pong(nonce)))
+                } else {
+                    None
+                }
+            }
         }
     }
 }
@@ -750,4 +780,25 @@ mod tests {
             assert_eq!(actual.unwrap(), expect);
         }
     }
+
+    #[test]
+    fn pongs_from_all_partitions_merge() {
+        type Timestamp = mz_repr::Timestamp;
+        let mut state = <(StorageCommand<Timestamp>, StorageResponse<Timestamp>)>::new(3);
+
+        let commands = state.split_command(StorageCommand::Ping(7));
+        assert_eq!(commands, vec![Some(StorageCommand::Ping(7)); 3]);
+
+        for shard_id in 0..2 {
+            assert!(state
+                .absorb_response(shard_id, StorageResponse::Pong(7))
+                .is_none());
+        }
+        let merged = state.absorb_response(2, StorageResponse::Pong(7));
+        assert!(matches!(merged, Some(Ok(StorageResponse::Pong(7)))));
+
+        // Pongs for pings that are no longer outstanding are dropped.
+        assert!(state.absorb_response(0, StorageResponse::Pong(7)).is_none());
+        assert!(state.absorb_response(1, StorageResponse::Pong(8)).is_none());
+    }
 }
@@ -39,7 +39,7 @@ use timely::order::{PartialOrder, TotalOrder};
 use timely::progress::frontier::{AntichainRef, MutableAntichain};
 use timely::progress::{Antichain, ChangeBatch, Timestamp};
 use tokio_stream::StreamMap;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 use mz_build_info::BuildInfo;
 use mz_cluster_client::client::ClusterReplicaLocation;
@@ -78,6 +78,8 @@ mod rehydration;
 mod remap_migration;
 mod statistics;
 
+pub use crate::controller::rehydration::{RehydrationConfig, RehydrationState};
+
 include!(concat!(env!("OUT_DIR"), "/mz_storage_client.controller.rs"));
 
 pub static METADATA_COLLECTION: TypedCollection<GlobalId, DurableCollectionMetadata> =
@@ -244,6 +246,14 @@ pub trait StorageController: Debug + Send {
     /// Acquire an immutable reference to the collection state, should it exist.
     fn collection(&self, id: GlobalId) -> Result<&CollectionState<Self::Timestamp>, StorageError>;
 
+    /// Returns the persist shard holding the contents of the collection `id`, should the
+    /// collection exist.
+    fn shard_id_for(&self, id: GlobalId) -> Option<ShardId> {
+        self.collection(id)
+            .ok()
+            .map(|collection| collection.collection_metadata.data_shard)
+    }
+
     /// Creates a storage instance with the specified ID.
     ///
     /// A storage instance can have zero or one replicas. The instance is
@@ -270,6 +280,10 @@ pub trait StorageController: Debug + Send {
     /// storage instance).
     fn connect_replica(&mut self, id: StorageInstanceId, location: ClusterReplicaLocation);
 
+    /// Returns the state of communication with the replica of the storage
+    /// instance with the given ID, or `None` if the instance does not exist.
+    fn instance_rehydration_state(&self, id: StorageInstanceId) -> Option<RehydrationState>;
+
     /// Acquire a mutable reference to the collection state, should it exist.
     fn collection_mut(
         &mut self,
@@ -797,6 +811,8 @@ pub struct Controller<T: Timestamp + Lattice + Codec64 + From<EpochMillis> + Tim
     persist: Arc<PersistClientCache>,
     /// Metrics of the Storage controller
     metrics: StorageControllerMetrics,
+    /// How storage instance clients reconnect to their replicas.
+    rehydration_config: RehydrationConfig,
 }
 
 #[derive(Debug)]
@@ -1081,6 +1097,7 @@ where
             self.build_info,
             self.metrics.for_instance(id),
             self.state.envd_epoch,
+            self.rehydration_config.clone(),
         );
         if self.state.initialized {
             client.send(StorageCommand::InitializationComplete);
@@ -1093,8 +1110,17 @@ where
     }
 
     fn drop_instance(&mut self, id: StorageInstanceId) {
-        let client = self.state.clients.remove(&id);
-        assert!(client.is_some(), "storage instance {id} does not exist");
+        let client = self
+            .state
+            .clients
+            .remove(&id)
+            .unwrap_or_else(|| panic!("storage instance {id} does not exist"));
+        // Close the connection to the replica gracefully rather than aborting
+        // the rehydration task, without blocking the controller on it.
+        mz_ore::task::spawn(
+            || format!("storage_instance_shutdown:{id}"),
+            client.shutdown(),
+        );
     }
 
     fn connect_replica(&mut self, id: StorageInstanceId, location: ClusterReplicaLocation) {
@@ -1106,6 +1132,10 @@ where
         client.connect(location);
     }
 
+    fn instance_rehydration_state(&self, id: StorageInstanceId) -> Option<RehydrationState> {
+        self.state.clients.get(&id).map(|client| client.state())
+    }
+
     // Add new migrations below and precede them with a short summary of the
     // migration's purpose and optional additional commentary about safety or
     // approach.
@@ -2049,9 +2079,8 @@ where
         let mut clients = self
             .state
             .clients
-            .values_mut()
-            .map(|client| client.response_stream())
-            .enumerate()
+            .iter_mut()
+            .map(|(id, client)| (*id, client.response_stream()))
             .collect::<StreamMap<_, _>>();
 
         use tokio_stream::StreamExt;
@@ -2060,11 +2089,17 @@ where
             // before processing external commands.
             biased;
 
-            Some(m) = self.internal_response_queue.recv() => m,
-            Some((_id, m)) = clients.next() => m,
+            Some(m) = self.internal_response_queue.recv() => Ok(m),
+            Some((id, m)) = clients.next() => m.map_err(|e| (id, e)),
         };
 
-        self.state.stashed_response = Some(msg);
+        match msg {
+            Ok(msg) => self.state.stashed_response = Some(msg),
+            // The client has given up on its replica and will not produce any
+            // further responses until it is told to connect again. There is
+            // nothing left for `process` to do.
+            Err((id, e)) => error!("storage instance {id} is unavailable: {e:#}"),
+        }
     }
 
     async fn process(&mut self) -> Result<(), anyhow::Error> {
@@ -2073,6 +2108,10 @@ where
             Some(StorageResponse::FrontierUppers(updates)) => {
                 self.update_write_frontiers(&updates);
             }
+            Some(StorageResponse::Pong(_nonce)) => {
+                // Pongs answer the liveness pings of the rehydration task,
+                // which does not forward them.
+            }
             Some(StorageResponse::DroppedIds(_ids)) => {
                 // TODO(petrosagg): It looks like the storage controller never cleans up GlobalIds
                 // from its state. It should probably be done as a reaction to this response.
@@ -2208,6 +2247,7 @@ where
         postgres_factory: &StashFactory,
         envd_epoch: NonZeroI64,
         metrics_registry: MetricsRegistry,
+        rehydration_config: RehydrationConfig,
     ) -> Self {
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
 
@@ -2219,6 +2259,7 @@ where
             persist_location,
             persist: persist_clients,
             metrics: StorageControllerMetrics::new(metrics_registry),
+            rehydration_config,
         }
     }
 
@@ -14,25 +14,31 @@
 //! minimal form. If it observes a send or receive failure while communicating
 //! with the underlying client, it will reconnect the client and replay the
 //! command stream.
+//!
+//! Reconnection attempts back off according to a [`RehydrationConfig`]. If the
+//! configured number of attempts is exhausted, the client reports an error on
+//! its response stream and stops communicating with the replica until it is
+//! told to connect again.
 
 use std::collections::BTreeMap;
+use std::future::Future;
 use std::num::NonZeroI64;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
 use differential_dataflow::lattice::Lattice;
-use futures::{Stream, StreamExt};
+use futures::{future, Stream};
+use rand::Rng;
 use timely::progress::{Antichain, Timestamp};
 use timely::PartialOrder;
 use tokio::select;
-use tokio::sync::mpsc::error::TryRecvError;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::watch;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::warn;
 
 use mz_build_info::BuildInfo;
 use mz_cluster_client::client::{ClusterReplicaLocation, ClusterStartupEpoch, TimelyConfig};
-use mz_ore::retry::Retry;
 use mz_ore::task::{AbortOnDropHandle, JoinHandleExt};
 use mz_persist_types::Codec64;
 use mz_repr::GlobalId;
@@ -45,14 +51,99 @@ use crate::client::{
 use crate::metrics::RehydratingStorageClientMetrics;
 use crate::types::parameters::StorageParameters;
 
+/// Configures how a [`RehydratingStorageClient`] reconnects to its storage
+/// replica.
+///
+/// Connection attempts back off exponentially, starting at `initial_backoff`
+/// and never waiting longer than `max_backoff` between attempts. Each wait is
+/// then randomized by up to `jitter` of its length in either direction, so
+/// that storage replicas which failed together do not all reconnect at once.
+///
+/// While connected, the client can optionally ping the replica every
+/// `ping_interval` and reconnect if it does not answer within `ping_timeout`.
+/// This detects replicas that hang without closing their connection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RehydrationConfig {
+    /// The time to wait after the first failed connection attempt.
+    pub initial_backoff: Duration,
+    /// The maximum time to wait between connection attempts, before jitter.
+    pub max_backoff: Duration,
+    /// The maximum number of connection attempts before the client gives up,
+    /// or `None` to retry forever.
+    pub max_attempts: Option<usize>,
+    /// The fraction of each wait by which it may be randomly shortened or
+    /// lengthened. Clamped to `[0, 1]`.
+    pub jitter: f64,
+    /// The time between liveness pings, or `None` to not ping the replica.
+    pub ping_interval: Option<Duration>,
+    /// The time the replica has to answer a liveness ping before the client
+    /// reconnects.
+    pub ping_timeout: Duration,
+}
+
+impl Default for RehydrationConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(125),
+            max_backoff: Duration::from_secs(1),
+            max_attempts: None,
+            jitter: 0.25,
+            ping_interval: None,
+            ping_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RehydrationConfig {
+    /// Returns the time to wait after the failed connection attempt with the
+    /// given zero-based index, before jitter is applied.
+    fn backoff(&self, attempt: usize) -> Duration {
+        let factor =
+            u32::try_from(attempt).map_or(u32::MAX, |attempt| 2u32.saturating_pow(attempt));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Returns the time to wait after the failed connection attempt with the
+    /// given zero-based index, randomized using `rng`.
+    fn jittered_backoff<R: Rng>(&self, attempt: usize, rng: &mut R) -> Duration {
+        let backoff = self.backoff(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return backoff;
+        }
+        backoff.mul_f64(rng.gen_range(1.0 - jitter..=1.0 + jitter))
+    }
+}
+
+/// The state of communication between a [`RehydratingStorageClient`] and its
+/// storage replica.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RehydrationState {
+    /// The client has not yet been told which replica to connect to.
+    AwaitingAddress,
+    /// The client is (re)connecting to the replica and replaying the command
+    /// stream.
+    Rehydrating,
+    /// The replica is connected and up to date with the command stream.
+    Live,
+    /// The client has exhausted its connection attempts, and waits to be told
+    /// to connect again.
+    GaveUp,
+    /// The client has stopped communicating with the replica.
+    Done,
+}
+
 /// A storage client that replays the command stream on failure.
 ///
 /// See the [module documentation](self) for details.
 #[derive(Debug)]
 pub struct RehydratingStorageClient<T> {
     command_tx: UnboundedSender<RehydrationCommand<T>>,
-    response_rx: UnboundedReceiverStream<StorageResponse<T>>,
-    _task: AbortOnDropHandle<()>,
+    response_rx: UnboundedReceiverStream<Result<StorageResponse<T>, anyhow::Error>>,
+    state_rx: watch::Receiver<RehydrationState>,
+    task: AbortOnDropHandle<()>,
 }
 
 type PartitionedClient<T> = Partitioned<StorageGrpcClient, StorageCommand<T>, StorageResponse<T>>;
@@ -68,31 +159,33 @@ where
         build_info: &'static BuildInfo,
         metrics: RehydratingStorageClientMetrics,
         envd_epoch: NonZeroI64,
+        rehydration_config: RehydrationConfig,
     ) -> RehydratingStorageClient<T> {
         let (command_tx, command_rx) = unbounded_channel();
         let (response_tx, response_rx) = unbounded_channel();
-        let mut task = RehydrationTask {
+        let (state_tx, state_rx) = watch::channel(RehydrationState::AwaitingAddress);
+        let mut task = RehydrationTask::new(
             build_info,
             command_rx,
             response_tx,
-            sources: BTreeMap::new(),
-            sinks: BTreeMap::new(),
-            uppers: BTreeMap::new(),
-            sinces: BTreeMap::new(),
-            initialized: false,
-            current_epoch: ClusterStartupEpoch::new(envd_epoch, 0),
-            config: Default::default(),
+            state_tx,
+            envd_epoch,
+            rehydration_config,
             metrics,
-        };
+        );
         let task = mz_ore::task::spawn(|| "rehydration", async move { task.run().await });
         RehydratingStorageClient {
             command_tx,
             response_rx: UnboundedReceiverStream::new(response_rx),
-            _task: task.abort_on_drop(),
+            state_rx,
+            task: task.abort_on_drop(),
         }
     }
 
     /// Connects to the storage replica at the specified network address.
+    ///
+    /// If the client has given up on its replica, it starts a new round of
+    /// connection attempts.
     pub fn connect(&mut self, location: ClusterReplicaLocation) {
         self.command_tx
             .send(RehydrationCommand::Connect { location })
@@ -106,8 +199,33 @@ where
             .expect("rehydration task should not drop first");
     }
 
+    /// Shuts down communication with the storage replica.
+    ///
+    /// Unlike dropping the client, this waits until all previously sent
+    /// commands have been handled and the connection to the replica, if any,
+    /// has been closed. Commands are only forwarded to a live replica. While
+    /// the client awaits an address or is reconnecting, they are absorbed
+    /// into the state that would be replayed to the replica.
+    pub async fn shutdown(self) {
+        // The rehydration task may already have stopped on its own, in which
+        // case there is nothing left to signal.
+        let _ = self.command_tx.send(RehydrationCommand::Shutdown);
+        self.task.await.expect("rehydration task failed");
+    }
+
+    /// Returns the current state of communication with the storage replica.
+    pub fn state(&self) -> RehydrationState {
+        *self.state_rx.borrow()
+    }
+
     /// Returns a stream that produces responses from the underlying client.
-    pub fn response_stream(&mut self) -> impl Stream<Item = StorageResponse<T>> + '_ {
+    ///
+    /// An error in the stream indicates that the client has exhausted its
+    /// connection attempts. It produces no further responses until it is told
+    /// to connect again.
+    pub fn response_stream(
+        &mut self,
+    ) -> impl Stream<Item = Result<StorageResponse<T>, anyhow::Error>> + '_ {
         &mut self.response_rx
     }
 }
@@ -121,6 +239,8 @@ enum RehydrationCommand<T> {
     },
     /// Send the contained storage command to the replica.
     Send(StorageCommand<T>),
+    /// Close the connection to the replica and stop.
+    Shutdown,
 }
 
 /// A task that manages rehydration.
@@ -131,7 +251,9 @@ struct RehydrationTask<T> {
     /// delivered.
     command_rx: UnboundedReceiver<RehydrationCommand<T>>,
     /// A channel upon which responses from the storage replica are delivered.
-    response_tx: UnboundedSender<StorageResponse<T>>,
+    response_tx: UnboundedSender<Result<StorageResponse<T>, anyhow::Error>>,
+    /// A channel upon which the current [`RehydrationState`] is published.
+    state_tx: watch::Sender<RehydrationState>,
     /// The sources that have been observed.
     sources: BTreeMap<GlobalId, CreateSourceCommand<T>>,
     /// The exports that have been observed.
@@ -147,6 +269,16 @@ struct RehydrationTask<T> {
     current_epoch: ClusterStartupEpoch,
     /// Storage configuration that has been observed.
     config: StorageParameters,
+    /// How to back off between connection attempts.
+    rehydration_config: RehydrationConfig,
+    /// The nonce and deadline of the liveness ping that awaits its pong, if
+    /// any.
+    pending_ping: Option<(u64, Instant)>,
+    /// When the replica last proved to be live, either by connecting or by
+    /// answering a ping.
+    last_pong: Instant,
+    /// The nonce to use for the next liveness ping.
+    next_ping_nonce: u64,
     /// Prometheus metrics
     metrics: RehydratingStorageClientMetrics,
 }
@@ -167,6 +299,9 @@ enum RehydrationTaskState<T: Timestamp + Lattice> {
         /// The connected client for the replica.
         client: PartitionedClient<T>,
     },
+    /// We have given up on connecting to the storage replica. Wait to be told
+    /// to connect again.
+    GaveUp,
     /// The caller has asked us to shut down communication with this storage
     /// cluster.
     Done,
@@ -177,11 +312,57 @@ where
     T: Timestamp + Lattice + Codec64,
     StorageGrpcClient: StorageClient<T>,
 {
+    fn new(
+        build_info: &'static BuildInfo,
+        command_rx: UnboundedReceiver<RehydrationCommand<T>>,
+        response_tx: UnboundedSender<Result<StorageResponse<T>, anyhow::Error>>,
+        state_tx: watch::Sender<RehydrationState>,
+        envd_epoch: NonZeroI64,
+        rehydration_config: RehydrationConfig,
+        metrics: RehydratingStorageClientMetrics,
+    ) -> RehydrationTask<T> {
+        RehydrationTask {
+            build_info,
+            command_rx,
+            response_tx,
+            state_tx,
+            sources: BTreeMap::new(),
+            sinks: BTreeMap::new(),
+            uppers: BTreeMap::new(),
+            sinces: BTreeMap::new(),
+            initialized: false,
+            current_epoch: ClusterStartupEpoch::new(envd_epoch, 0),
+            config: Default::default(),
+            rehydration_config,
+            pending_ping: None,
+            last_pong: Instant::now(),
+            next_ping_nonce: 0,
+            metrics,
+        }
+    }
+
     async fn run(&mut self) {
         let mut state = RehydrationTaskState::AwaitAddress;
         loop {
+            let public_state = match &state {
+                RehydrationTaskState::AwaitAddress => RehydrationState::AwaitingAddress,
+                RehydrationTaskState::Rehydrate { .. } => RehydrationState::Rehydrating,
+                RehydrationTaskState::Pump { .. } => RehydrationState::Live,
+                RehydrationTaskState::GaveUp => RehydrationState::GaveUp,
+                RehydrationTaskState::Done => RehydrationState::Done,
+            };
+            self.state_tx.send_if_modified(|current| {
+                let modified = *current != public_state;
+                *current = public_state;
+                modified
+            });
+
             state = match state {
-                RehydrationTaskState::AwaitAddress => self.step_await_address().await,
+                // Like initially, a client that gave up waits for an address,
+                // absorbing the commands to replay once it is connected.
+                RehydrationTaskState::AwaitAddress | RehydrationTaskState::GaveUp => {
+                    self.step_await_address().await
+                }
                 RehydrationTaskState::Rehydrate { location } => self.step_rehydrate(location).await,
                 RehydrationTaskState::Pump { location, client } => {
                     self.step_pump(location, client).await
@@ -201,6 +382,7 @@ where
                 Some(RehydrationCommand::Send(command)) => {
                     self.absorb_command(&command);
                 }
+                Some(RehydrationCommand::Shutdown) => break RehydrationTaskState::Done,
             }
         }
     }
@@ -210,30 +392,11 @@ where
         location: ClusterReplicaLocation,
     ) -> RehydrationTaskState<T> {
         // Reconnect to the storage replica.
-        let stream = Retry::default()
-            .clamp_backoff(Duration::from_secs(1))
-            .into_retry_stream();
-        tokio::pin!(stream);
+        let mut attempt = 0;
 
         // TODO(guswynn): cluster-unification: share this code with compute, by consolidating
         // on use of `ReplicaTask`.
         let (client, timely_command) = loop {
-            let state = stream.next().await.expect("infinite stream");
-            // Drain any pending commands, in case we've been told to connect
-            // to a new storage replica.
-            loop {
-                match self.command_rx.try_recv() {
-                    Ok(RehydrationCommand::Connect { location }) => {
-                        return RehydrationTaskState::Rehydrate { location };
-                    }
-                    Ok(RehydrationCommand::Send(command)) => {
-                        self.absorb_command(&command);
-                    }
-                    Err(TryRecvError::Disconnected) => return RehydrationTaskState::Done,
-                    Err(TryRecvError::Empty) => break,
-                }
-            }
-
             let timely_config = TimelyConfig {
                 workers: location.workers,
                 // Overridden by the storage `PartitionedState` implementation.
@@ -253,24 +416,54 @@ where
                 .map(|addr| (addr, self.metrics.clone()))
                 .collect();
             let version = self.build_info.semver_version();
-            let client = StorageGrpcClient::connect_partitioned(dests, version).await;
+            let client = StorageGrpcClient::connect_partitioned(dests, version);
+            let client = match self.absorb_commands_until(client).await {
+                Ok(client) => client,
+                Err(state) => return state,
+            };
 
             let client = match client {
                 Ok(client) => client,
                 Err(e) => {
-                    if state.i >= mz_service::retry::INFO_MIN_RETRIES {
+                    let attempts = attempt + 1;
+                    if self
+                        .rehydration_config
+                        .max_attempts
+                        .map_or(false, |max_attempts| attempts >= max_attempts)
+                    {
+                        // This was the last attempt the configuration allows
+                        // for. Tell the controller and wait for it to ask for
+                        // another connection.
+                        warn!(
+                            "giving up connecting to {:?} for storage after {attempts} attempts: {e}",
+                            location,
+                        );
+                        let _ = self.response_tx.send(Err(e.context(format!(
+                            "failed to connect to storage replica after {attempts} attempts"
+                        ))));
+                        return RehydrationTaskState::GaveUp;
+                    }
+                    let backoff = self
+                        .rehydration_config
+                        .jittered_backoff(attempt, &mut rand::thread_rng());
+                    if attempt >= mz_service::retry::INFO_MIN_RETRIES {
                         tracing::info!(
                             "error connecting to {:?} for storage, retrying in {:?}: {e}",
                             location,
-                            state.next_backoff.unwrap()
+                            backoff
                         );
                     } else {
                         tracing::debug!(
                             "error connecting to {:?} for storage, retrying in {:?}: {e}",
                             location,
-                            state.next_backoff.unwrap()
+                            backoff
                         );
                     }
+                    let sleep = tokio::time::sleep(backoff);
+                    if let Err(state) = self.absorb_commands_until(sleep).await {
+                        return state;
+                    }
+                    attempt += 1;
                     continue;
                 }
             };
@@ -289,7 +482,18 @@ where
             break (client, timely_command);
         };
 
-        // Rehydrate all commands.
+        let commands = self.rehydration_commands(timely_command);
+        self.pending_ping = None;
+        self.last_pong = Instant::now();
+        self.send_commands(location, client, commands).await
+    }
+
+    /// Returns the commands that bring a newly connected replica up to date
+    /// with the commands absorbed so far, starting with `timely_command`.
+    ///
+    /// The compaction frontiers of all collections are replayed in a single
+    /// `AllowCompaction` command, after the collections are created.
+    fn rehydration_commands(&self, timely_command: StorageCommand<T>) -> Vec<StorageCommand<T>> {
         let mut commands = vec![
             timely_command,
             StorageCommand::UpdateConfiguration(self.config.clone()),
@@ -305,7 +509,36 @@ where
         if self.initialized {
             commands.push(StorageCommand::InitializationComplete)
         }
-        self.send_commands(location, client, commands).await
+        commands
+    }
+
+    /// Awaits `future` while handling the commands that arrive in the
+    /// meantime, so that a connection attempt or the backoff after one does
+    /// not delay them.
+    ///
+    /// Commands to send are absorbed, to be replayed once the replica is
+    /// connected. If the caller asks to connect to a different replica or to
+    /// shut down, `future` is abandoned and the state to move to is returned
+    /// as the error.
+    async fn absorb_commands_until<F: Future>(
+        &mut self,
+        future: F,
+    ) -> Result<F::Output, RehydrationTaskState<T>> {
+        tokio::pin!(future);
+        loop {
+            select! {
+                output = &mut future => return Ok(output),
+                command = self.command_rx.recv() => match command {
+                    None | Some(RehydrationCommand::Shutdown) => {
+                        return Err(RehydrationTaskState::Done);
+                    }
+                    Some(RehydrationCommand::Connect { location }) => {
+                        return Err(RehydrationTaskState::Rehydrate { location });
+                    }
+                    Some(RehydrationCommand::Send(command)) => self.absorb_command(&command),
+                },
+            }
+        }
     }
 
     async fn step_pump(
@@ -313,6 +546,7 @@ where
         location: ClusterReplicaLocation,
         mut client: PartitionedClient<T>,
     ) -> RehydrationTaskState<T> {
+        let liveness_deadline = self.liveness_deadline();
         select! {
             // Command from controller to forward to storage cluster.
             command = self.command_rx.recv() => match command {
@@ -322,6 +556,8 @@ where
                     self.absorb_command(&command);
                     self.send_commands(location, client, vec![command]).await
                 }
+                // Dropping the client closes the connection to the replica.
+                Some(RehydrationCommand::Shutdown) => RehydrationTaskState::Done,
             },
             // Response from storage cluster to forward to controller.
             response = client.recv() => {
@@ -338,9 +574,63 @@ where
 
                 self.send_response(location, client, response)
             }
+            // Time to ping the replica, or to give up on the outstanding ping.
+            _ = async {
+                match liveness_deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                    None => future::pending().await,
+                }
+            } => self.check_liveness(location, client).await,
+        }
+    }
+
+    /// Returns when the next liveness check is due, or `None` if the replica
+    /// should not be pinged.
+    ///
+    /// Replicas only answer pings once initialization is complete, so we do
+    /// not ping them before that.
+    fn liveness_deadline(&self) -> Option<Instant> {
+        let ping_interval = self.rehydration_config.ping_interval?;
+        if !self.initialized {
+            return None;
+        }
+        match self.pending_ping {
+            Some((_nonce, deadline)) => Some(deadline),
+            None => Some(self.last_pong + ping_interval),
+        }
+    }
+
+    /// Pings the replica, or reconnects to it if it did not answer the
+    /// outstanding ping in time.
+    async fn check_liveness(
+        &mut self,
+        location: ClusterReplicaLocation,
+        client: PartitionedClient<T>,
+    ) -> RehydrationTaskState<T> {
+        match self.next_ping() {
+            Some(ping) => self.send_commands(location, client, vec![ping]).await,
+            None => {
+                warn!(
+                    "storage cluster did not answer ping within {:?}, reconnecting",
+                    self.rehydration_config.ping_timeout
+                );
+                RehydrationTaskState::Rehydrate { location }
+            }
         }
     }
 
+    /// Starts a new liveness ping and returns the command to send, or returns
+    /// `None` if the outstanding ping was not answered.
+    fn next_ping(&mut self) -> Option<StorageCommand<T>> {
+        if self.pending_ping.is_some() {
+            return None;
+        }
+        let nonce = self.next_ping_nonce;
+        self.next_ping_nonce += 1;
+        self.pending_ping = Some((nonce, Instant::now() + self.rehydration_config.ping_timeout));
+        Some(StorageCommand::Ping(nonce))
+    }
+
     async fn send_commands(
         &mut self,
         location: ClusterReplicaLocation,
@@ -364,7 +654,7 @@ where
         match response {
             Ok(response) => {
                 if let Some(response) = self.absorb_response(response) {
-                    if self.response_tx.send(response).is_err() {
+                    if self.response_tx.send(Ok(response)).is_err() {
                         RehydrationTaskState::Done
                     } else {
                         RehydrationTaskState::Pump { location, client }
@@ -386,6 +676,10 @@ where
                 // We assume these are ordered correctly
             }
             StorageCommand::InitializationComplete => self.initialized = true,
+            StorageCommand::Ping(_) => {
+                // Liveness pings are generated by this task and need not be
+                // replayed.
+            }
             StorageCommand::UpdateConfiguration(params) => {
                 self.config.update(params.clone());
             }
@@ -459,6 +753,259 @@ where
                 // Just forward it along.
                 Some(StorageResponse::StatisticsUpdates(source_stats, sink_stats))
             }
+            StorageResponse::Pong(nonce) => {
+                // Pongs answer the pings of this task, so we keep them to
+                // ourselves.
+                if matches!(self.pending_ping, Some((pending, _)) if pending == nonce) {
+                    self.pending_ping = None;
+                    self.last_pong = Instant::now();
+                }
+                None
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::num::NonZeroI64;
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use mz_cluster_client::client::{ClusterReplicaLocation, ClusterStartupEpoch, TimelyConfig};
+    use mz_ore::metrics::MetricsRegistry;
+    use proptest::prelude::*;
+    use timely::progress::Antichain;
+    use tokio::sync::mpsc::unbounded_channel;
+    use tokio::sync::watch;
+
+    use crate::client::{CreateSourceCommand, StorageCommand, StorageResponse};
+    use crate::metrics::StorageControllerMetrics;
+    use crate::types::instances::StorageInstanceId;
+
+    use super::{RehydratingStorageClient, RehydrationConfig, RehydrationState, RehydrationTask};
+
+    #[test]
+    fn jittered_backoff_stays_within_bounds() {
+        let config = RehydrationConfig::default();
+        let mut rng = rand::thread_rng();
+        for attempt in 0..64 {
+            let backoff = config.backoff(attempt);
+            assert!(backoff <= config.max_backoff);
+            let min = backoff.mul_f64(1.0 - config.jitter);
+            let max = backoff.mul_f64(1.0 + config.jitter);
+            for _ in 0..100 {
+                let jittered = config.jittered_backoff(attempt, &mut rng);
+                assert!(
+                    min <= jittered && jittered <= max,
+                    "{jittered:?} outside of [{min:?}, {max:?}]"
+                );
+            }
+        }
+
+        let config = RehydrationConfig {
+            jitter: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.jittered_backoff(0, &mut rng),
+            Duration::from_millis(125)
+        );
+    }
+
+    #[tokio::test]
+    async fn shutdown_while_reconnecting() {
+        // Reserve a port and release it again, so that connecting to it fails.
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+
+        let metrics = StorageControllerMetrics::new(MetricsRegistry::new());
+        let mut client = RehydratingStorageClient::<mz_repr::Timestamp>::new(
+            &mz_build_info::DUMMY_BUILD_INFO,
+            metrics.for_instance(StorageInstanceId::User(1)),
+            NonZeroI64::new(1).unwrap(),
+            RehydrationConfig {
+                initial_backoff: Duration::from_secs(3600),
+                max_backoff: Duration::from_secs(3600),
+                ..Default::default()
+            },
+        );
+        client.connect(ClusterReplicaLocation {
+            ctl_addrs: vec![addr.clone()],
+            dataflow_addrs: vec![addr],
+            workers: 1,
+        });
+
+        let mut state_rx = client.state_rx.clone();
+        while *state_rx.borrow_and_update() != RehydrationState::Rehydrating {
+            state_rx.changed().await.unwrap();
+        }
+
+        // The client now backs off for an hour between connection attempts,
+        // which must not delay the shutdown.
+        tokio::time::timeout(Duration::from_secs(10), client.shutdown())
+            .await
+            .expect("shutdown did not complete while reconnecting");
+    }
+
+    #[tokio::test]
+    async fn reconnect_after_giving_up() {
+        // Reserve a port and release it again, so that connecting to it fails.
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let location = ClusterReplicaLocation {
+            ctl_addrs: vec![addr.clone()],
+            dataflow_addrs: vec![addr],
+            workers: 1,
+        };
+
+        let metrics = StorageControllerMetrics::new(MetricsRegistry::new());
+        let mut client = RehydratingStorageClient::<mz_repr::Timestamp>::new(
+            &mz_build_info::DUMMY_BUILD_INFO,
+            metrics.for_instance(StorageInstanceId::User(1)),
+            NonZeroI64::new(1).unwrap(),
+            RehydrationConfig {
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+                max_attempts: Some(2),
+                ..Default::default()
+            },
+        );
+        let mut state_rx = client.state_rx.clone();
+
+        // The client reports a terminal error once it runs out of attempts.
+        client.connect(location.clone());
+        let response = tokio::time::timeout(Duration::from_secs(10), async {
+            client.response_stream().next().await
+        })
+        .await
+        .expect("client did not give up");
+        let err = response.expect("response stream ended").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("failed to connect to storage replica after 2 attempts"),
+            "{err:#}"
+        );
+        while *state_rx.borrow_and_update() != RehydrationState::GaveUp {
+            state_rx.changed().await.unwrap();
+        }
+
+        // The client still accepts commands, and starts over when it is told
+        // to connect again.
+        client.send(StorageCommand::InitializationComplete);
+        client.connect(location);
+        while *state_rx.borrow_and_update() != RehydrationState::Rehydrating {
+            state_rx.changed().await.unwrap();
+        }
+
+        tokio::time::timeout(Duration::from_secs(10), client.shutdown())
+            .await
+            .expect("shutdown did not complete after giving up");
+    }
+
+    #[test]
+    fn unanswered_ping_trips_liveness_deadline() {
+        let (_command_tx, command_rx) = unbounded_channel();
+        let (response_tx, _response_rx) = unbounded_channel();
+        let (state_tx, _state_rx) = watch::channel(RehydrationState::AwaitingAddress);
+        let metrics = StorageControllerMetrics::new(MetricsRegistry::new());
+        let ping_interval = Duration::from_secs(1);
+        let mut task = RehydrationTask::<mz_repr::Timestamp>::new(
+            &mz_build_info::DUMMY_BUILD_INFO,
+            command_rx,
+            response_tx,
+            state_tx,
+            NonZeroI64::new(1).unwrap(),
+            RehydrationConfig {
+                ping_interval: Some(ping_interval),
+                ..Default::default()
+            },
+            metrics.for_instance(StorageInstanceId::User(1)),
+        );
+
+        // Replicas are not pinged before initialization is complete.
+        assert_eq!(task.liveness_deadline(), None);
+        task.absorb_command(&StorageCommand::InitializationComplete);
+        assert_eq!(
+            task.liveness_deadline(),
+            Some(task.last_pong + ping_interval)
+        );
+
+        // An answered ping schedules the next one.
+        assert_eq!(task.next_ping(), Some(StorageCommand::Ping(0)));
+        let (_nonce, deadline) = task.pending_ping.unwrap();
+        assert_eq!(task.liveness_deadline(), Some(deadline));
+        assert_eq!(task.absorb_response(StorageResponse::Pong(0)), None);
+        assert_eq!(task.pending_ping, None);
+
+        // A ping without a matching pong trips the deadline.
+        assert_eq!(task.next_ping(), Some(StorageCommand::Ping(1)));
+        let (_nonce, deadline) = task.pending_ping.unwrap();
+        assert_eq!(task.absorb_response(StorageResponse::Pong(0)), None);
+        assert_eq!(task.liveness_deadline(), Some(deadline));
+        assert_eq!(task.next_ping(), None);
+    }
+
+    fn compaction_replayed_after_reconnect(source: CreateSourceCommand<mz_repr::Timestamp>) {
+        let (_command_tx, command_rx) = unbounded_channel();
+        let (response_tx, _response_rx) = unbounded_channel();
+        let (state_tx, _state_rx) = watch::channel(RehydrationState::AwaitingAddress);
+        let metrics = StorageControllerMetrics::new(MetricsRegistry::new());
+        let mut task = RehydrationTask::<mz_repr::Timestamp>::new(
+            &mz_build_info::DUMMY_BUILD_INFO,
+            command_rx,
+            response_tx,
+            state_tx,
+            NonZeroI64::new(1).unwrap(),
+            RehydrationConfig::default(),
+            metrics.for_instance(StorageInstanceId::User(1)),
+        );
+
+        let id = source.id;
+        let since = |t: u64| Antichain::from_elem(mz_repr::Timestamp::from(t));
+        task.absorb_command(&StorageCommand::CreateSources(vec![source.clone()]));
+        task.absorb_command(&StorageCommand::AllowCompaction(vec![(id, since(5))]));
+        task.absorb_command(&StorageCommand::AllowCompaction(vec![(id, since(7))]));
+        task.absorb_command(&StorageCommand::InitializationComplete);
+
+        // The replica reconnects, and learns the latest compaction frontier of
+        // the source after the source is created again.
+        let timely_command = StorageCommand::CreateTimely {
+            config: TimelyConfig::default(),
+            epoch: ClusterStartupEpoch::new(NonZeroI64::new(1).unwrap(), 1),
+        };
+        let commands = task.rehydration_commands(timely_command.clone());
+        assert_eq!(
+            commands,
+            vec![
+                timely_command,
+                StorageCommand::UpdateConfiguration(Default::default()),
+                StorageCommand::CreateSources(vec![source]),
+                StorageCommand::CreateSinks(vec![]),
+                StorageCommand::AllowCompaction(vec![(id, since(7))]),
+                StorageCommand::InitializationComplete,
+            ]
+        );
+
+        // The frontiers of dropped collections are not replayed.
+        task.absorb_response(StorageResponse::DroppedIds(vec![id]));
+        let commands = task.rehydration_commands(StorageCommand::InitializationComplete);
+        assert!(commands.contains(&StorageCommand::AllowCompaction(vec![])));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn rehydration_replays_compaction() {
+        proptest!(ProptestConfig::with_cases(8), |(source in any::<CreateSourceCommand<mz_repr::Timestamp>>())| {
+            // The proptest! macro interferes with rustfmt.
+            compaction_replayed_after_reconnect(source)
+        });
+    }
+}
@@ -504,6 +504,12 @@ impl<'w, A: Allocate> Worker<'w, A> {
             {
                 let mut internal_cmd_tx = command_sequencer.borrow_mut();
                 for cmd in cmds {
+                    // Pings only check that this loop is still running, so we
+                    // can answer them right away.
+                    if let StorageCommand::Ping(nonce) = cmd {
+                        self.send_storage_response(&response_tx, StorageResponse::Pong(nonce));
+                        continue;
+                    }
                     self.storage_state.handle_storage_command(
                         self.timely_worker.index(),
                         &mut *internal_cmd_tx,
@@ -943,7 +949,8 @@ impl<'w, A: Allocate> Worker<'w, A> {
                 StorageCommand::InitializationComplete
                 | StorageCommand::UpdateConfiguration(_)
                 | StorageCommand::CreateSources(_)
-                | StorageCommand::CreateSinks(_) => (),
+                | StorageCommand::CreateSinks(_)
+                | StorageCommand::Ping(_) => (),
             }
         }
 
@@ -1011,7 +1018,8 @@ impl<'w, A: Allocate> Worker<'w, A> {
                 }
                 StorageCommand::InitializationComplete
                 | StorageCommand::UpdateConfiguration(_)
-                | StorageCommand::AllowCompaction(_) => (),
+                | StorageCommand::AllowCompaction(_)
+                | StorageCommand::Ping(_) => (),
             }
         }
 
@@ -1073,6 +1081,10 @@ impl StorageState {
         match cmd {
             StorageCommand::CreateTimely { .. } => panic!("CreateTimely must be captured before"),
             StorageCommand::InitializationComplete => (),
+            // Pings are answered by the worker's main loop. The controller
+            // does not send them before initialization is complete, so there
+            // is nothing to answer during reconciliation either.
+            StorageCommand::Ping(_) => (),
             StorageCommand::UpdateConfiguration(params) => {
                 tracing::info!("Applying configuration update: {params:?}");
                 params.persist.apply(self.persist_clients.cfg());
@@ -405,6 +405,11 @@ impl ColumnKnowledge {
                             | AggregateFunc::MaxDate
                             | AggregateFunc::MaxTimestamp
                             | AggregateFunc::MaxTimestampTz
+                            | AggregateFunc::MaxInterval
+                            | AggregateFunc::MaxTime
+                            | AggregateFunc::MaxBytes
+                            | AggregateFunc::MaxUuid
+                            | AggregateFunc::MaxArray
                             | AggregateFunc::MinInt16
                             | AggregateFunc::MinInt32
                             | AggregateFunc::MinInt64
@@ -419,12 +424,32 @@ impl ColumnKnowledge {
                             | AggregateFunc::MinDate
                             | AggregateFunc::MinTimestamp
                             | AggregateFunc::MinTimestampTz
+                            | AggregateFunc::MinInterval
+                            | AggregateFunc::MinTime
+                            | AggregateFunc::MinBytes
+                            | AggregateFunc::MinUuid
+                            | AggregateFunc::MinArray
                             | AggregateFunc::Any
-                            | AggregateFunc::All => {
+                            | AggregateFunc::All
+                            | AggregateFunc::BoolAnd
+                            | AggregateFunc::BoolOr => {
                                 // These methods propagate constant values exactly.
                                 knowledge
                             }
-                            AggregateFunc::Count => DatumKnowledge::any(false),
+                            AggregateFunc::Count
+                            | AggregateFunc::CountNumeric
+                            | AggregateFunc::CountDistinct
+                            | AggregateFunc::RegrCount => DatumKnowledge::any(false),
+                            // These aggregates can be null even if none of
+                            // their inputs are, e.g. for a single row.
+                            AggregateFunc::StddevSamp
+                            | AggregateFunc::VarSamp
+                            | AggregateFunc::Corr
+                            | AggregateFunc::CovarPop
+                            | AggregateFunc::CovarSamp
+                            | AggregateFunc::RegrSlope
+                            | AggregateFunc::RegrIntercept
+                            | AggregateFunc::RegrR2 => DatumKnowledge::any(true),
                             _ => {
                                 // The remaining aggregates are non-null if
                                 // their inputs are non-null. This is correct
@@ -14,7 +14,6 @@
 //! can be simplified to a map operation.
 
 use crate::TransformArgs;
-use itertools::Itertools;
 use mz_expr::visit::Visit;
 use mz_expr::MirRelationExpr;
 
@@ -60,10 +59,14 @@ impl ReduceElision {
                 keys.iter()
                     .all(|k| group_key.contains(&mz_expr::MirScalarExpr::Column(*k)))
             }) {
-                let map_scalars = aggregates
+                let map_scalars = match aggregates
                     .iter()
                     .map(|a| a.on_unique(&input_type.column_types))
-                    .collect_vec();
+                    .collect::<Option<Vec<_>>>()
+                {
+                    Some(map_scalars) => map_scalars,
+                    None => return Ok(()),
+                };
 
                 let mut result = input.take_dangerous();
 